@@ -71,7 +71,7 @@ fn main() {
     ];
 
     for example in examples {
-        match NaturalDateParser::parse(example, now) {
+        match NaturalDateParser::parse(example, now, &tz) {
             Ok(timestamp) => {
                 // Convert back to system timezone for display
                 let zoned = timestamp.to_zoned(tz.clone());
@@ -0,0 +1,14 @@
+--- ==================================================================
+--  Graph statistics
+--- ==================================================================
+-- cached results of the link graph analysis. The table is rebuilt in its
+-- entirety whenever `zet graph stats --cache` runs.
+
+create table document_graph_stats (
+    document_id text    primary key,
+    in_degree   integer not null,
+    out_degree  integer not null,
+    pagerank    real    not null,
+    component   integer not null, -- index of the (weakly) connected component
+    foreign key (document_id) references document(id) on delete cascade
+) strict;
@@ -0,0 +1,30 @@
+--- ==================================================================
+--  Stubs
+--- ==================================================================
+-- placeholder documents for wikilink targets that do not resolve to any
+-- document. Stubs have no file, they only exist until a document with the
+-- same id is created. Only populated when `index.create_stubs` is enabled.
+
+create table document_stub (
+    id      text not null, -- slugified link target
+    title   text not null, -- the link target as written
+    from_id text not null, -- the document containing the link
+    primary key (id, from_id),
+    foreign key (from_id) references document(id) on delete cascade
+) strict;
+
+-- the links of a document are re-extracted when its hash changes
+create trigger clear_document_stubs_on_hash_update
+after update of hash on document
+for each row
+begin
+    delete from document_stub where from_id = NEW.id;
+end;
+
+-- a stub is materialized as soon as a document with the same id exists
+create trigger delete_materialized_stubs
+after insert on document
+for each row
+begin
+    delete from document_stub where id = NEW.id;
+end;
@@ -0,0 +1,9 @@
+--- ==================================================================
+--  Task status
+--- ==================================================================
+-- besides checked/unchecked, tasks can be in progress (`- [/]`) or
+-- cancelled (`- [-]`). `checked` is kept and is true only for done tasks.
+
+alter table document_task add column status text not null default 'todo';
+
+update document_task set status = 'done' where checked = 1;
@@ -0,0 +1,10 @@
+--- ==================================================================
+--  Task due date
+--- ==================================================================
+-- the due date of a task is resolved from the first date expression in its
+-- content ("call Bob next friday") when the document is indexed.
+-- due_start and due_end are the byte offsets of the expression in `content`.
+
+alter table document_task add column due text;
+alter table document_task add column due_start integer;
+alter table document_task add column due_end integer;
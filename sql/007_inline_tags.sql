@@ -0,0 +1,9 @@
+--- ==================================================================
+--  Inline tags
+--- ==================================================================
+-- tags can be written in the text of a document (`#book`) as well as in the
+-- frontmatter. range_start and range_end are the byte offsets of the tag
+-- name, without the `#`, in the document. Both are null for frontmatter tags.
+
+alter table document_tag_map add column range_start integer;
+alter table document_tag_map add column range_end integer;
@@ -0,0 +1,14 @@
+--- ==================================================================
+--  Nested tags
+--- ==================================================================
+-- nested tags (`project/zet/parser`) form a hierarchy through the `/` in
+-- their names. parent is the name of the parent tag (`project/zet`), null for
+-- top level tags. The parent need not be a tag of any document itself.
+
+alter table tag add column parent text;
+
+-- everything up to the last `/`, the characters after it are all in the set
+-- of characters stripped by the inner rtrim
+update tag set parent = nullif(rtrim(rtrim(tag, replace(tag, '/', '')), '/'), '');
+
+create index tag_parent on tag(parent);
@@ -0,0 +1,11 @@
+--- ==================================================================
+--  Link context
+--- ==================================================================
+-- block_start and block_end are the byte offsets of the paragraph or list
+-- item containing a link, in the same coordinates as range_start and
+-- range_end: the document content after the frontmatter. They are used to
+-- show the context of backlinks, and are null if the link is in no such
+-- block.
+
+alter table document_link add column block_start integer;
+alter table document_link add column block_end integer;
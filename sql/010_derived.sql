@@ -0,0 +1,24 @@
+--- ==================================================================
+--  Derived rows
+--- ==================================================================
+-- rows derived from a document by an index hook that has no table of its
+-- own. hook is the name of the hook, data its json encoded row
+-- <https://sqlite.org/json1.html#jsonb>.
+
+create table document_derived (
+    id          integer primary key,
+    document_id text    not null,
+    hook        text    not null,
+    data        blob    not null,
+    foreign key (document_id) references document(id) on delete cascade
+) strict;
+
+create index document_derived_hook on document_derived(hook);
+
+-- Clear the derived rows of a document when its hash changes
+create trigger clear_document_derived_on_hash_update
+after update of hash on document
+for each row
+begin
+    delete from document_derived where document_id = NEW.id;
+end;
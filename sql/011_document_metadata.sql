@@ -0,0 +1,24 @@
+--- ==================================================================
+--  Plugin metadata
+--- ==================================================================
+-- the output of a plugin for a document: the metadata it derived and its
+-- diagnostics, both jsonb encoded json <https://sqlite.org/json1.html#jsonb>.
+-- The ranges of the diagnostics are byte offsets in the document content
+-- after the frontmatter.
+
+create table document_metadata (
+    id          integer primary key,
+    document_id text    not null,
+    plugin      text    not null,
+    metadata    blob,
+    diagnostics blob    not null,
+    foreign key (document_id) references document(id) on delete cascade
+) strict;
+
+-- Clear the plugin output of a document when its hash changes
+create trigger clear_document_metadata_on_hash_update
+after update of hash on document
+for each row
+begin
+    delete from document_metadata where document_id = NEW.id;
+end;
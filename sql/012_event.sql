@@ -0,0 +1,18 @@
+--- ==================================================================
+--  Events
+--- ==================================================================
+-- an append-only log of the changes made to the index: documents added,
+-- updated and removed, and links added and removed. Rows are never updated
+-- or deleted, and refer to documents that may no longer exist. data is
+-- jsonb encoded json <https://sqlite.org/json1.html#jsonb> with the details
+-- of the event, such as the path of a document or the target of a link.
+
+create table event (
+    id          integer primary key,
+    timestamp   text    not null,
+    kind        text    not null,
+    document_id text    not null,
+    data        blob
+) strict;
+
+create index event_timestamp on event(timestamp);
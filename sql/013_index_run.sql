@@ -0,0 +1,13 @@
+--- ==================================================================
+--  Index runs
+--- ==================================================================
+-- one row per completed index run, including runs that found nothing to
+-- change. Used to report when the collection was last indexed and how long
+-- it took.
+
+create table index_run (
+    id          integer primary key,
+    started     text    not null,
+    finished    text    not null,
+    changes     integer not null
+) strict;
@@ -0,0 +1,36 @@
+--- ==================================================================
+--  Lookup
+--- ==================================================================
+-- the names a document can be found by: its title, the aliases in its
+-- frontmatter and its headings. kind is one of 'title', 'alias' and
+-- 'heading'. Used by fuzzy search, such as `zet find`.
+
+create table document_lookup (
+    id          integer primary key,
+    document_id text    not null,
+    kind        text    not null,
+    text        text    not null,
+    foreign key (document_id) references document(id) on delete cascade
+) strict;
+
+create index document_lookup_document on document_lookup(document_id);
+
+-- Clear the lookup rows of a document when its hash changes
+create trigger clear_document_lookup_on_hash_update
+after update of hash on document
+for each row
+begin
+    delete from document_lookup where document_id = NEW.id;
+end;
+
+-- documents indexed before this table existed
+insert into document_lookup (document_id, kind, text)
+select id, 'title', title from document where title != '';
+
+insert into document_lookup (document_id, kind, text)
+select document.id, 'alias', alias.value
+from document, json_each(document.frontmatter, '$.aliases') as alias
+where alias.type = 'text';
+
+insert into document_lookup (document_id, kind, text)
+select document_id, 'heading', content from document_heading;
@@ -0,0 +1,30 @@
+--- ==================================================================
+--  Query snapshots
+--- ==================================================================
+-- the generation of the index, incremented by every index run that changed
+-- the collection. A result computed from the index stays valid as long as
+-- the generation it was computed at is the current one.
+
+create table index_generation (
+    id          integer primary key check (id = 0),
+    generation  integer not null
+) strict;
+
+insert into index_generation (id, generation) values (0, 0);
+
+create trigger bump_index_generation
+after insert on index_run
+for each row when NEW.changes > 0
+begin
+    update index_generation set generation = generation + 1 where id = 0;
+end;
+
+-- the results of expensive aggregate queries, such as `zet graph stats`,
+-- keyed by the query. A snapshot computed at an older generation is stale.
+
+create table query_snapshot (
+    key         text    primary key,
+    generation  integer not null,
+    created     text    not null,
+    data        blob    not null
+) strict;
@@ -0,0 +1,26 @@
+--- ==================================================================
+--  Highlights
+--- ==================================================================
+-- the `==highlighted==` text of a document. range_start and range_end are
+-- the byte offsets of the highlight, delimiters included, in the document
+-- content after the frontmatter. Documents indexed before this table
+-- existed get their highlights once they change.
+
+create table document_highlight (
+    id          integer primary key,
+    document_id text    not null,
+    content     text    not null,
+    range_start integer not null,
+    range_end   integer not null,
+    foreign key (document_id) references document(id) on delete cascade
+) strict;
+
+create index document_highlight_document on document_highlight(document_id);
+
+-- Clear the highlights of a document when its hash changes
+create trigger clear_document_highlight_on_hash_update
+after update of hash on document
+for each row
+begin
+    delete from document_highlight where document_id = NEW.id;
+end;
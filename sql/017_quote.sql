@@ -0,0 +1,29 @@
+--- ==================================================================
+--  Quotes
+--- ==================================================================
+-- the block quotes of a document, block quotes nested in another one being
+-- part of it. attribution is the text of a last line starting with a dash,
+-- `— author`, left out of content. range_start and range_end are the byte
+-- offsets of the block quote in the document content after the frontmatter.
+-- Documents indexed before this table existed get their quotes once they
+-- change.
+
+create table document_quote (
+    id          integer primary key,
+    document_id text    not null,
+    content     text    not null,
+    attribution text,
+    range_start integer not null,
+    range_end   integer not null,
+    foreign key (document_id) references document(id) on delete cascade
+) strict;
+
+create index document_quote_document on document_quote(document_id);
+
+-- Clear the quotes of a document when its hash changes
+create trigger clear_document_quote_on_hash_update
+after update of hash on document
+for each row
+begin
+    delete from document_quote where document_id = NEW.id;
+end;
@@ -0,0 +1,29 @@
+--- ==================================================================
+--  Mentions
+--- ==================================================================
+-- the `@name` mentions of a document. The name is resolved to a person when
+-- the mentions are queried, so that mentions follow the notes of people as
+-- they are added and renamed. range_start and range_end are the byte offsets
+-- of the mention, `@` included, in the document content after the
+-- frontmatter. Documents indexed before this table existed get their
+-- mentions once they change.
+
+create table document_mention (
+    id          integer primary key,
+    document_id text    not null,
+    name        text    not null,
+    range_start integer not null,
+    range_end   integer not null,
+    foreign key (document_id) references document(id) on delete cascade
+) strict;
+
+create index document_mention_document on document_mention(document_id);
+create index document_mention_name on document_mention(name collate nocase);
+
+-- Clear the mentions of a document when its hash changes
+create trigger clear_document_mention_on_hash_update
+after update of hash on document
+for each row
+begin
+    delete from document_mention where document_id = NEW.id;
+end;
@@ -0,0 +1,13 @@
+--- ==================================================================
+--  Task owner
+--- ==================================================================
+-- the owner of a task is the name of its first `@name` mention. Action
+-- items are the tasks with an owner and the tasks under an `Action items`
+-- heading, as in meeting notes. Like mentions, owners are resolved to
+-- people when they are queried. Documents indexed before these columns
+-- existed get their owners once they change.
+
+alter table document_task add column owner text;
+alter table document_task add column action_item integer not null default 0;
+
+create index document_task_owner on document_task(owner collate nocase);
@@ -0,0 +1,11 @@
+--- ==================================================================
+--  Heading anchor
+--- ==================================================================
+-- the anchor links to a heading use, `#anchor`: the id of its `{#id}`
+-- attribute, or the slug of its text. Its classes and other attributes are
+-- in metadata. Documents indexed before this column existed are reindexed,
+-- see 023.
+
+alter table document_heading add column anchor text not null default '';
+
+create index document_heading_anchor on document_heading(document_id, anchor);
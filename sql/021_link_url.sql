@@ -0,0 +1,12 @@
+--- ==================================================================
+--  Link url
+--- ==================================================================
+-- url is the normalized url of a link to an external page or a DOI, see
+-- zet::core::url::normalize_url, and null for links to documents. Links to
+-- the same page written differently, with tracking parameters or a trailing
+-- slash, have the same url. Documents indexed before this column existed get
+-- their urls once they change.
+
+alter table document_link add column url text;
+
+create index document_link_url on document_link(url);
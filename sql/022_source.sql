@@ -0,0 +1,16 @@
+--- ==================================================================
+--  Sources
+--- ==================================================================
+-- the external pages and DOIs ever linked to or saved as the frontmatter url
+-- of a note, one row per normalized url, see zet::core::url::normalize_url.
+-- Unlike document_link, rows are kept when the links are removed, so that
+-- a source once saved is still known. original is the url as it was first
+-- written, first_seen and last_seen the dates of the first created and
+-- last modified documents referencing it.
+
+create table source (
+    url         text primary key,
+    original    text not null,
+    first_seen  text not null,
+    last_seen   text not null
+) strict;
@@ -0,0 +1,13 @@
+--- ==================================================================
+--  Heading anchor reindex
+--- ==================================================================
+-- the headings indexed before 020 have no anchor. The documents they are in
+-- no longer match their files, by their modified timestamp and hash, and are
+-- reindexed by the next index.
+
+update document
+set
+    modified = '1970-01-01T00:00:00Z',
+    hash = (hash + 1) % 4294967296
+where
+    id in (select document_id from document_heading where anchor = '');
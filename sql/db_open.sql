@@ -1,4 +1,5 @@
 PRAGMA journal_mode = wal; -- different implementation of the atomicity properties
 PRAGMA synchronous = normal; -- synchronise less often to the filesystem
 PRAGMA foreign_keys = on; -- check foreign key reference, slightly worst performance
+PRAGMA busy_timeout = 5000; -- wait for the writing process instead of failing with SQLITE_BUSY
 
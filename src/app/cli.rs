@@ -10,13 +10,19 @@ pub mod argument_parser {
     #[derive(Parser)]
     #[command(version, about, long_about, verbatim_doc_comment)]
     pub struct ArgumentParser {
-        /// Tell zet to look for a .zet directory in `root`.
-        /// If no such directory could be found, zet will look in user data
+        /// Tell zet to look for a .zet directory in `root`, defaults to
+        /// $ZET_ROOT. Without either, zet looks in the current directory and
+        /// its parents, up to $HOME or the filesystem boundary
         #[arg(long)]
         pub root: Option<PathBuf>,
         #[arg(long)]
         /// Set the logging leven of the application
         pub level: Option<crate::app::LogLevel>,
+        #[arg(long)]
+        /// Never write to the collection: queries read a snapshot of the index
+        /// and commands that would modify the collection fail. Implied when
+        /// the index is not writable
+        pub read_only: bool,
         #[command(subcommand)]
         pub command: crate::app::commands::Command,
     }
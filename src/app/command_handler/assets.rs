@@ -0,0 +1,46 @@
+use std::path::Path;
+
+use zet::config::Config;
+use zet::core::assets::{oversized_images, unreferenced_assets};
+use zet::preamble::*;
+
+use crate::app::commands::AssetsCommand;
+
+pub fn handle_command(root: &Path, config: &Config, command: AssetsCommand) -> Result<()> {
+    match command {
+        AssetsCommand::Gc { dry_run } => {
+            for path in unreferenced_assets(root, &config.assets)? {
+                println!("{}", relative(root, &path).display());
+                if !dry_run {
+                    std::fs::remove_file(&path)?;
+                }
+            }
+        }
+        AssetsCommand::Optimize { dry_run } => {
+            for image in oversized_images(root, &config.assets)? {
+                let name = relative(root, &image.path).display();
+                if dry_run {
+                    println!("{} ({} bytes)", name, image.size);
+                    continue;
+                }
+                match image.optimize(&config.assets)? {
+                    Some(size) => println!(
+                        "{} -> {} ({} -> {} bytes)",
+                        name,
+                        relative(root, &image.to).display(),
+                        image.size,
+                        size
+                    ),
+                    None => log::info!("kept {}, the optimized image is not smaller", name),
+                }
+            }
+            // documents whose references were rewritten are picked up by the
+            // next index
+        }
+    }
+    Ok(())
+}
+
+fn relative<'a>(root: &Path, path: &'a Path) -> &'a Path {
+    path.strip_prefix(root).unwrap_or(path)
+}
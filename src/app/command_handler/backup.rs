@@ -0,0 +1,30 @@
+use std::path::{Path, PathBuf};
+
+use zet::config::Config;
+use zet::core::backup::backup;
+use zet::preamble::*;
+
+pub fn handle_command(
+    root: &Path,
+    config: &Config,
+    out: Option<PathBuf>,
+    include_db: bool,
+) -> Result<()> {
+    let out = match out {
+        Some(out) => out,
+        None => {
+            let date = jiff::Timestamp::now()
+                .to_zoned(config.timezone()?)
+                .strftime("%Y-%m-%d")
+                .to_string();
+            PathBuf::from(format!("zet-backup-{date}.tar.gz"))
+        }
+    };
+    let manifest = backup(root, &config.assets, &out, include_db)?;
+    println!(
+        "{} files written to {}",
+        manifest.files.len(),
+        out.display()
+    );
+    Ok(())
+}
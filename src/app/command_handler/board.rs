@@ -0,0 +1,35 @@
+use std::io::Write;
+use std::path::Path;
+
+use zet::core::board::{Board, BoardGroupBy};
+use zet::core::db::DB;
+use zet::preamble::*;
+
+use crate::app::commands::ReportFormat;
+
+pub fn handle_command(
+    root: &Path,
+    group_by: BoardGroupBy,
+    width: usize,
+    output_format: ReportFormat,
+    pretty: bool,
+) -> Result<()> {
+    let db_path = zet::core::collection_db_file(root);
+    let db = DB::open(db_path)?;
+
+    let board = Board::load(&db, group_by)?;
+
+    let mut writer = std::io::BufWriter::new(std::io::stdout());
+    match output_format {
+        ReportFormat::Json => {
+            if pretty {
+                serde_json::to_writer_pretty(&mut writer, &board)?;
+            } else {
+                serde_json::to_writer(&mut writer, &board)?;
+            }
+        }
+        ReportFormat::Text => write!(writer, "{}", board.render_text(width))?,
+    }
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use color_eyre::eyre::eyre;
+use zet::config::Config;
+use zet::core::LinkTargets;
+use zet::core::compile::{Chapter, chapter_links, compile};
+use zet::core::db::{DB, DbGet, DbList};
+#[cfg(feature = "document-export")]
+use zet::core::document_export::{DocumentFormat, ExportedNote, convert, render_document};
+#[cfg(feature = "document-export")]
+use zet::core::html::Highlighter;
+use zet::core::parser::FrontMatterParser;
+use zet::core::parser::comments::strip_comments;
+use zet::core::transclusion::Transclusion;
+use zet::core::types::document::{Document, DocumentId, DocumentSummary};
+use zet::preamble::*;
+
+/// Compile the book of the index note `id` to `out`
+pub fn handle_command(
+    root: &Path,
+    config: &Config,
+    id: &str,
+    out: &Path,
+    title: Option<String>,
+) -> Result<()> {
+    let mut db = DB::open(zet::core::collection_db_file(root))?;
+    let index = Document::get(&mut db, &DocumentId(id.to_owned()))
+        .map_err(|_| eyre!("no document with id '{}'", id))?;
+    let targets = LinkTargets::load(&db)?;
+    let titles: HashMap<DocumentId, String> = DocumentSummary::list(&db)?
+        .into_iter()
+        .map(|d| (d.id, d.title))
+        .collect();
+    drop(db);
+
+    let read_body = |path: &Path| -> Result<String> {
+        let content = std::fs::read_to_string(path)?;
+        let (_, body) = FrontMatterParser::new(config.front_matter_format).parse(content);
+        Ok(body)
+    };
+
+    let transclusion = Transclusion::new(&targets, config.front_matter_format);
+    let mut chapters: Vec<Chapter> = Vec::new();
+    for (target, wiki) in chapter_links(&read_body(&index.path.0)?) {
+        let note = target.split('#').next().unwrap_or_default();
+        let Some(chapter) = targets.resolve(&index.id, note, wiki) else {
+            log::warn!("skipping the link to '{}', there is no such note", target);
+            continue;
+        };
+        if *chapter == index.id || chapters.iter().any(|c| c.id == *chapter) {
+            continue;
+        }
+        let Some(path) = targets.path(chapter) else {
+            continue;
+        };
+        let body = transclusion.expand(chapter, &read_body(path)?)?;
+        chapters.push(Chapter {
+            id: chapter.clone(),
+            title: titles
+                .get(chapter)
+                .cloned()
+                .unwrap_or_else(|| chapter.0.clone()),
+            body: strip_comments(&body, &config.parser.comments),
+        });
+    }
+    if chapters.is_empty() {
+        return Err(eyre!("the note '{}' lists no notes", id));
+    }
+
+    let title = title.unwrap_or_else(|| index.title.clone());
+    let book = compile(&title, &chapters, &targets);
+    let extension = out
+        .extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("md" | "markdown") => std::fs::write(out, book)?,
+        #[cfg(feature = "document-export")]
+        Some(extension @ ("pdf" | "epub")) => {
+            let format = match extension {
+                "pdf" => DocumentFormat::Pdf,
+                _ => DocumentFormat::Epub,
+            };
+            let note = ExportedNote {
+                id: &index.id,
+                path: &index.path.0,
+                body: book,
+            };
+            let highlighter = Highlighter::new(&config.html)?;
+            let html = render_document(&config.html, highlighter.as_ref(), &title, &[note]);
+            convert(&config.export, format, &title, &html, out)?
+        }
+        #[cfg(not(feature = "document-export"))]
+        Some("pdf" | "epub") => {
+            return Err(eyre!(
+                "zet was built without the document-export feature, compile to markdown instead"
+            ));
+        }
+        _ => return Err(eyre!("{:?} is not a .md, .pdf or .epub file", out)),
+    }
+    Ok(())
+}
@@ -4,21 +4,33 @@ use std::path::PathBuf;
 
 use color_eyre::eyre::eyre;
 
-use zet::core::template_engine::{render_template, resolve_group_from_cwd, resolve_template_string};
+use zet::config::DuplicatePolicy;
+use zet::core::db::{DB, DbDelete, DbGet};
+use zet::core::hooks::HookEvent;
+use zet::core::template_engine::{
+    render_collection_template, resolve_group_from_cwd, resolve_template_string,
+};
+use zet::core::types::document::DocumentId;
+use zet::core::types::stub::DocumentStub;
 use zet::preamble::*;
 
 #[allow(clippy::too_many_arguments)]
 pub fn handle_command(
     root: Option<PathBuf>,
-    title: String,
+    title: Option<String>,
     content: Option<String>,
     group: Option<String>,
     template: Option<String>,
     stdin: bool,
+    from: Option<String>,
     data_json: Option<String>,
     data_toml: Option<String>,
     data_json_path: Option<PathBuf>,
     data_toml_path: Option<PathBuf>,
+    from_stub: Option<String>,
+    no_index: bool,
+    open: bool,
+    duplicate: Option<DuplicatePolicy>,
 ) -> Result<()> {
     // Validate stdin and content are mutually exclusive
     if stdin && content.is_some() {
@@ -33,11 +45,29 @@ pub fn handle_command(
     // Load config
     let config = zet::config::Config::resolve(&collection_root)?;
 
-    // Read content from stdin or positional arg
+    // Take the title from the stub when materializing one
+    let stub = match from_stub {
+        Some(id) => {
+            let mut db = DB::open(zet::core::collection_db_file(&collection_root))?;
+            let stub = DocumentStub::get(&mut db, &DocumentId(id.clone()))
+                .map_err(|_| eyre!("no stub with id '{}'", id))?;
+            Some((db, stub))
+        }
+        None => None,
+    };
+    let title = match (&stub, title) {
+        (Some((_, stub)), _) => stub.title.clone(),
+        (None, Some(title)) => title,
+        (None, None) => return Err(eyre!("a title is required")),
+    };
+
+    // Read content from stdin, a file or url, or positional arg
     let body = if stdin {
         let mut buf = String::new();
         std::io::stdin().read_to_string(&mut buf)?;
         buf
+    } else if let Some(source) = from {
+        config.fetch.content(&source)?
     } else {
         content.unwrap_or_default()
     };
@@ -62,11 +92,6 @@ pub fn handle_command(
         resolved_group.map(|(_, gc)| gc),
     )?;
 
-    // Compute slug, filename, and id
-    let slug = zet::core::slug::slugify(&title);
-    let filename = format!("{}.md", slug);
-    let id = slug.clone();
-
     // Determine output directory
     let output_dir = if let Some((_, gc)) = resolved_group {
         // Use explicit --group: use group's first directory relative to collection root
@@ -84,11 +109,47 @@ pub fn handle_command(
     // But if --group was explicitly provided, use that group's dir; otherwise use CWD
     // (already handled above)
 
-    let output_path = output_dir.join(&filename);
+    // Compute id and filename by the id scheme of the group
+    let now = jiff::Timestamp::now().to_zoned(config.timezone()?);
+    let id_scheme = resolved_group
+        .map(|(_, gc)| gc.id_scheme)
+        .unwrap_or_default();
+    let (DocumentId(mut id), filename) =
+        id_scheme.new_note(&collection_root, &output_dir, &title, &now)?;
+    let mut output_path = output_dir.join(filename);
 
-    // Error if file already exists
+    // An existing note is handled by the policy given on the command line,
+    // or else by the one of the group
     if output_path.exists() {
-        return Err(eyre!("file already exists: {:?}", output_path));
+        let policy = duplicate
+            .or_else(|| resolved_group.and_then(|(_, gc)| gc.on_duplicate))
+            .unwrap_or_default();
+        match policy {
+            DuplicatePolicy::Fail => {
+                return Err(eyre!("file already exists: {:?}", output_path));
+            }
+            DuplicatePolicy::Suffix => {
+                let base = id.clone();
+                let mut n = 2;
+                while output_path.exists() {
+                    id = format!("{}-{}", base, n);
+                    output_path = output_dir.join(id_scheme.filename(&id, &title));
+                    n += 1;
+                }
+            }
+            DuplicatePolicy::OpenExisting => {
+                let abs_path = std::path::absolute(&output_path)?;
+                println!("{}", abs_path.display());
+                if open || config.editor.open_on_create {
+                    let existing = std::fs::read_to_string(&abs_path)?;
+                    config
+                        .editor
+                        .open(&abs_path, zet::core::editor::body_line(&existing))?;
+                }
+                return Ok(());
+            }
+            DuplicatePolicy::Force => {}
+        }
     }
 
     // Merge extra data from --data-* flags
@@ -124,19 +185,76 @@ pub fn handle_command(
         merge_json_object(&mut extra, json_val)?;
     }
 
+    // Capture metadata, also available to the template under the same key
+    let capture = config
+        .capture
+        .enabled
+        .then(|| zet::core::capture::capture_metadata(&config.capture.fields, &cwd));
+    if let Some(capture) = &capture {
+        extra
+            .entry(config.capture.key.clone())
+            .or_insert_with(|| capture.clone());
+    }
+
     // Build date string (today as %Y-%m-%d)
-    let date = jiff::Zoned::now().strftime("%Y-%m-%d").to_string();
+    let date = now.strftime("%Y-%m-%d").to_string();
 
     // Render template
-    let rendered = render_template(&template_str, &id, &title, &date, &body, &extra)?;
+    let mut rendered = render_collection_template(
+        &collection_root,
+        &template_str,
+        &id,
+        &title,
+        &date,
+        &body,
+        &extra,
+    )?;
+
+    // Templates that do not place the capture metadata themselves get it
+    // appended to their frontmatter
+    if let Some(capture) = &capture {
+        rendered = zet::core::frontmatter::insert_frontmatter_value(
+            &rendered,
+            config.front_matter_format,
+            &config.capture.key,
+            capture,
+        )?;
+    }
 
     // Write to file
-    std::fs::write(&output_path, rendered)?;
+    std::fs::write(&output_path, &rendered)?;
+
+    // The stub has a file now, the index picks it up as a document
+    if let Some((mut db, stub)) = stub {
+        DocumentStub::delete(&mut db, &[stub.id])?;
+    }
 
     // Print absolute file path to stdout
     let abs_path = std::path::absolute(&output_path)?;
     println!("{}", abs_path.display());
 
+    // Index the note right away, so that search, completion and backlinks
+    // see it without waiting for the next `zet index`, which indexes the
+    // other changes of the collection
+    if !no_index {
+        super::index::index_paths(&collection_root, &config, std::slice::from_ref(&abs_path))?;
+    }
+
+    config.hooks.run(
+        &collection_root,
+        &HookEvent::Create {
+            id: DocumentId(id),
+            title,
+            path: abs_path.clone(),
+        },
+    );
+
+    if open || config.editor.open_on_create {
+        config
+            .editor
+            .open(&abs_path, zet::core::editor::body_line(&rendered))?;
+    }
+
     Ok(())
 }
 
@@ -0,0 +1,98 @@
+use std::io::Write;
+
+use jiff::tz::TimeZone;
+use jiff::{Timestamp, Zoned};
+use serde::Serialize;
+use zet::config::Config;
+use zet::core::date_parser::NaturalDateParser;
+use zet::preamble::*;
+
+use crate::app::commands::ReportFormat;
+
+/// A resolved moment, in the formats most useful for scripting
+#[derive(Debug, Serialize)]
+struct ResolvedDate {
+    /// RFC 3339 timestamp in UTC
+    timestamp: String,
+    /// RFC 9557 datetime in the configured timezone
+    local: String,
+    date: String,
+    time: String,
+    unix: i64,
+}
+
+impl ResolvedDate {
+    fn new(timestamp: Timestamp, tz: &TimeZone) -> Self {
+        let zoned: Zoned = timestamp.to_zoned(tz.clone());
+        Self {
+            timestamp: timestamp.to_string(),
+            local: zoned.to_string(),
+            date: zoned.date().to_string(),
+            time: zoned.time().strftime("%H:%M:%S").to_string(),
+            unix: timestamp.as_second(),
+        }
+    }
+
+    fn write_text(&self, writer: &mut impl Write, prefix: &str) -> Result<()> {
+        writeln!(writer, "{prefix}timestamp: {}", self.timestamp)?;
+        writeln!(writer, "{prefix}local: {}", self.local)?;
+        writeln!(writer, "{prefix}date: {}", self.date)?;
+        writeln!(writer, "{prefix}time: {}", self.time)?;
+        writeln!(writer, "{prefix}unix: {}", self.unix)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ResolvedRange {
+    start: ResolvedDate,
+    /// the exclusive end of the range
+    end: ResolvedDate,
+}
+
+pub fn handle_command(
+    config: &Config,
+    expression: &str,
+    range: bool,
+    output_format: ReportFormat,
+    pretty: bool,
+) -> Result<()> {
+    let tz = config.timezone()?;
+    let now = Timestamp::now();
+
+    let mut writer = std::io::BufWriter::new(std::io::stdout());
+    if range {
+        let range =
+            NaturalDateParser::parse_range_with_config(expression, now, &tz, &config.dates)?;
+        let resolved = ResolvedRange {
+            start: ResolvedDate::new(range.start, &tz),
+            end: ResolvedDate::new(range.end, &tz),
+        };
+        match output_format {
+            ReportFormat::Json => write_json(&mut writer, &resolved, pretty)?,
+            ReportFormat::Text => {
+                resolved.start.write_text(&mut writer, "start ")?;
+                resolved.end.write_text(&mut writer, "end ")?;
+            }
+        }
+    } else {
+        let timestamp = NaturalDateParser::parse_with_config(expression, now, &tz, &config.dates)?;
+        let resolved = ResolvedDate::new(timestamp, &tz);
+        match output_format {
+            ReportFormat::Json => write_json(&mut writer, &resolved, pretty)?,
+            ReportFormat::Text => resolved.write_text(&mut writer, "")?,
+        }
+    }
+
+    Ok(())
+}
+
+fn write_json(writer: &mut impl Write, value: &impl Serialize, pretty: bool) -> Result<()> {
+    if pretty {
+        serde_json::to_writer_pretty(&mut *writer, value)?;
+    } else {
+        serde_json::to_writer(&mut *writer, value)?;
+    }
+    writeln!(writer)?;
+    Ok(())
+}
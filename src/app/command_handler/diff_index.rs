@@ -0,0 +1,129 @@
+use std::io::Write;
+use std::path::Path;
+
+use sql_minifier::macros::minify_sql as sql;
+use zet::config::Config;
+use zet::core::LinkTargets;
+use zet::core::db::{DB, DbGet};
+use zet::core::types::document::{Document, DocumentId};
+use zet::core::types::task::TaskStatus;
+use zet::preamble::*;
+
+use super::index::{IndexPlan, difference, link_targets, plan};
+
+/// The changes an index would make to a document
+struct DocumentDiff {
+    id: DocumentId,
+    /// `+` for a new document, `-` for a removed one and `~` for an updated one
+    marker: char,
+    title: String,
+    changes: Vec<String>,
+}
+
+pub fn handle_command(root: &Path, config: Config) -> Result<()> {
+    let mut db = DB::open(zet::core::collection_db_file(root))?;
+    let IndexPlan { journal, rows } = plan(root, &config, &db)?;
+
+    // the documents once indexed, which links are resolved against
+    let mut targets = LinkTargets::load(&db)?;
+    for id in &journal.removed {
+        targets.remove(id);
+    }
+    for document in &rows.documents {
+        targets.insert(document.id.clone(), document.path.0.clone());
+    }
+
+    let mut diffs = Vec::new();
+    for id in &journal.removed {
+        let document = Document::get(&mut db, id)?;
+        diffs.push(DocumentDiff {
+            id: document.id,
+            marker: '-',
+            title: document.title,
+            changes: Vec::new(),
+        });
+    }
+
+    for document in &rows.documents {
+        let previous = match journal.updated.contains(&document.id) {
+            true => Some(Document::get(&mut db, &document.id)?),
+            false => None,
+        };
+        let mut changes = Vec::new();
+        if let Some(previous) = &previous
+            && previous.title != document.title
+        {
+            changes.push(format!("title: {} -> {}", previous.title, document.title));
+        }
+
+        let before = link_targets(&db, std::slice::from_ref(&document.id))?
+            .remove(&document.id)
+            .unwrap_or_default();
+        let mut after: Vec<DocumentId> = rows
+            .links
+            .iter()
+            .filter(|link| DocumentId::from(link.from.clone()) == document.id)
+            .filter_map(|link| targets.resolve(&document.id, &link.to, link.wiki).cloned())
+            .collect();
+        after.sort();
+        for to in difference(&before, &after) {
+            changes.push(format!("- link {}", to.0));
+        }
+        for to in difference(&after, &before) {
+            changes.push(format!("+ link {}", to.0));
+        }
+
+        let before = previous_tasks(&db, &document.id)?;
+        let mut after: Vec<(TaskStatus, String)> = rows
+            .tasks
+            .iter()
+            .filter(|task| task.document_id == document.id)
+            .map(|task| (task.status, task.content.clone()))
+            .collect();
+        after.sort();
+        for (status, content) in difference(&before, &after) {
+            changes.push(format!("- task {} {}", status.marker(), content));
+        }
+        for (status, content) in difference(&after, &before) {
+            changes.push(format!("+ task {} {}", status.marker(), content));
+        }
+
+        // a document whose content changed without changing its title, links
+        // or tasks is left out
+        let marker = match previous {
+            Some(_) if changes.is_empty() => continue,
+            Some(_) => '~',
+            None => '+',
+        };
+        diffs.push(DocumentDiff {
+            id: document.id.clone(),
+            marker,
+            title: document.title.clone(),
+            changes,
+        });
+    }
+    diffs.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let mut writer = std::io::BufWriter::new(std::io::stdout());
+    for diff in &diffs {
+        writeln!(writer, "{} {}  {}", diff.marker, diff.id.0, diff.title)?;
+        for change in &diff.changes {
+            writeln!(writer, "    {}", change)?;
+        }
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// The status and content of the tasks of a document, as currently indexed
+fn previous_tasks(db: &rusqlite::Connection, id: &DocumentId) -> Result<Vec<(TaskStatus, String)>> {
+    let mut tasks = db
+        .prepare(sql!(
+            "select status, content from document_task where document_id = ?1"
+        ))?
+        .query_map([id], |r| Ok((r.get(0)?, r.get(1)?)))?
+        .collect::<rusqlite::Result<Vec<(TaskStatus, String)>>>()?;
+    tasks.sort();
+    Ok(tasks)
+}
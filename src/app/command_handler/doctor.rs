@@ -0,0 +1,60 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use zet::config::Config;
+use zet::core::read::{FileIssue, file_issues};
+use zet::core::workspace_paths;
+use zet::preamble::*;
+
+use crate::app::commands::ReportFormat;
+
+#[derive(Debug, Serialize)]
+struct FileReport {
+    path: PathBuf,
+    size: u64,
+    issues: Vec<FileIssue>,
+}
+
+/// Print the document files that are not indexed as they are, too large or
+/// not valid UTF-8
+pub fn handle_command(
+    root: &Path,
+    config: &Config,
+    output_format: ReportFormat,
+    pretty: bool,
+) -> Result<()> {
+    let mut paths = workspace_paths(root)?;
+    paths.sort();
+    let reports: Vec<FileReport> = file_issues(&paths, &config.index)?
+        .into_iter()
+        .map(|(path, file)| FileReport {
+            path,
+            size: file.size,
+            issues: file.issues,
+        })
+        .collect();
+
+    let mut writer = std::io::BufWriter::new(std::io::stdout());
+    match output_format {
+        ReportFormat::Json if pretty => serde_json::to_writer_pretty(&mut writer, &reports)?,
+        ReportFormat::Json => serde_json::to_writer(&mut writer, &reports)?,
+        ReportFormat::Text => {
+            for report in &reports {
+                let path = report.path.strip_prefix(root).unwrap_or(&report.path);
+                for issue in &report.issues {
+                    writeln!(
+                        writer,
+                        "{:<13}{}\t{} bytes",
+                        issue.as_str(),
+                        path.display(),
+                        report.size
+                    )?;
+                }
+            }
+        }
+    }
+    writer.flush()?;
+
+    Ok(())
+}
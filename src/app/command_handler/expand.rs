@@ -0,0 +1,31 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use color_eyre::eyre::eyre;
+use jiff::Timestamp;
+use zet::config::Config;
+use zet::core::snippets::load_snippets;
+use zet::preamble::*;
+
+pub fn handle_command(
+    root: &Path,
+    config: &Config,
+    trigger: &str,
+    variables: Vec<(String, String)>,
+    id: &str,
+    title: &str,
+) -> Result<()> {
+    let snippets = load_snippets(root)?;
+    let Some(snippet) = snippets.iter().find(|s| s.trigger == trigger) else {
+        return Err(eyre!("no snippet with the trigger {:?}", trigger));
+    };
+
+    let now = Timestamp::now().to_zoned(config.timezone()?);
+    let variables: HashMap<String, serde_json::Value> = variables
+        .into_iter()
+        .map(|(key, value)| (key, value.into()))
+        .collect();
+    print!("{}", snippet.expand(id, title, &now, &variables)?);
+
+    Ok(())
+}
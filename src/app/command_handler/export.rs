@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use color_eyre::eyre::eyre;
+use zet::config::Config;
+use zet::core::LinkTargets;
+use zet::core::backlinks::linked_mentions;
+use zet::core::db::{DB, DbGet, DbList, Page};
+#[cfg(feature = "document-export")]
+use zet::core::document_export::{DocumentFormat, ExportedNote, convert, render_document};
+use zet::core::html::{Destination, Highlighter, render_page};
+use zet::core::ics::Calendar;
+use zet::core::opml::Opml;
+use zet::core::parser::FrontMatterParser;
+use zet::core::parser::comments::strip_comments;
+#[cfg(feature = "document-export")]
+use zet::core::query::{DocumentQuery, SortByOption, SortOrder};
+use zet::core::redact::{is_private, overlaps_private, redact};
+use zet::core::sequence::neighbours;
+use zet::core::transclusion::Transclusion;
+use zet::core::types::document::{Document, DocumentId, DocumentSummary};
+use zet::preamble::*;
+
+use crate::app::commands::ExportCommand;
+#[cfg(feature = "document-export")]
+use crate::app::commands::ExportSelection;
+
+pub fn handle_command(root: &Path, config: Config, command: ExportCommand) -> Result<()> {
+    match command {
+        ExportCommand::Ics { output } => {
+            let db = DB::open(zet::core::collection_db_file(root))?;
+            let calendar = Calendar::load(&db, &config.timezone()?)?;
+            let ics = calendar.to_ics();
+            match output {
+                Some(path) => std::fs::write(path, ics)?,
+                None => print!("{ics}"),
+            }
+        }
+        #[cfg(feature = "document-export")]
+        ExportCommand::Pdf { selection } => {
+            let db = DB::open(zet::core::collection_db_file(root))?;
+            export_document(&db, &config, DocumentFormat::Pdf, selection)?
+        }
+        #[cfg(feature = "document-export")]
+        ExportCommand::Epub { selection } => {
+            let db = DB::open(zet::core::collection_db_file(root))?;
+            export_document(&db, &config, DocumentFormat::Epub, selection)?
+        }
+        ExportCommand::Opml { id, output } => {
+            let mut db = DB::open(zet::core::collection_db_file(root))?;
+            let document = Document::get(&mut db, &DocumentId(id.clone()))
+                .map_err(|_| eyre!("no document with id '{}'", id))?;
+            let content = std::fs::read_to_string(&document.path.0)?;
+            let (_, body) = FrontMatterParser::new(config.front_matter_format).parse(content);
+            let opml = Opml::from_markdown(Some(document.title), &body).to_opml();
+            match output {
+                Some(path) => std::fs::write(path, opml)?,
+                None => print!("{opml}"),
+            }
+        }
+        ExportCommand::Html {
+            output,
+            redact,
+            transclude,
+        } => {
+            let db = DB::open(zet::core::collection_db_file(root))?;
+            export_html(&db, &config, &output, redact, transclude)?
+        }
+    }
+    Ok(())
+}
+
+/// Write a page per document to `output`, at the path of its id. When
+/// redacting, private notes get no page and are not linked to, and the
+/// mentions in or next to private text are left out.
+fn export_html(
+    db: &DB,
+    config: &Config,
+    output: &Path,
+    redacting: bool,
+    transcluding: bool,
+) -> Result<()> {
+    let mut documents = Document::list(db)?;
+    if redacting {
+        documents.retain(|d| !is_private(&d.data));
+    }
+    let ids: Vec<&str> = documents.iter().map(|d| d.id.0.as_str()).collect();
+    let mut summaries = DocumentSummary::list(db)?;
+    summaries.retain(|s| ids.contains(&s.id.0.as_str()));
+    let highlighter = Highlighter::new(&config.html)?;
+
+    let targets = transclusion_targets(db, redacting)?;
+    let transclusion = Transclusion::new(&targets, config.front_matter_format).redacting(redacting);
+    let mut bodies = HashMap::with_capacity(documents.len());
+    for document in &documents {
+        let content = std::fs::read_to_string(&document.path.0)?;
+        let (_, mut body) = FrontMatterParser::new(config.front_matter_format).parse(content);
+        if transcluding {
+            body = transclusion.expand(&document.id, &body)?;
+        }
+        bodies.insert(&document.id, body);
+    }
+
+    for document in &documents {
+        let mut mentions = linked_mentions(
+            db,
+            &document.id,
+            config.front_matter_format,
+            Page::default(),
+        )?;
+        let mut body = bodies[&document.id].clone();
+        if redacting {
+            mentions.retain(|m| match (bodies.get(&m.document_id), &m.block) {
+                (Some(body), Some(block)) => !overlaps_private(body, block),
+                (Some(_), None) => true,
+                // a private note
+                (None, _) => false,
+            });
+            body = redact(&body);
+        }
+        // after redacting, the fences of private text being comments as well
+        let comments = &config.parser.comments;
+        let body = strip_comments(&body, comments);
+        for mention in &mut mentions {
+            mention.context = strip_comments(&mention.context, comments);
+        }
+
+        // links are resolved the way the index resolves them, relative to the
+        // directory of the page
+        let up = "../".repeat(document.id.0.matches('/').count());
+        let resolve = |destination: Destination| {
+            let Destination::Link(target) = destination else {
+                return None;
+            };
+            if target.contains("://") {
+                return None;
+            }
+            ids.iter()
+                .find(|id| target.ends_with(*id))
+                .map(|id| format!("{up}{id}.html"))
+        };
+
+        let path = output.join(format!("{}.html", document.id.0));
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(
+            path,
+            render_page(
+                &config.html,
+                highlighter.as_ref(),
+                &document.title,
+                &body,
+                &mentions,
+                neighbours(&summaries, &document.id).as_ref(),
+                resolve,
+            ),
+        )?;
+    }
+    Ok(())
+}
+
+/// The notes embeds are expanded into, private notes being left out when
+/// redacting
+fn transclusion_targets(db: &DB, redacting: bool) -> Result<LinkTargets> {
+    let mut targets = LinkTargets::load(db)?;
+    if redacting {
+        for document in Document::list(db)? {
+            if is_private(&document.data) {
+                targets.remove(&document.id);
+            }
+        }
+    }
+    Ok(targets)
+}
+
+/// Export the selected notes as a single document, the notes given by id
+/// first. When redacting, private notes given by id fail the export, those
+/// matching the filters are left out.
+#[cfg(feature = "document-export")]
+fn export_document(
+    db: &DB,
+    config: &Config,
+    format: DocumentFormat,
+    selection: ExportSelection,
+) -> Result<()> {
+    let mut documents = Vec::new();
+    for id in &selection.ids {
+        match DocumentQuery::new()
+            .with_ids(vec![id.clone()])
+            .execute(db)?
+            .pop()
+        {
+            Some(document) if selection.redact && is_private(&document.data) => {
+                return Err(eyre!(
+                    "the note {:?} is private, it can not be exported with --redact",
+                    id
+                ));
+            }
+            Some(document) => documents.push(document),
+            None => return Err(eyre!("no document with the id {:?}", id)),
+        }
+    }
+    if !selection.filters.is_empty() {
+        let filtered = selection
+            .filters
+            .into_iter()
+            .fold(DocumentQuery::new(), DocumentQuery::filter)
+            .order_by(SortByOption::Path, SortOrder::Ascending)
+            .execute(db)?;
+        for document in filtered {
+            if selection.redact && is_private(&document.data) {
+                continue;
+            }
+            if !documents.iter().any(|d: &Document| d.id == document.id) {
+                documents.push(document);
+            }
+        }
+    }
+    let Some(first) = documents.first() else {
+        return Err(eyre!("no notes selected"));
+    };
+    let title = selection.title.unwrap_or_else(|| first.title.clone());
+
+    let targets = transclusion_targets(db, selection.redact)?;
+    let transclusion =
+        Transclusion::new(&targets, config.front_matter_format).redacting(selection.redact);
+    let mut notes = Vec::with_capacity(documents.len());
+    for document in &documents {
+        let content = std::fs::read_to_string(&document.path.0)?;
+        let (_, mut body) = FrontMatterParser::new(config.front_matter_format).parse(content);
+        if selection.transclude {
+            body = transclusion.expand(&document.id, &body)?;
+        }
+        notes.push(ExportedNote {
+            id: &document.id,
+            path: &document.path.0,
+            body: match selection.redact {
+                true => strip_comments(&redact(&body), &config.parser.comments),
+                false => strip_comments(&body, &config.parser.comments),
+            },
+        });
+    }
+
+    let highlighter = Highlighter::new(&config.html)?;
+    let html = render_document(&config.html, highlighter.as_ref(), &title, &notes);
+    convert(&config.export, format, &title, &html, &selection.output)
+}
@@ -0,0 +1,54 @@
+use std::io::Write;
+use std::path::Path;
+
+use serde::Serialize;
+use zet::core::db::{DB, DbList};
+use zet::core::fuzzy::rank;
+use zet::core::types::document::DocumentId;
+use zet::core::types::lookup::{DocumentLookup, LookupKind};
+use zet::preamble::*;
+
+use crate::app::commands::ReportFormat;
+
+#[derive(Debug, Serialize)]
+struct FindMatch<'a> {
+    score: i64,
+    id: &'a DocumentId,
+    kind: LookupKind,
+    text: &'a str,
+}
+
+pub fn handle_command(
+    root: &Path,
+    query: &str,
+    limit: usize,
+    output_format: ReportFormat,
+    pretty: bool,
+) -> Result<()> {
+    let db = DB::open(zet::core::collection_db_file(root))?;
+    let lookups = DocumentLookup::list(&db)?;
+    let matches: Vec<FindMatch> = rank(query, &lookups)
+        .into_iter()
+        .take(limit)
+        .map(|(score, lookup)| FindMatch {
+            score,
+            id: &lookup.document_id,
+            kind: lookup.kind,
+            text: &lookup.text,
+        })
+        .collect();
+
+    let mut writer = std::io::BufWriter::new(std::io::stdout());
+    match output_format {
+        ReportFormat::Json if pretty => serde_json::to_writer_pretty(&mut writer, &matches)?,
+        ReportFormat::Json => serde_json::to_writer(&mut writer, &matches)?,
+        ReportFormat::Text => {
+            for m in &matches {
+                writeln!(writer, "{:<7}  {}  {}", m.kind.as_str(), m.id.0, m.text)?;
+            }
+        }
+    }
+    writer.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::eyre;
+use zet::config::Config;
+use zet::core::flavor::DocumentSettings;
+use zet::core::format::format;
+use zet::core::lock::Locks;
+use zet::core::read::read_utf8;
+use zet::preamble::*;
+
+/// Format the documents at `paths`, printing the paths of those changed. With
+/// `check` nothing is written, and an error is returned if a document is not
+/// formatted. Locked documents are skipped.
+pub fn handle_command(
+    root: &Path,
+    config: &Config,
+    paths: Vec<PathBuf>,
+    check: bool,
+) -> Result<()> {
+    let paths = match paths.is_empty() {
+        true => zet::core::workspace_paths(root)?,
+        false => paths,
+    };
+
+    let locks = Locks::load(root, config.front_matter_format)?;
+    let mut changed = 0;
+    for path in paths {
+        let Some(document) = read_utf8(&path)? else {
+            continue;
+        };
+        if locks.is_locked(&path, &document) {
+            log::info!("skipping the locked document {:?}", path);
+            continue;
+        }
+        let settings = DocumentSettings::of(config, &document)?;
+        let formatted = format(&document, &settings.format, &settings.parser.comments)?;
+        if formatted == document {
+            continue;
+        }
+        changed += 1;
+        println!("{}", path.display());
+        if !check {
+            std::fs::write(&path, formatted)?;
+        }
+    }
+
+    if check && changed > 0 {
+        return Err(eyre!("{} documents are not formatted", changed));
+    }
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use std::io::Write;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use zet::core::db::{DB, DbInsert};
+use zet::core::graph::{GraphStats, LinkGraph};
+use zet::core::snapshot;
+use zet::preamble::*;
+
+use crate::app::commands::{GraphCommand, ReportFormat};
+
+pub fn handle_command(root: &Path, command: GraphCommand) -> Result<()> {
+    match command {
+        GraphCommand::Stats {
+            limit,
+            cache,
+            no_cache,
+            output_format,
+            pretty,
+        } => handle_stats(root, limit, cache, !no_cache, output_format, pretty),
+    }
+}
+
+/// The statistics of the link graph, as kept in the `graph_stats` snapshot
+#[derive(Serialize, Deserialize)]
+struct StatsSnapshot {
+    documents: usize,
+    links: usize,
+    stats: Vec<GraphStats>,
+}
+
+fn handle_stats(
+    root: &Path,
+    limit: usize,
+    cache: bool,
+    use_snapshot: bool,
+    output_format: ReportFormat,
+    pretty: bool,
+) -> Result<()> {
+    let db_path = zet::core::collection_db_file(root);
+    let mut db = DB::open(db_path)?;
+
+    let StatsSnapshot {
+        documents,
+        links,
+        mut stats,
+    } = snapshot::cached(&db, "graph_stats", use_snapshot, |db| {
+        let graph = LinkGraph::load(db)?;
+        Ok(StatsSnapshot {
+            documents: graph.nodes.len(),
+            links: graph.edges.len(),
+            stats: graph.stats(),
+        })
+    })?;
+
+    if cache {
+        log::debug!("storing graph statistics for {} documents", stats.len());
+        GraphStats::insert(&mut db, &stats)?;
+    }
+
+    stats.sort_by(|a, b| b.pagerank.total_cmp(&a.pagerank));
+
+    let n_components = stats.iter().map(|s| s.component + 1).max().unwrap_or(0);
+    let clusters = isolated_clusters(&stats, n_components);
+
+    let mut writer = std::io::BufWriter::new(std::io::stdout());
+    match output_format {
+        ReportFormat::Json => {
+            let value = json!({
+                "documents": documents,
+                "links": links,
+                "components": n_components,
+                "hubs": stats.iter().take(limit).collect::<Vec<_>>(),
+                "isolated_clusters": clusters,
+                "stats": stats,
+            });
+            if pretty {
+                serde_json::to_writer_pretty(&mut writer, &value)?;
+            } else {
+                serde_json::to_writer(&mut writer, &value)?;
+            }
+        }
+        ReportFormat::Text => {
+            writeln!(
+                writer,
+                "documents: {}, links: {}, components: {}",
+                documents, links, n_components
+            )?;
+            writeln!(writer)?;
+            writeln!(writer, "hubs:")?;
+            writeln!(writer, "  {:>8} {:>4} {:>4}  id", "pagerank", "in", "out")?;
+            for s in stats.iter().take(limit) {
+                writeln!(
+                    writer,
+                    "  {:>8.4} {:>4} {:>4}  {}",
+                    s.pagerank, s.in_degree, s.out_degree, s.document_id.0
+                )?;
+            }
+            if !clusters.is_empty() {
+                writeln!(writer)?;
+                writeln!(writer, "isolated clusters:")?;
+                for cluster in &clusters {
+                    writeln!(writer, "  {}", cluster.join(", "))?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Every component except the largest one, as lists of document ids
+fn isolated_clusters(stats: &[GraphStats], n_components: usize) -> Vec<Vec<String>> {
+    let mut clusters = vec![Vec::new(); n_components];
+    for s in stats {
+        clusters[s.component].push(s.document_id.0.clone());
+    }
+    clusters.into_iter().skip(1).collect()
+}
@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+use color_eyre::eyre::eyre;
+use zet::core::db::{DB, DbList, DbQuery};
+use zet::core::types::document::{DocumentId, DocumentSummary};
+use zet::core::types::highlight::DocumentHighlight;
+use zet::preamble::*;
+
+use crate::app::commands::ReportFormat;
+
+/// Print the highlights of the document `document`, or of every document,
+/// under the title of their document
+pub fn handle_command(
+    root: &Path,
+    document: Option<String>,
+    output_format: ReportFormat,
+    pretty: bool,
+) -> Result<()> {
+    let db = DB::open(zet::core::collection_db_file(root))?;
+    let titles: HashMap<DocumentId, String> = DocumentSummary::list(&db)?
+        .into_iter()
+        .map(|d| (d.id, d.title))
+        .collect();
+    let document = document.map(DocumentId);
+    if let Some(id) = &document
+        && !titles.contains_key(id)
+    {
+        return Err(eyre!("no document with the id {:?}", id.0));
+    }
+    let highlights = DocumentHighlight::list(&db, document.as_ref())?;
+
+    let mut writer = std::io::BufWriter::new(std::io::stdout());
+    match output_format {
+        ReportFormat::Json if pretty => serde_json::to_writer_pretty(&mut writer, &highlights)?,
+        ReportFormat::Json => serde_json::to_writer(&mut writer, &highlights)?,
+        ReportFormat::Text => {
+            for (i, group) in highlights
+                .chunk_by(|a, b| a.document_id == b.document_id)
+                .enumerate()
+            {
+                let id = &group[0].document_id;
+                if i > 0 {
+                    writeln!(writer)?;
+                }
+                match titles.get(id).filter(|title| !title.is_empty()) {
+                    Some(title) => writeln!(writer, "{} ({})", title, id.0)?,
+                    None => writeln!(writer, "{}", id.0)?,
+                }
+                for highlight in group {
+                    writeln!(writer, "- {}", highlight.content)?;
+                }
+            }
+        }
+    }
+    writer.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,308 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::eyre;
+use jiff::{Timestamp, Zoned};
+use serde_json::{Value, json};
+use zet::config::Config;
+use zet::core::db::DB;
+use zet::core::email::{DATE_KEY, Email, FROM_KEY, MESSAGE_ID_KEY, is_maildir, maildir_messages};
+use zet::core::frontmatter::set_frontmatter_value;
+use zet::core::hooks::HookEvent;
+use zet::core::id::IdScheme;
+use zet::core::opml::Opml;
+use zet::core::parser::FrontMatterFormat;
+use zet::core::query::DocumentQuery;
+use zet::core::slug::slugify;
+use zet::core::template_engine::{
+    render_collection_template, resolve_group_from_cwd, resolve_template_string,
+};
+use zet::core::types::document::DocumentId;
+use zet::preamble::*;
+
+use crate::app::commands::ImportCommand;
+
+pub fn handle_command(root: &Path, config: Config, command: ImportCommand) -> Result<()> {
+    match command {
+        ImportCommand::Eml {
+            path,
+            group,
+            no_index,
+        } => handle_eml(root, config, &path, &group, no_index),
+        ImportCommand::Opml {
+            path,
+            group,
+            no_index,
+        } => handle_opml(root, config, &path, &group, no_index),
+    }
+}
+
+/// The directory, template and id scheme of the group notes are imported
+/// into. A group that is not configured is the directory of the same name.
+pub(super) struct ImportGroup {
+    root: PathBuf,
+    directory: PathBuf,
+    template: String,
+    id_scheme: IdScheme,
+    format: FrontMatterFormat,
+}
+
+impl ImportGroup {
+    pub(super) fn resolve(root: &Path, config: &Config, group: &str) -> Result<ImportGroup> {
+        let group_config = config.group.get(group);
+        let directory = match group_config.and_then(|g| g.directories.first()) {
+            Some(directory) => root.join(directory),
+            None => root.join(group),
+        };
+        let id_scheme = match group_config {
+            Some(group) => group.id_scheme,
+            None => config.id_scheme(root, &directory),
+        };
+        Ok(ImportGroup {
+            root: root.to_owned(),
+            template: resolve_template_string(root, None, group_config)?,
+            directory,
+            id_scheme,
+            format: config.front_matter_format,
+        })
+    }
+
+    /// The notes of `directory`, with the template of the group it belongs to
+    pub(super) fn of_directory(
+        root: &Path,
+        config: &Config,
+        directory: &Path,
+    ) -> Result<ImportGroup> {
+        let group_config = resolve_group_from_cwd(config, root, directory).map(|(_, g)| g);
+        let id_scheme = match group_config {
+            Some(group) => group.id_scheme,
+            None => config.id_scheme(root, directory),
+        };
+        Ok(ImportGroup {
+            root: root.to_owned(),
+            template: resolve_template_string(root, None, group_config)?,
+            directory: directory.to_owned(),
+            id_scheme,
+            format: config.front_matter_format,
+        })
+    }
+
+    /// The id and path of a new note titled `title`, suffixed with -2, -3,
+    /// ... if taken, as imported notes often share their titles
+    pub(super) fn new_note(&self, title: &str, now: &Zoned) -> Result<(String, PathBuf)> {
+        std::fs::create_dir_all(&self.directory)?;
+        let (DocumentId(mut id), filename) =
+            self.id_scheme
+                .new_note(&self.root, &self.directory, title, now)?;
+        let mut path = self.directory.join(filename);
+        let base = id.clone();
+        let mut n = 2;
+        while path.exists() {
+            id = format!("{}-{}", base, n);
+            path = self.directory.join(self.id_scheme.filename(&id, title));
+            n += 1;
+        }
+        Ok((id, path))
+    }
+
+    /// Render the template of the group for the note `id`, setting the
+    /// frontmatter `fields` that have a value
+    pub(super) fn render_note(
+        &self,
+        id: &str,
+        title: &str,
+        now: &Zoned,
+        content: &str,
+        fields: &[(&str, Option<&str>)],
+    ) -> Result<String> {
+        let extra: HashMap<String, Value> = fields
+            .iter()
+            .map(|(key, value)| (key.to_string(), json!(value)))
+            .collect();
+        let date = now.strftime("%Y-%m-%d").to_string();
+        let mut rendered = render_collection_template(
+            &self.root,
+            &self.template,
+            id,
+            title,
+            &date,
+            content,
+            &extra,
+        )?;
+
+        // set rather than templated, so that they are quoted as the format needs
+        for (key, value) in [("title", Some(title))].iter().chain(fields) {
+            if let Some(value) = value {
+                rendered = set_frontmatter_value(&rendered, self.format, key, &json!(value))?;
+            }
+        }
+        Ok(rendered)
+    }
+
+    /// Render the note and write it to `path`, see [`ImportGroup::render_note`]
+    pub(super) fn write_note(
+        &self,
+        (id, path): (String, PathBuf),
+        title: String,
+        now: &Zoned,
+        content: &str,
+        fields: &[(&str, Option<&str>)],
+    ) -> Result<(DocumentId, String, PathBuf)> {
+        let rendered = self.render_note(&id, &title, now, content, fields)?;
+        std::fs::write(&path, &rendered)?;
+        let path = std::path::absolute(&path)?;
+        println!("{}", path.display());
+        Ok((DocumentId(id), title, path))
+    }
+}
+
+/// Index the imported notes unless `no_index`, then run the create hooks
+pub(super) fn finish(
+    root: &Path,
+    config: &Config,
+    created: Vec<(DocumentId, String, PathBuf)>,
+    no_index: bool,
+) -> Result<()> {
+    if !no_index && !created.is_empty() {
+        let index_config = Config::resolve(root)?;
+        super::index::handle_command(root, index_config, false, false)?;
+    }
+    for (id, title, path) in created {
+        config
+            .hooks
+            .run(root, &HookEvent::Create { id, title, path });
+    }
+    Ok(())
+}
+
+/// Import the emails at `path` into `group`
+fn handle_eml(root: &Path, config: Config, path: &Path, group: &str, no_index: bool) -> Result<()> {
+    let messages = if is_maildir(path) {
+        maildir_messages(path)?
+    } else if path.is_dir() {
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(path)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<std::io::Result<_>>()?;
+        paths.retain(|p| {
+            p.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("eml"))
+        });
+        paths.sort();
+        paths
+    } else if path.is_file() {
+        vec![path.to_owned()]
+    } else {
+        return Err(eyre!("no email or maildir at {:?}", path));
+    };
+
+    let group = ImportGroup::resolve(root, &config, group)?;
+    let tz = config.timezone()?;
+
+    let db = DB::open(zet::core::collection_db_file(root))?;
+    let mut imported: HashSet<String> = DocumentQuery::new()
+        .with_frontmatter(MESSAGE_ID_KEY, Vec::new())
+        .execute(&db)?
+        .into_iter()
+        .filter_map(|d| Some(d.data.get(MESSAGE_ID_KEY)?.as_str()?.to_owned()))
+        .collect();
+    drop(db);
+
+    let mut created = Vec::new();
+    for message in messages {
+        let email = Email::read(&message)?;
+        if let Some(message_id) = &email.message_id
+            && !imported.insert(message_id.clone())
+        {
+            log::info!("skipping {:?}, imported before", message);
+            continue;
+        }
+
+        // a subject without a letter or digit would name the note `.md`
+        let title = email
+            .title()
+            .filter(|title| !slugify(title).is_empty())
+            .unwrap_or_else(|| "Untitled email".to_owned());
+        let sent = email
+            .date
+            .unwrap_or_else(Timestamp::now)
+            .to_zoned(tz.clone());
+        let (id, path) = group.new_note(&title, &sent)?;
+
+        let mut content = match (&email.text, &email.html) {
+            (Some(text), _) => text.trim().to_owned(),
+            (None, Some(html)) => config.fetch.html_to_markdown(html)?.trim().to_owned(),
+            (None, None) => String::new(),
+        };
+        if !email.attachments.is_empty() {
+            let assets = path.with_extension("");
+            std::fs::create_dir_all(&assets)?;
+            content.push_str("\n\n## Attachments\n");
+            for attachment in &email.attachments {
+                let file = unique_path(&assets, &attachment.filename);
+                std::fs::write(&file, &attachment.content)?;
+                let name = file.file_name().unwrap_or_default().to_string_lossy();
+                let target = zet::core::relative_link(&group.directory, &file);
+                content.push_str(&format!("\n- [{}](<{}>)", name, target));
+            }
+            content.push('\n');
+        }
+
+        let sent_at = email.date.map(|_| {
+            sent.timestamp()
+                .display_with_offset(sent.offset())
+                .to_string()
+        });
+        let fields = [
+            (FROM_KEY, email.from.as_deref()),
+            (DATE_KEY, sent_at.as_deref()),
+            (MESSAGE_ID_KEY, email.message_id.as_deref()),
+        ];
+        created.push(group.write_note((id, path), title, &sent, &content, &fields)?);
+    }
+
+    finish(root, &config, created, no_index)
+}
+
+/// Import the outline at `path` as a note of `group`, titled by the outline
+/// or else by the file name
+fn handle_opml(
+    root: &Path,
+    config: Config,
+    path: &Path,
+    group: &str,
+    no_index: bool,
+) -> Result<()> {
+    let xml =
+        std::fs::read_to_string(path).map_err(|e| eyre!("could not read {:?}: {}", path, e))?;
+    let opml = Opml::parse(&xml)?;
+    let title = opml.title.clone().unwrap_or_else(|| {
+        path.file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned()
+    });
+
+    let group = ImportGroup::resolve(root, &config, group)?;
+    let now = Timestamp::now().to_zoned(config.timezone()?);
+    let note = group.new_note(&title, &now)?;
+    let created = group.write_note(note, title, &now, &opml.to_markdown(), &[])?;
+
+    finish(root, &config, vec![created], no_index)
+}
+
+/// The path of `filename` in `directory`, suffixed with -2, -3, ... before
+/// its extension if taken
+pub(super) fn unique_path(directory: &Path, filename: &str) -> PathBuf {
+    let path = directory.join(filename);
+    let (stem, extension) = match filename.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{extension}")),
+        _ => (filename, String::new()),
+    };
+    let mut unique = path;
+    let mut n = 2;
+    while unique.exists() {
+        unique = directory.join(format!("{stem}-{n}{extension}"));
+        n += 1;
+    }
+    unique
+}
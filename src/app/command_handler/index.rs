@@ -1,37 +1,157 @@
+use jiff::Timestamp;
+use jiff::tz::TimeZone;
+use rusqlite::Connection;
 use serde_json::{Value, json};
 use sql_minifier::macros::minify_sql as sql;
-use std::path::Path;
-use zet::core::db::{DbDelete, DbInsert, DbUpdate};
-use zet::core::parser::ast_nodes::{Node, TaskListMarker};
-use zet::core::path_to_id;
-use zet::core::types::heading::NewDocumentHeading;
-use zet::core::types::link::{DocumentLink, DocumentLinkSource, NewDocumentLink};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use zet::core::date_parser::find_date;
+use zet::core::db::{DbDelete, DbGet, DbInsert, DbLock, DbUpdate, with_transaction};
+use zet::core::flavor::DocumentSettings;
+use zet::core::hooks::HookEvent;
+use zet::core::index_hook::{DerivedRow, IndexHooks};
+use zet::core::index_journal::IndexJournal;
+use zet::core::parser::arena::Ast;
+use zet::core::parser::comments::strip_comments;
+use zet::core::read::read_document_logged;
+use zet::core::readlist::URL_KEY;
+use zet::core::tags::{InlineTag, extract_inline_tags};
+use zet::core::types::derived::{DocumentDerived, NewDocumentDerived};
+use zet::core::types::event::{Event, EventKind, NewEvent};
+use zet::core::types::heading::{DocumentHeading, NewDocumentHeading};
+use zet::core::types::highlight::{DocumentHighlight, NewDocumentHighlight};
+use zet::core::types::index_run::{IndexRun, NewIndexRun};
+use zet::core::types::link::{DocumentLink, NewDocumentLink, UnresolvedLink};
+use zet::core::types::lookup::{DocumentLookup, LookupKind};
+use zet::core::types::mention::{DocumentMention, NewDocumentMention};
+use zet::core::types::metadata::{DocumentMetadata, NewDocumentMetadata};
+use zet::core::types::quote::{DocumentQuote, NewDocumentQuote};
+use zet::core::types::source::Source;
+use zet::core::types::stub::{DocumentStub, NewDocumentStub};
 use zet::core::types::tag::NewDocumentTag;
 use zet::core::types::task::{DocumentTask, NewDocumentTask};
-use zet::core::types::{RangeEnd, RangeStart};
+use zet::core::url::normalize_url;
 use zet::core::{
-    extract_id_from_frontmatter, extract_tags_from_frontmatter, extract_title_from_ast,
-    extract_title_from_frontmatter,
+    LinkTargets, extract_aliases_from_frontmatter, extract_id_from_frontmatter,
+    extract_tags_from_frontmatter, extract_title_from_ast, extract_title_from_frontmatter,
 };
 use zet::preamble::*;
 use zet::{
     config::Config,
     core::{
         db::DB,
-        parser::FrontMatterParser,
+        parser::{DocumentParser, FrontMatterParser, Parse},
         types::document::{
             CreatedTimestamp, Document, DocumentId, DocumentPath, ModifiedTimestamp,
         },
     },
 };
 
-pub fn handle_command(root: &Path, config: Config, _force: bool) -> Result<()> {
-    // let root = &config.root;
+pub fn handle_command(root: &Path, config: Config, _force: bool, dry_run: bool) -> Result<()> {
+    run(root, &config, dry_run, None)
+}
+
+/// Index the documents at `paths` only, e.g. a note that was just written,
+/// leaving the other changes of the collection to the next `zet index`
+pub fn index_paths(root: &Path, config: &Config, paths: &[PathBuf]) -> Result<()> {
+    run(root, config, false, Some(paths))
+}
+
+fn run(root: &Path, config: &Config, dry_run: bool, only: Option<&[PathBuf]>) -> Result<()> {
     let db_path = zet::core::collection_db_file(root);
     let mut db = DB::open(db_path)?;
 
+    if dry_run {
+        let IndexPlan { journal, .. } = plan_of(root, config, &db, only)?;
+        for path in &journal.new {
+            println!(
+                "new      {}",
+                path.strip_prefix(root).unwrap_or(path).display()
+            );
+        }
+        for id in &journal.updated {
+            println!("updated  {}", id.0);
+        }
+        for id in &journal.removed {
+            println!("removed  {}", id.0);
+        }
+        return Ok(());
+    }
+
+    // held until the index is written, a journal found while holding it was
+    // left behind by a run that did not complete
+    let _lock = DbLock::acquire(&zet::core::collection_db_lock_file(root))?;
+    if let Some(journal) = IndexJournal::read(root)? {
+        log::warn!(
+            "the index started at {} did not complete, its {} changes were rolled back",
+            journal.started,
+            journal.len()
+        );
+    }
+
+    // all changes are written in a single transaction, an index that fails
+    // halfway leaves the db as it was
+    let started = Timestamp::now();
+    let journal = with_transaction(&mut db, |db| {
+        let journal = index(root, config, db, only)?;
+        IndexRun::insert(
+            db,
+            &[NewIndexRun {
+                started,
+                finished: Timestamp::now(),
+                changes: journal.len(),
+            }],
+        )?;
+        Ok(journal)
+    })?;
+    IndexJournal::remove(root)?;
+
+    if !journal.is_empty() {
+        let IndexJournal {
+            new,
+            updated,
+            removed,
+            ..
+        } = journal;
+        config.hooks.run(
+            root,
+            &HookEvent::Index {
+                new,
+                updated,
+                removed,
+            },
+        );
+    }
+    Ok(())
+}
+
+/// The changes an index run would make
+pub struct IndexPlan {
+    pub journal: IndexJournal,
+    /// the rows of the new and updated documents
+    pub rows: IndexRows,
+}
+
+/// Detect the documents that changed since the last index and parse them,
+/// without writing anything
+pub fn plan(root: &Path, config: &Config, db: &Connection) -> Result<IndexPlan> {
+    plan_of(root, config, db, None)
+}
+
+/// Like [`plan`], for the documents at `only` if given
+fn plan_of(
+    root: &Path,
+    config: &Config,
+    db: &Connection,
+    only: Option<&[PathBuf]>,
+) -> Result<IndexPlan> {
     // we figure out which documents we need to process,reprocess and delete
-    let (new, updated, removed) = zet::core::collection_status(root, &db);
+    let status = match only {
+        Some(paths) => zet::core::paths_status(root, db, &config.index, paths),
+        None => zet::core::collection_status(root, db, &config.index),
+    };
+    let journal = IndexJournal::new(Timestamp::now(), &status);
+    let (new, updated, removed) = status;
 
     log::info!(
         "collection status since last index: n_new={}, n_updated={}, n_removed={}",
@@ -40,97 +160,363 @@ pub fn handle_command(root: &Path, config: Config, _force: bool) -> Result<()> {
         removed.len()
     );
 
-    // Delete removed documents. Associated data (links, headings) will be
-    //
-    // removed as well by trigger
-    Document::delete(&mut db, &removed)?;
-
     // parse and collect the data to be inserted into the db
-    let mut documents = Vec::with_capacity(new.len() + updated.len());
-    let mut fts_entries: Vec<(DocumentId, String, String)> = Vec::new(); // (id, title, body)
-    let mut links = Vec::new();
-    let mut headings = Vec::new();
-    let mut tasks = Vec::new();
-    let mut tags = Vec::new();
-    process_new_documents(
-        root,
-        &config,
-        new,
-        &mut documents,
-        &mut fts_entries,
-        &mut links,
-        &mut headings,
-        &mut tasks,
-        &mut tags,
-    )?;
-    process_existing_documents(
-        root,
-        &config,
-        updated,
-        &mut documents,
-        &mut fts_entries,
-        &mut links,
-        &mut headings,
-        &mut tasks,
-        &mut tags,
-    )?;
-
-    // Perform an upsert on the documents. This will clear any associated data
-    // as well
-    Document::update(&mut db, &documents)?;
+    let mut hooks = IndexHooks::builtin();
+    #[cfg(feature = "wasm-plugins")]
+    {
+        let host = zet::core::plugin::PluginHost::load(root)?;
+        if !host.is_empty() {
+            hooks.register(zet::core::plugin::PluginHook::new(host));
+        }
+    }
+    let mut rows = IndexRows::default();
+    process_new_documents(root, config, new, &mut hooks, &mut rows)?;
+    process_existing_documents(root, config, updated, &mut hooks, &mut rows)?;
+
+    Ok(IndexPlan { journal, rows })
+}
+
+/// Bring the index up to date, or that of the documents at `only`, returning
+/// the journal of the changes made
+fn index(
+    root: &Path,
+    config: &Config,
+    db: &mut Connection,
+    only: Option<&[PathBuf]>,
+) -> Result<IndexJournal> {
+    let IndexPlan { journal, rows } = plan_of(root, config, db, only)?;
+    let removed = journal.removed.clone();
+    let IndexRows {
+        documents,
+        fts_entries,
+        links,
+        headings,
+        tasks,
+        highlights,
+        quotes,
+        mentions,
+        tags,
+        metadata,
+        derived,
+        lookup,
+    } = rows;
+
+    if journal.is_empty() {
+        return Ok(journal);
+    }
+    journal.write(root)?;
+
+    // the state the events are derived from
+    let changed: Vec<DocumentId> = removed
+        .iter()
+        .chain(documents.iter().map(|d| &d.id))
+        .cloned()
+        .collect();
+    let previous_links = link_targets(db, &changed)?;
+    let removed_documents = removed
+        .iter()
+        .map(|id| Document::get(db, id))
+        .collect::<Result<Vec<Document>>>()?;
+
+    // Delete removed documents. Associated data (links, headings, tasks,
+    // tags) is removed as well by the foreign key cascades
+    Document::delete(db, &removed)?;
+
+    // Perform an upsert on the documents. This will clear any associated
+    // data as well
+    Document::update(db, &documents)?;
 
     // Populate FTS index (contentless - we manually insert)
-    populate_fts_index(&mut db, &fts_entries)?;
+    populate_fts_index(db, &fts_entries)?;
+
+    // the pages and DOIs referenced are recorded before the links are
+    // resolved, and kept when the references are removed
+    let sources = collect_sources(&config.timezone()?, &documents, &links);
+    Source::insert(db, &sources)?;
 
     // links needs to be handled in a special. We want to resolve the link
     // target to some actual document
-    let resolved_links = resolve_links(&db, links)?;
-    DocumentLink::insert(&mut db, &resolved_links)?;
-    DocumentTask::insert(&mut db, &tasks)?;
-    NewDocumentTag::insert(&mut db, &tags)?;
+    let (resolved_links, stubs) = resolve_links(db, links)?;
+    DocumentLink::insert(db, &resolved_links)?;
+    if config.index.create_stubs {
+        DocumentStub::insert(db, &stubs)?;
+    }
+    DocumentHeading::insert(db, &headings)?;
+    DocumentTask::insert(db, &tasks)?;
+    DocumentHighlight::insert(db, &highlights)?;
+    DocumentQuote::insert(db, &quotes)?;
+    DocumentMention::insert(db, &mentions)?;
+    NewDocumentTag::insert(db, &tags)?;
+    DocumentMetadata::insert(db, &metadata)?;
+    DocumentDerived::insert(db, &derived)?;
+    DocumentLookup::insert(db, &lookup)?;
+
+    let now = Timestamp::now();
+    let mut events = Vec::new();
+    for document in &removed_documents {
+        events.push(document_event(now, EventKind::DocumentRemoved, document));
+    }
+    for document in &documents {
+        let kind = match journal.updated.contains(&document.id) {
+            true => EventKind::DocumentUpdated,
+            false => EventKind::DocumentAdded,
+        };
+        events.push(document_event(now, kind, document));
+    }
+    let links = link_targets(db, &changed)?;
+    for id in &changed {
+        let before = previous_links
+            .get(id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let after = links.get(id).map(Vec::as_slice).unwrap_or_default();
+        for (kind, targets) in [
+            (EventKind::LinkRemoved, difference(before, after)),
+            (EventKind::LinkAdded, difference(after, before)),
+        ] {
+            for to in targets {
+                events.push(NewEvent {
+                    timestamp: now,
+                    kind,
+                    document_id: id.clone(),
+                    data: json!({ "to": to }),
+                });
+            }
+        }
+    }
+    Event::insert(db, &events)?;
 
-    Ok(())
+    Ok(journal)
+}
+
+/// The resolved link targets of the documents `ids`
+pub fn link_targets(
+    db: &Connection,
+    ids: &[DocumentId],
+) -> Result<HashMap<DocumentId, Vec<DocumentId>>> {
+    let mut query = db.prepare_cached(sql!(
+        "select to_id from document_link where from_id = ?1 and to_id is not null order by to_id"
+    ))?;
+    let mut targets = HashMap::with_capacity(ids.len());
+    for id in ids {
+        let to = query
+            .query_map([id], |r| r.get(0))?
+            .collect::<rusqlite::Result<Vec<DocumentId>>>()?;
+        targets.insert(id.clone(), to);
+    }
+    Ok(targets)
+}
+
+/// The elements of the sorted `a` not in the sorted `b`, counting duplicates
+pub fn difference<'a, T: Ord>(a: &'a [T], b: &[T]) -> Vec<&'a T> {
+    let mut b = b.iter().peekable();
+    let mut result = Vec::new();
+    for id in a {
+        while b.next_if(|other| *other < id).is_some() {}
+        if b.next_if(|other| *other == id).is_none() {
+            result.push(id);
+        }
+    }
+    result
+}
+
+fn document_event(timestamp: Timestamp, kind: EventKind, document: &Document) -> NewEvent {
+    NewEvent {
+        timestamp,
+        kind,
+        document_id: document.id.clone(),
+        data: json!({ "path": document.path.0, "title": document.title }),
+    }
+}
+
+/// The data collected from the indexed documents
+#[derive(Default)]
+pub struct IndexRows {
+    pub documents: Vec<Document>,
+    pub fts_entries: Vec<(DocumentId, String, String)>, // (id, title, body)
+    pub links: Vec<UnresolvedLink>,
+    pub headings: Vec<NewDocumentHeading>,
+    pub tasks: Vec<NewDocumentTask>,
+    pub highlights: Vec<NewDocumentHighlight>,
+    pub quotes: Vec<NewDocumentQuote>,
+    pub mentions: Vec<NewDocumentMention>,
+    pub tags: Vec<NewDocumentTag>,
+    pub metadata: Vec<NewDocumentMetadata>,
+    pub derived: Vec<NewDocumentDerived>,
+    pub lookup: Vec<DocumentLookup>,
+}
+
+impl IndexRows {
+    /// Collect `document` and the rows derived from it
+    fn push(
+        &mut self,
+        config: &Config,
+        tz: &TimeZone,
+        hooks: &mut IndexHooks,
+        document: Document,
+        ast: &Ast,
+        content: String,
+    ) -> Result<()> {
+        let first_task = self.tasks.len();
+        let first_heading = self.headings.len();
+        for row in hooks.run(&document, ast) {
+            match row {
+                DerivedRow::Heading(heading) => self.headings.push(heading),
+                DerivedRow::Task(task) => self.tasks.push(task),
+                DerivedRow::Link(link) => self.links.push(link),
+                DerivedRow::Highlight(highlight) => self.highlights.push(highlight),
+                DerivedRow::Quote(quote) => self.quotes.push(quote),
+                DerivedRow::Mention(mention) => self.mentions.push(mention),
+                DerivedRow::Metadata(metadata) => self.metadata.push(metadata),
+                DerivedRow::Custom(derived) => self.derived.push(derived),
+            }
+        }
+        resolve_task_due_dates(
+            &mut self.tasks[first_task..],
+            document.modified.0,
+            tz,
+            config,
+        );
+
+        // tags
+        for tag in extract_tags_from_frontmatter(&document.data) {
+            self.tags.push(NewDocumentTag {
+                document_id: document.id.clone(),
+                tag,
+                range_start: None,
+                range_end: None,
+            });
+        }
+        let settings = DocumentSettings::resolve(config, Some(&document.data))?;
+        let inline_tags = match settings.parser.inline_tags {
+            true => extract_inline_tags(&content)?,
+            false => Vec::new(),
+        };
+        for InlineTag { tag, range } in inline_tags {
+            self.tags.push(NewDocumentTag {
+                document_id: document.id.clone(),
+                tag,
+                range_start: Some(range.start),
+                range_end: Some(range.end),
+            });
+        }
+
+        // the names the document can be found by
+        let lookup = |kind, text: &str| DocumentLookup {
+            document_id: document.id.clone(),
+            kind,
+            text: text.to_owned(),
+        };
+        if !document.title.is_empty() {
+            self.lookup.push(lookup(LookupKind::Title, &document.title));
+        }
+        for alias in extract_aliases_from_frontmatter(&document.data) {
+            self.lookup.push(lookup(LookupKind::Alias, &alias));
+        }
+        for heading in &self.headings[first_heading..] {
+            self.lookup
+                .push(lookup(LookupKind::Heading, &heading.content));
+        }
+
+        // FTS entry (id, title, body content), comments are not searched
+        let content = strip_comments(&content, &settings.parser.comments);
+        self.fts_entries
+            .push((document.id.clone(), document.title.clone(), content));
+
+        self.documents.push(document);
+        Ok(())
+    }
 }
 
-fn resolve_links(db: &DB, unresolved_links: Vec<UnresolvedLink>) -> Result<Vec<NewDocumentLink>> {
+/// Resolve link targets to documents. Wikilinks that could not be resolved
+/// are returned as stubs.
+fn resolve_links(
+    db: &Connection,
+    unresolved_links: Vec<UnresolvedLink>,
+) -> Result<(Vec<NewDocumentLink>, Vec<NewDocumentStub>)> {
     let mut links = Vec::new();
+    let mut stubs = Vec::new();
 
     // linear search for now!
-    let ids: Vec<DocumentId> = db
-        .prepare(sql!("select id from document"))?
-        .query_map([], |r| r.get(0))?
-        .map(|f| f.map_err(From::from))
-        .collect::<Result<Vec<DocumentId>>>()?;
+    let targets = LinkTargets::load(db)?;
 
     for link in unresolved_links {
-        let res = ids
-            .iter()
-            .find(|id| link.to.ends_with(&id.0))
-            .map(|v| v.to_owned());
+        let from = DocumentId::from(link.from.clone());
+        let res = targets.resolve(&from, &link.to, link.wiki).cloned();
+        let url = match link.wiki {
+            true => None,
+            false => normalize_url(&link.to),
+        };
+        if res.is_none() && link.wiki {
+            let title = link.to.split('#').next().unwrap_or_default().trim();
+            if !title.is_empty() {
+                stubs.push(NewDocumentStub {
+                    id: DocumentId(zet::core::slug::slugify(title)),
+                    title: title.to_owned(),
+                    from: link.from.clone().into(),
+                });
+            }
+        }
         links.push(NewDocumentLink {
             from: link.from,
             to: res.map(From::from),
             range_start: link.range_start,
             range_end: link.range_end,
+            block_start: link.block.as_ref().map(|block| block.start),
+            block_end: link.block.map(|block| block.end),
+            url,
         })
     }
 
-    Ok(links)
+    Ok((links, stubs))
+}
+
+/// The external pages and DOIs the documents link to or have as their
+/// frontmatter url, seen from the creation to the last modification of the
+/// documents
+fn collect_sources(tz: &TimeZone, documents: &[Document], links: &[UnresolvedLink]) -> Vec<Source> {
+    let frontmatter_urls = documents
+        .iter()
+        .filter_map(|d| Some((d.id.clone(), d.data.get(URL_KEY)?.as_str()?)));
+    let link_urls = links
+        .iter()
+        .filter(|link| !link.wiki)
+        .map(|link| (DocumentId::from(link.from.clone()), link.to.as_str()));
+    let documents: HashMap<&DocumentId, &Document> = documents.iter().map(|d| (&d.id, d)).collect();
+
+    let mut sources: HashMap<String, Source> = HashMap::new();
+    for (id, original) in frontmatter_urls.chain(link_urls) {
+        let (Some(url), Some(document)) = (normalize_url(original), documents.get(&id)) else {
+            continue;
+        };
+        let first_seen = document.created.0.to_zoned(tz.clone()).date();
+        let last_seen = document.modified.0.to_zoned(tz.clone()).date();
+        let source = sources.entry(url.clone()).or_insert_with(|| Source {
+            url,
+            original: original.to_owned(),
+            first_seen,
+            last_seen,
+        });
+        if first_seen < source.first_seen {
+            source.original = original.to_owned();
+            source.first_seen = first_seen;
+        }
+        source.last_seen = source.last_seen.max(last_seen);
+    }
+    let mut sources: Vec<Source> = sources.into_values().collect();
+    sources.sort_by(|a, b| a.url.cmp(&b.url));
+    sources
 }
 
-#[allow(clippy::too_many_arguments)]
 fn process_new_documents(
     root: &Path,
     config: &Config,
     new: Vec<DocumentPath>,
-    documents: &mut Vec<Document>,
-    fts_entries: &mut Vec<(DocumentId, String, String)>,
-    links: &mut Vec<UnresolvedLink>,
-    headings: &mut Vec<NewDocumentHeading>,
-    tasks: &mut Vec<NewDocumentTask>,
-    tags: &mut Vec<NewDocumentTag>,
+    hooks: &mut IndexHooks,
+    rows: &mut IndexRows,
 ) -> Result<()> {
     log::info!("processing new documents");
+    let tz = config.timezone()?;
 
     for DocumentPath(path) in new {
         log::debug!("processing {:?}", path);
@@ -139,45 +525,25 @@ fn process_new_documents(
         let modified = ModifiedTimestamp(metadata.modified().map(TryFrom::try_from)??);
         let created = CreatedTimestamp(metadata.created().map(TryFrom::try_from)??);
 
-        let content = std::fs::read_to_string(&path)?;
+        let Some(content) = read_document_logged(&path, &config.index)?.content else {
+            continue;
+        };
         // hash
         let hash = zet::core::hash(&content);
 
         // frontmatter and ast
-        let (frontmatter, document) = zet::core::parser::parse(
-            FrontMatterParser::new(config.front_matter_format),
-            zet::core::parser::DocumentParser::new(),
-            content.clone(),
-        )?;
-        let frontmatter = frontmatter.unwrap_or(serde_json::Value::Null);
+        let (frontmatter, ast) = parse_document(config, &content)?;
 
         // id - check frontmatter first, then fall back to path-based generation
-        let id =
-            extract_id_from_frontmatter(&frontmatter).unwrap_or_else(|| path_to_id(root, &path));
+        let id = extract_id_from_frontmatter(&frontmatter)
+            .unwrap_or_else(|| config.id_scheme(root, &path).path_to_id(root, &path));
 
         // title
         let title = extract_title_from_frontmatter(&frontmatter)
-            .or_else(|| extract_title_from_ast(&document))
+            .or_else(|| extract_title_from_ast(&ast))
             .unwrap_or("".into());
 
-        // links
-        extract_links_from_ast(links, &id, &document);
-        extract_headings_from_ast(headings, &id, &document);
-        extract_tasks_from_ast(tasks, &id, &document);
-
-        // tags
-        for tag in extract_tags_from_frontmatter(&frontmatter) {
-            tags.push(NewDocumentTag {
-                document_id: id.clone(),
-                tag,
-            });
-        }
-
-        // FTS entry (id, title, body content)
-        fts_entries.push((id.clone(), title.clone(), content));
-
-        // documents
-        documents.push(Document {
+        let document = Document {
             id,
             title,
             path: DocumentPath(path),
@@ -185,13 +551,23 @@ fn process_new_documents(
             modified,
             created,
             data: frontmatter,
-        });
+        };
+        rows.push(config, &tz, hooks, document, &ast, content)?;
     }
 
     Ok(())
 }
 
-#[allow(clippy::too_many_arguments)]
+/// The frontmatter and nodes of `content`, the body parsed with the settings
+/// of the document
+fn parse_document(config: &Config, content: &str) -> Result<(Value, Ast)> {
+    let (frontmatter, body) =
+        FrontMatterParser::new(config.front_matter_format).parse(content.to_owned());
+    let settings = DocumentSettings::resolve(config, frontmatter.as_ref())?;
+    let ast = DocumentParser::from_config(&settings.parser).parse(&body)?;
+    Ok((frontmatter.unwrap_or(Value::Null), ast))
+}
+
 fn process_existing_documents(
     _root: &Path,
     config: &Config,
@@ -202,47 +578,23 @@ fn process_existing_documents(
         zet::core::types::document::CreatedTimestamp,
         u32,
     )>,
-
-    documents: &mut Vec<Document>,
-    fts_entries: &mut Vec<(DocumentId, String, String)>,
-    links: &mut Vec<UnresolvedLink>,
-    headings: &mut Vec<NewDocumentHeading>,
-    tasks: &mut Vec<NewDocumentTask>,
-    tags: &mut Vec<NewDocumentTag>,
+    hooks: &mut IndexHooks,
+    rows: &mut IndexRows,
 ) -> Result<()> {
+    let tz = config.timezone()?;
     for (id, path, modified, created, hash) in updated {
-        let content = std::fs::read_to_string(&path.0)?;
+        let Some(content) = read_document_logged(&path.0, &config.index)?.content else {
+            continue;
+        };
 
         // frontmatter and ast
-        let (frontmatter, document) = zet::core::parser::parse(
-            FrontMatterParser::new(config.front_matter_format),
-            zet::core::parser::DocumentParser::new(),
-            content.clone(),
-        )?;
-        // frontmatter and ast
-        let frontmatter = frontmatter.unwrap_or(Value::Null);
+        let (frontmatter, ast) = parse_document(config, &content)?;
         // title
         let title = extract_title_from_frontmatter(&frontmatter)
-            .or_else(|| extract_title_from_ast(&document))
+            .or_else(|| extract_title_from_ast(&ast))
             .unwrap_or("".into());
 
-        // links
-        extract_links_from_ast(links, &id, &document);
-        extract_headings_from_ast(headings, &id, &document);
-        extract_tasks_from_ast(tasks, &id, &document);
-
-        // tags
-        for tag in extract_tags_from_frontmatter(&frontmatter) {
-            tags.push(NewDocumentTag {
-                document_id: id.clone(),
-                tag,
-            });
-        }
-
-        // FTS entry (id, title, body content)
-        fts_entries.push((id.clone(), title.clone(), content));
-
-        documents.push(Document {
+        let document = Document {
             id,
             title,
             path,
@@ -250,28 +602,29 @@ fn process_existing_documents(
             modified,
             created,
             data: frontmatter,
-        });
+        };
+        rows.push(config, &tz, hooks, document, &ast, content)?;
     }
 
     Ok(())
 }
 
 /// Populate the contentless FTS index with document content
-fn populate_fts_index(db: &mut DB, entries: &[(DocumentId, String, String)]) -> Result<()> {
+fn populate_fts_index(db: &mut Connection, entries: &[(DocumentId, String, String)]) -> Result<()> {
     if entries.is_empty() {
         return Ok(());
     }
 
-    let tx = db.transaction()?;
+    let tx = db.savepoint()?;
     {
         // For contentless FTS, we need to delete old entries first, then insert new ones
         // Delete existing FTS entries for these documents
         let delete_query = sql!("DELETE FROM document_fts WHERE rowid IN (SELECT rowid FROM document WHERE id = ?)");
-        let mut delete_stmt = tx.prepare(delete_query)?;
+        let mut delete_stmt = tx.prepare_cached(delete_query)?;
 
         // Insert new FTS entries
         let insert_query = sql!("INSERT INTO document_fts(rowid, title, body) SELECT rowid, ?2, ?3 FROM document WHERE id = ?1");
-        let mut insert_stmt = tx.prepare(insert_query)?;
+        let mut insert_stmt = tx.prepare_cached(insert_query)?;
 
         for (id, title, body) in entries {
             // Delete old entry
@@ -285,128 +638,20 @@ fn populate_fts_index(db: &mut DB, entries: &[(DocumentId, String, String)]) ->
     Ok(())
 }
 
-struct UnresolvedLink {
-    range_start: RangeStart,
-    range_end: RangeEnd,
-    from: DocumentLinkSource,
-    /// unresolved link target, might or might not map to a document_id
-    to: String,
-}
-
-fn extract_links_from_ast(
-    links: &mut Vec<UnresolvedLink>,
-    document_id: &DocumentId,
-    nodes: &Vec<Node>,
-) {
-    for node in nodes {
-        match node {
-            // links
-            Node::WikiLink { target, range, .. } => links.push(UnresolvedLink {
-                from: document_id.clone().into(),
-                to: target.clone(),
-                range_start: range.start,
-                range_end: range.end,
-            }),
-            Node::InlineLink { target, range, .. } => links.push(UnresolvedLink {
-                from: document_id.clone().into(),
-                to: target.clone(),
-                range_start: range.start,
-                range_end: range.end,
-            }),
-            // container nodes
-            Node::Heading { children, .. } => extract_links_from_ast(links, document_id, children),
-            Node::Paragraph { children, .. } => {
-                extract_links_from_ast(links, document_id, children)
-            }
-            Node::BlockQuote { children, .. } => {
-                extract_links_from_ast(links, document_id, children)
-            }
-            Node::List { children, .. } => extract_links_from_ast(links, document_id, children),
-            Node::Item { children, .. } => extract_links_from_ast(links, document_id, children),
-            Node::CodeBlock { children, .. } => {
-                extract_links_from_ast(links, document_id, children)
-            }
-            // ignore the rest
-            _ => {}
-        }
-    }
-}
-
-fn extract_headings_from_ast(
-    headings: &mut Vec<NewDocumentHeading>,
-    document_id: &DocumentId,
-    nodes: &Vec<Node>,
-) {
-    for node in nodes {
-        if let Node::Heading {
-            range,
-            id,
-            classes,
-            attributes,
-            level,
-            content,
-            children,
-        } = node
-        {
-            let metadata = json!({
-                "id": id,
-                "classes": classes,
-                "attributes": attributes
-            });
-            let range_start = range.start;
-            let range_end = range.end;
-            headings.push(NewDocumentHeading {
-                document_id: document_id.clone(),
-                content: content.to_owned(),
-                level: *level,
-                metadata,
-                range_start,
-                range_end,
-            });
-            extract_headings_from_ast(headings, document_id, children);
-        }
-    }
-}
-
-// TODO this should probably be extended to capture that tasks typically have subtasks
-fn extract_tasks_from_ast(
-    tasks: &mut Vec<NewDocumentTask>,
-    document_id: &DocumentId,
-    nodes: &Vec<Node>,
+/// Resolve the due dates of `tasks` from the date expressions in their content.
+/// Relative expressions ("next friday") are relative to `now`, the time the
+/// document was last modified.
+fn resolve_task_due_dates(
+    tasks: &mut [NewDocumentTask],
+    now: Timestamp,
+    tz: &TimeZone,
+    config: &Config,
 ) {
-    for node in nodes {
-        match node {
-            Node::Heading { children, .. } => extract_tasks_from_ast(tasks, document_id, children),
-            Node::List { children, .. } => extract_tasks_from_ast(tasks, document_id, children),
-            Node::Item {
-                range,
-                task_list_marker,
-                children: _,
-                sub_lists,
-            } => {
-                match task_list_marker {
-                    TaskListMarker::UnChecked | TaskListMarker::Checked => {
-                        let checked = match task_list_marker {
-                            TaskListMarker::UnChecked => false,
-                            TaskListMarker::Checked => true,
-                            _ => unreachable!(),
-                        };
-                        let content = String::new();
-
-                        tasks.push(NewDocumentTask {
-                            document_id: document_id.to_owned(),
-                            parent_id: None,
-                            checked,
-                            content,
-                            range_start: range.start,
-                            range_end: range.end,
-                        });
-                    }
-                    TaskListMarker::NoCheckmark => {}
-                }
-                extract_tasks_from_ast(tasks, document_id, sub_lists);
-            }
-            _ => {}
+    for task in tasks {
+        if let Some(found) = find_date(&task.content, now, tz, &config.dates) {
+            task.due = Some(found.timestamp);
+            task.due_start = Some(found.start);
+            task.due_end = Some(found.end);
         }
     }
 }
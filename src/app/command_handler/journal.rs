@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use color_eyre::eyre::eyre;
+use jiff::Timestamp;
+use serde_json::json;
+use zet::config::Config;
+use zet::core::journal::{DEFAULT_JOURNAL_TEMPLATE, Direction, Journal, Period};
+use zet::core::template_engine::{load_template_file, render, template_engine};
+use zet::preamble::*;
+
+use crate::app::commands::{DateExpr, DateRangeExpr, JournalCommand};
+
+pub fn handle_command(root: &Path, config: Config, command: JournalCommand) -> Result<()> {
+    match command {
+        JournalCommand::Ensure { period, range } => handle_ensure(root, &config, period, range),
+    }
+}
+
+/// Print the path of the note of the period containing `date`, or of the
+/// nearest note before or after it
+pub fn handle_navigate(
+    root: &Path,
+    config: &Config,
+    period: Period,
+    date: Option<DateExpr>,
+    direction: Option<Direction>,
+) -> Result<()> {
+    let journal = Journal::new(
+        root,
+        period,
+        config.journal.period(period),
+        config.dates.week_start,
+    );
+    let date = resolve_date(config, date)?;
+    let start = period.start_of(date, &journal.week_start)?;
+
+    let start = match direction {
+        Some(direction) => journal.adjacent(start, direction)?.ok_or_else(|| {
+            let which = match direction {
+                Direction::Previous => "before",
+                Direction::Next => "after",
+            };
+            eyre!(
+                "no {} note {} {}",
+                period,
+                which,
+                journal.title(start).unwrap_or_default()
+            )
+        })?,
+        None => start,
+    };
+    let path = journal.path(start)?;
+    if !path.exists() {
+        return Err(eyre!("no {} note {:?}", period, path));
+    }
+    println!("{}", std::path::absolute(&path)?.display());
+
+    Ok(())
+}
+
+fn resolve_date(config: &Config, date: Option<DateExpr>) -> Result<jiff::civil::Date> {
+    Ok(match date {
+        Some(date) => date.resolve(config)?,
+        None => Timestamp::now(),
+    }
+    .to_zoned(config.timezone()?)
+    .date())
+}
+
+/// Create the notes of the periods overlapping `range`, or of the current
+/// one. Notes that already exist are left untouched.
+fn handle_ensure(
+    root: &Path,
+    config: &Config,
+    period: Period,
+    range: Option<DateRangeExpr>,
+) -> Result<()> {
+    let period_config = config.journal.period(period);
+    let journal = Journal::new(root, period, period_config, config.dates.week_start);
+    let (first, last) = match range {
+        Some(range) => range.resolve(config)?.whole_dates(&config.timezone()?),
+        None => {
+            let today = resolve_date(config, None)?;
+            (today, today)
+        }
+    };
+
+    std::fs::create_dir_all(&journal.directory)?;
+
+    let template_str = match period_config.template.as_deref() {
+        Some(template) => load_template_file(root, template)?,
+        None => DEFAULT_JOURNAL_TEMPLATE.to_owned(),
+    };
+
+    for start in period.starts(first, last, &journal.week_start)? {
+        let title = journal.title(start)?;
+        let id = journal.id(start)?;
+        let path = journal.path(start)?;
+
+        if path.exists() {
+            log::debug!("{} note {:?} already exists", period, path);
+            continue;
+        }
+
+        let date = start.strftime("%Y-%m-%d").to_string();
+        let extra: HashMap<String, serde_json::Value> = HashMap::from([
+            ("period".to_owned(), json!(period.to_string())),
+            (
+                "end".to_owned(),
+                json!(period.end_of(start).strftime("%Y-%m-%d").to_string()),
+            ),
+        ]);
+        let mut tera = template_engine(root)?;
+        journal.register_functions(&mut tera, start);
+        let rendered = render(tera, &template_str, &id, &title, &date, "", &extra)?;
+
+        std::fs::write(&path, rendered)?;
+        println!("{}", std::path::absolute(&path)?.display());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::eyre;
+use serde::Serialize;
+use zet::config::Config;
+use zet::core::flavor::DocumentSettings;
+use zet::core::lint::{LintIssue, LintRule};
+use zet::core::lock::Locks;
+use zet::core::read::read_document_logged;
+use zet::preamble::*;
+
+use crate::app::commands::ReportFormat;
+
+/// Fixes can uncover other issues, e.g. a closed code fence reveals the urls
+/// after it, so they are applied until the document is clean
+const MAX_FIX_ROUNDS: usize = 4;
+
+#[derive(Debug, Serialize)]
+struct LintReport {
+    path: PathBuf,
+    rule: LintRule,
+    /// 1-based line of the issue
+    line: usize,
+    /// 1-based column of the issue, in characters
+    column: usize,
+    message: String,
+}
+
+pub fn handle_command(
+    root: &Path,
+    config: &Config,
+    paths: Vec<PathBuf>,
+    fix: bool,
+    output_format: ReportFormat,
+    pretty: bool,
+) -> Result<()> {
+    let paths = match paths.is_empty() {
+        true => zet::core::workspace_paths(root)?,
+        false => paths,
+    };
+
+    let tz = config.timezone()?;
+    #[cfg(feature = "wasm-plugins")]
+    let plugins = zet::core::plugin::PluginHost::load(root)?;
+    let locks = Locks::load(root, config.front_matter_format)?;
+    let mut reports = Vec::new();
+    for path in paths {
+        let file = read_document_logged(&path, &config.index)?;
+        let Some(mut document) = file.content else {
+            continue;
+        };
+        let mut issues = lint(&document, config)?;
+
+        let fixable = issues.iter().any(|issue| issue.fix.is_some());
+        if fix && fixable && locks.is_locked(&path, &document) {
+            log::warn!("not fixing the locked document {:?}", path);
+        } else if fix && fixable && !file.issues.is_empty() {
+            // a truncated or lossily decoded document would be written back
+            // with less than it has
+            log::warn!("not fixing {:?}, it is not read as it is", path);
+        } else if fix && fixable {
+            for _ in 0..MAX_FIX_ROUNDS {
+                if issues.iter().all(|issue| issue.fix.is_none()) {
+                    break;
+                }
+                document = zet::core::lint::fix(&document, &issues);
+                issues = lint(&document, config)?;
+            }
+            std::fs::write(&path, &document)?;
+            log::info!("fixed {:?}", path);
+        }
+
+        issues.extend(zet::core::lint::lint_filename(
+            root,
+            &path,
+            &document,
+            config.front_matter_format,
+            &config.lint,
+            &tz,
+        )?);
+        #[cfg(feature = "wasm-plugins")]
+        issues.extend(zet::core::plugin::lint_plugins(
+            &plugins,
+            root,
+            &path,
+            &document,
+            config.front_matter_format,
+        )?);
+        reports.extend(
+            issues
+                .into_iter()
+                .map(|issue| report(&path, &document, issue)),
+        );
+    }
+
+    let mut writer = std::io::BufWriter::new(std::io::stdout());
+    match output_format {
+        ReportFormat::Json => {
+            if pretty {
+                serde_json::to_writer_pretty(&mut writer, &reports)?;
+            } else {
+                serde_json::to_writer(&mut writer, &reports)?;
+            }
+        }
+        ReportFormat::Text => {
+            for report in &reports {
+                writeln!(
+                    writer,
+                    "{}:{}:{}: {}: {}",
+                    report.path.display(),
+                    report.line,
+                    report.column,
+                    report.rule,
+                    report.message
+                )?;
+            }
+        }
+    }
+    writer.flush()?;
+
+    if !reports.is_empty() {
+        return Err(eyre!("found {} lint issues", reports.len()));
+    }
+    Ok(())
+}
+
+fn lint(document: &str, config: &Config) -> Result<Vec<LintIssue>> {
+    let settings = DocumentSettings::of(config, document)?;
+    zet::core::lint::lint(
+        document,
+        config.front_matter_format,
+        &settings.parser,
+        &config.lint,
+    )
+}
+
+fn report(path: &Path, document: &str, issue: LintIssue) -> LintReport {
+    let before = &document[..issue.range.start];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    LintReport {
+        path: path.to_owned(),
+        rule: issue.rule,
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+        message: issue.message,
+    }
+}
@@ -0,0 +1,84 @@
+use std::io::Write;
+use std::path::Path;
+
+use zet::core::date_parser::TimeRange;
+use zet::core::db::{DB, DbList, Page};
+use zet::core::query::{DocumentQuery, SortByOption, SortOrder};
+use zet::core::types::stub::DocumentStub;
+use zet::preamble::*;
+
+use crate::app::commands::ReportFormat;
+
+pub fn handle_command(
+    root: &Path,
+    stubs: bool,
+    created: Option<TimeRange>,
+    modified: Option<TimeRange>,
+    page: Page,
+    output_format: ReportFormat,
+    pretty: bool,
+) -> Result<()> {
+    let db_path = zet::core::collection_db_file(root);
+    let db = DB::open(db_path)?;
+
+    let mut writer = std::io::BufWriter::new(std::io::stdout());
+    if stubs {
+        let stubs = page.apply(DocumentStub::list(&db)?);
+        match output_format {
+            ReportFormat::Json => write_json(&mut writer, &stubs, pretty)?,
+            ReportFormat::Text => {
+                for stub in stubs {
+                    let referenced_by: Vec<&str> =
+                        stub.referenced_by.iter().map(|id| id.0.as_str()).collect();
+                    writeln!(
+                        writer,
+                        "{}\t{}\t{}",
+                        stub.id.0,
+                        stub.title,
+                        referenced_by.join(",")
+                    )?;
+                }
+            }
+        }
+    } else {
+        let mut query = DocumentQuery::new()
+            .order_by(SortByOption::Id, SortOrder::Ascending)
+            .page(page);
+        if let Some(created) = created {
+            query = query.created(created);
+        }
+        if let Some(modified) = modified {
+            query = query.modified(modified);
+        }
+        let documents = query.execute_summaries(&db)?;
+        match output_format {
+            ReportFormat::Json => write_json(&mut writer, &documents, pretty)?,
+            ReportFormat::Text => {
+                for document in documents {
+                    writeln!(
+                        writer,
+                        "{}\t{}\t{}",
+                        document.id.0,
+                        document.title,
+                        document.path.0.display()
+                    )?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn write_json<W: Write, T: serde::Serialize>(
+    writer: &mut W,
+    value: &T,
+    pretty: bool,
+) -> Result<()> {
+    if pretty {
+        serde_json::to_writer_pretty(writer, value)?;
+    } else {
+        serde_json::to_writer(writer, value)?;
+    }
+    Ok(())
+}
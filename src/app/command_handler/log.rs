@@ -0,0 +1,44 @@
+use std::io::Write;
+use std::path::Path;
+
+use jiff::Timestamp;
+use zet::core::db::{DB, DbQuery};
+use zet::core::types::event::Event;
+use zet::preamble::*;
+
+use crate::app::commands::ReportFormat;
+
+pub fn handle_command(
+    root: &Path,
+    since: Option<Timestamp>,
+    output_format: ReportFormat,
+    pretty: bool,
+) -> Result<()> {
+    let db = DB::open(zet::core::collection_db_file(root))?;
+    let events = Event::list(&db, since.unwrap_or(Timestamp::MIN))?;
+
+    let mut writer = std::io::BufWriter::new(std::io::stdout());
+    match output_format {
+        ReportFormat::Json if pretty => serde_json::to_writer_pretty(&mut writer, &events)?,
+        ReportFormat::Json => serde_json::to_writer(&mut writer, &events)?,
+        ReportFormat::Text => {
+            for event in &events {
+                let detail = match event.data.get("to") {
+                    Some(to) => to.as_str().unwrap_or_default(),
+                    None => event.data["path"].as_str().unwrap_or_default(),
+                };
+                writeln!(
+                    writer,
+                    "{}  {:<16}  {}  {}",
+                    event.timestamp,
+                    event.kind.as_str(),
+                    event.document_id.0,
+                    detail
+                )?;
+            }
+        }
+    }
+    writer.flush()?;
+
+    Ok(())
+}
@@ -1,7 +1,9 @@
 use color_eyre::eyre::eyre;
-use std::collections::HashMap;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
 use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
 use tower_lsp_server::jsonrpc::{Error as LspError, Result};
 use tower_lsp_server::ls_types::request::{
     GotoDeclarationParams, GotoDeclarationResponse, GotoImplementationParams,
@@ -9,21 +11,43 @@ use tower_lsp_server::ls_types::request::{
 };
 use tower_lsp_server::ls_types::*;
 use tower_lsp_server::{Client, LanguageServer, LspService, Server};
+use zet::config::Config;
+use zet::core::db::{DB, DbList};
+use zet::core::flavor::DocumentSettings;
+use zet::core::graph::LocalGraph;
+use zet::core::lock::Locks;
+use zet::core::overlay::Overlay;
+use zet::core::parser::DocumentParser;
 use zet::core::template_engine::{
     render_template, resolve_group_from_cwd, resolve_template_string,
 };
+use zet::core::types::document::{Document, DocumentId, DocumentSummary};
+use zet::core::types::stub::DocumentStub;
+use zet::core::uri::{path_to_uri, uri_to_path};
 use zet::preamble::*;
 
-pub fn handle_command(root: Option<PathBuf>) -> Result<()> {
+use super::import::ImportGroup;
+
+/// Serve the collection at `root`, if any, and the collections of the
+/// workspace folders of the editor
+pub fn handle_command(root: Option<PathBuf>) -> zet::result::Result<()> {
     tokio::runtime::Builder::new_multi_thread()
         .enable_all()
-        .build()
-        .unwrap()
+        .build()?
         .block_on(async {
             let stdin = tokio::io::stdin();
             let stdout = tokio::io::stdout();
 
-            let (service, socket) = LspService::new(|client| Backend { client });
+            let (service, socket) = LspService::build(|client| Backend {
+                client,
+                collections: RwLock::new(
+                    root.into_iter()
+                        .map(|root| (root, Overlay::default()))
+                        .collect(),
+                ),
+            })
+            .custom_method("zet/localGraph", Backend::local_graph)
+            .finish();
             Server::new(stdin, stdout, socket).serve(service).await;
         });
     Ok(())
@@ -32,14 +56,618 @@ pub fn handle_command(root: Option<PathBuf>) -> Result<()> {
 #[derive(Debug)]
 struct Backend {
     client: Client,
+    /// the collections served by their root, with the buffers the editor has
+    /// open in them, shadowing their index
+    collections: RwLock<BTreeMap<PathBuf, Overlay>>,
+}
+
+/// Maximum number of hops returned by `zet/localGraph`
+const LOCAL_GRAPH_MAX_DEPTH: usize = 2;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LocalGraphParams {
+    text_document: TextDocumentIdentifier,
+    /// number of hops from the current document, defaults to 1
+    depth: Option<usize>,
+}
+
+/// Convert a byte offset in `document` into a position, in utf-16 code units.
+/// Offsets past the end or within a character, e.g. from an index older than
+/// the document, are moved back to the previous character.
+fn offset_to_position(document: &str, offset: usize) -> Position {
+    let before = &document[..document.floor_char_boundary(offset)];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Position::new(
+        before.matches('\n').count() as u32,
+        before[line_start..].encode_utf16().count() as u32,
+    )
+}
+
+/// Convert a position, in utf-16 code units, into a byte offset in `document`
+fn position_to_offset(document: &str, position: Position) -> usize {
+    let line_start = match position.line {
+        0 => 0,
+        line => document
+            .match_indices('\n')
+            .nth(line as usize - 1)
+            .map_or(document.len(), |(i, _)| i + 1),
+    };
+    let line = &document[line_start..];
+    let line = &line[..line.find('\n').unwrap_or(line.len())];
+    let mut units = 0;
+    for (i, c) in line.char_indices() {
+        if units >= position.character as usize {
+            return line_start + i;
+        }
+        units += c.len_utf16();
+    }
+    line_start + line.len()
+}
+
+fn offset_range(document: &str, range: &std::ops::Range<usize>) -> Range {
+    Range::new(
+        offset_to_position(document, range.start),
+        offset_to_position(document, range.end),
+    )
+}
+
+/// The root of the collection containing `dir`, `dir` itself or one of its
+/// ancestors
+fn enclosing_collection(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|dir| zet::core::is_collection(dir).unwrap_or(false))
+        .map(Path::to_owned)
+}
+
+/// The deepest of `roots` containing `path`
+fn deepest_root<'a>(roots: impl Iterator<Item = &'a PathBuf>, path: &Path) -> Option<PathBuf> {
+    roots
+        .filter(|root| zet::core::relative_path(root, path).is_some())
+        .max_by_key(|root| root.components().count())
+        .cloned()
+}
+
+fn internal_error(e: impl std::fmt::Display) -> LspError {
+    let mut error = LspError::internal_error();
+    error.message = e.to_string().into();
+    error
+}
+
+impl Backend {
+    fn open_db(&self, root: &Path) -> zet::result::Result<DB> {
+        DB::open(zet::core::collection_db_file(root))
+    }
+
+    /// The root of the collection of the document at `path`, the deepest of
+    /// the collections served containing it. The collection of a document
+    /// outside of them is served from then on.
+    fn root_of(&self, path: &Path) -> Option<PathBuf> {
+        if let Some(root) = deepest_root(self.collections.read().unwrap().keys(), path) {
+            return Some(root);
+        }
+        let root = enclosing_collection(path.parent()?)?;
+        log::info!("serving the collection at {:?}", root);
+        self.collections
+            .write()
+            .unwrap()
+            .entry(root.clone())
+            .or_default();
+        Some(root)
+    }
+
+    /// Serve the collection of the workspace folder `uri`
+    fn add_folder(&self, uri: &Uri) {
+        match uri_to_path(uri).and_then(|dir| enclosing_collection(&dir)) {
+            Some(root) => {
+                self.collections.write().unwrap().entry(root).or_default();
+            }
+            None => log::info!("the workspace folder {:?} is not in a collection", uri),
+        }
+    }
+
+    /// Stop serving the collection of the workspace folder `uri`
+    fn remove_folder(&self, uri: &Uri) {
+        if let Some(root) = uri_to_path(uri).and_then(|dir| enclosing_collection(&dir)) {
+            self.collections.write().unwrap().remove(&root);
+        }
+    }
+
+    /// Run `f` with the buffers of the collection at `root`
+    fn with_overlay<T>(&self, root: &Path, f: impl FnOnce(&Overlay) -> T) -> T {
+        match self.collections.read().unwrap().get(root) {
+            Some(overlay) => f(overlay),
+            None => f(&Overlay::default()),
+        }
+    }
+
+    fn document_at(&self, root: &Path, db: &mut DB, path: &Path) -> Option<Document> {
+        self.with_overlay(root, |overlay| overlay.document(db, path))
+    }
+
+    /// The content of the document at `path`, from its buffer if the editor
+    /// has it open
+    fn document_text(&self, path: &Path) -> zet::result::Result<String> {
+        let buffer = self
+            .collections
+            .read()
+            .unwrap()
+            .values()
+            .find_map(|overlay| overlay.get(path).map(|buffer| buffer.text.clone()));
+        match buffer {
+            Some(text) => Ok(text),
+            None => Ok(std::fs::read_to_string(path)?),
+        }
+    }
+
+    /// Whether the document at `path`, whose text is `document`, is locked,
+    /// no edits of it being offered then
+    fn is_locked(&self, path: &Path, document: &str) -> zet::result::Result<bool> {
+        let Some(root) = self.root_of(path) else {
+            return Ok(false);
+        };
+        let config = Config::resolve(&root)?;
+        Ok(Locks::load(&root, config.front_matter_format)?.is_locked(path, document))
+    }
+
+    /// Parse the new content of the buffer of `uri`
+    fn update_buffer(&self, uri: &Uri, text: String) {
+        let Some(path) = uri_to_path(uri) else {
+            return;
+        };
+        let Some(root) = self.root_of(&path) else {
+            log::warn!("{:?} is not in a collection", path);
+            return;
+        };
+        let result = Config::resolve(&root).and_then(|config| {
+            self.collections
+                .write()
+                .unwrap()
+                .entry(root.clone())
+                .or_default()
+                .update(&config, &root, &path, text)
+        });
+        if let Err(e) = result {
+            log::warn!("could not parse the buffer of {:?}: {}", path, e);
+        }
+    }
+
+    /// `zet/localGraph`: the neighborhood of the given document as nodes and edges
+    async fn local_graph(&self, params: LocalGraphParams) -> Result<Option<LocalGraph>> {
+        let Some(path) = uri_to_path(&params.text_document.uri) else {
+            return Err(LspError::invalid_params("expected a file:// uri"));
+        };
+        let depth = params.depth.unwrap_or(1).clamp(1, LOCAL_GRAPH_MAX_DEPTH);
+        let Some(root) = self.root_of(&path) else {
+            return Ok(None);
+        };
+
+        let mut db = self.open_db(&root).map_err(internal_error)?;
+        let Some(document) = self.document_at(&root, &mut db, &path) else {
+            // the document has not been indexed (yet)
+            return Ok(None);
+        };
+
+        self.with_overlay(&root, |overlay| {
+            overlay.local_graph(&db, &document.id, depth)
+        })
+        .map(Some)
+        .map_err(internal_error)
+    }
+
+    /// The snippets of the collection, expanded in the document at `uri`
+    fn snippet_completions(&self, uri: &Uri) -> zet::result::Result<Vec<CompletionItem>> {
+        let Some(path) = uri_to_path(uri) else {
+            return Ok(Vec::new());
+        };
+        let Some(root) = self.root_of(&path) else {
+            return Ok(Vec::new());
+        };
+        let snippets = zet::core::snippets::load_snippets(&root)?;
+        if snippets.is_empty() {
+            return Ok(Vec::new());
+        }
+        let config = Config::resolve(&root)?;
+        let now = jiff::Timestamp::now().to_zoned(config.timezone()?);
+        let mut db = self.open_db(&root)?;
+        let document = self.document_at(&root, &mut db, &path);
+        let (id, title) = document
+            .as_ref()
+            .map_or(("", ""), |d| (d.id.0.as_str(), d.title.as_str()));
+
+        let mut items = Vec::with_capacity(snippets.len());
+        for snippet in snippets {
+            let body = match snippet.expand(id, title, &now, &HashMap::new()) {
+                Ok(body) => body,
+                Err(e) => {
+                    log::warn!("could not expand snippet {:?}: {}", snippet.trigger, e);
+                    continue;
+                }
+            };
+            items.push(CompletionItem {
+                label: snippet.trigger,
+                kind: Some(CompletionItemKind::SNIPPET),
+                detail: snippet.description,
+                insert_text: Some(body),
+                insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
+                ..Default::default()
+            });
+        }
+        Ok(items)
+    }
+
+    /// The people of the collection, when a mention is being written at
+    /// `position` of the document at `uri`
+    fn mention_completions(
+        &self,
+        uri: &Uri,
+        position: Position,
+    ) -> zet::result::Result<Option<Vec<CompletionItem>>> {
+        let Some(path) = uri_to_path(uri) else {
+            return Ok(None);
+        };
+        let Some(root) = self.root_of(&path) else {
+            return Ok(None);
+        };
+        let document = self.document_text(&path)?;
+        let offset = position_to_offset(&document, position);
+        let line_start = document[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let Some(start) = zet::core::mentions::partial_mention(&document[line_start..offset])
+        else {
+            return Ok(None);
+        };
+        let range = offset_range(&document, &(line_start + start..offset));
+
+        let config = Config::resolve(&root)?;
+        let db = self.open_db(&root)?;
+        let people = zet::core::mentions::people(&root, &config, &db)?;
+        Ok(Some(
+            people
+                .into_iter()
+                .map(|person| CompletionItem {
+                    label: person.title.clone(),
+                    kind: Some(CompletionItemKind::REFERENCE),
+                    detail: Some(format!("@{}", person.handle)),
+                    filter_text: Some(format!("@{}", person.title)),
+                    text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                        range,
+                        new_text: format!("@{}", person.handle),
+                    })),
+                    ..Default::default()
+                })
+                .collect(),
+        ))
+    }
+
+    /// The documents and stubs of the collection, when the target of a
+    /// wikilink is being written at `position` of the document at `uri`
+    fn link_completions(
+        &self,
+        uri: &Uri,
+        position: Position,
+    ) -> zet::result::Result<Option<Vec<CompletionItem>>> {
+        let Some(path) = uri_to_path(uri) else {
+            return Ok(None);
+        };
+        let Some(root) = self.root_of(&path) else {
+            return Ok(None);
+        };
+        let document = self.document_text(&path)?;
+        let offset = position_to_offset(&document, position);
+        let line_start = document[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let Some(start) = zet::core::partial_wiki_link(&document[line_start..offset]) else {
+            return Ok(None);
+        };
+        let range = offset_range(&document, &(line_start + start..offset));
+
+        let db = self.open_db(&root)?;
+        let item = |id: DocumentId, title: String, kind, detail| CompletionItem {
+            filter_text: Some(format!("{} {}", title, id.0)),
+            label: title,
+            kind: Some(kind),
+            detail: Some(detail),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range,
+                new_text: id.0,
+            })),
+            ..Default::default()
+        };
+        let documents = DocumentSummary::list(&db)?.into_iter().map(|document| {
+            let detail = document.id.0.clone();
+            item(
+                document.id,
+                document.title,
+                CompletionItemKind::FILE,
+                detail,
+            )
+        });
+        // the notes linked to that do not exist yet
+        let stubs = DocumentStub::list(&db)?.into_iter().map(|stub| {
+            let detail = format!("stub, linked from {} notes", stub.referenced_by.len());
+            item(stub.id, stub.title, CompletionItemKind::REFERENCE, detail)
+        });
+        Ok(Some(documents.chain(stubs).collect()))
+    }
+
+    /// Quick fixes of the lint issues within `range`, and one fixing all
+    /// issues of the document
+    fn lint_fixes(&self, uri: &Uri, range: Range) -> zet::result::Result<CodeActionResponse> {
+        let Some(path) = uri_to_path(uri) else {
+            return Ok(Vec::new());
+        };
+        let Some(root) = self.root_of(&path) else {
+            return Ok(Vec::new());
+        };
+        let document = self.document_text(&path)?;
+        if self.is_locked(&path, &document)? {
+            return Ok(Vec::new());
+        }
+        let config = Config::resolve(&root)?;
+        let settings = DocumentSettings::of(&config, &document)?;
+        let issues = zet::core::lint::lint(
+            &document,
+            config.front_matter_format,
+            &settings.parser,
+            &config.lint,
+        )?;
+
+        let action = |title: String, kind: CodeActionKind, edits: Vec<TextEdit>| {
+            CodeActionOrCommand::CodeAction(CodeAction {
+                title,
+                kind: Some(kind),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(HashMap::from([(uri.clone(), edits)])),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+        };
+        let edit = |fix: &zet::core::lint::TextEdit| TextEdit {
+            range: offset_range(&document, &fix.range),
+            new_text: fix.replacement.clone(),
+        };
+
+        let mut actions: CodeActionResponse = issues
+            .iter()
+            .filter(|issue| {
+                let issue_range = offset_range(&document, &issue.range);
+                issue_range.start <= range.end && range.start <= issue_range.end
+            })
+            .filter_map(|issue| {
+                Some(action(
+                    format!("Fix {}: {}", issue.rule, issue.message),
+                    CodeActionKind::QUICKFIX,
+                    vec![edit(issue.fix.as_ref()?)],
+                ))
+            })
+            .collect();
+        if issues.iter().any(|issue| issue.fix.is_some()) {
+            // the fixed document replaces the whole document, as fixes of
+            // overlapping issues are skipped
+            let fixed = zet::core::lint::fix(&document, &issues);
+            actions.push(action(
+                "Fix all lint issues".to_owned(),
+                CodeActionKind::SOURCE_FIX_ALL,
+                vec![TextEdit {
+                    range: offset_range(&document, &(0..document.len())),
+                    new_text: fixed,
+                }],
+            ));
+        }
+        Ok(actions)
+    }
+
+    /// The action extracting the selection `range` of the document into a new
+    /// note next to it, see `[extract]`
+    fn extract_action(
+        &self,
+        uri: &Uri,
+        range: Range,
+    ) -> zet::result::Result<Option<CodeActionOrCommand>> {
+        if range.start == range.end {
+            return Ok(None);
+        }
+        let Some(path) = uri_to_path(uri) else {
+            return Ok(None);
+        };
+        let Some(root) = self.root_of(&path) else {
+            return Ok(None);
+        };
+        let mut db = self.open_db(&root)?;
+        let Some(source) = self.document_at(&root, &mut db, &path) else {
+            return Ok(None);
+        };
+        let document = self.document_text(&path)?;
+        if self.is_locked(&path, &document)? {
+            return Ok(None);
+        }
+        let config = Config::resolve(&root)?;
+        let settings = DocumentSettings::of(&config, &document)?;
+        let parser = DocumentParser::from_config(&settings.parser);
+        let selection =
+            position_to_offset(&document, range.start)..position_to_offset(&document, range.end);
+        let Some(extract) = config
+            .extract
+            .extract(&document, selection, &source.id, &parser)?
+        else {
+            return Ok(None);
+        };
+
+        let directory = path.parent().unwrap_or(&root);
+        let group = ImportGroup::of_directory(&root, &config, directory)?;
+        let now = jiff::Timestamp::now().to_zoned(config.timezone()?);
+        let (id, new_path) = group.new_note(&extract.title, &now)?;
+        let note = group.render_note(&id, &extract.title, &now, &extract.content, &[])?;
+        let Some(new_uri) = path_to_uri(&new_path) else {
+            return Ok(None);
+        };
+        let mut link = config.extract.link(&DocumentId(id), &extract.title);
+        if document[extract.range.clone()].ends_with('\n') {
+            link.push('\n');
+        }
+
+        let edit = |uri: Uri, range: Range, new_text: String| {
+            DocumentChangeOperation::Edit(TextDocumentEdit {
+                text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+                edits: vec![OneOf::Left(TextEdit { range, new_text })],
+            })
+        };
+        let changes = vec![
+            DocumentChangeOperation::Op(ResourceOp::Create(CreateFile {
+                uri: new_uri.clone(),
+                options: None,
+                annotation_id: None,
+            })),
+            edit(new_uri, Range::default(), note),
+            edit(uri.clone(), offset_range(&document, &extract.range), link),
+        ];
+        Ok(Some(CodeActionOrCommand::CodeAction(CodeAction {
+            title: format!("Extract to a new note \"{}\"", extract.title),
+            kind: Some(CodeActionKind::REFACTOR_EXTRACT),
+            edit: Some(WorkspaceEdit {
+                document_changes: Some(DocumentChanges::Operations(changes)),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })))
+    }
+
+    /// The lint issues of the document, including the diagnostics of the
+    /// plugins
+    fn lint_diagnostics(&self, uri: &Uri) -> zet::result::Result<Vec<Diagnostic>> {
+        let Some(path) = uri_to_path(uri) else {
+            return Ok(Vec::new());
+        };
+        let Some(root) = self.root_of(&path) else {
+            return Ok(Vec::new());
+        };
+        let document = self.document_text(&path)?;
+        let config = Config::resolve(&root)?;
+        let settings = DocumentSettings::of(&config, &document)?;
+        #[allow(unused_mut)]
+        let mut issues = zet::core::lint::lint(
+            &document,
+            config.front_matter_format,
+            &settings.parser,
+            &config.lint,
+        )?;
+        #[cfg(feature = "wasm-plugins")]
+        issues.extend(zet::core::plugin::lint_plugins(
+            &zet::core::plugin::PluginHost::load(&root)?,
+            &root,
+            &path,
+            &document,
+            config.front_matter_format,
+        )?);
+
+        Ok(issues
+            .into_iter()
+            .map(|issue| Diagnostic {
+                range: offset_range(&document, &issue.range),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String(issue.rule.to_string())),
+                source: Some(APP_NAME.to_owned()),
+                message: issue.message,
+                ..Default::default()
+            })
+            .collect())
+    }
+
+    /// Format the tables within `range` of the document
+    fn format_tables(&self, uri: &Uri, range: Range) -> zet::result::Result<Vec<TextEdit>> {
+        let Some(path) = uri_to_path(uri) else {
+            return Ok(Vec::new());
+        };
+        let document = self.document_text(&path)?;
+        if self.is_locked(&path, &document)? {
+            return Ok(Vec::new());
+        }
+        let range =
+            position_to_offset(&document, range.start)..position_to_offset(&document, range.end);
+        let edits = zet::core::format::format_tables_in_range(&document, range)?;
+        Ok(edits
+            .iter()
+            .map(|edit| TextEdit {
+                range: offset_range(&document, &edit.range),
+                new_text: edit.replacement.clone(),
+            })
+            .collect())
+    }
+
+    /// The headings of the document as an outline
+    fn heading_symbols(&self, uri: &Uri) -> zet::result::Result<Vec<DocumentSymbol>> {
+        let Some(path) = uri_to_path(uri) else {
+            return Ok(Vec::new());
+        };
+        let document = self.document_text(&path)?;
+        let offset = zet::core::frontmatter::body_offset(&document);
+        let Some(root) = self.root_of(&path) else {
+            return Ok(Vec::new());
+        };
+        let mut db = self.open_db(&root)?;
+        let Some(id) = self.document_at(&root, &mut db, &path).map(|d| d.id) else {
+            return Ok(Vec::new());
+        };
+        let headings = self.with_overlay(&root, |overlay| overlay.headings(&db, &id))?;
+
+        #[allow(deprecated)]
+        Ok(headings
+            .into_iter()
+            // the headings of an index older than the document on disk
+            .filter(|heading| offset + heading.range_end <= document.len())
+            .map(|heading| {
+                let start = offset + heading.range_start;
+                let end = (offset + heading.range_end).max(start);
+                let range = offset_range(&document, &(start..end));
+                DocumentSymbol {
+                    name: heading.content,
+                    detail: Some(format!("h{}", heading.level)),
+                    kind: SymbolKind::STRING,
+                    tags: None,
+                    deprecated: None,
+                    range,
+                    selection_range: range,
+                    children: None,
+                }
+            })
+            .collect())
+    }
 }
 
 impl LanguageServer for Backend {
-    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        match params.workspace_folders {
+            Some(folders) => folders.iter().for_each(|f| self.add_folder(&f.uri)),
+            #[allow(deprecated)]
+            None => params.root_uri.iter().for_each(|uri| self.add_folder(uri)),
+        }
+
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
+                text_document_sync: Some(TextDocumentSyncCapability::Kind(
+                    TextDocumentSyncKind::FULL,
+                )),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
-                completion_provider: Some(CompletionOptions::default()),
+                completion_provider: Some(CompletionOptions {
+                    trigger_characters: Some(vec!["@".to_owned(), "[".to_owned()]),
+                    ..Default::default()
+                }),
+                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+                document_symbol_provider: Some(OneOf::Left(true)),
+                document_range_formatting_provider: Some(OneOf::Left(true)),
+                diagnostic_provider: Some(DiagnosticServerCapabilities::Options(
+                    DiagnosticOptions {
+                        identifier: Some(APP_NAME.to_owned()),
+                        ..Default::default()
+                    },
+                )),
+                workspace: Some(WorkspaceServerCapabilities {
+                    workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+                        supported: Some(true),
+                        change_notifications: Some(OneOf::Left(true)),
+                    }),
+                    file_operations: None,
+                }),
                 ..Default::default()
             },
             ..Default::default()
@@ -56,11 +684,23 @@ impl LanguageServer for Backend {
         Ok(())
     }
 
-    async fn completion(&self, _: CompletionParams) -> Result<Option<CompletionResponse>> {
-        Ok(Some(CompletionResponse::Array(vec![
-            CompletionItem::new_simple("Hello".to_string(), "Some detail".to_string()),
-            CompletionItem::new_simple("Bye".to_string(), "More detail".to_string()),
-        ])))
+    async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
+        let TextDocumentPositionParams {
+            text_document,
+            position,
+        } = &params.text_document_position;
+        let items = match self.mention_completions(&text_document.uri, *position) {
+            Ok(Some(people)) => Ok(people),
+            Ok(None) => match self.link_completions(&text_document.uri, *position) {
+                Ok(Some(links)) => Ok(links),
+                Ok(None) => self.snippet_completions(&text_document.uri),
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        };
+        items
+            .map(|items| Some(CompletionResponse::Array(items)))
+            .map_err(internal_error)
     }
 
     async fn hover(&self, _: HoverParams) -> Result<Option<Hover>> {
@@ -71,13 +711,14 @@ impl LanguageServer for Backend {
     }
 
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
-        let _ = params;
-        log::warn!("got a `textDocument/didOpen` notification, but it is not implemented");
+        self.update_buffer(&params.text_document.uri, params.text_document.text);
     }
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
-        let _ = params;
-        log::warn!("got a `textDocument/didChange` notification, but it is not implemented");
+        // documents are synchronized in full, the last change is the content
+        if let Some(change) = params.content_changes.into_iter().next_back() {
+            self.update_buffer(&params.text_document.uri, change.text);
+        }
     }
 
     async fn will_save(&self, params: WillSaveTextDocumentParams) {
@@ -100,8 +741,11 @@ impl LanguageServer for Backend {
     }
 
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
-        let _ = params;
-        log::warn!("got a `textDocument/didClose` notification, but it is not implemented");
+        if let Some(path) = uri_to_path(&params.text_document.uri) {
+            for overlay in self.collections.write().unwrap().values_mut() {
+                overlay.close(&path);
+            }
+        }
     }
 
     // Notebook Document Synchronization
@@ -276,9 +920,9 @@ impl LanguageServer for Backend {
         &self,
         params: DocumentSymbolParams,
     ) -> Result<Option<DocumentSymbolResponse>> {
-        let _ = params;
-        log::error!("got a `textDocument/documentSymbol` request, but it is not implemented");
-        Err(LspError::method_not_found())
+        self.heading_symbols(&params.text_document.uri)
+            .map(|symbols| Some(DocumentSymbolResponse::Nested(symbols)))
+            .map_err(internal_error)
     }
 
     async fn semantic_tokens_full(
@@ -344,9 +988,18 @@ impl LanguageServer for Backend {
         &self,
         params: DocumentDiagnosticParams,
     ) -> Result<DocumentDiagnosticReportResult> {
-        let _ = params;
-        log::error!("got a `textDocument/diagnostic` request, but it is not implemented");
-        Err(LspError::method_not_found())
+        let items = self
+            .lint_diagnostics(&params.text_document.uri)
+            .map_err(internal_error)?;
+        Ok(DocumentDiagnosticReportResult::Report(
+            DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+                related_documents: None,
+                full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                    result_id: None,
+                    items,
+                },
+            }),
+        ))
     }
 
     async fn workspace_diagnostic(
@@ -365,9 +1018,13 @@ impl LanguageServer for Backend {
     }
 
     async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
-        let _ = params;
-        log::error!("got a `textDocument/codeAction` request, but it is not implemented");
-        Err(LspError::method_not_found())
+        let uri = &params.text_document.uri;
+        let mut actions = self.lint_fixes(uri, params.range).map_err(internal_error)?;
+        match self.extract_action(uri, params.range) {
+            Ok(action) => actions.extend(action),
+            Err(e) => log::warn!("could not extract the selection of {:?}: {}", uri, e),
+        }
+        Ok(Some(actions))
     }
 
     async fn code_action_resolve(&self, params: CodeAction) -> Result<CodeAction> {
@@ -401,9 +1058,9 @@ impl LanguageServer for Backend {
         &self,
         params: DocumentRangeFormattingParams,
     ) -> Result<Option<Vec<TextEdit>>> {
-        let _ = params;
-        log::error!("got a `textDocument/rangeFormatting` request, but it is not implemented");
-        Err(LspError::method_not_found())
+        self.format_tables(&params.text_document.uri, params.range)
+            .map(Some)
+            .map_err(internal_error)
     }
 
     async fn on_type_formatting(
@@ -464,10 +1121,12 @@ impl LanguageServer for Backend {
     }
 
     async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) {
-        let _ = params;
-        log::warn!(
-            "got a `workspace/didChangeWorkspaceFolders` notification, but it is not implemented"
-        );
+        for folder in &params.event.removed {
+            self.remove_folder(&folder.uri);
+        }
+        for folder in &params.event.added {
+            self.add_folder(&folder.uri);
+        }
     }
 
     async fn will_create_files(&self, params: CreateFilesParams) -> Result<Option<WorkspaceEdit>> {
@@ -516,3 +1175,57 @@ impl LanguageServer for Backend {
         Err(LspError::method_not_found())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_routing() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let work = temp.path().join("work");
+        let nested = work.join("nested");
+        std::fs::create_dir_all(nested.join(".zet")).unwrap();
+        std::fs::create_dir_all(work.join(".zet")).unwrap();
+        std::fs::create_dir_all(temp.path().join("elsewhere")).unwrap();
+
+        assert_eq!(
+            enclosing_collection(&work.join("notes")),
+            Some(work.clone())
+        );
+        assert_eq!(enclosing_collection(&nested), Some(nested.clone()));
+        assert_eq!(enclosing_collection(&temp.path().join("elsewhere")), None);
+
+        let roots = [work.clone(), nested.clone()];
+        assert_eq!(
+            deepest_root(roots.iter(), &nested.join("a.md")),
+            Some(nested.clone())
+        );
+        assert_eq!(deepest_root(roots.iter(), &work.join("a.md")), Some(work));
+        assert_eq!(deepest_root(roots.iter(), &temp.path().join("a.md")), None);
+    }
+
+    #[test]
+    fn test_offset_to_position() {
+        let document = "# Ä\nbé";
+        assert_eq!(offset_to_position(document, 6), Position::new(1, 1));
+        // within a character, or past the end
+        assert_eq!(offset_to_position(document, 3), Position::new(0, 2));
+        assert_eq!(offset_to_position(document, 7), Position::new(1, 1));
+        assert_eq!(offset_to_position(document, 100), Position::new(1, 2));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_windows_routing() {
+        let uri = |s: &str| s.parse::<Uri>().unwrap();
+        let roots = [PathBuf::from(r"C:\Notes")];
+        assert_eq!(
+            deepest_root(
+                roots.iter(),
+                &uri_to_path(&uri("file:///c%3A/notes/a.md")).unwrap()
+            ),
+            Some(roots[0].clone())
+        );
+    }
+}
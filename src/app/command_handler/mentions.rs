@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+use serde::Serialize;
+use zet::config::Config;
+use zet::core::db::{DB, DbList, DbQuery};
+use zet::core::mentions::{Person, people, resolve};
+use zet::core::types::document::{DocumentId, DocumentSummary};
+use zet::core::types::mention::DocumentMention;
+use zet::preamble::*;
+
+use crate::app::commands::ReportFormat;
+
+#[derive(Debug, Serialize)]
+struct Mentions<'a> {
+    /// the person mentioned, `None` if no note of the people group has the
+    /// name
+    person: Option<&'a Person>,
+    mentions: Vec<DocumentMention>,
+}
+
+/// Print the documents mentioning `person`, by any of their names
+pub fn handle_command(
+    root: &Path,
+    config: &Config,
+    person: &str,
+    output_format: ReportFormat,
+    pretty: bool,
+) -> Result<()> {
+    let db = DB::open(zet::core::collection_db_file(root))?;
+    let people = people(root, config, &db)?;
+    let name = person.trim_start_matches('@');
+    let person = resolve(&people, name);
+    let names = match person {
+        Some(person) => person.names.clone(),
+        None => vec![name.to_owned()],
+    };
+    let mentions = DocumentMention::list(&db, names.as_slice())?;
+
+    let mut writer = std::io::BufWriter::new(std::io::stdout());
+    match output_format {
+        ReportFormat::Json => {
+            let mentions = Mentions { person, mentions };
+            match pretty {
+                true => serde_json::to_writer_pretty(&mut writer, &mentions)?,
+                false => serde_json::to_writer(&mut writer, &mentions)?,
+            }
+        }
+        ReportFormat::Text => {
+            let titles: HashMap<DocumentId, String> = DocumentSummary::list(&db)?
+                .into_iter()
+                .map(|d| (d.id, d.title))
+                .collect();
+            for group in mentions.chunk_by(|a, b| a.document_id == b.document_id) {
+                let id = &group[0].document_id;
+                let title = titles.get(id).map_or("", String::as_str);
+                writeln!(writer, "{}\t{}\t{}", id.0, title, group.len())?;
+            }
+        }
+    }
+    writer.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::eyre;
+use normalize_path::NormalizePath;
+use zet::config::Config;
+use zet::core::db::DB;
+use zet::core::merge::{CollisionStrategy, MergeOptions, plan};
+use zet::core::slug::slugify;
+use zet::preamble::*;
+
+pub fn handle_command(
+    root: &Path,
+    config: &Config,
+    other: &Path,
+    into: Option<PathBuf>,
+    strategy: CollisionStrategy,
+    prefix: Option<String>,
+    dry_run: bool,
+) -> Result<()> {
+    let other = zet::core::resolve_root(Some(other.to_owned()))?.normalize();
+    if other == root {
+        return Err(eyre!("can not merge a collection into itself"));
+    }
+    let directory = root.join(into.unwrap_or_default()).normalize();
+    if !directory.starts_with(root) {
+        return Err(eyre!("{:?} is not in the collection", directory));
+    }
+    let prefix =
+        prefix.unwrap_or_else(|| slugify(other.file_name().unwrap_or_default().to_string_lossy()));
+
+    // the notes, their ids and their links are taken from the indexes, which
+    // have to be up to date
+    super::index::handle_command(&other, Config::resolve(&other)?, false, false)?;
+    super::index::handle_command(root, Config::resolve(root)?, false, false)?;
+    let db = DB::open(zet::core::collection_db_file(root))?;
+    let other_db = DB::open(zet::core::collection_db_file(&other))?;
+    let other_config = Config::resolve(&other)?;
+
+    let options = MergeOptions {
+        directory,
+        strategy,
+        prefix,
+    };
+    let plan = plan(
+        root,
+        config,
+        &db,
+        &other,
+        &other_config,
+        &other_db,
+        &options,
+    )?;
+    let from_paths = plan
+        .notes
+        .iter()
+        .map(|note| (&note.document.path.0, &note.to))
+        .chain(plan.assets.iter().map(|asset| (&asset.from, &asset.to)));
+    for (from, to) in from_paths {
+        match to {
+            Some(to) => println!(
+                "{} -> {}",
+                relative(&other, from).display(),
+                relative(root, to).display()
+            ),
+            None => println!("{} skipped", relative(&other, from).display()),
+        }
+    }
+    if dry_run {
+        return Ok(());
+    }
+
+    drop(other_db);
+    plan.apply()?;
+    drop(db);
+    super::index::handle_command(root, Config::resolve(root)?, false, false)
+}
+
+fn relative<'a>(root: &Path, path: &'a Path) -> &'a Path {
+    path.strip_prefix(root).unwrap_or(path)
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use color_eyre::eyre::eyre;
+use zet::config::Config;
+use zet::core::LinkTargets;
+use zet::core::db::{DB, DbGet, DbList};
+use zet::core::hooks::HookEvent;
+use zet::core::lock::Locks;
+use zet::core::merge_notes::{MergedNote, Redirect, merge_notes, trash_dir};
+use zet::core::rename::rewrite_link_paths;
+use zet::core::types::document::{Document, DocumentId};
+use zet::preamble::*;
+
+use super::import::unique_path;
+
+/// Merge the notes `ids` into the note `into`, moving them to the trash
+pub fn handle_command(
+    root: &Path,
+    config: &Config,
+    ids: [&str; 2],
+    into: &str,
+    title: Option<String>,
+    no_index: bool,
+) -> Result<()> {
+    if ids[0] == ids[1] {
+        return Err(eyre!("a note can not be merged with itself"));
+    }
+    let mut db = DB::open(zet::core::collection_db_file(root))?;
+    let mut notes = Vec::with_capacity(ids.len());
+    for id in ids {
+        let document = Document::get(&mut db, &DocumentId(id.to_owned()))
+            .map_err(|_| eyre!("no document with id '{}'", id))?;
+        notes.push(document);
+    }
+    let targets = LinkTargets::load(&db)?;
+    let mut documents = Document::list(&db)?;
+    documents.sort_by(|a, b| a.path.0.cmp(&b.path.0));
+    drop(db);
+
+    let into = DocumentId(into.to_owned());
+    let kept = notes.iter().find(|note| note.id == into);
+    if kept.is_none() && targets.path(&into).is_some() {
+        return Err(eyre!("a document with id '{}' already exists", into.0));
+    }
+    let title = title.unwrap_or_else(|| kept.unwrap_or(&notes[0]).title.clone());
+    let into_path = match kept {
+        Some(note) => note.path.0.clone(),
+        None => {
+            let directory = notes[0].path.0.parent().unwrap_or(root);
+            let filename = config.id_scheme(root, directory).filename(&into.0, &title);
+            directory.join(filename)
+        }
+    };
+    if kept.is_none() && into_path.exists() {
+        return Err(eyre!("{:?} already exists", into_path));
+    }
+
+    let locks = Locks::load(root, config.front_matter_format)?;
+    let merged: Vec<(DocumentId, String)> = notes
+        .iter()
+        .map(|note| (note.id.clone(), note.title.clone()))
+        .collect();
+    let redirect = Redirect {
+        merged: &merged,
+        into: &into,
+        into_path: &into_path,
+        targets: &targets,
+    };
+    let mut parts = Vec::with_capacity(notes.len());
+    for note in &notes {
+        let path = &note.path.0;
+        let content = std::fs::read_to_string(path)?;
+        if locks.is_locked(path, &content) {
+            return Err(eyre!("the document '{}' is locked", note.id.0));
+        }
+        // the links to each other first, the others are relative to the note
+        let content = redirect
+            .redirect(&content, &into_path, &note.id, &into)?
+            .unwrap_or(content);
+        let content =
+            rewrite_link_paths(&content, path, &into_path, &HashMap::new())?.unwrap_or(content);
+        parts.push(MergedNote {
+            id: note.id.clone(),
+            title: note.title.clone(),
+            content,
+        });
+    }
+
+    let content = merge_notes(&parts, &into, &title, config.front_matter_format)?;
+    std::fs::write(&into_path, content)?;
+    println!("{}", into_path.display());
+
+    let trash = trash_dir(root);
+    for note in notes.iter().filter(|note| note.path.0 != into_path) {
+        std::fs::create_dir_all(&trash)?;
+        let filename = note
+            .path
+            .0
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy();
+        let to = unique_path(&trash, &filename);
+        std::fs::rename(&note.path.0, &to)?;
+        log::info!("moved {:?} to {:?}", note.path.0, to);
+    }
+
+    let others = documents
+        .iter()
+        .filter(|d| notes.iter().all(|note| note.id != d.id));
+    for other in others {
+        let path = &other.path.0;
+        let content = std::fs::read_to_string(path)?;
+        let Some(edited) = redirect.redirect(&content, path, &other.id, &other.id)? else {
+            continue;
+        };
+        if locks.is_locked(path, &content) {
+            log::warn!("not updating the links of the locked document {:?}", path);
+            continue;
+        }
+        std::fs::write(path, edited)?;
+        println!("{}", path.display());
+    }
+
+    if !no_index {
+        super::index::handle_command(root, Config::resolve(root)?, false, false)?;
+    }
+    if kept.is_none() {
+        config.hooks.run(
+            root,
+            &HookEvent::Create {
+                id: into,
+                title,
+                path: std::path::absolute(&into_path)?,
+            },
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use std::path::Path;
+
+use color_eyre::eyre::eyre;
+use zet::config::Config;
+use zet::core::db::DB;
+use zet::core::frontmatter::{
+    remove_frontmatter_key, rename_frontmatter_key, set_frontmatter_value,
+};
+use zet::core::lock::Locks;
+use zet::core::query::{DocumentQuery, SortByOption, SortOrder};
+use zet::core::read::read_utf8;
+use zet::preamble::*;
+
+use crate::app::commands::{MetaCommand, MetaSelection};
+
+pub fn handle_command(root: &Path, config: &Config, command: MetaCommand) -> Result<()> {
+    let (MetaCommand::Set { selection, .. }
+    | MetaCommand::Unset { selection, .. }
+    | MetaCommand::RenameKey { selection, .. }) = &command;
+    let MetaSelection { filters, dry_run } = selection;
+
+    // the documents are selected using the index, which has to be up to date
+    super::index::handle_command(root, Config::resolve(root)?, false, false)?;
+    let db = DB::open(zet::core::collection_db_file(root))?;
+
+    let documents = filters
+        .iter()
+        .cloned()
+        .fold(DocumentQuery::new(), DocumentQuery::filter)
+        .order_by(SortByOption::Path, SortOrder::Ascending)
+        .execute(&db)?;
+    drop(db);
+
+    // every edit is computed before any file is written, so that a failing
+    // edit leaves the collection untouched
+    let format = config.front_matter_format;
+    let locks = Locks::load(root, format)?;
+    let mut edits = Vec::new();
+    for document in documents {
+        let path = document.path.0;
+        let Some(content) = read_utf8(&path)? else {
+            continue;
+        };
+        if locks.is_locked(&path, &content) {
+            log::warn!(
+                "skipping the locked document {}",
+                relative(root, &path).display()
+            );
+            continue;
+        }
+        let edited = match &command {
+            MetaCommand::Set { key, value, .. } => {
+                set_frontmatter_value(&content, format, key, &parse_value(value))
+            }
+            MetaCommand::Unset { key, .. } => remove_frontmatter_key(&content, format, key),
+            MetaCommand::RenameKey { from, to, .. } => {
+                rename_frontmatter_key(&content, format, from, to)
+            }
+        }
+        .map_err(|e| eyre!("{}: {}", relative(root, &path).display(), e))?;
+        if edited != content {
+            edits.push((path, content, edited));
+        }
+    }
+
+    for (path, content, edited) in &edits {
+        let name = relative(root, path).display().to_string();
+        if *dry_run {
+            print!("{}", diff(&name, content, edited));
+        } else {
+            std::fs::write(path, edited)?;
+            println!("{name}");
+        }
+    }
+    if *dry_run || edits.is_empty() {
+        return Ok(());
+    }
+
+    super::index::handle_command(root, Config::resolve(root)?, false, false)
+}
+
+/// Values are json, such as `5`, `true` or `["a", "b"]`, anything else is a
+/// string
+fn parse_value(value: &str) -> serde_json::Value {
+    serde_json::from_str(value).unwrap_or_else(|_| serde_json::Value::String(value.to_owned()))
+}
+
+/// A unified diff with a single hunk, spanning the lines from the first to the
+/// last changed one
+fn diff(name: &str, old: &str, new: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let prefix = old_lines
+        .iter()
+        .zip(&new_lines)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let removed = &old_lines[prefix..old_lines.len() - suffix];
+    let added = &new_lines[prefix..new_lines.len() - suffix];
+
+    let mut diff = format!("--- {name}\n+++ {name}\n");
+    diff.push_str(&format!(
+        "@@ -{},{} +{},{} @@\n",
+        prefix + 1,
+        removed.len(),
+        prefix + 1,
+        added.len()
+    ));
+    for line in removed {
+        diff.push_str(&format!("-{line}\n"));
+    }
+    for line in added {
+        diff.push_str(&format!("+{line}\n"));
+    }
+    diff
+}
+
+fn relative<'a>(root: &Path, path: &'a Path) -> &'a Path {
+    path.strip_prefix(root).unwrap_or(path)
+}
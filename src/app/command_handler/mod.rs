@@ -1,30 +1,104 @@
+use zet::core::journal::{Direction, Period};
 use zet::core::parser::FrontMatterFormat;
 
+pub mod assets;
+pub mod backup;
+pub mod board;
+pub mod compile;
 pub mod create;
+pub mod date;
+pub mod diff_index;
+pub mod doctor;
+pub mod expand;
+pub mod export;
+pub mod find;
+pub mod format;
+pub mod graph;
+pub mod highlights;
+pub mod import;
 pub mod index;
 pub mod init;
+pub mod journal;
+pub mod lint;
+pub mod list;
+pub mod log;
 pub mod lsp;
+pub mod mentions;
+pub mod merge;
+pub mod merge_notes;
+pub mod meta;
+pub mod normalize_filenames;
 pub mod parse;
 pub mod query;
+pub mod quotes;
 pub mod raw_parse;
+pub mod readlist;
+pub mod refs;
+pub mod restore;
+pub mod rollup;
+pub mod section;
+pub mod sequence;
+pub mod serve;
+pub mod source;
+pub mod split;
+pub mod split_note;
+pub mod table;
+pub mod tag;
+pub mod tags;
+pub mod tasks;
+pub mod templates;
+pub mod timeline;
+pub mod words;
 
 use crate::app::preamble::*;
+use color_eyre::eyre::eyre;
+use sequence::SequenceStep;
+use zet::core::db::{DbLock, Page};
 use zet::preamble::*;
 
-pub fn handle_command(command: Command, root: Option<PathBuf>) -> Result<()> {
+pub fn handle_command(command: Command, root: Option<PathBuf>, read_only: bool) -> Result<()> {
+    zet::core::db::set_read_only(read_only);
+    if command.is_mutating() {
+        if read_only {
+            return Err(eyre!(
+                "zet was started with --read-only, this command would modify the collection"
+            ));
+        }
+        if let Ok(root) = zet::core::resolve_root(root.clone()) {
+            let db = zet::core::collection_db_file(&root);
+            if zet::core::db::is_read_only(&db) {
+                return Err(eyre!(
+                    "{:?} is not writable, this command would modify the collection",
+                    db
+                ));
+            }
+        }
+    }
+    // held until the command is done, so that the commands writing to the
+    // collection, to its documents or to its index, run one after the other
+    let _lock = match command.is_mutating() {
+        true => match zet::core::resolve_root(root.clone()) {
+            Ok(root) => DbLock::of_collection(&root)?,
+            Err(_) => None,
+        },
+        false => None,
+    };
+
     match command {
         Command::Init { root, force } => init::handle_command(root, force)?,
         Command::Parse { path, pretty_print } => {
             parse::handle_command(FrontMatterFormat::Yaml, pretty_print, path)?
         }
         Command::RawParse { path } => raw_parse::handle_command(FrontMatterFormat::Yaml, path)?,
-        Command::Index { force } => {
+        Command::Index { force, dry_run } => {
             let root = zet::core::resolve_root(root)?;
-            let config = zet::config::Config {
-                front_matter_format: FrontMatterFormat::Yaml,
-                group: Default::default(),
-            };
-            index::handle_command(&root, config, force)?
+            let config = zet::config::Config::resolve(&root)?;
+            index::handle_command(&root, config, force, dry_run)?
+        }
+        Command::DiffIndex => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            diff_index::handle_command(&root, config)?
         }
         Command::Query {
             ids,
@@ -45,6 +119,7 @@ pub fn handle_command(command: Command, root: Option<PathBuf>) -> Result<()> {
             match_patterns,
             sort_configs,
             limit,
+            page,
             output_format,
             delimiter,
             pretty,
@@ -54,8 +129,14 @@ pub fn handle_command(command: Command, root: Option<PathBuf>) -> Result<()> {
 
             let config = zet::config::Config {
                 front_matter_format: FrontMatterFormat::Yaml,
-                group: Default::default(),
+                ..zet::config::Config::resolve(&root)?
             };
+            let created = created.map(|e| e.resolve(&config)).transpose()?;
+            let modified = modified.map(|e| e.resolve(&config)).transpose()?;
+            let created_before = created_before.map(|e| e.resolve(&config)).transpose()?;
+            let created_after = created_after.map(|e| e.resolve(&config)).transpose()?;
+            let modified_before = modified_before.map(|e| e.resolve(&config)).transpose()?;
+            let modified_after = modified_after.map(|e| e.resolve(&config)).transpose()?;
 
             query::handle_command(
                 &root,
@@ -77,25 +158,57 @@ pub fn handle_command(command: Command, root: Option<PathBuf>) -> Result<()> {
                 links_from,
                 match_patterns,
                 sort_configs,
-                limit,
+                Page::new(limit, page),
                 output_format,
                 delimiter,
                 pretty,
                 template,
             )?;
         }
-        Command::Lsp => {}
-        Command::Format => todo!(),
+        Command::Lsp => {
+            // the editor may start the server outside of a collection, and
+            // name the collections as workspace folders
+            let root = match root {
+                Some(root) => Some(zet::core::resolve_root(Some(root))?),
+                None => zet::core::resolve_root(None).ok(),
+            };
+            lsp::handle_command(root)?
+        }
+        Command::Format {
+            paths,
+            check,
+            link_style,
+            wrap,
+            tables,
+            smart_punctuation,
+        } => {
+            let root = zet::core::resolve_root(root)?;
+            let mut config = zet::config::Config::resolve(&root)?;
+            if link_style.is_some() {
+                config.format.link_style = link_style;
+            }
+            if wrap.is_some() {
+                config.format.wrap = wrap;
+            }
+            config.format.tables |= tables;
+            config.format.smart_punctuation |= smart_punctuation;
+            format::handle_command(&root, &config, paths, check)?
+        }
         Command::Create {
             title,
             content,
             group,
             template,
             stdin,
+            from,
             data_json,
             data_toml,
             data_json_path,
             data_toml_path,
+            from_stub,
+            no_index,
+            open,
+            duplicate,
         } => create::handle_command(
             root,
             title,
@@ -103,11 +216,382 @@ pub fn handle_command(command: Command, root: Option<PathBuf>) -> Result<()> {
             group,
             template,
             stdin,
+            from,
             data_json,
             data_toml,
             data_json_path,
             data_toml_path,
+            from_stub,
+            no_index,
+            open,
+            duplicate.policy(),
         )?,
+        Command::List {
+            stubs,
+            created,
+            modified,
+            limit,
+            page,
+            output_format,
+            pretty,
+        } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            let created = created.map(|e| e.resolve(&config)).transpose()?;
+            let modified = modified.map(|e| e.resolve(&config)).transpose()?;
+            let page = Page::new(limit, page);
+            list::handle_command(&root, stubs, created, modified, page, output_format, pretty)?
+        }
+        Command::Graph { command } => {
+            let root = zet::core::resolve_root(root)?;
+            graph::handle_command(&root, command)?
+        }
+        Command::Journal {
+            command,
+            period,
+            date,
+            prev,
+            next,
+        } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            match command {
+                Some(command) => journal::handle_command(&root, config, command)?,
+                None => {
+                    let direction = match (prev, next) {
+                        (true, _) => Some(Direction::Previous),
+                        (_, true) => Some(Direction::Next),
+                        _ => None,
+                    };
+                    journal::handle_navigate(&root, &config, period, date, direction)?
+                }
+            }
+        }
+        Command::Rollup {
+            day,
+            week,
+            month,
+            range,
+            force,
+        } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            let (period, date) = match (day, week, month, range) {
+                (Some(date), _, _, _) => (Period::Daily, date.resolve(&config)?),
+                (_, Some(date), _, _) => (Period::Weekly, date.resolve(&config)?),
+                (_, _, Some(date), _) => (Period::Monthly, date.resolve(&config)?),
+                (_, _, _, Some(range)) => {
+                    let range = range.resolve(&config)?;
+                    return rollup::handle_range(&root, config, range, force);
+                }
+                _ => unreachable!("clap requires one of --day, --week, --month or --range"),
+            };
+            rollup::handle_command(&root, config, period, date, force)?
+        }
+        Command::Board {
+            group_by,
+            width,
+            output_format,
+            pretty,
+        } => {
+            let root = zet::core::resolve_root(root)?;
+            board::handle_command(&root, group_by, width, output_format, pretty)?
+        }
+        Command::Lint {
+            paths,
+            fix,
+            output_format,
+            pretty,
+        } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            lint::handle_command(&root, &config, paths, fix, output_format, pretty)?
+        }
+        Command::NormalizeFilenames { dry_run } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            normalize_filenames::handle_command(&root, &config, dry_run)?
+        }
+        Command::Date {
+            expression,
+            range,
+            output_format,
+            pretty,
+        } => {
+            // dates can be resolved outside of a collection, using the defaults
+            let root = match root {
+                Some(root) => Some(root),
+                None => std::env::current_dir()?
+                    .ancestors()
+                    .find(|dir| zet::core::collection_config_dir(dir).is_dir())
+                    .map(|dir| dir.to_path_buf()),
+            };
+            let config = match root {
+                Some(root) => zet::config::Config::resolve(&root)?,
+                None => zet::config::Config::default(),
+            };
+            date::handle_command(&config, &expression, range, output_format, pretty)?
+        }
+        Command::Meta { command } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            meta::handle_command(&root, &config, command)?
+        }
+        Command::Tags {
+            prefix,
+            tree,
+            output_format,
+            pretty,
+        } => {
+            let root = zet::core::resolve_root(root)?;
+            tags::handle_command(&root, prefix, tree, output_format, pretty)?
+        }
+        Command::Highlights {
+            document,
+            output_format,
+            pretty,
+        } => {
+            let root = zet::core::resolve_root(root)?;
+            highlights::handle_command(&root, document, output_format, pretty)?
+        }
+        Command::Quotes {
+            document,
+            attributed,
+            note,
+            force,
+            output_format,
+            pretty,
+        } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            match note {
+                Some(title) => {
+                    quotes::handle_note(&root, &config, document, attributed, &title, force)?
+                }
+                None => quotes::handle_command(&root, document, attributed, output_format, pretty)?,
+            }
+        }
+        Command::MentionsOf {
+            person,
+            output_format,
+            pretty,
+        } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            mentions::handle_command(&root, &config, &person, output_format, pretty)?
+        }
+        Command::Refs {
+            target,
+            output_format,
+            pretty,
+        } => {
+            let root = zet::core::resolve_root(root)?;
+            refs::handle_command(&root, &target, output_format, pretty)?
+        }
+        Command::Source { command } => {
+            let root = zet::core::resolve_root(root)?;
+            source::handle_command(&root, command)?
+        }
+        Command::Tasks {
+            owner,
+            action_items,
+            open,
+            output_format,
+            pretty,
+        } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            tasks::handle_command(
+                &root,
+                &config,
+                owner,
+                action_items,
+                open,
+                output_format,
+                pretty,
+            )?
+        }
+        Command::Doctor {
+            output_format,
+            pretty,
+        } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            doctor::handle_command(&root, &config, output_format, pretty)?
+        }
+        Command::Timeline {
+            tags,
+            match_pattern,
+            range,
+            by,
+            output_format,
+            pretty,
+        } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            let range = range.map(|e| e.resolve(&config)).transpose()?;
+            timeline::handle_command(
+                &root,
+                &config,
+                tags,
+                match_pattern,
+                range,
+                by,
+                output_format,
+                pretty,
+            )?
+        }
+        Command::Import { command } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            import::handle_command(&root, config, command)?
+        }
+        Command::Section { command } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            section::handle_command(&root, &config, command)?
+        }
+        Command::MergeNotes {
+            a,
+            b,
+            into,
+            title,
+            no_index,
+        } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            merge_notes::handle_command(&root, &config, [&a, &b], &into, title, no_index)?
+        }
+        Command::SplitNote {
+            id,
+            level,
+            embed,
+            no_index,
+        } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            split_note::handle_command(&root, &config, &id, level, embed, no_index)?
+        }
+        Command::Table { command } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            table::handle_command(&root, &config, command)?
+        }
+        Command::Readlist { command } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            readlist::handle_command(&root, config, command)?
+        }
+        Command::Tag { command } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            tag::handle_command(&root, &config, command)?
+        }
+        Command::Assets { command } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            assets::handle_command(&root, &config, command)?
+        }
+        Command::Expand {
+            trigger,
+            variables,
+            id,
+            title,
+        } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            expand::handle_command(&root, &config, &trigger, variables, &id, &title)?
+        }
+        Command::Export { command } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            export::handle_command(&root, config, command)?
+        }
+        Command::Backup { out, include_db } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            backup::handle_command(&root, &config, out, include_db)?
+        }
+        Command::Restore {
+            archive,
+            into,
+            force,
+        } => restore::handle_command(&archive, into, force)?,
+        Command::Merge {
+            other,
+            into,
+            on_collision,
+            prefix,
+            dry_run,
+        } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            merge::handle_command(&root, &config, &other, into, on_collision, prefix, dry_run)?
+        }
+        Command::Split {
+            directory,
+            out,
+            cross_links,
+            dry_run,
+        } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            split::handle_command(&root, &config, &directory, &out, cross_links, dry_run)?
+        }
+        Command::Find {
+            query,
+            limit,
+            output_format,
+            pretty,
+        } => {
+            let root = zet::core::resolve_root(root)?;
+            find::handle_command(&root, &query, limit, output_format, pretty)?
+        }
+        Command::Compile { id, out, title } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            compile::handle_command(&root, &config, &id, &out, title)?
+        }
+        Command::Words {
+            ids,
+            transclude,
+            output_format,
+            pretty,
+        } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            words::handle_command(&root, &config, ids, transclude, output_format, pretty)?
+        }
+        Command::Next { args } => {
+            let root = zet::core::resolve_root(root)?;
+            sequence::handle_command(&root, SequenceStep::Next, args)?
+        }
+        Command::Prev { args } => {
+            let root = zet::core::resolve_root(root)?;
+            sequence::handle_command(&root, SequenceStep::Prev, args)?
+        }
+        Command::Children { args } => {
+            let root = zet::core::resolve_root(root)?;
+            sequence::handle_command(&root, SequenceStep::Children, args)?
+        }
+        Command::Serve { address, metrics } => {
+            let root = zet::core::resolve_root(root)?;
+            serve::handle_command(root, address, metrics)?
+        }
+        Command::Templates { command } => {
+            let root = zet::core::resolve_root(root)?;
+            templates::handle_command(&root, command)?
+        }
+        Command::Log {
+            since,
+            output_format,
+            pretty,
+        } => {
+            let root = zet::core::resolve_root(root)?;
+            let config = zet::config::Config::resolve(&root)?;
+            let since = since.map(|date| date.resolve(&config)).transpose()?;
+            log::handle_command(&root, since, output_format, pretty)?
+        }
     }
     Ok(())
 }
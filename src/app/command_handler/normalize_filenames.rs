@@ -0,0 +1,100 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use color_eyre::eyre::eyre;
+use zet::config::Config;
+use zet::core::db::{DB, DbList};
+use zet::core::filename::expected_filename;
+use zet::core::hooks::HookEvent;
+use zet::core::lock::Locks;
+use zet::core::rename::{Rename, rename_documents};
+use zet::core::types::document::Document;
+use zet::preamble::*;
+
+pub fn handle_command(root: &Path, config: &Config, dry_run: bool) -> Result<()> {
+    let Some(pattern) = &config.lint.filename_pattern else {
+        return Err(eyre!(
+            "no file name pattern configured, set lint.filename_pattern"
+        ));
+    };
+    let tz = config.timezone()?;
+
+    // the links are resolved using the index, which has to be up to date
+    super::index::handle_command(root, Config::resolve(root)?, false, false)?;
+    let db = DB::open(zet::core::collection_db_file(root))?;
+
+    let mut documents = Document::list(&db)?;
+    documents.sort_by(|a, b| a.path.0.cmp(&b.path.0));
+
+    let locks = Locks::load(root, config.front_matter_format)?;
+    let mut renames = Vec::new();
+    let mut targets = HashSet::new();
+    for document in documents {
+        let path = &document.path.0;
+        let content = std::fs::read_to_string(path)?;
+        if locks.is_locked(path, &content) {
+            log::info!("not renaming the locked document {:?}", path);
+            continue;
+        }
+        let Some(filename) = expected_filename(
+            pattern,
+            root,
+            path,
+            &content,
+            config.front_matter_format,
+            &tz,
+        )?
+        else {
+            continue;
+        };
+        if path
+            .file_name()
+            .is_some_and(|name| name == filename.as_str())
+        {
+            continue;
+        }
+        let to = path.with_file_name(&filename);
+        if to.exists() || !targets.insert(to.clone()) {
+            log::warn!("not renaming {:?}, {:?} already exists", path, to);
+            continue;
+        }
+        let id_scheme = config.id_scheme(root, &to);
+        renames.push(Rename {
+            document,
+            to,
+            id_scheme,
+        });
+    }
+
+    for rename in &renames {
+        println!(
+            "{} -> {}",
+            relative(root, &rename.document.path.0).display(),
+            relative(root, &rename.to).display()
+        );
+    }
+    if dry_run || renames.is_empty() {
+        return Ok(());
+    }
+
+    for path in rename_documents(&db, root, &renames, &locks)? {
+        log::info!("rewrote the links of {:?}", path);
+    }
+    drop(db);
+    for rename in &renames {
+        config.hooks.run(
+            root,
+            &HookEvent::Rename {
+                old_id: rename.document.id.clone(),
+                new_id: rename.new_id(root),
+                from: rename.document.path.0.clone(),
+                to: rename.to.clone(),
+            },
+        );
+    }
+    super::index::handle_command(root, Config::resolve(root)?, false, false)
+}
+
+fn relative<'a>(root: &Path, path: &'a Path) -> &'a Path {
+    path.strip_prefix(root).unwrap_or(path)
+}
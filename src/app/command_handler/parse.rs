@@ -2,6 +2,7 @@ use std::io::BufWriter;
 
 use zet::core::parser::FrontMatterFormat;
 use zet::core::parser::FrontMatterParser;
+use zet::core::parser::ast_nodes::AST_VERSION;
 
 use crate::app::preamble::*;
 use zet::preamble::*;
@@ -24,6 +25,7 @@ pub fn handle_command(
     let frontmatter = serde_json::to_value(frontmatter)?;
     let content = serde_json::to_value(content)?;
     let mut res = serde_json::Map::new();
+    res.insert("version".into(), AST_VERSION.into());
     res.insert("frontmatter".into(), frontmatter);
     res.insert("content".into(), content);
 
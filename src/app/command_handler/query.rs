@@ -4,7 +4,8 @@ use std::path::Path;
 use jiff::Timestamp;
 use tera::Context;
 use tera::Tera;
-use zet::core::db::DB;
+use zet::core::date_parser::TimeRange;
+use zet::core::db::{DB, Page};
 use zet::core::query::DocumentQuery;
 use zet::core::query::SortByOption as QuerySortByOption;
 use zet::core::query::SortOrder as QuerySortOrder;
@@ -28,8 +29,8 @@ pub fn handle_command(
     tagless: bool,
     exclude_list: Vec<String>,
     exclude_by_path: Vec<String>,
-    created: Option<Timestamp>,
-    modified: Option<Timestamp>,
+    created: Option<TimeRange>,
+    modified: Option<TimeRange>,
     created_before: Option<Timestamp>,
     created_after: Option<Timestamp>,
     modified_before: Option<Timestamp>,
@@ -38,7 +39,7 @@ pub fn handle_command(
     links_from: Vec<String>,
     match_patterns: Vec<String>,
     sort_configs: Vec<SortConfig>,
-    limit: Option<usize>,
+    page: Page,
     output_format: OutputFormat,
     delimiter: Option<String>,
     pretty: bool,
@@ -73,11 +74,11 @@ pub fn handle_command(
     if !exclude_by_path.is_empty() {
         query = query.exclude_paths(exclude_by_path);
     }
-    if let Some(ts) = created {
-        query = query.created(ts);
+    if let Some(range) = created {
+        query = query.created(range);
     }
-    if let Some(ts) = modified {
-        query = query.modified(ts);
+    if let Some(range) = modified {
+        query = query.modified(range);
     }
     if let Some(ts) = created_before {
         query = query.created_before(ts);
@@ -121,10 +122,7 @@ pub fn handle_command(
         query = query.order_by(query_by, query_order);
     }
 
-    // Add limit
-    if let Some(n) = limit {
-        query = query.limit(n);
-    }
+    query = query.page(page);
 
     let documents = query.execute(&db)?;
 
@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+use color_eyre::eyre::eyre;
+use serde_json::json;
+use zet::config::Config;
+use zet::core::db::{DB, DbList, DbQuery};
+use zet::core::quotes::DEFAULT_QUOTES_TEMPLATE;
+use zet::core::template_engine::render_collection_template;
+use zet::core::types::document::{DocumentId, DocumentSummary};
+use zet::core::types::quote::DocumentQuote;
+use zet::preamble::*;
+
+use crate::app::commands::ReportFormat;
+
+/// Print the quotes of the document `document`, or of every document, under
+/// the title of their document
+pub fn handle_command(
+    root: &Path,
+    document: Option<String>,
+    attributed: bool,
+    output_format: ReportFormat,
+    pretty: bool,
+) -> Result<()> {
+    let (titles, quotes) = load(root, document, attributed)?;
+
+    let mut writer = std::io::BufWriter::new(std::io::stdout());
+    match output_format {
+        ReportFormat::Json if pretty => serde_json::to_writer_pretty(&mut writer, &quotes)?,
+        ReportFormat::Json => serde_json::to_writer(&mut writer, &quotes)?,
+        ReportFormat::Text => {
+            for (i, group) in quotes
+                .chunk_by(|a, b| a.document_id == b.document_id)
+                .enumerate()
+            {
+                let id = &group[0].document_id;
+                if i > 0 {
+                    writeln!(writer)?;
+                }
+                match titles.get(id).filter(|title| !title.is_empty()) {
+                    Some(title) => writeln!(writer, "{} ({})", title, id.0)?,
+                    None => writeln!(writer, "{}", id.0)?,
+                }
+                for quote in group {
+                    match &quote.attribution {
+                        Some(attribution) => {
+                            writeln!(writer, "- {} — {}", quote.content, attribution)?
+                        }
+                        None => writeln!(writer, "- {}", quote.content)?,
+                    }
+                }
+            }
+        }
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Write the quotes of the document `document`, or of every document, into a
+/// new note titled `title` at the root of the collection
+pub fn handle_note(
+    root: &Path,
+    config: &Config,
+    document: Option<String>,
+    attributed: bool,
+    title: &str,
+    force: bool,
+) -> Result<()> {
+    let id = zet::core::slug::slugify(title);
+    let path = root.join(format!("{}.md", id));
+    if path.exists() && !force {
+        return Err(eyre!(
+            "note already exists: {:?}, use --force to overwrite it",
+            path
+        ));
+    }
+
+    let (_, mut quotes) = load(root, document, attributed)?;
+    // the quotes of a previous compilation
+    quotes.retain(|q| q.document_id.0 != id);
+
+    let date = jiff::Timestamp::now()
+        .to_zoned(config.timezone()?)
+        .strftime("%Y-%m-%d")
+        .to_string();
+    let extra = HashMap::from([("quotes".to_owned(), json!(quotes))]);
+    let rendered =
+        render_collection_template(root, DEFAULT_QUOTES_TEMPLATE, &id, title, &date, "", &extra)?;
+
+    std::fs::write(&path, rendered)?;
+    println!("{}", std::path::absolute(&path)?.display());
+
+    Ok(())
+}
+
+fn load(
+    root: &Path,
+    document: Option<String>,
+    attributed: bool,
+) -> Result<(HashMap<DocumentId, String>, Vec<DocumentQuote>)> {
+    let db = DB::open(zet::core::collection_db_file(root))?;
+    let titles: HashMap<DocumentId, String> = DocumentSummary::list(&db)?
+        .into_iter()
+        .map(|d| (d.id, d.title))
+        .collect();
+    let document = document.map(DocumentId);
+    if let Some(id) = &document
+        && !titles.contains_key(id)
+    {
+        return Err(eyre!("no document with the id {:?}", id.0));
+    }
+    let mut quotes = DocumentQuote::list(&db, document.as_ref())?;
+    if attributed {
+        quotes.retain(|q| q.attribution.is_some());
+    }
+    Ok((titles, quotes))
+}
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+use color_eyre::eyre::eyre;
+use serde_json::{Value, json};
+use zet::config::Config;
+use zet::core::db::DB;
+use zet::core::fetch::{PageMetadata, is_url};
+use zet::core::frontmatter::set_frontmatter_value;
+use zet::core::hooks::HookEvent;
+use zet::core::readlist::{DEFAULT_READLIST_TEMPLATE, STATUS_KEY, URL_KEY, entries, find_url};
+use zet::core::template_engine::{load_template_file, render_collection_template};
+use zet::core::types::document::DocumentId;
+use zet::preamble::*;
+
+use crate::app::commands::{ReadlistCommand, ReportFormat};
+
+pub fn handle_command(root: &Path, config: Config, command: ReadlistCommand) -> Result<()> {
+    match command {
+        ReadlistCommand::Add {
+            url,
+            title,
+            no_index,
+        } => handle_add(root, config, &url, title, no_index),
+        ReadlistCommand::List {
+            status,
+            output_format,
+            pretty,
+        } => handle_list(root, &config, status, output_format, pretty),
+    }
+}
+
+/// Create a note for the page at `url`, titled by the page unless `title` is
+/// given
+fn handle_add(
+    root: &Path,
+    config: Config,
+    url: &str,
+    title: Option<String>,
+    no_index: bool,
+) -> Result<()> {
+    if !is_url(url) {
+        return Err(eyre!("{:?} is not an http(s) url", url));
+    }
+    let db = DB::open(zet::core::collection_db_file(root))?;
+    if let Some(id) = find_url(&db, url)? {
+        return Err(eyre!("{} is already on the reading list as {}", url, id.0));
+    }
+
+    let metadata = PageMetadata::from_html(&config.fetch.page(url)?);
+    let title = title
+        .or_else(|| metadata.title.clone())
+        .unwrap_or_else(|| url.to_owned());
+
+    let readlist = &config.readlist;
+    let directory = root.join(&readlist.directory);
+    std::fs::create_dir_all(&directory)?;
+    let now = jiff::Timestamp::now().to_zoned(config.timezone()?);
+    let (DocumentId(id), filename) = config
+        .id_scheme(root, &directory)
+        .new_note(root, &directory, &title, &now)?;
+    let path = directory.join(filename);
+    if path.exists() {
+        return Err(eyre!("file already exists: {:?}", path));
+    }
+
+    let template_str = match &readlist.template {
+        Some(template) => load_template_file(root, template)?,
+        None => DEFAULT_READLIST_TEMPLATE.to_owned(),
+    };
+    let mut extra: HashMap<String, Value> = match serde_json::to_value(&metadata)? {
+        Value::Object(map) => map.into_iter().collect(),
+        _ => HashMap::new(),
+    };
+    extra.insert(URL_KEY.to_owned(), json!(url));
+    let date = now.strftime("%Y-%m-%d").to_string();
+    let mut rendered =
+        render_collection_template(root, &template_str, &id, &title, &date, "", &extra)?;
+
+    // set rather than templated, so that they are quoted as the format needs
+    let format = config.front_matter_format;
+    let fields = [
+        ("title", Some(title.as_str())),
+        (URL_KEY, Some(url)),
+        (STATUS_KEY, Some(readlist.initial_status())),
+        ("author", metadata.author.as_deref()),
+        ("site", metadata.site.as_deref()),
+        ("published", metadata.published.as_deref()),
+    ];
+    for (key, value) in fields {
+        if let Some(value) = value {
+            rendered = set_frontmatter_value(&rendered, format, key, &json!(value))?;
+        }
+    }
+
+    std::fs::write(&path, &rendered)?;
+    let path = std::path::absolute(&path)?;
+    println!("{}", path.display());
+
+    if !no_index {
+        let index_config = Config::resolve(root)?;
+        super::index::handle_command(root, index_config, false, false)?;
+    }
+
+    config.hooks.run(
+        root,
+        &HookEvent::Create {
+            id: DocumentId(id),
+            title,
+            path,
+        },
+    );
+
+    Ok(())
+}
+
+fn handle_list(
+    root: &Path,
+    config: &Config,
+    status: Vec<String>,
+    output_format: ReportFormat,
+    pretty: bool,
+) -> Result<()> {
+    let statuses = &config.readlist.statuses;
+    if let Some(unknown) = status.iter().find(|s| !statuses.contains(s)) {
+        return Err(eyre!(
+            "unknown status {:?}, the statuses are {}",
+            unknown,
+            statuses.join(", ")
+        ));
+    }
+    let status = if status.is_empty() {
+        statuses.clone()
+    } else {
+        status
+    };
+
+    let db = DB::open(zet::core::collection_db_file(root))?;
+    let entries = entries(&db, &status)?;
+
+    let mut writer = std::io::BufWriter::new(std::io::stdout());
+    match output_format {
+        ReportFormat::Json if pretty => serde_json::to_writer_pretty(&mut writer, &entries)?,
+        ReportFormat::Json => serde_json::to_writer(&mut writer, &entries)?,
+        ReportFormat::Text => {
+            for entry in entries {
+                writeln!(
+                    writer,
+                    "{}\t{}\t{}\t{}",
+                    entry.id.0, entry.status, entry.title, entry.url
+                )?;
+            }
+        }
+    }
+    writer.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+use color_eyre::eyre::eyre;
+use serde::Serialize;
+use zet::core::db::{DB, DbList, DbQuery};
+use zet::core::types::document::{DocumentId, DocumentSummary};
+use zet::core::types::link::DocumentLink;
+use zet::core::url::normalize_url;
+use zet::preamble::*;
+
+use crate::app::commands::ReportFormat;
+
+#[derive(Debug, Serialize)]
+struct References {
+    /// the normalized url the links were matched by
+    url: String,
+    links: Vec<DocumentLink>,
+}
+
+/// Print the documents linking to the page or DOI `target`
+pub fn handle_command(
+    root: &Path,
+    target: &str,
+    output_format: ReportFormat,
+    pretty: bool,
+) -> Result<()> {
+    let url = normalize_url(target).ok_or_else(|| eyre!("{target} is not an url or a DOI"))?;
+    let db = DB::open(zet::core::collection_db_file(root))?;
+    let links = <DocumentLink as DbQuery<_, &str>>::list(&db, &url)?;
+
+    let mut writer = std::io::BufWriter::new(std::io::stdout());
+    match output_format {
+        ReportFormat::Json => {
+            let references = References { url, links };
+            match pretty {
+                true => serde_json::to_writer_pretty(&mut writer, &references)?,
+                false => serde_json::to_writer(&mut writer, &references)?,
+            }
+        }
+        ReportFormat::Text => {
+            let titles: HashMap<DocumentId, String> = DocumentSummary::list(&db)?
+                .into_iter()
+                .map(|d| (d.id, d.title))
+                .collect();
+            for group in links.chunk_by(|a, b| a.from == b.from) {
+                let id = DocumentId::from(group[0].from.clone());
+                let title = titles.get(&id).map_or("", String::as_str);
+                writeln!(writer, "{}\t{}\t{}", id.0, title, group.len())?;
+            }
+        }
+    }
+    writer.flush()?;
+
+    Ok(())
+}
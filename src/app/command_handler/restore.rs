@@ -0,0 +1,28 @@
+use std::path::{Path, PathBuf};
+
+use normalize_path::NormalizePath;
+use resolve_path::PathResolveExt;
+use zet::config::Config;
+use zet::core::backup::restore;
+use zet::core::collection_config_dir;
+use zet::core::db::DbLock;
+use zet::preamble::*;
+
+pub fn handle_command(archive: &Path, into: Option<PathBuf>, force: bool) -> Result<()> {
+    let target = into.unwrap_or(std::env::current_dir()?);
+    let target: PathBuf = target.try_resolve()?.into_owned().normalize();
+
+    // a collection restored over is locked like the one zet runs in
+    let _lock = DbLock::of_collection(&target)?;
+    let manifest = restore(archive, &target, force)?;
+    println!(
+        "{} files restored to {}",
+        manifest.files.len(),
+        target.display()
+    );
+
+    // an archive of a collection without configuration has no .zet
+    std::fs::create_dir_all(collection_config_dir(&target))?;
+    let config = Config::resolve(&target)?;
+    super::index::handle_command(&target, config, false, false)
+}
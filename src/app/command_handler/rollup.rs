@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::eyre;
+use jiff::Timestamp;
+use serde_json::json;
+use zet::config::Config;
+use zet::core::date_parser::TimeRange;
+use zet::core::db::DB;
+use zet::core::journal::Period;
+use zet::core::mentions::people;
+use zet::core::rollup::{DEFAULT_ROLLUP_TEMPLATE, Rollup};
+use zet::core::template_engine::{render_collection_template, resolve_template_string};
+use zet::core::types::document::DocumentId;
+use zet::preamble::*;
+
+const DEFAULT_ROLLUP_DIRECTORY: &str = "journal";
+
+pub fn handle_command(
+    root: &Path,
+    config: Config,
+    period: Period,
+    date: Timestamp,
+    force: bool,
+) -> Result<()> {
+    let period_config = config.rollup.period(period);
+    let tz = config.timezone()?;
+    let start = period.start_of(date.to_zoned(tz.clone()).date(), &config.dates.week_start)?;
+
+    let title = match period_config.title_format.as_deref() {
+        Some(format) => period.title(start, Some(format))?,
+        None => format!("Rollup {}", period.title(start, None)?),
+    };
+    let id = zet::core::slug::slugify(&title);
+
+    let path = rollup_path(root, period_config.directory.as_deref(), &id, force)?;
+
+    let db = DB::open(zet::core::collection_db_file(root))?;
+    let people = people(root, &config, &db)?;
+    let rollup = Rollup::load(&db, period, start, &tz, &[DocumentId(id.clone())], &people)?;
+
+    let template_str = match period_config.template.as_deref() {
+        Some(template) => resolve_template_string(root, Some(template), None)?,
+        None => DEFAULT_ROLLUP_TEMPLATE.to_owned(),
+    };
+
+    write_rollup(root, &path, &template_str, &id, &title, &rollup)
+}
+
+/// Generate the rollup of an arbitrary range, using the default directory
+/// and template
+pub fn handle_range(root: &Path, config: Config, range: TimeRange, force: bool) -> Result<()> {
+    let tz = config.timezone()?;
+    let (start, end) = range.dates(&tz);
+    let title = format!(
+        "Rollup {} to {}",
+        start.strftime("%Y-%m-%d"),
+        end.strftime("%Y-%m-%d")
+    );
+    let id = zet::core::slug::slugify(&title);
+
+    let path = rollup_path(root, None, &id, force)?;
+
+    let db = DB::open(zet::core::collection_db_file(root))?;
+    let people = people(root, &config, &db)?;
+    let rollup = Rollup::load_range(&db, range, &tz, &[DocumentId(id.clone())], &people)?;
+
+    write_rollup(root, &path, DEFAULT_ROLLUP_TEMPLATE, &id, &title, &rollup)
+}
+
+fn rollup_path(root: &Path, directory: Option<&str>, id: &str, force: bool) -> Result<PathBuf> {
+    let directory = root.join(directory.unwrap_or(DEFAULT_ROLLUP_DIRECTORY));
+    std::fs::create_dir_all(&directory)?;
+    let path = directory.join(format!("{}.md", id));
+    if path.exists() && !force {
+        return Err(eyre!(
+            "rollup note already exists: {:?}, use --force to overwrite it",
+            path
+        ));
+    }
+    Ok(path)
+}
+
+fn write_rollup(
+    root: &Path,
+    path: &Path,
+    template_str: &str,
+    id: &str,
+    title: &str,
+    rollup: &Rollup,
+) -> Result<()> {
+    let date = rollup.start.strftime("%Y-%m-%d").to_string();
+    let extra: HashMap<String, serde_json::Value> = HashMap::from([
+        (
+            "period".to_owned(),
+            json!(rollup.period.map(|p| p.to_string())),
+        ),
+        ("start".to_owned(), json!(date)),
+        (
+            "end".to_owned(),
+            json!(rollup.end.strftime("%Y-%m-%d").to_string()),
+        ),
+        ("created".to_owned(), json!(rollup.created)),
+        ("modified".to_owned(), json!(rollup.modified)),
+        ("completed_tasks".to_owned(), json!(rollup.completed_tasks)),
+        ("action_items".to_owned(), json!(rollup.action_items)),
+    ]);
+    let rendered = render_collection_template(root, template_str, id, title, &date, "", &extra)?;
+
+    std::fs::write(path, rendered)?;
+    println!("{}", std::path::absolute(path)?.display());
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use std::path::Path;
+
+use color_eyre::eyre::eyre;
+use zet::config::Config;
+use zet::core::db::{DB, DbGet};
+use zet::core::flavor::DocumentSettings;
+use zet::core::lock::Locks;
+use zet::core::outline::{Position, move_section, shift_section};
+use zet::core::parser::DocumentParser;
+use zet::core::types::document::{Document, DocumentId};
+use zet::preamble::*;
+
+use crate::app::commands::{SectionArgs, SectionCommand};
+
+pub fn handle_command(root: &Path, config: &Config, command: SectionCommand) -> Result<()> {
+    let (SectionCommand::Move { section, .. }
+    | SectionCommand::Promote { section }
+    | SectionCommand::Demote { section }) = &command;
+    let SectionArgs {
+        id,
+        heading,
+        no_index,
+    } = section;
+
+    let mut db = DB::open(zet::core::collection_db_file(root))?;
+    let document = Document::get(&mut db, &DocumentId(id.clone()))
+        .map_err(|_| eyre!("no document with id '{}'", id))?;
+    drop(db);
+    let path = document.path.0;
+    let content = std::fs::read_to_string(&path)?;
+    if Locks::load(root, config.front_matter_format)?.is_locked(&path, &content) {
+        return Err(eyre!("the document '{}' is locked", id));
+    }
+
+    let settings = DocumentSettings::of(config, &content)?;
+    let parser = DocumentParser::from_config(&settings.parser);
+    let edited = match &command {
+        SectionCommand::Move { before, after, .. } => {
+            let position = match (before, after) {
+                (Some(before), _) => Position::Before(before.clone()),
+                (None, Some(after)) => Position::After(after.clone()),
+                (None, None) => return Err(eyre!("either --before or --after is needed")),
+            };
+            move_section(&content, heading, &position, &parser)?
+        }
+        SectionCommand::Promote { .. } => shift_section(&content, heading, -1, &parser)?,
+        SectionCommand::Demote { .. } => shift_section(&content, heading, 1, &parser)?,
+    };
+    if edited == content {
+        return Ok(());
+    }
+    std::fs::write(&path, edited)?;
+    println!("{}", path.display());
+
+    if !no_index {
+        super::index::handle_command(root, Config::resolve(root)?, false, false)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use std::io::Write;
+use std::path::Path;
+
+use color_eyre::eyre::eyre;
+use zet::core::db::{DB, DbList};
+use zet::core::sequence::neighbours;
+use zet::core::types::document::{DocumentId, DocumentSummary};
+use zet::preamble::*;
+
+use crate::app::commands::{ReportFormat, SequenceArgs};
+
+#[derive(Debug, Clone, Copy)]
+pub enum SequenceStep {
+    Next,
+    Prev,
+    Children,
+}
+
+pub fn handle_command(root: &Path, step: SequenceStep, args: SequenceArgs) -> Result<()> {
+    let db = DB::open(zet::core::collection_db_file(root))?;
+    let documents = DocumentSummary::list(&db)?;
+    let id = DocumentId(args.id);
+    let neighbours = neighbours(&documents, &id)
+        .ok_or_else(|| eyre!("{} is not a position in a sequence", id.0))?;
+    let documents = match step {
+        SequenceStep::Next => Vec::from_iter(neighbours.next),
+        SequenceStep::Prev => Vec::from_iter(neighbours.prev),
+        SequenceStep::Children => neighbours.children,
+    };
+
+    let mut writer = std::io::BufWriter::new(std::io::stdout());
+    match args.output_format {
+        ReportFormat::Json if args.pretty => serde_json::to_writer_pretty(&mut writer, &documents)?,
+        ReportFormat::Json => serde_json::to_writer(&mut writer, &documents)?,
+        ReportFormat::Text => {
+            for document in &documents {
+                writeln!(
+                    writer,
+                    "{}\t{}\t{}",
+                    document.id.0,
+                    document.title,
+                    document.path.0.display()
+                )?;
+            }
+        }
+    }
+    writer.flush()?;
+
+    Ok(())
+}
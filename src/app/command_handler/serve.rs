@@ -0,0 +1,69 @@
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::eyre;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use zet::core::db::DB;
+use zet::core::metrics::Metrics;
+use zet::preamble::*;
+
+pub fn handle_command(root: PathBuf, address: SocketAddr, metrics: bool) -> Result<()> {
+    if !metrics {
+        return Err(eyre!("nothing to serve, see --metrics"));
+    }
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(async {
+            let listener = TcpListener::bind(address).await?;
+            log::info!(
+                "serving metrics at http://{}/metrics",
+                listener.local_addr()?
+            );
+            loop {
+                let (stream, peer) = listener.accept().await?;
+                let root = root.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = respond(&root, stream).await {
+                        log::warn!("could not respond to {}: {}", peer, e);
+                    }
+                });
+            }
+        })
+}
+
+/// Answer a single http request, closing the connection afterwards
+async fn respond(root: &Path, mut stream: TcpStream) -> Result<()> {
+    let mut reader = BufReader::new(&mut stream);
+    let mut request = String::new();
+    reader.read_line(&mut request).await?;
+    // the headers are of no interest
+    let mut header = String::new();
+    while reader.read_line(&mut header).await? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request.split_whitespace();
+    let (status, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => match render_metrics(root) {
+            Ok(body) => ("200 OK", body),
+            Err(e) => ("500 Internal Server Error", format!("{}\n", e)),
+        },
+        _ => ("404 Not Found", "not found\n".to_owned()),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\ncontent-type: text/plain; version=0.0.4; charset=utf-8\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+fn render_metrics(root: &Path) -> Result<String> {
+    let db = DB::open(zet::core::collection_db_file(root))?;
+    Ok(Metrics::collect(&db)?.render())
+}
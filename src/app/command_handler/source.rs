@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+
+use color_eyre::eyre::eyre;
+use serde::Serialize;
+use zet::core::db::{DB, DbList, DbQuery};
+use zet::core::query::DocumentQuery;
+use zet::core::readlist::URL_KEY;
+use zet::core::types::document::{DocumentId, DocumentSummary};
+use zet::core::types::link::DocumentLink;
+use zet::core::types::source::Source;
+use zet::core::url::normalize_url;
+use zet::preamble::*;
+
+use crate::app::commands::{ReportFormat, SourceCommand};
+
+#[derive(Debug, Serialize)]
+struct SourceReport {
+    #[serde(flatten)]
+    source: Source,
+    /// the documents currently linking to the source or having it as their
+    /// frontmatter url
+    documents: Vec<DocumentId>,
+}
+
+pub fn handle_command(root: &Path, command: SourceCommand) -> Result<()> {
+    match command {
+        SourceCommand::Check {
+            url,
+            output_format,
+            pretty,
+        } => check(root, &url, output_format, pretty),
+    }
+}
+
+/// Print the source `target` normalizes to, failing if it was never referenced
+fn check(root: &Path, target: &str, output_format: ReportFormat, pretty: bool) -> Result<()> {
+    let url = normalize_url(target).ok_or_else(|| eyre!("{target} is not an url or a DOI"))?;
+    let db = DB::open(zet::core::collection_db_file(root))?;
+    let source = Source::find(&db, &url)?.ok_or_else(|| eyre!("{url} was never saved"))?;
+
+    let titles: BTreeMap<DocumentId, String> = DocumentSummary::list(&db)?
+        .into_iter()
+        .map(|d| (d.id, d.title))
+        .collect();
+    let mut documents: Vec<DocumentId> = DocumentQuery::new()
+        .with_frontmatter(URL_KEY, Vec::new())
+        .execute(&db)?
+        .into_iter()
+        .filter(|d| {
+            d.data
+                .get(URL_KEY)
+                .and_then(|u| u.as_str())
+                .and_then(normalize_url)
+                .is_some_and(|u| u == url)
+        })
+        .map(|d| d.id)
+        .chain(
+            <DocumentLink as DbQuery<_, &str>>::list(&db, &url)?
+                .into_iter()
+                .map(|link| DocumentId::from(link.from)),
+        )
+        .collect();
+    documents.sort();
+    documents.dedup();
+
+    let mut writer = std::io::BufWriter::new(std::io::stdout());
+    match output_format {
+        ReportFormat::Json => {
+            let report = SourceReport { source, documents };
+            match pretty {
+                true => serde_json::to_writer_pretty(&mut writer, &report)?,
+                false => serde_json::to_writer(&mut writer, &report)?,
+            }
+        }
+        ReportFormat::Text => {
+            writeln!(
+                writer,
+                "{}\t{}\t{}",
+                source.url, source.first_seen, source.last_seen
+            )?;
+            for id in documents {
+                let title = titles.get(&id).map_or("", String::as_str);
+                writeln!(writer, "{}\t{}", id.0, title)?;
+            }
+        }
+    }
+    writer.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use std::path::Path;
+
+use color_eyre::eyre::eyre;
+use normalize_path::NormalizePath;
+use zet::config::Config;
+use zet::core::db::DB;
+use zet::core::split::{CrossLinks, plan};
+use zet::preamble::*;
+
+pub fn handle_command(
+    root: &Path,
+    config: &Config,
+    directory: &Path,
+    out: &Path,
+    cross_links: CrossLinks,
+    dry_run: bool,
+) -> Result<()> {
+    let directory = std::path::absolute(directory)?.normalize();
+    if !directory.starts_with(root) || directory == root {
+        return Err(eyre!(
+            "{:?} is not a directory in the collection",
+            directory
+        ));
+    }
+    let new_root = std::path::absolute(out)?.normalize();
+    if new_root.starts_with(root) {
+        return Err(eyre!("{:?} is in the collection", new_root));
+    }
+    if new_root.exists() && std::fs::read_dir(&new_root)?.next().is_some() {
+        return Err(eyre!("{:?} is not empty", new_root));
+    }
+
+    // the notes, their ids and their links are taken from the index, which
+    // has to be up to date
+    super::index::handle_command(root, Config::resolve(root)?, false, false)?;
+    let db = DB::open(zet::core::collection_db_file(root))?;
+    let plan = plan(root, config, &db, &directory, &new_root, cross_links)?;
+
+    for note in &plan.notes {
+        println!(
+            "{} -> {}",
+            relative(root, &note.document.path.0).display(),
+            note.to.display()
+        );
+    }
+    for (from, to, copied) in &plan.assets {
+        let action = if *copied { "copied" } else { "->" };
+        println!(
+            "{} {} {}",
+            relative(root, from).display(),
+            action,
+            to.display()
+        );
+    }
+    for stub in plan.old_stubs.iter().chain(&plan.new_stubs) {
+        println!("stub {}", stub.path.display());
+    }
+    if dry_run {
+        return Ok(());
+    }
+
+    drop(db);
+    plan.apply()?;
+    // the directory is left if it holds files that are not notes or assets
+    remove_empty_dirs(&directory)?;
+    super::index::handle_command(&new_root, Config::resolve(&new_root)?, false, false)?;
+    super::index::handle_command(root, Config::resolve(root)?, false, false)
+}
+
+/// Remove `dir` and its subdirectories if they are empty
+fn remove_empty_dirs(dir: &Path) -> Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            remove_empty_dirs(&path)?;
+        }
+    }
+    if std::fs::read_dir(dir)?.next().is_none() {
+        std::fs::remove_dir(dir)?;
+    }
+    Ok(())
+}
+
+fn relative<'a>(root: &Path, path: &'a Path) -> &'a Path {
+    path.strip_prefix(root).unwrap_or(path)
+}
@@ -0,0 +1,117 @@
+use std::path::Path;
+
+use color_eyre::eyre::eyre;
+use jiff::Timestamp;
+use zet::config::Config;
+use zet::core::LinkTargets;
+use zet::core::db::{DB, DbGet, DbList};
+use zet::core::flavor::DocumentSettings;
+use zet::core::lock::Locks;
+use zet::core::parser::DocumentParser;
+use zet::core::split_note::{HeadingLinks, MovedHeading, split_note};
+use zet::core::types::document::{Document, DocumentId};
+use zet::preamble::*;
+
+use super::import::{ImportGroup, finish};
+
+/// Split the note `id` at its headings of `level`, into notes next to it
+pub fn handle_command(
+    root: &Path,
+    config: &Config,
+    id: &str,
+    level: u8,
+    embed: bool,
+    no_index: bool,
+) -> Result<()> {
+    let mut db = DB::open(zet::core::collection_db_file(root))?;
+    let document = Document::get(&mut db, &DocumentId(id.to_owned()))
+        .map_err(|_| eyre!("no document with id '{}'", id))?;
+    let targets = LinkTargets::load(&db)?;
+    let mut documents = Document::list(&db)?;
+    documents.sort_by(|a, b| a.path.0.cmp(&b.path.0));
+    drop(db);
+
+    let path = document.path.0.clone();
+    let content = std::fs::read_to_string(&path)?;
+    let locks = Locks::load(root, config.front_matter_format)?;
+    if locks.is_locked(&path, &content) {
+        return Err(eyre!("the document '{}' is locked", id));
+    }
+    let settings = DocumentSettings::of(config, &content)?;
+    let parser = DocumentParser::from_config(&settings.parser);
+    let split = split_note(&content, level, &parser)?;
+    if split.sections.is_empty() {
+        return Err(eyre!(
+            "the document '{}' has no headings of level {}",
+            id,
+            level
+        ));
+    }
+
+    let directory = path.parent().unwrap_or(root);
+    let group = ImportGroup::of_directory(root, config, directory)?;
+    let now = Timestamp::now().to_zoned(config.timezone()?);
+    let mut created = Vec::with_capacity(split.sections.len());
+    let mut moved = Vec::new();
+    for section in &split.sections {
+        let note = group.new_note(&section.title, &now)?;
+        let (new_id, title, new_path) =
+            group.write_note(note, section.title.clone(), &now, &section.content, &[])?;
+        for (i, anchor) in section.anchors.iter().enumerate() {
+            moved.push(MovedHeading {
+                anchor: anchor.clone(),
+                id: new_id.clone(),
+                path: new_path.clone(),
+                top: i == 0,
+            });
+        }
+        created.push((new_id, title, new_path));
+    }
+
+    let replacements: Vec<String> = created
+        .iter()
+        .map(|(new_id, title, _)| match embed {
+            true => format!("![[{}]]", new_id.0),
+            false => format!("[[{}|{}]]", new_id.0, title),
+        })
+        .collect();
+    let links = HeadingLinks {
+        split: &document.id,
+        split_path: &path,
+        moved: &moved,
+        targets: &targets,
+    };
+    let edited = split.replace(&replacements);
+    let edited = links
+        .retarget(&edited, &path, &document.id, &document.id)?
+        .unwrap_or(edited);
+    std::fs::write(&path, edited)?;
+    println!("{}", path.display());
+
+    // the links of the sections resolve as they did in the note
+    for (new_id, _, new_path) in &created {
+        let content = std::fs::read_to_string(new_path)?;
+        if let Some(edited) = links.retarget(&content, new_path, &document.id, new_id)? {
+            std::fs::write(new_path, edited)?;
+        }
+    }
+
+    for other in documents.iter().filter(|d| d.id != document.id) {
+        let other_path = &other.path.0;
+        let content = std::fs::read_to_string(other_path)?;
+        let Some(edited) = links.retarget(&content, other_path, &other.id, &other.id)? else {
+            continue;
+        };
+        if locks.is_locked(other_path, &content) {
+            log::warn!(
+                "not updating the links of the locked document {:?}",
+                other_path
+            );
+            continue;
+        }
+        std::fs::write(other_path, edited)?;
+        println!("{}", other_path.display());
+    }
+
+    finish(root, config, created, no_index)
+}
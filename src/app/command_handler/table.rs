@@ -0,0 +1,93 @@
+use std::path::Path;
+
+use color_eyre::eyre::eyre;
+use zet::config::Config;
+use zet::core::db::{DB, DbGet};
+use zet::core::flavor::DocumentSettings;
+use zet::core::lock::Locks;
+use zet::core::parser::DocumentParser;
+use zet::core::table::{Table, TableFormat, insert_table, tables};
+use zet::core::types::document::{Document, DocumentId};
+use zet::preamble::*;
+
+use crate::app::commands::TableCommand;
+
+pub fn handle_command(root: &Path, config: &Config, command: TableCommand) -> Result<()> {
+    match command {
+        TableCommand::Import {
+            file,
+            into,
+            under,
+            format,
+            no_index,
+        } => {
+            let format = format
+                .or_else(|| TableFormat::of_path(&file))
+                .ok_or_else(|| eyre!("the format of {:?} is unknown, pass --format", file))?;
+            let data = std::fs::read_to_string(&file)
+                .map_err(|e| eyre!("could not read {:?}: {}", file, e))?;
+            let table = Table::parse(&data, format)?;
+
+            let path = document_path(root, &into)?;
+            let content = std::fs::read_to_string(&path)?;
+            if Locks::load(root, config.front_matter_format)?.is_locked(&path, &content) {
+                return Err(eyre!("the document '{}' is locked", into));
+            }
+            let parser = parser(config, &content)?;
+            let edited = insert_table(&content, &under, &table, &parser)?;
+            if edited == content {
+                return Ok(());
+            }
+            std::fs::write(&path, edited)?;
+            println!("{}", path.display());
+
+            if !no_index {
+                super::index::handle_command(root, Config::resolve(root)?, false, false)?;
+            }
+        }
+        TableCommand::Export {
+            id,
+            nth,
+            format,
+            output,
+        } => {
+            let path = document_path(root, &id)?;
+            let content = std::fs::read_to_string(&path)?;
+            let mut tables = tables(&content, &parser(config, &content)?)?;
+            let count = tables.len();
+            if nth == 0 || nth > count {
+                return Err(eyre!(
+                    "the document '{}' has {} tables, there is no table {}",
+                    id,
+                    count,
+                    nth
+                ));
+            }
+            let table = tables.swap_remove(nth - 1);
+            let exported = match format {
+                TableFormat::Csv => table.to_csv(),
+                TableFormat::Json => {
+                    format!("{}\n", serde_json::to_string_pretty(&table.to_json())?)
+                }
+            };
+            match output {
+                Some(path) => std::fs::write(path, exported)?,
+                None => print!("{exported}"),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn document_path(root: &Path, id: &str) -> Result<std::path::PathBuf> {
+    let mut db = DB::open(zet::core::collection_db_file(root))?;
+    let document = Document::get(&mut db, &DocumentId(id.to_owned()))
+        .map_err(|_| eyre!("no document with id '{}'", id))?;
+    Ok(document.path.0)
+}
+
+/// The parser of `document`, by the flavor it is written in
+fn parser(config: &Config, document: &str) -> Result<DocumentParser> {
+    let settings = DocumentSettings::of(config, document)?;
+    Ok(DocumentParser::from_config(&settings.parser))
+}
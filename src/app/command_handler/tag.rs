@@ -0,0 +1,35 @@
+use std::path::Path;
+
+use zet::config::Config;
+use zet::core::db::DB;
+use zet::core::lock::Locks;
+use zet::core::tags::rename_tags;
+use zet::preamble::*;
+
+use crate::app::commands::TagCommand;
+
+pub fn handle_command(root: &Path, config: &Config, command: TagCommand) -> Result<()> {
+    let (from, to) = match command {
+        TagCommand::Rename { old, new } => (vec![old], new),
+        TagCommand::Merge { tags, into } => (tags, into),
+    };
+    // tags are stored lowercased, and may be given as written inline
+    let normalize = |tag: &str| tag.trim_start_matches('#').to_lowercase();
+    let from: Vec<String> = from.iter().map(|tag| normalize(tag)).collect();
+    let to = normalize(&to);
+
+    // the tags are found using the index, which has to be up to date
+    super::index::handle_command(root, Config::resolve(root)?, false, false)?;
+    let db = DB::open(zet::core::collection_db_file(root))?;
+    let locks = Locks::load(root, config.front_matter_format)?;
+    let paths = rename_tags(&db, &from, &to, config.front_matter_format, &locks)?;
+    drop(db);
+
+    for path in &paths {
+        println!("{}", path.strip_prefix(root).unwrap_or(path).display());
+    }
+    if paths.is_empty() {
+        return Ok(());
+    }
+    super::index::handle_command(root, Config::resolve(root)?, false, false)
+}
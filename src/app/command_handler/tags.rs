@@ -0,0 +1,53 @@
+use std::io::Write;
+use std::path::Path;
+
+use zet::core::db::DB;
+use zet::core::tags::{TagTree, list_tags, tag_tree};
+use zet::preamble::*;
+
+use crate::app::commands::ReportFormat;
+
+pub fn handle_command(
+    root: &Path,
+    prefix: Option<String>,
+    tree: bool,
+    output_format: ReportFormat,
+    pretty: bool,
+) -> Result<()> {
+    let db = DB::open(zet::core::collection_db_file(root))?;
+    let prefix = prefix.unwrap_or_default();
+    let tags = list_tags(&db, prefix.trim_start_matches('#'))?;
+
+    let mut writer = std::io::BufWriter::new(std::io::stdout());
+    match (output_format, tree) {
+        (ReportFormat::Json, false) if pretty => serde_json::to_writer_pretty(&mut writer, &tags)?,
+        (ReportFormat::Json, false) => serde_json::to_writer(&mut writer, &tags)?,
+        (ReportFormat::Json, true) if pretty => {
+            serde_json::to_writer_pretty(&mut writer, &tag_tree(&tags))?
+        }
+        (ReportFormat::Json, true) => serde_json::to_writer(&mut writer, &tag_tree(&tags))?,
+        (ReportFormat::Text, false) => {
+            for tag in &tags {
+                writeln!(writer, "{:>4}  {}", tag.documents, tag.tag)?;
+            }
+        }
+        (ReportFormat::Text, true) => write_tree(&mut writer, &tag_tree(&tags), 0)?,
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+fn write_tree(writer: &mut impl Write, nodes: &[TagTree], depth: usize) -> Result<()> {
+    for node in nodes {
+        writeln!(
+            writer,
+            "{:>4}  {}{}",
+            node.documents,
+            "  ".repeat(depth),
+            node.name
+        )?;
+        write_tree(writer, &node.children, depth + 1)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+use zet::config::Config;
+use zet::core::db::{DB, DbList};
+use zet::core::mentions::{people, resolve};
+use zet::core::types::document::{DocumentId, DocumentSummary};
+use zet::core::types::task::{DocumentTask, TaskStatus};
+use zet::preamble::*;
+
+use crate::app::commands::ReportFormat;
+
+/// Print the tasks of the collection under the title of their document,
+/// only the ones owned by `owner` if given
+pub fn handle_command(
+    root: &Path,
+    config: &Config,
+    owner: Option<String>,
+    action_items: bool,
+    open: bool,
+    output_format: ReportFormat,
+    pretty: bool,
+) -> Result<()> {
+    let db = DB::open(zet::core::collection_db_file(root))?;
+    let names = match owner {
+        Some(owner) => {
+            let owner = owner.trim_start_matches('@').to_owned();
+            let people = people(root, config, &db)?;
+            Some(match resolve(&people, &owner) {
+                Some(person) => person.names.clone(),
+                None => vec![owner],
+            })
+        }
+        None => None,
+    };
+
+    let tasks: Vec<DocumentTask> = DocumentTask::list(&db)?
+        .into_iter()
+        .filter(|t| !action_items || t.action_item)
+        .filter(|t| !open || matches!(t.status, TaskStatus::Todo | TaskStatus::InProgress))
+        .filter(|t| match (&names, &t.owner) {
+            (None, _) => true,
+            (Some(names), Some(owner)) => names.iter().any(|n| n.eq_ignore_ascii_case(owner)),
+            (Some(_), None) => false,
+        })
+        .collect();
+
+    let mut writer = std::io::BufWriter::new(std::io::stdout());
+    match output_format {
+        ReportFormat::Json if pretty => serde_json::to_writer_pretty(&mut writer, &tasks)?,
+        ReportFormat::Json => serde_json::to_writer(&mut writer, &tasks)?,
+        ReportFormat::Text => {
+            let titles: HashMap<DocumentId, String> = DocumentSummary::list(&db)?
+                .into_iter()
+                .map(|d| (d.id, d.title))
+                .collect();
+            for (i, group) in tasks
+                .chunk_by(|a, b| a.document_id == b.document_id)
+                .enumerate()
+            {
+                let id = &group[0].document_id;
+                if i > 0 {
+                    writeln!(writer)?;
+                }
+                match titles.get(id).filter(|title| !title.is_empty()) {
+                    Some(title) => writeln!(writer, "{} ({})", title, id.0)?,
+                    None => writeln!(writer, "{}", id.0)?,
+                }
+                for task in group {
+                    writeln!(writer, "- {} {}", task.status.marker(), task.content)?;
+                }
+            }
+        }
+    }
+    writer.flush()?;
+
+    Ok(())
+}
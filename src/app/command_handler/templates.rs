@@ -0,0 +1,89 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::eyre;
+use serde::Serialize;
+use zet::core::template_engine::{
+    DEFAULT_TEMPLATE, list_templates, load_template_file, template_path, template_variables,
+};
+use zet::preamble::*;
+
+use crate::app::commands::{ReportFormat, TemplatesCommand};
+
+#[derive(Debug, Serialize)]
+struct TemplateSummary {
+    name: String,
+    path: PathBuf,
+    variables: Vec<String>,
+}
+
+pub fn handle_command(root: &Path, command: TemplatesCommand) -> Result<()> {
+    match command {
+        TemplatesCommand::List {
+            output_format,
+            pretty,
+        } => handle_list(root, output_format, pretty),
+        TemplatesCommand::Show { name } => handle_show(root, &name),
+        TemplatesCommand::New { name } => handle_new(root, &name),
+    }
+}
+
+fn handle_list(root: &Path, output_format: ReportFormat, pretty: bool) -> Result<()> {
+    let mut templates = Vec::new();
+    for template in list_templates(root)? {
+        let content = std::fs::read_to_string(&template.path)?;
+        // a broken template is still listed, `zet templates show` reports why
+        let variables = template_variables(root, &content).unwrap_or_else(|e| {
+            log::warn!("could not parse template {}: {}", template.name, e);
+            Vec::new()
+        });
+        templates.push(TemplateSummary {
+            name: template.name,
+            path: template.path,
+            variables,
+        });
+    }
+
+    let mut writer = std::io::BufWriter::new(std::io::stdout());
+    match output_format {
+        ReportFormat::Json if pretty => serde_json::to_writer_pretty(&mut writer, &templates)?,
+        ReportFormat::Json => serde_json::to_writer(&mut writer, &templates)?,
+        ReportFormat::Text => {
+            let width = templates.iter().map(|t| t.name.len()).max().unwrap_or(0);
+            for t in &templates {
+                writeln!(writer, "{:<width$}  {}", t.name, t.variables.join(", "))?;
+            }
+        }
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+fn handle_show(root: &Path, name: &str) -> Result<()> {
+    let content = load_template_file(root, name)?;
+    let variables = template_variables(root, &content)?;
+
+    let mut writer = std::io::BufWriter::new(std::io::stdout());
+    writeln!(writer, "{}", template_path(root, name).display())?;
+    writeln!(writer, "variables: {}", variables.join(", "))?;
+    writeln!(writer)?;
+    write!(writer, "{}", content)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+fn handle_new(root: &Path, name: &str) -> Result<()> {
+    let path = template_path(root, name);
+    if path.exists() {
+        return Err(eyre!("the template {} already exists at {:?}", name, path));
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, DEFAULT_TEMPLATE)?;
+    println!("{}", std::path::absolute(&path)?.display());
+
+    Ok(())
+}
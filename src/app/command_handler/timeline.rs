@@ -0,0 +1,79 @@
+use std::io::Write;
+use std::path::Path;
+
+use zet::config::Config;
+use zet::core::date_parser::TimeRange;
+use zet::core::db::DB;
+use zet::core::journal::Period;
+use zet::core::query::{DocumentQuery, SortByOption, SortOrder};
+use zet::core::timeline::{TimelineEntryKind, timeline};
+use zet::preamble::*;
+
+use crate::app::commands::ReportFormat;
+
+/// Print the timeline of the notes with all of `tags` and matching `pattern`,
+/// by `period`
+#[allow(clippy::too_many_arguments)]
+pub fn handle_command(
+    root: &Path,
+    config: &Config,
+    tags: Vec<String>,
+    pattern: Option<String>,
+    range: Option<TimeRange>,
+    period: Period,
+    output_format: ReportFormat,
+    pretty: bool,
+) -> Result<()> {
+    let db = DB::open(zet::core::collection_db_file(root))?;
+    let tz = config.timezone()?;
+
+    let mut query = DocumentQuery::new()
+        .with_tags(
+            tags.iter()
+                .map(|tag| tag.trim_start_matches('#').to_owned())
+                .collect(),
+        )
+        .order_by(SortByOption::Id, SortOrder::Ascending);
+    if let Some(pattern) = pattern {
+        query = query.with_match(pattern);
+    }
+    let documents = query.execute(&db)?;
+    let range = range.map(|range| range.dates(&tz));
+    let buckets = timeline(
+        &db,
+        &documents,
+        period,
+        range,
+        &tz,
+        &config.dates.week_start,
+    )?;
+
+    let mut writer = std::io::BufWriter::new(std::io::stdout());
+    match output_format {
+        ReportFormat::Json if pretty => serde_json::to_writer_pretty(&mut writer, &buckets)?,
+        ReportFormat::Json => serde_json::to_writer(&mut writer, &buckets)?,
+        ReportFormat::Text => {
+            for (i, bucket) in buckets.iter().enumerate() {
+                if i > 0 {
+                    writeln!(writer)?;
+                }
+                writeln!(writer, "{}", bucket.title)?;
+                for entry in &bucket.entries {
+                    let kind = match entry.kind {
+                        TimelineEntryKind::Note => "",
+                        TimelineEntryKind::Event => "event: ",
+                        TimelineEntryKind::Task => "task: ",
+                    };
+                    writeln!(
+                        writer,
+                        "- {} {}{} ({})",
+                        entry.date, kind, entry.title, entry.document_id.0
+                    )?;
+                }
+            }
+        }
+    }
+    writer.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use std::io::Write;
+use std::path::Path;
+
+use color_eyre::eyre::eyre;
+use serde::Serialize;
+use zet::config::Config;
+use zet::core::LinkTargets;
+use zet::core::db::{DB, DbList};
+use zet::core::parser::FrontMatterParser;
+use zet::core::parser::comments::strip_comments;
+use zet::core::query::DocumentQuery;
+use zet::core::read::read_document_logged;
+use zet::core::transclusion::Transclusion;
+use zet::core::types::document::{Document, DocumentId};
+use zet::core::words::count_words;
+use zet::preamble::*;
+
+use crate::app::commands::ReportFormat;
+
+#[derive(Debug, Serialize)]
+struct WordCount {
+    id: DocumentId,
+    title: String,
+    words: usize,
+}
+
+/// Print the number of words of the documents `ids`, or of every document,
+/// followed by their total when there are several
+pub fn handle_command(
+    root: &Path,
+    config: &Config,
+    ids: Vec<String>,
+    transclude: bool,
+    output_format: ReportFormat,
+    pretty: bool,
+) -> Result<()> {
+    let db = DB::open(zet::core::collection_db_file(root))?;
+    let mut documents = Vec::with_capacity(ids.len());
+    for id in &ids {
+        match DocumentQuery::new()
+            .with_ids(vec![id.clone()])
+            .execute(&db)?
+            .pop()
+        {
+            Some(document) => documents.push(document),
+            None => return Err(eyre!("no document with the id {:?}", id)),
+        }
+    }
+    if ids.is_empty() {
+        documents = Document::list(&db)?;
+    }
+
+    let targets = LinkTargets::load(&db)?;
+    let transclusion = Transclusion::new(&targets, config.front_matter_format);
+    let mut counts = Vec::with_capacity(documents.len());
+    for document in documents {
+        let Some(content) = read_document_logged(&document.path.0, &config.index)?.content else {
+            continue;
+        };
+        let (_, mut body) = FrontMatterParser::new(config.front_matter_format).parse(content);
+        if transclude {
+            body = transclusion.expand(&document.id, &body)?;
+        }
+        counts.push(WordCount {
+            words: count_words(&strip_comments(&body, &config.parser.comments)),
+            id: document.id,
+            title: document.title,
+        });
+    }
+
+    let mut writer = std::io::BufWriter::new(std::io::stdout());
+    match output_format {
+        ReportFormat::Json => match pretty {
+            true => serde_json::to_writer_pretty(&mut writer, &counts)?,
+            false => serde_json::to_writer(&mut writer, &counts)?,
+        },
+        ReportFormat::Text => {
+            for count in &counts {
+                writeln!(writer, "{}\t{}\t{}", count.id.0, count.title, count.words)?;
+            }
+            if counts.len() > 1 {
+                let total: usize = counts.iter().map(|count| count.words).sum();
+                writeln!(writer, "total\t\t{}", total)?;
+            }
+        }
+    }
+    writer.flush()?;
+
+    Ok(())
+}
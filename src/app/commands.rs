@@ -2,13 +2,26 @@ use chumsky::Parser;
 use chumsky::extra;
 use chumsky::prelude::choice;
 use chumsky::prelude::just;
+use clap::ArgGroup;
+use clap::Args;
 use clap::Subcommand;
 use clap::ValueEnum;
 use color_eyre::eyre::eyre;
 use jiff::Timestamp;
+use std::convert::Infallible;
 use std::fmt::Display;
+use std::net::SocketAddr;
 use std::path::PathBuf;
-use zet::core::date_parser::NaturalDateParser;
+use std::str::FromStr;
+use zet::config::{Config, DuplicatePolicy};
+use zet::core::board::BoardGroupBy;
+use zet::core::date_parser::{NaturalDateParser, TimeRange};
+use zet::core::format::LinkStyle;
+use zet::core::journal::Period;
+use zet::core::merge::CollisionStrategy;
+use zet::core::query::DocumentFilter;
+use zet::core::split::CrossLinks;
+use zet::core::table::TableFormat;
 
 #[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Debug)]
@@ -23,7 +36,13 @@ pub enum Command {
         #[arg(long, default_value_t = false)]
         /// clear the cache and reindex the entire collection
         force: bool,
+        #[arg(long)]
+        /// only print the documents that would be added, updated and removed
+        dry_run: bool,
     },
+    /// Show how the titles, links and tasks of the index would change if the
+    /// collection was indexed, without indexing it
+    DiffIndex,
     Init {
         root: Option<PathBuf>,
         #[arg(long, default_value_t = false)]
@@ -58,18 +77,20 @@ pub enum Command {
         ////////////////////////////////////////////////////////////
         // created and modified timestamps
         ////////////////////////////////////////////////////////////
-        #[arg(long, value_parser=natural_language_parser)]
-        created: Option<Timestamp>,
-        #[arg(long, value_parser=natural_language_parser)]
-        modified: Option<Timestamp>,
-        #[arg(long, value_parser=natural_language_parser)]
-        created_before: Option<Timestamp>,
-        #[arg(long, value_parser=natural_language_parser)]
-        created_after: Option<Timestamp>,
-        #[arg(long, value_parser=natural_language_parser)]
-        modified_before: Option<Timestamp>,
-        #[arg(long, value_parser=natural_language_parser)]
-        modified_after: Option<Timestamp>,
+        #[arg(long)]
+        /// list notes created within a date range, e.g. "this week" or "from monday to friday"
+        created: Option<DateRangeExpr>,
+        #[arg(long)]
+        /// list notes modified within a date range
+        modified: Option<DateRangeExpr>,
+        #[arg(long)]
+        created_before: Option<DateExpr>,
+        #[arg(long)]
+        created_after: Option<DateExpr>,
+        #[arg(long)]
+        modified_before: Option<DateExpr>,
+        #[arg(long)]
+        modified_after: Option<DateExpr>,
 
         ////////////////////////////////////////////////////////////
         // links
@@ -99,6 +120,10 @@ pub enum Command {
         #[arg(long)]
         /// limit the number of results returned
         limit: Option<usize>,
+        #[arg(long, requires = "limit")]
+        /// the page of results to return, counting from 1, each page holding
+        /// `limit` results
+        page: Option<usize>,
         #[arg(long, value_enum, default_value_t=OutputFormat::Template)]
         /// how each document should be formatted
         output_format: OutputFormat,
@@ -112,14 +137,34 @@ pub enum Command {
         template: Option<String>,
     },
     Lsp,
-    Format,
+    /// Format the documents of the collection as configured in `[format]`
+    Format {
+        /// the documents to format, defaults to all documents of the collection
+        paths: Vec<PathBuf>,
+        #[arg(long)]
+        /// only list the documents that are not formatted
+        check: bool,
+        #[arg(long, value_enum)]
+        /// the style to write links in, overriding `format.link_style`
+        link_style: Option<LinkStyle>,
+        #[arg(long)]
+        /// the column to wrap paragraphs at, overriding `format.wrap`
+        wrap: Option<usize>,
+        #[arg(long)]
+        /// pad the cells of tables, as if `format.tables` was set
+        tables: bool,
+        #[arg(long)]
+        /// write typographic punctuation, as if `format.smart_punctuation` was set
+        smart_punctuation: bool,
+    },
     RawParse {
         path: PathBuf,
     },
     /// Create a new note from a template
     Create {
         /// Title of the new note
-        title: String,
+        #[arg(required_unless_present = "from_stub")]
+        title: Option<String>,
         /// Optional inline content (mapped to {{content}})
         content: Option<String>,
         /// Select a group (determines template + output directory)
@@ -131,6 +176,10 @@ pub enum Command {
         /// Read content from stdin (mutually exclusive with content arg)
         #[arg(long, default_value_t = false)]
         stdin: bool,
+        /// Seed the content from a file or a url, html pages being converted
+        /// to markdown, see `fetch.command` and `fetch.html_command`
+        #[arg(long, conflicts_with_all = ["content", "stdin"])]
+        from: Option<String>,
         /// Inline arbitrary data as JSON
         #[arg(long)]
         data_json: Option<String>,
@@ -143,6 +192,938 @@ pub enum Command {
         /// Load arbitrary data from a TOML file
         #[arg(long)]
         data_toml_path: Option<PathBuf>,
+        /// Materialize the stub with the given id, using its title
+        #[arg(long, conflicts_with = "title")]
+        from_stub: Option<String>,
+        /// Leave the new note for the next `zet index` instead of indexing it
+        /// right away
+        #[arg(long, default_value_t = false)]
+        no_index: bool,
+        /// Open the new note in the editor, see `editor.open_on_create`
+        #[arg(long, default_value_t = false)]
+        open: bool,
+        #[command(flatten)]
+        duplicate: DuplicateArgs,
+    },
+    /// List the documents of the collection
+    List {
+        #[arg(long)]
+        /// list stub documents (unresolved wikilink targets) instead
+        stubs: bool,
+        #[arg(long, conflicts_with = "stubs")]
+        /// only list documents created within a date range
+        created: Option<DateRangeExpr>,
+        #[arg(long, conflicts_with = "stubs")]
+        /// only list documents modified within a date range
+        modified: Option<DateRangeExpr>,
+        #[arg(long)]
+        /// list at most `limit` documents
+        limit: Option<usize>,
+        #[arg(long, requires = "limit")]
+        /// the page of documents to list, counting from 1, each page holding
+        /// `limit` documents
+        page: Option<usize>,
+        #[arg(long, value_enum, default_value_t=ReportFormat::Text)]
+        output_format: ReportFormat,
+        #[arg(long)]
+        /// whether json output should be pretty printed or not
+        pretty: bool,
+    },
+    /// Analyse the link graph of the collection
+    Graph {
+        #[command(subcommand)]
+        command: GraphCommand,
+    },
+    /// Periodic (daily, weekly, monthly) notes. Without a subcommand, print
+    /// the path of the note of a period, e.g. `zet journal --prev` for the
+    /// latest daily note before today
+    #[command(args_conflicts_with_subcommands = true)]
+    Journal {
+        #[command(subcommand)]
+        command: Option<JournalCommand>,
+        #[arg(long, value_enum, default_value_t=Period::Daily)]
+        period: Period,
+        #[arg(long)]
+        /// the date of the period, defaults to today
+        date: Option<DateExpr>,
+        #[arg(long, conflicts_with = "next")]
+        /// the nearest note before the period instead
+        prev: bool,
+        #[arg(long)]
+        /// the nearest note after the period instead
+        next: bool,
+    },
+    /// Generate a summary note of the tasks completed and notes created and
+    /// modified in a period
+    #[command(group(ArgGroup::new("rollup_period").required(true).args(["day", "week", "month", "range"])))]
+    Rollup {
+        #[arg(long)]
+        /// the day containing the given date
+        day: Option<DateExpr>,
+        #[arg(long)]
+        /// the week containing the given date
+        week: Option<DateExpr>,
+        #[arg(long)]
+        /// the month containing the given date
+        month: Option<DateExpr>,
+        #[arg(long)]
+        /// an arbitrary date range, e.g. "last 3 weeks"
+        range: Option<DateRangeExpr>,
+        #[arg(long)]
+        /// overwrite an existing rollup note
+        force: bool,
+    },
+    /// Show the tasks of the collection as a kanban board
+    Board {
+        #[arg(long, value_enum, default_value_t=BoardGroupBy::Status)]
+        /// what the columns of the board are
+        group_by: BoardGroupBy,
+        #[arg(long, default_value_t = 30)]
+        /// width of each column in characters
+        width: usize,
+        #[arg(long, value_enum, default_value_t=ReportFormat::Text)]
+        output_format: ReportFormat,
+        #[arg(long)]
+        /// whether json output should be pretty printed or not
+        pretty: bool,
+    },
+    /// Check the formatting of the documents of the collection
+    Lint {
+        /// the documents to check, defaults to all documents of the collection
+        paths: Vec<PathBuf>,
+        #[arg(long)]
+        /// apply the fixes of the issues found
+        fix: bool,
+        #[arg(long, value_enum, default_value_t=ReportFormat::Text)]
+        output_format: ReportFormat,
+        #[arg(long)]
+        /// whether json output should be pretty printed or not
+        pretty: bool,
+    },
+    /// Rename the documents whose file names do not follow `lint.filename_pattern`,
+    /// rewriting the links to them
+    NormalizeFilenames {
+        #[arg(long)]
+        /// only print the renames
+        dry_run: bool,
+    },
+    /// Resolve a natural language date expression, e.g. "next friday at 3pm"
+    Date {
+        /// the expression to resolve
+        expression: String,
+        #[arg(long)]
+        /// resolve the expression as a range, e.g. "last 3 weeks"
+        range: bool,
+        #[arg(long, value_enum, default_value_t=ReportFormat::Text)]
+        output_format: ReportFormat,
+        #[arg(long)]
+        /// whether json output should be pretty printed or not
+        pretty: bool,
+    },
+    /// Edit the frontmatter of the documents matching a set of filters
+    Meta {
+        #[command(subcommand)]
+        command: MetaCommand,
+    },
+    /// List the tags of the collection and the number of documents tagged
+    /// with each
+    Tags {
+        /// only list the tags starting with `prefix`, e.g. "project/" for the
+        /// tags nested under project
+        prefix: Option<String>,
+        #[arg(long)]
+        /// show the nested tags as a tree
+        tree: bool,
+        #[arg(long, value_enum, default_value_t=ReportFormat::Text)]
+        output_format: ReportFormat,
+        #[arg(long)]
+        /// whether json output should be pretty printed or not
+        pretty: bool,
+    },
+    /// Rename and merge tags, both inline and in the frontmatter
+    Tag {
+        #[command(subcommand)]
+        command: TagCommand,
+    },
+    /// List the `==highlighted==` text of the collection, by document
+    Highlights {
+        #[arg(long)]
+        /// only list the highlights of the document with this id
+        document: Option<String>,
+        #[arg(long, value_enum, default_value_t=ReportFormat::Text)]
+        output_format: ReportFormat,
+        #[arg(long)]
+        /// whether json output should be pretty printed or not
+        pretty: bool,
+    },
+    /// List the block quotes of the collection by document, or compile them
+    /// into a note
+    Quotes {
+        #[arg(long)]
+        /// only list the quotes of the document with this id
+        document: Option<String>,
+        #[arg(long)]
+        /// only list the quotes attributed by a `— author` last line
+        attributed: bool,
+        #[arg(long)]
+        /// write the quotes into a new note with this title instead
+        note: Option<String>,
+        #[arg(long, requires = "note")]
+        /// overwrite an existing note
+        force: bool,
+        #[arg(long, value_enum, default_value_t=ReportFormat::Text)]
+        output_format: ReportFormat,
+        #[arg(long)]
+        /// whether json output should be pretty printed or not
+        pretty: bool,
+    },
+    /// List the documents mentioning a person, e.g. `zet mentions-of @alice`
+    MentionsOf {
+        /// the person, by the file name of their note, the slug of its title
+        /// or one of its aliases
+        person: String,
+        #[arg(long, value_enum, default_value_t=ReportFormat::Text)]
+        output_format: ReportFormat,
+        #[arg(long)]
+        /// whether json output should be pretty printed or not
+        pretty: bool,
+    },
+    /// List the documents linking to an external page or a DOI, e.g. `zet
+    /// refs https://example.com/post` or `zet refs 10.1000/182`. Urls are
+    /// matched normalized, without tracking parameters or trailing slashes.
+    Refs {
+        /// the url or DOI
+        target: String,
+        #[arg(long, value_enum, default_value_t=ReportFormat::Text)]
+        output_format: ReportFormat,
+        #[arg(long)]
+        /// whether json output should be pretty printed or not
+        pretty: bool,
+    },
+    /// The external pages and DOIs ever referenced from the collection
+    Source {
+        #[command(subcommand)]
+        command: SourceCommand,
+    },
+    /// List the tasks of the collection by document, e.g. the action items
+    /// of meeting notes with `zet tasks --owner alice`
+    Tasks {
+        #[arg(long)]
+        /// only list the tasks owned by this person, the tasks whose first
+        /// mention names them
+        owner: Option<String>,
+        #[arg(long)]
+        /// only list the action items, the owned tasks and the tasks under an
+        /// `Action items` heading
+        action_items: bool,
+        #[arg(long)]
+        /// only list the tasks to do or in progress
+        open: bool,
+        #[arg(long, value_enum, default_value_t=ReportFormat::Text)]
+        output_format: ReportFormat,
+        #[arg(long)]
+        /// whether json output should be pretty printed or not
+        pretty: bool,
+    },
+    /// List the document files the index does not read as they are, the ones
+    /// skipped or truncated for their size and the ones that are not valid
+    /// UTF-8
+    Doctor {
+        #[arg(long, value_enum, default_value_t=ReportFormat::Text)]
+        output_format: ReportFormat,
+        #[arg(long)]
+        /// whether json output should be pretty printed or not
+        pretty: bool,
+    },
+    /// List the notes matching a query and their events and dated tasks
+    /// chronologically, e.g. `zet timeline --tag project-x`. A note is dated
+    /// by its `date` frontmatter entry, or by its creation.
+    Timeline {
+        #[arg(long = "tag", value_delimiter = ',')]
+        /// only the notes with all of these tags
+        tags: Vec<String>,
+        #[arg(long = "match")]
+        /// only the notes matching this full text search pattern
+        match_pattern: Option<String>,
+        #[arg(long)]
+        /// only the entries within a date range, e.g. "this year"
+        range: Option<DateRangeExpr>,
+        #[arg(long, value_enum, default_value_t=Period::Monthly)]
+        /// the period the entries are grouped by
+        by: Period,
+        #[arg(long, value_enum, default_value_t=ReportFormat::Text)]
+        output_format: ReportFormat,
+        #[arg(long)]
+        /// whether json output should be pretty printed or not
+        pretty: bool,
+    },
+    /// The reading list, literature notes of web pages with a reading status
+    Readlist {
+        #[command(subcommand)]
+        command: ReadlistCommand,
+    },
+    /// Manage the images and other files referenced from documents
+    Assets {
+        #[command(subcommand)]
+        command: AssetsCommand,
+    },
+    /// Expand a snippet of `.zet/snippets.toml`
+    Expand {
+        /// the trigger of the snippet
+        trigger: String,
+        #[arg(long = "var", value_parser = parse_variable)]
+        /// an extra template variable, e.g. "author=kit"
+        variables: Vec<(String, String)>,
+        #[arg(long, default_value = "")]
+        /// id of the document the snippet is expanded in
+        id: String,
+        #[arg(long, default_value = "")]
+        /// title of the document the snippet is expanded in
+        title: String,
+    },
+    /// Export the collection to other formats
+    Export {
+        #[command(subcommand)]
+        command: ExportCommand,
+    },
+    /// Archive the documents, assets and configuration of the collection into
+    /// a single file, with a manifest of its contents
+    Backup {
+        #[arg(long)]
+        /// the archive to write, compressed with gzip when ending in .gz or
+        /// .tgz and with zstd when ending in .zst. Defaults to
+        /// zet-backup-<date>.tar.gz
+        out: Option<PathBuf>,
+        #[arg(long)]
+        /// also archive the index, keeping the history listed by `zet log`
+        include_db: bool,
+    },
+    /// Restore a collection from an archive written by `zet backup`, checking
+    /// it against its manifest, and index it
+    Restore {
+        /// the archive to restore
+        archive: PathBuf,
+        #[arg(long)]
+        /// the directory to restore into, defaults to the current directory
+        into: Option<PathBuf>,
+        #[arg(long)]
+        /// restore into a directory that is not empty, replacing the files
+        /// of the archive
+        force: bool,
+    },
+    /// Import the notes and assets of another collection, rewriting the links
+    /// between the imported notes, and index the result
+    Merge {
+        /// the root of the collection to import
+        other: PathBuf,
+        #[arg(long)]
+        /// the directory of this collection to import into, defaults to its
+        /// root
+        into: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t = CollisionStrategy::Fail)]
+        /// what to do with the notes and assets whose file or id is taken in
+        /// this collection
+        on_collision: CollisionStrategy,
+        #[arg(long)]
+        /// the prefix of colliding notes and assets, defaults to the name of
+        /// the other collection's directory
+        prefix: Option<String>,
+        #[arg(long)]
+        /// only print where the notes and assets would be imported to
+        dry_run: bool,
+    },
+    /// Import notes from other formats, e.g. emails forwarded to a mailbox
+    Import {
+        #[command(subcommand)]
+        command: ImportCommand,
+    },
+    /// Edit the outline of a document, moving, promoting and demoting its
+    /// sections with their subsections
+    Section {
+        #[command(subcommand)]
+        command: SectionCommand,
+    },
+    /// Merge two notes into one, e.g. `zet merge-notes apples pears --into
+    /// fruit`. Their bodies follow each other under headings of their titles,
+    /// their frontmatter is combined, the links to them are pointed to the
+    /// merged note and they are moved to .zet/trash.
+    MergeNotes {
+        /// the id of the first note
+        a: String,
+        /// the id of the second note
+        b: String,
+        #[arg(long)]
+        /// the id of the merged note, a new one or one of the two notes
+        into: String,
+        #[arg(long)]
+        /// title of the merged note, defaults to the title of the note merged
+        /// into or else of the first note
+        title: Option<String>,
+        /// Leave the notes for the next `zet index` instead of indexing them
+        /// right away
+        #[arg(long, default_value_t = false)]
+        no_index: bool,
+    },
+    /// Split a note by its headings, e.g. `zet split-note paper --level 2`.
+    /// Each section of the level becomes a note titled by its heading and is
+    /// replaced by a link to it, and the links to its headings are pointed
+    /// to the new note.
+    SplitNote {
+        /// the id of the note
+        id: String,
+        #[arg(long, default_value_t = 2)]
+        /// the level of the headings to split at
+        level: u8,
+        #[arg(long)]
+        /// replace the sections with embeds rather than links
+        embed: bool,
+        /// Leave the notes for the next `zet index` instead of indexing them
+        /// right away
+        #[arg(long, default_value_t = false)]
+        no_index: bool,
+    },
+    /// Put csv or json data into notes as markdown tables, and get the
+    /// tables of notes back as data
+    Table {
+        #[command(subcommand)]
+        command: TableCommand,
+    },
+    /// Move the notes and assets of a directory into a new collection
+    Split {
+        /// the directory to move
+        directory: PathBuf,
+        #[arg(long)]
+        /// the root of the new collection, a directory that does not exist or
+        /// is empty
+        out: PathBuf,
+        #[arg(long, value_enum, default_value_t = CrossLinks::Stub)]
+        /// what becomes of the links between the moved notes and the notes
+        /// that stay
+        cross_links: CrossLinks,
+        #[arg(long)]
+        /// only print what would be moved and stubbed
+        dry_run: bool,
+    },
+    /// Assemble the notes listed by an index note into a single document, e.g.
+    /// `zet compile book --out book.pdf`. The chapters are the notes the
+    /// first link or embed of each list item points to, in order, their
+    /// headings nested under the title of the book and the links between
+    /// them pointing to their headings.
+    Compile {
+        /// the id of the index note
+        id: String,
+        #[arg(long, short)]
+        /// the file to write, as markdown, pdf or epub by its extension. Pdf
+        /// and epub are converted with `export.pdf_command` and
+        /// `export.epub_command`.
+        out: PathBuf,
+        #[arg(long)]
+        /// title of the book, defaults to the title of the index note
+        title: Option<String>,
+    },
+    /// Count the words of documents, their text without markup, code blocks
+    /// and comments
+    Words {
+        /// ids of the documents to count, defaults to every document
+        ids: Vec<String>,
+        #[arg(long)]
+        /// count the embedded notes `![[note]]` as part of the embedding
+        /// one, recursively
+        transclude: bool,
+        #[arg(long, value_enum, default_value_t=ReportFormat::Text)]
+        output_format: ReportFormat,
+        #[arg(long)]
+        /// whether json output should be pretty printed or not
+        pretty: bool,
+    },
+    /// Find documents by their titles, aliases and headings, best match first
+    Find {
+        /// the characters to look for, in order, e.g. "mlnotes" for "Machine
+        /// learning notes"
+        query: String,
+        #[arg(long, default_value_t = 20)]
+        /// the number of matches to list
+        limit: usize,
+        #[arg(long, value_enum, default_value_t=ReportFormat::Text)]
+        output_format: ReportFormat,
+        #[arg(long)]
+        /// whether json output should be pretty printed or not
+        pretty: bool,
+    },
+    /// Serve the collection over http
+    Serve {
+        #[arg(long, default_value = "127.0.0.1:9184")]
+        /// the address to listen on
+        address: SocketAddr,
+        #[arg(long)]
+        /// serve the health metrics of the collection at /metrics, in the
+        /// Prometheus text format
+        metrics: bool,
+    },
+    /// List, inspect and create the templates in `.zet/templates`
+    Templates {
+        #[command(subcommand)]
+        command: TemplatesCommand,
+    },
+    /// The note read after the given one in its folgezettel sequence
+    Next {
+        #[command(flatten)]
+        args: SequenceArgs,
+    },
+    /// The note read before the given one in its folgezettel sequence
+    Prev {
+        #[command(flatten)]
+        args: SequenceArgs,
+    },
+    /// The notes branching off the given one in its folgezettel sequence,
+    /// e.g. `3a` and `3b` for `3`
+    Children {
+        #[command(flatten)]
+        args: SequenceArgs,
+    },
+    /// List the changes made to the index, oldest first
+    Log {
+        #[arg(long)]
+        /// only list the changes made since the given date, e.g. "yesterday"
+        since: Option<DateExpr>,
+        #[arg(long, value_enum, default_value_t=ReportFormat::Text)]
+        output_format: ReportFormat,
+        #[arg(long)]
+        /// whether json output should be pretty printed or not
+        pretty: bool,
+    },
+}
+
+impl Command {
+    /// Whether the command writes to the collection, to its documents or to
+    /// its index
+    pub fn is_mutating(&self) -> bool {
+        match self {
+            Command::Index { dry_run, .. }
+            | Command::NormalizeFilenames { dry_run }
+            | Command::Merge { dry_run, .. }
+            | Command::Split { dry_run, .. } => !dry_run,
+            Command::Format { check, .. } => !check,
+            Command::Lint { fix, .. } => *fix,
+            Command::Meta { command } => match command {
+                MetaCommand::Set { selection, .. }
+                | MetaCommand::Unset { selection, .. }
+                | MetaCommand::RenameKey { selection, .. } => !selection.dry_run,
+            },
+            Command::Assets { command } => match command {
+                AssetsCommand::Gc { dry_run } | AssetsCommand::Optimize { dry_run } => !dry_run,
+            },
+            Command::Templates { command } => matches!(command, TemplatesCommand::New { .. }),
+            Command::Quotes { note, .. } => note.is_some(),
+            Command::Readlist { command } => matches!(command, ReadlistCommand::Add { .. }),
+            Command::Journal { command, .. } => command.is_some(),
+            Command::Table { command } => matches!(command, TableCommand::Import { .. }),
+            Command::Graph {
+                command: GraphCommand::Stats { cache, .. },
+            } => *cache,
+            Command::Section { .. } | Command::SplitNote { .. } | Command::MergeNotes { .. } => {
+                true
+            }
+            Command::Init { .. }
+            | Command::Import { .. }
+            | Command::Restore { .. }
+            | Command::Create { .. }
+            | Command::Rollup { .. }
+            | Command::Tag { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct SequenceArgs {
+    /// the id of the note, e.g. "21/3a1" or "202603010930"
+    pub id: String,
+    #[arg(long, value_enum, default_value_t=ReportFormat::Text)]
+    pub output_format: ReportFormat,
+    #[arg(long)]
+    /// whether json output should be pretty printed or not
+    pub pretty: bool,
+}
+
+/// What `zet create` does when the note already exists, overriding the
+/// `on_duplicate` of the group. It fails by default.
+#[derive(Args, Debug)]
+#[group(multiple = false)]
+pub struct DuplicateArgs {
+    /// Create the note under the first free filename, appending -2, -3, ...
+    #[arg(long)]
+    pub suffix: bool,
+    /// Print the path of the existing note instead, opening it with --open
+    #[arg(long)]
+    pub open_existing: bool,
+    /// Overwrite the existing note
+    #[arg(long)]
+    pub force: bool,
+}
+
+impl DuplicateArgs {
+    pub fn policy(&self) -> Option<DuplicatePolicy> {
+        if self.suffix {
+            Some(DuplicatePolicy::Suffix)
+        } else if self.open_existing {
+            Some(DuplicatePolicy::OpenExisting)
+        } else if self.force {
+            Some(DuplicatePolicy::Force)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum MetaCommand {
+    /// Set a frontmatter key, the value is parsed as json and falls back to a string
+    Set {
+        key: String,
+        value: String,
+        #[command(flatten)]
+        selection: MetaSelection,
+    },
+    /// Remove a frontmatter key
+    Unset {
+        key: String,
+        #[command(flatten)]
+        selection: MetaSelection,
+    },
+    /// Rename a frontmatter key, keeping its value
+    RenameKey {
+        from: String,
+        to: String,
+        #[command(flatten)]
+        selection: MetaSelection,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TagCommand {
+    /// Rename a tag
+    Rename { old: String, new: String },
+    /// Replace several tags with a single one
+    Merge {
+        #[arg(required = true)]
+        tags: Vec<String>,
+        #[arg(long)]
+        /// the tag replacing the merged ones
+        into: String,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct MetaSelection {
+    #[arg(long = "filter")]
+    /// only edit the documents matching all filters, e.g. "tag:#book" or "path:journal/".
+    /// The keys are id, title, path, tag, links-to, links-from and match
+    pub filters: Vec<DocumentFilter>,
+    #[arg(long)]
+    /// only print a diff of the edits
+    pub dry_run: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AssetsCommand {
+    /// Delete the assets no document refers to
+    Gc {
+        #[arg(long)]
+        /// only list the assets
+        dry_run: bool,
+    },
+    /// Shrink the referenced images larger than `assets.max_size` using
+    /// `assets.optimize_command`
+    Optimize {
+        #[arg(long)]
+        /// only list the images
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TemplatesCommand {
+    /// List the templates and the variables they read
+    List {
+        #[arg(long, value_enum, default_value_t=ReportFormat::Text)]
+        output_format: ReportFormat,
+        #[arg(long)]
+        /// whether json output should be pretty printed or not
+        pretty: bool,
+    },
+    /// Print the variables and the content of a template
+    Show {
+        /// the name of the template, as given to `--template`
+        name: String,
+    },
+    /// Create a template from the default template
+    New {
+        /// the name of the template, as given to `--template`
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ReadlistCommand {
+    /// Add a note for the page at the url, with the title and metadata of
+    /// the page and the first of `readlist.statuses` as its status
+    Add {
+        url: String,
+        #[arg(long)]
+        /// the title of the note, instead of the title of the page
+        title: Option<String>,
+        /// Leave the new note for the next `zet index` instead of indexing it
+        /// right away
+        #[arg(long, default_value_t = false)]
+        no_index: bool,
+    },
+    /// List the notes of the reading list, the status of a note is changed
+    /// with `zet meta set status <status> --filter id:<id>`
+    List {
+        #[arg(long)]
+        /// only list the notes with one of these statuses
+        status: Vec<String>,
+        #[arg(long, value_enum, default_value_t=ReportFormat::Text)]
+        output_format: ReportFormat,
+        #[arg(long)]
+        /// whether json output should be pretty printed or not
+        pretty: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ImportCommand {
+    /// Import emails as notes of a group: the subject becomes the title, the
+    /// sender, date and message id the frontmatter, the text the content and
+    /// the attachments assets next to the note. Emails already imported, by
+    /// their message id, are skipped.
+    Eml {
+        /// an `.eml` file, a Maildir or a directory of `.eml` files
+        path: PathBuf,
+        #[arg(long, default_value = "inbox")]
+        /// the group to import into, by its first directory, template and id
+        /// scheme. A group that is not configured is a directory of the same
+        /// name.
+        group: String,
+        /// Leave the imported notes for the next `zet index` instead of
+        /// indexing them right away
+        #[arg(long, default_value_t = false)]
+        no_index: bool,
+    },
+    /// Import an OPML outline as a note of nested lists, titled by the
+    /// outline. Outlines with an url become links.
+    Opml {
+        /// the `.opml` file
+        path: PathBuf,
+        #[arg(long, default_value = "inbox")]
+        /// the group to import into, see `zet import eml`
+        group: String,
+        /// Leave the imported note for the next `zet index` instead of
+        /// indexing it right away
+        #[arg(long, default_value_t = false)]
+        no_index: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SectionCommand {
+    /// Move a section before or after another one, e.g. `zet section move
+    /// 202603010930 "Results" --after "Method"`. The levels of its headings
+    /// are kept.
+    #[command(group(ArgGroup::new("position").required(true).args(["before", "after"])))]
+    Move {
+        #[command(flatten)]
+        section: SectionArgs,
+        #[arg(long)]
+        /// the heading of the section to move it before
+        before: Option<String>,
+        #[arg(long)]
+        /// the heading of the section to move it after, after its
+        /// subsections
+        after: Option<String>,
+    },
+    /// Raise the headings of a section by a level, `### A` becoming `## A`
+    Promote {
+        #[command(flatten)]
+        section: SectionArgs,
+    },
+    /// Lower the headings of a section by a level, `## A` becoming `### A`
+    Demote {
+        #[command(flatten)]
+        section: SectionArgs,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct SectionArgs {
+    /// the id of the document
+    pub id: String,
+    /// the text of the heading of the section, matched regardless of case
+    pub heading: String,
+    /// Leave the document for the next `zet index` instead of indexing it
+    /// right away
+    #[arg(long, default_value_t = false)]
+    pub no_index: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TableCommand {
+    /// Put the data of a file as a table under a heading of a note, e.g.
+    /// `zet table import results.csv --into 202603010930 --under Data`. The
+    /// first table of the section is replaced, if there is one, and a
+    /// missing heading is added at the end of the note.
+    Import {
+        /// the `.csv` or `.json` file, its first row or the keys of its
+        /// objects being the header
+        file: PathBuf,
+        #[arg(long)]
+        /// the id of the note
+        into: String,
+        #[arg(long)]
+        /// the text of the heading, matched regardless of case
+        under: String,
+        #[arg(long, value_enum)]
+        /// the format of the file, defaults to the one of its extension
+        format: Option<TableFormat>,
+        /// Leave the note for the next `zet index` instead of indexing it
+        /// right away
+        #[arg(long, default_value_t = false)]
+        no_index: bool,
+    },
+    /// Print a table of a note as csv or json
+    Export {
+        /// the id of the note
+        id: String,
+        #[arg(long, default_value_t = 1)]
+        /// which table of the note, counting from 1
+        nth: usize,
+        #[arg(long, value_enum, default_value_t = TableFormat::Csv)]
+        format: TableFormat,
+        #[arg(long, short)]
+        /// file to write the table to, defaults to stdout
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SourceCommand {
+    /// Check whether a page or DOI was ever linked to or saved, e.g. `zet
+    /// source check https://example.com/post` before writing a literature
+    /// note. Prints when it was first and last seen and the documents
+    /// referencing it, and fails if it is unknown.
+    Check {
+        /// the url or DOI
+        url: String,
+        #[arg(long, value_enum, default_value_t=ReportFormat::Text)]
+        output_format: ReportFormat,
+        #[arg(long)]
+        /// whether json output should be pretty printed or not
+        pretty: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ExportCommand {
+    /// Export frontmatter events and tasks with due dates as an iCalendar file
+    Ics {
+        #[arg(long, short)]
+        /// file to write the calendar to, defaults to stdout
+        output: Option<PathBuf>,
+    },
+    /// Export notes as a single pdf, using `export.pdf_command`
+    #[cfg(feature = "document-export")]
+    Pdf {
+        #[command(flatten)]
+        selection: ExportSelection,
+    },
+    /// Export notes as a single epub, using `export.epub_command`
+    #[cfg(feature = "document-export")]
+    Epub {
+        #[command(flatten)]
+        selection: ExportSelection,
+    },
+    /// Export the outline of a note, its headings and list items, as OPML
+    Opml {
+        /// the id of the note
+        id: String,
+        #[arg(long, short)]
+        /// file to write the outline to, defaults to stdout
+        output: Option<PathBuf>,
+    },
+    /// Export every document as an html page, followed by the documents
+    /// linking to it
+    Html {
+        #[arg(long, short)]
+        /// directory to write the pages to
+        output: PathBuf,
+        #[arg(long)]
+        /// leave out notes with `private: true` in their frontmatter, and the
+        /// text between `%%private%%` and `%%/private%%` fences
+        redact: bool,
+        #[arg(long)]
+        /// replace the embeds `![[note]]` of the pages by the embedded notes,
+        /// see `zet export pdf --transclude`
+        transclude: bool,
+    },
+}
+
+#[cfg(feature = "document-export")]
+#[derive(Args, Debug)]
+pub struct ExportSelection {
+    /// ids of the notes to export, in this order
+    pub ids: Vec<String>,
+    #[arg(long = "filter")]
+    /// export the notes matching all filters as well, see `zet meta`
+    pub filters: Vec<DocumentFilter>,
+    #[arg(long, short)]
+    /// the file to write
+    pub output: PathBuf,
+    #[arg(long)]
+    /// title of the document, defaults to the title of the first note
+    pub title: Option<String>,
+    #[arg(long)]
+    /// leave out notes with `private: true` in their frontmatter, and the
+    /// text between `%%private%%` and `%%/private%%` fences
+    pub redact: bool,
+    #[arg(long)]
+    /// replace the embeds `![[note]]` of the notes by the embedded notes,
+    /// recursively, so that a note of embeds exports as the whole document
+    pub transclude: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum JournalCommand {
+    /// Create the periodic notes of a range of periods that do not exist yet
+    Ensure {
+        #[arg(long, value_enum, default_value_t=Period::Daily)]
+        period: Period,
+        #[arg(long)]
+        /// the days to create the notes of the periods of, e.g. "this week"
+        /// or "next 7 days", defaults to today
+        range: Option<DateRangeExpr>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum GraphCommand {
+    /// Compute degree, PageRank centrality and connected components
+    Stats {
+        #[arg(long, default_value_t = 10)]
+        /// number of hub documents to list
+        limit: usize,
+        #[arg(long)]
+        /// store the statistics of each document in the database
+        cache: bool,
+        #[arg(long)]
+        /// recompute the statistics even if the index has not changed since
+        /// they were last computed
+        no_cache: bool,
+        #[arg(long, value_enum, default_value_t=ReportFormat::Text)]
+        output_format: ReportFormat,
+        #[arg(long)]
+        /// whether json output should be pretty printed or not
+        pretty: bool,
     },
 }
 
@@ -218,6 +1199,13 @@ fn parse_sort_option(input: &str) -> zet::result::Result<SortConfig> {
     Ok(SortConfig { by, order })
 }
 
+fn parse_variable(input: &str) -> zet::result::Result<(String, String)> {
+    let (key, value) = input
+        .split_once('=')
+        .ok_or_else(|| eyre!("invalid variable {:?}, expected key=value", input))?;
+    Ok((key.to_owned(), value.to_owned()))
+}
+
 #[derive(Debug, Clone, ValueEnum)]
 pub enum OutputFormat {
     Template,
@@ -226,15 +1214,71 @@ pub enum OutputFormat {
     Json,
 }
 
+#[derive(Debug, Clone, ValueEnum)]
+pub enum ReportFormat {
+    Text,
+    Json,
+}
+
+impl Display for ReportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
 impl Display for OutputFormat {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}", self)
     }
 }
 
-// private
+/// A natural language date expression, such as "next friday". It is resolved
+/// once the configuration, which holds the timezone and parser settings, is
+/// known.
+#[derive(Debug, Clone)]
+pub struct DateExpr(pub String);
+
+/// A natural language date range expression, such as "last 3 weeks", see
+/// [`DateExpr`]
+#[derive(Debug, Clone)]
+pub struct DateRangeExpr(pub String);
+
+impl FromStr for DateExpr {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(DateExpr(s.to_owned()))
+    }
+}
 
-fn natural_language_parser(input: &str) -> zet::result::Result<Timestamp> {
-    NaturalDateParser::parse(input, jiff::Timestamp::now())
-        .map_err(|e| eyre!("invalid date expression: {:?}", e))
+impl FromStr for DateRangeExpr {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(DateRangeExpr(s.to_owned()))
+    }
+}
+
+impl DateExpr {
+    pub fn resolve(&self, config: &Config) -> zet::result::Result<Timestamp> {
+        NaturalDateParser::parse_with_config(
+            &self.0,
+            Timestamp::now(),
+            &config.timezone()?,
+            &config.dates,
+        )
+        .map_err(|e| eyre!("invalid date expression {:?}: {}", self.0, e))
+    }
+}
+
+impl DateRangeExpr {
+    pub fn resolve(&self, config: &Config) -> zet::result::Result<TimeRange> {
+        NaturalDateParser::parse_range_with_config(
+            &self.0,
+            Timestamp::now(),
+            &config.timezone()?,
+            &config.dates,
+        )
+        .map_err(|e| eyre!("invalid date range expression {:?}: {}", self.0, e))
+    }
 }
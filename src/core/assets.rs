@@ -0,0 +1,390 @@
+//! Asset files of a collection, the images and other files referenced from
+//! documents.
+//!
+//! A reference is the destination of an image or a link (`![](img/a.png)`,
+//! `[report](report.pdf)`, `![[a.png]]`). It resolves relative to the
+//! directory of the document, then to the root of the collection. Wiki embeds
+//! also resolve by file name alone.
+
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use color_eyre::eyre::eyre;
+use ignore::WalkBuilder;
+use normalize_path::NormalizePath;
+use pulldown_cmark::{Event, LinkType, Parser, Tag};
+use serde::{Deserialize, Serialize};
+
+use crate::core::frontmatter::split_frontmatter;
+use crate::core::parser::DocumentParserOptions;
+use crate::core::{percent_decode, workspace_paths};
+use crate::result::Result;
+
+/// Images that can be optimized, vector images and other assets are left as is
+const RASTER_EXTENSIONS: [&str; 8] = ["png", "jpg", "jpeg", "gif", "webp", "avif", "bmp", "tiff"];
+
+/// The `[assets]` section of the configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AssetsConfig {
+    /// extensions of the files that are assets
+    pub extensions: Vec<String>,
+    /// images larger than this, in bytes, are optimized
+    pub max_size: u64,
+    /// program and arguments optimizing an image, `{input}` and `{output}`
+    /// are replaced by the paths of the image and of the optimized image
+    pub optimize_command: Vec<String>,
+    /// extension of optimized images, e.g. "webp". Optimized images keep
+    /// their extension if unset.
+    pub optimize_extension: Option<String>,
+}
+
+impl Default for AssetsConfig {
+    fn default() -> Self {
+        Self {
+            extensions: [
+                "png", "jpg", "jpeg", "gif", "webp", "avif", "bmp", "tiff", "svg", "pdf", "mp3",
+                "mp4", "ogg", "wav", "webm",
+            ]
+            .map(String::from)
+            .to_vec(),
+            max_size: 1_000_000,
+            optimize_command: [
+                "magick",
+                "{input}",
+                "-resize",
+                "2048x2048>",
+                "-quality",
+                "85",
+                "{output}",
+            ]
+            .map(String::from)
+            .to_vec(),
+            optimize_extension: None,
+        }
+    }
+}
+
+/// A reference from a document to an asset
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetReference {
+    pub document: PathBuf,
+    /// the destination as written
+    pub target: String,
+    /// byte range of `target` in the document, `None` if it is not written
+    /// at the reference, as for reference style links
+    pub range: Option<Range<usize>>,
+    pub asset: PathBuf,
+}
+
+fn has_extension(path: &Path, extensions: &[impl AsRef<str>]) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            extensions
+                .iter()
+                .any(|e| e.as_ref().eq_ignore_ascii_case(ext))
+        })
+}
+
+/// The asset files of the collection
+pub fn asset_paths(root: &Path, config: &AssetsConfig) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = WalkBuilder::new(root)
+        .build()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+        .map(|e| e.into_path())
+        .filter(|path| has_extension(path, &config.extensions))
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// The references of every document of the collection to `assets`
+pub fn collect_references(root: &Path, assets: &[PathBuf]) -> Result<Vec<AssetReference>> {
+    let assets: HashSet<&Path> = assets.iter().map(PathBuf::as_path).collect();
+    let mut references = Vec::new();
+    for path in workspace_paths(root)? {
+        let content = std::fs::read_to_string(&path)?;
+        references.extend(find_references(root, &path, &content, &assets));
+    }
+    Ok(references)
+}
+
+/// The references of `document`, the content of the file at `path`, to any
+/// of `assets`
+pub fn find_references(
+    root: &Path,
+    path: &Path,
+    document: &str,
+    assets: &HashSet<&Path>,
+) -> Vec<AssetReference> {
+    let body_offset = match split_frontmatter(document) {
+        Some((_, body)) => document.len() - body.len(),
+        None => 0,
+    };
+    let body = &document[body_offset..];
+    let directory = path.parent().unwrap_or(root);
+
+    let mut references = Vec::new();
+    for (event, range) in
+        Parser::new_ext(body, DocumentParserOptions::default().0).into_offset_iter()
+    {
+        let (Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            ..
+        })
+        | Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            ..
+        })) = event
+        else {
+            continue;
+        };
+        let target = dest_url.as_ref();
+        if target.contains("://") || target.starts_with(['#', '?']) || target.starts_with("mailto:")
+        {
+            continue;
+        }
+        let wiki = matches!(link_type, LinkType::WikiLink { .. });
+        let Some(asset) = resolve_reference(root, directory, target, wiki, assets) else {
+            continue;
+        };
+
+        // the target comes first in `![[target|alt]]` and last in `![alt](target)`
+        let source = &body[range.clone()];
+        let position = match wiki {
+            true => source.find(target),
+            false => source.rfind(target),
+        };
+        references.push(AssetReference {
+            document: path.to_owned(),
+            target: target.to_owned(),
+            range: position.map(|position| {
+                let start = body_offset + range.start + position;
+                start..start + target.len()
+            }),
+            asset,
+        });
+    }
+    references
+}
+
+fn resolve_reference(
+    root: &Path,
+    directory: &Path,
+    target: &str,
+    wiki: bool,
+    assets: &HashSet<&Path>,
+) -> Option<PathBuf> {
+    let target = percent_decode(target.split(['#', '?']).next().unwrap_or_default());
+    let candidates = match target.strip_prefix('/') {
+        Some(target) => vec![root.join(target)],
+        None => vec![directory.join(&target), root.join(&target)],
+    };
+    if let Some(asset) = candidates
+        .into_iter()
+        .map(|path| path.normalize())
+        .find(|path| assets.contains(path.as_path()))
+    {
+        return Some(asset);
+    }
+    if wiki {
+        let mut matches: Vec<&&Path> = assets.iter().filter(|a| a.ends_with(&target)).collect();
+        matches.sort();
+        return matches.first().map(|path| path.to_path_buf());
+    }
+    None
+}
+
+/// The assets no document refers to
+pub fn unreferenced_assets(root: &Path, config: &AssetsConfig) -> Result<Vec<PathBuf>> {
+    let assets = asset_paths(root, config);
+    let references = collect_references(root, &assets)?;
+    let referenced: HashSet<&PathBuf> = references.iter().map(|r| &r.asset).collect();
+    Ok(assets
+        .iter()
+        .filter(|asset| !referenced.contains(asset))
+        .cloned()
+        .collect())
+}
+
+/// A referenced image larger than the configured maximum size
+#[derive(Debug, Clone)]
+pub struct OversizedImage {
+    pub path: PathBuf,
+    pub size: u64,
+    /// the path of the optimized image
+    pub to: PathBuf,
+    pub references: Vec<AssetReference>,
+}
+
+/// The referenced raster images larger than `config.max_size`
+pub fn oversized_images(root: &Path, config: &AssetsConfig) -> Result<Vec<OversizedImage>> {
+    let assets: Vec<PathBuf> = asset_paths(root, config)
+        .into_iter()
+        .filter(|path| has_extension(path, &RASTER_EXTENSIONS))
+        .collect();
+    let mut references: BTreeMap<PathBuf, Vec<AssetReference>> = BTreeMap::new();
+    for reference in collect_references(root, &assets)? {
+        references
+            .entry(reference.asset.clone())
+            .or_default()
+            .push(reference);
+    }
+
+    let mut images = Vec::new();
+    for (path, references) in references {
+        let size = std::fs::metadata(&path)?.len();
+        if size <= config.max_size {
+            continue;
+        }
+        let to = match &config.optimize_extension {
+            Some(extension) => path.with_extension(extension),
+            None => path.clone(),
+        };
+        images.push(OversizedImage {
+            path,
+            size,
+            to,
+            references,
+        });
+    }
+    Ok(images)
+}
+
+impl OversizedImage {
+    /// Run the optimize command on the image, replacing it if the result is
+    /// smaller and rewriting the references to it if its name changes.
+    /// Returns the new size, `None` if the image was kept.
+    pub fn optimize(&self, config: &AssetsConfig) -> Result<Option<u64>> {
+        let Some((program, args)) = config.optimize_command.split_first() else {
+            return Err(eyre!("no optimize command configured"));
+        };
+        if self.to != self.path && self.to.exists() {
+            return Err(eyre!(
+                "can not optimize {:?}, {:?} already exists",
+                self.path,
+                self.to
+            ));
+        }
+        let extension = self.to.extension().unwrap_or_default().to_string_lossy();
+        let output = self.path.with_extension(format!("optimized.{extension}"));
+        let replace = |arg: &String| {
+            arg.replace("{input}", &self.path.to_string_lossy())
+                .replace("{output}", &output.to_string_lossy())
+        };
+
+        let status = Command::new(program)
+            .args(args.iter().map(replace))
+            .status()
+            .map_err(|e| eyre!("could not run {:?}: {}", program, e))?;
+        if !status.success() || !output.exists() {
+            let _ = std::fs::remove_file(&output);
+            return Err(eyre!(
+                "could not optimize {:?}, {:?} failed",
+                self.path,
+                program
+            ));
+        }
+        let size = std::fs::metadata(&output)?.len();
+        if size >= self.size {
+            std::fs::remove_file(&output)?;
+            return Ok(None);
+        }
+
+        std::fs::rename(&output, &self.to)?;
+        if self.to != self.path {
+            self.rewrite_references()?;
+            std::fs::remove_file(&self.path)?;
+        }
+        Ok(Some(size))
+    }
+
+    /// Point the references to the optimized image, only the extension of the
+    /// file changes
+    fn rewrite_references(&self) -> Result<()> {
+        let Some(extension) = self.to.extension().and_then(|e| e.to_str()) else {
+            return Ok(());
+        };
+        let mut documents: BTreeMap<&Path, Vec<(&Range<usize>, &str)>> = BTreeMap::new();
+        for reference in &self.references {
+            match &reference.range {
+                Some(range) => documents
+                    .entry(&reference.document)
+                    .or_default()
+                    .push((range, &reference.target)),
+                None => log::warn!(
+                    "could not rewrite the reference to {:?} in {:?}",
+                    self.path,
+                    reference.document
+                ),
+            }
+        }
+
+        for (path, mut edits) in documents {
+            let mut content = std::fs::read_to_string(path)?;
+            edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+            for (range, target) in edits {
+                // the extension is the last one before a fragment or query
+                let end = target.find(['#', '?']).unwrap_or(target.len());
+                let Some(dot) = target[..end].rfind('.') else {
+                    continue;
+                };
+                let replacement = format!("{}.{extension}{}", &target[..dot], &target[end..]);
+                content.replace_range(range.clone(), &replacement);
+            }
+            std::fs::write(path, content)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_references() {
+        let root = Path::new("/notes");
+        let assets = [
+            Path::new("/notes/img/a b.png"),
+            Path::new("/notes/img/c.png"),
+            Path::new("/notes/files/report.pdf"),
+        ];
+        let assets: HashSet<&Path> = assets.into_iter().collect();
+        let document = "---\ntitle: x\n---\n\n![a](../img/a%20b.png) ![[c.png|c]]\n[report](/files/report.pdf#page=2) [web](https://x.org/c.png) ![missing](d.png)\n";
+
+        let references = find_references(root, Path::new("/notes/journal/x.md"), document, &assets);
+        let found: Vec<(&str, &Path, &str)> = references
+            .iter()
+            .map(|r| {
+                (
+                    r.target.as_str(),
+                    r.asset.as_path(),
+                    &document[r.range.clone().unwrap()],
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "../img/a%20b.png",
+                    Path::new("/notes/img/a b.png"),
+                    "../img/a%20b.png"
+                ),
+                ("c.png", Path::new("/notes/img/c.png"), "c.png"),
+                (
+                    "/files/report.pdf#page=2",
+                    Path::new("/notes/files/report.pdf"),
+                    "/files/report.pdf#page=2"
+                ),
+            ]
+        );
+    }
+}
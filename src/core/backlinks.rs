@@ -0,0 +1,97 @@
+//! The documents linking to a document, each with the paragraph or list item
+//! the link is in.
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::PathBuf;
+
+use rusqlite::Connection;
+use serde::Serialize;
+use sql_minifier::macros::minify_sql as sql;
+
+use crate::core::db::Page;
+use crate::core::parser::{FrontMatterFormat, FrontMatterParser};
+use crate::core::types::document::{DocumentId, DocumentPath};
+use crate::result::Result;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LinkedMention {
+    pub document_id: DocumentId,
+    pub title: String,
+    /// the markdown source of the block containing the link, empty if the
+    /// link is in no paragraph or list item
+    pub context: String,
+    /// the range of the block in the content after the frontmatter of the
+    /// linking document
+    #[serde(skip)]
+    pub block: Option<Range<usize>>,
+}
+
+/// The mentions of the document `id` in other documents, one per block
+/// linking to it. The blocks are read from the linking documents, using the
+/// ranges stored in the index.
+pub fn linked_mentions(
+    db: &Connection,
+    id: &DocumentId,
+    format: FrontMatterFormat,
+    page: Page,
+) -> Result<Vec<LinkedMention>> {
+    let query = sql!(
+        r#"
+        select distinct
+            d.id,
+            d.title,
+            d.path,
+            l.block_start,
+            l.block_end
+        from
+            document_link l
+            join document d on d.id = l.from_id
+        where
+            l.to_id = ?1 and l.from_id != ?1
+        order by
+            d.title, d.id, l.block_start
+    "#
+    );
+    let mut query = db.prepare(&format!("{}{}", query, page.sql()))?;
+    let rows = query
+        .query_map([id], |r| {
+            Ok((
+                r.get::<_, DocumentId>(0)?,
+                r.get::<_, String>(1)?,
+                r.get::<_, DocumentPath>(2)?,
+                r.get::<_, Option<usize>>(3)?,
+                r.get::<_, Option<usize>>(4)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    // the content after the frontmatter, which the ranges are relative to
+    let mut bodies: HashMap<PathBuf, String> = HashMap::new();
+    let mut mentions = Vec::with_capacity(rows.len());
+    for (document_id, title, DocumentPath(path), start, end) in rows {
+        let block = start.zip(end).map(|(start, end)| start..end);
+        let context = match &block {
+            Some(block) => {
+                if !bodies.contains_key(&path) {
+                    let content = std::fs::read_to_string(&path)?;
+                    let (_, body) = FrontMatterParser::new(format).parse(content);
+                    bodies.insert(path.clone(), body);
+                }
+                bodies[&path]
+                    .get(block.clone())
+                    .unwrap_or_default()
+                    .trim_end()
+                    .to_owned()
+            }
+            None => String::new(),
+        };
+        mentions.push(LinkedMention {
+            document_id,
+            title,
+            context,
+            block,
+        });
+    }
+    Ok(mentions)
+}
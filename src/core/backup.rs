@@ -0,0 +1,499 @@
+//! Backing up a collection as a single archive and restoring it.
+//!
+//! A backup is a tar archive holding the documents, the assets and the files
+//! in .zet other than the index, optionally with a copy of the index. Its
+//! first entry is `manifest.json`, listing the size and hash of every other
+//! entry, which a restore checks before writing anything to the target
+//! directory. Archives ending in `.gz` or `.tgz` are compressed with gzip,
+//! those ending in `.zst` with the `zstd` program.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+
+use color_eyre::eyre::eyre;
+use flate2::Compression as GzipLevel;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use jiff::Timestamp;
+use serde::{Deserialize, Serialize};
+
+use crate::core::assets::{AssetsConfig, asset_paths};
+use crate::core::{
+    collection_config_dir, collection_db_file, hash_bytes, path_to_slash, relative_path,
+    workspace_paths,
+};
+use crate::result::Result;
+use crate::{DB_LOCK_NAME, DB_NAME, INDEX_JOURNAL_NAME};
+
+/// The version of the archive layout, restores refuse newer archives
+pub const BACKUP_FORMAT: u32 = 1;
+const MANIFEST_NAME: &str = "manifest.json";
+const BLOCK: usize = 512;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format: u32,
+    /// the version of zet that wrote the archive
+    pub zet_version: String,
+    pub created: Timestamp,
+    /// whether the archive holds a copy of the index
+    pub database: bool,
+    /// the entries following the manifest, by their path in the collection
+    pub files: BTreeMap<String, ManifestEntry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub size: u64,
+    pub hash: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// The compression of an archive written to `path`, by its extension
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("gz" | "tgz") => Compression::Gzip,
+            Some("zst" | "tzst") => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+
+    /// The compression of an archive starting with `magic`
+    fn detect(magic: &[u8]) -> Self {
+        match magic {
+            [0x1f, 0x8b, ..] => Compression::Gzip,
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+}
+
+/// Write a backup of the collection at `root` to `out`, with a copy of the
+/// index if `database` is set
+pub fn backup(root: &Path, assets: &AssetsConfig, out: &Path, database: bool) -> Result<Manifest> {
+    let mut paths: BTreeSet<PathBuf> = workspace_paths(root)?.into_iter().collect();
+    paths.extend(asset_paths(root, assets));
+    collect_config_files(&collection_config_dir(root), &mut paths)?;
+    // the archive may be written into the collection itself
+    let out = std::path::absolute(out)?;
+    paths.remove(&out);
+
+    // the index is copied as of a single transaction
+    let snapshot = std::env::temp_dir().join(format!("zet-backup-{}.sqlite", uuid::Uuid::new_v4()));
+    if database {
+        let db = rusqlite::Connection::open(collection_db_file(root))?;
+        db.execute("vacuum into ?1", [snapshot.to_string_lossy()])?;
+    }
+    let result = write_backup(root, &paths, database.then_some(snapshot.as_path()), &out);
+    if database {
+        std::fs::remove_file(&snapshot)?;
+    }
+    result
+}
+
+fn write_backup(
+    root: &Path,
+    paths: &BTreeSet<PathBuf>,
+    snapshot: Option<&Path>,
+    out: &Path,
+) -> Result<Manifest> {
+    let db_name = archive_path(root, &collection_db_file(root))?;
+    let mut sources = Vec::with_capacity(paths.len() + 1);
+    for path in paths {
+        sources.push((archive_path(root, path)?, path.as_path()));
+    }
+    sources.extend(snapshot.map(|snapshot| (db_name, snapshot)));
+
+    let mut files = BTreeMap::new();
+    for (name, path) in &sources {
+        let content = std::fs::read(path)?;
+        files.insert(
+            name.clone(),
+            ManifestEntry {
+                size: content.len() as u64,
+                hash: hash_bytes(&content),
+            },
+        );
+    }
+    let manifest = Manifest {
+        format: BACKUP_FORMAT,
+        zet_version: env!("CARGO_PKG_VERSION").to_owned(),
+        created: Timestamp::now(),
+        database: snapshot.is_some(),
+        files,
+    };
+
+    let mut writer = ArchiveWriter::create(out, Compression::from_path(out))?;
+    write_entry(
+        &mut writer,
+        MANIFEST_NAME,
+        &serde_json::to_vec_pretty(&manifest)?,
+    )?;
+    for (name, path) in &sources {
+        write_entry(&mut writer, name, &std::fs::read(path)?)?;
+    }
+    writer.write_all(&[0; 2 * BLOCK])?;
+    writer.finish()?;
+    Ok(manifest)
+}
+
+/// The files in .zet, everything but the index and its bookkeeping
+fn collect_config_files(dir: &Path, paths: &mut BTreeSet<PathBuf>) -> Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_config_files(&path, paths)?;
+            continue;
+        }
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let index_file = name.starts_with(DB_NAME) // and its -wal and -shm files
+            || name == DB_LOCK_NAME
+            || name == INDEX_JOURNAL_NAME;
+        if !index_file {
+            paths.insert(path);
+        }
+    }
+    Ok(())
+}
+
+/// The path of `path` in the archive, relative to `root` with `/` separators
+fn archive_path(root: &Path, path: &Path) -> Result<String> {
+    let relative =
+        relative_path(root, path).ok_or_else(|| eyre!("{:?} is not in the collection", path))?;
+    Ok(path_to_slash(&relative))
+}
+
+/// Restore the backup `archive` into `target`, which has to be empty unless
+/// `force` is set, in which case the files of the backup replace those in it.
+/// Nothing is written unless every entry matches the manifest.
+pub fn restore(archive: &Path, target: &Path, force: bool) -> Result<Manifest> {
+    if !force && target.exists() && std::fs::read_dir(target)?.next().is_some() {
+        return Err(eyre!(
+            "{:?} is not empty, specify --force to restore into it",
+            target
+        ));
+    }
+    std::fs::create_dir_all(target)?;
+    let staging = target.join(format!(".zet-restore-{}", uuid::Uuid::new_v4()));
+    let result = stage(archive, &staging).and_then(|manifest| {
+        for name in manifest.files.keys() {
+            let to = target.join(name);
+            if let Some(parent) = to.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::rename(staging.join(name), to)?;
+        }
+        Ok(manifest)
+    });
+    if staging.exists() {
+        std::fs::remove_dir_all(&staging)?;
+    }
+    result
+}
+
+/// Extract `archive` into `staging`, checking every entry against the
+/// manifest
+fn stage(archive: &Path, staging: &Path) -> Result<Manifest> {
+    let mut reader = ArchiveReader::open(archive)?;
+    let Some((name, content)) = read_entry(&mut reader)? else {
+        return Err(eyre!("{:?} is empty", archive));
+    };
+    if name != MANIFEST_NAME {
+        return Err(eyre!("{:?} does not start with a manifest", archive));
+    }
+    let manifest: Manifest = serde_json::from_slice(&content)
+        .map_err(|e| eyre!("the manifest of {:?} is invalid: {}", archive, e))?;
+    if manifest.format > BACKUP_FORMAT {
+        return Err(eyre!(
+            "{:?} was written by zet {}, which this version can not restore",
+            archive,
+            manifest.zet_version
+        ));
+    }
+
+    let mut seen = BTreeSet::new();
+    while let Some((name, content)) = read_entry(&mut reader)? {
+        let expected = manifest
+            .files
+            .get(&name)
+            .ok_or_else(|| eyre!("{} is not in the manifest", name))?;
+        if !is_safe(&name) {
+            return Err(eyre!("{} points outside of the collection", name));
+        }
+        let actual = ManifestEntry {
+            size: content.len() as u64,
+            hash: hash_bytes(&content),
+        };
+        if actual != *expected || !seen.insert(name.clone()) {
+            return Err(eyre!("{} does not match the manifest", name));
+        }
+        let path = staging.join(&name);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, content)?;
+    }
+    reader.finish()?;
+    if let Some(missing) = manifest.files.keys().find(|name| !seen.contains(*name)) {
+        return Err(eyre!("{} is missing from {:?}", missing, archive));
+    }
+    Ok(manifest)
+}
+
+/// Whether the archive path `name` stays within the directory it is
+/// extracted to
+fn is_safe(name: &str) -> bool {
+    Path::new(name)
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+}
+
+////////////////////////////////////////////////////////////
+// Tar
+////////////////////////////////////////////////////////////
+
+/// Write a regular file entry in the ustar format
+fn write_entry(writer: &mut impl Write, name: &str, content: &[u8]) -> Result<()> {
+    let mut header = [0u8; BLOCK];
+    // names longer than the name field are split at a `/` into the prefix
+    let (prefix, name) = match name.len() {
+        0..=100 => ("", name),
+        _ => name
+            .char_indices()
+            .filter(|(i, c)| *c == '/' && *i <= 155 && name.len() - i - 1 <= 100)
+            .map(|(i, _)| (&name[..i], &name[i + 1..]))
+            .next()
+            .ok_or_else(|| eyre!("the path {} is too long to archive", name))?,
+    };
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    octal(&mut header[100..108], 0o644);
+    octal(&mut header[108..116], 0);
+    octal(&mut header[116..124], 0);
+    octal(&mut header[124..136], content.len() as u64);
+    octal(
+        &mut header[136..148],
+        Timestamp::now().as_second().max(0) as u64,
+    );
+    header[156] = b'0';
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+    header[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+    header[148..156].fill(b' ');
+    let checksum: u64 = header.iter().map(|b| *b as u64).sum();
+    octal(&mut header[148..155], checksum);
+
+    writer.write_all(&header)?;
+    writer.write_all(content)?;
+    writer.write_all(&vec![0; padding(content.len())])?;
+    Ok(())
+}
+
+/// Read the next regular file, skipping directories. None at the end of the
+/// archive.
+fn read_entry(reader: &mut impl Read) -> Result<Option<(String, Vec<u8>)>> {
+    loop {
+        let mut header = [0u8; BLOCK];
+        reader.read_exact(&mut header)?;
+        if header.iter().all(|b| *b == 0) {
+            return Ok(None);
+        }
+        let stored: u64 = parse_octal(&header[148..156])?;
+        let mut blank = header;
+        blank[148..156].fill(b' ');
+        if stored != blank.iter().map(|b| *b as u64).sum::<u64>() {
+            return Err(eyre!("the archive is corrupted"));
+        }
+
+        let field = |bytes: &[u8]| {
+            let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+            String::from_utf8_lossy(&bytes[..end]).into_owned()
+        };
+        let (name, prefix) = (field(&header[..100]), field(&header[345..500]));
+        let name = match prefix.is_empty() {
+            true => name,
+            false => format!("{prefix}/{name}"),
+        };
+        let size = parse_octal(&header[124..136])? as usize;
+        let mut content = vec![0; size];
+        reader.read_exact(&mut content)?;
+        std::io::copy(&mut reader.take(padding(size) as u64), &mut std::io::sink())?;
+        match header[156] {
+            b'0' | 0 => return Ok(Some((name, content))),
+            b'5' => continue,
+            kind => return Err(eyre!("{} is not a regular file ({})", name, kind as char)),
+        }
+    }
+}
+
+fn padding(len: usize) -> usize {
+    (BLOCK - len % BLOCK) % BLOCK
+}
+
+/// Write `value` as zero padded octal digits followed by a NUL
+fn octal(field: &mut [u8], value: u64) {
+    let digits = format!("{:0width$o}", value, width = field.len() - 1);
+    field[..digits.len()].copy_from_slice(digits.as_bytes());
+    field[digits.len()] = 0;
+}
+
+fn parse_octal(field: &[u8]) -> Result<u64> {
+    let digits = String::from_utf8_lossy(field);
+    let digits = digits.trim_matches(|c: char| c == '\0' || c == ' ');
+    u64::from_str_radix(digits, 8).map_err(|_| eyre!("the archive is corrupted"))
+}
+
+////////////////////////////////////////////////////////////
+// Compression
+////////////////////////////////////////////////////////////
+
+/// An archive file, written through its compression
+enum ArchiveWriter {
+    File(std::io::BufWriter<std::fs::File>),
+    Gzip(GzEncoder<std::io::BufWriter<std::fs::File>>),
+    Zstd(Child),
+}
+
+impl ArchiveWriter {
+    fn create(path: &Path, compression: Compression) -> Result<Self> {
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        Ok(match compression {
+            Compression::None => ArchiveWriter::File(file),
+            Compression::Gzip => ArchiveWriter::Gzip(GzEncoder::new(file, GzipLevel::default())),
+            Compression::Zstd => ArchiveWriter::Zstd(
+                Command::new("zstd")
+                    .args(["-q", "-c"])
+                    .stdin(Stdio::piped())
+                    .stdout(file.into_inner().map_err(|e| e.into_error())?)
+                    .spawn()
+                    .map_err(|e| eyre!("could not run zstd: {}", e))?,
+            ),
+        })
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            ArchiveWriter::File(mut file) => file.flush()?,
+            ArchiveWriter::Gzip(encoder) => encoder.finish()?.flush()?,
+            ArchiveWriter::Zstd(mut child) => {
+                drop(child.stdin.take());
+                let status = child.wait()?;
+                if !status.success() {
+                    return Err(eyre!("zstd failed with {}", status));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Write for ArchiveWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            ArchiveWriter::File(file) => file.write(buf),
+            ArchiveWriter::Gzip(encoder) => encoder.write(buf),
+            ArchiveWriter::Zstd(child) => child.stdin.as_mut().expect("stdin is piped").write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            ArchiveWriter::File(file) => file.flush(),
+            ArchiveWriter::Gzip(encoder) => encoder.flush(),
+            ArchiveWriter::Zstd(child) => child.stdin.as_mut().expect("stdin is piped").flush(),
+        }
+    }
+}
+
+/// An archive file, read through its compression
+enum ArchiveReader {
+    File(std::io::BufReader<std::fs::File>),
+    Gzip(GzDecoder<std::io::BufReader<std::fs::File>>),
+    Zstd(Child),
+}
+
+impl ArchiveReader {
+    fn open(path: &Path) -> Result<Self> {
+        let mut magic = [0u8; 4];
+        let read = std::fs::File::open(path)?.read(&mut magic)?;
+        let file = std::io::BufReader::new(std::fs::File::open(path)?);
+        Ok(match Compression::detect(&magic[..read]) {
+            Compression::None => ArchiveReader::File(file),
+            Compression::Gzip => ArchiveReader::Gzip(GzDecoder::new(file)),
+            Compression::Zstd => ArchiveReader::Zstd(
+                Command::new("zstd")
+                    .args(["-q", "-d", "-c"])
+                    .arg(path)
+                    .stdout(Stdio::piped())
+                    .spawn()
+                    .map_err(|e| eyre!("could not run zstd: {}", e))?,
+            ),
+        })
+    }
+
+    fn finish(self) -> Result<()> {
+        if let ArchiveReader::Zstd(mut child) = self {
+            drop(child.stdout.take());
+            let status = child.wait()?;
+            if !status.success() {
+                return Err(eyre!("zstd failed with {}", status));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Read for ArchiveReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            ArchiveReader::File(file) => file.read(buf),
+            ArchiveReader::Gzip(decoder) => decoder.read(buf),
+            ArchiveReader::Zstd(child) => child.stdout.as_mut().expect("stdout is piped").read(buf),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tar_entries() {
+        let long = format!("{}/{}.md", "d".repeat(120), "n".repeat(90));
+        let mut archive = Vec::new();
+        write_entry(&mut archive, "a.md", b"# A\n").unwrap();
+        write_entry(&mut archive, &long, &[7; 600]).unwrap();
+        archive.extend([0; 2 * BLOCK]);
+        assert_eq!(archive.len() % BLOCK, 0);
+
+        let mut reader = archive.as_slice();
+        assert_eq!(
+            read_entry(&mut reader).unwrap(),
+            Some(("a.md".to_owned(), b"# A\n".to_vec()))
+        );
+        assert_eq!(read_entry(&mut reader).unwrap(), Some((long, vec![7; 600])));
+        assert_eq!(read_entry(&mut reader).unwrap(), None);
+
+        // a flipped byte in a header fails its checksum
+        archive[10] ^= 1;
+        assert!(read_entry(&mut archive.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_is_safe() {
+        assert!(is_safe("notes/a.md"));
+        assert!(!is_safe("../a.md"));
+        assert!(!is_safe("/etc/passwd"));
+    }
+}
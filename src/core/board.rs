@@ -0,0 +1,231 @@
+//! A kanban board of the tasks in the collection, grouped into columns.
+
+use clap::ValueEnum;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use sql_minifier::macros::minify_sql as sql;
+
+use crate::core::types::document::DocumentId;
+use crate::core::types::task::TaskStatus;
+use crate::result::Result;
+
+/// Column name for tasks in documents without tags
+const UNTAGGED: &str = "untagged";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum BoardGroupBy {
+    #[default]
+    Status,
+    Tag,
+    Document,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardTask {
+    pub document_id: DocumentId,
+    pub document_title: String,
+    pub status: TaskStatus,
+    pub content: String,
+    #[serde(skip)]
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardColumn {
+    pub name: String,
+    pub tasks: Vec<BoardTask>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Board {
+    pub group_by: BoardGroupBy,
+    pub columns: Vec<BoardColumn>,
+}
+
+impl Board {
+    pub fn load(db: &Connection, group_by: BoardGroupBy) -> Result<Board> {
+        let tasks = db
+            .prepare(sql!(
+                r#"
+                select
+                    d.id,
+                    d.title,
+                    t.status,
+                    t.content,
+                    (
+                        select group_concat(distinct tag.tag)
+                        from document_tag_map m join tag on tag.id = m.tag_id
+                        where m.document_id = d.id
+                    )
+                from
+                    document_task t
+                    join document d on d.id = t.document_id
+                order by
+                    d.id, t.range_start
+            "#
+            ))?
+            .query_map([], |r| {
+                let tags: Option<String> = r.get(4)?;
+                Ok(BoardTask {
+                    document_id: r.get(0)?,
+                    document_title: r.get(1)?,
+                    status: r.get(2)?,
+                    content: r.get(3)?,
+                    tags: tags
+                        .map(|t| t.split(',').map(ToOwned::to_owned).collect())
+                        .unwrap_or_default(),
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<BoardTask>>>()?;
+
+        Ok(Board::from_tasks(tasks, group_by))
+    }
+
+    fn from_tasks(tasks: Vec<BoardTask>, group_by: BoardGroupBy) -> Board {
+        let mut columns: Vec<BoardColumn> = match group_by {
+            // status columns are always shown, in workflow order
+            BoardGroupBy::Status => TaskStatus::all()
+                .iter()
+                .map(|s| BoardColumn {
+                    name: s.to_string(),
+                    tasks: Vec::new(),
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        for task in tasks {
+            let names = match group_by {
+                BoardGroupBy::Status => vec![task.status.to_string()],
+                BoardGroupBy::Document => vec![task.document_id.0.clone()],
+                BoardGroupBy::Tag if task.tags.is_empty() => vec![UNTAGGED.to_owned()],
+                BoardGroupBy::Tag => task.tags.clone(),
+            };
+            for name in names {
+                match columns.iter_mut().find(|c| c.name == name) {
+                    Some(column) => column.tasks.push(task.clone()),
+                    None => columns.push(BoardColumn {
+                        name,
+                        tasks: vec![task.clone()],
+                    }),
+                }
+            }
+        }
+
+        if group_by != BoardGroupBy::Status {
+            columns.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+
+        Board { group_by, columns }
+    }
+
+    /// Render the board as side by side columns of `width` characters
+    pub fn render_text(&self, width: usize) -> String {
+        let width = width.max(4);
+        let cell = |s: &str| {
+            if s.chars().count() > width {
+                let truncated: String = s.chars().take(width - 1).collect();
+                format!("{truncated}…")
+            } else {
+                format!("{s:width$}")
+            }
+        };
+        let mut lines = Vec::new();
+        lines.push(
+            self.columns
+                .iter()
+                .map(|c| cell(&format!("{} ({})", c.name, c.tasks.len())))
+                .collect::<Vec<_>>(),
+        );
+        lines.push(self.columns.iter().map(|_| "-".repeat(width)).collect());
+
+        let rows = self
+            .columns
+            .iter()
+            .map(|c| c.tasks.len())
+            .max()
+            .unwrap_or(0);
+        for i in 0..rows {
+            lines.push(
+                self.columns
+                    .iter()
+                    .map(|c| match c.tasks.get(i) {
+                        Some(t) if self.group_by == BoardGroupBy::Status => cell(&t.content),
+                        Some(t) => cell(&format!("{} {}", t.status.marker(), t.content)),
+                        None => cell(""),
+                    })
+                    .collect(),
+            );
+        }
+
+        lines
+            .into_iter()
+            .map(|cells| format!("{}\n", cells.join(" | ").trim_end()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(doc: &str, status: TaskStatus, content: &str, tags: &[&str]) -> BoardTask {
+        BoardTask {
+            document_id: DocumentId(doc.to_owned()),
+            document_title: doc.to_owned(),
+            status,
+            content: content.to_owned(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    fn tasks() -> Vec<BoardTask> {
+        vec![
+            task("a", TaskStatus::Todo, "write", &["work"]),
+            task("a", TaskStatus::Done, "plan", &["work"]),
+            task("b", TaskStatus::InProgress, "read", &[]),
+        ]
+    }
+
+    fn column_sizes(board: &Board) -> Vec<(String, usize)> {
+        board
+            .columns
+            .iter()
+            .map(|c| (c.name.clone(), c.tasks.len()))
+            .collect()
+    }
+
+    #[test]
+    fn test_group_by_status() {
+        let board = Board::from_tasks(tasks(), BoardGroupBy::Status);
+        assert_eq!(
+            column_sizes(&board),
+            vec![
+                ("todo".into(), 1),
+                ("in_progress".into(), 1),
+                ("done".into(), 1),
+                ("cancelled".into(), 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_by_tag() {
+        let board = Board::from_tasks(tasks(), BoardGroupBy::Tag);
+        assert_eq!(
+            column_sizes(&board),
+            vec![("untagged".into(), 1), ("work".into(), 2)]
+        );
+    }
+
+    #[test]
+    fn test_render_text() {
+        let board = Board::from_tasks(tasks(), BoardGroupBy::Document);
+        let text = board.render_text(10);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "a (2)      | b (1)");
+        assert_eq!(lines[2], "[ ] write  | [/] read");
+        assert_eq!(lines[3], "[x] plan   |");
+    }
+}
@@ -0,0 +1,80 @@
+//! Metadata about the moment a note was captured: which machine, which git
+//! branch and which directory `zet create` was invoked from.
+
+use std::path::Path;
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureField {
+    Hostname,
+    GitBranch,
+    Cwd,
+}
+
+impl CaptureField {
+    pub fn all() -> Vec<CaptureField> {
+        vec![
+            CaptureField::Hostname,
+            CaptureField::GitBranch,
+            CaptureField::Cwd,
+        ]
+    }
+
+    fn key(&self) -> &'static str {
+        match self {
+            CaptureField::Hostname => "hostname",
+            CaptureField::GitBranch => "git_branch",
+            CaptureField::Cwd => "cwd",
+        }
+    }
+}
+
+/// Collect the requested fields. Fields that can not be determined (no git
+/// repository, unknown hostname) are left out.
+pub fn capture_metadata(fields: &[CaptureField], cwd: &Path) -> Value {
+    let mut map = Map::new();
+    for field in fields {
+        let value = match field {
+            CaptureField::Hostname => hostname(),
+            CaptureField::GitBranch => git_branch(cwd),
+            CaptureField::Cwd => Some(cwd.display().to_string()),
+        };
+        if let Some(value) = value {
+            map.insert(field.key().to_owned(), Value::String(value));
+        }
+    }
+    Value::Object(map)
+}
+
+fn hostname() -> Option<String> {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .or_else(|| command_output(Command::new("hostname")))
+        .map(|s| s.trim().to_owned())
+        .filter(|s| !s.is_empty())
+}
+
+fn git_branch(cwd: &Path) -> Option<String> {
+    let mut command = Command::new("git");
+    command
+        .arg("-C")
+        .arg(cwd)
+        .args(["rev-parse", "--abbrev-ref", "HEAD"]);
+    command_output(command)
+        .map(|s| s.trim().to_owned())
+        .filter(|s| !s.is_empty())
+}
+
+fn command_output(mut command: Command) -> Option<String> {
+    let output = command.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
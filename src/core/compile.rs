@@ -0,0 +1,282 @@
+//! Compiling a book: the notes an index note lists, in order, assembled into
+//! a single document.
+//!
+//! The chapters are the notes the first link or embed of each list item of
+//! the index note points to. Each chapter starts with a heading, its title
+//! unless it starts with one already, and its headings are shifted so that
+//! the highest is of level 2 under the title of the book. Links between the
+//! chapters point to their headings in the book, links to notes that are not
+//! part of it become their text.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use pulldown_cmark::{CowStr, Event, LinkType, Parser, Tag, TagEnd};
+
+use crate::core::LinkTargets;
+use crate::core::outline::set_heading_level;
+use crate::core::parser::DocumentParserOptions;
+use crate::core::slug::{HeadingAnchors, heading_anchor};
+use crate::core::types::document::DocumentId;
+
+/// A note of the book, its body being the content after the frontmatter
+#[derive(Debug, Clone)]
+pub struct Chapter {
+    pub id: DocumentId,
+    pub title: String,
+    pub body: String,
+}
+
+/// The targets of the first link or embed of each list item of `body`, in
+/// order, and whether they are wiki links
+pub fn chapter_links(body: &str) -> Vec<(String, bool)> {
+    let mut links = Vec::new();
+    // whether the innermost list item being read has had its link
+    let mut items: Vec<bool> = Vec::new();
+    for event in Parser::new_ext(body, DocumentParserOptions::default().0) {
+        match event {
+            Event::Start(Tag::Item) => items.push(false),
+            Event::End(TagEnd::Item) => {
+                items.pop();
+            }
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                ..
+            })
+            | Event::Start(Tag::Image {
+                link_type: link_type @ LinkType::WikiLink { .. },
+                dest_url,
+                ..
+            }) => {
+                if let Some(linked) = items.last_mut()
+                    && !*linked
+                    && !dest_url.contains("://")
+                    && !dest_url.starts_with('#')
+                {
+                    *linked = true;
+                    let wiki = matches!(link_type, LinkType::WikiLink { .. });
+                    links.push((dest_url.into_string(), wiki));
+                }
+            }
+            _ => {}
+        }
+    }
+    links
+}
+
+/// The book titled `title` of `chapters`, links being resolved among
+/// `targets`
+pub fn compile(title: &str, chapters: &[Chapter], targets: &LinkTargets) -> String {
+    let mut book = format!("# {}\n", title.trim());
+    let mut ranges: Vec<(&DocumentId, Range<usize>)> = Vec::with_capacity(chapters.len());
+    for chapter in chapters {
+        let body = match starts_with_heading(&chapter.body) {
+            true => chapter.body.trim().to_owned(),
+            false => format!("# {}\n\n{}", chapter.title.trim(), chapter.body.trim()),
+        };
+        book.push('\n');
+        let start = book.len();
+        book.push_str(&shift_headings(&body, 2));
+        book.push('\n');
+        ranges.push((&chapter.id, start..book.len()));
+    }
+
+    let chapter_at = |offset: usize| {
+        ranges
+            .iter()
+            .find(|(_, range)| range.contains(&offset))
+            .map(|(id, _)| *id)
+    };
+
+    // the anchors of the headings of each chapter, by the anchor of their
+    // text in the chapter, and of the first one
+    let mut anchors = HeadingAnchors::default();
+    let mut headings: HashMap<&DocumentId, Vec<(String, String)>> = HashMap::new();
+    let mut heading: Option<(Option<CowStr>, usize, String)> = None;
+    let mut links = Vec::new();
+    let mut link: Option<(Range<usize>, String, bool, String)> = None;
+    for (event, range) in
+        Parser::new_ext(&book, DocumentParserOptions::default().0).into_offset_iter()
+    {
+        match event {
+            Event::Start(Tag::Heading { id, .. }) => {
+                heading = Some((id, range.start, String::new()))
+            }
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                ..
+            }) => {
+                let wiki = matches!(link_type, LinkType::WikiLink { .. });
+                link = Some((range, dest_url.into_string(), wiki, String::new()));
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, _, content)) = &mut heading {
+                    content.push_str(&text);
+                }
+                if let Some((_, _, _, content)) = &mut link {
+                    content.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some((id, start, content)) = heading.take() {
+                    let anchor = anchors.anchor(id.as_deref(), &content);
+                    if let Some(chapter) = chapter_at(start) {
+                        headings
+                            .entry(chapter)
+                            .or_default()
+                            .push((heading_anchor(&content), anchor));
+                    }
+                }
+            }
+            Event::End(TagEnd::Link) => links.extend(link.take()),
+            _ => {}
+        }
+    }
+
+    let anchor = |chapter: &DocumentId, fragment: Option<&str>| -> Option<String> {
+        let headings = headings.get(chapter)?;
+        match fragment {
+            Some(fragment) => {
+                let fragment = heading_anchor(fragment);
+                headings
+                    .iter()
+                    .find(|(slug, anchor)| *slug == fragment || *anchor == fragment)
+                    .map(|(_, anchor)| anchor.clone())
+            }
+            None => headings.first().map(|(_, anchor)| anchor.clone()),
+        }
+    };
+
+    let mut edits = Vec::new();
+    for (mut range, target, wiki, text) in links {
+        let Some(from) = chapter_at(range.start) else {
+            continue;
+        };
+        if target.contains("://") || target.starts_with("mailto:") {
+            continue;
+        }
+        if wiki {
+            // the range of a wiki link may end before its closing brackets
+            match book[range.start..].find("]]") {
+                Some(end) => range.end = range.start + end + 2,
+                None => continue,
+            }
+        }
+        let (note, fragment) = match target.split_once('#') {
+            Some((note, fragment)) => (note, Some(fragment)),
+            None => (target.as_str(), None),
+        };
+        let to = match note.is_empty() {
+            true => Some(from),
+            false => targets.resolve(from, note, wiki),
+        };
+        let Some(to) = to else {
+            continue;
+        };
+        let replacement = match ranges.iter().any(|(id, _)| *id == to) {
+            true => match anchor(to, fragment) {
+                Some(anchor) => format!("[{}](#{})", text, anchor),
+                None => continue,
+            },
+            false => text,
+        };
+        edits.push((range, replacement));
+    }
+    for (range, replacement) in edits.into_iter().rev() {
+        book.replace_range(range, &replacement);
+    }
+    book
+}
+
+/// Whether the first block of `body` is a heading
+fn starts_with_heading(body: &str) -> bool {
+    Parser::new_ext(body, DocumentParserOptions::default().0)
+        .find(|event| matches!(event, Event::Start(_)))
+        .is_some_and(|event| matches!(event, Event::Start(Tag::Heading { .. })))
+}
+
+/// `body` with its headings shifted so that the highest is of level `top`,
+/// and written as atx headings
+pub(crate) fn shift_headings(body: &str, top: usize) -> String {
+    let headings: Vec<(usize, Range<usize>)> =
+        Parser::new_ext(body, DocumentParserOptions::default().0)
+            .into_offset_iter()
+            .filter_map(|(event, range)| match event {
+                Event::Start(Tag::Heading { level, .. }) => Some((level as usize, range)),
+                _ => None,
+            })
+            .collect();
+    let Some(highest) = headings.iter().map(|(level, _)| *level).min() else {
+        return body.to_owned();
+    };
+
+    let mut shifted = body.to_owned();
+    for (level, range) in headings.into_iter().rev() {
+        let level = (level + top - highest).min(6);
+        let heading = set_heading_level(&body[range.clone()], level);
+        shifted.replace_range(range, &heading);
+    }
+    shifted
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn test_chapter_links() {
+        let body = "# Book\n\nSee [[preface]].\n\n1. [[intro]], not [[other]]\n2. ![[method]]\n   - [results](results.md)\n3. [web](https://example.com) [[appendix]]\n";
+        assert_eq!(
+            chapter_links(body),
+            vec![
+                ("intro".to_owned(), true),
+                ("method".to_owned(), true),
+                ("results.md".to_owned(), false),
+                ("appendix".to_owned(), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compile() {
+        let mut targets = LinkTargets::default();
+        for id in ["intro", "method", "other"] {
+            targets.insert(
+                DocumentId(id.to_owned()),
+                PathBuf::from(format!("/{id}.md")),
+            );
+        }
+        let chapter = |id: &str, title: &str, body: &str| Chapter {
+            id: DocumentId(id.to_owned()),
+            title: title.to_owned(),
+            body: body.to_owned(),
+        };
+        let chapters = [
+            chapter(
+                "intro",
+                "Introduction",
+                "Read the [[method#Setup|setup]], [[other]] and [[missing]].\n",
+            ),
+            chapter(
+                "method",
+                "Method",
+                "Method\n======\n\n### Setup\n\nBack to [the intro](intro.md).\n\n## Summary\n\nsee [above](#setup)\n",
+            ),
+        ];
+        assert_eq!(
+            compile("Book", &chapters, &targets),
+            "# Book\n\n\
+             ## Introduction\n\n\
+             Read the [setup](#setup), other and [[missing]].\n\n\
+             ## Method\n\n\
+             #### Setup\n\n\
+             Back to [the intro](#introduction).\n\n\
+             ### Summary\n\n\
+             see [above](#setup)\n"
+        );
+    }
+}
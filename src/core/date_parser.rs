@@ -1,8 +1,250 @@
 use chumsky::prelude::*;
 use jiff::{Timestamp, ToSpan, Zoned, civil::Date, tz::TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Parse a natural language date expression, such as "next friday at 3pm", see
+/// [`NaturalDateParser::parse`]
+pub fn parse(input: &str, now: Timestamp, tz: &TimeZone) -> crate::result::Result<Timestamp> {
+    Ok(NaturalDateParser::parse(input, now, tz)?)
+}
+
+/// Parse a natural language date range expression, such as "last 3 weeks", see
+/// [`NaturalDateParser::parse_range`]
+pub fn parse_range(input: &str, now: Timestamp, tz: &TimeZone) -> crate::result::Result<TimeRange> {
+    Ok(NaturalDateParser::parse_range(input, now, tz)?)
+}
+
+/// Longest date expression, in words, that [`find_date`] looks for
+const MAX_EXPRESSION_WORDS: usize = 6;
+
+/// Markers the due date of a task follows, e.g. "due: friday", "@due(friday)"
+/// or "📅 2025-06-01"
+const DUE_MARKERS: [&str; 3] = ["@due(", "due:", "📅"];
+
+/// A date expression found within a longer text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DateMatch {
+    pub timestamp: Timestamp,
+    /// byte offset of the start of the expression in the text
+    pub start: usize,
+    /// byte offset of the end of the expression in the text
+    pub end: usize,
+}
+
+/// Find the date expression of `text`, e.g. "next friday" in "call Bob next
+/// friday". If the text has one of the [`DUE_MARKERS`], only the expression
+/// following the first of them is taken. Otherwise the first expression is,
+/// ignoring a time of day alone ("night mode") and numbers separated by a
+/// slash ("10/20"), which are as often part of the text. Expressions start
+/// and end at word boundaries, and the longest one starting at the earliest
+/// word is preferred.
+pub fn find_date(
+    text: &str,
+    now: Timestamp,
+    tz: &TimeZone,
+    config: &DateParserConfig,
+) -> Option<DateMatch> {
+    let lowercase = lowercase(text);
+    let tokenizer = token_parser(config);
+    let tokenize = |start: usize, end: usize| {
+        tokenizer
+            .parse(&lowercase[start..end])
+            .into_result()
+            .ok()
+            .map(|tokens| (start, end, tokens))
+    };
+
+    // the spans of the candidate expressions and their tokens, in order of
+    // preference
+    let candidates: Vec<(usize, usize, Vec<NatDatToken>)> = match due_marker(&lowercase) {
+        Some(marked) => {
+            let words = words(text, marked);
+            let last = MAX_EXPRESSION_WORDS.min(words.len());
+            match words.first() {
+                Some(&(start, _)) => words[..last]
+                    .iter()
+                    .rev()
+                    .filter_map(|&(_, end)| tokenize(start, end))
+                    .collect(),
+                None => Vec::new(),
+            }
+        }
+        None => {
+            let words = words(text, 0..text.len());
+            let mut candidates = Vec::new();
+            for (i, &(start, _)) in words.iter().enumerate() {
+                let last = (i + MAX_EXPRESSION_WORDS).min(words.len());
+                candidates.extend(
+                    words[i..last]
+                        .iter()
+                        .rev()
+                        .filter_map(|&(_, end)| tokenize(start, end))
+                        .filter(|(_, _, tokens)| !is_incidental(tokens)),
+                );
+            }
+            candidates
+        }
+    };
+
+    let patterns = pattern_parser(config);
+    candidates.iter().find_map(|(start, end, tokens)| {
+        let pattern = patterns.parse(tokens.as_slice()).into_result().ok()?;
+        let timestamp = pattern.to_timestamp(now, tz, config).ok()?;
+        Some(DateMatch {
+            timestamp,
+            start: *start,
+            end: *end,
+        })
+    })
+}
+
+/// The span of `text` following its first due marker, up to the closing
+/// parenthesis of "@due(...)"
+fn due_marker(text: &str) -> Option<std::ops::Range<usize>> {
+    let (at, marker) = DUE_MARKERS
+        .iter()
+        .filter_map(|marker| {
+            // "due:" but not "overdue:"
+            text.match_indices(marker).find(|(at, _)| {
+                !text[..*at]
+                    .chars()
+                    .next_back()
+                    .is_some_and(char::is_alphanumeric)
+            })
+        })
+        .min()?;
+    let start = at + marker.len();
+    let end = match marker.ends_with('(') {
+        true => text[start..]
+            .find(')')
+            .map_or(text.len(), |end| start + end),
+        false => text.len(),
+    };
+    Some(start..end)
+}
+
+/// The byte spans of the words of `text` within `range`, without surrounding
+/// punctuation
+fn words(text: &str, range: std::ops::Range<usize>) -> Vec<(usize, usize)> {
+    let is_punctuation = |c: char| !c.is_alphanumeric();
+    text[range.clone()]
+        .split_whitespace()
+        .filter_map(|word| {
+            let offset = word.as_ptr() as usize - text.as_ptr() as usize;
+            let trimmed = word.trim_matches(is_punctuation);
+            if trimmed.is_empty() {
+                return None;
+            }
+            let start = offset + word.find(trimmed)?;
+            Some((start, start + trimmed.len()))
+        })
+        .collect()
+}
+
+/// `text` in lowercase, keeping the characters whose lowercase is of another
+/// length so that offsets in it are offsets in `text`
+fn lowercase(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            let mut lower = c.to_lowercase();
+            match (lower.next(), lower.next()) {
+                (Some(l), None) if l.len_utf8() == c.len_utf8() => l,
+                _ => c,
+            }
+        })
+        .collect()
+}
+
+/// Whether an expression of `tokens` found in free text is a time of day
+/// alone, as in "night mode", or has numbers separated by a slash, as in
+/// "10/20"
+fn is_incidental(tokens: &[NatDatToken]) -> bool {
+    use NatDatToken::*;
+    let mut words = tokens.iter().filter(|t| !matches!(t, The | In | At));
+    let time_of_day = matches!(
+        (words.next(), words.next()),
+        (
+            Some(Morning | Afternoon | Evening | Night | Noon | Midnight),
+            None
+        )
+    );
+    time_of_day || tokens.contains(&Slash)
+}
 
 pub struct NaturalDateParser;
 
+/// Settings of the natural date parser, the `[dates]` section of the configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DateParserConfig {
+    /// time of "morning", e.g. "tomorrow morning"
+    pub morning: jiff::civil::Time,
+    /// time of "afternoon"
+    pub afternoon: jiff::civil::Time,
+    /// time of "evening"
+    pub evening: jiff::civil::Time,
+    /// time of "night", e.g. "tonight"
+    pub night: jiff::civil::Time,
+    /// first day of the week, used by "this week", "this friday" and bare
+    /// weekdays such as "friday"
+    pub week_start: Weekday,
+    /// names of weekdays in addition to the english ones, e.g. `montag = "monday"`
+    pub weekday_names: HashMap<String, Weekday>,
+    /// names of months in addition to the english ones, e.g. `januar = "january"`
+    pub month_names: HashMap<String, Month>,
+}
+
+impl Default for DateParserConfig {
+    fn default() -> Self {
+        Self {
+            morning: jiff::civil::time(9, 0, 0, 0),
+            afternoon: jiff::civil::time(13, 0, 0, 0),
+            evening: jiff::civil::time(18, 0, 0, 0),
+            night: jiff::civil::time(21, 0, 0, 0),
+            week_start: Weekday::Monday,
+            weekday_names: HashMap::new(),
+            month_names: HashMap::new(),
+        }
+    }
+}
+
+/// A span of time from `start` (inclusive) to `end` (exclusive)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeRange {
+    pub start: Timestamp,
+    pub end: Timestamp,
+}
+
+impl TimeRange {
+    pub fn contains(&self, timestamp: Timestamp) -> bool {
+        self.start <= timestamp && timestamp < self.end
+    }
+
+    /// The first and last day covered by the range in `tz`
+    pub fn dates(&self, tz: &TimeZone) -> (Date, Date) {
+        let first = self.start.to_zoned(tz.clone()).date();
+        let last = (self.end - jiff::SignedDuration::from_nanos(1))
+            .to_zoned(tz.clone())
+            .date();
+        (first, last.max(first))
+    }
+
+    /// The first and last day of the range in `tz`, the day it ends in being
+    /// excluded. Unlike [`TimeRange::dates`], a window of n days from now,
+    /// e.g. "next 7 days", counts n days rather than the n + 1 it touches.
+    pub fn whole_dates(&self, tz: &TimeZone) -> (Date, Date) {
+        let first = self.start.to_zoned(tz.clone()).date();
+        let last = self
+            .end
+            .to_zoned(tz.clone())
+            .date()
+            .yesterday()
+            .unwrap_or(first);
+        (first, last.max(first))
+    }
+}
+
 #[derive(Debug)]
 pub enum ParseError {
     TokenizationError(String),
@@ -10,6 +252,18 @@ pub enum ParseError {
     ConversionError(String),
 }
 
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::TokenizationError(e) => write!(f, "unrecognized word: {e}"),
+            ParseError::PatternParseError(e) => write!(f, "unrecognized date expression: {e}"),
+            ParseError::ConversionError(e) => write!(f, "invalid date: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
 impl NaturalDateParser {
     /// Parse a natural language date string into a UTC Timestamp
     ///
@@ -19,45 +273,99 @@ impl NaturalDateParser {
     ///
     /// **Important:** The returned timestamp may not display the expected date when
     /// shown directly as UTC. For example, "today" at midnight in timezone CET (UTC+1)
-    /// returns a UTC timestamp that appears to be yesterday. Always convert to a
-    /// timezone for display:
+    /// returns a UTC timestamp that appears to be yesterday. Always convert to the
+    /// timezone the expression was parsed in for display:
     ///
     /// ```ignore
     /// use jiff::{Timestamp, tz::TimeZone};
     ///
     /// let now = Timestamp::now();
-    /// let timestamp = NaturalDateParser::parse("today", now)?;
+    /// let tz = TimeZone::system();
+    /// let timestamp = NaturalDateParser::parse("today", now, tz)?;
     ///
-    /// // Convert to system timezone for display
-    /// let zoned = timestamp.to_zoned(TimeZone::system());
+    /// let zoned = timestamp.to_zoned(tz);
     /// println!("Today: {}", zoned);  // Shows correct date
     /// ```
     ///
     /// # Arguments
     /// * `input` - Natural language date string (e.g., "in 3 days", "next friday", "today")
+    ///   or a calendar date (e.g., "march 3", "3rd of march 2026", "2025-06-01", "06/01")
     /// * `now` - Reference timestamp to calculate relative dates from
+    /// * `tz` - Timezone the civil dates and times of the expression are in
     ///
     /// # Design Note
     /// We return `Timestamp` (UTC) rather than `Zoned` (timezone-aware) because:
     /// - Timestamps are the universal interchange format
     /// - Consumers can convert to any timezone they need
     /// - Keeps the API simple with a single return type
-    pub fn parse(input: &str, now: Timestamp) -> Result<Timestamp, ParseError> {
+    pub fn parse(input: &str, now: Timestamp, tz: &TimeZone) -> Result<Timestamp, ParseError> {
+        Self::parse_with_config(input, now, tz, &DateParserConfig::default())
+    }
+
+    /// Like [`NaturalDateParser::parse`], taking the times that words such as
+    /// "morning" resolve to from `config`
+    pub fn parse_with_config(
+        input: &str,
+        now: Timestamp,
+        tz: &TimeZone,
+        config: &DateParserConfig,
+    ) -> Result<Timestamp, ParseError> {
         // Step 1: Tokenize the input string
         let lowercase_input = input.to_lowercase();
-        let tokens = token_parser()
+        let tokens = token_parser(config)
             .parse(lowercase_input.as_str())
             .into_result()
             .map_err(|e| ParseError::TokenizationError(format!("{:?}", e)))?;
 
         // Step 2: Parse tokens into a TimePattern
-        let pattern = pattern_parser()
+        let pattern = pattern_parser(config)
             .parse(tokens.as_slice())
             .into_result()
             .map_err(|e| ParseError::PatternParseError(format!("{:?}", e)))?;
 
         // Step 3: Convert pattern to timestamp
-        pattern.to_timestamp(now)
+        pattern.to_timestamp(now, tz, config)
+    }
+
+    /// Parse a natural language expression describing a span of time
+    ///
+    /// # Arguments
+    /// * `input` - a range ("from monday to friday", "between march 3 and march 5",
+    ///   "2025-06-01 to 2025-06-07"), a duration up to or from now ("last 3 weeks",
+    ///   "past 2 days", "next 10 days"), a calendar period ("this week", "last month",
+    ///   "next year") or any single moment accepted by [`NaturalDateParser::parse`],
+    ///   which covers the day it falls on, or the minute when a time of day is given.
+    /// * `now` - Reference timestamp to calculate relative dates from
+    /// * `tz` - Timezone the civil dates and times of the expression are in
+    ///
+    /// Weeks start on monday.
+    pub fn parse_range(
+        input: &str,
+        now: Timestamp,
+        tz: &TimeZone,
+    ) -> Result<TimeRange, ParseError> {
+        Self::parse_range_with_config(input, now, tz, &DateParserConfig::default())
+    }
+
+    /// Like [`NaturalDateParser::parse_range`], see [`NaturalDateParser::parse_with_config`]
+    pub fn parse_range_with_config(
+        input: &str,
+        now: Timestamp,
+        tz: &TimeZone,
+        config: &DateParserConfig,
+    ) -> Result<TimeRange, ParseError> {
+        let lowercase_input = input.to_lowercase();
+        let tokens = token_parser(config)
+            .parse(lowercase_input.as_str())
+            .into_result()
+            .map_err(|e| ParseError::TokenizationError(format!("{:?}", e)))?;
+
+        let pattern = range_parser(config)
+            .parse(tokens.as_slice())
+            .into_result()
+            .map_err(|e| ParseError::PatternParseError(format!("{:?}", e)))?;
+
+        pattern.to_range(now, tz, config)
     }
 }
 
@@ -67,11 +375,23 @@ struct Time {
     minute: Option<u32>,
 }
 
+impl From<jiff::civil::Time> for Time {
+    fn from(time: jiff::civil::Time) -> Self {
+        Time {
+            hour: time.hour() as u32,
+            minute: Some(time.minute() as u32),
+        }
+    }
+}
+
 /// This enum tries to list some common natural language patterns for referring
 /// to different moments in time
 #[derive(Clone)]
 enum TimePattern {
     /// today
+    /// this morning
+    /// tonight
+    /// noon
     Today { at: Option<Time> },
     /// tomorrow
     Tomorrow { at: Option<Time> },
@@ -121,6 +441,24 @@ enum TimePattern {
     /// on monday
     /// on friday at 07:15am
     OnWeekday { moment: Weekday, at: Option<Time> },
+    /// friday
+    /// weekend
+    ///
+    /// The day of the current week, which may have passed already. The
+    /// weekend is its saturday.
+    Weekday { moment: Weekday, at: Option<Time> },
+    /// 2025-06-01
+    /// 06/01 (month/day)
+    /// march 3
+    /// 3rd of march 2026 at 10
+    ///
+    /// Dates without a year are in the current year.
+    Calendar {
+        year: Option<u32>,
+        month: u32,
+        day: u32,
+        at: Option<Time>,
+    },
 }
 
 #[derive(Clone)]
@@ -128,6 +466,51 @@ enum TimeMoment {
     Weekday(Weekday),
     Month(Month),
     Week,
+    /// `month`, as opposed to a named month
+    CalendarMonth,
+    Year,
+}
+
+/// Patterns describing a span of time rather than a single moment
+#[derive(Clone)]
+enum RangePattern {
+    /// from monday to friday
+    /// between march 3 and march 5
+    /// 2025-06-01 to 2025-06-07
+    Between { from: TimePattern, to: TimePattern },
+    /// last 3 weeks
+    /// past 2 days
+    Past { n: u32, stride: TimeStride },
+    /// next 10 days
+    Coming { n: u32, stride: TimeStride },
+    /// this week
+    /// last month
+    /// next year
+    Period {
+        anchor: RangeAnchor,
+        unit: PeriodUnit,
+    },
+    /// weekend
+    /// next weekend
+    ///
+    /// The saturday of the pattern and the sunday after it
+    Weekend(TimePattern),
+    /// today
+    /// tomorrow at 10
+    Single(TimePattern),
+}
+
+#[derive(Clone, Copy)]
+enum RangeAnchor {
+    Last,
+    This,
+    Next,
+}
+
+#[derive(Clone, Copy)]
+enum PeriodUnit {
+    Week,
+    Month,
     Year,
 }
 
@@ -142,9 +525,12 @@ enum TimeStride {
     Years,
 }
 
-fn token_parser<'src>() -> impl Parser<'src, &'src str, Vec<NatDatToken>> {
+fn token_parser<'src>(config: &DateParserConfig) -> impl Parser<'src, &'src str, Vec<NatDatToken>> {
     let keyword1 = choice((
         just("yesterday").to(NatDatToken::Yesterday),
+        just("midnight").to(NatDatToken::Midnight),
+        just("tonight").to(NatDatToken::Tonight),
+        just("noon").to(NatDatToken::Noon),
         just("tomorrow").to(NatDatToken::Tomorrow),
         just("beginning").to(NatDatToken::Beginning),
         just("afternoon").to(NatDatToken::Afternoon),
@@ -154,6 +540,9 @@ fn token_parser<'src>() -> impl Parser<'src, &'src str, Vec<NatDatToken>> {
         just("weekend").to(NatDatToken::Weekend),
         just("minutes").to(NatDatToken::Minutes),
         just("months").to(NatDatToken::Months),
+        just("month").to(NatDatToken::Months), // Singular month maps to Months token
+        just("between").to(NatDatToken::Between),
+        just("until").to(NatDatToken::To),
         just("today").to(NatDatToken::Today),
         just("hours").to(NatDatToken::Hours),
         just("weeks").to(NatDatToken::Weeks),
@@ -175,6 +564,9 @@ fn token_parser<'src>() -> impl Parser<'src, &'src str, Vec<NatDatToken>> {
         just("ago").to(NatDatToken::Ago),
         just("end").to(NatDatToken::End),
         just("the").to(NatDatToken::The),
+        just("past").to(NatDatToken::Past),
+        just("and").to(NatDatToken::And),
+        just("to").to(NatDatToken::To),
         just("at").to(NatDatToken::At),
         just("in").to(NatDatToken::In),
         just("on").to(NatDatToken::On),
@@ -182,13 +574,16 @@ fn token_parser<'src>() -> impl Parser<'src, &'src str, Vec<NatDatToken>> {
         just("am").to(NatDatToken::Am),
         just("pm").to(NatDatToken::Pm),
         just(":").to(NatDatToken::Colon),
+        just("-").to(NatDatToken::Dash),
+        just("/").to(NatDatToken::Slash),
+        just(",").to(NatDatToken::Comma),
         just("@").to(NatDatToken::At),
     ));
 
     choice((
         keyword1,
-        month().map(NatDatToken::Month),
-        weekday().map(NatDatToken::Weekday),
+        month(config).map(NatDatToken::Month),
+        weekday(config).map(NatDatToken::Weekday),
         number().map(NatDatToken::Number), // Try number before keyword2 to avoid "on" matching "one"
         keyword2,
     ))
@@ -277,24 +672,66 @@ fn parse_time<'src>()
         })
 }
 
-// Parse TimeMoment (weekday, month, week, year)
+// Parse time of day words: morning, afternoon, evening, night, noon, midnight
+fn parse_time_of_day<'src>(
+    config: &DateParserConfig,
+) -> impl Parser<'src, &'src [NatDatToken], Time, extra::Err<Rich<'src, NatDatToken>>> + Clone {
+    choice((
+        tok!(NatDatToken::Morning).to(Time::from(config.morning)),
+        tok!(NatDatToken::Afternoon).to(Time::from(config.afternoon)),
+        tok!(NatDatToken::Evening).to(Time::from(config.evening)),
+        tok!(NatDatToken::Night).to(Time::from(config.night)),
+        tok!(NatDatToken::Noon).to(Time::from(jiff::civil::time(12, 0, 0, 0))),
+        tok!(NatDatToken::Midnight).to(Time::from(jiff::civil::Time::midnight())),
+    ))
+}
+
+// Parse TimeMoment (weekday, weekend, month, week, year)
 fn parse_moment<'src>()
 -> impl Parser<'src, &'src [NatDatToken], TimeMoment, extra::Err<Rich<'src, NatDatToken>>> + Clone {
     choice((
         parse_weekday().map(TimeMoment::Weekday),
+        tok!(NatDatToken::Weekend).to(TimeMoment::Weekday(Weekday::Saturday)),
         parse_month().map(TimeMoment::Month),
         tok!(NatDatToken::Weeks).to(TimeMoment::Week),
+        tok!(NatDatToken::Months).to(TimeMoment::CalendarMonth),
         tok!(NatDatToken::Years).to(TimeMoment::Year),
     ))
 }
 
 // Main pattern parser
-fn pattern_parser<'src>()
--> impl Parser<'src, &'src [NatDatToken], TimePattern, extra::Err<Rich<'src, NatDatToken>>> + Clone
+fn pattern_parser<'src>(
+    config: &DateParserConfig,
+) -> impl Parser<'src, &'src [NatDatToken], TimePattern, extra::Err<Rich<'src, NatDatToken>>> + Clone
 {
-    let time_opt = parse_time().or_not();
+    let time_of_day = parse_time_of_day(config);
+    // [at|@] <hour> [:<minute>] [am|pm]
+    // [at|@] <time of day>
+    // in the <time of day>
+    let time_opt = choice((
+        parse_time(),
+        tok!(NatDatToken::At)
+            .or_not()
+            .ignore_then(time_of_day.clone()),
+        tok!(NatDatToken::In)
+            .ignore_then(tok!(NatDatToken::The))
+            .ignore_then(time_of_day.clone()),
+    ))
+    .or_not();
 
     choice((
+        // "this" <time of day>
+        tok!(NatDatToken::This)
+            .ignore_then(time_of_day.clone())
+            .map(|at| TimePattern::Today { at: Some(at) }),
+        // "tonight"
+        tok!(NatDatToken::Tonight).to(TimePattern::Today {
+            at: Some(Time::from(config.night)),
+        }),
+        // <time of day>
+        time_of_day
+            .clone()
+            .map(|at| TimePattern::Today { at: Some(at) }),
         // "today" [at time]
         tok!(NatDatToken::Today)
             .ignore_then(time_opt.clone())
@@ -346,20 +783,162 @@ fn pattern_parser<'src>()
             .ignore_then(parse_weekday())
             .then(time_opt.clone())
             .map(|(moment, at)| TimePattern::OnWeekday { moment, at }),
+        // <year> "-" <month> "-" <day> [at time]
+        parse_number()
+            .then_ignore(tok!(NatDatToken::Dash))
+            .then(parse_number())
+            .then_ignore(tok!(NatDatToken::Dash))
+            .then(parse_number())
+            .then(time_opt.clone())
+            .map(|(((year, month), day), at)| TimePattern::Calendar {
+                year: Some(year),
+                month,
+                day,
+                at,
+            }),
+        // <month> "/" <day> ["/" <year>] [at time]
+        parse_number()
+            .then_ignore(tok!(NatDatToken::Slash))
+            .then(parse_number())
+            .then(
+                tok!(NatDatToken::Slash)
+                    .ignore_then(parse_number())
+                    .or_not(),
+            )
+            .then(time_opt.clone())
+            .map(|(((month, day), year), at)| TimePattern::Calendar {
+                year,
+                month,
+                day,
+                at,
+            }),
+        // ["on"] <month name> <day> [","] [<year>] [at time]
+        tok!(NatDatToken::On)
+            .or_not()
+            .ignore_then(parse_month())
+            .then(parse_number())
+            .then(
+                tok!(NatDatToken::Comma)
+                    .or_not()
+                    .ignore_then(parse_number())
+                    .or_not(),
+            )
+            .then(time_opt.clone())
+            .map(|(((month, day), year), at)| TimePattern::Calendar {
+                year,
+                month: month_to_number(&month) as u32,
+                day,
+                at,
+            }),
+        // ["on"] ["the"] <day> ["of"] <month name> [<year>] [at time]
+        tok!(NatDatToken::On)
+            .or_not()
+            .ignore_then(tok!(NatDatToken::The).or_not())
+            .ignore_then(parse_number())
+            .then_ignore(tok!(NatDatToken::Of).or_not())
+            .then(parse_month())
+            .then(parse_number().or_not())
+            .then(time_opt.clone())
+            .map(|(((day, month), year), at)| TimePattern::Calendar {
+                year,
+                month: month_to_number(&month) as u32,
+                day,
+                at,
+            }),
+        // <weekday> [at time]
+        parse_weekday()
+            .then(time_opt.clone())
+            .map(|(moment, at)| TimePattern::Weekday { moment, at }),
+        // "weekend" [at time]
+        tok!(NatDatToken::Weekend)
+            .ignore_then(time_opt.clone())
+            .map(|at| TimePattern::Weekday {
+                moment: Weekday::Saturday,
+                at,
+            }),
+    ))
+}
+
+// Range parser, see `RangePattern`
+fn range_parser<'src>(
+    config: &DateParserConfig,
+) -> impl Parser<'src, &'src [NatDatToken], RangePattern, extra::Err<Rich<'src, NatDatToken>>> + Clone
+{
+    let anchor = choice((
+        tok!(NatDatToken::Last).to(RangeAnchor::Last),
+        tok!(NatDatToken::This).to(RangeAnchor::This),
+        tok!(NatDatToken::Next).to(RangeAnchor::Next),
+    ));
+    let unit = choice((
+        tok!(NatDatToken::Weeks).to(PeriodUnit::Week),
+        tok!(NatDatToken::Months).to(PeriodUnit::Month),
+        tok!(NatDatToken::Years).to(PeriodUnit::Year),
+    ));
+
+    choice((
+        // ["from"] <pattern> "to" <pattern>
+        tok!(NatDatToken::From)
+            .or_not()
+            .ignore_then(pattern_parser(config))
+            .then_ignore(tok!(NatDatToken::To))
+            .then(pattern_parser(config))
+            .map(|(from, to)| RangePattern::Between { from, to }),
+        // "between" <pattern> "and" <pattern>
+        tok!(NatDatToken::Between)
+            .ignore_then(pattern_parser(config))
+            .then_ignore(tok!(NatDatToken::And))
+            .then(pattern_parser(config))
+            .map(|(from, to)| RangePattern::Between { from, to }),
+        // ("last" | "past") <number> <stride>
+        tok!(NatDatToken::Last)
+            .or(tok!(NatDatToken::Past))
+            .ignore_then(parse_number())
+            .then(parse_stride())
+            .map(|(n, stride)| RangePattern::Past { n, stride }),
+        // "next" <number> <stride>
+        tok!(NatDatToken::Next)
+            .ignore_then(parse_number())
+            .then(parse_stride())
+            .map(|(n, stride)| RangePattern::Coming { n, stride }),
+        // ["last" | "this" | "next"] "weekend"
+        anchor
+            .or_not()
+            .then_ignore(tok!(NatDatToken::Weekend))
+            .map(|anchor| {
+                let moment = TimeMoment::Weekday(Weekday::Saturday);
+                RangePattern::Weekend(match anchor {
+                    None => TimePattern::Weekday {
+                        moment: Weekday::Saturday,
+                        at: None,
+                    },
+                    Some(RangeAnchor::Last) => TimePattern::Last { moment, at: None },
+                    Some(RangeAnchor::This) => TimePattern::This { moment, at: None },
+                    Some(RangeAnchor::Next) => TimePattern::Next { moment, at: None },
+                })
+            }),
+        // ("last" | "this" | "next") ("week" | "month" | "year")
+        anchor
+            .then(unit)
+            .map(|(anchor, unit)| RangePattern::Period { anchor, unit }),
+        pattern_parser(config).map(RangePattern::Single),
     ))
 }
 
 // Timestamp conversion implementation
 impl TimePattern {
-    fn to_timestamp(&self, now: Timestamp) -> Result<Timestamp, ParseError> {
-        // Convert to system timezone for easier manipulation
-        let tz = TimeZone::system();
+    fn to_timestamp(
+        &self,
+        now: Timestamp,
+        tz: &TimeZone,
+        config: &DateParserConfig,
+    ) -> Result<Timestamp, ParseError> {
+        // Convert to the civil time of the timezone for easier manipulation
         let zoned_now = now.to_zoned(tz.clone());
 
         match self {
             TimePattern::Today { at } => {
                 let date = zoned_now.date();
-                apply_time(date, at, &tz)
+                apply_time(date, at, tz)
             }
 
             TimePattern::Tomorrow { at } => {
@@ -367,7 +946,7 @@ impl TimePattern {
                     .date()
                     .checked_add(1.day())
                     .map_err(|e| ParseError::ConversionError(format!("date overflow: {}", e)))?;
-                apply_time(date, at, &tz)
+                apply_time(date, at, tz)
             }
 
             TimePattern::Yesterday { at } => {
@@ -375,30 +954,30 @@ impl TimePattern {
                     .date()
                     .checked_sub(1.day())
                     .map_err(|e| ParseError::ConversionError(format!("date underflow: {}", e)))?;
-                apply_time(date, at, &tz)
+                apply_time(date, at, tz)
             }
 
             TimePattern::InAmount { n, stride, at } | TimePattern::FromNow { n, stride, at } => {
-                let span = stride_to_span(*n, stride);
+                let span = stride_to_span(*n, stride)?;
                 let future = zoned_now
                     .checked_add(span)
                     .map_err(|e| ParseError::ConversionError(format!("date overflow: {}", e)))?;
 
                 if at.is_some() {
-                    apply_time(future.date(), at, &tz)
+                    apply_time(future.date(), at, tz)
                 } else {
                     Ok(future.timestamp())
                 }
             }
 
             TimePattern::Ago { n, stride, at } => {
-                let span = stride_to_span(*n, stride);
+                let span = stride_to_span(*n, stride)?;
                 let past = zoned_now
                     .checked_sub(span)
                     .map_err(|e| ParseError::ConversionError(format!("date underflow: {}", e)))?;
 
                 if at.is_some() {
-                    apply_time(past.date(), at, &tz)
+                    apply_time(past.date(), at, tz)
                 } else {
                     Ok(past.timestamp())
                 }
@@ -406,39 +985,195 @@ impl TimePattern {
 
             TimePattern::Next { moment, at } => {
                 let target = find_next_moment(&zoned_now, moment)?;
-                apply_time(target, at, &tz)
+                apply_time(target, at, tz)
             }
 
             TimePattern::This { moment, at } => {
-                let target = find_this_moment(&zoned_now, moment)?;
-                apply_time(target, at, &tz)
+                let target = find_this_moment(&zoned_now, moment, &config.week_start)?;
+                apply_time(target, at, tz)
             }
 
             TimePattern::Last { moment, at } => {
                 let target = find_last_moment(&zoned_now, moment)?;
-                apply_time(target, at, &tz)
+                apply_time(target, at, tz)
             }
 
             TimePattern::OnWeekday { moment, at } => {
                 let target = find_next_weekday(&zoned_now, moment)?;
-                apply_time(target, at, &tz)
+                apply_time(target, at, tz)
+            }
+
+            TimePattern::Weekday { moment, at } => {
+                let position = week_position(weekday_to_jiff(moment), &config.week_start);
+                let target = start_of_week(zoned_now.date(), &config.week_start)?
+                    .checked_add(((position - 1) as i64).days())
+                    .map_err(|e| ParseError::ConversionError(format!("date overflow: {}", e)))?;
+                apply_time(target, at, tz)
+            }
+
+            TimePattern::Calendar {
+                year,
+                month,
+                day,
+                at,
+            } => {
+                let target = calendar_date(zoned_now.date(), *year, *month, *day)?;
+                apply_time(target, at, tz)
             }
         }
     }
 }
 
-fn stride_to_span(n: u32, stride: &TimeStride) -> jiff::Span {
-    match stride {
-        TimeStride::Seconds => (n as i64).seconds(),
-        TimeStride::Minutes => (n as i64).minutes(),
-        TimeStride::Hours => (n as i64).hours(),
-        TimeStride::Days => (n as i64).days(),
-        TimeStride::Weeks => (n as i64).weeks(),
-        TimeStride::Months => (n as i64).months(),
-        TimeStride::Years => (n as i64).years(),
+impl TimePattern {
+    /// The explicit time of day of the pattern, if any
+    fn at(&self) -> Option<Time> {
+        match self {
+            TimePattern::Today { at }
+            | TimePattern::Tomorrow { at }
+            | TimePattern::Yesterday { at }
+            | TimePattern::InAmount { at, .. }
+            | TimePattern::FromNow { at, .. }
+            | TimePattern::Ago { at, .. }
+            | TimePattern::Next { at, .. }
+            | TimePattern::This { at, .. }
+            | TimePattern::Last { at, .. }
+            | TimePattern::OnWeekday { at, .. }
+            | TimePattern::Weekday { at, .. }
+            | TimePattern::Calendar { at, .. } => *at,
+        }
+    }
+
+    /// The span covered by the pattern: the minute of an explicit time of
+    /// day, otherwise the whole day
+    fn to_range(
+        &self,
+        now: Timestamp,
+        tz: &TimeZone,
+        config: &DateParserConfig,
+    ) -> Result<TimeRange, ParseError> {
+        let timestamp = self.to_timestamp(now, tz, config)?;
+
+        if self.at().is_some() {
+            let end = timestamp
+                .checked_add(1.minute())
+                .map_err(|e| ParseError::ConversionError(format!("date overflow: {}", e)))?;
+            return Ok(TimeRange {
+                start: timestamp,
+                end,
+            });
+        }
+
+        let date = timestamp.to_zoned(tz.clone()).date();
+        let next = date
+            .checked_add(1.day())
+            .map_err(|e| ParseError::ConversionError(format!("date overflow: {}", e)))?;
+        Ok(TimeRange {
+            start: apply_time(date, &None, tz)?,
+            end: apply_time(next, &None, tz)?,
+        })
     }
 }
 
+impl RangePattern {
+    fn to_range(
+        &self,
+        now: Timestamp,
+        tz: &TimeZone,
+        config: &DateParserConfig,
+    ) -> Result<TimeRange, ParseError> {
+        let zoned_now = now.to_zoned(tz.clone());
+
+        let range = match self {
+            RangePattern::Between { from, to } => TimeRange {
+                start: from.to_range(now, tz, config)?.start,
+                end: to.to_range(now, tz, config)?.end,
+            },
+
+            RangePattern::Past { n, stride } => {
+                let start = zoned_now
+                    .checked_sub(stride_to_span(*n, stride)?)
+                    .map_err(|e| ParseError::ConversionError(format!("date underflow: {}", e)))?;
+                TimeRange {
+                    start: start.timestamp(),
+                    end: now,
+                }
+            }
+
+            RangePattern::Coming { n, stride } => {
+                let end = zoned_now
+                    .checked_add(stride_to_span(*n, stride)?)
+                    .map_err(|e| ParseError::ConversionError(format!("date overflow: {}", e)))?;
+                TimeRange {
+                    start: now,
+                    end: end.timestamp(),
+                }
+            }
+
+            RangePattern::Period { anchor, unit } => {
+                let current = zoned_now.date();
+                let (start, length) = match unit {
+                    PeriodUnit::Week => (start_of_week(current, &config.week_start)?, 1.week()),
+                    PeriodUnit::Month => (current.first_of_month(), 1.month()),
+                    PeriodUnit::Year => (current.first_of_year(), 1.year()),
+                };
+                let start = match anchor {
+                    RangeAnchor::Last => start.checked_sub(length),
+                    RangeAnchor::This => Ok(start),
+                    RangeAnchor::Next => start.checked_add(length),
+                }
+                .map_err(|e| ParseError::ConversionError(format!("date overflow: {}", e)))?;
+                let end = start
+                    .checked_add(length)
+                    .map_err(|e| ParseError::ConversionError(format!("date overflow: {}", e)))?;
+                TimeRange {
+                    start: apply_time(start, &None, tz)?,
+                    end: apply_time(end, &None, tz)?,
+                }
+            }
+
+            RangePattern::Weekend(saturday) => {
+                let start = saturday.to_range(now, tz, config)?.start;
+                let monday = start
+                    .to_zoned(tz.clone())
+                    .date()
+                    .checked_add(2.days())
+                    .map_err(|e| ParseError::ConversionError(format!("date overflow: {}", e)))?;
+                TimeRange {
+                    start,
+                    end: apply_time(monday, &None, tz)?,
+                }
+            }
+
+            RangePattern::Single(pattern) => pattern.to_range(now, tz, config)?,
+        };
+
+        if range.end < range.start {
+            return Err(ParseError::ConversionError(format!(
+                "range ends before it starts: {} - {}",
+                range.start, range.end
+            )));
+        }
+
+        Ok(range)
+    }
+}
+
+/// `n` strides as a span, an error if it is out of the bounds of a span
+fn stride_to_span(n: u32, stride: &TimeStride) -> Result<jiff::Span, ParseError> {
+    let span = jiff::Span::new();
+    let n = n as i64;
+    match stride {
+        TimeStride::Seconds => span.try_seconds(n),
+        TimeStride::Minutes => span.try_minutes(n),
+        TimeStride::Hours => span.try_hours(n),
+        TimeStride::Days => span.try_days(n),
+        TimeStride::Weeks => span.try_weeks(n),
+        TimeStride::Months => span.try_months(n),
+        TimeStride::Years => span.try_years(n),
+    }
+    .map_err(|e| ParseError::ConversionError(format!("span out of range: {}", e)))
+}
+
 /// Apply a time component to a date, returning a UTC timestamp.
 ///
 /// The date is interpreted as being in the given timezone, then converted to UTC.
@@ -451,12 +1186,47 @@ fn apply_time(date: Date, time_opt: &Option<Time>, tz: &TimeZone) -> Result<Time
         (0, 0)
     };
 
-    date.at(hour, minute, 0, 0)
+    let time = jiff::civil::Time::new(hour, minute, 0, 0)
+        .map_err(|e| ParseError::ConversionError(format!("invalid time: {}", e)))?;
+
+    date.to_datetime(time)
         .to_zoned(tz.clone())
         .map(|z| z.timestamp())
         .map_err(|e| ParseError::ConversionError(format!("failed to create timestamp: {}", e)))
 }
 
+/// Build the date `year`-`month`-`day`, defaulting to the year of `current`.
+/// Two digit years are taken to be in the 21st century.
+fn calendar_date(
+    current: Date,
+    year: Option<u32>,
+    month: u32,
+    day: u32,
+) -> Result<Date, ParseError> {
+    let year = match year {
+        Some(year) if year < 100 => year as i64 + 2000,
+        Some(year) => year as i64,
+        None => current.year() as i64,
+    };
+    let (Ok(year), Ok(month), Ok(day)) =
+        (i16::try_from(year), i8::try_from(month), i8::try_from(day))
+    else {
+        return Err(ParseError::ConversionError(format!(
+            "invalid date: {}-{}-{}",
+            year, month, day
+        )));
+    };
+
+    Date::new(year, month, day)
+        .map_err(|e| ParseError::ConversionError(format!("invalid date: {}", e)))
+}
+
+/// The first day of the week containing `date`
+pub(crate) fn start_of_week(date: Date, week_start: &Weekday) -> Result<Date, ParseError> {
+    date.checked_sub(((week_position(date.weekday(), week_start) - 1) as i64).days())
+        .map_err(|e| ParseError::ConversionError(format!("date underflow: {}", e)))
+}
+
 fn find_next_weekday(now: &Zoned, target_weekday: &Weekday) -> Result<Date, ParseError> {
     let current = now.date();
     let current_weekday = weekday_to_number(current.weekday());
@@ -504,19 +1274,28 @@ fn find_next_moment(now: &Zoned, moment: &TimeMoment) -> Result<Date, ParseError
                 .map_err(|e| ParseError::ConversionError(format!("date overflow: {}", e)))
         }
 
+        TimeMoment::CalendarMonth => current
+            .first_of_month()
+            .checked_add(1.month())
+            .map_err(|e| ParseError::ConversionError(format!("date overflow: {}", e))),
+
         TimeMoment::Year => Date::new(current.year() + 1, 1, 1)
             .map_err(|e| ParseError::ConversionError(format!("invalid date: {}", e))),
     }
 }
 
-fn find_this_moment(now: &Zoned, moment: &TimeMoment) -> Result<Date, ParseError> {
+fn find_this_moment(
+    now: &Zoned,
+    moment: &TimeMoment,
+    week_start: &Weekday,
+) -> Result<Date, ParseError> {
     let current = now.date();
 
     match moment {
         TimeMoment::Weekday(wd) => {
             // "this friday" means the upcoming friday in the current week
-            let current_weekday = weekday_to_number(current.weekday());
-            let target = weekday_to_number(weekday_to_jiff(wd));
+            let current_weekday = week_position(current.weekday(), week_start);
+            let target = week_position(weekday_to_jiff(wd), week_start);
 
             if current_weekday <= target {
                 // Target is later this week
@@ -544,6 +1323,8 @@ fn find_this_moment(now: &Zoned, moment: &TimeMoment) -> Result<Date, ParseError
             Ok(current)
         }
 
+        TimeMoment::CalendarMonth => Ok(current.first_of_month()),
+
         TimeMoment::Year => {
             // "this year" = first day of current year
             Date::new(current.year(), 1, 1)
@@ -595,6 +1376,11 @@ fn find_last_moment(now: &Zoned, moment: &TimeMoment) -> Result<Date, ParseError
                 .map_err(|e| ParseError::ConversionError(format!("date underflow: {}", e)))
         }
 
+        TimeMoment::CalendarMonth => current
+            .first_of_month()
+            .checked_sub(1.month())
+            .map_err(|e| ParseError::ConversionError(format!("date underflow: {}", e))),
+
         TimeMoment::Year => Date::new(current.year() - 1, 1, 1)
             .map_err(|e| ParseError::ConversionError(format!("invalid date: {}", e))),
     }
@@ -612,6 +1398,11 @@ fn weekday_to_jiff(wd: &Weekday) -> jiff::civil::Weekday {
     }
 }
 
+/// Position of `wd` in a week starting on `week_start`, from 1 to 7
+fn week_position(wd: jiff::civil::Weekday, week_start: &Weekday) -> i8 {
+    (weekday_to_number(wd) - weekday_to_number(weekday_to_jiff(week_start))).rem_euclid(7) + 1
+}
+
 fn weekday_to_number(wd: jiff::civil::Weekday) -> i8 {
     match wd {
         jiff::civil::Weekday::Monday => 1,
@@ -656,69 +1447,100 @@ fn number<'src>() -> impl Parser<'src, &'src str, u32> {
         just("eleven").to(11),
         just("twelve").to(12),
         just("thirteen").to(13),
-        text::int(10).map(|s: &str| s.parse().unwrap()),
+        // digits, allowing leading zeros ("06") and ordinal suffixes ("3rd")
+        text::digits(10)
+            .at_most(9)
+            .to_slice()
+            .map(|s: &str| s.parse().unwrap())
+            .then_ignore(choice((just("st"), just("nd"), just("rd"), just("th"))).or_not()),
     ))
     .padded()
 }
 
-fn weekday<'src>() -> impl Parser<'src, &'src str, Weekday> {
-    choice((
-        just("monday"),
-        just("tuesday"),
-        just("wednesday"),
-        just("thursday"),
-        just("friday"),
-        just("saturday"),
-        just("sunday"),
-    ))
-    .map(|s| match s {
-        "monday" => Weekday::Monday,
-        "tuesday" => Weekday::Tuesday,
-        "wednesday" => Weekday::Wednesday,
-        "thursday" => Weekday::Thursday,
-        "friday" => Weekday::Friday,
-        "saturday" => Weekday::Saturday,
-        "sunday" => Weekday::Sunday,
-        _ => unreachable!(),
-    })
+const WEEKDAY_NAMES: [(&str, Weekday); 7] = [
+    ("monday", Weekday::Monday),
+    ("tuesday", Weekday::Tuesday),
+    ("wednesday", Weekday::Wednesday),
+    ("thursday", Weekday::Thursday),
+    ("friday", Weekday::Friday),
+    ("saturday", Weekday::Saturday),
+    ("sunday", Weekday::Sunday),
+];
+
+const MONTH_NAMES: [(&str, Month); 24] = [
+    ("january", Month::January),
+    ("february", Month::February),
+    ("march", Month::March),
+    ("april", Month::April),
+    ("may", Month::May),
+    ("june", Month::June),
+    ("july", Month::July),
+    ("august", Month::August),
+    ("september", Month::September),
+    ("october", Month::October),
+    ("november", Month::November),
+    ("december", Month::December),
+    ("jan", Month::January),
+    ("feb", Month::February),
+    ("mar", Month::March),
+    ("apr", Month::April),
+    ("jun", Month::June),
+    ("jul", Month::July),
+    ("aug", Month::August),
+    ("sept", Month::September),
+    ("sep", Month::September),
+    ("oct", Month::October),
+    ("nov", Month::November),
+    ("dec", Month::December),
+];
+
+/// Match any of the names of the table, trying longer names first so that
+/// "september" is not read as "sep"
+fn name_table<'src, T: Clone + 'src>(
+    mut names: Vec<(String, T)>,
+) -> impl Parser<'src, &'src str, T> + Clone {
+    names.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+    choice(
+        names
+            .into_iter()
+            .map(|(name, value)| just(name).to(value))
+            .collect::<Vec<_>>(),
+    )
     .padded()
 }
 
-fn month<'src>() -> impl Parser<'src, &'src str, Month> {
-    let padded_just = |p| just(p).padded();
-    choice((
-        padded_just("january"),
-        padded_just("february"),
-        padded_just("march"),
-        padded_just("april"),
-        padded_just("may"),
-        padded_just("june"),
-        padded_just("july"),
-        padded_just("august"),
-        padded_just("september"),
-        padded_just("october"),
-        padded_just("november"),
-        padded_just("december"),
-    ))
-    .map(|s: &str| match s {
-        "january" => Month::January,
-        "february" => Month::February,
-        "march" => Month::March,
-        "april" => Month::April,
-        "may" => Month::May,
-        "june" => Month::June,
-        "july" => Month::July,
-        "august" => Month::August,
-        "september" => Month::September,
-        "october" => Month::October,
-        "november" => Month::November,
-        "december" => Month::December,
-        _ => unreachable!(),
-    })
+fn weekday<'src>(config: &DateParserConfig) -> impl Parser<'src, &'src str, Weekday> + Clone {
+    let names = WEEKDAY_NAMES
+        .iter()
+        .map(|(name, weekday)| (name.to_string(), *weekday))
+        .chain(
+            config
+                .weekday_names
+                .iter()
+                .map(|(name, weekday)| (name.to_lowercase(), *weekday)),
+        )
+        .collect();
+    name_table(names)
+}
+
+fn month<'src>(config: &DateParserConfig) -> impl Parser<'src, &'src str, Month> + Clone {
+    let names = MONTH_NAMES
+        .iter()
+        .map(|(name, month)| (name.to_string(), *month))
+        .chain(
+            config
+                .month_names
+                .iter()
+                .map(|(name, month)| (name.to_lowercase(), *month)),
+        )
+        .collect();
+    name_table(names)
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-enum Weekday {
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Weekday {
+    #[default]
     Monday,
     Tuesday,
     Wednesday,
@@ -728,8 +1550,9 @@ enum Weekday {
     Sunday,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-enum Month {
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Month {
     January,
     February,
     March,
@@ -754,6 +1577,9 @@ enum NatDatToken {
     Next,
     At,
     Colon,
+    Dash,
+    Slash,
+    Comma,
     Today,
     Tomorrow,
     Yesterday,
@@ -772,6 +1598,9 @@ enum NatDatToken {
     Afternoon,
     Evening,
     Night,
+    Noon,
+    Midnight,
+    Tonight,
     // Other
     Weekend,
     Beginning,
@@ -779,6 +1608,10 @@ enum NatDatToken {
     Start,
     Of,
     The,
+    To,
+    And,
+    Between,
+    Past,
     // Values
     Number(u32),
     Month(Month),
@@ -793,6 +1626,11 @@ mod tests {
     use super::*;
     use jiff::civil::date;
 
+    /// A fixed offset, so that results do not depend on the system timezone
+    fn test_tz() -> TimeZone {
+        TimeZone::fixed(jiff::tz::offset(1))
+    }
+
     fn test_timestamp() -> Timestamp {
         // Thursday, January 16, 2025, 12:00:00 UTC
         date(2025, 1, 16)
@@ -804,7 +1642,7 @@ mod tests {
 
     /// Helper to assert date matches, ignoring time components
     fn assert_date_matches(result: Timestamp, expected_date: Date) {
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result_zoned = result.to_zoned(test_tz());
         assert_eq!(result_zoned.date(), expected_date);
     }
 
@@ -815,7 +1653,7 @@ mod tests {
         expected_hour: i8,
         expected_minute: i8,
     ) {
-        let zoned = result.to_zoned(TimeZone::system());
+        let zoned = result.to_zoned(test_tz());
         assert_eq!(zoned.date(), expected_date);
         assert_eq!(zoned.hour(), expected_hour);
         assert_eq!(zoned.minute(), expected_minute);
@@ -831,7 +1669,7 @@ mod tests {
         input: &str,
     ) {
         // Always check date using system timezone
-        let zoned_sys = result.to_zoned(TimeZone::system());
+        let zoned_sys = result.to_zoned(test_tz());
         assert_eq!(
             zoned_sys.date(),
             expected_date,
@@ -948,24 +1786,24 @@ mod tests {
     #[test]
     fn test_today() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("today", now).unwrap();
+        let result = NaturalDateParser::parse("today", now, &test_tz()).unwrap();
         let expected = date(2025, 1, 16)
             .at(0, 0, 0, 0)
-            .to_zoned(TimeZone::system())
+            .to_zoned(test_tz())
             .unwrap()
             .timestamp();
 
         // Check that the dates match (ignoring time)
-        let result_zoned = result.to_zoned(TimeZone::system());
-        let expected_zoned = expected.to_zoned(TimeZone::system());
+        let result_zoned = result.to_zoned(test_tz());
+        let expected_zoned = expected.to_zoned(test_tz());
         assert_eq!(result_zoned.date(), expected_zoned.date());
     }
 
     #[test]
     fn test_tomorrow() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("tomorrow", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("tomorrow", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         assert_eq!(result_zoned.date(), date(2025, 1, 17));
     }
@@ -973,8 +1811,8 @@ mod tests {
     #[test]
     fn test_yesterday() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("yesterday", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("yesterday", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         assert_eq!(result_zoned.date(), date(2025, 1, 15));
     }
@@ -982,8 +1820,8 @@ mod tests {
     #[test]
     fn test_in_days() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("in 3 days", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("in 3 days", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         assert_eq!(result_zoned.date(), date(2025, 1, 19));
     }
@@ -991,8 +1829,8 @@ mod tests {
     #[test]
     fn test_in_weeks() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("in 2 weeks", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("in 2 weeks", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         assert_eq!(result_zoned.date(), date(2025, 1, 30));
     }
@@ -1000,8 +1838,8 @@ mod tests {
     #[test]
     fn test_in_months() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("in 1 months", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("in 1 months", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         assert_eq!(result_zoned.date(), date(2025, 2, 16));
     }
@@ -1009,8 +1847,8 @@ mod tests {
     #[test]
     fn test_from_now() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("3 days from now", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("3 days from now", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         assert_eq!(result_zoned.date(), date(2025, 1, 19));
     }
@@ -1018,8 +1856,8 @@ mod tests {
     #[test]
     fn test_ago() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("3 days ago", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("3 days ago", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         assert_eq!(result_zoned.date(), date(2025, 1, 13));
     }
@@ -1027,8 +1865,8 @@ mod tests {
     #[test]
     fn test_next_weekday() {
         let now = test_timestamp(); // Thursday, Jan 16, 2025
-        let result = NaturalDateParser::parse("next monday", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("next monday", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         // Next Monday after Thursday should be Jan 20
         assert_eq!(result_zoned.date(), date(2025, 1, 20));
@@ -1037,18 +1875,36 @@ mod tests {
     #[test]
     fn test_next_friday() {
         let now = test_timestamp(); // Thursday, Jan 16, 2025
-        let result = NaturalDateParser::parse("next friday", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("next friday", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         // Next Friday after Thursday should be Jan 17
         assert_eq!(result_zoned.date(), date(2025, 1, 17));
     }
 
+    #[test]
+    fn test_weekend() {
+        let now = test_timestamp(); // Thursday, Jan 16, 2025
+        let cases = [
+            ("weekend", date(2025, 1, 18)),
+            ("this weekend", date(2025, 1, 18)),
+            ("next weekend", date(2025, 1, 18)),
+            ("last weekend", date(2025, 1, 11)),
+        ];
+        for (input, expected) in cases {
+            let result = NaturalDateParser::parse(input, now, &test_tz())
+                .unwrap_or_else(|e| panic!("failed to parse '{}': {:?}", input, e));
+            assert_date_matches(result, expected);
+        }
+        let result = NaturalDateParser::parse("next weekend at 10", now, &test_tz()).unwrap();
+        assert_datetime_matches(result, date(2025, 1, 18), 10, 0);
+    }
+
     #[test]
     fn test_last_weekday() {
         let now = test_timestamp(); // Thursday, Jan 16, 2025
-        let result = NaturalDateParser::parse("last monday", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("last monday", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         // Last Monday before Thursday should be Jan 13
         assert_eq!(result_zoned.date(), date(2025, 1, 13));
@@ -1057,8 +1913,8 @@ mod tests {
     #[test]
     fn test_this_friday() {
         let now = test_timestamp(); // Thursday, Jan 16, 2025
-        let result = NaturalDateParser::parse("this friday", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("this friday", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         // This Friday (upcoming Friday in current week) should be Jan 17
         assert_eq!(result_zoned.date(), date(2025, 1, 17));
@@ -1067,8 +1923,8 @@ mod tests {
     #[test]
     fn test_on_weekday() {
         let now = test_timestamp(); // Thursday, Jan 16, 2025
-        let result = NaturalDateParser::parse("on friday", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("on friday", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         // Next Friday should be Jan 17
         assert_eq!(result_zoned.date(), date(2025, 1, 17));
@@ -1077,8 +1933,8 @@ mod tests {
     #[test]
     fn test_with_time_at() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("tomorrow at 10:30", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("tomorrow at 10:30", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         assert_eq!(result_zoned.date(), date(2025, 1, 17));
         assert_eq!(result_zoned.hour(), 10);
@@ -1088,8 +1944,8 @@ mod tests {
     #[test]
     fn test_with_time_pm() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("tomorrow at 3 pm", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("tomorrow at 3 pm", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         assert_eq!(result_zoned.date(), date(2025, 1, 17));
         assert_eq!(result_zoned.hour(), 15); // 3 PM = 15:00
@@ -1098,8 +1954,8 @@ mod tests {
     #[test]
     fn test_with_time_am() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("tomorrow at 10 am", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("tomorrow at 10 am", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         assert_eq!(result_zoned.date(), date(2025, 1, 17));
         assert_eq!(result_zoned.hour(), 10);
@@ -1108,8 +1964,8 @@ mod tests {
     #[test]
     fn test_with_time_and_minutes_pm() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("next friday at 10:13 pm", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("next friday at 10:13 pm", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         assert_eq!(result_zoned.date(), date(2025, 1, 17));
         assert_eq!(result_zoned.hour(), 22); // 10 PM = 22:00
@@ -1119,8 +1975,8 @@ mod tests {
     #[test]
     fn test_number_words() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("in three days", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("in three days", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         assert_eq!(result_zoned.date(), date(2025, 1, 19));
     }
@@ -1128,13 +1984,13 @@ mod tests {
     #[test]
     fn test_case_insensitive() {
         let now = test_timestamp();
-        let result1 = NaturalDateParser::parse("TOMORROW", now).unwrap();
-        let result2 = NaturalDateParser::parse("Tomorrow", now).unwrap();
-        let result3 = NaturalDateParser::parse("tomorrow", now).unwrap();
+        let result1 = NaturalDateParser::parse("TOMORROW", now, &test_tz()).unwrap();
+        let result2 = NaturalDateParser::parse("Tomorrow", now, &test_tz()).unwrap();
+        let result3 = NaturalDateParser::parse("tomorrow", now, &test_tz()).unwrap();
 
-        let r1 = result1.to_zoned(TimeZone::system());
-        let r2 = result2.to_zoned(TimeZone::system());
-        let r3 = result3.to_zoned(TimeZone::system());
+        let r1 = result1.to_zoned(test_tz());
+        let r2 = result2.to_zoned(test_tz());
+        let r3 = result3.to_zoned(test_tz());
 
         assert_eq!(r1.date(), r2.date());
         assert_eq!(r2.date(), r3.date());
@@ -1143,8 +1999,8 @@ mod tests {
     #[test]
     fn test_next_month() {
         let now = test_timestamp(); // January 16, 2025
-        let result = NaturalDateParser::parse("next march", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("next march", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         // March is after January, so it should be March 1, 2025
         assert_eq!(result_zoned.date(), date(2025, 3, 1));
@@ -1153,8 +2009,8 @@ mod tests {
     #[test]
     fn test_last_month() {
         let now = test_timestamp(); // January 16, 2025
-        let result = NaturalDateParser::parse("last december", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("last december", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         // December is before January, so it should be December 1, 2024
         assert_eq!(result_zoned.date(), date(2024, 12, 1));
@@ -1163,7 +2019,7 @@ mod tests {
     #[test]
     fn test_hours_and_minutes() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("in 2 hours", now).unwrap();
+        let result = NaturalDateParser::parse("in 2 hours", now, &test_tz()).unwrap();
 
         // Should be 2 hours after 12:00 = 14:00
         let result_zoned = result.to_zoned(TimeZone::UTC);
@@ -1173,7 +2029,7 @@ mod tests {
     #[test]
     fn test_in_minutes() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("in 30 minutes", now).unwrap();
+        let result = NaturalDateParser::parse("in 30 minutes", now, &test_tz()).unwrap();
 
         // Should be 30 minutes after 12:00 = 12:30
         let result_zoned = result.to_zoned(TimeZone::UTC);
@@ -1185,7 +2041,7 @@ mod tests {
     #[test]
     fn test_in_seconds() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("in 30 seconds", now).unwrap();
+        let result = NaturalDateParser::parse("in 30 seconds", now, &test_tz()).unwrap();
 
         // Should be 30 seconds after 12:00:00
         let result_zoned = result.to_zoned(TimeZone::UTC);
@@ -1195,7 +2051,7 @@ mod tests {
     #[test]
     fn test_seconds_from_now() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("45 seconds from now", now).unwrap();
+        let result = NaturalDateParser::parse("45 seconds from now", now, &test_tz()).unwrap();
 
         let result_zoned = result.to_zoned(TimeZone::UTC);
         assert_eq!(result_zoned.second(), 45);
@@ -1204,7 +2060,7 @@ mod tests {
     #[test]
     fn test_seconds_ago() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("10 seconds ago", now).unwrap();
+        let result = NaturalDateParser::parse("10 seconds ago", now, &test_tz()).unwrap();
 
         let result_zoned = result.to_zoned(TimeZone::UTC);
         assert_eq!(result_zoned.second(), 50); // 12:00:00 - 10s = 11:59:50
@@ -1214,8 +2070,8 @@ mod tests {
     #[test]
     fn test_in_years() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("in 2 years", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("in 2 years", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         assert_eq!(result_zoned.date(), date(2027, 1, 16));
     }
@@ -1223,8 +2079,8 @@ mod tests {
     #[test]
     fn test_years_from_now() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("3 years from now", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("3 years from now", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         assert_eq!(result_zoned.date(), date(2028, 1, 16));
     }
@@ -1232,8 +2088,8 @@ mod tests {
     #[test]
     fn test_years_ago() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("1 years ago", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("1 years ago", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         assert_eq!(result_zoned.date(), date(2024, 1, 16));
     }
@@ -1254,11 +2110,11 @@ mod tests {
         ];
 
         for (input, expected_date, time_check) in test_cases {
-            let result = NaturalDateParser::parse(input, now)
+            let result = NaturalDateParser::parse(input, now, &test_tz())
                 .unwrap_or_else(|_| panic!("Failed to parse '{}'", input));
 
             // Always check date using system timezone
-            let zoned_sys = result.to_zoned(TimeZone::system());
+            let zoned_sys = result.to_zoned(test_tz());
             assert_eq!(
                 zoned_sys.date(),
                 expected_date,
@@ -1304,11 +2160,11 @@ mod tests {
         ];
 
         for (input, expected_date, time_check) in test_cases {
-            let result = NaturalDateParser::parse(input, now)
+            let result = NaturalDateParser::parse(input, now, &test_tz())
                 .unwrap_or_else(|_| panic!("Failed to parse '{}'", input));
 
             // Always check date using system timezone
-            let zoned_sys = result.to_zoned(TimeZone::system());
+            let zoned_sys = result.to_zoned(test_tz());
             assert_eq!(
                 zoned_sys.date(),
                 expected_date,
@@ -1341,7 +2197,7 @@ mod tests {
     #[test]
     fn test_in_days_with_time() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("in 5 days at 3 pm", now).unwrap();
+        let result = NaturalDateParser::parse("in 5 days at 3 pm", now, &test_tz()).unwrap();
 
         assert_datetime_matches(result, date(2025, 1, 21), 15, 0);
     }
@@ -1349,7 +2205,7 @@ mod tests {
     #[test]
     fn test_in_weeks_with_time() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("in 2 weeks at 8:30 am", now).unwrap();
+        let result = NaturalDateParser::parse("in 2 weeks at 8:30 am", now, &test_tz()).unwrap();
 
         assert_datetime_matches(result, date(2025, 1, 30), 8, 30);
     }
@@ -1357,7 +2213,7 @@ mod tests {
     #[test]
     fn test_ago_with_time() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("3 days ago at 2 pm", now).unwrap();
+        let result = NaturalDateParser::parse("3 days ago at 2 pm", now, &test_tz()).unwrap();
 
         assert_datetime_matches(result, date(2025, 1, 13), 14, 0);
     }
@@ -1365,7 +2221,8 @@ mod tests {
     #[test]
     fn test_from_now_with_time() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("2 weeks from now at 10 am", now).unwrap();
+        let result =
+            NaturalDateParser::parse("2 weeks from now at 10 am", now, &test_tz()).unwrap();
 
         assert_datetime_matches(result, date(2025, 1, 30), 10, 0);
     }
@@ -1373,7 +2230,7 @@ mod tests {
     #[test]
     fn test_in_months_with_time() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("in 3 months at 11:45 pm", now).unwrap();
+        let result = NaturalDateParser::parse("in 3 months at 11:45 pm", now, &test_tz()).unwrap();
 
         assert_datetime_matches(result, date(2025, 4, 16), 23, 45);
     }
@@ -1381,7 +2238,7 @@ mod tests {
     #[test]
     fn test_in_years_with_time() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("in 1 years at 2 pm", now).unwrap();
+        let result = NaturalDateParser::parse("in 1 years at 2 pm", now, &test_tz()).unwrap();
 
         assert_datetime_matches(result, date(2026, 1, 16), 14, 0);
     }
@@ -1391,8 +2248,8 @@ mod tests {
     #[test]
     fn test_next_week() {
         let now = test_timestamp(); // Thursday, Jan 16, 2025
-        let result = NaturalDateParser::parse("next week", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("next week", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         // Next week should be 7 days ahead = Jan 23
         assert_eq!(result_zoned.date(), date(2025, 1, 23));
@@ -1401,8 +2258,8 @@ mod tests {
     #[test]
     fn test_this_week() {
         let now = test_timestamp(); // Thursday, Jan 16, 2025
-        let result = NaturalDateParser::parse("this week", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("this week", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         // This week should return current date
         assert_eq!(result_zoned.date(), date(2025, 1, 16));
@@ -1411,8 +2268,8 @@ mod tests {
     #[test]
     fn test_last_week() {
         let now = test_timestamp(); // Thursday, Jan 16, 2025
-        let result = NaturalDateParser::parse("last week", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("last week", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         // Last week should be 7 days ago = Jan 9
         assert_eq!(result_zoned.date(), date(2025, 1, 9));
@@ -1421,8 +2278,8 @@ mod tests {
     #[test]
     fn test_next_year() {
         let now = test_timestamp(); // 2025
-        let result = NaturalDateParser::parse("next year", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("next year", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         // Next year should be Jan 1, 2026
         assert_eq!(result_zoned.date(), date(2026, 1, 1));
@@ -1431,8 +2288,8 @@ mod tests {
     #[test]
     fn test_this_year() {
         let now = test_timestamp(); // 2025
-        let result = NaturalDateParser::parse("this year", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("this year", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         // This year should be Jan 1, 2025
         assert_eq!(result_zoned.date(), date(2025, 1, 1));
@@ -1441,8 +2298,8 @@ mod tests {
     #[test]
     fn test_last_year() {
         let now = test_timestamp(); // 2025
-        let result = NaturalDateParser::parse("last year", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("last year", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         // Last year should be Jan 1, 2024
         assert_eq!(result_zoned.date(), date(2024, 1, 1));
@@ -1451,7 +2308,7 @@ mod tests {
     #[test]
     fn test_next_month_with_time() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("next january at 9 am", now).unwrap();
+        let result = NaturalDateParser::parse("next january at 9 am", now, &test_tz()).unwrap();
 
         // January is before current month (January), so next January is 2026
         assert_datetime_matches(result, date(2026, 1, 1), 9, 0);
@@ -1460,7 +2317,7 @@ mod tests {
     #[test]
     fn test_last_year_with_time() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("last year at 12 pm", now).unwrap();
+        let result = NaturalDateParser::parse("last year at 12 pm", now, &test_tz()).unwrap();
 
         // Last year at noon
         assert_datetime_matches(result, date(2024, 1, 1), 12, 0);
@@ -1469,7 +2326,7 @@ mod tests {
     #[test]
     fn test_this_week_with_time() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("this week at 2 pm", now).unwrap();
+        let result = NaturalDateParser::parse("this week at 2 pm", now, &test_tz()).unwrap();
 
         // This week at 2pm (current date)
         assert_datetime_matches(result, date(2025, 1, 16), 14, 0);
@@ -1490,7 +2347,7 @@ mod tests {
         ];
 
         for (input, expected_date) in test_cases {
-            let result = NaturalDateParser::parse(input, now).unwrap();
+            let result = NaturalDateParser::parse(input, now, &test_tz()).unwrap();
             assert_date_matches(result, expected_date);
         }
     }
@@ -1510,7 +2367,7 @@ mod tests {
         ];
 
         for (input, expected_date) in test_cases {
-            let result = NaturalDateParser::parse(input, now).unwrap();
+            let result = NaturalDateParser::parse(input, now, &test_tz()).unwrap();
             assert_date_matches(result, expected_date);
         }
     }
@@ -1530,7 +2387,7 @@ mod tests {
         ];
 
         for (input, expected_date) in test_cases {
-            let result = NaturalDateParser::parse(input, now).unwrap();
+            let result = NaturalDateParser::parse(input, now, &test_tz()).unwrap();
             assert_date_matches(result, expected_date);
         }
     }
@@ -1551,7 +2408,7 @@ mod tests {
         ];
 
         for (input, expected_date) in test_cases {
-            let result = NaturalDateParser::parse(input, now).unwrap();
+            let result = NaturalDateParser::parse(input, now, &test_tz()).unwrap();
             assert_date_matches(result, expected_date);
         }
     }
@@ -1561,7 +2418,7 @@ mod tests {
     #[test]
     fn test_midnight_twelve_am() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("tomorrow at 12 am", now).unwrap();
+        let result = NaturalDateParser::parse("tomorrow at 12 am", now, &test_tz()).unwrap();
 
         // 12:00 AM should be midnight (00:00)
         assert_datetime_matches(result, date(2025, 1, 17), 0, 0);
@@ -1570,7 +2427,7 @@ mod tests {
     #[test]
     fn test_noon_twelve_pm() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("tomorrow at 12 pm", now).unwrap();
+        let result = NaturalDateParser::parse("tomorrow at 12 pm", now, &test_tz()).unwrap();
 
         // 12:00 PM should be noon (12:00)
         assert_datetime_matches(result, date(2025, 1, 17), 12, 0);
@@ -1579,7 +2436,7 @@ mod tests {
     #[test]
     fn test_one_am() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("tomorrow at 1 am", now).unwrap();
+        let result = NaturalDateParser::parse("tomorrow at 1 am", now, &test_tz()).unwrap();
 
         assert_datetime_matches(result, date(2025, 1, 17), 1, 0);
     }
@@ -1587,7 +2444,7 @@ mod tests {
     #[test]
     fn test_one_pm() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("tomorrow at 1 pm", now).unwrap();
+        let result = NaturalDateParser::parse("tomorrow at 1 pm", now, &test_tz()).unwrap();
 
         assert_datetime_matches(result, date(2025, 1, 17), 13, 0);
     }
@@ -1595,7 +2452,7 @@ mod tests {
     #[test]
     fn test_eleven_am() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("tomorrow at 11 am", now).unwrap();
+        let result = NaturalDateParser::parse("tomorrow at 11 am", now, &test_tz()).unwrap();
 
         assert_datetime_matches(result, date(2025, 1, 17), 11, 0);
     }
@@ -1603,7 +2460,7 @@ mod tests {
     #[test]
     fn test_eleven_pm() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("tomorrow at 11 pm", now).unwrap();
+        let result = NaturalDateParser::parse("tomorrow at 11 pm", now, &test_tz()).unwrap();
 
         assert_datetime_matches(result, date(2025, 1, 17), 23, 0);
     }
@@ -1611,7 +2468,7 @@ mod tests {
     #[test]
     fn test_hour_only_no_minutes_am() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("tomorrow at 5 am", now).unwrap();
+        let result = NaturalDateParser::parse("tomorrow at 5 am", now, &test_tz()).unwrap();
 
         assert_datetime_matches(result, date(2025, 1, 17), 5, 0);
     }
@@ -1619,7 +2476,7 @@ mod tests {
     #[test]
     fn test_hour_only_no_minutes_pm() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("tomorrow at 3 pm", now).unwrap();
+        let result = NaturalDateParser::parse("tomorrow at 3 pm", now, &test_tz()).unwrap();
 
         assert_datetime_matches(result, date(2025, 1, 17), 15, 0);
     }
@@ -1627,7 +2484,7 @@ mod tests {
     #[test]
     fn test_today_with_time() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("today at 2 pm", now).unwrap();
+        let result = NaturalDateParser::parse("today at 2 pm", now, &test_tz()).unwrap();
 
         assert_datetime_matches(result, date(2025, 1, 16), 14, 0);
     }
@@ -1635,7 +2492,7 @@ mod tests {
     #[test]
     fn test_yesterday_with_time() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("yesterday at 8:30 am", now).unwrap();
+        let result = NaturalDateParser::parse("yesterday at 8:30 am", now, &test_tz()).unwrap();
 
         assert_datetime_matches(result, date(2025, 1, 15), 8, 30);
     }
@@ -1643,7 +2500,7 @@ mod tests {
     #[test]
     fn test_on_weekday_with_time() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("on monday at 7:15 am", now).unwrap();
+        let result = NaturalDateParser::parse("on monday at 7:15 am", now, &test_tz()).unwrap();
 
         assert_datetime_matches(result, date(2025, 1, 20), 7, 15);
     }
@@ -1651,7 +2508,7 @@ mod tests {
     #[test]
     fn test_midnight_with_minutes() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("tomorrow at 12:30 am", now).unwrap();
+        let result = NaturalDateParser::parse("tomorrow at 12:30 am", now, &test_tz()).unwrap();
 
         // 12:30 AM should be 00:30
         assert_datetime_matches(result, date(2025, 1, 17), 0, 30);
@@ -1662,21 +2519,21 @@ mod tests {
     #[test]
     fn test_empty_string() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("", now);
+        let result = NaturalDateParser::parse("", now, &test_tz());
         assert!(result.is_err(), "Empty string should fail to parse");
     }
 
     #[test]
     fn test_unsupported_pattern() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("foobar baz", now);
+        let result = NaturalDateParser::parse("foobar baz", now, &test_tz());
         assert!(result.is_err(), "Unsupported pattern should fail to parse");
     }
 
     #[test]
     fn test_partial_pattern_missing_unit() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("in 5", now);
+        let result = NaturalDateParser::parse("in 5", now, &test_tz());
         assert!(
             result.is_err(),
             "Partial pattern 'in 5' without time unit should fail"
@@ -1686,7 +2543,7 @@ mod tests {
     #[test]
     fn test_invalid_time_hour_over_24() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("tomorrow at 25:00", now);
+        let result = NaturalDateParser::parse("tomorrow at 25:00", now, &test_tz());
         // This might succeed at parse but fail at conversion - either is acceptable
         if let Ok(timestamp) = result {
             // If it parses, it should handle the overflow somehow
@@ -1699,14 +2556,14 @@ mod tests {
     #[test]
     fn test_just_time_no_date() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("at 3 pm", now);
+        let result = NaturalDateParser::parse("at 3 pm", now, &test_tz());
         assert!(result.is_err(), "Time without date should fail to parse");
     }
 
     #[test]
     fn test_invalid_weekday() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("next fooday", now);
+        let result = NaturalDateParser::parse("next fooday", now, &test_tz());
         assert!(
             result.is_err(),
             "Invalid weekday 'fooday' should fail to parse"
@@ -1716,7 +2573,7 @@ mod tests {
     #[test]
     fn test_invalid_month() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("next foovember", now);
+        let result = NaturalDateParser::parse("next foovember", now, &test_tz());
         assert!(
             result.is_err(),
             "Invalid month 'foovember' should fail to parse"
@@ -1726,7 +2583,7 @@ mod tests {
     #[test]
     fn test_missing_number() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("in days", now);
+        let result = NaturalDateParser::parse("in days", now, &test_tz());
         assert!(
             result.is_err(),
             "Missing number in 'in days' should fail to parse"
@@ -1736,7 +2593,7 @@ mod tests {
     #[test]
     fn test_double_pattern() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("next last monday", now);
+        let result = NaturalDateParser::parse("next last monday", now, &test_tz());
         assert!(
             result.is_err(),
             "Conflicting patterns 'next last' should fail to parse"
@@ -1748,8 +2605,8 @@ mod tests {
     #[test]
     fn test_large_day_offset() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("in 365 days", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("in 365 days", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         // 365 days from Jan 16, 2025 should be Jan 16, 2026
         assert_eq!(result_zoned.date(), date(2026, 1, 16));
@@ -1758,8 +2615,8 @@ mod tests {
     #[test]
     fn test_large_week_offset() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("in 52 weeks", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("in 52 weeks", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         // 52 weeks from Jan 16, 2025 should be around Jan 15, 2026
         assert_eq!(result_zoned.date(), date(2026, 1, 15));
@@ -1768,13 +2625,30 @@ mod tests {
     #[test]
     fn test_very_large_number() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("in 1000 days", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("in 1000 days", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         // 1000 days from Jan 16, 2025 should be around Oct 13, 2027
         assert_eq!(result_zoned.date(), date(2027, 10, 13));
     }
 
+    #[test]
+    fn test_out_of_range_span() {
+        let now = test_timestamp();
+        for input in [
+            "99999999 days ago",
+            "in 99999999 weeks",
+            "99999999 years from now",
+            "past 99999999 months",
+        ] {
+            assert!(
+                NaturalDateParser::parse(input, now, &test_tz()).is_err()
+                    && NaturalDateParser::parse_range(input, now, &test_tz()).is_err(),
+                "{input} should fail to parse"
+            );
+        }
+    }
+
     #[test]
     fn test_month_boundary_january_31() {
         // Test from Jan 31 going forward
@@ -1784,8 +2658,8 @@ mod tests {
             .unwrap()
             .timestamp();
 
-        let result = NaturalDateParser::parse("tomorrow", jan_31).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("tomorrow", jan_31, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         // Tomorrow from Jan 31 should be Feb 1
         assert_eq!(result_zoned.date(), date(2025, 2, 1));
@@ -1800,8 +2674,8 @@ mod tests {
             .unwrap()
             .timestamp();
 
-        let result = NaturalDateParser::parse("tomorrow", dec_31).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("tomorrow", dec_31, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         // Tomorrow from Dec 31, 2024 should be Jan 1, 2025
         assert_eq!(result_zoned.date(), date(2025, 1, 1));
@@ -1816,8 +2690,8 @@ mod tests {
             .unwrap()
             .timestamp();
 
-        let result = NaturalDateParser::parse("in 1 months", jan_31).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("in 1 months", jan_31, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         // 1 month from Jan 31 should handle February's shorter length
         // jiff typically adjusts to Feb 28 (or March 2/3 depending on implementation)
@@ -1846,7 +2720,7 @@ mod tests {
         ];
 
         for (input, expected_date) in test_cases {
-            let result = NaturalDateParser::parse(input, now)
+            let result = NaturalDateParser::parse(input, now, &test_tz())
                 .unwrap_or_else(|_| panic!("Failed to parse '{}'", input));
             assert_date_matches(result, expected_date);
         }
@@ -1855,8 +2729,8 @@ mod tests {
     #[test]
     fn test_large_years_offset() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("in 50 years", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("in 50 years", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         assert_eq!(result_zoned.date(), date(2075, 1, 16));
     }
@@ -1864,12 +2738,71 @@ mod tests {
     #[test]
     fn test_large_ago_offset() {
         let now = test_timestamp();
-        let result = NaturalDateParser::parse("500 days ago", now).unwrap();
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result = NaturalDateParser::parse("500 days ago", now, &test_tz()).unwrap();
+        let result_zoned = result.to_zoned(test_tz());
 
         // 500 days before Jan 16, 2025 should be around Sep 4, 2023
         assert_eq!(result_zoned.date(), date(2023, 9, 4));
     }
+
+    #[test]
+    fn test_find_date() {
+        let now = test_timestamp();
+        let config = DateParserConfig::default();
+        let text = "call Bob next friday, about the trip";
+        let found = find_date(text, now, &test_tz(), &config).unwrap();
+        assert_eq!(&text[found.start..found.end], "next friday");
+        assert_eq!(
+            found.timestamp,
+            NaturalDateParser::parse("next friday", now, &test_tz()).unwrap()
+        );
+
+        let text = "renew passport (2025-06-01)";
+        let found = find_date(text, now, &test_tz(), &config).unwrap();
+        assert_eq!(&text[found.start..found.end], "2025-06-01");
+
+        // the longest expression is preferred
+        let text = "dentist tomorrow at 3pm";
+        let found = find_date(text, now, &test_tz(), &config).unwrap();
+        assert_eq!(&text[found.start..found.end], "tomorrow at 3pm");
+
+        assert_eq!(find_date("call Bob", now, &test_tz(), &config), None);
+        assert_eq!(find_date("", now, &test_tz(), &config), None);
+
+        // words of the text rather than dates
+        for text in [
+            "work on night mode",
+            "fix the morning standup doc",
+            "score was 10/20",
+        ] {
+            assert_eq!(find_date(text, now, &test_tz(), &config), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn test_find_date_after_marker() {
+        let now = test_timestamp();
+        let config = DateParserConfig::default();
+        let found = |text: &'static str| {
+            let found = find_date(text, now, &test_tz(), &config).unwrap();
+            &text[found.start..found.end]
+        };
+        assert_eq!(found("score 10/20 by friday due: 06/01"), "06/01");
+        assert_eq!(
+            found("draft @due(tomorrow morning) with next friday's notes"),
+            "tomorrow morning"
+        );
+        assert_eq!(found("renew passport 📅 2025-06-01"), "2025-06-01");
+        assert_eq!(found("DUE: tomorrow night"), "tomorrow night");
+
+        // only the expression following the marker is taken
+        assert_eq!(
+            find_date("due: soon, next friday", now, &test_tz(), &config),
+            None
+        );
+        // not a marker
+        assert_eq!(found("overdue: call Bob tomorrow"), "tomorrow");
+    }
 }
 
 // ===== Generative Tests Module =====
@@ -1879,6 +2812,10 @@ mod generative_tests {
     use super::*;
     use jiff::{civil::date, tz::TimeZone};
 
+    fn test_tz() -> TimeZone {
+        TimeZone::fixed(jiff::tz::offset(1))
+    }
+
     fn test_timestamp() -> Timestamp {
         // Thursday, January 16, 2025, 12:00:00 UTC
         date(2025, 1, 16)
@@ -1890,7 +2827,7 @@ mod generative_tests {
 
     /// Helper to assert date matches, ignoring time components
     fn assert_date_matches(result: Timestamp, expected_date: Date) {
-        let result_zoned = result.to_zoned(TimeZone::system());
+        let result_zoned = result.to_zoned(test_tz());
         assert_eq!(result_zoned.date(), expected_date);
     }
 
@@ -1901,7 +2838,7 @@ mod generative_tests {
         expected_hour: i8,
         expected_minute: i8,
     ) {
-        let zoned = result.to_zoned(TimeZone::system());
+        let zoned = result.to_zoned(test_tz());
         assert_eq!(zoned.date(), expected_date);
         assert_eq!(zoned.hour(), expected_hour);
         assert_eq!(zoned.minute(), expected_minute);
@@ -1914,7 +2851,7 @@ mod generative_tests {
         time_check: Option<(i8, i8, i8)>,
         input: &str,
     ) {
-        let zoned_sys = result.to_zoned(TimeZone::system());
+        let zoned_sys = result.to_zoned(test_tz());
         assert_eq!(
             zoned_sys.date(),
             expected_date,
@@ -2037,7 +2974,7 @@ mod generative_tests {
         ];
 
         for (input, expected_date, time_check) in test_cases {
-            let result = NaturalDateParser::parse(input, now)
+            let result = NaturalDateParser::parse(input, now, &test_tz())
                 .unwrap_or_else(|_| panic!("Failed to parse '{}'", input));
             assert_timestamp_with_time(result, expected_date, time_check, input);
         }
@@ -2058,7 +2995,7 @@ mod generative_tests {
         ];
 
         for (input, expected_date, time_check) in test_cases {
-            let result = NaturalDateParser::parse(input, now)
+            let result = NaturalDateParser::parse(input, now, &test_tz())
                 .unwrap_or_else(|_| panic!("Failed to parse '{}'", input));
             assert_timestamp_with_time(result, expected_date, time_check, input);
         }
@@ -2079,7 +3016,7 @@ mod generative_tests {
         ];
 
         for (input, expected_date, time_check) in test_cases {
-            let result = NaturalDateParser::parse(input, now)
+            let result = NaturalDateParser::parse(input, now, &test_tz())
                 .unwrap_or_else(|_| panic!("Failed to parse '{}'", input));
             assert_timestamp_with_time(result, expected_date, time_check, input);
         }
@@ -2102,7 +3039,7 @@ mod generative_tests {
         // Test "next" pattern for all weekdays
         for day in &weekdays {
             let input = format!("next {}", day);
-            let result = NaturalDateParser::parse(&input, now)
+            let result = NaturalDateParser::parse(&input, now, &test_tz())
                 .unwrap_or_else(|_| panic!("Failed to parse '{}'", input));
             let expected_date = next_weekday_date(day);
             assert_date_matches(result, expected_date);
@@ -2111,7 +3048,7 @@ mod generative_tests {
         // Test "this" pattern for all weekdays
         for day in &weekdays {
             let input = format!("this {}", day);
-            let result = NaturalDateParser::parse(&input, now)
+            let result = NaturalDateParser::parse(&input, now, &test_tz())
                 .unwrap_or_else(|_| panic!("Failed to parse '{}'", input));
             let expected_date = this_weekday_date(day);
             assert_date_matches(result, expected_date);
@@ -2120,7 +3057,7 @@ mod generative_tests {
         // Test "last" pattern for all weekdays
         for day in &weekdays {
             let input = format!("last {}", day);
-            let result = NaturalDateParser::parse(&input, now)
+            let result = NaturalDateParser::parse(&input, now, &test_tz())
                 .unwrap_or_else(|_| panic!("Failed to parse '{}'", input));
             let expected_date = last_weekday_date(day);
             assert_date_matches(result, expected_date);
@@ -2129,7 +3066,7 @@ mod generative_tests {
         // Test "on" pattern for all weekdays (behaves like "next")
         for day in &weekdays {
             let input = format!("on {}", day);
-            let result = NaturalDateParser::parse(&input, now)
+            let result = NaturalDateParser::parse(&input, now, &test_tz())
                 .unwrap_or_else(|_| panic!("Failed to parse '{}'", input));
             let expected_date = next_weekday_date(day);
             assert_date_matches(result, expected_date);
@@ -2157,7 +3094,7 @@ mod generative_tests {
 
         for month in months {
             let input = format!("next {}", month);
-            let result = NaturalDateParser::parse(&input, now)
+            let result = NaturalDateParser::parse(&input, now, &test_tz())
                 .unwrap_or_else(|_| panic!("Failed to parse '{}'", input));
             let expected_date = next_month_date(month);
             assert_date_matches(result, expected_date);
@@ -2185,7 +3122,7 @@ mod generative_tests {
 
         for month in months {
             let input = format!("last {}", month);
-            let result = NaturalDateParser::parse(&input, now)
+            let result = NaturalDateParser::parse(&input, now, &test_tz())
                 .unwrap_or_else(|_| panic!("Failed to parse '{}'", input));
             let expected_date = last_month_date(month);
             assert_date_matches(result, expected_date);
@@ -2210,7 +3147,7 @@ mod generative_tests {
         ];
 
         for (input, expected_date, hour, minute) in test_cases {
-            let result = NaturalDateParser::parse(input, now)
+            let result = NaturalDateParser::parse(input, now, &test_tz())
                 .unwrap_or_else(|_| panic!("Failed to parse '{}'", input));
             assert_datetime_matches(result, expected_date, hour, minute);
         }
@@ -2232,7 +3169,7 @@ mod generative_tests {
         ];
 
         for (input, expected_date, hour, minute) in test_cases {
-            let result = NaturalDateParser::parse(input, now)
+            let result = NaturalDateParser::parse(input, now, &test_tz())
                 .unwrap_or_else(|_| panic!("Failed to parse '{}'", input));
             assert_datetime_matches(result, expected_date, hour, minute);
         }
@@ -2259,7 +3196,7 @@ mod generative_tests {
         ];
 
         for (input, expected_date) in test_cases {
-            let result = NaturalDateParser::parse(input, now)
+            let result = NaturalDateParser::parse(input, now, &test_tz())
                 .unwrap_or_else(|_| panic!("Failed to parse '{}'", input));
             assert_date_matches(result, expected_date);
         }
@@ -2280,7 +3217,7 @@ mod generative_tests {
         ];
 
         for (input, expected_date) in test_cases {
-            let result = NaturalDateParser::parse(input, now)
+            let result = NaturalDateParser::parse(input, now, &test_tz())
                 .unwrap_or_else(|_| panic!("Failed to parse '{}'", input));
             assert_date_matches(result, expected_date);
         }
@@ -2300,8 +3237,8 @@ mod generative_tests {
         ];
 
         for (numeric, word) in test_pairs {
-            let result_numeric = NaturalDateParser::parse(numeric, now);
-            let result_word = NaturalDateParser::parse(word, now);
+            let result_numeric = NaturalDateParser::parse(numeric, now, &test_tz());
+            let result_word = NaturalDateParser::parse(word, now, &test_tz());
 
             assert!(
                 result_numeric.is_ok(),
@@ -2312,8 +3249,8 @@ mod generative_tests {
 
             // Both should parse to the same date
             if let (Ok(num_ts), Ok(word_ts)) = (result_numeric, result_word) {
-                let num_zoned = num_ts.to_zoned(TimeZone::system());
-                let word_zoned = word_ts.to_zoned(TimeZone::system());
+                let num_zoned = num_ts.to_zoned(test_tz());
+                let word_zoned = word_ts.to_zoned(test_tz());
                 assert_eq!(
                     num_zoned.date(),
                     word_zoned.date(),
@@ -2344,9 +3281,240 @@ mod generative_tests {
         ];
 
         for (input, expected_date) in test_cases {
-            let result = NaturalDateParser::parse(input, now)
+            let result = NaturalDateParser::parse(input, now, &test_tz())
                 .unwrap_or_else(|_| panic!("Failed to parse '{}'", input));
             assert_date_matches(result, expected_date);
         }
     }
+    #[test]
+    fn test_iso_date() {
+        let now = test_timestamp();
+        let result = NaturalDateParser::parse("2025-06-01", now, &test_tz()).unwrap();
+        assert_date_matches(result, date(2025, 6, 1));
+
+        let result = NaturalDateParser::parse("2025-06-01 at 14:30", now, &test_tz()).unwrap();
+        assert_datetime_matches(result, date(2025, 6, 1), 14, 30);
+    }
+
+    #[test]
+    fn test_slash_date() {
+        let now = test_timestamp();
+        let result = NaturalDateParser::parse("06/01", now, &test_tz()).unwrap();
+        assert_date_matches(result, date(2025, 6, 1));
+
+        let result = NaturalDateParser::parse("12/24/26", now, &test_tz()).unwrap();
+        assert_date_matches(result, date(2026, 12, 24));
+    }
+
+    #[test]
+    fn test_month_day() {
+        let now = test_timestamp();
+        for input in [
+            "march 3",
+            "March 3rd",
+            "on mar 3",
+            "3 march",
+            "the 3rd of march",
+        ] {
+            let result = NaturalDateParser::parse(input, now, &test_tz()).unwrap();
+            assert_date_matches(result, date(2025, 3, 3));
+        }
+    }
+
+    #[test]
+    fn test_month_day_year() {
+        let now = test_timestamp();
+        for input in [
+            "3rd of march 2026",
+            "march 3, 2026",
+            "march 3 2026",
+            "sept 3 2026",
+        ] {
+            let result = NaturalDateParser::parse(input, now, &test_tz()).unwrap();
+            let expected = if input.starts_with("sept") {
+                date(2026, 9, 3)
+            } else {
+                date(2026, 3, 3)
+            };
+            assert_date_matches(result, expected);
+        }
+
+        let result = NaturalDateParser::parse("march 3 2026 at 5pm", now, &test_tz()).unwrap();
+        assert_datetime_matches(result, date(2026, 3, 3), 17, 0);
+    }
+
+    #[test]
+    fn test_invalid_calendar_date() {
+        let now = test_timestamp();
+        assert!(NaturalDateParser::parse("2025-02-30", now, &test_tz()).is_err());
+        assert!(NaturalDateParser::parse("13/01", now, &test_tz()).is_err());
+        assert!(NaturalDateParser::parse("march 32", now, &test_tz()).is_err());
+    }
+    /// Assert that `input` covers the days `first` through `last`
+    fn assert_range_dates(input: &str, first: Date, last: Date) {
+        let range = NaturalDateParser::parse_range(input, test_timestamp(), &test_tz()).unwrap();
+        assert_eq!(
+            range.dates(&test_tz()),
+            (first, last),
+            "Range mismatch for '{}'",
+            input
+        );
+    }
+
+    #[test]
+    fn test_range_single_day() {
+        assert_range_dates("today", date(2025, 1, 16), date(2025, 1, 16));
+        assert_range_dates("2025-06-01", date(2025, 6, 1), date(2025, 6, 1));
+        assert_range_dates("3 days ago", date(2025, 1, 13), date(2025, 1, 13));
+
+        let range =
+            NaturalDateParser::parse_range("tomorrow at 10", test_timestamp(), &test_tz()).unwrap();
+        assert_eq!(range.end, range.start + 1.minute());
+    }
+
+    #[test]
+    fn test_range_between() {
+        assert_range_dates(
+            "from monday to friday",
+            date(2025, 1, 13),
+            date(2025, 1, 17),
+        );
+        assert_range_dates("monday to wednesday", date(2025, 1, 13), date(2025, 1, 15));
+        assert_range_dates(
+            "between march 3 and march 5",
+            date(2025, 3, 3),
+            date(2025, 3, 5),
+        );
+        assert_range_dates(
+            "2025-06-01 until 2025-06-07",
+            date(2025, 6, 1),
+            date(2025, 6, 7),
+        );
+        assert!(
+            NaturalDateParser::parse_range("from friday to monday", test_timestamp(), &test_tz())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_range_periods() {
+        assert_range_dates("this week", date(2025, 1, 13), date(2025, 1, 19));
+        assert_range_dates("last week", date(2025, 1, 6), date(2025, 1, 12));
+        assert_range_dates("next week", date(2025, 1, 20), date(2025, 1, 26));
+        assert_range_dates("this month", date(2025, 1, 1), date(2025, 1, 31));
+        assert_range_dates("last month", date(2024, 12, 1), date(2024, 12, 31));
+        assert_range_dates("next year", date(2026, 1, 1), date(2026, 12, 31));
+    }
+
+    #[test]
+    fn test_range_weekend() {
+        // Thursday, Jan 16, 2025
+        assert_range_dates("weekend", date(2025, 1, 18), date(2025, 1, 19));
+        assert_range_dates("this weekend", date(2025, 1, 18), date(2025, 1, 19));
+        assert_range_dates("next weekend", date(2025, 1, 18), date(2025, 1, 19));
+        assert_range_dates("last weekend", date(2025, 1, 11), date(2025, 1, 12));
+    }
+
+    #[test]
+    fn test_range_durations() {
+        let now = test_timestamp();
+        let range = NaturalDateParser::parse_range("last 3 weeks", now, &test_tz()).unwrap();
+        assert_eq!(range.end, now);
+        assert_eq!(range.start.to_zoned(test_tz()).date(), date(2024, 12, 26));
+
+        let range = NaturalDateParser::parse_range("past 2 days", now, &test_tz()).unwrap();
+        assert_eq!(range.end, now);
+        assert!(range.contains(now - 1.hour()));
+
+        let range = NaturalDateParser::parse_range("next 10 days", now, &test_tz()).unwrap();
+        assert_eq!(range.start, now);
+        assert_eq!(range.end.to_zoned(test_tz()).date(), date(2025, 1, 26));
+    }
+
+    #[test]
+    fn test_month_moment() {
+        let now = test_timestamp();
+        let result = NaturalDateParser::parse("next month", now, &test_tz()).unwrap();
+        assert_date_matches(result, date(2025, 2, 1));
+        let result = NaturalDateParser::parse("last month", now, &test_tz()).unwrap();
+        assert_date_matches(result, date(2024, 12, 1));
+    }
+    #[test]
+    fn test_time_of_day_words() {
+        let now = test_timestamp();
+        let cases = [
+            ("tomorrow morning", date(2025, 1, 17), 9),
+            ("tomorrow in the afternoon", date(2025, 1, 17), 13),
+            ("friday evening", date(2025, 1, 17), 18),
+            ("tonight", date(2025, 1, 16), 21),
+            ("this morning", date(2025, 1, 16), 9),
+            ("noon", date(2025, 1, 16), 12),
+            ("next monday at noon", date(2025, 1, 20), 12),
+            ("march 3 at midnight", date(2025, 3, 3), 0),
+        ];
+        for (input, expected_date, expected_hour) in cases {
+            let result = NaturalDateParser::parse(input, now, &test_tz())
+                .unwrap_or_else(|e| panic!("failed to parse '{}': {:?}", input, e));
+            assert_datetime_matches(result, expected_date, expected_hour, 0);
+        }
+    }
+
+    #[test]
+    fn test_time_of_day_config() {
+        let now = test_timestamp();
+        let config = DateParserConfig {
+            morning: jiff::civil::time(7, 30, 0, 0),
+            ..Default::default()
+        };
+        let result =
+            NaturalDateParser::parse_with_config("tomorrow morning", now, &test_tz(), &config)
+                .unwrap();
+        assert_datetime_matches(result, date(2025, 1, 17), 7, 30);
+    }
+    #[test]
+    fn test_week_start_sunday() {
+        let now = test_timestamp(); // Thursday
+        let config = DateParserConfig {
+            week_start: Weekday::Sunday,
+            ..Default::default()
+        };
+        let range =
+            NaturalDateParser::parse_range_with_config("this week", now, &test_tz(), &config)
+                .unwrap();
+        assert_eq!(
+            range.dates(&test_tz()),
+            (date(2025, 1, 12), date(2025, 1, 18))
+        );
+
+        // sunday starts the week, so it has already passed
+        let result =
+            NaturalDateParser::parse_with_config("sunday", now, &test_tz(), &config).unwrap();
+        assert_date_matches(result, date(2025, 1, 12));
+        let result = NaturalDateParser::parse("sunday", now, &test_tz()).unwrap();
+        assert_date_matches(result, date(2025, 1, 19));
+    }
+
+    #[test]
+    fn test_localized_names() {
+        let now = test_timestamp();
+        let config = DateParserConfig {
+            weekday_names: HashMap::from([("Freitag".to_owned(), Weekday::Friday)]),
+            month_names: HashMap::from([
+                ("märz".to_owned(), Month::March),
+                ("mär".to_owned(), Month::March),
+            ]),
+            ..Default::default()
+        };
+        let result =
+            NaturalDateParser::parse_with_config("next freitag", now, &test_tz(), &config).unwrap();
+        assert_date_matches(result, date(2025, 1, 17));
+        let result =
+            NaturalDateParser::parse_with_config("3 März 2026", now, &test_tz(), &config).unwrap();
+        assert_date_matches(result, date(2026, 3, 3));
+        let result =
+            NaturalDateParser::parse_with_config("mär 3", now, &test_tz(), &config).unwrap();
+        assert_date_matches(result, date(2025, 3, 3));
+
+        assert!(NaturalDateParser::parse("next freitag", now, &test_tz()).is_err());
+    }
 }
@@ -1,10 +1,16 @@
-use rusqlite::Connection;
+use color_eyre::eyre::eyre;
+use rusqlite::{Connection, OpenFlags};
 use rusqlite_migration::{M, Migrations};
 use sql_minifier::macros::load_sql;
 use std::{
-    cell::LazyCell,
+    fs::{File, TryLockError},
     ops::{Deref, DerefMut},
-    path::Path,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, LazyLock, Mutex, PoisonError, Weak,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
 };
 
 use crate::preamble::*;
@@ -12,45 +18,187 @@ use crate::preamble::*;
 const DB_OPEN: &str = load_sql!("sql/db_open.sql");
 const DB_CLOSE: &str = load_sql!("sql/db_close.sql");
 
-const MIGRATIONS: LazyCell<Migrations> = LazyCell::new(|| {
+/// The number of prepared statements kept per connection. Statements run for
+/// every document while indexing are prepared with `prepare_cached`, so they
+/// are compiled once per connection rather than once per batch.
+const STATEMENT_CACHE_CAPACITY: usize = 64;
+
+static MIGRATIONS: LazyLock<Migrations> = LazyLock::new(|| {
     Migrations::new(vec![
         M::up(load_sql!("sql/001_init.sql")),
         M::up(load_sql!("sql/002_fts.sql")),
+        M::up(load_sql!("sql/003_graph.sql")),
+        M::up(load_sql!("sql/004_stub.sql")),
+        M::up(load_sql!("sql/005_task_status.sql")),
+        M::up(load_sql!("sql/006_task_due.sql")),
+        M::up(load_sql!("sql/007_inline_tags.sql")),
+        M::up(load_sql!("sql/008_nested_tags.sql")),
+        M::up(load_sql!("sql/009_link_context.sql")),
+        M::up(load_sql!("sql/010_derived.sql")),
+        M::up(load_sql!("sql/011_document_metadata.sql")),
+        M::up(load_sql!("sql/012_event.sql")),
+        M::up(load_sql!("sql/013_index_run.sql")),
+        M::up(load_sql!("sql/014_lookup.sql")),
+        M::up(load_sql!("sql/015_snapshot.sql")),
+        M::up(load_sql!("sql/016_highlight.sql")),
+        M::up(load_sql!("sql/017_quote.sql")),
+        M::up(load_sql!("sql/018_mention.sql")),
+        M::up(load_sql!("sql/019_task_owner.sql")),
+        M::up(load_sql!("sql/020_heading_anchor.sql")),
+        M::up(load_sql!("sql/021_link_url.sql")),
+        M::up(load_sql!("sql/022_source.sql")),
+        M::up(load_sql!("sql/023_heading_anchor_reindex.sql")),
     ])
 });
 
-#[repr(transparent)]
-pub struct DB(Connection);
+/// Set by `--read-only`, every db is then opened with [`DB::open_read_only`]
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+pub fn set_read_only(read_only: bool) {
+    READ_ONLY.store(read_only, Ordering::Relaxed);
+}
+
+/// Whether `--read-only` was given
+pub fn read_only() -> bool {
+    READ_ONLY.load(Ordering::Relaxed)
+}
+
+/// Whether the db at `path` exists but can not be written, because of its
+/// permissions, those of its directory or a read-only filesystem
+pub fn is_read_only(path: &Path) -> bool {
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return false;
+    };
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    metadata.permissions().readonly()
+        || std::fs::metadata(dir).is_ok_and(|m| m.permissions().readonly())
+        || File::options().append(true).open(path).is_err()
+}
+
+pub struct DB {
+    conn: Connection,
+    read_only: bool,
+}
 
 impl DB {
+    /// Open the db at `path`, creating and migrating it. A db that can not be
+    /// written, or any db once `--read-only` is given, is opened read-only.
     pub fn open<P: AsRef<Path> + std::fmt::Debug>(path: P) -> Result<DB> {
+        if read_only() || is_read_only(path.as_ref()) {
+            return Self::open_read_only(path);
+        }
         log::debug!("opening db at {:?}", path);
         // open and create a sqlite db
         let mut conn = Connection::open(path)?;
 
         conn.execute_batch(DB_OPEN)?;
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
 
         MIGRATIONS.to_latest(&mut conn)?;
 
-        Ok(DB(conn))
+        Ok(DB {
+            conn,
+            read_only: false,
+        })
+    }
+
+    /// Open the db at `path` without writing to it. A db that can not be
+    /// written is opened as immutable: sqlite then neither locks it nor uses
+    /// the files it keeps next to it, and changes made by other processes
+    /// after it was opened are not seen.
+    pub fn open_read_only<P: AsRef<Path> + std::fmt::Debug>(path: P) -> Result<DB> {
+        log::debug!("opening db at {:?} read-only", path);
+        let mode = match is_read_only(path.as_ref()) {
+            true => "immutable=1",
+            false => "mode=ro",
+        };
+        let path = path.as_ref().to_string_lossy();
+        let uri = format!(
+            "file:{}?{}",
+            path.replace('%', "%25")
+                .replace('?', "%3f")
+                .replace('#', "%23"),
+            mode
+        );
+        let conn = Connection::open_with_flags(
+            uri,
+            OpenFlags::SQLITE_OPEN_READ_ONLY
+                | OpenFlags::SQLITE_OPEN_URI
+                | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+        if MIGRATIONS.pending_migrations(&conn)? != 0 {
+            return Err(eyre!(
+                "the db at {:?} was written by an older zet, it needs to be migrated by a zet that can write to it",
+                path
+            ));
+        }
+        Ok(DB {
+            conn,
+            read_only: true,
+        })
     }
 }
 // util traits
 impl Drop for DB {
     fn drop(&mut self) {
-        self.0.execute_batch(DB_CLOSE).unwrap();
+        if !self.read_only {
+            self.conn.execute_batch(DB_CLOSE).unwrap();
+        }
     }
 }
 impl Deref for DB {
     type Target = Connection;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.conn
     }
 }
 impl DerefMut for DB {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut self.conn
+    }
+}
+
+/// A window of the rows of a query, at most `limit` rows after the first
+/// `offset`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Page {
+    pub limit: Option<usize>,
+    pub offset: usize,
+}
+
+impl Page {
+    /// The `number`th page, counting from 1, of `limit` rows each. Without a
+    /// limit there is only one page.
+    pub fn new(limit: Option<usize>, number: Option<usize>) -> Self {
+        let offset = match (limit, number) {
+            (Some(limit), Some(number)) => number.saturating_sub(1) * limit,
+            _ => 0,
+        };
+        Page { limit, offset }
+    }
+
+    /// The `LIMIT` and `OFFSET` clause selecting the page
+    pub fn sql(&self) -> String {
+        match (self.limit, self.offset) {
+            (None, 0) => String::new(),
+            (None, offset) => format!(" LIMIT -1 OFFSET {offset}"),
+            (Some(limit), 0) => format!(" LIMIT {limit}"),
+            (Some(limit), offset) => format!(" LIMIT {limit} OFFSET {offset}"),
+        }
+    }
+
+    /// The page of `items`, for rows that are not selected in sql
+    pub fn apply<T>(&self, items: impl IntoIterator<Item = T>) -> Vec<T> {
+        items
+            .into_iter()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect()
     }
 }
 
@@ -73,6 +221,121 @@ pub trait DbDelete<Id> {
     fn delete(db: &mut Connection, ids: &[Id]) -> Result<()>;
 }
 
+/// Run `f` inside a single transaction, committed if `f` succeeds and rolled
+/// back otherwise. The `Db*` traits write through savepoints, so they nest
+/// inside it. The [`DbLock`] of the db is held until it is done.
+pub fn with_transaction<T>(
+    db: &mut Connection,
+    f: impl FnOnce(&mut Connection) -> Result<T>,
+) -> Result<T> {
+    let _lock = DbLock::of(db)?;
+    db.execute_batch("begin immediate")?;
+    match f(db) {
+        Ok(value) => {
+            db.execute_batch("commit")?;
+            Ok(value)
+        }
+        Err(e) => {
+            if let Err(rollback) = db.execute_batch("rollback") {
+                log::error!("could not roll back transaction: {}", rollback);
+            }
+            Err(e)
+        }
+    }
+}
+
+/// The attempts made to acquire a [`DbLock`], the delay between them starts
+/// at `DB_LOCK_BACKOFF` and doubles up to a second
+const DB_LOCK_ATTEMPTS: u32 = 10;
+const DB_LOCK_BACKOFF: Duration = Duration::from_millis(50);
+
+/// The locks held by this process, a lock acquired again while it is held
+/// is shared rather than waited for
+static HELD_LOCKS: Mutex<Vec<(PathBuf, Weak<File>)>> = Mutex::new(Vec::new());
+
+/// An advisory lock held by the process writing to a collection, to its db or
+/// its documents, released when dropped. Processes only reading the
+/// collection do not take it.
+pub struct DbLock {
+    _file: Arc<File>,
+}
+
+impl DbLock {
+    /// Lock the file at `path`, typically `.zet/db.lock`, retrying while
+    /// another process holds it
+    pub fn acquire(path: &Path) -> Result<DbLock> {
+        Self::acquire_with(path, DB_LOCK_ATTEMPTS, DB_LOCK_BACKOFF)
+    }
+
+    /// The lock of the collection at `root`, none if it has no `.zet` yet
+    pub fn of_collection(root: &Path) -> Result<Option<DbLock>> {
+        match crate::core::collection_config_dir(root).is_dir() {
+            true => Self::acquire(&crate::core::collection_db_lock_file(root)).map(Some),
+            false => Ok(None),
+        }
+    }
+
+    /// The lock of the collection of the db `db`, none for a db in memory
+    pub fn of(db: &Connection) -> Result<Option<DbLock>> {
+        match db.path() {
+            Some(path) if !path.is_empty() => {
+                Self::acquire(&Path::new(path).with_file_name(crate::DB_LOCK_NAME)).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn acquire_with(path: &Path, attempts: u32, backoff: Duration) -> Result<DbLock> {
+        let path = std::path::absolute(path)?;
+        if let Some(file) = Self::held(&path) {
+            return Ok(DbLock { _file: file });
+        }
+
+        let file = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)?;
+        let mut delay = backoff;
+        for attempt in 1..=attempts {
+            match file.try_lock() {
+                Ok(()) => {
+                    let file = Arc::new(file);
+                    HELD_LOCKS
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .push((path, Arc::downgrade(&file)));
+                    return Ok(DbLock { _file: file });
+                }
+                Err(TryLockError::WouldBlock) if attempt < attempts => {
+                    log::debug!("{:?} is locked, retrying in {:?}", path, delay);
+                    std::thread::sleep(delay);
+                    delay = (delay * 2).min(Duration::from_secs(1));
+                    // taken by another thread of this process meanwhile
+                    if let Some(file) = Self::held(&path) {
+                        return Ok(DbLock { _file: file });
+                    }
+                }
+                Err(TryLockError::WouldBlock) => break,
+                Err(TryLockError::Error(e)) => return Err(e.into()),
+            }
+        }
+        Err(eyre!(
+            "another zet process is writing to the collection, try again once it is done"
+        ))
+    }
+
+    /// The lock this process holds on `path`, if any. [`HELD_LOCKS`] is only
+    /// locked while looking, not while waiting for a lock.
+    fn held(path: &Path) -> Option<Arc<File>> {
+        let mut held = HELD_LOCKS.lock().unwrap_or_else(PoisonError::into_inner);
+        held.retain(|(_, file)| file.strong_count() > 0);
+        held.iter()
+            .find(|(held, _)| held == path)
+            .and_then(|(_, file)| file.upgrade())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -83,4 +346,96 @@ mod test {
         DB::open(":memory:")?;
         Ok(())
     }
+
+    #[test]
+    pub fn heading_anchor_reindex() -> Result<()> {
+        let mut conn = Connection::open_in_memory()?;
+        MIGRATIONS.to_version(&mut conn, 19)?;
+        conn.execute_batch(
+            "insert into document (id, title, path, hash, modified, created)
+             values
+                ('a', 'A', 'a.md', 7, '2025-01-01T00:00:00Z', '2025-01-01T00:00:00Z'),
+                ('b', 'B', 'b.md', 7, '2025-01-01T00:00:00Z', '2025-01-01T00:00:00Z');
+             insert into document_heading
+                (document_id, content, level, metadata, range_start, range_end)
+             values ('a', 'A', 1, jsonb('{}'), 0, 3);",
+        )?;
+        MIGRATIONS.to_latest(&mut conn)?;
+
+        // only the documents with headings are reindexed
+        let mut stmt = conn.prepare("select id, hash, modified from document order by id")?;
+        let rows: Vec<(String, u32, String)> = stmt
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        assert_eq!(
+            rows,
+            [
+                ("a".to_owned(), 8, "1970-01-01T00:00:00Z".to_owned()),
+                ("b".to_owned(), 7, "2025-01-01T00:00:00Z".to_owned()),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    pub fn statement_cache() -> Result<()> {
+        let mut db = DB::open(":memory:")?;
+        db.execute_batch("create table t (x integer)")?;
+        // a cached statement outlives the savepoint it was prepared in
+        for x in 0..3 {
+            let tx = db.savepoint()?;
+            tx.prepare_cached("insert into t values (?1)")?
+                .execute([x])?;
+            tx.commit()?;
+        }
+        let count: i64 = db.query_row("select count(*) from t", [], |r| r.get(0))?;
+        assert_eq!(count, 3);
+        Ok(())
+    }
+
+    #[test]
+    pub fn page() {
+        assert_eq!(Page::new(None, Some(3)).sql(), "");
+        assert_eq!(Page::new(Some(10), None).sql(), " LIMIT 10");
+        assert_eq!(Page::new(Some(10), Some(3)).sql(), " LIMIT 10 OFFSET 20");
+        assert_eq!(Page::new(Some(2), Some(2)).apply(1..=5), vec![3, 4]);
+    }
+
+    #[test]
+    pub fn lock() -> Result<()> {
+        let dir = assert_fs::TempDir::new()?;
+        let path = dir.path().join(crate::DB_LOCK_NAME);
+        // held by another process
+        let other = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)?;
+        other.lock()?;
+        let error = DbLock::acquire_with(&path, 2, Duration::from_millis(1)).err();
+        assert!(error.is_some_and(|e| e.to_string().starts_with("another zet process")));
+        drop(other);
+
+        // shared within this process while it is held
+        let lock = DbLock::acquire(&path)?;
+        DbLock::acquire_with(&path, 1, Duration::from_millis(1))?;
+        let other = File::options().write(true).open(&path)?;
+        assert!(other.try_lock().is_err());
+        drop(lock);
+        assert!(other.try_lock().is_ok());
+        drop(other);
+
+        // waiting for a lock does not hold up the other locks of this process
+        let other = File::options().write(true).open(&path)?;
+        other.lock()?;
+        let waiting = std::thread::spawn(move || {
+            DbLock::acquire_with(&path, 4, Duration::from_millis(200)).err()
+        });
+        std::thread::sleep(Duration::from_millis(50));
+        let start = std::time::Instant::now();
+        DbLock::acquire(&dir.path().join("other.lock"))?;
+        assert!(start.elapsed() < Duration::from_millis(150));
+        assert!(waiting.join().unwrap().is_some());
+        Ok(())
+    }
 }
@@ -0,0 +1,135 @@
+//! Exporting notes as a single pdf or epub document. The notes are rendered
+//! through the html pipeline and converted by an external program, such as
+//! weasyprint or pandoc.
+
+use std::path::Path;
+use std::process::Command;
+
+use color_eyre::eyre::eyre;
+use normalize_path::NormalizePath;
+use serde::{Deserialize, Serialize};
+
+use crate::core::html::{Destination, Highlighter, HtmlConfig, escape_html, render_markdown};
+use crate::core::types::document::DocumentId;
+use crate::result::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentFormat {
+    Pdf,
+    Epub,
+}
+
+/// The `[export]` section of the configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportConfig {
+    /// program and arguments converting html to pdf. `{input}`, `{output}`
+    /// and `{title}` are replaced by the html file, the pdf file and the title
+    pub pdf_command: Vec<String>,
+    /// program and arguments converting html to epub, see `pdf_command`
+    pub epub_command: Vec<String>,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            pdf_command: ["weasyprint", "{input}", "{output}"]
+                .map(String::from)
+                .to_vec(),
+            epub_command: [
+                "pandoc",
+                "{input}",
+                "--from",
+                "html",
+                "--metadata",
+                "title={title}",
+                "-o",
+                "{output}",
+            ]
+            .map(String::from)
+            .to_vec(),
+        }
+    }
+}
+
+/// A note to export, its body being the content after the frontmatter
+#[derive(Debug, Clone)]
+pub struct ExportedNote<'a> {
+    pub id: &'a DocumentId,
+    pub path: &'a Path,
+    pub body: String,
+}
+
+/// Render `notes` as a single html document, a section per note. Links
+/// between the notes point to their sections, and images to the absolute
+/// paths of their files, so that the converter finds them. Math is kept as
+/// tex in `math` spans, rendered by converters running the scripts of
+/// `config`.
+pub fn render_document(
+    config: &HtmlConfig,
+    highlighter: Option<&Highlighter>,
+    title: &str,
+    notes: &[ExportedNote],
+) -> String {
+    let mut content = String::new();
+    for note in notes {
+        let directory = note.path.parent().unwrap_or(Path::new(""));
+        let resolve = |destination: Destination| match destination {
+            Destination::Link(target) if !target.contains("://") => notes
+                .iter()
+                .find(|n| target.ends_with(&n.id.0))
+                .map(|n| format!("#{}", n.id.0)),
+            Destination::Image(target) if !target.contains("://") => {
+                let path = directory.join(target).normalize();
+                path.exists().then(|| path.display().to_string())
+            }
+            _ => None,
+        };
+        content.push_str(&format!(
+            "<section id=\"{}\">\n{}</section>\n",
+            escape_html(&note.id.0),
+            render_markdown(&note.body, config, highlighter, resolve)
+        ));
+    }
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n{}</head>\n<body>\n{content}</body>\n</html>\n",
+        escape_html(title),
+        config.head(highlighter, &content)
+    )
+}
+
+/// Convert the html document `html` to `output` using the command configured
+/// for `format`
+pub fn convert(
+    config: &ExportConfig,
+    format: DocumentFormat,
+    title: &str,
+    html: &str,
+    output: &Path,
+) -> Result<()> {
+    let command = match format {
+        DocumentFormat::Pdf => &config.pdf_command,
+        DocumentFormat::Epub => &config.epub_command,
+    };
+    let Some((program, args)) = command.split_first() else {
+        return Err(eyre!("no {:?} export command configured", format));
+    };
+
+    let input = std::env::temp_dir().join(format!("zet-export-{}.html", uuid::Uuid::new_v4()));
+    std::fs::write(&input, html)?;
+    let replace = |arg: &String| {
+        arg.replace("{input}", &input.to_string_lossy())
+            .replace("{output}", &output.to_string_lossy())
+            .replace("{title}", title)
+    };
+    let status = Command::new(program)
+        .args(args.iter().map(replace))
+        .status();
+    std::fs::remove_file(&input)?;
+
+    let status = status.map_err(|e| eyre!("could not run {:?}: {}", program, e))?;
+    if !status.success() {
+        return Err(eyre!("{:?} failed with {}", program, status));
+    }
+    Ok(())
+}
@@ -0,0 +1,98 @@
+//! Opening notes in the editor of the user, with the cursor placed on a
+//! given line for the editors whose command line allows it.
+
+use std::path::Path;
+use std::process::Command;
+
+use color_eyre::eyre::eyre;
+use serde::{Deserialize, Serialize};
+
+use crate::core::frontmatter::body_offset;
+use crate::result::Result;
+
+/// The `[editor]` section of the configuration
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EditorConfig {
+    /// the editor program and its arguments, defaults to $VISUAL, then $EDITOR
+    pub command: Option<Vec<String>>,
+    /// open notes in the editor once `zet create` has written them
+    pub open_on_create: bool,
+}
+
+impl EditorConfig {
+    /// The configured editor, or the one in $VISUAL or $EDITOR
+    pub fn editor(&self) -> Option<Vec<String>> {
+        if let Some(command) = self.command.as_ref().filter(|c| !c.is_empty()) {
+            return Some(command.clone());
+        }
+        ["VISUAL", "EDITOR"].iter().find_map(|var| {
+            let value = std::env::var(var).ok()?;
+            let command: Vec<String> = value.split_whitespace().map(str::to_owned).collect();
+            (!command.is_empty()).then_some(command)
+        })
+    }
+
+    /// Open `path` in the editor with the cursor on `line`, counted from 1,
+    /// waiting for the editor to exit
+    pub fn open(&self, path: &Path, line: usize) -> Result<()> {
+        let editor = self
+            .editor()
+            .ok_or_else(|| eyre!("no editor configured, set $VISUAL, $EDITOR or editor.command"))?;
+        let (program, args) = editor.split_first().expect("editor command is not empty");
+        let status = Command::new(program)
+            .args(args)
+            .args(line_args(program, path, line))
+            .status()
+            .map_err(|e| eyre!("could not run {:?}: {}", program, e))?;
+        if !status.success() {
+            return Err(eyre!("{:?} failed with {}", program, status));
+        }
+        Ok(())
+    }
+}
+
+/// The arguments opening `path` at `line` in the editor `program`. Editors
+/// without a known syntax are given just the path.
+fn line_args(program: &str, path: &Path, line: usize) -> Vec<String> {
+    let name = Path::new(program)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let path = path.display().to_string();
+    match name.as_str() {
+        "vi" | "vim" | "nvim" | "gvim" | "nano" | "micro" | "kak" | "emacs" | "emacsclient"
+        | "gedit" => vec![format!("+{line}"), path],
+        "code" | "code-insiders" | "codium" => vec!["--goto".to_owned(), format!("{path}:{line}")],
+        "hx" | "helix" | "subl" | "zed" => vec![format!("{path}:{line}")],
+        _ => vec![path],
+    }
+}
+
+/// The line, counted from 1, following the frontmatter of `document`
+pub fn body_line(document: &str) -> usize {
+    document[..body_offset(document)].matches('\n').count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_args() {
+        let path = Path::new("/notes/a.md");
+        assert_eq!(
+            line_args("/usr/bin/nvim", path, 5),
+            vec!["+5", "/notes/a.md"]
+        );
+        assert_eq!(line_args("code", path, 5), vec!["--goto", "/notes/a.md:5"]);
+        assert_eq!(line_args("hx", path, 5), vec!["/notes/a.md:5"]);
+        assert_eq!(line_args("ed", path, 5), vec!["/notes/a.md"]);
+    }
+
+    #[test]
+    fn test_body_line() {
+        assert_eq!(body_line("# A\n"), 1);
+        assert_eq!(body_line("---\ntitle: A\n---\n# A\n"), 4);
+    }
+}
@@ -0,0 +1,518 @@
+//! Reading emails ([RFC 5322](https://datatracker.ietf.org/doc/html/rfc5322))
+//! saved as `.eml` files or in a
+//! [Maildir](https://cr.yp.to/proto/maildir.html), to import them as notes.
+//!
+//! Only what a note needs is read: the subject, sender, date and message id,
+//! the text of the message and its attachments. Multipart messages are
+//! walked depth first, the first `text/plain` part being the text and the
+//! first `text/html` part the fallback. Header words encoded as of RFC 2047
+//! and bodies in base64 or quoted-printable are decoded, in any charset
+//! known to [`encoding_rs`].
+
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use color_eyre::eyre::eyre;
+use jiff::Timestamp;
+
+use crate::result::Result;
+
+/// Frontmatter key of the sender of an imported email
+pub const FROM_KEY: &str = "from";
+/// Frontmatter key of the time an imported email was sent
+pub const DATE_KEY: &str = "date";
+/// Frontmatter key of the message id of an imported email, by which an email
+/// is only imported once
+pub const MESSAGE_ID_KEY: &str = "message_id";
+
+/// Prefixes of the subjects of forwarded emails
+const FORWARD_PREFIXES: [&str; 3] = ["fwd:", "fw:", "tr:"];
+
+/// A parsed email
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Email {
+    pub subject: Option<String>,
+    pub from: Option<String>,
+    pub date: Option<Timestamp>,
+    /// the `Message-ID`, without the angle brackets
+    pub message_id: Option<String>,
+    /// the first `text/plain` part
+    pub text: Option<String>,
+    /// the first `text/html` part
+    pub html: Option<String>,
+    pub attachments: Vec<Attachment>,
+}
+
+/// A file attached to an email
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    /// the file name, without any directories
+    pub filename: String,
+    pub content: Vec<u8>,
+}
+
+/// A header or MIME part: its headers, in order, and its body
+struct Part<'a> {
+    headers: Vec<(String, String)>,
+    body: &'a [u8],
+}
+
+impl<'a> Part<'a> {
+    fn parse(raw: &'a [u8]) -> Part<'a> {
+        let (head, body) = split_head(raw);
+        let head = String::from_utf8_lossy(head);
+        let mut headers: Vec<(String, String)> = Vec::new();
+        for line in head.lines() {
+            // folded lines continue the previous header
+            if line.starts_with([' ', '\t']) {
+                if let Some((_, value)) = headers.last_mut() {
+                    value.push(' ');
+                    value.push_str(line.trim());
+                }
+                continue;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                headers.push((name.trim().to_ascii_lowercase(), value.trim().to_owned()));
+            }
+        }
+        Part { headers, body }
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The lowercased media type and the parameters of the `Content-Type`,
+    /// `text/plain` if there is none
+    fn content_type(&self) -> (String, Vec<(String, String)>) {
+        match self.header("content-type") {
+            Some(value) => {
+                let (media_type, parameters) = parameters(value);
+                (media_type.to_ascii_lowercase(), parameters)
+            }
+            None => ("text/plain".to_owned(), Vec::new()),
+        }
+    }
+
+    /// The body without its transfer encoding
+    fn decoded_body(&self) -> Vec<u8> {
+        let encoding = self
+            .header("content-transfer-encoding")
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match encoding.as_str() {
+            "base64" => {
+                let encoded: Vec<u8> = self
+                    .body
+                    .iter()
+                    .copied()
+                    .filter(|b| !b.is_ascii_whitespace())
+                    .collect();
+                STANDARD
+                    .decode(encoded)
+                    .unwrap_or_else(|_| self.body.to_vec())
+            }
+            "quoted-printable" => decode_quoted_printable(self.body),
+            _ => self.body.to_vec(),
+        }
+    }
+
+    /// The file name of the part if it is an attachment, given by its
+    /// `Content-Disposition` or else by the `name` of its `Content-Type`
+    fn attachment_name(&self) -> Option<String> {
+        let disposition = self.header("content-disposition").map(parameters);
+        let filename = disposition
+            .as_ref()
+            .and_then(|(_, parameters)| parameter(parameters, "filename"))
+            .or_else(|| parameter(&self.content_type().1, "name"));
+        let is_attachment = disposition
+            .as_ref()
+            .is_some_and(|(kind, _)| kind.eq_ignore_ascii_case("attachment"));
+        if !is_attachment && filename.is_none() {
+            return None;
+        }
+        let filename = decode_words(&filename.unwrap_or_default());
+        // never let the name of an attachment write outside its directory
+        let filename = filename
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_owned();
+        match filename.is_empty() || filename.starts_with('.') {
+            true => Some("attachment".to_owned()),
+            false => Some(filename),
+        }
+    }
+}
+
+impl Email {
+    /// Parse the raw bytes of an email
+    pub fn parse(raw: &[u8]) -> Email {
+        let message = Part::parse(raw);
+        let mut email = Email {
+            subject: message.header("subject").map(decode_words),
+            from: message.header("from").map(decode_words),
+            date: message.header("date").and_then(parse_date),
+            message_id: message.header("message-id").map(|id| {
+                id.trim()
+                    .trim_start_matches('<')
+                    .trim_end_matches('>')
+                    .to_owned()
+            }),
+            ..Email::default()
+        };
+        email.collect(&message);
+        email
+    }
+
+    /// The subject without the prefixes of forwarded emails, as the title of
+    /// a note
+    pub fn title(&self) -> Option<String> {
+        let mut subject = self.subject.as_deref()?.trim();
+        while let Some(prefix) = FORWARD_PREFIXES.iter().find(|prefix| {
+            subject
+                .get(..prefix.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+        }) {
+            subject = subject[prefix.len()..].trim_start();
+        }
+        (!subject.is_empty()).then(|| subject.to_owned())
+    }
+
+    /// Read the email at `path`
+    pub fn read(path: &Path) -> Result<Email> {
+        let raw = std::fs::read(path).map_err(|e| eyre!("could not read {:?}: {}", path, e))?;
+        Ok(Email::parse(&raw))
+    }
+
+    /// Take the text, html and attachments of `part` and of its sub parts
+    fn collect(&mut self, part: &Part) {
+        let (media_type, parameters) = part.content_type();
+        if media_type.starts_with("multipart/") {
+            if let Some(boundary) = parameter(&parameters, "boundary") {
+                for raw in split_multipart(part.body, &boundary) {
+                    self.collect(&Part::parse(raw));
+                }
+            }
+            return;
+        }
+        if media_type == "message/rfc822" {
+            self.collect(&Part::parse(part.body));
+            return;
+        }
+        if let Some(filename) = part.attachment_name() {
+            self.attachments.push(Attachment {
+                filename,
+                content: part.decoded_body(),
+            });
+            return;
+        }
+        let text = || {
+            let charset = parameter(&parameters, "charset").unwrap_or_default();
+            decode_charset(&part.decoded_body(), &charset)
+        };
+        match media_type.as_str() {
+            "text/plain" if self.text.is_none() => self.text = Some(text()),
+            "text/html" if self.html.is_none() => self.html = Some(text()),
+            _ => {}
+        }
+    }
+}
+
+/// The messages of the Maildir at `directory`, new ones first, each in the
+/// order of their file names
+pub fn maildir_messages(directory: &Path) -> Result<Vec<PathBuf>> {
+    let mut messages = Vec::new();
+    for sub in ["new", "cur"] {
+        let mut paths = std::fs::read_dir(directory.join(sub))
+            .map_err(|e| eyre!("{:?} is not a maildir: {}", directory, e))?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<std::io::Result<Vec<PathBuf>>>()?;
+        paths.retain(|path| path.is_file());
+        paths.sort();
+        messages.extend(paths);
+    }
+    Ok(messages)
+}
+
+/// Whether `directory` is a Maildir, having `cur` and `new` subdirectories
+pub fn is_maildir(directory: &Path) -> bool {
+    directory.join("cur").is_dir() && directory.join("new").is_dir()
+}
+
+/// Split `raw` at the empty line ending the headers
+fn split_head(raw: &[u8]) -> (&[u8], &[u8]) {
+    for (separator, len) in [(&b"\r\n\r\n"[..], 4), (&b"\n\n"[..], 2)] {
+        if let Some(at) = raw.windows(len).position(|w| w == separator) {
+            // a part may start with the empty line when it has no headers
+            return (&raw[..at], &raw[at + len..]);
+        }
+    }
+    match raw.starts_with(b"\r\n") || raw.starts_with(b"\n") {
+        true => (&[], raw),
+        false => (raw, &[]),
+    }
+}
+
+/// The parts of a multipart body between the `--boundary` delimiters
+fn split_multipart<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{boundary}");
+    let delimiter = delimiter.as_bytes();
+    let mut parts = Vec::new();
+    let mut start = None;
+    let mut offset = 0;
+    for line in body.split_inclusive(|b| *b == b'\n') {
+        let content = line.trim_ascii_end();
+        if content.starts_with(delimiter) {
+            if let Some(start) = start {
+                // the line break before the delimiter belongs to it
+                let end = trim_line_break(&body[..offset]).len().max(start);
+                parts.push(&body[start..end]);
+            }
+            if content[delimiter.len()..].starts_with(b"--") {
+                return parts;
+            }
+            start = Some(offset + line.len());
+        }
+        offset += line.len();
+    }
+    if let Some(start) = start {
+        parts.push(&body[start..]);
+    }
+    parts
+}
+
+fn trim_line_break(bytes: &[u8]) -> &[u8] {
+    let bytes = bytes.strip_suffix(b"\n").unwrap_or(bytes);
+    bytes.strip_suffix(b"\r").unwrap_or(bytes)
+}
+
+/// The value before the first `;` of a structured header and its
+/// `key=value` parameters, with lowercased keys and unquoted values
+fn parameters(value: &str) -> (String, Vec<(String, String)>) {
+    let mut fields = split_unquoted(value, ';').into_iter();
+    let first = fields.next().unwrap_or_default().trim().to_owned();
+    let parameters = fields
+        .filter_map(|field| {
+            let (key, value) = field.split_once('=')?;
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            Some((key.trim().to_ascii_lowercase(), value.to_owned()))
+        })
+        .collect();
+    (first, parameters)
+}
+
+fn parameter(parameters: &[(String, String)], key: &str) -> Option<String> {
+    // RFC 2231 continuations and charsets (`filename*=utf-8''a%20b.pdf`) are
+    // read as the plain parameter
+    let extended = format!("{key}*");
+    parameters.iter().find_map(|(k, value)| match k {
+        k if k == key => Some(value.clone()),
+        k if *k == extended => {
+            let value = value.splitn(3, '\'').last().unwrap_or(value);
+            Some(crate::core::percent_decode(value))
+        }
+        _ => None,
+    })
+}
+
+/// Split `value` at the `separator`s outside of double quotes
+fn split_unquoted(value: &str, separator: char) -> Vec<&str> {
+    let mut fields = Vec::new();
+    let mut quoted = false;
+    let mut start = 0;
+    for (i, c) in value.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c == separator && !quoted => {
+                fields.push(&value[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    fields.push(&value[start..]);
+    fields
+}
+
+/// Decode the RFC 2047 encoded words (`=?utf-8?B?...?=`) of a header value.
+/// The whitespace between two encoded words is dropped.
+pub fn decode_words(value: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = value;
+    let mut after_word = false;
+    while let Some(start) = rest.find("=?") {
+        let mut parts = rest[start + 2..].splitn(3, '?');
+        let decoded_word = match (parts.next(), parts.next(), parts.next()) {
+            (Some(charset), Some(encoding), Some(text)) => text.find("?=").and_then(|end| {
+                let decoded = decode_word(charset, encoding, &text[..end])?;
+                // =?charset?encoding?text?=
+                let length = 2 + charset.len() + 1 + encoding.len() + 1 + end + 2;
+                Some((decoded, length))
+            }),
+            _ => None,
+        };
+        let Some((text, length)) = decoded_word else {
+            decoded.push_str(&rest[..start + 2]);
+            rest = &rest[start + 2..];
+            after_word = false;
+            continue;
+        };
+        let between = &rest[..start];
+        if !(after_word && between.trim().is_empty()) {
+            decoded.push_str(between);
+        }
+        decoded.push_str(&text);
+        rest = &rest[start + length..];
+        after_word = true;
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_word(charset: &str, encoding: &str, text: &str) -> Option<String> {
+    let bytes = match encoding.to_ascii_lowercase().as_str() {
+        "b" => STANDARD.decode(text).ok()?,
+        "q" => decode_quoted_printable(text.replace('_', " ").as_bytes()),
+        _ => return None,
+    };
+    Some(decode_charset(&bytes, charset))
+}
+
+/// Decode quoted-printable `bytes`, soft line breaks (`=` at the end of a
+/// line) joining lines
+fn decode_quoted_printable(bytes: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'=' {
+            decoded.push(bytes[i]);
+            i += 1;
+            continue;
+        }
+        let rest = &bytes[i + 1..];
+        if rest.starts_with(b"\r\n") {
+            i += 3;
+        } else if rest.starts_with(b"\n") {
+            i += 2;
+        } else if let Some(byte) = rest
+            .get(..2)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            decoded.push(byte);
+            i += 3;
+        } else {
+            decoded.push(b'=');
+            i += 1;
+        }
+    }
+    decoded
+}
+
+/// Decode `bytes` written in `charset`, UTF-8 if it is unknown
+fn decode_charset(bytes: &[u8], charset: &str) -> String {
+    let encoding =
+        encoding_rs::Encoding::for_label(charset.trim().as_bytes()).unwrap_or(encoding_rs::UTF_8);
+    let (text, _, _) = encoding.decode(bytes);
+    text.replace("\r\n", "\n")
+}
+
+/// The time of a `Date` header, e.g. `Tue, 1 Jul 2003 10:52:37 +0200`
+fn parse_date(value: &str) -> Option<Timestamp> {
+    // comments like `(CEST)` after the offset are not part of RFC 2822 dates
+    let value = value.split('(').next().unwrap_or_default().trim();
+    jiff::fmt::rfc2822::parse(value)
+        .ok()
+        .map(|zoned| zoned.timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plain() {
+        let raw = "From: Alice <alice@example.com>\r\n\
+            Subject: =?utf-8?B?UmVhZDo=?=\r\n =?utf-8?Q?_caf=C3=A9_notes?=\r\n\
+            Date: Tue, 1 Jul 2003 10:52:37 +0200 (CEST)\r\n\
+            Message-ID: <abc@example.com>\r\n\
+            Content-Type: text/plain; charset=\"iso-8859-1\"\r\n\
+            Content-Transfer-Encoding: quoted-printable\r\n\
+            \r\n\
+            Caf=E9 au lait, a long line that is =\r\nsoft broken\r\n";
+        let email = Email::parse(raw.as_bytes());
+        assert_eq!(email.subject.as_deref(), Some("Read: café notes"));
+        assert_eq!(email.title().as_deref(), Some("Read: café notes"));
+        assert_eq!(email.from.as_deref(), Some("Alice <alice@example.com>"));
+        assert_eq!(
+            email.date,
+            Some("2003-07-01T08:52:37Z".parse::<Timestamp>().unwrap())
+        );
+        assert_eq!(email.message_id.as_deref(), Some("abc@example.com"));
+        assert_eq!(
+            email.text.as_deref(),
+            Some("Café au lait, a long line that is soft broken\n")
+        );
+        assert!(email.attachments.is_empty());
+    }
+
+    #[test]
+    fn test_parse_multipart() {
+        let raw = "Subject: Fwd: FW:  Report\n\
+            Content-Type: multipart/mixed; boundary=\"outer\"\n\
+            \n\
+            preamble\n\
+            --outer\n\
+            Content-Type: multipart/alternative; boundary=inner\n\
+            \n\
+            --inner\n\
+            Content-Type: text/plain\n\
+            \n\
+            See the report.\n\
+            --inner\n\
+            Content-Type: text/html\n\
+            \n\
+            <p>See the report.</p>\n\
+            --inner--\n\
+            --outer\n\
+            Content-Type: application/pdf; name=\"ignored.pdf\"\n\
+            Content-Disposition: attachment; filename=\"../q3 report.pdf\"\n\
+            Content-Transfer-Encoding: base64\n\
+            \n\
+            JVBE\n\
+            Rg==\n\
+            --outer--\n\
+            epilogue\n";
+        let email = Email::parse(raw.as_bytes());
+        assert_eq!(email.title().as_deref(), Some("Report"));
+        assert_eq!(email.text.as_deref(), Some("See the report."));
+        assert_eq!(email.html.as_deref(), Some("<p>See the report.</p>"));
+        assert_eq!(
+            email.attachments,
+            vec![Attachment {
+                filename: "q3 report.pdf".to_owned(),
+                content: b"%PDF".to_vec(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_decode_words() {
+        assert_eq!(decode_words("plain subject"), "plain subject");
+        assert_eq!(
+            decode_words("=?ISO-8859-1?Q?Andr=E9?= Pirard <p@example.com>"),
+            "André Pirard <p@example.com>"
+        );
+        assert_eq!(decode_words("a =?x?y"), "a =?x?y");
+    }
+}
@@ -0,0 +1,195 @@
+//! Extracting a selection of a note into a new note, the "extract to a new
+//! note" action of the language server.
+//!
+//! The new note is titled by the heading the selection starts with, or else
+//! by the heading of the section the selection is in, its headings are
+//! shifted under its title and it links back to the note it was extracted
+//! from, each of which the `[extract]` section of the configuration turns
+//! off.
+
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::compile::shift_headings;
+use crate::core::frontmatter::body_offset;
+use crate::core::outline::sections;
+use crate::core::parser::{DocumentParser, Parse};
+use crate::core::types::document::DocumentId;
+use crate::result::Result;
+
+/// The `[extract]` section of the configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExtractConfig {
+    /// title the new note by the heading the selection starts with, or else
+    /// by the heading of its section, rather than by its first line
+    pub title_from_heading: bool,
+    /// shift the headings of the selection so that the highest is of
+    /// `heading_level`
+    pub shift_headings: bool,
+    /// the level of the highest heading of the new note, its title being of
+    /// level 1
+    pub heading_level: usize,
+    /// start the new note with a line linking to the note it was extracted
+    /// from
+    pub backlink: bool,
+    /// replace the selection with an embed of the new note rather than a link
+    pub embed: bool,
+}
+
+impl Default for ExtractConfig {
+    fn default() -> Self {
+        Self {
+            title_from_heading: true,
+            shift_headings: true,
+            heading_level: 2,
+            backlink: true,
+            embed: false,
+        }
+    }
+}
+
+/// A note extracted from a selection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Extract {
+    pub title: String,
+    /// the content of the new note, under its title
+    pub content: String,
+    /// the range of the note replaced by the link to the new note
+    pub range: Range<usize>,
+}
+
+impl ExtractConfig {
+    /// The note extracted from the range `selection` of the note `id`, or
+    /// `None` if the selection is blank
+    pub fn extract(
+        &self,
+        document: &str,
+        selection: Range<usize>,
+        id: &DocumentId,
+        parser: &DocumentParser,
+    ) -> Result<Option<Extract>> {
+        // the frontmatter is never extracted
+        let offset = body_offset(document);
+        let body = &document[offset..];
+        let end = selection.end.clamp(offset, document.len()) - offset;
+        let start = selection.start.clamp(offset, offset + end) - offset;
+        let text = &body[start..end];
+        if text.trim().is_empty() {
+            return Ok(None);
+        }
+        let start = start + text.len() - text.trim_start().len();
+
+        let ast = parser.parse(body)?;
+        let sections = sections(&ast, body);
+        let heading = sections.iter().find(|s| s.heading.start == start);
+        let enclosing = sections
+            .iter()
+            .rfind(|s| s.heading.end <= start && s.range.contains(&start));
+        let (title, content) = match (self.title_from_heading, heading, enclosing) {
+            // the heading is carried as the title rather than repeated
+            (true, Some(heading), _) => (heading.content.clone(), &body[heading.heading.end..end]),
+            (true, None, Some(section)) => (section.content.clone(), &body[start..end]),
+            _ => (first_line(text), &body[start..end]),
+        };
+
+        let mut content = match self.shift_headings {
+            true => shift_headings(content.trim(), self.heading_level.clamp(1, 6)),
+            false => content.trim().to_owned(),
+        };
+        if self.backlink {
+            content = format!("From [[{}]]\n\n{}", id.0, content);
+        }
+        Ok(Some(Extract {
+            title,
+            content,
+            range: offset + start..offset + end,
+        }))
+    }
+
+    /// The text replacing the extracted selection, linking to the new note
+    /// `id` titled `title`
+    pub fn link(&self, id: &DocumentId, title: &str) -> String {
+        match self.embed {
+            true => format!("![[{}]]", id.0),
+            false => format!("[[{}|{}]]", id.0, title),
+        }
+    }
+}
+
+/// The first line of `text` with text in it, without the markers of a
+/// heading, quote or list item
+fn first_line(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            line.trim_start_matches(['#', '>', '-', '*', '+', ' '])
+                .trim()
+        })
+        .find(|line| !line.is_empty())
+        .unwrap_or_default()
+        .to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOCUMENT: &str =
+        "---\ntitle: Paper\n---\n# Paper\n\n## Method\n\nFirst we\nmeasured.\n\n### Setup\n\ns\n";
+
+    fn extract(config: &ExtractConfig, selected: &str) -> Extract {
+        let start = DOCUMENT.find(selected).unwrap();
+        config
+            .extract(
+                DOCUMENT,
+                start..start + selected.len(),
+                &DocumentId("paper".to_owned()),
+                &DocumentParser::new(),
+            )
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_extract() {
+        let config = ExtractConfig::default();
+        let selected = "## Method\n\nFirst we\nmeasured.\n\n### Setup\n\ns\n";
+        assert_eq!(
+            extract(&config, selected),
+            Extract {
+                title: "Method".to_owned(),
+                content: "From [[paper]]\n\nFirst we\nmeasured.\n\n## Setup\n\ns".to_owned(),
+                range: DOCUMENT.find(selected).unwrap()..DOCUMENT.len(),
+            }
+        );
+
+        // the heading of the section the selection is in
+        let extracted = extract(&config, "measured.");
+        assert_eq!(extracted.title, "Method");
+        assert_eq!(extracted.content, "From [[paper]]\n\nmeasured.");
+
+        let config = ExtractConfig {
+            title_from_heading: false,
+            shift_headings: false,
+            backlink: false,
+            ..Default::default()
+        };
+        let extracted = extract(&config, "## Method\n\nFirst we");
+        assert_eq!(extracted.title, "Method");
+        assert_eq!(extracted.content, "## Method\n\nFirst we");
+        assert_eq!(extract(&config, "First we\nmeasured.").title, "First we");
+
+        assert!(
+            config
+                .extract(
+                    DOCUMENT,
+                    0..4,
+                    &DocumentId("paper".to_owned()),
+                    &DocumentParser::new()
+                )
+                .unwrap()
+                .is_none()
+        );
+    }
+}
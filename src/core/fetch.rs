@@ -0,0 +1,281 @@
+//! Seeding the content of new notes from a file or a url. Urls are fetched
+//! and html pages converted to markdown by external programs, curl and
+//! pandoc by default. The title and other metadata of a page are read from
+//! its `<title>` and `<meta>` tags.
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use color_eyre::eyre::eyre;
+use serde::{Deserialize, Serialize};
+
+use crate::result::Result;
+
+/// The `[fetch]` section of the configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FetchConfig {
+    /// program and arguments writing the content at `{url}` to stdout
+    pub command: Vec<String>,
+    /// program and arguments converting the html on stdin to markdown on
+    /// stdout. Pages are kept as html when empty.
+    pub html_command: Vec<String>,
+}
+
+impl Default for FetchConfig {
+    fn default() -> Self {
+        Self {
+            command: [
+                "curl",
+                "--fail",
+                "--silent",
+                "--show-error",
+                "--location",
+                "{url}",
+            ]
+            .map(String::from)
+            .to_vec(),
+            html_command: ["pandoc", "--from", "html", "--to", "gfm"]
+                .map(String::from)
+                .to_vec(),
+        }
+    }
+}
+
+impl FetchConfig {
+    /// The content of `source`, a file or an http(s) url, html pages being
+    /// converted to markdown
+    pub fn content(&self, source: &str) -> Result<String> {
+        if !is_url(source) {
+            return std::fs::read_to_string(Path::new(source))
+                .map_err(|e| eyre!("could not read {:?}: {}", source, e));
+        }
+        let content = self.page(source)?;
+        if !is_html(&content) {
+            return Ok(content);
+        }
+        self.html_to_markdown(&content)
+            .map_err(|e| eyre!("could not convert {} to markdown: {}", source, e))
+    }
+
+    /// The markdown of `html`, as is when there is no `html_command`
+    pub fn html_to_markdown(&self, html: &str) -> Result<String> {
+        if self.html_command.is_empty() {
+            return Ok(html.to_owned());
+        }
+        run(&self.html_command, Some(html))
+    }
+
+    /// The content at the http(s) `url`, as is
+    pub fn page(&self, url: &str) -> Result<String> {
+        let args: Vec<String> = self
+            .command
+            .iter()
+            .map(|arg| arg.replace("{url}", url))
+            .collect();
+        run(&args, None).map_err(|e| eyre!("could not fetch {}: {}", url, e))
+    }
+}
+
+/// The metadata of an html page
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub site: Option<String>,
+    pub description: Option<String>,
+    pub published: Option<String>,
+}
+
+impl PageMetadata {
+    /// The metadata of the html page `html`, from its open graph, citation
+    /// and standard `<meta>` tags and its `<title>`
+    pub fn from_html(html: &str) -> Self {
+        let meta = meta_tags(html);
+        let get = |names: &[&str]| {
+            names.iter().find_map(|name| {
+                meta.iter()
+                    .find(|(n, content)| n.eq_ignore_ascii_case(name) && !content.is_empty())
+                    .map(|(_, content)| content.clone())
+            })
+        };
+        Self {
+            title: get(&["og:title", "citation_title", "twitter:title"])
+                .or_else(|| title_tag(html)),
+            author: get(&["author", "citation_author", "article:author"]),
+            site: get(&["og:site_name", "application-name"]),
+            description: get(&["og:description", "description", "twitter:description"]),
+            published: get(&[
+                "article:published_time",
+                "citation_publication_date",
+                "date",
+            ]),
+        }
+    }
+}
+
+pub fn is_url(source: &str) -> bool {
+    source.starts_with("http://") || source.starts_with("https://")
+}
+
+/// The text of the `<title>` of `html`
+fn title_tag(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let start = lower.find("<title")?;
+    let start = start + lower[start..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    let title = decode_entities(html[start..end].trim());
+    (!title.is_empty()).then_some(title)
+}
+
+/// The `name` or `property` and the `content` of the `<meta>` tags of `html`
+fn meta_tags(html: &str) -> Vec<(String, String)> {
+    let lower = html.to_ascii_lowercase();
+    let mut tags = Vec::new();
+    for (start, _) in lower.match_indices("<meta") {
+        let Some(end) = lower[start..].find('>') else {
+            break;
+        };
+        let attributes = attributes(&html[start + "<meta".len()..start + end]);
+        let name = attributes
+            .iter()
+            .find(|(key, _)| key == "name" || key == "property")
+            .map(|(_, value)| value.clone());
+        let content = attributes
+            .iter()
+            .find(|(key, _)| key == "content")
+            .map(|(_, value)| decode_entities(value.trim()));
+        if let (Some(name), Some(content)) = (name, content) {
+            tags.push((name, content));
+        }
+    }
+    tags
+}
+
+/// The attributes of the tag `source`, keys lowercased
+fn attributes(source: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    let mut rest = source;
+    while let Some(eq) = rest.find('=') {
+        let key = rest[..eq].split_whitespace().last().unwrap_or_default();
+        let value = rest[eq + 1..].trim_start();
+        let (value, next) = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => match value[1..].find(quote) {
+                Some(end) => (&value[1..1 + end], &value[end + 2..]),
+                None => (&value[1..], ""),
+            },
+            _ => {
+                let end = value.find(char::is_whitespace).unwrap_or(value.len());
+                (&value[..end], &value[end..])
+            }
+        };
+        attributes.push((key.to_ascii_lowercase(), value.to_owned()));
+        rest = next;
+    }
+    attributes
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+fn is_html(content: &str) -> bool {
+    let start: String = content.trim_start().chars().take(512).collect();
+    let start = start.to_ascii_lowercase();
+    start.starts_with("<!doctype html") || start.starts_with("<html") || start.contains("<head")
+}
+
+/// Run `command`, writing `input` to its stdin, and return its stdout
+fn run(command: &[String], input: Option<&str>) -> Result<String> {
+    let Some((program, args)) = command.split_first() else {
+        return Err(eyre!("empty command"));
+    };
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| eyre!("could not run {:?}: {}", program, e))?;
+    if let Some(input) = input {
+        // dropped once written, closing the stdin of the program
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        stdin.write_all(input.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(eyre!("{:?} failed with {}", program, output.status));
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_content() {
+        let config = FetchConfig {
+            command: command(&["echo", "<html><body>{url}</body></html>"]),
+            html_command: command(&["sed", "s/<[^>]*>//g"]),
+        };
+        assert_eq!(
+            config.content("https://example.com/paper").unwrap(),
+            "https://example.com/paper\n"
+        );
+
+        let config = FetchConfig {
+            command: command(&["echo", "# {url}"]),
+            ..config
+        };
+        // content that is not html is kept as is
+        assert_eq!(config.content("http://a").unwrap(), "# http://a\n");
+
+        let file = assert_fs::NamedTempFile::new("agenda.md").unwrap();
+        std::fs::write(file.path(), "- item\n").unwrap();
+        assert_eq!(
+            config.content(&file.path().to_string_lossy()).unwrap(),
+            "- item\n"
+        );
+    }
+
+    #[test]
+    fn test_page_metadata() {
+        let html = r#"<!doctype html><html><head>
+            <title>Fallback &amp; more</title>
+            <meta property="og:title" content="A &quot;Paper&quot;">
+            <meta name=author content='Ada Lovelace'>
+            <meta property="og:site_name" content="Journal" />
+            <meta name="description" content="">
+            </head><body></body></html>"#;
+        assert_eq!(
+            PageMetadata::from_html(html),
+            PageMetadata {
+                title: Some("A \"Paper\"".to_owned()),
+                author: Some("Ada Lovelace".to_owned()),
+                site: Some("Journal".to_owned()),
+                description: None,
+                published: None,
+            }
+        );
+        assert_eq!(
+            PageMetadata::from_html("<title>Fallback &amp; more</title>").title,
+            Some("Fallback & more".to_owned())
+        );
+    }
+}
@@ -0,0 +1,54 @@
+//! The file name convention of a collection, a template such as
+//! `{{ date }}-{{ slug }}.md` rendered with the fields of each document.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use jiff::Timestamp;
+use jiff::tz::TimeZone;
+use serde_json::json;
+
+use crate::core::parser::{DocumentParser, FrontMatterFormat, FrontMatterParser, Parse};
+use crate::core::template_engine::render_template;
+use crate::core::{
+    extract_id_from_frontmatter, extract_title_from_ast, extract_title_from_frontmatter,
+};
+use crate::result::Result;
+
+/// The file name `path` should have according to `pattern`, given its
+/// `content`. The variables of the pattern are `id`, `title`, `slug` (the
+/// slugified title) and `date` (the creation date of the file). Returns `None`
+/// for documents without a title.
+pub fn expected_filename(
+    pattern: &str,
+    root: &Path,
+    path: &Path,
+    content: &str,
+    format: FrontMatterFormat,
+    tz: &TimeZone,
+) -> Result<Option<String>> {
+    let (frontmatter, body) = FrontMatterParser::new(format).parse(content.to_owned());
+    let frontmatter = frontmatter.unwrap_or_default();
+    let title = match extract_title_from_frontmatter(&frontmatter) {
+        Some(title) => title,
+        None => match extract_title_from_ast(&DocumentParser::new().parse(&body)?) {
+            Some(title) => title,
+            None => return Ok(None),
+        },
+    };
+    let id = extract_id_from_frontmatter(&frontmatter)
+        .unwrap_or_else(|| crate::core::path_to_id(root, path));
+
+    let created: Timestamp = std::fs::metadata(path)?.created()?.try_into()?;
+    let date = created
+        .to_zoned(tz.clone())
+        .strftime("%Y-%m-%d")
+        .to_string();
+
+    // slugify keeps path separators, which are not valid in a file name
+    let slug = crate::core::slug::slugify(&title).replace('/', "-");
+    let extra = HashMap::from([("slug".to_owned(), json!(slug))]);
+
+    let filename = render_template(pattern, &id.0, &title, &date, "", &extra)?;
+    Ok(Some(filename.trim().to_owned()))
+}
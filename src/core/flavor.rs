@@ -0,0 +1,156 @@
+//! Presets of the markdown syntax and conventions of a collection.
+//!
+//! A flavor sets the `[parser]` and `[format]` settings of the collection in
+//! one switch. It only provides defaults: the settings written in the
+//! configuration take precedence over the ones of the flavor.
+//!
+//! A document can set its own flavor and settings under the `zet` key of its
+//! frontmatter, e.g. to keep the conventions of the collection it was
+//! imported from:
+//!
+//! ```yaml
+//! zet:
+//!   flavor: obsidian
+//!   format:
+//!     wrap: false
+//! ```
+
+use figment::Figment;
+use figment::providers::Serialized;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::config::Config;
+use crate::core::format::FormatConfig;
+use crate::core::parser::FrontMatterParser;
+use crate::core::parser::comments::ParserConfig;
+use crate::result::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Flavor {
+    /// plain commonmark: html comments and nothing beyond the spec
+    #[serde(rename = "commonmark")]
+    CommonMark,
+    /// github flavored markdown: tables, task lists, strikethrough, footnotes,
+    /// math and alerts
+    Gfm,
+    /// obsidian: gfm, and wiki links, `#tags`, `%%comments%%` and callouts
+    Obsidian,
+}
+
+impl Flavor {
+    /// The settings of the flavor, shaped like the configuration
+    pub fn preset(self) -> Value {
+        let gfm = self != Flavor::CommonMark;
+        let obsidian = self == Flavor::Obsidian;
+        let comments = match obsidian {
+            true => json!(["obsidian", "html"]),
+            false => json!(["html"]),
+        };
+        json!({
+            "parser": {
+                "comments": comments,
+                "wikilinks": obsidian,
+                "alerts": gfm,
+                "footnotes": gfm,
+                "math": gfm,
+                "tables": gfm,
+                "task_lists": gfm,
+                "strikethrough": gfm,
+                "heading_attributes": false,
+                "inline_tags": obsidian,
+            },
+            "format": {
+                "tables": gfm,
+            },
+        })
+    }
+}
+
+/// The settings a document is parsed, formatted and linted with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentSettings {
+    pub parser: ParserConfig,
+    pub format: FormatConfig,
+}
+
+impl DocumentSettings {
+    /// The settings of `document`, see [`DocumentSettings::resolve`]
+    pub fn of(config: &Config, document: &str) -> Result<DocumentSettings> {
+        let (frontmatter, _) =
+            FrontMatterParser::new(config.front_matter_format).parse(document.to_owned());
+        Self::resolve(config, frontmatter.as_ref())
+    }
+
+    /// The settings of the document with `frontmatter`: the ones of `config`,
+    /// overridden by the flavor and then the settings under its `zet` key
+    pub fn resolve(config: &Config, frontmatter: Option<&Value>) -> Result<DocumentSettings> {
+        let collection = DocumentSettings {
+            parser: config.parser.clone(),
+            format: config.format.clone(),
+        };
+        let Some(overrides) = frontmatter.and_then(|data| data.get("zet")) else {
+            return Ok(collection);
+        };
+        let mut figment = Figment::from(Serialized::defaults(collection));
+        if let Some(flavor) = overrides.get("flavor") {
+            let flavor: Flavor = serde_json::from_value(flavor.clone())?;
+            figment = figment.merge(Serialized::defaults(flavor.preset()));
+        }
+        Ok(figment.merge(Serialized::defaults(overrides)).extract()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::format::FormatConfig;
+    use crate::core::parser::ast_nodes::CommentSyntax;
+    use crate::core::parser::comments::ParserConfig;
+
+    #[test]
+    fn test_presets() {
+        for flavor in [Flavor::CommonMark, Flavor::Gfm, Flavor::Obsidian] {
+            let preset = flavor.preset();
+            let parser: ParserConfig = serde_json::from_value(preset["parser"].clone()).unwrap();
+            let format: FormatConfig = serde_json::from_value(preset["format"].clone()).unwrap();
+            assert_eq!(parser.wikilinks, flavor == Flavor::Obsidian);
+            assert_eq!(parser.tables, format.tables);
+            assert_eq!(
+                parser.comments.contains(&CommentSyntax::Obsidian),
+                flavor == Flavor::Obsidian
+            );
+        }
+        assert_eq!(
+            serde_json::to_value(Flavor::CommonMark).unwrap(),
+            json!("commonmark")
+        );
+    }
+
+    #[test]
+    fn test_document_settings() {
+        let mut config = Config::default();
+        config.format.wrap = Some(80);
+        config.format.tables = true;
+
+        let settings = DocumentSettings::of(&config, "# A\n").unwrap();
+        assert_eq!(settings.format.wrap, Some(80));
+        assert!(settings.parser.inline_tags);
+
+        let document = "---\nzet:\n  flavor: commonmark\n  format:\n    wrap: false\n---\n# A\n";
+        let settings = DocumentSettings::of(&config, document).unwrap();
+        assert_eq!(settings.format.wrap, None);
+        assert!(!settings.format.tables);
+        assert!(!settings.parser.inline_tags);
+
+        let document = "---\nzet:\n  parser:\n    wikilinks: false\n---\n";
+        let settings = DocumentSettings::of(&config, document).unwrap();
+        assert!(!settings.parser.wikilinks);
+        assert!(settings.parser.tables);
+        assert_eq!(settings.format.wrap, Some(80));
+
+        let document = "---\nzet:\n  flavor: markdown\n---\n";
+        assert!(DocumentSettings::of(&config, document).is_err());
+    }
+}
@@ -0,0 +1,859 @@
+//! Formatting documents, rewriting their markdown without changing what it
+//! renders to.
+//!
+//! Each rule produces [`TextEdit`]s of the body, which are applied before the
+//! next rule runs. The frontmatter is left as written.
+
+use std::ops::Range;
+
+use clap::ValueEnum;
+use pulldown_cmark::{Event, LinkType, Parser, Tag, TagEnd};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::core::frontmatter::split_frontmatter;
+use crate::core::lint::{TextEdit, apply_edits};
+use crate::core::parser::arena::Ast;
+use crate::core::parser::ast_nodes::{ColumnAlignment, CommentSyntax, Node, NodeKind, TableCell};
+use crate::core::parser::comments::find_comments;
+use crate::core::parser::{DocumentParser, DocumentParserOptions, Parse};
+use crate::result::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinkStyle {
+    /// `[text](url "title")`
+    Inline,
+    /// `[text][1]`, with `[1]: url "title"` at the end of the document
+    Reference,
+}
+
+/// The `[format]` section of the configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormatConfig {
+    /// the style links are written in, links are left as written if unset
+    pub link_style: Option<LinkStyle>,
+    /// the column paragraphs are wrapped at, paragraphs are left as written
+    /// if unset or `false`
+    #[serde(deserialize_with = "deserialize_wrap")]
+    pub wrap: Option<usize>,
+    /// pad the cells of tables to the width of their column
+    pub tables: bool,
+    /// write typographic quotes, dashes and ellipses in text, as
+    /// `html.smart_punctuation` renders them
+    pub smart_punctuation: bool,
+}
+
+/// A column, or `false` to not wrap, e.g. in the frontmatter of a document
+/// opting out of the wrapping of the collection
+fn deserialize_wrap<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<usize>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Wrap {
+        Column(usize),
+        Enabled(bool),
+    }
+    match Option::<Wrap>::deserialize(deserializer)? {
+        Some(Wrap::Column(width)) => Ok(Some(width)),
+        Some(Wrap::Enabled(false)) | None => Ok(None),
+        Some(Wrap::Enabled(true)) => Err(D::Error::custom("expected a column or false")),
+    }
+}
+
+/// Format `document` as configured by `config`. Comments written in one of
+/// `comments` are left as written, as is the markdown around them.
+pub fn format(document: &str, config: &FormatConfig, comments: &[CommentSyntax]) -> Result<String> {
+    let body_offset = match split_frontmatter(document) {
+        Some((_, body)) => document.len() - body.len(),
+        None => 0,
+    };
+    // each pass formats the output of the one before, the edits of passes
+    // over the same text would otherwise overlap and be dropped
+    let mut body = document[body_offset..].to_owned();
+    let edits = match config.link_style {
+        Some(LinkStyle::Inline) => inline_links(&body),
+        Some(LinkStyle::Reference) => reference_links(&body),
+        None => Vec::new(),
+    };
+    apply_pass(&mut body, edits, comments);
+    if config.smart_punctuation {
+        let edits = smart_punctuation(&body);
+        apply_pass(&mut body, edits, comments);
+    }
+    if let Some(width) = config.wrap {
+        let edits = wrap_paragraphs(&body, width);
+        apply_pass(&mut body, edits, comments);
+    }
+    if config.tables {
+        let ast = DocumentParser::with_comments(comments).parse(&body)?;
+        let mut edits = Vec::new();
+        format_tables(&mut edits, &body, &ast, None);
+        apply_pass(&mut body, edits, comments);
+    }
+    let mut formatted = format!("{}{}", &document[..body_offset], body);
+
+    // documents end with a single line break
+    let end = formatted.trim_end().len();
+    if end > body_offset {
+        formatted.truncate(end);
+        formatted.push('\n');
+    }
+    Ok(formatted)
+}
+
+/// Apply the `edits` of a pass to `body`, leaving the comments written in one
+/// of `comments` as written
+fn apply_pass(body: &mut String, mut edits: Vec<TextEdit>, comments: &[CommentSyntax]) {
+    if edits.is_empty() {
+        return;
+    }
+    let comments = find_comments(body, comments);
+    edits.retain(|edit| {
+        !comments
+            .iter()
+            .any(|c| c.range.start < edit.range.end && edit.range.start < c.range.end)
+    });
+    *body = apply_edits(body, &edits);
+}
+
+/// A link as written in the body
+struct SourceLink {
+    link_type: LinkType,
+    /// byte range of the whole link
+    range: Range<usize>,
+    /// byte range of the text between the brackets
+    text: Range<usize>,
+    url: String,
+    title: String,
+}
+
+/// The links of `body`, except for wiki links and autolinks
+fn source_links(body: &str) -> Vec<SourceLink> {
+    let mut links = Vec::new();
+    let mut current: Option<SourceLink> = None;
+    let parser = Parser::new_ext(body, DocumentParserOptions::default().0);
+    for (event, range) in parser.into_offset_iter() {
+        match event {
+            Event::Start(Tag::Link {
+                link_type:
+                    link_type @ (LinkType::Inline
+                    | LinkType::Reference
+                    | LinkType::Collapsed
+                    | LinkType::Shortcut),
+                dest_url,
+                title,
+                ..
+            }) => {
+                current = Some(SourceLink {
+                    link_type,
+                    text: range.start + 1..range.start + 1,
+                    range,
+                    url: dest_url.into_string(),
+                    title: title.into_string(),
+                })
+            }
+            Event::End(TagEnd::Link) => {
+                // the text ends where its last event does
+                if let Some(mut link) = current.take()
+                    && body[link.text.end..].starts_with(']')
+                {
+                    // the range of a collapsed link ends before its `[]`
+                    if link.link_type == LinkType::Collapsed
+                        && body[link.range.end..].starts_with("[]")
+                    {
+                        link.range.end += "[]".len();
+                    }
+                    links.push(link);
+                }
+            }
+            _ => {
+                if let Some(link) = &mut current {
+                    link.text.end = link.text.end.max(range.end);
+                }
+            }
+        }
+    }
+    links
+}
+
+fn inline_destination(url: &str, title: &str) -> String {
+    let url = match url.is_empty() || url.contains([' ', '(', ')', '<', '>']) {
+        true => format!("<{url}>"),
+        false => url.to_owned(),
+    };
+    match title.is_empty() {
+        true => url,
+        false => format!("{url} \"{}\"", title.replace('"', "\\\"")),
+    }
+}
+
+/// Rewrite reference links as inline links, and remove the definitions no
+/// longer referenced
+fn inline_links(body: &str) -> Vec<TextEdit> {
+    let mut edits = Vec::new();
+    for link in source_links(body) {
+        if link.link_type == LinkType::Inline {
+            continue;
+        }
+        edits.push(TextEdit {
+            replacement: format!(
+                "[{}]({})",
+                &body[link.text.clone()],
+                inline_destination(&link.url, &link.title)
+            ),
+            range: link.range,
+        });
+    }
+    if edits.is_empty() {
+        return edits;
+    }
+
+    // definitions may still be referenced by images
+    let mut parser = Parser::new_ext(body, DocumentParserOptions::default().0);
+    let mut image_labels = Vec::new();
+    for event in parser.by_ref() {
+        if let Event::Start(Tag::Image {
+            link_type: LinkType::Reference | LinkType::Collapsed | LinkType::Shortcut,
+            id,
+            ..
+        }) = event
+        {
+            image_labels.push(id.to_lowercase());
+        }
+    }
+    for (label, definition) in parser.reference_definitions().iter() {
+        if image_labels.contains(&label.to_lowercase()) {
+            continue;
+        }
+        edits.push(TextEdit {
+            range: whole_lines(body, definition.span.clone()),
+            replacement: String::new(),
+        });
+    }
+    edits
+}
+
+/// Rewrite inline links as reference links, numbered in the order they
+/// appear. Links to the same url and title share a definition, existing
+/// definitions are reused.
+fn reference_links(body: &str) -> Vec<TextEdit> {
+    let parser = Parser::new_ext(body, DocumentParserOptions::default().0);
+    let mut definitions: Vec<(String, String, String)> = parser
+        .reference_definitions()
+        .iter()
+        .map(|(label, d)| {
+            (
+                label.to_owned(),
+                d.dest.to_string(),
+                d.title.as_deref().unwrap_or_default().to_owned(),
+            )
+        })
+        .collect();
+    let existing = definitions.len();
+
+    let mut edits = Vec::new();
+    let mut next = 1;
+    for link in source_links(body) {
+        if link.link_type != LinkType::Inline {
+            continue;
+        }
+        let label = match definitions
+            .iter()
+            .find(|(_, url, title)| *url == link.url && *title == link.title)
+        {
+            Some((label, _, _)) => label.clone(),
+            None => {
+                while definitions
+                    .iter()
+                    .any(|(label, _, _)| *label == next.to_string())
+                {
+                    next += 1;
+                }
+                definitions.push((next.to_string(), link.url.clone(), link.title.clone()));
+                next.to_string()
+            }
+        };
+        edits.push(TextEdit {
+            replacement: format!("[{}][{label}]", &body[link.text.clone()]),
+            range: link.range,
+        });
+    }
+
+    if definitions.len() > existing {
+        let mut appended = match body.ends_with("\n\n") || body.is_empty() {
+            true => String::new(),
+            false if body.ends_with('\n') => "\n".to_owned(),
+            false => "\n\n".to_owned(),
+        };
+        for (label, url, title) in &definitions[existing..] {
+            appended.push_str(&format!("[{label}]: {}\n", inline_destination(url, title)));
+        }
+        edits.push(TextEdit {
+            range: body.len()..body.len(),
+            replacement: appended,
+        });
+    }
+    edits
+}
+
+/// The inline content of a paragraph or a tight list item, as written
+#[derive(Default)]
+struct InlineBlock {
+    range: Range<usize>,
+    /// ranges that are not wrapped: code, links, math and html
+    atoms: Vec<Range<usize>>,
+    hard_breaks: Vec<Range<usize>>,
+}
+
+/// Reflow the paragraphs of `body` to lines of at most `width` characters,
+/// where the words allow it. Code, links, math and inline html are kept
+/// whole, and hard breaks where they are. Headings, code blocks, tables and
+/// block quotes are left as written.
+fn wrap_paragraphs(body: &str, width: usize) -> Vec<TextEdit> {
+    let mut blocks = Vec::new();
+    let mut current: Option<InlineBlock> = None;
+    // depth of the blocks whose content is not wrapped
+    let mut skipped = 0;
+    // depth of the links and images, which are kept whole
+    let mut links = 0;
+
+    let parser = Parser::new_ext(body, DocumentParserOptions::default().0);
+    for (event, range) in parser.into_offset_iter() {
+        if links > 0 {
+            match event {
+                Event::Start(Tag::Link { .. } | Tag::Image { .. }) => links += 1,
+                Event::End(TagEnd::Link | TagEnd::Image) => links -= 1,
+                _ => {}
+            }
+            continue;
+        }
+        let (is_atom, is_break) = match &event {
+            Event::Start(Tag::Link { .. } | Tag::Image { .. }) => {
+                links += 1;
+                (true, false)
+            }
+            Event::Code(_)
+            | Event::InlineMath(_)
+            | Event::DisplayMath(_)
+            | Event::InlineHtml(_)
+            | Event::FootnoteReference(_) => (true, false),
+            // the checkbox is wrapped with the text of its item, like the
+            // `[/]` and `[-]` markers pulldown-cmark reads as text
+            Event::TaskListMarker(_) => (true, false),
+            Event::HardBreak => (false, true),
+            Event::Text(_)
+            | Event::SoftBreak
+            | Event::Start(
+                Tag::Emphasis
+                | Tag::Strong
+                | Tag::Strikethrough
+                | Tag::Superscript
+                | Tag::Subscript,
+            )
+            | Event::End(
+                TagEnd::Emphasis
+                | TagEnd::Strong
+                | TagEnd::Strikethrough
+                | TagEnd::Superscript
+                | TagEnd::Subscript,
+            ) => (false, false),
+            // the start or end of a block ends the inline content
+            _ => {
+                blocks.extend(current.take());
+                match event {
+                    Event::Start(
+                        Tag::Heading { .. }
+                        | Tag::CodeBlock(_)
+                        | Tag::Table(_)
+                        | Tag::BlockQuote(_)
+                        | Tag::HtmlBlock
+                        | Tag::MetadataBlock(_),
+                    ) => skipped += 1,
+                    Event::End(
+                        TagEnd::Heading(_)
+                        | TagEnd::CodeBlock
+                        | TagEnd::Table
+                        | TagEnd::BlockQuote(_)
+                        | TagEnd::HtmlBlock
+                        | TagEnd::MetadataBlock(_),
+                    ) => skipped -= 1,
+                    _ => {}
+                }
+                continue;
+            }
+        };
+        if skipped > 0 {
+            continue;
+        }
+        let block = current.get_or_insert_with(|| InlineBlock {
+            range: range.clone(),
+            ..InlineBlock::default()
+        });
+        block.range.end = block.range.end.max(range.end);
+        if is_atom {
+            block.atoms.push(range);
+        } else if is_break {
+            block.hard_breaks.push(range);
+        }
+    }
+    blocks.extend(current);
+
+    let mut edits = Vec::new();
+    for block in blocks {
+        let source = body[block.range.clone()].trim_end();
+        let range = block.range.start..block.range.start + source.len();
+        let line_start = body[..range.start].rfind('\n').map_or(0, |i| i + 1);
+        let prefix = &body[line_start..range.start];
+        let wrapped = wrap_block(body, &block, prefix.chars().count(), width);
+        if wrapped != source {
+            edits.push(TextEdit {
+                range,
+                replacement: wrapped,
+            });
+        }
+    }
+    edits
+}
+
+/// Words that would start a block if they started a line: headings, list
+/// items, block quotes, setext underlines, table delimiters, html blocks and
+/// code fences
+fn is_block_marker(word: &str) -> bool {
+    let is_ordered_item = word.len() > 1
+        && word[..word.len() - 1].chars().all(|c| c.is_ascii_digit())
+        && word.ends_with(['.', ')']);
+    word.chars().all(|c| c == '#')
+        || matches!(word, "-" | "+" | "*")
+        || is_ordered_item
+        || word.starts_with(['>', '<', '|'])
+        || word.chars().all(|c| matches!(c, '=' | '-' | ':' | '|'))
+        || word.starts_with("```")
+        || word.starts_with("~~~")
+        || (word.starts_with("[^") && word.ends_with(':'))
+}
+
+/// Lines being filled with words
+struct Lines {
+    text: String,
+    indent: usize,
+    width: usize,
+    column: usize,
+    line_is_empty: bool,
+}
+
+impl Lines {
+    fn push_word(&mut self, word: &str) {
+        if word.is_empty() {
+            return;
+        }
+        let length = word.chars().count();
+        if self.line_is_empty {
+            self.line_is_empty = false;
+        } else if self.column + 1 + length > self.width && !is_block_marker(word) {
+            self.break_line();
+            self.line_is_empty = false;
+        } else {
+            self.text.push(' ');
+            self.column += 1;
+        }
+        self.text.push_str(word);
+        self.column += length;
+    }
+
+    fn break_line(&mut self) {
+        self.text.push('\n');
+        self.text.push_str(&" ".repeat(self.indent));
+        self.column = self.indent;
+        self.line_is_empty = true;
+    }
+}
+
+fn wrap_block(body: &str, block: &InlineBlock, indent: usize, width: usize) -> String {
+    let mut lines = Lines {
+        text: String::new(),
+        indent,
+        width,
+        column: indent,
+        line_is_empty: true,
+    };
+    let mut word = String::new();
+
+    let mut position = block.range.start;
+    while position < block.range.end {
+        if let Some(atom) = block.atoms.iter().find(|atom| atom.start == position) {
+            word.push_str(&body[atom.clone()]);
+            position = atom.end;
+        } else if let Some(hard_break) = block.hard_breaks.iter().find(|b| b.start == position) {
+            lines.push_word(&std::mem::take(&mut word));
+            lines
+                .text
+                .push_str(body[hard_break.clone()].trim_end_matches(['\n', '\r']));
+            lines.break_line();
+            position = hard_break.end;
+        } else {
+            let Some(c) = body[position..].chars().next() else {
+                break;
+            };
+            if c.is_whitespace() {
+                lines.push_word(&std::mem::take(&mut word));
+            } else {
+                word.push(c);
+            }
+            position += c.len_utf8();
+        }
+    }
+    lines.push_word(&word);
+    lines.text
+}
+
+/// Replace straight quotes, `--`, `---` and `...` in the text of `body` with
+/// their typographic counterparts. Code, math, html, wiki links, autolinks
+/// and escaped characters are left as written.
+fn smart_punctuation(body: &str) -> Vec<TextEdit> {
+    let mut edits = Vec::new();
+    // depth of the blocks and links whose text is not prose
+    let mut skipped = 0;
+    let parser = Parser::new_ext(body, DocumentParserOptions::default().0);
+    for (event, range) in parser.into_offset_iter() {
+        match event {
+            Event::Start(
+                Tag::CodeBlock(_)
+                | Tag::MetadataBlock(_)
+                | Tag::Link {
+                    link_type: LinkType::WikiLink { .. } | LinkType::Autolink | LinkType::Email,
+                    ..
+                },
+            ) => skipped += 1,
+            Event::End(TagEnd::CodeBlock | TagEnd::MetadataBlock(_)) => skipped -= 1,
+            Event::End(TagEnd::Link) if skipped > 0 => skipped -= 1,
+            Event::Text(text) if skipped == 0 && body[range.clone()] == *text => {
+                let replacement = smart_text(body, range.start, &text);
+                if replacement != *text {
+                    edits.push(TextEdit { range, replacement });
+                }
+            }
+            _ => {}
+        }
+    }
+    edits
+}
+
+/// `text`, found at `offset` in `body`, with typographic punctuation. Quotes
+/// open after whitespace, an opening bracket or a dash, and close otherwise.
+fn smart_text(body: &str, offset: usize, text: &str) -> String {
+    let text = text
+        .replace("...", "\u{2026}")
+        .replace("---", "\u{2014}")
+        .replace("--", "\u{2013}");
+    let mut result = String::with_capacity(text.len());
+    let mut previous = body[..offset].chars().next_back();
+    for c in text.chars() {
+        let opens = previous.is_none_or(|p| {
+            p.is_whitespace() || matches!(p, '(' | '[' | '{' | '\u{2013}' | '\u{2014}')
+        });
+        let escaped = previous == Some('\\');
+        result.push(match (c, opens) {
+            _ if escaped => c,
+            ('"', true) => '\u{201c}',
+            ('"', false) => '\u{201d}',
+            ('\'', true) => '\u{2018}',
+            ('\'', false) => '\u{2019}',
+            _ => c,
+        });
+        previous = Some(c);
+    }
+    result
+}
+
+/// The edits formatting the tables of `document` overlapping `range`, e.g. the
+/// selection of an editor
+pub fn format_tables_in_range(document: &str, range: Range<usize>) -> Result<Vec<TextEdit>> {
+    let body_offset = match split_frontmatter(document) {
+        Some((_, body)) => document.len() - body.len(),
+        None => 0,
+    };
+    let body = &document[body_offset..];
+    let ast = DocumentParser::new().parse(body)?;
+
+    let within = range.start.saturating_sub(body_offset)..range.end.saturating_sub(body_offset);
+    let mut edits = Vec::new();
+    format_tables(&mut edits, body, &ast, Some(&within));
+    for edit in &mut edits {
+        edit.range = edit.range.start + body_offset..edit.range.end + body_offset;
+    }
+    Ok(edits)
+}
+
+fn format_tables(edits: &mut Vec<TextEdit>, body: &str, ast: &Ast, within: Option<&Range<usize>>) {
+    for table in ast.nodes_of_kind(NodeKind::Table) {
+        let Node::Table {
+            range,
+            header,
+            column_alignment,
+            rows,
+        } = table.node()
+        else {
+            continue;
+        };
+        if within.is_some_and(|within| range.end < within.start || within.end < range.start) {
+            continue;
+        }
+        let cells = |cells: &[TableCell]| -> Vec<String> {
+            cells
+                .iter()
+                .map(|cell| body[cell.range.clone()].trim().to_owned())
+                .collect()
+        };
+        let header = cells(&header.cells);
+        let rows: Vec<Vec<String>> = rows.iter().map(|row| cells(&row.cells)).collect();
+
+        let source = body[range.clone()].trim_end();
+        let line_start = body[..range.start].rfind('\n').map_or(0, |i| i + 1);
+        let indent = &body[line_start..range.start];
+        // tables in block quotes are left as written
+        if !indent.chars().all(char::is_whitespace) {
+            continue;
+        }
+        let table = format_table(&header, column_alignment, &rows, indent);
+        if table != source {
+            edits.push(TextEdit {
+                range: range.start..range.start + source.len(),
+                replacement: table,
+            });
+        }
+    }
+}
+
+/// Write a table with the cells of each column padded to the same width and
+/// aligned as its delimiter says. Rows missing cells are filled with empty
+/// ones. Every line but the first is indented with `indent`.
+pub(crate) fn format_table(
+    header: &[String],
+    alignment: &[ColumnAlignment],
+    rows: &[Vec<String>],
+    indent: &str,
+) -> String {
+    let columns = alignment.len().max(header.len());
+    let cell =
+        |row: &[String], column: usize| row.get(column).map_or("", String::as_str).to_owned();
+    let width = |column: usize| {
+        std::iter::once(header)
+            .chain(rows.iter().map(Vec::as_slice))
+            .map(|row| cell(row, column).chars().count())
+            .max()
+            .unwrap_or(0)
+            // the shortest delimiter holding an alignment, `:-:`
+            .max(3)
+    };
+    let widths: Vec<usize> = (0..columns).map(width).collect();
+    let alignment = |column: usize| {
+        alignment
+            .get(column)
+            .copied()
+            .unwrap_or(ColumnAlignment::None)
+    };
+
+    let line = |row: &[String]| {
+        let cells: Vec<String> = (0..columns)
+            .map(|column| {
+                let text = cell(row, column);
+                let padding = widths[column] - text.chars().count();
+                let (before, after) = match alignment(column) {
+                    ColumnAlignment::Right => (padding, 0),
+                    ColumnAlignment::Center => (padding / 2, padding - padding / 2),
+                    ColumnAlignment::None | ColumnAlignment::Left => (0, padding),
+                };
+                format!("{}{text}{}", " ".repeat(before), " ".repeat(after))
+            })
+            .collect();
+        format!("| {} |", cells.join(" | "))
+    };
+    let delimiter: Vec<String> = (0..columns)
+        .map(|column| {
+            let width = widths[column];
+            match alignment(column) {
+                ColumnAlignment::None => "-".repeat(width),
+                ColumnAlignment::Left => format!(":{}", "-".repeat(width - 1)),
+                ColumnAlignment::Right => format!("{}:", "-".repeat(width - 1)),
+                ColumnAlignment::Center => format!(":{}:", "-".repeat(width - 2)),
+            }
+        })
+        .collect();
+
+    let mut lines = vec![line(header), format!("| {} |", delimiter.join(" | "))];
+    lines.extend(rows.iter().map(|row| line(row)));
+    lines.join(&format!("\n{indent}"))
+}
+
+/// Extend `range` to the lines it spans, including the line break
+fn whole_lines(body: &str, range: Range<usize>) -> Range<usize> {
+    let start = body[..range.start].rfind('\n').map_or(0, |i| i + 1);
+    let end = body[range.end..]
+        .find('\n')
+        .map_or(body.len(), |i| range.end + i + 1);
+    start..end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format_links(document: &str, link_style: LinkStyle) -> String {
+        let config = FormatConfig {
+            link_style: Some(link_style),
+            ..FormatConfig::default()
+        };
+        format(document, &config, &[]).unwrap()
+    }
+
+    #[test]
+    fn test_reference_links() {
+        let document = "---\nid: a\n---\n\nsee [a *b*](https://a.com \"A\"), [c](https://c.com) and\n[again](https://a.com \"A\"), [[wiki]] and [d][x]\n\n[x]: https://c.com\n";
+        assert_eq!(
+            format_links(document, LinkStyle::Reference),
+            "---\nid: a\n---\n\nsee [a *b*][1], [c][x] and\n[again][1], [[wiki]] and [d][x]\n\n[x]: https://c.com\n\n[1]: https://a.com \"A\"\n"
+        );
+    }
+
+    #[test]
+    fn test_inline_links() {
+        let document = "see [a][1], [b][] and [c]\n\n![img][1]\n\n[1]: https://a.com \"A \\\"q\\\"\"\n[b]: <a b.md>\n[c]: https://c.com\n";
+        assert_eq!(
+            format_links(document, LinkStyle::Inline),
+            "see [a](https://a.com \"A \\\"q\\\"\"), [b](<a b.md>) and [c](https://c.com)\n\n![img][1]\n\n[1]: https://a.com \"A \\\"q\\\"\"\n"
+        );
+
+        // converting back and forth keeps the document
+        let document = "see [a](https://a.com \"A\") and [b](b.md)\n";
+        let references = format_links(document, LinkStyle::Reference);
+        assert_eq!(format_links(&references, LinkStyle::Inline), document);
+    }
+
+    fn wrap(document: &str, width: usize) -> String {
+        let config = FormatConfig {
+            wrap: Some(width),
+            ..FormatConfig::default()
+        };
+        format(document, &config, &[]).unwrap()
+    }
+
+    #[test]
+    fn test_wrap() {
+        let document = "# a heading that is longer than the width\n\nsome words, `a code span`\nand [a link](https://example.com/a/long/path) to\nwrap - 1. at *the width*  \nafter a break\n\n```\na code block that is longer than the width\n```\n\n- an item that is longer than the width\n  - [ ] nested\n\n> a quote that is longer than the width\n";
+        assert_eq!(
+            wrap(document, 20),
+            "# a heading that is longer than the width\n\nsome words,\n`a code span` and\n[a link](https://example.com/a/long/path)\nto wrap - 1. at *the\nwidth*  \nafter a break\n\n```\na code block that is longer than the width\n```\n\n- an item that is\n  longer than the\n  width\n  - [ ] nested\n\n> a quote that is longer than the width\n"
+        );
+
+        // short lines are joined
+        assert_eq!(wrap("a\nb\nc\n", 80), "a b c\n");
+        // block markers do not start a line
+        assert_eq!(wrap("aaaa - b\n", 5), "aaaa -\nb\n");
+        assert_eq!(wrap("aaaa # b\n", 5), "aaaa #\nb\n");
+        // the blank lines of loose lists are kept, as are their absence
+        assert_eq!(wrap("- a\n  b\n\n- c\n", 80), "- a b\n\n- c\n");
+        assert_eq!(wrap("- a\n  b\n- c\n", 80), "- a b\n- c\n");
+        // tasks are wrapped as one unit, whatever their marker
+        assert_eq!(
+            wrap(
+                "- [ ] a task that is longer\n- [/] a task that is longer\n",
+                14
+            ),
+            "- [ ] a task\n  that is\n  longer\n- [/] a task\n  that is\n  longer\n"
+        );
+    }
+
+    #[test]
+    fn test_tables() {
+        let config = FormatConfig {
+            tables: true,
+            ..FormatConfig::default()
+        };
+        let document = "# Table\n\n|a|right|center|\n|:-|--:|:-:|\n|long cell|1|x|\n|b\n\n- item\n\n  | a | b |\n  |---|---|\n  | c |\n";
+        assert_eq!(
+            format(document, &config, &[]).unwrap(),
+            "# Table\n\n| a         | right | center |\n| :-------- | ----: | :----: |\n| long cell |     1 |   x    |\n| b         |       |        |\n\n- item\n\n  | a   | b   |\n  | --- | --- |\n  | c   |     |\n"
+        );
+
+        // only the tables within the range are formatted
+        let document = "|a|\n|-|\n\ntext\n\n|b|\n|-|\n";
+        let edits = format_tables_in_range(document, 12..14).unwrap();
+        assert!(edits.is_empty());
+        let edits = format_tables_in_range(document, 20..21).unwrap();
+        assert_eq!(
+            edits,
+            vec![TextEdit {
+                range: 15..22,
+                replacement: "| b   |\n| --- |".to_owned()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_smart_punctuation() {
+        let config = FormatConfig {
+            smart_punctuation: true,
+            ..FormatConfig::default()
+        };
+        let document = "# \"Quotes\"\n\nit's \"*so*\" -- well --- 'fine'... `a -- b` [[a--b]] \\\"x\\\"\n\n```\n\"code\"\n```\n";
+        assert_eq!(
+            format(document, &config, &[]).unwrap(),
+            "# \u{201c}Quotes\u{201d}\n\nit\u{2019}s \u{201c}*so*\u{201d} \u{2013} well \u{2014} \u{2018}fine\u{2019}\u{2026} `a -- b` [[a--b]] \\\"x\\\"\n\n```\n\"code\"\n```\n"
+        );
+    }
+
+    #[test]
+    fn test_passes() {
+        // each pass formats the output of the one before
+        let config = FormatConfig {
+            link_style: Some(LinkStyle::Reference),
+            wrap: Some(20),
+            ..FormatConfig::default()
+        };
+        let document = "see [a link](https://example.com) in a paragraph that is long\n";
+        assert_eq!(
+            format(document, &config, &[]).unwrap(),
+            "see [a link][1] in a\nparagraph that is\nlong\n\n[1]: https://example.com\n"
+        );
+
+        let config = FormatConfig {
+            wrap: Some(20),
+            smart_punctuation: true,
+            ..FormatConfig::default()
+        };
+        let document = "it's \"quoted\" -- and a paragraph that is long\n";
+        assert_eq!(
+            format(document, &config, &[]).unwrap(),
+            "it\u{2019}s \u{201c}quoted\u{201d} \u{2013} and\na paragraph that is\nlong\n"
+        );
+    }
+
+    #[test]
+    fn test_comments() {
+        let config = FormatConfig {
+            wrap: Some(20),
+            smart_punctuation: true,
+            ..FormatConfig::default()
+        };
+        let document = "a paragraph that is longer than the width\n\n%% a \"comment\" that is longer than the width %%\n";
+        let comments = [CommentSyntax::Obsidian];
+        let formatted = format(document, &config, &comments).unwrap();
+        assert!(
+            formatted.starts_with("a paragraph that is\n"),
+            "{formatted}"
+        );
+        assert!(
+            formatted.ends_with("\n\n%% a \"comment\" that is longer than the width %%\n"),
+            "{formatted}"
+        );
+        // not recognized, the comment is text
+        let formatted = format(document, &config, &[]).unwrap();
+        assert!(
+            formatted.contains("%% a \u{201c}comment\u{201d} that\n"),
+            "{formatted}"
+        );
+    }
+}
@@ -0,0 +1,521 @@
+//! Writing values into the frontmatter of an existing document.
+//!
+//! The frontmatter is edited textually, values are appended to the end of the
+//! frontmatter block so that the formatting of the rest of the document is
+//! left untouched.
+
+use color_eyre::eyre::eyre;
+use serde_json::Value;
+
+use crate::core::parser::{FrontMatterFormat, FrontMatterParser};
+use crate::result::Result;
+
+const DELIMITER: &str = "---";
+
+/// Split a document into its frontmatter block (without delimiters) and the
+/// remaining content. Returns `None` if the document has no frontmatter.
+///
+/// The frontmatter starts with a `---` line at the very start of the document
+/// and ends at the next `---` line. A `---` followed by a blank line is a
+/// horizontal rule rather than the start of a frontmatter, as is one that is
+/// never closed.
+pub(crate) fn split_frontmatter(document: &str) -> Option<(&str, &str)> {
+    let rest = document
+        .strip_prefix(DELIMITER)?
+        .strip_prefix('\n')
+        .or_else(|| document.strip_prefix("---\r\n"))?;
+    if rest
+        .lines()
+        .next()
+        .is_some_and(|line| line.trim().is_empty())
+    {
+        return None;
+    }
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == DELIMITER {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// The byte offset of the content of `document` after its frontmatter, the
+/// offsets the ast of the document is relative to
+pub fn body_offset(document: &str) -> usize {
+    match split_frontmatter(document) {
+        Some((_, body)) => document.len() - body.len(),
+        None => 0,
+    }
+}
+
+/// Insert `key: value` into the frontmatter of `document`, creating the
+/// frontmatter if the document has none. Existing keys are never overwritten,
+/// in that case the document is returned unchanged.
+pub fn insert_frontmatter_value(
+    document: &str,
+    format: FrontMatterFormat,
+    key: &str,
+    value: &Value,
+) -> Result<String> {
+    let (frontmatter, content) = split_frontmatter(document).unwrap_or(("", document));
+
+    let (data, _) = FrontMatterParser::new(format).parse(document.to_owned());
+    if data.as_ref().and_then(|d| d.get(key)).is_some() {
+        return Ok(document.to_owned());
+    }
+
+    let frontmatter = match format {
+        FrontMatterFormat::Yaml => {
+            let mut frontmatter = frontmatter.to_owned();
+            write_yaml(&mut frontmatter, key, value, 0);
+            frontmatter
+        }
+        FrontMatterFormat::Toml => {
+            let mut table = toml::Table::new();
+            table.insert(key.to_owned(), json_to_toml(value)?);
+            let mut frontmatter = frontmatter.to_owned();
+            if !frontmatter.is_empty() && !frontmatter.ends_with('\n') {
+                frontmatter.push('\n');
+            }
+            frontmatter.push_str(&toml::to_string(&table)?);
+            frontmatter
+        }
+        FrontMatterFormat::Json => {
+            let mut data = match data {
+                Some(Value::Object(map)) => map,
+                None => Default::default(),
+                Some(_) => return Err(eyre!("frontmatter is not a json object")),
+            };
+            data.insert(key.to_owned(), value.clone());
+            format!("{}\n", serde_json::to_string_pretty(&data)?)
+        }
+    };
+
+    Ok(format!("{DELIMITER}\n{frontmatter}{DELIMITER}\n{content}"))
+}
+
+/// A top level entry of a yaml or toml frontmatter block
+#[derive(Debug, Clone)]
+struct Entry<'a> {
+    /// `None` for lines that could not be attributed to a key
+    key: Option<&'a str>,
+    /// the comment and blank lines preceding the entry
+    comments: String,
+    lines: String,
+}
+
+impl Entry<'_> {
+    fn text(&self) -> String {
+        format!("{}{}", self.comments, self.lines)
+    }
+}
+
+/// Split a yaml or toml frontmatter block (without delimiters) into its top
+/// level entries. The remaining text, trailing comments and toml tables, is
+/// returned as well.
+fn split_entries(frontmatter: &str, format: FrontMatterFormat) -> (Vec<Entry<'_>>, String) {
+    let separator = match format {
+        FrontMatterFormat::Toml => '=',
+        _ => ':',
+    };
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut comments = String::new();
+    let mut lines = frontmatter.split_inclusive('\n');
+    for line in lines.by_ref() {
+        if format == FrontMatterFormat::Toml && line.starts_with('[') {
+            // keys of tables are not top level keys
+            comments.push_str(line);
+            break;
+        }
+        let starts_entry = !line.starts_with([' ', '\t', '-', '#']) && line.contains(separator);
+        if starts_entry {
+            let key = line.split(separator).next().unwrap_or_default();
+            entries.push(Entry {
+                key: Some(key.trim().trim_matches(['"', '\''])),
+                comments: std::mem::take(&mut comments),
+                lines: line.to_owned(),
+            });
+        } else if line.starts_with('#') || line.trim().is_empty() {
+            comments.push_str(line);
+        } else {
+            match entries.last_mut() {
+                Some(entry) => {
+                    entry.lines.push_str(&std::mem::take(&mut comments));
+                    entry.lines.push_str(line);
+                }
+                None => entries.push(Entry {
+                    key: None,
+                    comments: std::mem::take(&mut comments),
+                    lines: line.to_owned(),
+                }),
+            }
+        }
+    }
+    comments.extend(lines);
+    (entries, comments)
+}
+
+fn join_entries(entries: &[Entry], rest: &str) -> String {
+    let mut frontmatter: String = entries.iter().map(Entry::text).collect();
+    frontmatter.push_str(rest);
+    frontmatter
+}
+
+/// Reorder the top level keys of a yaml frontmatter block (without delimiters)
+/// so that the keys in `order` come first, in that order. Other keys keep their
+/// relative order, and comment lines stay with the key that follows them.
+/// Returns `None` if the keys are already in order.
+pub fn sort_yaml_keys(frontmatter: &str, order: &[String]) -> Option<String> {
+    let (entries, rest) = split_entries(frontmatter, FrontMatterFormat::Yaml);
+
+    let rank = |key: Option<&str>| {
+        key.and_then(|key| order.iter().position(|k| k == key))
+            .unwrap_or(order.len())
+    };
+    let mut sorted = entries.clone();
+    sorted.sort_by_key(|entry| rank(entry.key));
+    if sorted
+        .iter()
+        .map(|e| e.key)
+        .eq(entries.iter().map(|e| e.key))
+    {
+        return None;
+    }
+
+    Some(join_entries(&sorted, &rest))
+}
+
+/// Set `key` to `value` in the frontmatter of `document`, replacing the
+/// current value if there is one. Comments and the formatting of the other
+/// keys are kept, except for json frontmatter which is rewritten.
+pub fn set_frontmatter_value(
+    document: &str,
+    format: FrontMatterFormat,
+    key: &str,
+    value: &Value,
+) -> Result<String> {
+    if format == FrontMatterFormat::Json {
+        return edit_json(document, |data| {
+            data.insert(key.to_owned(), value.clone());
+            Ok(())
+        });
+    }
+    let Some((frontmatter, content)) = split_frontmatter(document) else {
+        return insert_frontmatter_value(document, format, key, value);
+    };
+    let (mut entries, mut rest) = split_entries(frontmatter, format);
+
+    let lines = match format {
+        FrontMatterFormat::Toml => {
+            let mut table = toml::Table::new();
+            table.insert(key.to_owned(), json_to_toml(value)?);
+            toml::to_string(&table)?
+        }
+        _ => {
+            let mut lines = String::new();
+            write_yaml(&mut lines, key, value, 0);
+            lines
+        }
+    };
+
+    let position = entries.iter().position(|e| e.key == Some(key));
+    if format == FrontMatterFormat::Toml && value.is_object() {
+        // a table is written after the top level keys
+        if let Some(position) = position {
+            entries.remove(position);
+        }
+        if !rest.is_empty() && !rest.ends_with('\n') {
+            rest.push('\n');
+        }
+        rest.push_str(&lines);
+    } else {
+        match position {
+            Some(position) => entries[position].lines = lines,
+            None => entries.push(Entry {
+                key: Some(key),
+                comments: String::new(),
+                lines,
+            }),
+        }
+    }
+
+    let frontmatter = join_entries(&entries, &rest);
+    Ok(format!("{DELIMITER}\n{frontmatter}{DELIMITER}\n{content}"))
+}
+
+/// Remove `key`, and the comments preceding it, from the frontmatter of
+/// `document`
+pub fn remove_frontmatter_key(
+    document: &str,
+    format: FrontMatterFormat,
+    key: &str,
+) -> Result<String> {
+    if format == FrontMatterFormat::Json {
+        return edit_json(document, |data| {
+            data.remove(key);
+            Ok(())
+        });
+    }
+    let Some((frontmatter, content)) = split_frontmatter(document) else {
+        return Ok(document.to_owned());
+    };
+    let (mut entries, rest) = split_entries(frontmatter, format);
+    if !entries.iter().any(|e| e.key == Some(key)) {
+        return Ok(document.to_owned());
+    }
+    entries.retain(|e| e.key != Some(key));
+
+    let frontmatter = join_entries(&entries, &rest);
+    Ok(format!("{DELIMITER}\n{frontmatter}{DELIMITER}\n{content}"))
+}
+
+/// Rename the top level key `from` to `to` in the frontmatter of `document`,
+/// keeping its value as written. Fails if `to` is already present.
+pub fn rename_frontmatter_key(
+    document: &str,
+    format: FrontMatterFormat,
+    from: &str,
+    to: &str,
+) -> Result<String> {
+    if format == FrontMatterFormat::Json {
+        return edit_json(document, |data| {
+            if data.contains_key(to) {
+                return Err(eyre!("frontmatter already contains {to}"));
+            }
+            if let Some(value) = data.remove(from) {
+                data.insert(to.to_owned(), value);
+            }
+            Ok(())
+        });
+    }
+    let Some((frontmatter, content)) = split_frontmatter(document) else {
+        return Ok(document.to_owned());
+    };
+    let (mut entries, rest) = split_entries(frontmatter, format);
+    if entries.iter().any(|e| e.key == Some(to)) {
+        return Err(eyre!("frontmatter already contains {to}"));
+    }
+    let Some(entry) = entries.iter_mut().find(|e| e.key == Some(from)) else {
+        return Ok(document.to_owned());
+    };
+    // the key is the first thing on the first line of the entry
+    entry.lines = entry.lines.replacen(from, to, 1);
+    entry.key = Some(to);
+
+    let frontmatter = join_entries(&entries, &rest);
+    Ok(format!("{DELIMITER}\n{frontmatter}{DELIMITER}\n{content}"))
+}
+
+/// Edit the json frontmatter of `document`, which is written back pretty
+/// printed
+fn edit_json(
+    document: &str,
+    edit: impl FnOnce(&mut serde_json::Map<String, Value>) -> Result<()>,
+) -> Result<String> {
+    let content = split_frontmatter(document).map_or(document, |(_, content)| content);
+    let (data, _) = FrontMatterParser::new(FrontMatterFormat::Json).parse(document.to_owned());
+    let mut data = match data {
+        Some(Value::Object(map)) => map,
+        None => Default::default(),
+        Some(_) => return Err(eyre!("frontmatter is not a json object")),
+    };
+    let original = data.clone();
+    edit(&mut data)?;
+    if data == original {
+        return Ok(document.to_owned());
+    }
+    Ok(format!(
+        "{DELIMITER}\n{}\n{DELIMITER}\n{content}",
+        serde_json::to_string_pretty(&data)?
+    ))
+}
+
+/// Write a value in yaml block style. Scalars are written as json, which is
+/// valid yaml.
+fn write_yaml(out: &mut String, key: &str, value: &Value, indent: usize) {
+    let pad = "  ".repeat(indent);
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            out.push_str(&format!("{pad}{key}:\n"));
+            for (k, v) in map {
+                write_yaml(out, k, v, indent + 1);
+            }
+        }
+        _ => out.push_str(&format!("{pad}{key}: {value}\n")),
+    }
+}
+
+fn json_to_toml(value: &Value) -> Result<toml::Value> {
+    Ok(match value {
+        Value::Bool(b) => toml::Value::Boolean(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => toml::Value::Integer(i),
+            None => toml::Value::Float(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => toml::Value::String(s.clone()),
+        Value::Array(values) => {
+            toml::Value::Array(values.iter().map(json_to_toml).collect::<Result<_>>()?)
+        }
+        Value::Object(map) => toml::Value::Table(
+            map.iter()
+                .map(|(k, v)| Ok((k.clone(), json_to_toml(v)?)))
+                .collect::<Result<_>>()?,
+        ),
+        Value::Null => return Err(eyre!("null can not be represented in toml")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_split_frontmatter() {
+        assert_eq!(
+            split_frontmatter("---\nid: a\n---\n\n# A\n"),
+            Some(("id: a\n", "\n# A\n"))
+        );
+        assert_eq!(split_frontmatter("---\n---\n# A\n"), Some(("", "# A\n")));
+        assert_eq!(
+            split_frontmatter("---\r\nid: a\r\n---\r\n# A"),
+            Some(("id: a\r\n", "# A"))
+        );
+        // horizontal rules near the top
+        assert_eq!(split_frontmatter("---\n\ntext\n\n---\n"), None);
+        assert_eq!(split_frontmatter("---\ntext\n"), None);
+        assert_eq!(split_frontmatter("# A\n---\nid: a\n---\n"), None);
+        assert_eq!(split_frontmatter("----\nid: a\n---\n"), None);
+    }
+
+    #[test]
+    fn test_insert_yaml() {
+        let document = "---\nid: a\ntitle: A\n---\n\n# A\n";
+        let value = json!({"hostname": "box", "cwd": "/tmp"});
+
+        let result =
+            insert_frontmatter_value(document, FrontMatterFormat::Yaml, "capture", &value).unwrap();
+
+        let (data, content) = FrontMatterParser::new(FrontMatterFormat::Yaml).parse(result);
+        let data = data.unwrap();
+        assert_eq!(data["id"], "a");
+        assert_eq!(data["capture"], value);
+        assert_eq!(content.trim(), "# A");
+    }
+
+    #[test]
+    fn test_insert_without_frontmatter() {
+        let document = "# A\n";
+        let value = json!({"cwd": "/tmp"});
+
+        let result =
+            insert_frontmatter_value(document, FrontMatterFormat::Yaml, "capture", &value).unwrap();
+
+        assert!(result.starts_with("---\ncapture:\n"));
+        assert!(result.ends_with("---\n# A\n"));
+    }
+
+    #[test]
+    fn test_insert_toml() {
+        let document = "---\nid = \"a\"\n---\n# A\n";
+        let value = json!({"cwd": "/tmp"});
+
+        let result =
+            insert_frontmatter_value(document, FrontMatterFormat::Toml, "capture", &value).unwrap();
+
+        let (data, _) = FrontMatterParser::new(FrontMatterFormat::Toml).parse(result);
+        let data = data.unwrap();
+        assert_eq!(data["id"], "a");
+        assert_eq!(data["capture"]["cwd"], "/tmp");
+    }
+
+    #[test]
+    fn test_sort_yaml_keys() {
+        let order = vec!["id".to_owned(), "title".to_owned()];
+        let frontmatter = "tags:\n  - a\n# the title\ntitle: A\nid: a\n";
+        assert_eq!(
+            sort_yaml_keys(frontmatter, &order).as_deref(),
+            Some("id: a\n# the title\ntitle: A\ntags:\n  - a\n")
+        );
+        assert_eq!(sort_yaml_keys("id: a\ntitle: A\nx: 1\n", &order), None);
+    }
+
+    #[test]
+    fn test_set_yaml() {
+        let document = "---\n# the id\nid: a\ntags:\n  - x\ntitle: A\n---\n# A\n";
+
+        let result =
+            set_frontmatter_value(document, FrontMatterFormat::Yaml, "tags", &json!(["y"]))
+                .unwrap();
+        assert_eq!(
+            result,
+            "---\n# the id\nid: a\ntags: [\"y\"]\ntitle: A\n---\n# A\n"
+        );
+
+        let result =
+            set_frontmatter_value(document, FrontMatterFormat::Yaml, "rating", &json!(5)).unwrap();
+        assert!(result.ends_with("title: A\nrating: 5\n---\n# A\n"));
+    }
+
+    #[test]
+    fn test_set_toml() {
+        let document = "---\nid = \"a\"\n[extra]\nx = 1\n---\n# A\n";
+
+        let result =
+            set_frontmatter_value(document, FrontMatterFormat::Toml, "status", &json!("read"))
+                .unwrap();
+        assert_eq!(
+            result,
+            "---\nid = \"a\"\nstatus = \"read\"\n[extra]\nx = 1\n---\n# A\n"
+        );
+    }
+
+    #[test]
+    fn test_remove_key() {
+        let document = "---\nid: a\n# the tags\ntags:\n  - x\ntitle: A\n---\n# A\n";
+
+        let result = remove_frontmatter_key(document, FrontMatterFormat::Yaml, "tags").unwrap();
+        assert_eq!(result, "---\nid: a\ntitle: A\n---\n# A\n");
+
+        let result = remove_frontmatter_key(document, FrontMatterFormat::Yaml, "other").unwrap();
+        assert_eq!(result, document);
+    }
+
+    #[test]
+    fn test_rename_key() {
+        let document = "---\nid: a\ntag:\n  - tag\n---\n# A\n";
+
+        let result =
+            rename_frontmatter_key(document, FrontMatterFormat::Yaml, "tag", "tags").unwrap();
+        assert_eq!(result, "---\nid: a\ntags:\n  - tag\n---\n# A\n");
+
+        assert!(rename_frontmatter_key(document, FrontMatterFormat::Yaml, "tag", "id").is_err());
+    }
+
+    #[test]
+    fn test_edit_json() {
+        let document = "---\n{\"id\": \"a\", \"draft\": true}\n---\n# A\n";
+
+        let result = remove_frontmatter_key(document, FrontMatterFormat::Json, "draft").unwrap();
+        let (data, _) = FrontMatterParser::new(FrontMatterFormat::Json).parse(result);
+        assert_eq!(data.unwrap(), json!({"id": "a"}));
+    }
+
+    #[test]
+    fn test_existing_key_is_kept() {
+        let document = "---\ncapture: manual\n---\n# A\n";
+
+        let result = insert_frontmatter_value(
+            document,
+            FrontMatterFormat::Yaml,
+            "capture",
+            &json!({"cwd": "/tmp"}),
+        )
+        .unwrap();
+
+        assert_eq!(result, document);
+    }
+}
@@ -0,0 +1,127 @@
+//! Fuzzy matching of the names of documents, in the style of skim and fzf.
+//!
+//! A pattern matches a name if its characters appear in the name in order,
+//! ignoring case. Among the ways a pattern can match, the best scoring one is
+//! used: every matched character scores, more so at the start of a word or
+//! following the previous match, and every character skipped between two
+//! matches costs.
+
+use crate::core::types::lookup::DocumentLookup;
+
+const SCORE_MATCH: i64 = 16;
+const BONUS_WORD_START: i64 = 10;
+const BONUS_FIRST_CHAR: i64 = 8;
+const BONUS_CONSECUTIVE: i64 = 8;
+const PENALTY_GAP: i64 = 1;
+/// the most characters before the first match are penalized for
+const MAX_LEADING_GAP: i64 = 10;
+
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// The bonus of matching `chars[i]`
+fn bonus(chars: &[char], i: usize) -> i64 {
+    let Some(previous) = i.checked_sub(1).map(|p| chars[p]) else {
+        return BONUS_WORD_START + BONUS_FIRST_CHAR;
+    };
+    let current = chars[i];
+    if (!previous.is_alphanumeric() && current.is_alphanumeric())
+        || (previous.is_lowercase() && current.is_uppercase())
+    {
+        BONUS_WORD_START
+    } else {
+        0
+    }
+}
+
+/// The score of the best match of `pattern` in `candidate`, `None` if it does
+/// not match
+pub fn fuzzy_score(pattern: &str, candidate: &str) -> Option<i64> {
+    let pattern: Vec<char> = pattern
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(lowercase)
+        .collect();
+    if pattern.is_empty() {
+        return Some(0);
+    }
+    let chars: Vec<char> = candidate.chars().collect();
+    let lower: Vec<char> = chars.iter().copied().map(lowercase).collect();
+    let bonuses: Vec<i64> = (0..chars.len()).map(|i| bonus(&chars, i)).collect();
+
+    // scores[j], the best score of matching the pattern so far with its last
+    // character matched at j
+    let mut scores: Vec<Option<i64>> = (0..chars.len())
+        .map(|j| {
+            (lower[j] == pattern[0])
+                .then(|| SCORE_MATCH + bonuses[j] - (j as i64).min(MAX_LEADING_GAP) * PENALTY_GAP)
+        })
+        .collect();
+
+    for &p in &pattern[1..] {
+        let mut next = vec![None; chars.len()];
+        // the best of scores[k] + k * PENALTY_GAP for the k before j - 1, a
+        // match at k followed by a gap up to j
+        let mut best_gapped: Option<i64> = None;
+        for j in 1..chars.len() {
+            if j >= 2
+                && let Some(score) = scores[j - 2]
+            {
+                let gapped = score + (j as i64 - 2) * PENALTY_GAP;
+                best_gapped = Some(best_gapped.map_or(gapped, |best| best.max(gapped)));
+            }
+            if lower[j] != p {
+                continue;
+            }
+            let consecutive = scores[j - 1].map(|score| score + BONUS_CONSECUTIVE);
+            let gapped = best_gapped.map(|best| best - (j as i64 - 1) * PENALTY_GAP);
+            next[j] = consecutive
+                .into_iter()
+                .chain(gapped)
+                .max()
+                .map(|score| score + SCORE_MATCH + bonuses[j]);
+        }
+        scores = next;
+    }
+    scores.into_iter().flatten().max()
+}
+
+/// The names matching `pattern`, best first. Names scoring the same are
+/// ordered shortest first.
+pub fn rank<'a>(pattern: &str, lookups: &'a [DocumentLookup]) -> Vec<(i64, &'a DocumentLookup)> {
+    let mut matches: Vec<(i64, &DocumentLookup)> = lookups
+        .iter()
+        .filter_map(|lookup| Some((fuzzy_score(pattern, &lookup.text)?, lookup)))
+        .collect();
+    matches.sort_by(|(a_score, a), (b_score, b)| {
+        b_score
+            .cmp(a_score)
+            .then(a.text.chars().count().cmp(&b.text.chars().count()))
+            .then(a.kind.cmp(&b.kind))
+            .then(a.document_id.cmp(&b.document_id))
+    });
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("xyz", "abc"), None);
+        assert_eq!(fuzzy_score("ba", "ab"), None);
+        assert_eq!(fuzzy_score("", "abc"), Some(0));
+        assert!(fuzzy_score("ABC", "abc").is_some());
+
+        // consecutive matches beat scattered ones
+        assert!(fuzzy_score("rust", "rust book") > fuzzy_score("rust", "read until start"));
+        // word starts beat the middle of words
+        assert!(fuzzy_score("ml", "machine learning") > fuzzy_score("ml", "html"));
+        assert!(fuzzy_score("ps", "ProjectSetup") > fuzzy_score("ps", "apps"));
+        // the best match is found, not the first: the word "ab" rather than the
+        // first "a"
+        assert_eq!(fuzzy_score("ab", "xaxx ab"), fuzzy_score("ab", "xxxx ab"));
+    }
+}
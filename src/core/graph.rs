@@ -0,0 +1,428 @@
+use std::collections::HashMap;
+
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+use sql_minifier::macros::minify_sql as sql;
+
+use crate::core::db::{DbInsert, DbList};
+use crate::core::query::DocumentQuery;
+use crate::core::types::document::{DocumentId, DocumentPath};
+use crate::result::Result;
+
+const PAGERANK_DAMPING: f64 = 0.85;
+const PAGERANK_MAX_ITERATIONS: usize = 100;
+const PAGERANK_TOLERANCE: f64 = 1e-9;
+
+/// The resolved link graph of the collection. Nodes are documents and edges
+/// are links whose target could be resolved to a document.
+#[derive(Debug, Default)]
+pub struct LinkGraph {
+    pub nodes: Vec<DocumentId>,
+    /// (from, to) indices into `nodes`
+    pub edges: Vec<(usize, usize)>,
+}
+
+/// Computed centrality measures for a single document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphStats {
+    pub document_id: DocumentId,
+    pub in_degree: usize,
+    pub out_degree: usize,
+    pub pagerank: f64,
+    pub component: usize,
+}
+
+impl LinkGraph {
+    /// Load every document and every resolved link from the database
+    pub fn load(db: &Connection) -> Result<LinkGraph> {
+        let nodes: Vec<DocumentId> = db
+            .prepare(sql!("select id from document order by id"))?
+            .query_map([], |r| r.get(0))?
+            .map(|f| f.map_err(From::from))
+            .collect::<Result<Vec<DocumentId>>>()?;
+
+        let index: HashMap<&DocumentId, usize> =
+            nodes.iter().enumerate().map(|(i, id)| (id, i)).collect();
+
+        let links: Vec<(DocumentId, DocumentId)> = db
+            .prepare(sql!(
+                r#"
+                select
+                    from_id,
+                    to_id
+                from
+                    document_link
+                where
+                    to_id is not null
+                "#
+            ))?
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?
+            .map(|f| f.map_err(From::from))
+            .collect::<Result<Vec<(DocumentId, DocumentId)>>>()?;
+
+        let edges = links
+            .iter()
+            .filter_map(|(from, to)| Some((*index.get(from)?, *index.get(to)?)))
+            .collect();
+
+        Ok(LinkGraph { nodes, edges })
+    }
+
+    pub fn in_degrees(&self) -> Vec<usize> {
+        let mut degrees = vec![0; self.nodes.len()];
+        for (_, to) in &self.edges {
+            degrees[*to] += 1;
+        }
+        degrees
+    }
+
+    pub fn out_degrees(&self) -> Vec<usize> {
+        let mut degrees = vec![0; self.nodes.len()];
+        for (from, _) in &self.edges {
+            degrees[*from] += 1;
+        }
+        degrees
+    }
+
+    /// PageRank over the directed link graph. Documents without outgoing links
+    /// distribute their rank evenly over the entire collection.
+    pub fn pagerank(&self) -> Vec<f64> {
+        let n = self.nodes.len();
+        if n == 0 {
+            return Vec::new();
+        }
+        let out_degrees = self.out_degrees();
+        let base = (1.0 - PAGERANK_DAMPING) / n as f64;
+
+        let mut rank = vec![1.0 / n as f64; n];
+        for _ in 0..PAGERANK_MAX_ITERATIONS {
+            let dangling: f64 = (0..n)
+                .filter(|i| out_degrees[*i] == 0)
+                .map(|i| rank[i])
+                .sum();
+
+            let mut next = vec![base + PAGERANK_DAMPING * dangling / n as f64; n];
+            for (from, to) in &self.edges {
+                next[*to] += PAGERANK_DAMPING * rank[*from] / out_degrees[*from] as f64;
+            }
+
+            let delta: f64 = rank.iter().zip(&next).map(|(a, b)| (a - b).abs()).sum();
+            rank = next;
+            if delta < PAGERANK_TOLERANCE {
+                break;
+            }
+        }
+        rank
+    }
+
+    /// Weakly connected components, i.e. link direction is ignored. Returns the
+    /// component index of every node. Components are numbered by size, largest
+    /// first.
+    pub fn connected_components(&self) -> Vec<usize> {
+        let n = self.nodes.len();
+        let mut parent: Vec<usize> = (0..n).collect();
+
+        fn find(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+
+        for (from, to) in &self.edges {
+            let a = find(&mut parent, *from);
+            let b = find(&mut parent, *to);
+            if a != b {
+                parent[a] = b;
+            }
+        }
+
+        let roots: Vec<usize> = (0..n).map(|i| find(&mut parent, i)).collect();
+
+        let mut sizes: HashMap<usize, usize> = HashMap::new();
+        for root in &roots {
+            *sizes.entry(*root).or_default() += 1;
+        }
+        // order by size (desc), then by first occurrence to keep output stable
+        let mut order: Vec<usize> = Vec::new();
+        let mut seen = vec![false; n];
+        for root in &roots {
+            if !seen[*root] {
+                seen[*root] = true;
+                order.push(*root);
+            }
+        }
+        order.sort_by_key(|root| std::cmp::Reverse(sizes[root]));
+        let numbering: HashMap<usize, usize> =
+            order.into_iter().enumerate().map(|(i, r)| (r, i)).collect();
+
+        roots.iter().map(|root| numbering[root]).collect()
+    }
+
+    /// Compute all statistics for every document in the graph
+    pub fn stats(&self) -> Vec<GraphStats> {
+        let in_degrees = self.in_degrees();
+        let out_degrees = self.out_degrees();
+        let pagerank = self.pagerank();
+        let components = self.connected_components();
+
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(i, id)| GraphStats {
+                document_id: id.clone(),
+                in_degree: in_degrees[i],
+                out_degree: out_degrees[i],
+                pagerank: pagerank[i],
+                component: components[i],
+            })
+            .collect()
+    }
+}
+
+/// The neighborhood of a single document, suitable for rendering a "local
+/// graph" in an editor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalGraph {
+    pub center: DocumentId,
+    pub nodes: Vec<LocalGraphNode>,
+    pub edges: Vec<LocalGraphEdge>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalGraphNode {
+    pub id: DocumentId,
+    pub title: String,
+    pub path: DocumentPath,
+    /// number of hops from the center document
+    pub depth: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalGraphEdge {
+    pub from: DocumentId,
+    pub to: DocumentId,
+}
+
+impl LinkGraph {
+    /// Breadth first search from `center`, following links in both directions.
+    /// Returns (node index, depth) pairs for every node within `hops` hops.
+    pub fn neighborhood(&self, center: &DocumentId, hops: usize) -> Vec<(usize, usize)> {
+        let Some(start) = self.nodes.iter().position(|id| id == center) else {
+            return Vec::new();
+        };
+
+        let mut depth: Vec<Option<usize>> = vec![None; self.nodes.len()];
+        depth[start] = Some(0);
+
+        // the frontier of a hop is the nodes found the hop before
+        for d in 1..=hops {
+            let mut found = false;
+            for (from, to) in &self.edges {
+                for (a, b) in [(*from, *to), (*to, *from)] {
+                    if depth[a] == Some(d - 1) && depth[b].is_none() {
+                        depth[b] = Some(d);
+                        found = true;
+                    }
+                }
+            }
+            if !found {
+                break;
+            }
+        }
+
+        depth
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, d)| d.map(|d| (i, d)))
+            .collect()
+    }
+}
+
+impl LocalGraph {
+    /// Load the `hops` neighborhood of `center` from the database
+    pub fn load(db: &Connection, center: &DocumentId, hops: usize) -> Result<LocalGraph> {
+        Self::from_graph(db, &LinkGraph::load(db)?, center, hops)
+    }
+
+    /// The `hops` neighborhood of `center` in `graph`, the titles and paths of
+    /// its documents being read from the database
+    pub fn from_graph(
+        db: &Connection,
+        graph: &LinkGraph,
+        center: &DocumentId,
+        hops: usize,
+    ) -> Result<LocalGraph> {
+        let neighborhood = graph.neighborhood(center, hops);
+
+        let depths: HashMap<usize, usize> = neighborhood.iter().copied().collect();
+        let indices: HashMap<&DocumentId, usize> = neighborhood
+            .iter()
+            .map(|(i, _)| (&graph.nodes[*i], *i))
+            .collect();
+        let ids: Vec<String> = neighborhood
+            .iter()
+            .map(|(i, _)| graph.nodes[*i].0.clone())
+            .collect();
+
+        let nodes = if ids.is_empty() {
+            Vec::new()
+        } else {
+            DocumentQuery::new()
+                .with_ids(ids)
+                .execute(db)?
+                .into_iter()
+                .filter_map(|d| {
+                    let i = *indices.get(&d.id)?;
+                    Some(LocalGraphNode {
+                        id: d.id,
+                        title: d.title,
+                        path: d.path,
+                        depth: depths[&i],
+                    })
+                })
+                .collect()
+        };
+
+        let edges = graph
+            .edges
+            .iter()
+            .filter(|(from, to)| depths.contains_key(from) && depths.contains_key(to))
+            .map(|(from, to)| LocalGraphEdge {
+                from: graph.nodes[*from].clone(),
+                to: graph.nodes[*to].clone(),
+            })
+            .collect();
+
+        Ok(LocalGraph {
+            center: center.clone(),
+            nodes,
+            edges,
+        })
+    }
+}
+
+////////////////////////////////////////////////////////////
+// Crud trait implementations
+////////////////////////////////////////////////////////////
+
+impl DbList<GraphStats> for GraphStats {
+    fn list(db: &Connection) -> Result<Vec<GraphStats>> {
+        db.prepare(sql!(
+            r#"
+            select
+                document_id,
+                in_degree,
+                out_degree,
+                pagerank,
+                component
+            from
+                document_graph_stats
+            order by
+                pagerank desc
+            "#
+        ))?
+        .query_map([], |r| {
+            Ok(GraphStats {
+                document_id: r.get(0)?,
+                in_degree: r.get(1)?,
+                out_degree: r.get(2)?,
+                pagerank: r.get(3)?,
+                component: r.get(4)?,
+            })
+        })?
+        .map(|f| f.map_err(From::from))
+        .collect::<Result<Vec<GraphStats>>>()
+    }
+}
+
+/// Replaces the cached graph statistics with `values`
+impl DbInsert<GraphStats, ()> for GraphStats {
+    fn insert(db: &mut Connection, values: &[GraphStats]) -> Result<Vec<()>> {
+        let tx = db.savepoint()?;
+        {
+            tx.execute(sql!("delete from document_graph_stats"), [])?;
+            let mut query = tx.prepare(sql!(
+                r#"
+                insert into document_graph_stats (
+                    document_id,
+                    in_degree,
+                    out_degree,
+                    pagerank,
+                    component
+                ) values (
+                    ?1,
+                    ?2,
+                    ?3,
+                    ?4,
+                    ?5
+                )
+                "#
+            ))?;
+            for s in values {
+                query.execute(params![
+                    s.document_id,
+                    s.in_degree,
+                    s.out_degree,
+                    s.pagerank,
+                    s.component
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(vec![(); values.len()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(n: usize, edges: &[(usize, usize)]) -> LinkGraph {
+        LinkGraph {
+            nodes: (0..n).map(|i| DocumentId(format!("n{i}"))).collect(),
+            edges: edges.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_degrees() {
+        let g = graph(3, &[(0, 1), (0, 2), (1, 2)]);
+        assert_eq!(g.in_degrees(), vec![0, 1, 2]);
+        assert_eq!(g.out_degrees(), vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn test_pagerank_sums_to_one() {
+        let g = graph(4, &[(0, 1), (0, 2), (1, 2), (3, 0)]);
+        let rank = g.pagerank();
+        let total: f64 = rank.iter().sum();
+        assert!((total - 1.0).abs() < 1e-6);
+        // the most linked to node should have the highest rank
+        let best = (0..4).max_by(|a, b| rank[*a].total_cmp(&rank[*b])).unwrap();
+        assert_eq!(best, 2);
+    }
+
+    #[test]
+    fn test_neighborhood() {
+        // 0 - 1 - 2 - 3, 4 is isolated
+        let g = graph(5, &[(0, 1), (2, 1), (2, 3)]);
+        let mut one_hop = g.neighborhood(&DocumentId("n1".into()), 1);
+        one_hop.sort();
+        assert_eq!(one_hop, vec![(0, 1), (1, 0), (2, 1)]);
+
+        let mut two_hops = g.neighborhood(&DocumentId("n0".into()), 2);
+        two_hops.sort();
+        assert_eq!(two_hops, vec![(0, 0), (1, 1), (2, 2)]);
+
+        assert!(g.neighborhood(&DocumentId("missing".into()), 2).is_empty());
+    }
+
+    #[test]
+    fn test_connected_components() {
+        let g = graph(5, &[(0, 1), (2, 1), (3, 4)]);
+        let components = g.connected_components();
+        assert_eq!(components, vec![0, 0, 0, 1, 1]);
+    }
+}
@@ -0,0 +1,126 @@
+//! External commands run on lifecycle events, such as regenerating a website
+//! once the collection has been indexed. A hook receives the event as json on
+//! its stdin and runs in the collection root.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use color_eyre::eyre::eyre;
+use serde::{Deserialize, Serialize};
+
+use crate::core::types::document::DocumentId;
+use crate::result::Result;
+
+/// The `[hooks]` section of the configuration, each hook being a program
+/// and its arguments
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// run after a note has been created
+    pub on_create: Option<Vec<String>>,
+    /// run after an index that changed the collection
+    pub on_index: Option<Vec<String>>,
+    /// run after a note has been renamed
+    pub on_rename: Option<Vec<String>>,
+}
+
+/// The payload of a hook
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum HookEvent {
+    Create {
+        id: DocumentId,
+        title: String,
+        path: PathBuf,
+    },
+    Index {
+        new: Vec<PathBuf>,
+        updated: Vec<DocumentId>,
+        removed: Vec<DocumentId>,
+    },
+    Rename {
+        old_id: DocumentId,
+        new_id: DocumentId,
+        from: PathBuf,
+        to: PathBuf,
+    },
+}
+
+impl HookEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookEvent::Create { .. } => "on_create",
+            HookEvent::Index { .. } => "on_index",
+            HookEvent::Rename { .. } => "on_rename",
+        }
+    }
+}
+
+impl HooksConfig {
+    fn command(&self, event: &HookEvent) -> Option<&[String]> {
+        match event {
+            HookEvent::Create { .. } => self.on_create.as_deref(),
+            HookEvent::Index { .. } => self.on_index.as_deref(),
+            HookEvent::Rename { .. } => self.on_rename.as_deref(),
+        }
+    }
+
+    /// Run the hook of `event`, if one is configured. The action the event
+    /// reports has already happened, a failing hook is logged rather than
+    /// returned as an error.
+    pub fn run(&self, root: &Path, event: &HookEvent) {
+        let Some(command) = self.command(event) else {
+            return;
+        };
+        if let Err(e) = run_hook(root, command, event) {
+            log::warn!("the {} hook failed: {}", event.name(), e);
+        }
+    }
+}
+
+fn run_hook(root: &Path, command: &[String], event: &HookEvent) -> Result<()> {
+    let Some((program, args)) = command.split_first() else {
+        return Err(eyre!("empty command"));
+    };
+    let payload = serde_json::to_string(event)?;
+    log::debug!("running the {} hook {:?}", event.name(), command);
+
+    let mut child = Command::new(program)
+        .args(args)
+        .current_dir(root)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| eyre!("could not run {:?}: {}", program, e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // a hook that does not read its payload closes the pipe early
+        if let Err(e) = stdin.write_all(payload.as_bytes())
+            && e.kind() != std::io::ErrorKind::BrokenPipe
+        {
+            return Err(e.into());
+        }
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(eyre!("{:?} failed with {}", program, status));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload() {
+        let event = HookEvent::Create {
+            id: DocumentId("notes/a".to_owned()),
+            title: "A".to_owned(),
+            path: PathBuf::from("/notes/a.md"),
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"event":"create","id":"notes/a","title":"A","path":"/notes/a.md"}"#
+        );
+    }
+}
@@ -0,0 +1,581 @@
+//! Rendering documents as html pages, with the links between documents
+//! pointing to their pages.
+
+use std::collections::HashMap;
+use std::process::Command;
+
+use color_eyre::eyre::eyre;
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::html::{
+    ClassStyle, ClassedHTMLGenerator, css_for_theme_with_class_style, highlighted_html_for_string,
+};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+use crate::core::backlinks::LinkedMention;
+use crate::core::parser::DocumentParserOptions;
+use crate::core::parser::ast_nodes::DiagramKind;
+use crate::core::sequence::Neighbours;
+use crate::core::slug::HeadingAnchors;
+use crate::core::types::document::DocumentSummary;
+use crate::result::Result;
+
+/// The `[html]` section of the configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HtmlConfig {
+    /// render the math of the pages with KaTeX when they are opened
+    pub math: bool,
+    /// where `katex.min.js` and `katex.min.css` are loaded from
+    pub katex_url: String,
+    /// highlight fenced code blocks by the language in their info string
+    pub highlight: bool,
+    /// the syntect theme code is highlighted with, e.g. `base16-ocean.dark`
+    pub highlight_theme: String,
+    /// mark up code with css classes instead of the colors of the theme, for
+    /// pages styled by their own stylesheet. The theme is included as the
+    /// default stylesheet.
+    pub highlight_classes: bool,
+    /// where mermaid is loaded from, to render mermaid diagrams when the
+    /// pages are opened
+    pub mermaid_url: String,
+    /// commands rendering diagrams as svg, by kind of diagram (`mermaid` or
+    /// `dot`). `{input}` and `{output}` are replaced by the file of the
+    /// diagram and the svg file to write, e.g. `["dot", "-Tsvg", "{input}",
+    /// "-o", "{output}"]`. Diagrams without a command are rendered when the
+    /// pages are opened.
+    pub diagram_commands: HashMap<String, Vec<String>>,
+    /// render straight quotes, `--`, `---` and `...` as typographic quotes,
+    /// dashes and ellipses
+    pub smart_punctuation: bool,
+}
+
+impl Default for HtmlConfig {
+    fn default() -> Self {
+        Self {
+            math: true,
+            katex_url: "https://cdn.jsdelivr.net/npm/katex@0.16.11/dist".to_owned(),
+            highlight: true,
+            highlight_theme: "InspiredGitHub".to_owned(),
+            highlight_classes: false,
+            mermaid_url: "https://cdn.jsdelivr.net/npm/mermaid@11/dist/mermaid.esm.min.mjs"
+                .to_owned(),
+            diagram_commands: HashMap::new(),
+            smart_punctuation: false,
+        }
+    }
+}
+
+/// Renders the `math-inline` and `math-display` spans written by
+/// [`render_markdown`] once KaTeX has loaded
+const KATEX_SHIM: &str = r#"<script>
+document.addEventListener("DOMContentLoaded", function () {
+  for (const element of document.querySelectorAll(".math")) {
+    katex.render(element.textContent, element, {
+      displayMode: element.classList.contains("math-display"),
+      throwOnError: false,
+    });
+  }
+});
+</script>
+"#;
+
+/// The wrapper of the mermaid diagrams rendered when a page is opened
+const MERMAID_CLASS: &str = "<pre class=\"mermaid\">";
+
+impl HtmlConfig {
+    /// The elements to add to the head of a page, given its rendered
+    /// `content`
+    pub fn head(&self, highlighter: Option<&Highlighter>, content: &str) -> String {
+        let mut head = String::new();
+        if let Some(css) = highlighter.and_then(|h| h.stylesheet.as_ref()) {
+            head.push_str(&format!("<style>\n{css}</style>\n"));
+        }
+        if self.math {
+            let url = escape_html(self.katex_url.trim_end_matches('/'));
+            head.push_str(&format!(
+                "<link rel=\"stylesheet\" href=\"{url}/katex.min.css\">\n<script defer src=\"{url}/katex.min.js\"></script>\n"
+            ));
+            head.push_str(KATEX_SHIM);
+        }
+        if content.contains(MERMAID_CLASS) {
+            head.push_str(&format!(
+                "<script type=\"module\">\nimport mermaid from \"{}\";\nmermaid.initialize({{ startOnLoad: true }});\n</script>\n",
+                escape_html(&self.mermaid_url)
+            ));
+        }
+        head
+    }
+}
+
+/// Highlights the code of fenced code blocks
+pub struct Highlighter {
+    syntaxes: SyntaxSet,
+    theme: Theme,
+    /// the stylesheet of the theme, when code is marked up with classes
+    stylesheet: Option<String>,
+}
+
+impl Highlighter {
+    /// The highlighter configured by `config`, `None` if highlighting is off
+    pub fn new(config: &HtmlConfig) -> Result<Option<Highlighter>> {
+        if !config.highlight {
+            return Ok(None);
+        }
+        let mut themes = ThemeSet::load_defaults();
+        let Some(theme) = themes.themes.remove(&config.highlight_theme) else {
+            let mut names: Vec<&String> = themes.themes.keys().collect();
+            names.sort();
+            return Err(eyre!(
+                "unknown highlight theme {:?}, expected one of {}",
+                config.highlight_theme,
+                names
+                    .iter()
+                    .map(|n| n.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        };
+        let stylesheet = match config.highlight_classes {
+            true => Some(css_for_theme_with_class_style(&theme, ClassStyle::Spaced)?),
+            false => None,
+        };
+        Ok(Some(Highlighter {
+            syntaxes: SyntaxSet::load_defaults_newlines(),
+            theme,
+            stylesheet,
+        }))
+    }
+
+    /// Highlight `code` by the language named first in `tag`, the info string
+    /// of its code block. Returns `None` for unknown languages.
+    pub fn highlight(&self, tag: &str, code: &str) -> Option<String> {
+        let language = tag.split([' ', ',', '{']).next()?;
+        let syntax = self.syntaxes.find_syntax_by_token(language)?;
+        if self.stylesheet.is_none() {
+            return highlighted_html_for_string(code, &self.syntaxes, syntax, &self.theme).ok();
+        }
+        let mut generator =
+            ClassedHTMLGenerator::new_with_class_style(syntax, &self.syntaxes, ClassStyle::Spaced);
+        for line in LinesWithEndings::from(code) {
+            generator
+                .parse_html_for_line_which_includes_newline(line)
+                .ok()?;
+        }
+        Some(format!(
+            "<pre class=\"code\"><code class=\"language-{}\">{}</code></pre>\n",
+            escape_html(language),
+            generator.finalize()
+        ))
+    }
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The destination of a link or an image, as written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Destination<'a> {
+    Link(&'a str),
+    Image(&'a str),
+}
+
+/// Render markdown as html. Every destination is passed to `resolve`, which
+/// returns the url to use instead, if any. Fenced code blocks are highlighted
+/// by `highlighter`, if given, and diagrams rendered as configured.
+pub fn render_markdown(
+    markdown: &str,
+    config: &HtmlConfig,
+    highlighter: Option<&Highlighter>,
+    resolve: impl Fn(Destination) -> Option<String>,
+) -> String {
+    let mut events = Vec::new();
+    // the tag and code of the fenced code block being read
+    let mut code_block: Option<(CowStr, String)> = None;
+    // the index of the start of the heading being read and its text
+    let mut heading: Option<(usize, String)> = None;
+    let mut anchors = HeadingAnchors::default();
+    let mut options = DocumentParserOptions::default().0;
+    if config.smart_punctuation {
+        options.insert(Options::ENABLE_SMART_PUNCTUATION);
+    }
+    for event in Parser::new_ext(markdown, options) {
+        match &event {
+            Event::Start(Tag::Heading { .. }) => heading = Some((events.len(), String::new())),
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, content)) = &mut heading {
+                    content.push_str(text);
+                }
+            }
+            // headings are anchored by their `{#id}`, or the slug of their text
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some((start, content)) = heading.take()
+                    && let Some(Event::Start(Tag::Heading { id, .. })) = events.get_mut(start)
+                {
+                    let anchor = anchors.anchor(id.as_deref(), &content);
+                    *id = Some(anchor.into());
+                }
+            }
+            _ => {}
+        }
+        if let Some((_, code)) = &mut code_block {
+            match event {
+                Event::Text(text) => code.push_str(&text),
+                Event::End(TagEnd::CodeBlock) => {
+                    let Some((tag, code)) = code_block.take() else {
+                        continue;
+                    };
+                    let html = match DiagramKind::from_tag(&tag) {
+                        Some(kind) => Some(render_diagram(config, kind, &code)),
+                        None => highlighter.and_then(|h| h.highlight(&tag, &code)),
+                    };
+                    match html {
+                        Some(html) => events.push(Event::Html(html.into())),
+                        None => events.extend([
+                            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(tag))),
+                            Event::Text(code.into()),
+                            Event::End(TagEnd::CodeBlock),
+                        ]),
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+        events.push(match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(tag)))
+                if highlighter.is_some() || DiagramKind::from_tag(&tag).is_some() =>
+            {
+                code_block = Some((tag, String::new()));
+                continue;
+            }
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => Event::Start(Tag::Link {
+                link_type,
+                dest_url: resolve(Destination::Link(&dest_url))
+                    .map(CowStr::from)
+                    .unwrap_or(dest_url),
+                title,
+                id,
+            }),
+            Event::Start(Tag::Image {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => Event::Start(Tag::Image {
+                link_type,
+                dest_url: resolve(Destination::Image(&dest_url))
+                    .map(CowStr::from)
+                    .unwrap_or(dest_url),
+                title,
+                id,
+            }),
+            event => event,
+        });
+    }
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, events.into_iter());
+    html
+}
+
+/// Render a diagram as svg with the command configured for its kind, or wrap
+/// its source to be rendered when the page is opened. Mermaid diagrams are
+/// rendered by the script added by [`HtmlConfig::head`], graphviz diagrams
+/// are left to a script of the reader's choice.
+fn render_diagram(config: &HtmlConfig, kind: DiagramKind, source: &str) -> String {
+    if let Some(command) = config.diagram_commands.get(kind.as_str()) {
+        match run_diagram_command(command, kind, source) {
+            Ok(svg) => return format!("<figure class=\"diagram {kind}\">\n{svg}\n</figure>\n"),
+            Err(e) => log::warn!("could not render {kind} diagram: {e}"),
+        }
+    }
+    match kind {
+        DiagramKind::Mermaid => format!("{MERMAID_CLASS}{}</pre>\n", escape_html(source)),
+        DiagramKind::Dot => format!("<pre class=\"graphviz\">{}</pre>\n", escape_html(source)),
+    }
+}
+
+fn run_diagram_command(command: &[String], kind: DiagramKind, source: &str) -> Result<String> {
+    let Some((program, args)) = command.split_first() else {
+        return Err(eyre!("empty command"));
+    };
+    let name = format!("zet-diagram-{}", uuid::Uuid::new_v4());
+    let input = std::env::temp_dir().join(format!("{name}.{kind}"));
+    let output = std::env::temp_dir().join(format!("{name}.svg"));
+    std::fs::write(&input, source)?;
+    let replace = |arg: &String| {
+        arg.replace("{input}", &input.to_string_lossy())
+            .replace("{output}", &output.to_string_lossy())
+    };
+    let status = Command::new(program)
+        .args(args.iter().map(replace))
+        .status();
+    let svg = std::fs::read_to_string(&output);
+    std::fs::remove_file(&input)?;
+    let _ = std::fs::remove_file(&output);
+
+    let status = status.map_err(|e| eyre!("could not run {:?}: {}", program, e))?;
+    if !status.success() {
+        return Err(eyre!("{:?} failed with {}", program, status));
+    }
+    // the svg is inlined, without the xml declaration and doctype
+    let svg = svg?;
+    match svg.find("<svg") {
+        Some(start) => Ok(svg[start..].trim_end().to_owned()),
+        None => Err(eyre!("{:?} did not write an svg", program)),
+    }
+}
+
+/// The links to the notes before and after a note in its folgezettel
+/// sequence and to the notes branching off it
+fn render_sequence(
+    sequence: &Neighbours,
+    resolve: impl Fn(Destination) -> Option<String>,
+) -> String {
+    let link = |document: &DocumentSummary| {
+        let href = resolve(Destination::Link(&document.id.0)).unwrap_or_default();
+        format!(
+            "<a href=\"{}\">{}</a>",
+            escape_html(&href),
+            escape_html(&document.title)
+        )
+    };
+    let mut nav = String::from("<nav class=\"sequence\">\n");
+    if let Some(prev) = &sequence.prev {
+        nav.push_str(&format!("<span class=\"prev\">{}</span>\n", link(prev)));
+    }
+    if let Some(next) = &sequence.next {
+        nav.push_str(&format!("<span class=\"next\">{}</span>\n", link(next)));
+    }
+    if !sequence.children.is_empty() {
+        nav.push_str("<ul class=\"children\">\n");
+        for child in &sequence.children {
+            nav.push_str(&format!("<li>{}</li>\n", link(child)));
+        }
+        nav.push_str("</ul>\n");
+    }
+    nav.push_str("</nav>\n");
+    nav
+}
+
+/// Render a document as a standalone page, followed by its neighbours in its
+/// sequence and the blocks of the other documents linking to it
+pub fn render_page(
+    config: &HtmlConfig,
+    highlighter: Option<&Highlighter>,
+    title: &str,
+    body: &str,
+    mentions: &[LinkedMention],
+    sequence: Option<&Neighbours>,
+    resolve: impl Fn(Destination) -> Option<String>,
+) -> String {
+    let mut content = format!(
+        "<article>\n{}</article>\n",
+        render_markdown(body, config, highlighter, &resolve)
+    );
+    if let Some(sequence) = sequence {
+        content.push_str(&render_sequence(sequence, &resolve));
+    }
+    if !mentions.is_empty() {
+        content.push_str("<section class=\"linked-mentions\">\n<h2>Linked mentions</h2>\n<ul>\n");
+        for mention in mentions {
+            let href = resolve(Destination::Link(&mention.document_id.0)).unwrap_or_default();
+            content.push_str(&format!(
+                "<li>\n<a href=\"{}\">{}</a>\n<blockquote>\n{}</blockquote>\n</li>\n",
+                escape_html(&href),
+                escape_html(&mention.title),
+                render_markdown(&mention.context, config, highlighter, &resolve)
+            ));
+        }
+        content.push_str("</ul>\n</section>\n");
+    }
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n{}</head>\n<body>\n{content}</body>\n</html>\n",
+        escape_html(title),
+        config.head(highlighter, &content),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::document::{
+        CreatedTimestamp, DocumentId, DocumentPath, ModifiedTimestamp,
+    };
+
+    #[test]
+    fn test_render_page() {
+        let resolve = |destination: Destination| {
+            (destination == Destination::Link("b")).then(|| "b.html".to_owned())
+        };
+        let mentions = [LinkedMention {
+            document_id: DocumentId("b".to_owned()),
+            title: "B & co".to_owned(),
+            context: "see [[a]]".to_owned(),
+            block: None,
+        }];
+        let config = HtmlConfig::default();
+        let html = render_page(
+            &config,
+            None,
+            "A",
+            "# A\n\nlinks to [[b]]\n",
+            &mentions,
+            None,
+            resolve,
+        );
+        assert!(html.contains("<title>A</title>"));
+        assert!(html.contains("links to <a href=\"b.html\">b</a>"));
+        assert!(html.contains("<h2>Linked mentions</h2>"));
+        assert!(html.contains("<a href=\"b.html\">B &amp; co</a>"));
+        assert!(html.contains("<p>see <a href=\"a\">a</a></p>"));
+
+        let html = render_page(&config, None, "A", "# A\n", &[], None, resolve);
+        assert!(!html.contains("Linked mentions"));
+
+        let summary = |id: &str| DocumentSummary {
+            id: DocumentId(id.to_owned()),
+            title: id.to_uppercase(),
+            path: DocumentPath(format!("{id}.md").into()),
+            hash: 0,
+            modified: ModifiedTimestamp(jiff::Timestamp::UNIX_EPOCH),
+            created: CreatedTimestamp(jiff::Timestamp::UNIX_EPOCH),
+        };
+        let sequence = Neighbours {
+            prev: None,
+            next: Some(summary("b")),
+            children: vec![summary("b")],
+        };
+        let html = render_page(&config, None, "A", "# A\n", &[], Some(&sequence), resolve);
+        assert!(html.contains("<span class=\"next\"><a href=\"b.html\">B</a></span>"));
+        assert!(html.contains("<ul class=\"children\">\n<li><a href=\"b.html\">B</a></li>"));
+        assert!(!html.contains("class=\"prev\""));
+    }
+
+    #[test]
+    fn test_render_math() {
+        let resolve = |_: Destination| None;
+        let body = "# A\n\n$a^2$ and\n\n$$\n\\sum x\n$$\n";
+        let html = render_page(&HtmlConfig::default(), None, "A", body, &[], None, resolve);
+        assert!(html.contains("<span class=\"math math-inline\">a^2</span>"));
+        assert!(html.contains("<span class=\"math math-display\">"));
+        assert!(html.contains("katex.min.js"));
+
+        let config = HtmlConfig {
+            math: false,
+            ..HtmlConfig::default()
+        };
+        let html = render_page(&config, None, "A", body, &[], None, resolve);
+        assert!(!html.contains("katex"));
+    }
+
+    #[test]
+    fn test_highlight() {
+        let resolve = |_: Destination| None;
+        let body = "```rust\nfn main() {}\n```\n\n```unknown\na < b\n```\n";
+        let config = HtmlConfig::default();
+        let highlighter = Highlighter::new(&config).unwrap();
+        let html = render_markdown(body, &config, highlighter.as_ref(), resolve);
+        assert!(html.contains("<pre style=\""), "{html}");
+        assert!(html.contains("<code class=\"language-unknown\">a &lt; b\n</code>"));
+
+        let config = HtmlConfig {
+            highlight_classes: true,
+            ..HtmlConfig::default()
+        };
+        let highlighter = Highlighter::new(&config).unwrap();
+        let html = render_page(&config, highlighter.as_ref(), "A", body, &[], None, resolve);
+        assert!(html.contains("<code class=\"language-rust\"><span class=\"source rust\">"));
+        assert!(html.contains("<style>"));
+
+        let config = HtmlConfig {
+            highlight_theme: "missing".to_owned(),
+            ..HtmlConfig::default()
+        };
+        assert!(Highlighter::new(&config).is_err());
+    }
+
+    #[test]
+    fn test_render_diagrams() {
+        let resolve = |_: Destination| None;
+        let body = "```mermaid\ngraph TD\n  A --> B\n```\n\n```dot\ndigraph { a -> b }\n```\n";
+        let config = HtmlConfig::default();
+        let html = render_page(&config, None, "A", body, &[], None, resolve);
+        assert!(html.contains("<pre class=\"mermaid\">graph TD\n  A --&gt; B\n</pre>"));
+        assert!(html.contains("<pre class=\"graphviz\">digraph { a -&gt; b }\n</pre>"));
+        assert!(html.contains("import mermaid from"));
+
+        let html = render_page(&config, None, "A", "# A\n", &[], None, resolve);
+        assert!(!html.contains("mermaid"));
+
+        // a "renderer" writing the same svg for every diagram
+        let command = [
+            "sh",
+            "-c",
+            "printf '<?xml?>\\n<svg/>\\n' > \"$0\"",
+            "{output}",
+        ];
+        let config = HtmlConfig {
+            diagram_commands: HashMap::from([(
+                "dot".to_owned(),
+                command.map(String::from).to_vec(),
+            )]),
+            ..HtmlConfig::default()
+        };
+        let html = render_markdown(body, &config, None, resolve);
+        assert!(html.contains("<figure class=\"diagram dot\">\n<svg/>\n</figure>"));
+        assert!(html.contains("<pre class=\"mermaid\">"));
+    }
+
+    #[test]
+    fn test_smart_punctuation() {
+        let resolve = |_: Destination| None;
+        let config = HtmlConfig {
+            smart_punctuation: true,
+            ..HtmlConfig::default()
+        };
+        let html = render_markdown("\"a\" -- b...\n", &config, None, resolve);
+        assert_eq!(html, "<p>\u{201c}a\u{201d} \u{2013} b\u{2026}</p>\n");
+    }
+
+    #[test]
+    fn test_alerts() {
+        let resolve = |_: Destination| None;
+        let html = render_markdown(
+            "> [!WARNING]\n> careful\n",
+            &HtmlConfig::default(),
+            None,
+            resolve,
+        );
+        assert_eq!(
+            html,
+            "<blockquote class=\"markdown-alert-warning\">\n<p>careful</p>\n</blockquote>\n"
+        );
+    }
+
+    #[test]
+    fn test_heading_anchors() {
+        let resolve = |_: Destination| None;
+        let html = render_markdown(
+            "# A `b`\n\n## A b\n\n## Custom {#custom .wide}\n",
+            &HtmlConfig::default(),
+            None,
+            resolve,
+        );
+        assert_eq!(
+            html,
+            "<h1 id=\"a-b\">A <code>b</code></h1>\n<h2 id=\"a-b-1\">A b</h2>\n<h2 id=\"custom\" class=\"wide\">Custom</h2>\n"
+        );
+    }
+}
@@ -0,0 +1,379 @@
+//! Export of the dated content of the collection as an iCalendar
+//! ([RFC 5545](https://datatracker.ietf.org/doc/html/rfc5545)) file.
+//!
+//! Two sources are exported:
+//! - `event` entries in the frontmatter of a document, either a single date, a
+//!   table with a `start` (and optional `end`, `title`, `location` and
+//!   `description`) or a list of those.
+//! - tasks with a due date, resolved from a date expression in their content
+//!   ("call Bob next friday") when the collection is indexed.
+
+use jiff::Timestamp;
+use jiff::civil::{Date, DateTime};
+use jiff::tz::TimeZone;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sql_minifier::macros::minify_sql as sql;
+
+use crate::core::db::DbList;
+use crate::core::types::document::{Document, DocumentId};
+use crate::core::types::task::TaskStatus;
+use crate::result::Result;
+
+/// Frontmatter key holding the events of a document
+pub const EVENT_KEY: &str = "event";
+
+const PRODUCT_ID: &str = "-//zet//zet//EN";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventTime {
+    /// an all day entry
+    Date(Date),
+    DateTime(Timestamp),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub document_id: DocumentId,
+    pub summary: String,
+    pub start: EventTime,
+    pub end: Option<EventTime>,
+    pub location: Option<String>,
+    pub description: Option<String>,
+    pub stamp: Timestamp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarTodo {
+    pub uid: String,
+    pub document_id: DocumentId,
+    pub summary: String,
+    /// an all day entry when the due date has no time of day
+    pub due: EventTime,
+    pub status: TaskStatus,
+    pub stamp: Timestamp,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Calendar {
+    pub events: Vec<CalendarEvent>,
+    pub todos: Vec<CalendarTodo>,
+}
+
+impl Calendar {
+    /// Collect the frontmatter events and dated tasks of the collection.
+    /// Event times without an offset are interpreted in `tz`.
+    pub fn load(db: &Connection, tz: &TimeZone) -> Result<Calendar> {
+        let mut documents = Document::list(db)?;
+        documents.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut events = Vec::new();
+        for document in &documents {
+            let entries = match document.data.get(EVENT_KEY) {
+                Some(Value::Array(entries)) => entries.iter().collect(),
+                Some(entry) => vec![entry],
+                None => continue,
+            };
+            for (i, entry) in entries.into_iter().enumerate() {
+                match parse_event(entry, tz) {
+                    Some((start, end, fields)) => events.push(CalendarEvent {
+                        uid: format!("{}-event-{}@zet", document.id.0, i),
+                        document_id: document.id.clone(),
+                        summary: fields.title.unwrap_or_else(|| document.title.clone()),
+                        start,
+                        end,
+                        location: fields.location,
+                        description: fields.description,
+                        stamp: document.modified.0,
+                    }),
+                    None => log::warn!(
+                        "ignoring invalid event entry {} of document {}",
+                        entry,
+                        document.id.0
+                    ),
+                }
+            }
+        }
+
+        let todos = db
+            .prepare(sql!(
+                r#"
+                select
+                    d.id,
+                    d.modified,
+                    t.status,
+                    t.content,
+                    t.range_start,
+                    t.due
+                from
+                    document_task t
+                    join document d on d.id = t.document_id
+                where
+                    t.due is not null
+                order by
+                    d.id, t.range_start
+            "#
+            ))?
+            .query_map([], |r| {
+                Ok((
+                    r.get::<_, DocumentId>(0)?,
+                    r.get::<_, Timestamp>(1)?,
+                    r.get::<_, TaskStatus>(2)?,
+                    r.get::<_, String>(3)?,
+                    r.get::<_, i64>(4)?,
+                    r.get::<_, Timestamp>(5)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?
+            .into_iter()
+            .map(
+                |(document_id, stamp, status, content, offset, due)| CalendarTodo {
+                    uid: format!("{}-task-{}@zet", document_id.0, offset),
+                    document_id,
+                    summary: content,
+                    due: due_time(due, tz),
+                    status,
+                    stamp,
+                },
+            )
+            .collect();
+
+        Ok(Calendar { events, todos })
+    }
+
+    /// Serialize the calendar in the iCalendar format
+    pub fn to_ics(&self) -> String {
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_owned(),
+            "VERSION:2.0".to_owned(),
+            format!("PRODID:{PRODUCT_ID}"),
+        ];
+
+        for event in &self.events {
+            lines.push("BEGIN:VEVENT".to_owned());
+            lines.push(format!("UID:{}", escape(&event.uid)));
+            lines.push(format!("DTSTAMP:{}", format_timestamp(event.stamp)));
+            lines.push(format_time("DTSTART", event.start));
+            if let Some(end) = event.end {
+                // the end of an all day event is exclusive
+                let end = match end {
+                    EventTime::Date(date) => EventTime::Date(date.tomorrow().unwrap_or(date)),
+                    end => end,
+                };
+                lines.push(format_time("DTEND", end));
+            }
+            lines.push(format!("SUMMARY:{}", escape(&event.summary)));
+            if let Some(location) = &event.location {
+                lines.push(format!("LOCATION:{}", escape(location)));
+            }
+            if let Some(description) = &event.description {
+                lines.push(format!("DESCRIPTION:{}", escape(description)));
+            }
+            lines.push("END:VEVENT".to_owned());
+        }
+
+        for todo in &self.todos {
+            lines.push("BEGIN:VTODO".to_owned());
+            lines.push(format!("UID:{}", escape(&todo.uid)));
+            lines.push(format!("DTSTAMP:{}", format_timestamp(todo.stamp)));
+            lines.push(format_time("DUE", todo.due));
+            lines.push(format!("SUMMARY:{}", escape(&todo.summary)));
+            lines.push(format!(
+                "STATUS:{}",
+                match todo.status {
+                    TaskStatus::Todo => "NEEDS-ACTION",
+                    TaskStatus::InProgress => "IN-PROCESS",
+                    TaskStatus::Done => "COMPLETED",
+                    TaskStatus::Cancelled => "CANCELLED",
+                }
+            ));
+            lines.push("END:VTODO".to_owned());
+        }
+
+        lines.push("END:VCALENDAR".to_owned());
+
+        lines
+            .iter()
+            .map(|line| format!("{}\r\n", fold(line)))
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct EventFields {
+    title: Option<String>,
+    location: Option<String>,
+    description: Option<String>,
+}
+
+fn parse_event(
+    entry: &Value,
+    tz: &TimeZone,
+) -> Option<(EventTime, Option<EventTime>, EventFields)> {
+    match entry {
+        Value::String(start) => Some((parse_time(start, tz)?, None, EventFields::default())),
+        Value::Object(table) => {
+            let field = |key: &str| {
+                table
+                    .get(key)
+                    .and_then(Value::as_str)
+                    .map(ToOwned::to_owned)
+            };
+            let start = parse_time(table.get("start")?.as_str()?, tz)?;
+            let end = match field("end") {
+                Some(end) => Some(parse_time(&end, tz)?),
+                None => None,
+            };
+            let fields = EventFields {
+                title: field("title"),
+                location: field("location"),
+                description: field("description"),
+            };
+            Some((start, end, fields))
+        }
+        _ => None,
+    }
+}
+
+/// Parse a date (`2025-06-01`), a date and time (`2025-06-01 10:00`) in `tz`
+/// or a timestamp with an offset (`2025-06-01T10:00:00+02:00`)
+fn parse_time(input: &str, tz: &TimeZone) -> Option<EventTime> {
+    let input = input.trim();
+    if let Ok(timestamp) = input.parse::<Timestamp>() {
+        return Some(EventTime::DateTime(timestamp));
+    }
+    if let Ok(datetime) = input.parse::<DateTime>()
+        && input.len() > "YYYY-MM-DD".len()
+    {
+        return Some(EventTime::DateTime(
+            datetime.to_zoned(tz.clone()).ok()?.timestamp(),
+        ));
+    }
+    input.parse::<Date>().ok().map(EventTime::Date)
+}
+
+/// A due date at midnight in `tz` is taken to be a whole day, e.g. "next friday"
+fn due_time(due: Timestamp, tz: &TimeZone) -> EventTime {
+    let zoned = due.to_zoned(tz.clone());
+    if zoned.time() == jiff::civil::Time::midnight() {
+        EventTime::Date(zoned.date())
+    } else {
+        EventTime::DateTime(due)
+    }
+}
+
+fn format_timestamp(timestamp: Timestamp) -> String {
+    timestamp.strftime("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn format_time(name: &str, time: EventTime) -> String {
+    match time {
+        EventTime::Date(date) => format!("{name};VALUE=DATE:{}", date.strftime("%Y%m%d")),
+        EventTime::DateTime(timestamp) => format!("{name}:{}", format_timestamp(timestamp)),
+    }
+}
+
+/// Escape a text value
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line into lines of at most 75 octets
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jiff::ToSpan;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_event() {
+        let tz = TimeZone::UTC;
+        let (start, end, _) = parse_event(&json!("2025-06-01"), &tz).unwrap();
+        assert_eq!(start, EventTime::Date(jiff::civil::date(2025, 6, 1)));
+        assert_eq!(end, None);
+
+        let (start, end, fields) = parse_event(
+            &json!({"start": "2025-06-01 10:00", "end": "2025-06-01T12:00:00Z", "title": "Meeting"}),
+            &tz,
+        )
+        .unwrap();
+        assert_eq!(
+            start,
+            EventTime::DateTime("2025-06-01T10:00:00Z".parse().unwrap())
+        );
+        assert_eq!(
+            end,
+            Some(EventTime::DateTime("2025-06-01T12:00:00Z".parse().unwrap()))
+        );
+        assert_eq!(fields.title.as_deref(), Some("Meeting"));
+
+        assert!(parse_event(&json!({"end": "2025-06-01"}), &tz).is_none());
+        assert!(parse_event(&json!("next week"), &tz).is_none());
+    }
+
+    #[test]
+    fn test_due_time() {
+        let tz = TimeZone::UTC;
+        assert_eq!(
+            due_time("2025-06-01T00:00:00Z".parse().unwrap(), &tz),
+            EventTime::Date(jiff::civil::date(2025, 6, 1))
+        );
+        assert_eq!(
+            due_time("2025-06-01T15:00:00Z".parse().unwrap(), &tz),
+            EventTime::DateTime("2025-06-01T15:00:00Z".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_to_ics() {
+        let calendar = Calendar {
+            events: vec![CalendarEvent {
+                uid: "a-event-0@zet".into(),
+                document_id: DocumentId("a".into()),
+                summary: "Trip, day one".into(),
+                start: EventTime::Date(jiff::civil::date(2025, 6, 1)),
+                end: Some(EventTime::Date(jiff::civil::date(2025, 6, 2))),
+                location: None,
+                description: None,
+                stamp: Timestamp::UNIX_EPOCH + 1.hour(),
+            }],
+            todos: vec![],
+        };
+        let ics = calendar.to_ics();
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.contains("DTSTAMP:19700101T010000Z\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20250601\r\n"));
+        assert!(ics.contains("DTEND;VALUE=DATE:20250603\r\n"));
+        assert!(ics.contains("SUMMARY:Trip\\, day one\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    }
+
+    #[test]
+    fn test_fold() {
+        let line = "x".repeat(100);
+        let folded = fold(&line);
+        let parts: Vec<&str> = folded.split("\r\n").collect();
+        assert_eq!(parts[0].len(), 75);
+        assert_eq!(parts[1], format!(" {}", "x".repeat(25)));
+    }
+}
@@ -0,0 +1,303 @@
+//! Schemes for the ids of new notes.
+//!
+//! By default a note is identified by its slugged title and named after it.
+//! The other schemes generate an id independent of the title, a timestamp,
+//! an ulid, an uuid or the next number in the directory of the note, and name
+//! the file `<id>-<slug>.md`. The id is read back from the start of the
+//! filename of a document without an id in its frontmatter, and links to
+//! such filenames resolve to the id.
+
+use std::path::Path;
+
+use jiff::Zoned;
+use serde::{Deserialize, Serialize};
+use sql_minifier::macros::minify_sql as sql;
+
+use crate::core::db::DB;
+use crate::core::slug::slugify;
+use crate::core::types::document::DocumentId;
+use crate::core::{collection_db_file, path_to_id, path_to_slash, relative_path, workspace_paths};
+use crate::result::Result;
+
+/// The characters of an ulid, crockford's base32
+const ULID_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IdScheme {
+    /// the slugged title
+    #[default]
+    Slug,
+    /// the minute the note was created, `YYYYMMDDHHMM`, as luhmann style
+    /// zettelkasten use
+    Timestamp,
+    Ulid,
+    Uuid,
+    /// the next number in the directory of the note, prefixed by the
+    /// directory
+    Sequence,
+}
+
+impl IdScheme {
+    /// The id and filename of a new note titled `title` in `directory`, a
+    /// directory of the collection at `root`
+    pub fn new_note(
+        &self,
+        root: &Path,
+        directory: &Path,
+        title: &str,
+        now: &Zoned,
+    ) -> Result<(DocumentId, String)> {
+        let taken = match self {
+            IdScheme::Timestamp => self.collection_ids(root)?,
+            _ => self.directory_ids(directory)?,
+        };
+        let id = match self {
+            IdScheme::Slug => slugify(title),
+            IdScheme::Timestamp => {
+                // two notes created within a minute take consecutive minutes
+                let mut time = now.clone();
+                loop {
+                    let id = time.strftime("%Y%m%d%H%M").to_string();
+                    if !taken.contains(&id) {
+                        break id;
+                    }
+                    time = time.checked_add(jiff::SignedDuration::from_mins(1))?;
+                }
+            }
+            IdScheme::Ulid => ulid(now),
+            IdScheme::Uuid => uuid::Uuid::new_v4().to_string(),
+            IdScheme::Sequence => {
+                let next = taken.iter().filter_map(|n| n.parse::<u64>().ok()).max();
+                sequence_id(root, directory, &(next.unwrap_or(0) + 1).to_string())
+            }
+        };
+        let filename = self.filename(&id, title);
+        Ok((DocumentId(id), filename))
+    }
+
+    /// The filename of the note `id` titled `title`
+    pub fn filename(&self, id: &str, title: &str) -> String {
+        if *self == IdScheme::Slug {
+            return format!("{}.md", id);
+        }
+        // slugify keeps path separators, which are not valid in a file name
+        let slug = slugify(title).replace('/', "-");
+        let id = id.rsplit('/').next().unwrap_or(id);
+        match slug.is_empty() {
+            true => format!("{}.md", id),
+            false => format!("{}-{}.md", id, slug),
+        }
+    }
+
+    /// The id of the document at `path`, read from the start of its filename,
+    /// falling back to its path
+    pub fn path_to_id(&self, root: &Path, path: &Path) -> DocumentId {
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy())
+            .unwrap_or_default();
+        match self.stem_id(&stem) {
+            Some(id) if *self == IdScheme::Sequence => {
+                let directory = path.parent().unwrap_or(root);
+                DocumentId(sequence_id(root, directory, id))
+            }
+            Some(id) => DocumentId(id.to_owned()),
+            None => path_to_id(root, path),
+        }
+    }
+
+    /// The id at the start of a filename without its extension
+    fn stem_id<'a>(&self, stem: &'a str) -> Option<&'a str> {
+        let len = match self {
+            IdScheme::Slug => return None,
+            IdScheme::Timestamp => 12,
+            IdScheme::Ulid => 26,
+            IdScheme::Uuid => 36,
+            IdScheme::Sequence => stem.bytes().take_while(u8::is_ascii_digit).count(),
+        };
+        let (id, rest) = stem.split_at_checked(len)?;
+        if !(rest.is_empty() || rest.starts_with('-')) {
+            return None;
+        }
+        let valid = match self {
+            IdScheme::Slug => false,
+            IdScheme::Timestamp | IdScheme::Sequence => {
+                !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit())
+            }
+            IdScheme::Ulid => id
+                .bytes()
+                .all(|b| ULID_ALPHABET.contains(&b.to_ascii_uppercase())),
+            IdScheme::Uuid => uuid::Uuid::try_parse(id).is_ok(),
+        };
+        valid.then_some(id)
+    }
+
+    /// The ids of the notes in `directory` named by this scheme
+    fn directory_ids(&self, directory: &Path) -> Result<Vec<String>> {
+        if *self == IdScheme::Slug || !directory.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut ids = Vec::new();
+        for entry in std::fs::read_dir(directory)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "md") {
+                continue;
+            }
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            ids.extend(self.stem_id(&stem).map(str::to_owned));
+        }
+        Ok(ids)
+    }
+
+    /// The ids taken in the collection at `root`, timestamps being unique
+    /// across it: the indexed ids, and the ids at the start of the filenames
+    /// of its notes, in any directory
+    fn collection_ids(&self, root: &Path) -> Result<Vec<String>> {
+        let mut ids = Vec::new();
+        let db_file = collection_db_file(root);
+        if db_file.is_file() {
+            let db = DB::open_read_only(db_file)?;
+            ids = db
+                .prepare(sql!("select id from document"))?
+                .query_map([], |r| r.get(0))?
+                .collect::<rusqlite::Result<_>>()?;
+        }
+        for path in workspace_paths(root)? {
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            ids.extend(self.stem_id(&stem).map(str::to_owned));
+        }
+        Ok(ids)
+    }
+}
+
+/// The part of the link target `to` naming a document by a generated id,
+/// when it names it by a filename of the form `<id>-<slug>`. Sequence ids are
+/// not recognized, being indistinguishable from slugs starting with a number.
+pub fn strip_title(to: &str) -> Option<&str> {
+    let start = to.rfind('/').map_or(0, |i| i + 1);
+    let stem = &to[start..];
+    [IdScheme::Timestamp, IdScheme::Ulid, IdScheme::Uuid]
+        .iter()
+        .find_map(|scheme| scheme.stem_id(stem))
+        .filter(|id| id.len() < stem.len())
+        .map(|id| &to[..start + id.len()])
+}
+
+/// The sequence id `n` in `directory`, prefixed by its path in the collection
+fn sequence_id(root: &Path, directory: &Path, n: &str) -> String {
+    match relative_path(root, directory) {
+        Some(relative) if !relative.as_os_str().is_empty() => {
+            format!("{}/{}", slugify(path_to_slash(&relative)), n)
+        }
+        _ => n.to_owned(),
+    }
+}
+
+/// An ulid of the current time, its 80 random bits read from the system
+fn ulid(now: &Zoned) -> String {
+    let millis = now.timestamp().as_millisecond() as u128;
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes[6..]).expect("the system has a source of randomness");
+    let value = (millis << 80) | u128::from_be_bytes(bytes);
+    (0..26)
+        .rev()
+        .map(|i| ULID_ALPHABET[((value >> (5 * i)) & 31) as usize] as char)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> Zoned {
+        "2026-03-01T09:30:00[UTC]".parse().unwrap()
+    }
+
+    #[test]
+    fn test_new_note() {
+        let root = assert_fs::TempDir::new().unwrap();
+        let dir = root.path().join("zettel");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let (id, filename) = IdScheme::Slug
+            .new_note(root.path(), &dir, "A Note", &now())
+            .unwrap();
+        assert_eq!((id.0.as_str(), filename.as_str()), ("a-note", "a-note.md"));
+
+        let (id, filename) = IdScheme::Timestamp
+            .new_note(root.path(), &dir, "A Note", &now())
+            .unwrap();
+        assert_eq!(id.0, "202603010930");
+        assert_eq!(filename, "202603010930-a-note.md");
+        std::fs::write(dir.join(&filename), "").unwrap();
+        let (id, _) = IdScheme::Timestamp
+            .new_note(root.path(), &dir, "Another", &now())
+            .unwrap();
+        assert_eq!(id.0, "202603010931");
+        // taken in another directory
+        let other = root.path().join("inbox");
+        std::fs::create_dir_all(&other).unwrap();
+        std::fs::write(other.join("202603010931-another.md"), "").unwrap();
+        let (id, _) = IdScheme::Timestamp
+            .new_note(root.path(), &dir, "Third", &now())
+            .unwrap();
+        assert_eq!(id.0, "202603010932");
+
+        let dir = root.path().join("meetings");
+        std::fs::create_dir_all(&dir).unwrap();
+        let (id, filename) = IdScheme::Sequence
+            .new_note(root.path(), &dir, "First", &now())
+            .unwrap();
+        assert_eq!(
+            (id.0.as_str(), filename.as_str()),
+            ("meetings/1", "1-first.md")
+        );
+        std::fs::write(dir.join("7-seventh.md"), "").unwrap();
+        let (id, _) = IdScheme::Sequence
+            .new_note(root.path(), &dir, "Next", &now())
+            .unwrap();
+        assert_eq!(id.0, "meetings/8");
+
+        let (id, filename) = IdScheme::Ulid
+            .new_note(root.path(), &dir, "A Note", &now())
+            .unwrap();
+        assert_eq!(id.0.len(), 26);
+        assert_eq!(
+            IdScheme::Ulid.stem_id(&filename[..filename.len() - 3]),
+            Some(id.0.as_str())
+        );
+        let (id, _) = IdScheme::Uuid
+            .new_note(root.path(), &dir, "A Note", &now())
+            .unwrap();
+        assert!(uuid::Uuid::try_parse(&id.0).is_ok());
+    }
+
+    #[test]
+    fn test_path_to_id() {
+        let root = Path::new("/notes");
+        let id = |scheme: IdScheme, path: &str| scheme.path_to_id(root, Path::new(path)).0;
+        assert_eq!(
+            id(IdScheme::Timestamp, "/notes/z/202603010930-a-note.md"),
+            "202603010930"
+        );
+        assert_eq!(
+            id(IdScheme::Timestamp, "/notes/z/2026-review.md"),
+            "z/2026-review"
+        );
+        assert_eq!(id(IdScheme::Sequence, "/notes/z/3-agenda.md"), "z/3");
+        assert_eq!(id(IdScheme::Sequence, "/notes/3.md"), "3");
+        assert_eq!(id(IdScheme::Slug, "/notes/z/3-agenda.md"), "z/3-agenda");
+    }
+
+    #[test]
+    fn test_strip_title() {
+        assert_eq!(strip_title("z/202603010930-a-note"), Some("z/202603010930"));
+        assert_eq!(strip_title("202603010930"), None);
+        assert_eq!(strip_title("3-agenda"), None);
+        assert_eq!(
+            strip_title("01J0000000000000000000000Z-paper"),
+            Some("01J0000000000000000000000Z")
+        );
+    }
+}
@@ -0,0 +1,483 @@
+//! Hooks deriving rows from documents as they are indexed.
+//!
+//! The index parses every document once and walks its AST once, handing each
+//! node to every registered [`IndexHook`]. Once the walk is done each hook
+//! returns the rows it derived from the document. The headings, tasks, links,
+//! highlights, quotes and mentions of a document are derived by the built-in
+//! hooks; other hooks store their rows as json in the `document_derived` table.
+
+use serde::Serialize;
+
+use crate::core::extract_text_from_ast;
+use crate::core::parser::arena::{Ast, NodeId};
+use crate::core::parser::ast_nodes::{Node, TaskListMarker};
+use crate::core::quotes::quote_text;
+use crate::core::slug::HeadingAnchors;
+use crate::core::types::derived::NewDocumentDerived;
+use crate::core::types::document::Document;
+use crate::core::types::heading::NewDocumentHeading;
+use crate::core::types::highlight::NewDocumentHighlight;
+use crate::core::types::link::UnresolvedLink;
+use crate::core::types::mention::NewDocumentMention;
+use crate::core::types::metadata::NewDocumentMetadata;
+use crate::core::types::quote::NewDocumentQuote;
+use crate::core::types::task::{NewDocumentTask, TaskStatus};
+use crate::result::Result;
+
+/// A row derived from a document by an [`IndexHook`]
+#[derive(Debug, Clone)]
+pub enum DerivedRow {
+    Heading(NewDocumentHeading),
+    Task(NewDocumentTask),
+    Link(UnresolvedLink),
+    Highlight(NewDocumentHighlight),
+    Quote(NewDocumentQuote),
+    Mention(NewDocumentMention),
+    Metadata(NewDocumentMetadata),
+    Custom(NewDocumentDerived),
+}
+
+impl DerivedRow {
+    /// A row of a hook without a table of its own
+    pub fn custom(document: &Document, hook: &str, data: &impl Serialize) -> Result<Self> {
+        Ok(DerivedRow::Custom(NewDocumentDerived {
+            document_id: document.id.clone(),
+            hook: hook.to_owned(),
+            data: serde_json::to_value(data)?,
+        }))
+    }
+}
+
+pub trait IndexHook {
+    /// Called for every node `id` of the `ast` of `doc` in document order
+    fn on_node(&mut self, _doc: &Document, _ast: &Ast, _id: NodeId) {}
+
+    /// Called once every node of `doc` has been visited, returns the rows
+    /// derived from the document
+    fn on_document(&mut self, doc: &Document, ast: &Ast) -> Vec<DerivedRow>;
+}
+
+/// The hooks run on every indexed document
+#[derive(Default)]
+pub struct IndexHooks {
+    hooks: Vec<Box<dyn IndexHook>>,
+}
+
+impl IndexHooks {
+    /// The hooks deriving the headings, tasks, links, highlights, quotes and
+    /// mentions of documents
+    pub fn builtin() -> Self {
+        let mut hooks = Self::default();
+        hooks.register(HeadingHook::default());
+        hooks.register(TaskHook::default());
+        hooks.register(LinkHook::default());
+        hooks.register(HighlightHook::default());
+        hooks.register(QuoteHook::default());
+        hooks.register(MentionHook::default());
+        hooks
+    }
+
+    pub fn register(&mut self, hook: impl IndexHook + 'static) -> &mut Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Walk `ast` once, returning the rows derived by all hooks
+    pub fn run(&mut self, doc: &Document, ast: &Ast) -> Vec<DerivedRow> {
+        for id in ast.ids() {
+            for hook in &mut self.hooks {
+                hook.on_node(doc, ast, id);
+            }
+        }
+        self.hooks
+            .iter_mut()
+            .flat_map(|hook| hook.on_document(doc, ast))
+            .collect()
+    }
+}
+
+/// Derives the headings of a document
+#[derive(Default)]
+pub struct HeadingHook {
+    headings: Vec<NewDocumentHeading>,
+    anchors: HeadingAnchors,
+}
+
+impl IndexHook for HeadingHook {
+    fn on_node(&mut self, doc: &Document, ast: &Ast, id: NodeId) {
+        let node = &ast[id];
+        if let Node::Heading {
+            range,
+            id,
+            classes,
+            attributes,
+            level,
+            content,
+            ..
+        } = node
+        {
+            self.headings.push(NewDocumentHeading {
+                document_id: doc.id.clone(),
+                content: content.to_owned(),
+                level: *level,
+                anchor: self.anchors.anchor(id.as_deref(), content),
+                metadata: serde_json::json!({
+                    "id": id,
+                    "classes": classes,
+                    "attributes": attributes
+                }),
+                range_start: range.start,
+                range_end: range.end,
+            });
+        }
+    }
+
+    fn on_document(&mut self, _doc: &Document, _ast: &Ast) -> Vec<DerivedRow> {
+        self.anchors = HeadingAnchors::default();
+        self.headings.drain(..).map(DerivedRow::Heading).collect()
+    }
+}
+
+/// The heading of the section of action items in meeting notes
+pub const ACTION_ITEMS_HEADING: &str = "Action items";
+
+/// Derives the tasks, the list items with a checkbox, of a document. Their
+/// due dates are resolved by the index. A task is owned by the person it
+/// mentions first, and is an action item if it has an owner or is in the
+/// section of an [`ACTION_ITEMS_HEADING`] heading.
+// TODO this should probably be extended to capture that tasks typically have subtasks
+#[derive(Default)]
+pub struct TaskHook {
+    tasks: Vec<NewDocumentTask>,
+    /// the level of the action items heading of the current section
+    action_items: Option<u8>,
+}
+
+impl IndexHook for TaskHook {
+    fn on_node(&mut self, doc: &Document, ast: &Ast, id: NodeId) {
+        let node = &ast[id];
+        // sections are tracked in document order, as the parser may nest a
+        // heading in the section of a deeper one
+        if let Node::Heading { level, content, .. } = node {
+            if self.action_items.is_some_and(|section| *level <= section) {
+                self.action_items = None;
+            }
+            if content.trim().eq_ignore_ascii_case(ACTION_ITEMS_HEADING) {
+                self.action_items = Some(*level);
+            }
+            return;
+        }
+        let Node::Item {
+            range,
+            task_list_marker,
+        } = node
+        else {
+            return;
+        };
+        let status = match task_list_marker {
+            TaskListMarker::UnChecked => TaskStatus::Todo,
+            TaskListMarker::InProgress => TaskStatus::InProgress,
+            TaskListMarker::Checked => TaskStatus::Done,
+            TaskListMarker::Cancelled => TaskStatus::Cancelled,
+            TaskListMarker::NoCheckmark => return,
+        };
+        let owner = first_mention(ast, id);
+        self.tasks.push(NewDocumentTask {
+            document_id: doc.id.clone(),
+            parent_id: None,
+            checked: status == TaskStatus::Done,
+            status,
+            content: extract_text_from_ast(ast, ast.children(id)),
+            range_start: range.start,
+            range_end: range.end,
+            due: None,
+            due_start: None,
+            due_end: None,
+            action_item: owner.is_some() || self.action_items.is_some(),
+            owner,
+        });
+    }
+
+    fn on_document(&mut self, _doc: &Document, _ast: &Ast) -> Vec<DerivedRow> {
+        self.action_items = None;
+        self.tasks.drain(..).map(DerivedRow::Task).collect()
+    }
+}
+
+/// The name of the first `@name` mention of the inline nodes within `id`, or
+/// of the paragraphs among them
+fn first_mention(ast: &Ast, id: NodeId) -> Option<String> {
+    ast.children(id).find_map(|child| match &ast[child] {
+        Node::Mention { name, .. } => Some(name.to_owned()),
+        Node::Paragraph { .. } => first_mention(ast, child),
+        _ => None,
+    })
+}
+
+/// Derives the wiki and inline links of a document, with the range of the
+/// innermost paragraph or list item containing them
+#[derive(Default)]
+pub struct LinkHook {
+    links: Vec<UnresolvedLink>,
+}
+
+impl IndexHook for LinkHook {
+    fn on_node(&mut self, doc: &Document, ast: &Ast, id: NodeId) {
+        let node = &ast[id];
+        let (target, range, wiki) = match node {
+            Node::WikiLink { target, range, .. } => (target, range, true),
+            Node::InlineLink { target, range, .. } => (target, range, false),
+            // autolinks of email addresses are no links between pages
+            Node::AutoLink { target, range } if target.contains("://") => (target, range, false),
+            _ => return,
+        };
+        let block = ast.ancestors(id).find_map(|parent| match &ast[parent] {
+            Node::Paragraph { range, .. } | Node::Item { range, .. } => Some(range.clone()),
+            _ => None,
+        });
+        self.links.push(UnresolvedLink {
+            from: doc.id.clone().into(),
+            to: target.clone(),
+            range_start: range.start,
+            range_end: range.end,
+            block,
+            wiki,
+        });
+    }
+
+    fn on_document(&mut self, _doc: &Document, _ast: &Ast) -> Vec<DerivedRow> {
+        self.links.drain(..).map(DerivedRow::Link).collect()
+    }
+}
+
+/// Derives the highlights of a document
+#[derive(Default)]
+pub struct HighlightHook {
+    highlights: Vec<NewDocumentHighlight>,
+}
+
+impl IndexHook for HighlightHook {
+    fn on_node(&mut self, doc: &Document, ast: &Ast, id: NodeId) {
+        let node = &ast[id];
+        if let Node::Highlight { range, text } = node {
+            self.highlights.push(NewDocumentHighlight {
+                document_id: doc.id.clone(),
+                content: text.to_owned(),
+                range_start: range.start,
+                range_end: range.end,
+            });
+        }
+    }
+
+    fn on_document(&mut self, _doc: &Document, _ast: &Ast) -> Vec<DerivedRow> {
+        self.highlights
+            .drain(..)
+            .map(DerivedRow::Highlight)
+            .collect()
+    }
+}
+
+/// Derives the block quotes of a document that are not alerts, block quotes
+/// nested in another one being part of it
+#[derive(Default)]
+pub struct QuoteHook {
+    quotes: Vec<NewDocumentQuote>,
+}
+
+impl IndexHook for QuoteHook {
+    fn on_node(&mut self, doc: &Document, ast: &Ast, id: NodeId) {
+        let node = &ast[id];
+        let Node::BlockQuote {
+            range,
+            kind: None,
+            depth: 1,
+        } = node
+        else {
+            return;
+        };
+        let (content, attribution) = quote_text(ast, id);
+        self.quotes.push(NewDocumentQuote {
+            document_id: doc.id.clone(),
+            content,
+            attribution,
+            range_start: range.start,
+            range_end: range.end,
+        });
+    }
+
+    fn on_document(&mut self, _doc: &Document, _ast: &Ast) -> Vec<DerivedRow> {
+        self.quotes.drain(..).map(DerivedRow::Quote).collect()
+    }
+}
+
+/// Derives the `@name` mentions of a document
+#[derive(Default)]
+pub struct MentionHook {
+    mentions: Vec<NewDocumentMention>,
+}
+
+impl IndexHook for MentionHook {
+    fn on_node(&mut self, doc: &Document, ast: &Ast, id: NodeId) {
+        let node = &ast[id];
+        if let Node::Mention { range, name } = node {
+            self.mentions.push(NewDocumentMention {
+                document_id: doc.id.clone(),
+                name: name.to_owned(),
+                range_start: range.start,
+                range_end: range.end,
+            });
+        }
+    }
+
+    fn on_document(&mut self, _doc: &Document, _ast: &Ast) -> Vec<DerivedRow> {
+        self.mentions.drain(..).map(DerivedRow::Mention).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::{DocumentParser, Parse};
+    use crate::core::types::document::{
+        CreatedTimestamp, DocumentId, DocumentPath, ModifiedTimestamp,
+    };
+    use jiff::Timestamp;
+
+    fn document() -> Document {
+        Document {
+            id: DocumentId("a".to_owned()),
+            title: "A".to_owned(),
+            path: DocumentPath("a.md".into()),
+            hash: 0,
+            modified: ModifiedTimestamp(Timestamp::UNIX_EPOCH),
+            created: CreatedTimestamp(Timestamp::UNIX_EPOCH),
+            data: serde_json::Value::Null,
+        }
+    }
+
+    /// Counts the code blocks of a document
+    struct CodeBlockHook(usize);
+
+    impl IndexHook for CodeBlockHook {
+        fn on_node(&mut self, _doc: &Document, ast: &Ast, id: NodeId) {
+            let node = &ast[id];
+            if let Node::CodeBlock { .. } = node {
+                self.0 += 1;
+            }
+        }
+
+        fn on_document(&mut self, doc: &Document, _ast: &Ast) -> Vec<DerivedRow> {
+            let count = std::mem::take(&mut self.0);
+            vec![DerivedRow::custom(doc, "code_blocks", &count).unwrap()]
+        }
+    }
+
+    #[test]
+    fn test_hooks() {
+        let body = "# A\n\nsee [[b]], ==marked== with @alice\n\n> quoted\n> — B\n\n- [ ] task\n  - [x] [c](c)\n\n```\ncode\n```\n";
+        let ast = DocumentParser::new().parse(body).unwrap();
+        let mut hooks = IndexHooks::builtin();
+        hooks.register(CodeBlockHook(0));
+
+        let rows = hooks.run(&document(), &ast);
+        let summary: Vec<String> = rows
+            .iter()
+            .map(|row| match row {
+                DerivedRow::Heading(h) => format!("heading {}", h.content),
+                DerivedRow::Task(t) => format!("task {} {}", t.status, t.content),
+                DerivedRow::Link(l) => {
+                    let block = l.block.clone().unwrap();
+                    format!("link {} in {:?}", l.to, body[block].trim())
+                }
+                DerivedRow::Highlight(h) => {
+                    format!(
+                        "highlight {} at {:?}",
+                        h.content,
+                        &body[h.range_start..h.range_end]
+                    )
+                }
+                DerivedRow::Mention(m) => format!("mention {}", m.name),
+                DerivedRow::Quote(q) => format!("quote {} by {:?}", q.content, q.attribution),
+                DerivedRow::Metadata(m) => format!("{} {}", m.plugin, m.metadata),
+                DerivedRow::Custom(d) => format!("{} {}", d.hook, d.data),
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                "heading A",
+                "task todo task",
+                "task done c",
+                "link b in \"see [[b]], ==marked== with @alice\"",
+                "link c in \"- [x] [c](c)\"",
+                "highlight marked at \"==marked==\"",
+                "quote quoted by Some(\"B\")",
+                "mention alice",
+                "code_blocks 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_action_items() {
+        let body = "# Meeting
+
+- [ ] not an action item
+- [ ] ask @bob and @carol
+
+## Action items
+
+- [ ] draft the plan
+  - [ ] with @alice
+
+### Later
+
+- [x] within the section
+
+## Notes
+
+- [ ] after the section
+";
+        let ast = DocumentParser::new().parse(body).unwrap();
+        let rows = IndexHooks::builtin().run(&document(), &ast);
+        let tasks: Vec<(String, Option<String>, bool)> = rows
+            .into_iter()
+            .filter_map(|row| match row {
+                DerivedRow::Task(t) => Some((t.content, t.owner, t.action_item)),
+                _ => None,
+            })
+            .collect();
+        let task = |content: &str, owner: Option<&str>, action_item| {
+            (content.to_owned(), owner.map(str::to_owned), action_item)
+        };
+        assert_eq!(
+            tasks,
+            vec![
+                task("not an action item", None, false),
+                task("ask @bob and @carol", Some("bob"), true),
+                task("draft the plan", None, true),
+                task("with @alice", Some("alice"), true),
+                task("within the section", None, true),
+                task("after the section", None, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_heading_anchors() {
+        let body = "# Notes\n\n## Notes\n\n## Plan {#the-plan .draft}\n";
+        let ast = DocumentParser::new().parse(body).unwrap();
+        let mut hooks = IndexHooks::builtin();
+        // run twice, the anchors of one document not numbering the other's
+        hooks.run(&document(), &ast);
+        let anchors: Vec<String> = hooks
+            .run(&document(), &ast)
+            .into_iter()
+            .filter_map(|row| match row {
+                DerivedRow::Heading(h) => Some(h.anchor),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(anchors, vec!["notes", "notes-1", "the-plan"]);
+    }
+}
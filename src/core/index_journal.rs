@@ -0,0 +1,73 @@
+//! The journal of an index run.
+//!
+//! Before the changes of an index run are written to the db, the documents
+//! they concern are recorded in `.zet/index.journal`. The changes themselves
+//! are written in a single transaction, so a run that is interrupted leaves
+//! the db as it was; the journal left behind tells the next run that the
+//! previous one did not complete.
+
+use std::path::{Path, PathBuf};
+
+use jiff::Timestamp;
+use serde::{Deserialize, Serialize};
+
+use crate::core::types::document::{DocumentId, DocumentPath};
+use crate::core::{CollectionStatus, index_journal_file};
+use crate::result::Result;
+
+/// The changes planned by an index run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexJournal {
+    pub started: Timestamp,
+    pub new: Vec<PathBuf>,
+    pub updated: Vec<DocumentId>,
+    pub removed: Vec<DocumentId>,
+}
+
+impl IndexJournal {
+    pub fn new(started: Timestamp, (new, updated, removed): &CollectionStatus) -> Self {
+        Self {
+            started,
+            new: new.iter().map(|DocumentPath(path)| path.clone()).collect(),
+            updated: updated.iter().map(|(id, ..)| id.clone()).collect(),
+            removed: removed.clone(),
+        }
+    }
+
+    /// The number of documents the run changes
+    pub fn len(&self) -> usize {
+        self.new.len() + self.updated.len() + self.removed.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The journal left behind by an index run that did not complete
+    pub fn read(root: &Path) -> Result<Option<Self>> {
+        let path = index_journal_file(root);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+
+    /// Write the journal, replacing the previous one atomically
+    pub fn write(&self, root: &Path) -> Result<()> {
+        let path = index_journal_file(root);
+        let tmp = path.with_extension("journal.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    /// Remove the journal once the changes have been committed
+    pub fn remove(root: &Path) -> Result<()> {
+        let path = index_journal_file(root);
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,349 @@
+//! Periodic (daily, weekly, monthly) notes.
+//!
+//! The note of a period is found by its file name, the slug of its title, so
+//! navigating between notes looks for the files of the periods before or
+//! after a date, skipping the periods without a note.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use jiff::ToSpan;
+use jiff::civil::{Date, Weekday};
+use serde::{Deserialize, Serialize};
+use tera::Tera;
+
+use crate::config::PeriodicNoteConfig;
+use crate::core::date_parser::{self, start_of_week};
+use crate::core::slug::slugify;
+use crate::result::Result;
+
+/// Directory relative to the collection root periodic notes are created in
+/// unless configured otherwise
+pub const DEFAULT_JOURNAL_DIRECTORY: &str = "journal";
+
+/// How many periods before or after a date are looked at for a note
+pub const SEARCH_LIMIT: usize = 366;
+
+/// The template of periodic notes unless configured otherwise, linking the
+/// notes of the adjacent periods
+pub const DEFAULT_JOURNAL_TEMPLATE: &str = r#"---
+id: {{ id }}
+title: {{ title }}
+---
+
+# {{ title }}
+
+« [[{{ adjacent_note(direction="previous") }}]] | [[{{ adjacent_note(direction="next") }}]] »
+
+{{ content }}
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Period {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl Period {
+    /// Format used for the title (and thereby the filename) of a periodic note
+    /// unless configured otherwise
+    pub fn default_title_format(&self) -> &'static str {
+        match self {
+            Period::Daily => "%Y-%m-%d",
+            Period::Weekly => "%G-W%V",
+            Period::Monthly => "%Y-%m",
+        }
+    }
+
+    /// The first day of the period containing `date`, weeks starting on
+    /// `week_start`
+    pub fn start_of(&self, date: Date, week_start: &date_parser::Weekday) -> Result<Date> {
+        Ok(match self {
+            Period::Daily => date,
+            Period::Weekly => start_of_week(date, week_start)?,
+            Period::Monthly => date.first_of_month(),
+        })
+    }
+
+    /// The last day of the period starting at `start`
+    pub fn end_of(&self, start: Date) -> Date {
+        match self {
+            Period::Daily => start,
+            Period::Weekly => start + 6.days(),
+            Period::Monthly => start.last_of_month(),
+        }
+    }
+
+    /// The start of the period following the one starting at `start`
+    pub fn next(&self, start: Date) -> Date {
+        self.end_of(start) + 1.day()
+    }
+
+    /// The start of the period preceding the one starting at `start`
+    pub fn previous(&self, start: Date, week_start: &date_parser::Weekday) -> Result<Date> {
+        self.start_of(start - 1.day(), week_start)
+    }
+
+    /// The starts of the consecutive periods from the one containing `first`
+    /// to the one containing `last`
+    pub fn starts(
+        &self,
+        first: Date,
+        last: Date,
+        week_start: &date_parser::Weekday,
+    ) -> Result<Vec<Date>> {
+        let mut date = self.start_of(first, week_start)?;
+        let mut starts = Vec::new();
+        while date <= last {
+            starts.push(date);
+            date = self.next(date);
+        }
+        Ok(starts)
+    }
+
+    /// The title of the period starting at `start`. A week is titled as the
+    /// iso week most of its days are in, the dates of the format being those
+    /// of the monday of that week, so a week starting on sunday is named by
+    /// the iso week it mostly overlaps rather than the one its sunday ends.
+    pub fn title(&self, start: Date, format: Option<&str>) -> Result<String> {
+        let format = format.unwrap_or(self.default_title_format());
+        let date = match self {
+            Period::Weekly => {
+                let middle = start + 3.days();
+                middle - middle.weekday().since(Weekday::Monday).days()
+            }
+            Period::Daily | Period::Monthly => start,
+        };
+        Ok(jiff::fmt::strtime::format(format, date)?)
+    }
+}
+
+/// Which way to navigate from a periodic note
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Previous,
+    Next,
+}
+
+/// The periodic notes of a period, where they are and what they are named
+#[derive(Debug, Clone)]
+pub struct Journal {
+    pub period: Period,
+    pub directory: PathBuf,
+    pub title_format: Option<String>,
+    /// the first day of the weeks of weekly notes
+    pub week_start: date_parser::Weekday,
+}
+
+impl Journal {
+    pub fn new(
+        root: &Path,
+        period: Period,
+        config: &PeriodicNoteConfig,
+        week_start: date_parser::Weekday,
+    ) -> Self {
+        Self {
+            period,
+            directory: root.join(
+                config
+                    .directory
+                    .as_deref()
+                    .unwrap_or(DEFAULT_JOURNAL_DIRECTORY),
+            ),
+            title_format: config.title_format.clone(),
+            week_start,
+        }
+    }
+
+    /// The title of the note of the period starting at `start`
+    pub fn title(&self, start: Date) -> Result<String> {
+        self.period.title(start, self.title_format.as_deref())
+    }
+
+    /// The id, and file name, of the note of the period starting at `start`
+    pub fn id(&self, start: Date) -> Result<String> {
+        Ok(slugify(&self.title(start)?))
+    }
+
+    /// The path of the note of the period starting at `start`
+    pub fn path(&self, start: Date) -> Result<PathBuf> {
+        Ok(self.directory.join(format!("{}.md", self.id(start)?)))
+    }
+
+    /// The start of the nearest period in `direction` of the one starting at
+    /// `start` that has a note, within [`SEARCH_LIMIT`] periods
+    pub fn adjacent(&self, start: Date, direction: Direction) -> Result<Option<Date>> {
+        let mut date = start;
+        for _ in 0..SEARCH_LIMIT {
+            date = match direction {
+                Direction::Previous => self.period.previous(date, &self.week_start)?,
+                Direction::Next => self.period.next(date),
+            };
+            if self.path(date)?.exists() {
+                return Ok(Some(date));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Register `adjacent_note(direction="previous"|"next")` with `tera`,
+    /// the id of the nearest note before or after the note of the period
+    /// starting at `start`, or of the adjacent period when there is none
+    pub fn register_functions(&self, tera: &mut Tera, start: Date) {
+        let journal = self.clone();
+        tera.register_function(
+            "adjacent_note",
+            move |args: &HashMap<String, tera::Value>| {
+                let (direction, adjacent) = match args.get("direction").and_then(|d| d.as_str()) {
+                    Some("previous") => (
+                        Direction::Previous,
+                        journal
+                            .period
+                            .previous(start, &journal.week_start)
+                            .map_err(|e| tera::Error::msg(e.to_string()))?,
+                    ),
+                    Some("next") => (Direction::Next, journal.period.next(start)),
+                    _ => {
+                        return Err(tera::Error::msg(
+                            "adjacent_note needs a direction, \"previous\" or \"next\"",
+                        ));
+                    }
+                };
+                let date = journal
+                    .adjacent(start, direction)
+                    .map_err(|e| tera::Error::msg(e.to_string()))?
+                    .unwrap_or(adjacent);
+                let id = journal
+                    .id(date)
+                    .map_err(|e| tera::Error::msg(e.to_string()))?;
+                Ok(tera::Value::String(id))
+            },
+        );
+    }
+}
+
+impl std::fmt::Display for Period {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Period::Daily => "daily",
+            Period::Weekly => "weekly",
+            Period::Monthly => "monthly",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jiff::civil::date;
+
+    const MONDAY: date_parser::Weekday = date_parser::Weekday::Monday;
+
+    #[test]
+    fn test_period_starts() {
+        // 2025-01-01 is a wednesday
+        let from = date(2025, 1, 1);
+
+        let starts = |period: Period, last| period.starts(from, last, &MONDAY).unwrap();
+        assert_eq!(
+            starts(Period::Daily, date(2025, 1, 3)),
+            vec![date(2025, 1, 1), date(2025, 1, 2), date(2025, 1, 3)]
+        );
+        assert_eq!(
+            starts(Period::Weekly, date(2025, 1, 6)),
+            vec![date(2024, 12, 30), date(2025, 1, 6)]
+        );
+        assert_eq!(
+            starts(Period::Monthly, date(2025, 3, 31)),
+            vec![date(2025, 1, 1), date(2025, 2, 1), date(2025, 3, 1)]
+        );
+        assert_eq!(starts(Period::Daily, from), vec![from]);
+        // weeks starting on sunday, 2025-01-05 being one
+        assert_eq!(
+            Period::Weekly
+                .starts(from, date(2025, 1, 11), &date_parser::Weekday::Sunday)
+                .unwrap(),
+            vec![date(2024, 12, 29), date(2025, 1, 5)]
+        );
+    }
+
+    #[test]
+    fn test_period_previous() {
+        let previous = |period: Period, start| period.previous(start, &MONDAY).unwrap();
+        assert_eq!(
+            previous(Period::Daily, date(2025, 1, 1)),
+            date(2024, 12, 31)
+        );
+        assert_eq!(
+            previous(Period::Weekly, date(2024, 12, 30)),
+            date(2024, 12, 23)
+        );
+        assert_eq!(
+            previous(Period::Monthly, date(2025, 3, 1)),
+            date(2025, 2, 1)
+        );
+    }
+
+    #[test]
+    fn test_adjacent() {
+        let root = assert_fs::TempDir::new().unwrap();
+        let journal = Journal::new(
+            root.path(),
+            Period::Daily,
+            &PeriodicNoteConfig::default(),
+            MONDAY,
+        );
+        std::fs::create_dir_all(&journal.directory).unwrap();
+        for day in ["2025-01-01.md", "2025-01-05.md"] {
+            std::fs::write(journal.directory.join(day), "").unwrap();
+        }
+
+        let start = date(2025, 1, 3);
+        assert_eq!(
+            journal.adjacent(start, Direction::Previous).unwrap(),
+            Some(date(2025, 1, 1))
+        );
+        assert_eq!(
+            journal.adjacent(start, Direction::Next).unwrap(),
+            Some(date(2025, 1, 5))
+        );
+        assert_eq!(
+            journal
+                .adjacent(date(2025, 1, 1), Direction::Previous)
+                .unwrap(),
+            None
+        );
+
+        let mut tera = Tera::default();
+        journal.register_functions(&mut tera, date(2025, 1, 5));
+        let rendered = tera
+            .render_str(
+                r#"{{ adjacent_note(direction="previous") }} {{ adjacent_note(direction="next") }}"#,
+                &tera::Context::new(),
+            )
+            .unwrap();
+        // the next day when no later note exists
+        assert_eq!(rendered, "2025-01-01 2025-01-06");
+    }
+
+    #[test]
+    fn test_period_titles() {
+        let start = date(2024, 12, 30);
+        assert_eq!(Period::Daily.title(start, None).unwrap(), "2024-12-30");
+        assert_eq!(Period::Weekly.title(start, None).unwrap(), "2025-W01");
+        // the iso week most days of a week starting on sunday are in
+        assert_eq!(
+            Period::Weekly.title(date(2026, 10, 11), None).unwrap(),
+            "2026-W42"
+        );
+        assert_eq!(Period::Monthly.title(start, None).unwrap(), "2024-12");
+        assert_eq!(
+            Period::Daily.title(start, Some("%A %d %B")).unwrap(),
+            "Monday 30 December"
+        );
+    }
+}
@@ -0,0 +1,664 @@
+//! Checks of the formatting of a document that can be fixed mechanically.
+//!
+//! Most issues carry the edit that fixes them, which is applied by `zet lint
+//! --fix` and offered as a quick fix by the language server. File names not
+//! following the configured pattern are fixed by `zet normalize-filenames`.
+
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::Path;
+
+use jiff::tz::TimeZone;
+use serde::{Deserialize, Serialize};
+
+use crate::core::filename::expected_filename;
+use crate::core::frontmatter::{sort_yaml_keys, split_frontmatter};
+use crate::core::inline_source_span;
+use crate::core::parser::arena::{Ast, Siblings};
+use crate::core::parser::ast_nodes::Node;
+use crate::core::parser::comments::ParserConfig;
+use crate::core::parser::{DocumentParser, FrontMatterFormat, Parse};
+use crate::core::slug::{HeadingAnchors, heading_anchor};
+use crate::result::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LintRule {
+    /// frontmatter keys not in the configured order
+    FrontmatterKeyOrder,
+    /// an url in text that is not a link
+    BareUrl,
+    /// a fenced code block that is never closed
+    UnclosedCodeFence,
+    /// a file name not following the configured pattern
+    FilenamePattern,
+    /// a footnote reference without a definition
+    UndefinedFootnote,
+    /// a footnote definition that is never referenced
+    UnusedFootnote,
+    /// numeric footnotes not numbered in the order they are referenced
+    FootnoteNumbering,
+    /// a link to a heading of the document that no heading has the anchor of
+    BrokenAnchor,
+    /// a diagnostic of a wasm plugin
+    Plugin,
+}
+
+impl LintRule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LintRule::FrontmatterKeyOrder => "frontmatter-key-order",
+            LintRule::BareUrl => "bare-url",
+            LintRule::UnclosedCodeFence => "unclosed-code-fence",
+            LintRule::FilenamePattern => "filename-pattern",
+            LintRule::UndefinedFootnote => "undefined-footnote",
+            LintRule::UnusedFootnote => "unused-footnote",
+            LintRule::FootnoteNumbering => "footnote-numbering",
+            LintRule::BrokenAnchor => "broken-anchor",
+            LintRule::Plugin => "plugin",
+        }
+    }
+}
+
+impl std::fmt::Display for LintRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The `[lint]` section of the configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LintConfig {
+    /// frontmatter keys that should come first, in this order
+    pub frontmatter_key_order: Vec<String>,
+    /// template of the file names of documents, e.g. `{{ date }}-{{ slug }}.md`,
+    /// see [`expected_filename`]. File names are not checked if unset.
+    pub filename_pattern: Option<String>,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            frontmatter_key_order: vec!["id".to_owned(), "title".to_owned()],
+            filename_pattern: None,
+        }
+    }
+}
+
+/// Replace the bytes in `range` of a document with `replacement`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LintIssue {
+    pub rule: LintRule,
+    /// byte range of the offending text in the document
+    pub range: Range<usize>,
+    pub message: String,
+    pub fix: Option<TextEdit>,
+}
+
+/// Check `document`, including its frontmatter. The body is parsed with the
+/// syntax enabled by `parser`.
+pub fn lint(
+    document: &str,
+    format: FrontMatterFormat,
+    parser: &ParserConfig,
+    config: &LintConfig,
+) -> Result<Vec<LintIssue>> {
+    let mut issues = Vec::new();
+
+    let body_offset = match split_frontmatter(document) {
+        Some((frontmatter, body)) => {
+            let start = frontmatter.as_ptr() as usize - document.as_ptr() as usize;
+            let range = start..start + frontmatter.len();
+            if format == FrontMatterFormat::Yaml
+                && let Some(sorted) = sort_yaml_keys(frontmatter, &config.frontmatter_key_order)
+            {
+                issues.push(LintIssue {
+                    rule: LintRule::FrontmatterKeyOrder,
+                    range: range.clone(),
+                    message: format!(
+                        "frontmatter keys should start with {}",
+                        config.frontmatter_key_order.join(", ")
+                    ),
+                    fix: Some(TextEdit {
+                        range,
+                        replacement: sorted,
+                    }),
+                });
+            }
+            document.len() - body.len()
+        }
+        None => 0,
+    };
+
+    let body = &document[body_offset..];
+    let ast = DocumentParser::from_config(parser).parse(body)?;
+    lint_nodes(&mut issues, body, &ast, ast.roots());
+    lint_footnotes(&mut issues, body, &ast);
+    lint_anchors(&mut issues, body, &ast);
+
+    for issue in &mut issues[..] {
+        if issue.rule != LintRule::FrontmatterKeyOrder {
+            issue.range = shift(&issue.range, body_offset);
+            if let Some(fix) = &mut issue.fix {
+                fix.range = shift(&fix.range, body_offset);
+            }
+        }
+    }
+    issues.sort_by_key(|issue| issue.range.start);
+
+    Ok(issues)
+}
+
+/// Apply the fixes of `issues` to `document`. Fixes overlapping an earlier
+/// fix are skipped, running the lint again will find them.
+pub fn fix(document: &str, issues: &[LintIssue]) -> String {
+    apply_edits(
+        document,
+        issues.iter().filter_map(|issue| issue.fix.as_ref()),
+    )
+}
+
+/// Apply `edits` to `document`. Edits overlapping an earlier edit are skipped.
+pub fn apply_edits<'a>(document: &str, edits: impl IntoIterator<Item = &'a TextEdit>) -> String {
+    let mut edits: Vec<&TextEdit> = edits.into_iter().collect();
+    edits.sort_by_key(|edit| edit.range.start);
+
+    let mut fixed = String::with_capacity(document.len());
+    let mut offset = 0;
+    for edit in edits {
+        if edit.range.start < offset {
+            continue;
+        }
+        fixed.push_str(&document[offset..edit.range.start]);
+        fixed.push_str(&edit.replacement);
+        offset = edit.range.end;
+    }
+    fixed.push_str(&document[offset..]);
+    fixed
+}
+
+/// Check that the file name of the document at `path` follows the configured
+/// pattern
+pub fn lint_filename(
+    root: &Path,
+    path: &Path,
+    document: &str,
+    format: FrontMatterFormat,
+    config: &LintConfig,
+    tz: &TimeZone,
+) -> Result<Option<LintIssue>> {
+    let Some(pattern) = &config.filename_pattern else {
+        return Ok(None);
+    };
+    let Some(expected) = expected_filename(pattern, root, path, document, format, tz)? else {
+        return Ok(None);
+    };
+    if path
+        .file_name()
+        .is_some_and(|name| name == expected.as_str())
+    {
+        return Ok(None);
+    }
+    Ok(Some(LintIssue {
+        rule: LintRule::FilenamePattern,
+        range: 0..0,
+        message: format!("file name should be {expected}"),
+        fix: None,
+    }))
+}
+
+fn shift(range: &Range<usize>, offset: usize) -> Range<usize> {
+    range.start + offset..range.end + offset
+}
+
+fn lint_nodes(issues: &mut Vec<LintIssue>, body: &str, ast: &Ast, ids: Siblings) {
+    find_bare_urls(issues, body, ast, ids.clone());
+    for id in ids {
+        match &ast[id] {
+            Node::Heading { .. }
+            | Node::Paragraph { .. }
+            | Node::BlockQuote { .. }
+            | Node::List { .. }
+            | Node::Item { .. } => lint_nodes(issues, body, ast, ast.children(id)),
+            Node::CodeBlock {
+                range,
+                is_fenced: true,
+                ..
+            } => issues.extend(unclosed_fence(body, range.clone())),
+            _ => {}
+        }
+    }
+}
+
+/// Check that the links of the document to its own headings, `[x](#anchor)`
+/// and `[[#heading]]`, point to one. A link to an anchor no heading has is
+/// fixed to point to the heading sharing the most words with it, as after the
+/// heading was renamed.
+fn lint_anchors(issues: &mut Vec<LintIssue>, body: &str, ast: &Ast) {
+    let mut anchors = HeadingAnchors::default();
+    let headings: Vec<(String, &str)> = ast
+        .ids()
+        .filter_map(|id| match ast.node(id) {
+            Node::Heading { id, content, .. } => {
+                Some((anchors.anchor(id.as_deref(), content), content.as_str()))
+            }
+            _ => None,
+        })
+        .collect();
+
+    for id in ast.ids() {
+        // the target comes first in `[[#heading|title]]` and last in `[title](#anchor)`
+        let (target, range, position) = match ast.node(id) {
+            Node::WikiLink { target, range, .. } => {
+                (target, range, body[range.clone()].find(target.as_str()))
+            }
+            Node::InlineLink { target, range, .. } => {
+                (target, range, body[range.clone()].rfind(target.as_str()))
+            }
+            _ => continue,
+        };
+        let wiki = matches!(ast.node(id), Node::WikiLink { .. });
+        let (Some(written), Some(position)) = (target.strip_prefix('#'), position) else {
+            continue;
+        };
+        // wiki links name the heading by its text
+        let anchor = match wiki {
+            true => heading_anchor(written),
+            false => written.to_owned(),
+        };
+        // even that of a heading with an `{#id}` anchor
+        let exists = headings.iter().any(|(other, content)| {
+            *other == anchor || (wiki && heading_anchor(content) == anchor)
+        });
+        if exists {
+            continue;
+        }
+        let start = range.start + position + "#".len();
+        issues.push(LintIssue {
+            rule: LintRule::BrokenAnchor,
+            range: range.clone(),
+            message: format!("no heading has the anchor #{anchor}"),
+            fix: renamed_heading(&anchor, &headings).map(|(anchor, content)| TextEdit {
+                range: start..start + written.len(),
+                replacement: match wiki {
+                    true => content.to_string(),
+                    false => anchor.clone(),
+                },
+            }),
+        });
+    }
+}
+
+/// The heading among `headings`, their anchors and texts, sharing more words
+/// with `anchor` than any other
+fn renamed_heading<'a>(
+    anchor: &str,
+    headings: &'a [(String, &'a str)],
+) -> Option<&'a (String, &'a str)> {
+    let words = |anchor: &str| -> HashSet<String> {
+        anchor
+            .split(['-', '_'])
+            .filter(|word| !word.is_empty())
+            .map(str::to_owned)
+            .collect()
+    };
+    let stale = words(anchor);
+    let mut candidates: Vec<(usize, &(String, &str))> = headings
+        .iter()
+        .map(|heading| (words(&heading.0).intersection(&stale).count(), heading))
+        .filter(|(shared, _)| *shared > 0)
+        .collect();
+    candidates.sort_by_key(|(shared, _)| std::cmp::Reverse(*shared));
+    match candidates.as_slice() {
+        [(shared, heading), rest @ ..] if rest.first().is_none_or(|(next, _)| next < shared) => {
+            Some(heading)
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FootnoteKind {
+    Reference,
+    /// a reference to a label without a definition, which the parser reads as
+    /// text
+    UndefinedReference,
+    Definition,
+}
+
+#[derive(Debug, Clone)]
+struct Footnote {
+    kind: FootnoteKind,
+    label: String,
+    /// byte range of the whole reference or definition, starting with `[^`
+    range: Range<usize>,
+}
+
+impl Footnote {
+    fn label_range(&self) -> Range<usize> {
+        let start = self.range.start + "[^".len();
+        start..start + self.label.len()
+    }
+}
+
+/// Check that every footnote reference has a definition and the other way
+/// around, and that numeric footnotes are numbered in the order they are
+/// first referenced
+fn lint_footnotes(issues: &mut Vec<LintIssue>, body: &str, ast: &Ast) {
+    let mut footnotes = Vec::new();
+    collect_footnotes(&mut footnotes, body, ast, ast.roots());
+    footnotes.sort_by_key(|footnote| footnote.range.start);
+
+    let is_referenced = |label: &str| {
+        footnotes
+            .iter()
+            .any(|f| f.kind == FootnoteKind::Reference && f.label == label)
+    };
+    for footnote in &footnotes {
+        let (rule, message) = match footnote.kind {
+            FootnoteKind::UndefinedReference => (
+                LintRule::UndefinedFootnote,
+                format!("footnote [^{}] is not defined", footnote.label),
+            ),
+            FootnoteKind::Definition if !is_referenced(&footnote.label) => (
+                LintRule::UnusedFootnote,
+                format!("footnote [^{}] is never referenced", footnote.label),
+            ),
+            _ => continue,
+        };
+        issues.push(LintIssue {
+            rule,
+            range: footnote.range.clone(),
+            message,
+            fix: Some(TextEdit {
+                range: footnote.range.clone(),
+                replacement: String::new(),
+            }),
+        });
+    }
+
+    // numeric labels by their first reference, then the unreferenced ones
+    let is_numeric = |label: &str| label.chars().all(|c| c.is_ascii_digit());
+    let mut labels: Vec<&str> = Vec::new();
+    let references = footnotes
+        .iter()
+        .filter(|f| f.kind != FootnoteKind::Definition);
+    let definitions = footnotes
+        .iter()
+        .filter(|f| f.kind == FootnoteKind::Definition);
+    for footnote in references.chain(definitions) {
+        if is_numeric(&footnote.label) && !labels.contains(&footnote.label.as_str()) {
+            labels.push(&footnote.label);
+        }
+    }
+    for footnote in &footnotes {
+        let Some(index) = labels.iter().position(|label| *label == footnote.label) else {
+            continue;
+        };
+        let number = (index + 1).to_string();
+        if footnote.label != number {
+            issues.push(LintIssue {
+                rule: LintRule::FootnoteNumbering,
+                range: footnote.range.clone(),
+                message: format!("footnote [^{}] should be [^{number}]", footnote.label),
+                fix: Some(TextEdit {
+                    range: footnote.label_range(),
+                    replacement: number,
+                }),
+            });
+        }
+    }
+}
+
+fn collect_footnotes(footnotes: &mut Vec<Footnote>, body: &str, ast: &Ast, ids: Siblings) {
+    find_undefined_footnotes(footnotes, body, ast, ids.clone());
+    for id in ids {
+        match &ast[id] {
+            Node::FootnoteReference { range, name } => footnotes.push(Footnote {
+                kind: FootnoteKind::Reference,
+                label: name.clone(),
+                range: range.clone(),
+            }),
+            Node::FootnoteDefinition { range, id, .. } if body[range.clone()].starts_with("[^") => {
+                footnotes.push(Footnote {
+                    kind: FootnoteKind::Definition,
+                    label: id.clone(),
+                    range: range.clone(),
+                })
+            }
+            Node::Heading { .. }
+            | Node::Paragraph { .. }
+            | Node::BlockQuote { .. }
+            | Node::List { .. }
+            | Node::Item { .. } => collect_footnotes(footnotes, body, ast, ast.children(id)),
+            _ => {}
+        }
+    }
+}
+
+/// Find the `[^label]` in the text of the inline nodes among `ids`,
+/// references the parser did not resolve to a definition
+fn find_undefined_footnotes(footnotes: &mut Vec<Footnote>, body: &str, ast: &Ast, ids: Siblings) {
+    let Some((span, excluded)) = inline_source_span(ast, ids) else {
+        return;
+    };
+
+    let text = &body[span.clone()];
+    let mut offset = 0;
+    while let Some(start) = text[offset..].find("[^").map(|start| offset + start) {
+        offset = start + "[^".len();
+        let position = span.start + start;
+        if excluded.iter().any(|range| range.contains(&position)) {
+            continue;
+        }
+        let rest = &text[offset..];
+        let Some(end) = rest.find(|c: char| c == ']' || c == '[' || c.is_whitespace()) else {
+            continue;
+        };
+        if end == 0 || !rest[end..].starts_with(']') {
+            continue;
+        }
+        footnotes.push(Footnote {
+            kind: FootnoteKind::UndefinedReference,
+            label: rest[..end].to_owned(),
+            range: position..position + "[^".len() + end + "]".len(),
+        });
+    }
+}
+
+/// The text after which an url ends
+const URL_TERMINATORS: [char; 3] = ['<', '>', '"'];
+/// Characters that end a sentence rather than an url
+const URL_TRAILING_PUNCTUATION: [char; 8] = ['.', ',', ':', ';', '!', '?', '\'', ')'];
+
+/// Find the urls in the source of the inline nodes among `ids` that are not
+/// part of a link or code
+fn find_bare_urls(issues: &mut Vec<LintIssue>, body: &str, ast: &Ast, ids: Siblings) {
+    let Some((span, excluded)) = inline_source_span(ast, ids) else {
+        return;
+    };
+
+    let text = &body[span.clone()];
+    let mut offset = 0;
+    while let Some(start) = ["https://", "http://"]
+        .iter()
+        .filter_map(|scheme| text[offset..].find(scheme))
+        .min()
+        .map(|start| offset + start)
+    {
+        let rest = &text[start..];
+        let end = rest
+            .find(|c: char| c.is_whitespace() || URL_TERMINATORS.contains(&c))
+            .unwrap_or(rest.len());
+        offset = start + end;
+        let position = span.start + start;
+        if excluded.iter().any(|range| range.contains(&position)) {
+            continue;
+        }
+
+        let mut url = &rest[..end];
+        // keep the parentheses of urls such as https://en.wikipedia.org/wiki/Rust_(language)
+        while let Some(stripped) = url.strip_suffix(URL_TRAILING_PUNCTUATION) {
+            if url.ends_with(')') && url.matches('(').count() >= url.matches(')').count() {
+                break;
+            }
+            url = stripped;
+        }
+        if url.len() <= "https://".len() {
+            continue;
+        }
+
+        let url_range = position..position + url.len();
+        issues.push(LintIssue {
+            rule: LintRule::BareUrl,
+            range: url_range.clone(),
+            message: format!("bare url {url}, should be a link"),
+            fix: Some(TextEdit {
+                range: url_range,
+                replacement: format!("<{url}>"),
+            }),
+        });
+    }
+}
+
+fn unclosed_fence(body: &str, range: Range<usize>) -> Option<LintIssue> {
+    let block = &body[range.clone()];
+    let mut lines = block.lines();
+    let opening = lines.next()?;
+    let indent = opening.len() - opening.trim_start_matches([' ', '>']).len();
+    let fence_char = opening[indent..].chars().next()?;
+    if fence_char != '`' && fence_char != '~' {
+        return None;
+    }
+    let fence_len = opening[indent..]
+        .chars()
+        .take_while(|c| *c == fence_char)
+        .count();
+    let is_closing = |line: &str| {
+        let line = line.trim_start_matches([' ', '>']).trim_end();
+        line.len() >= fence_len && line.chars().all(|c| c == fence_char)
+    };
+    if lines.last().is_some_and(is_closing) {
+        return None;
+    }
+
+    let fence = fence_char.to_string().repeat(fence_len);
+    let separator = if block.ends_with('\n') { "" } else { "\n" };
+    Some(LintIssue {
+        rule: LintRule::UnclosedCodeFence,
+        range: range.start..range.start + opening.len(),
+        message: "code block is never closed".to_owned(),
+        fix: Some(TextEdit {
+            range: range.end..range.end,
+            replacement: format!("{separator}{}{fence}\n", &opening[..indent]),
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(document: &str) -> Vec<LintRule> {
+        lint(
+            document,
+            FrontMatterFormat::Yaml,
+            &ParserConfig::default(),
+            &LintConfig::default(),
+        )
+        .unwrap()
+        .iter()
+        .map(|issue| issue.rule)
+        .collect()
+    }
+
+    fn fixed(document: &str) -> String {
+        let issues = lint(
+            document,
+            FrontMatterFormat::Yaml,
+            &ParserConfig::default(),
+            &LintConfig::default(),
+        )
+        .unwrap();
+        fix(document, &issues)
+    }
+
+    #[test]
+    fn test_clean_document() {
+        let document =
+            "---\nid: a\ntitle: A\n---\n\n# A\n\nsee <https://example.com>\n\n```\ncode\n```\n";
+        assert_eq!(rules(document), vec![]);
+    }
+
+    #[test]
+    fn test_bare_url() {
+        let document = "---\nid: a\n---\n\nsee https://example.com/a_(b). and `https://code` or\n[link](https://example.com)\n- http://example.com/_x_\n";
+        assert_eq!(rules(document), vec![LintRule::BareUrl, LintRule::BareUrl]);
+        assert_eq!(
+            fixed(document),
+            "---\nid: a\n---\n\nsee <https://example.com/a_(b)>. and `https://code` or\n[link](https://example.com)\n- <http://example.com/_x_>\n"
+        );
+    }
+
+    #[test]
+    fn test_unclosed_code_fence() {
+        let document = "# A\n\n````rust\nfn main() {}\n";
+        assert_eq!(rules(document), vec![LintRule::UnclosedCodeFence]);
+        assert_eq!(fixed(document), "# A\n\n````rust\nfn main() {}\n````\n");
+    }
+
+    #[test]
+    fn test_footnotes() {
+        let document = "a[^2] b[^x] c[^1] d[^2] `[^y]`\n\n[^1]: one\n[^2]: two\n[^note]: unused\n";
+        assert_eq!(
+            rules(document),
+            vec![
+                LintRule::FootnoteNumbering,
+                LintRule::UndefinedFootnote,
+                LintRule::FootnoteNumbering,
+                LintRule::FootnoteNumbering,
+                LintRule::FootnoteNumbering,
+                LintRule::FootnoteNumbering,
+                LintRule::UnusedFootnote,
+            ]
+        );
+        assert_eq!(
+            fixed(document),
+            "a[^1] b c[^2] d[^1] `[^y]`\n\n[^2]: one\n[^1]: two\n"
+        );
+
+        let document = "a[^1] b[^2]\n\n[^1]: one\n[^2]: two\n";
+        assert_eq!(rules(document), vec![]);
+    }
+
+    #[test]
+    fn test_frontmatter_key_order() {
+        let document = "---\ntitle: A\nid: a\n---\n# A\n";
+        assert_eq!(rules(document), vec![LintRule::FrontmatterKeyOrder]);
+        assert_eq!(fixed(document), "---\nid: a\ntitle: A\n---\n# A\n");
+    }
+
+    #[test]
+    fn test_broken_anchor() {
+        let document = "# Next actions\n\n## Setup {#install}\n\nsee [a](#next-steps), [b](#install), [[#Next Steps|c]], [[#next actions]], [[#Setup]] and [d](#gone)\n";
+        assert_eq!(
+            rules(document),
+            vec![
+                LintRule::BrokenAnchor,
+                LintRule::BrokenAnchor,
+                LintRule::BrokenAnchor
+            ]
+        );
+        assert_eq!(
+            fixed(document),
+            "# Next actions\n\n## Setup {#install}\n\nsee [a](#next-actions), [b](#install), [[#Next actions|c]], [[#next actions]], [[#Setup]] and [d](#gone)\n"
+        );
+    }
+}
@@ -0,0 +1,80 @@
+//! Locked notes, published or canonical notes zet does not modify.
+//!
+//! A note is locked by `locked: true` in its frontmatter, or by its path
+//! being listed in .zet/locked, one path relative to the collection root per
+//! line, where a directory locks every note below it. Formatting, lint
+//! fixes, meta and tag edits skip locked notes, renames refuse to move them
+//! or to rewrite the links in them.
+
+use std::path::{Path, PathBuf};
+
+use crate::core::locked_list_file;
+use crate::core::parser::{FrontMatterFormat, FrontMatterParser};
+use crate::result::Result;
+
+pub const LOCKED_KEY: &str = "locked";
+
+#[derive(Debug, Clone)]
+pub struct Locks {
+    /// the paths listed in .zet/locked
+    listed: Vec<PathBuf>,
+    format: FrontMatterFormat,
+}
+
+impl Locks {
+    /// The locks of the collection at `root`, whose frontmatter is in
+    /// `format`
+    pub fn load(root: &Path, format: FrontMatterFormat) -> Result<Self> {
+        let file = locked_list_file(root);
+        let listed = match file.is_file() {
+            true => std::fs::read_to_string(&file)?
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(|line| root.join(line.trim_matches('/')))
+                .collect(),
+            false => Vec::new(),
+        };
+        Ok(Self { listed, format })
+    }
+
+    /// Whether the note at `path`, whose content is `document`, is locked
+    pub fn is_locked(&self, path: &Path, document: &str) -> bool {
+        if self.listed.iter().any(|listed| path.starts_with(listed)) {
+            return true;
+        }
+        let (frontmatter, _) = FrontMatterParser::new(self.format).parse(document.to_owned());
+        frontmatter.is_some_and(|data| data.get(LOCKED_KEY) == Some(&serde_json::Value::Bool(true)))
+    }
+
+    /// Whether the note at `path` is locked, reading its content
+    pub fn is_file_locked(&self, path: &Path) -> Result<bool> {
+        Ok(self.is_locked(path, &std::fs::read_to_string(path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_locked() {
+        let root = assert_fs::TempDir::new().unwrap();
+        std::fs::create_dir_all(root.path().join(".zet")).unwrap();
+        std::fs::write(
+            root.path().join(".zet/locked"),
+            "# published\npublished/\nreadme.md\n",
+        )
+        .unwrap();
+        let locks = Locks::load(root.path(), FrontMatterFormat::Yaml).unwrap();
+        let locked =
+            |path: &str, document: &str| locks.is_locked(&root.path().join(path), document);
+
+        assert!(locked("published/post.md", "# Post\n"));
+        assert!(locked("readme.md", "# Readme\n"));
+        assert!(!locked("published-drafts/post.md", "# Post\n"));
+        assert!(locked("a.md", "---\nlocked: true\n---\n# A\n"));
+        assert!(!locked("a.md", "---\nlocked: false\n---\n# A\n"));
+        assert!(!locked("a.md", "# A\n\nlocked: true\n"));
+    }
+}
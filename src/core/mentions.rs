@@ -0,0 +1,147 @@
+//! Mentions of people, `@name`.
+//!
+//! The people of a collection are the notes of the group named by
+//! `mentions.group`, `people` by default, or of the directory of that name
+//! when no such group is configured. A mention names a person by the file
+//! name of their note, the slug of its title or one of its aliases, ignoring
+//! case. Mentions are indexed by name and resolved when they are queried.
+
+use std::path::{Path, PathBuf};
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::core::db::DbList;
+use crate::core::extract_aliases_from_frontmatter;
+use crate::core::slug::slugify;
+use crate::core::types::document::{Document, DocumentId};
+use crate::result::Result;
+
+/// The `[mentions]` section of the configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MentionsConfig {
+    /// the group, or directory, of the notes of people
+    pub group: String,
+}
+
+impl Default for MentionsConfig {
+    fn default() -> Self {
+        Self {
+            group: "people".to_owned(),
+        }
+    }
+}
+
+/// A person, a note of the people group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Person {
+    pub id: DocumentId,
+    pub title: String,
+    /// the name a new mention of the person is written with, the file name
+    /// of their note
+    pub handle: String,
+    /// the names the person is mentioned by
+    pub names: Vec<String>,
+}
+
+impl Person {
+    /// Whether `name` names the person
+    pub fn is_named(&self, name: &str) -> bool {
+        self.names.iter().any(|n| n.eq_ignore_ascii_case(name))
+    }
+}
+
+/// The directories of the notes of people
+fn directories(root: &Path, config: &Config) -> Vec<PathBuf> {
+    let group = &config.mentions.group;
+    match config.group.get(group) {
+        Some(group) => group.directories.iter().map(|d| root.join(d)).collect(),
+        None => vec![root.join(group)],
+    }
+}
+
+/// The people of the collection at `root`
+pub fn people(root: &Path, config: &Config, db: &Connection) -> Result<Vec<Person>> {
+    let directories = directories(root, config);
+    let mut people: Vec<Person> = Document::list(db)?
+        .into_iter()
+        .filter(|d| directories.iter().any(|dir| d.path.0.starts_with(dir)))
+        .map(|d| {
+            let handle = d
+                .path
+                .0
+                .file_stem()
+                .map_or_else(|| d.id.0.clone(), |s| s.to_string_lossy().into_owned());
+            let mut names = vec![handle.clone(), slugify(&d.title)];
+            names.extend(extract_aliases_from_frontmatter(&d.data));
+            names.dedup();
+            Person {
+                id: d.id,
+                title: d.title,
+                handle,
+                names,
+            }
+        })
+        .collect();
+    people.sort_by(|a, b| a.handle.cmp(&b.handle));
+    Ok(people)
+}
+
+/// The person named `name`, preferring the one with it as handle
+pub fn resolve<'a>(people: &'a [Person], name: &str) -> Option<&'a Person> {
+    let name = name.trim_start_matches('@');
+    people
+        .iter()
+        .find(|p| p.handle.eq_ignore_ascii_case(name))
+        .or_else(|| people.iter().find(|p| p.is_named(name)))
+}
+
+/// The start of the mention being written at the end of `line`, the
+/// offset of its `@`
+pub fn partial_mention(line: &str) -> Option<usize> {
+    let start = line.rfind('@')?;
+    let name = &line[start + 1..];
+    let before = line[..start].chars().next_back();
+    let valid = !before.is_some_and(|c| c.is_alphanumeric() || "@/._-".contains(c))
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || "._-".contains(c));
+    valid.then_some(start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let person = |handle: &str, title: &str, aliases: &[&str]| Person {
+            id: DocumentId(format!("people/{handle}")),
+            title: title.to_owned(),
+            handle: handle.to_owned(),
+            names: [handle, &slugify(title)]
+                .into_iter()
+                .map(str::to_owned)
+                .chain(aliases.iter().map(|a| a.to_string()))
+                .collect(),
+        };
+        let people = vec![
+            person("alice", "Alice Liddell", &["ali"]),
+            person("bob", "Bob", &[]),
+        ];
+        assert_eq!(resolve(&people, "@Alice").unwrap().handle, "alice");
+        assert_eq!(resolve(&people, "alice-liddell").unwrap().handle, "alice");
+        assert_eq!(resolve(&people, "ali").unwrap().handle, "alice");
+        assert!(resolve(&people, "carol").is_none());
+    }
+
+    #[test]
+    fn test_partial_mention() {
+        assert_eq!(partial_mention("ask @al"), Some(4));
+        assert_eq!(partial_mention("@"), Some(0));
+        assert_eq!(partial_mention("mail a@b"), None);
+        assert_eq!(partial_mention("ask @al about"), None);
+    }
+}
@@ -0,0 +1,302 @@
+//! Importing the notes and assets of another collection.
+//!
+//! The notes and assets of the other collection keep their path relative to
+//! its root, below a directory of this collection. A note collides when its
+//! file or its id is taken in this collection, an asset when its file is
+//! taken by a different file. Depending on the strategy, colliding notes and
+//! assets fail the merge, are skipped, or are imported with a prefix on their
+//! file name. Links between the imported notes are rewritten to the new ids.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use color_eyre::eyre::eyre;
+use rusqlite::Connection;
+use serde_json::Value;
+
+use crate::config::Config;
+use crate::core::ID_KEY;
+use crate::core::assets::{asset_paths, find_references};
+use crate::core::db::DbList;
+use crate::core::frontmatter::set_frontmatter_value;
+use crate::core::parser::FrontMatterFormat;
+use crate::core::rename::rewrite_resolved_links;
+use crate::core::types::document::{Document, DocumentId};
+use crate::result::Result;
+
+/// What to do with the notes and assets colliding with this collection
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CollisionStrategy {
+    /// import nothing if anything collides
+    #[default]
+    Fail,
+    /// keep the notes and assets of this collection, links to a skipped note
+    /// pointing to the note taking its id
+    Skip,
+    /// import colliding notes and assets with `<prefix>-` prepended to their
+    /// file name, and to the id of notes with an id in their frontmatter
+    Prefix,
+}
+
+/// A note of the other collection
+#[derive(Debug, Clone)]
+pub struct MergeNote {
+    pub document: Document,
+    /// where the note is imported to, `None` if it is skipped
+    pub to: Option<PathBuf>,
+    pub new_id: DocumentId,
+    pub collides: bool,
+}
+
+/// An asset of the other collection
+#[derive(Debug, Clone)]
+pub struct MergeAsset {
+    pub from: PathBuf,
+    /// where the asset is imported to, `None` if it is skipped or this
+    /// collection has the same file
+    pub to: Option<PathBuf>,
+    pub collides: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct MergeOptions {
+    /// the directory of this collection the other one is merged into
+    pub directory: PathBuf,
+    pub strategy: CollisionStrategy,
+    pub prefix: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct MergePlan {
+    pub notes: Vec<MergeNote>,
+    pub assets: Vec<MergeAsset>,
+    other: PathBuf,
+    /// the frontmatter format of the other collection
+    format: FrontMatterFormat,
+    /// the ids of the other collection, which its links resolve to
+    ids: Vec<DocumentId>,
+    prefix: String,
+}
+
+/// Where the notes and assets of the collection at `other` go when merged
+/// into the collection at `root`
+pub fn plan(
+    root: &Path,
+    config: &Config,
+    db: &Connection,
+    other: &Path,
+    other_config: &Config,
+    other_db: &Connection,
+    options: &MergeOptions,
+) -> Result<MergePlan> {
+    let MergeOptions {
+        directory,
+        strategy,
+        prefix,
+    } = options;
+    let strategy = *strategy;
+    let mut documents = Document::list(other_db)?;
+    let ids: Vec<DocumentId> = documents.iter().map(|d| d.id.clone()).collect();
+    documents.sort_by(|a, b| a.path.0.cmp(&b.path.0));
+
+    let mut taken: HashSet<DocumentId> = Document::list(db)?.into_iter().map(|d| d.id).collect();
+
+    let mut notes = Vec::new();
+    for document in documents {
+        let relative = document
+            .path
+            .0
+            .strip_prefix(other)
+            .map_err(|_| eyre!("{:?} is not in {:?}", document.path.0, other))?;
+        let fixed_id = document.data.get(ID_KEY).is_some();
+        let new_id = |to: &Path, id: &DocumentId| match fixed_id {
+            true => id.clone(),
+            false => config.id_scheme(root, to).path_to_id(root, to),
+        };
+
+        let mut to = directory.join(relative);
+        let mut id = new_id(&to, &document.id);
+        let collides = to.exists() || taken.contains(&id);
+        let mut skipped = false;
+        if collides {
+            match strategy {
+                CollisionStrategy::Fail => {}
+                CollisionStrategy::Skip => skipped = true,
+                CollisionStrategy::Prefix => {
+                    to = prefixed_path(&to, prefix);
+                    id = new_id(&to, &DocumentId(prefixed(&document.id.0, prefix)));
+                    if to.exists() || taken.contains(&id) {
+                        return Err(eyre!(
+                            "{:?} collides with this collection, even prefixed as {}",
+                            relative,
+                            id.0
+                        ));
+                    }
+                }
+            }
+        }
+        if !skipped {
+            taken.insert(id.clone());
+        }
+        notes.push(MergeNote {
+            document,
+            to: (!skipped).then_some(to),
+            new_id: id,
+            collides,
+        });
+    }
+
+    let mut assets = Vec::new();
+    for from in asset_paths(other, &other_config.assets) {
+        let relative = from.strip_prefix(other)?;
+        let mut to = directory.join(relative);
+        let collides = to.exists() && !same_content(&from, &to)?;
+        let skipped = match (to.exists(), collides, strategy) {
+            (false, _, _) => false,
+            (true, false, _) => true,
+            (true, true, CollisionStrategy::Skip) => true,
+            (true, true, CollisionStrategy::Fail) => false,
+            (true, true, CollisionStrategy::Prefix) => {
+                to = prefixed_path(&to, prefix);
+                if to.exists() {
+                    return Err(eyre!(
+                        "{:?} collides with this collection, even prefixed as {:?}",
+                        relative,
+                        to
+                    ));
+                }
+                false
+            }
+        };
+        assets.push(MergeAsset {
+            from,
+            to: (!skipped).then_some(to),
+            collides,
+        });
+    }
+
+    if strategy == CollisionStrategy::Fail {
+        let collisions: Vec<String> = notes
+            .iter()
+            .filter(|n| n.collides)
+            .map(|n| n.document.path.0.display().to_string())
+            .chain(
+                assets
+                    .iter()
+                    .filter(|a| a.collides)
+                    .map(|a| a.from.display().to_string()),
+            )
+            .collect();
+        if !collisions.is_empty() {
+            return Err(eyre!(
+                "{} files collide with this collection, skip or prefix them with --on-collision:\n{}",
+                collisions.len(),
+                collisions.join("\n")
+            ));
+        }
+    }
+
+    Ok(MergePlan {
+        notes,
+        assets,
+        other: other.to_owned(),
+        format: other_config.front_matter_format,
+        ids,
+        prefix: prefix.clone(),
+    })
+}
+
+impl MergePlan {
+    /// Copy the notes and assets, rewriting the links and asset references of
+    /// the notes
+    pub fn apply(&self) -> Result<()> {
+        let new_ids: HashMap<DocumentId, DocumentId> = self
+            .notes
+            .iter()
+            .map(|n| (n.document.id.clone(), n.new_id.clone()))
+            .collect();
+        let prefixed_assets: HashSet<&Path> = self
+            .assets
+            .iter()
+            .filter(|a| a.collides && a.to.is_some())
+            .map(|a| a.from.as_path())
+            .collect();
+
+        for note in &self.notes {
+            let Some(to) = &note.to else {
+                continue;
+            };
+            let from = &note.document.path.0;
+            let mut content = std::fs::read_to_string(from)?;
+
+            // asset references first, their ranges are in the original content
+            let mut references = find_references(&self.other, from, &content, &prefixed_assets);
+            references.sort_by_key(|r| std::cmp::Reverse(r.range.as_ref().map(|r| r.start)));
+            for reference in references {
+                if let Some(range) = reference.range {
+                    content.replace_range(range, &prefixed(&reference.target, &self.prefix));
+                }
+            }
+            if let Some(rewritten) = rewrite_resolved_links(&content, &self.ids, &new_ids)? {
+                content = rewritten;
+            }
+            if note.document.data.get(ID_KEY).is_some() && note.new_id != note.document.id {
+                content = set_frontmatter_value(
+                    &content,
+                    self.format,
+                    ID_KEY,
+                    &Value::String(note.new_id.0.clone()),
+                )?;
+            }
+
+            if let Some(parent) = to.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(to, content)?;
+        }
+
+        for asset in &self.assets {
+            let Some(to) = &asset.to else {
+                continue;
+            };
+            if let Some(parent) = to.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::copy(&asset.from, to)?;
+        }
+        Ok(())
+    }
+}
+
+/// `name` with `<prefix>-` prepended to its last path segment
+fn prefixed(name: &str, prefix: &str) -> String {
+    match name.rsplit_once('/') {
+        Some((directory, name)) => format!("{directory}/{prefix}-{name}"),
+        None => format!("{prefix}-{name}"),
+    }
+}
+
+fn prefixed_path(path: &Path, prefix: &str) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{prefix}-{name}"))
+}
+
+fn same_content(a: &Path, b: &Path) -> Result<bool> {
+    Ok(std::fs::read(a)? == std::fs::read(b)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefixed() {
+        assert_eq!(prefixed("b", "work"), "work-b");
+        assert_eq!(prefixed("projects/b", "work"), "projects/work-b");
+        assert_eq!(
+            prefixed_path(Path::new("/notes/img/a.png"), "work"),
+            Path::new("/notes/img/work-a.png")
+        );
+    }
+}
@@ -0,0 +1,295 @@
+//! Merging notes into one: their bodies follow each other, each under a top
+//! level heading of its title, and their frontmatter is combined.
+//!
+//! The keys of the first note are kept and those only the others have are
+//! added. The tags and aliases are joined, the titles of the merged notes
+//! becoming aliases, and the earliest `created` and `date` are kept. Links to
+//! the merged notes are pointed to the new one.
+
+use std::path::{Path, PathBuf};
+
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use serde_json::Value;
+
+use crate::core::compile::shift_headings;
+use crate::core::frontmatter::{body_offset, set_frontmatter_value};
+use crate::core::parser::{DocumentParserOptions, FrontMatterFormat, FrontMatterParser};
+use crate::core::rename::{LinkEdit, edit_links};
+use crate::core::slug::heading_anchor;
+use crate::core::timeline::DATE_KEY;
+use crate::core::types::document::DocumentId;
+use crate::core::{
+    ALIASES_KEY, ID_KEY, LinkTargets, TAGS_KEY, TITLE_KEY, collection_config_dir, relative_link,
+};
+use crate::result::Result;
+
+/// The keys of the dates a note was written at, the earliest being kept
+const CREATED_KEYS: [&str; 2] = ["created", DATE_KEY];
+
+/// .zet/trash
+pub fn trash_dir(root: &Path) -> PathBuf {
+    collection_config_dir(root).join("trash")
+}
+
+/// A note to merge
+#[derive(Debug, Clone)]
+pub struct MergedNote {
+    pub id: DocumentId,
+    pub title: String,
+    /// the document, frontmatter included
+    pub content: String,
+}
+
+/// The note `id` titled `title` of `notes`, in order
+pub fn merge_notes(
+    notes: &[MergedNote],
+    id: &DocumentId,
+    title: &str,
+    format: FrontMatterFormat,
+) -> Result<String> {
+    let parser = FrontMatterParser::new(format);
+    let mut frontmatters = Vec::with_capacity(notes.len());
+    let mut body = String::new();
+    for note in notes {
+        let (frontmatter, note_body) = parser.parse(note.content.clone());
+        frontmatters.extend(frontmatter.and_then(|f| f.as_object().cloned()));
+        if !body.is_empty() {
+            body.push('\n');
+        }
+        let note_body = without_title(&note_body, &note.title);
+        body.push_str(&format!("# {}\n", note.title.trim()));
+        if !note_body.trim().is_empty() {
+            body.push_str(&format!("\n{}\n", shift_headings(note_body.trim(), 2)));
+        }
+    }
+
+    // the frontmatter of the first note, as it is written, and its values
+    let first = notes.first().map_or("", |note| &note.content);
+    let mut merged = format!("{}{}", &first[..body_offset(first)], body);
+    let mut set = |key: &str, value: Value| -> Result<()> {
+        merged = set_frontmatter_value(&merged, format, key, &value)?;
+        Ok(())
+    };
+    set(ID_KEY, Value::String(id.0.clone()))?;
+    set(TITLE_KEY, Value::String(title.to_owned()))?;
+
+    let values = |key: &'static str| frontmatters.iter().filter_map(move |f| f.get(key));
+    let mut tags: Vec<Value> = Vec::new();
+    for tag in values(TAGS_KEY).filter_map(Value::as_array).flatten() {
+        if !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+    if !tags.is_empty() {
+        set(TAGS_KEY, Value::Array(tags))?;
+    }
+
+    let mut aliases: Vec<Value> = Vec::new();
+    let names = values(ALIASES_KEY)
+        .flat_map(|aliases| match aliases {
+            Value::Array(aliases) => aliases.clone(),
+            alias => vec![alias.clone()],
+        })
+        .chain(notes.iter().map(|note| Value::String(note.title.clone())));
+    for alias in names {
+        if alias.as_str().is_some_and(|a| a != title) && !aliases.contains(&alias) {
+            aliases.push(alias);
+        }
+    }
+    if !aliases.is_empty() {
+        set(ALIASES_KEY, Value::Array(aliases))?;
+    }
+
+    for key in CREATED_KEYS {
+        // dates written alike compare as text, otherwise the first is kept
+        let earliest =
+            values(key).reduce(
+                |earliest, value| match (earliest.as_str(), value.as_str()) {
+                    (Some(e), Some(v)) if v.len() == e.len() && v < e => value,
+                    _ => earliest,
+                },
+            );
+        if let Some(earliest) = earliest {
+            set(key, earliest.clone())?;
+        }
+    }
+
+    // the keys only the notes after the first have
+    let first_keys = frontmatters.first().cloned().unwrap_or_default();
+    for frontmatter in frontmatters.iter().skip(1) {
+        for (key, value) in frontmatter {
+            let merged_key = [ID_KEY, TITLE_KEY, TAGS_KEY, ALIASES_KEY].contains(&key.as_str())
+                || CREATED_KEYS.contains(&key.as_str());
+            if !merged_key && !first_keys.contains_key(key) {
+                set(key, value.clone())?;
+            }
+        }
+    }
+    Ok(merged)
+}
+
+/// `body` without its first heading if it is `title`, the heading of its part
+/// in the merged note repeating it
+fn without_title<'a>(body: &'a str, title: &str) -> &'a str {
+    let mut events = Parser::new_ext(body, DocumentParserOptions::default().0).into_offset_iter();
+    let Some((Event::Start(Tag::Heading { .. }), _)) =
+        events.find(|(event, _)| matches!(event, Event::Start(_)))
+    else {
+        return body;
+    };
+    let mut text = String::new();
+    for (event, range) in events {
+        match event {
+            Event::Text(t) | Event::Code(t) => text.push_str(&t),
+            Event::End(TagEnd::Heading(_)) => {
+                return match text.trim().eq_ignore_ascii_case(title.trim()) {
+                    true => &body[range.end..],
+                    false => body,
+                };
+            }
+            _ => {}
+        }
+    }
+    body
+}
+
+/// Points the links to merged notes to the note they were merged into
+#[derive(Debug, Clone)]
+pub struct Redirect<'a> {
+    /// the merged notes and their titles
+    pub merged: &'a [(DocumentId, String)],
+    pub into: &'a DocumentId,
+    pub into_path: &'a Path,
+    /// the documents links resolved among before the merge
+    pub targets: &'a LinkTargets,
+}
+
+impl Redirect<'_> {
+    /// Rewrite the links to the merged notes of `document`, to be written at
+    /// `path`. Its links resolve as they did in the document `from`, and
+    /// `here` is the note it is now, the links of the merged notes to each
+    /// other pointing to their headings in the new note. Returns `None` if no
+    /// link was changed.
+    pub fn redirect(
+        &self,
+        document: &str,
+        path: &Path,
+        from: &DocumentId,
+        here: &DocumentId,
+    ) -> Result<Option<String>> {
+        let directory = path.parent().unwrap_or(Path::new(""));
+        edit_links(document, |link| {
+            let (note, fragment) = match link.target.split_once('#') {
+                Some((note, fragment)) => (note, Some(fragment)),
+                None => (link.target, None),
+            };
+            if note.is_empty() {
+                return None;
+            }
+            let to = self.targets.resolve(from, note, link.wiki)?;
+            let (_, title) = self.merged.iter().find(|(id, _)| id == to)?;
+            let replacement = match (here == self.into, fragment) {
+                (true, Some(fragment)) => format!("#{fragment}"),
+                (true, None) => format!("#{}", heading_anchor(title)),
+                (false, fragment) => {
+                    let target = match link.wiki {
+                        true => self.into.0.clone(),
+                        false => relative_link(directory, self.into_path),
+                    };
+                    match fragment {
+                        Some(fragment) => format!("{target}#{fragment}"),
+                        None => target,
+                    }
+                }
+            };
+            (replacement != link.target).then_some(LinkEdit::Target {
+                len: link.target.len(),
+                replacement,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_notes() {
+        let note = |id: &str, title: &str, content: &str| MergedNote {
+            id: DocumentId(id.to_owned()),
+            title: title.to_owned(),
+            content: content.to_owned(),
+        };
+        let notes = [
+            note(
+                "a",
+                "Apples",
+                "---\n# the fruit\ntitle: Apples\ntags: [fruit, red]\ncreated: 2024-05-01\n---\n# Apples\n\nred\n\n## Kinds\n\nmany\n",
+            ),
+            note(
+                "b",
+                "Pears",
+                "---\ntitle: Pears\ntags: [fruit, green]\ncreated: 2023-01-02\nsource: garden\n---\nPears are green.\n\n### Kinds\n\nfew\n",
+            ),
+        ];
+        assert_eq!(
+            merge_notes(
+                &notes,
+                &DocumentId("fruit".to_owned()),
+                "Fruit",
+                FrontMatterFormat::Yaml
+            )
+            .unwrap(),
+            "---\n# the fruit\ntitle: \"Fruit\"\ntags: [\"fruit\",\"red\",\"green\"]\ncreated: \"2023-01-02\"\nid: \"fruit\"\naliases: [\"Apples\",\"Pears\"]\nsource: \"garden\"\n---\n\
+             # Apples\n\nred\n\n## Kinds\n\nmany\n\n\
+             # Pears\n\nPears are green.\n\n## Kinds\n\nfew\n"
+        );
+    }
+
+    #[test]
+    fn test_redirect() {
+        let mut targets = LinkTargets::default();
+        for id in ["a", "b", "other"] {
+            targets.insert(
+                DocumentId(id.to_owned()),
+                PathBuf::from(format!("/notes/{id}.md")),
+            );
+        }
+        let [a, b, c, other] = ["a", "b", "c", "other"].map(|id| DocumentId(id.to_owned()));
+        let merged = [
+            (a.clone(), "Apples".to_owned()),
+            (b.clone(), "Pears".to_owned()),
+        ];
+        let redirect = Redirect {
+            merged: &merged,
+            into: &c,
+            into_path: Path::new("/notes/c.md"),
+            targets: &targets,
+        };
+        assert_eq!(
+            redirect
+                .redirect(
+                    "[[a]], [[b#Kinds|kinds]], [x](b.md) and [[other]]\n",
+                    Path::new("/notes/other.md"),
+                    &other,
+                    &other
+                )
+                .unwrap()
+                .unwrap(),
+            "[[c]], [[c#Kinds|kinds]], [x](c.md) and [[other]]\n"
+        );
+        assert_eq!(
+            redirect
+                .redirect(
+                    "[[b]] [[b#Kinds]] [[#Kinds]]\n",
+                    Path::new("/notes/c.md"),
+                    &a,
+                    &c
+                )
+                .unwrap()
+                .unwrap(),
+            "[[#pears]] [[#Kinds]] [[#Kinds]]\n"
+        );
+    }
+}
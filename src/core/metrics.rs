@@ -0,0 +1,127 @@
+//! Health metrics of a collection in the Prometheus text format
+//! <https://prometheus.io/docs/instrumenting/exposition_formats/>.
+
+use std::fmt::Write;
+
+use rusqlite::Connection;
+use sql_minifier::macros::minify_sql as sql;
+
+use crate::core::types::index_run::IndexRun;
+use crate::result::Result;
+
+/// The metrics of a collection, as of its last index
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metrics {
+    pub documents: usize,
+    /// links whose target is not a document of the collection
+    pub broken_links: usize,
+    /// tasks that are neither done nor cancelled
+    pub open_tasks: usize,
+    pub index_runs: usize,
+    pub last_index: Option<IndexRun>,
+}
+
+impl Metrics {
+    pub fn collect(db: &Connection) -> Result<Self> {
+        let count = |query: &str| -> Result<usize> { Ok(db.query_row(query, [], |r| r.get(0))?) };
+        Ok(Metrics {
+            documents: count(sql!("select count(*) from document"))?,
+            broken_links: count(sql!(
+                "select count(*) from document_link where to_id is null and url is null"
+            ))?,
+            open_tasks: count(sql!(
+                "select count(*) from document_task where status in ('todo', 'in_progress')"
+            ))?,
+            index_runs: IndexRun::count(db)?,
+            last_index: IndexRun::last(db)?,
+        })
+    }
+
+    /// The metrics in the Prometheus text format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, value: f64| {
+            let _ = writeln!(out, "# HELP zet_{name} {help}");
+            let _ = writeln!(out, "# TYPE zet_{name} {kind}");
+            let _ = writeln!(out, "zet_{name} {value}");
+        };
+        metric(
+            "documents",
+            "gauge",
+            "The number of indexed documents.",
+            self.documents as f64,
+        );
+        metric(
+            "broken_links",
+            "gauge",
+            "The number of links not pointing to a document.",
+            self.broken_links as f64,
+        );
+        metric(
+            "open_tasks",
+            "gauge",
+            "The number of tasks neither done nor cancelled.",
+            self.open_tasks as f64,
+        );
+        metric(
+            "index_runs_total",
+            "counter",
+            "The number of completed index runs.",
+            self.index_runs as f64,
+        );
+        if let Some(run) = &self.last_index {
+            metric(
+                "index_duration_seconds",
+                "gauge",
+                "The duration of the last index run.",
+                run.duration().as_secs_f64(),
+            );
+            metric(
+                "index_changes",
+                "gauge",
+                "The number of documents changed by the last index run.",
+                run.changes as f64,
+            );
+            metric(
+                "last_index_timestamp_seconds",
+                "gauge",
+                "The unix time the last index run finished.",
+                run.finished.as_millisecond() as f64 / 1000.0,
+            );
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jiff::Timestamp;
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics {
+            documents: 3,
+            broken_links: 1,
+            open_tasks: 2,
+            index_runs: 4,
+            last_index: Some(IndexRun {
+                id: 4,
+                started: Timestamp::from_millisecond(1_000).unwrap(),
+                finished: Timestamp::from_millisecond(1_500).unwrap(),
+                changes: 1,
+            }),
+        };
+        let rendered = metrics.render();
+        assert!(rendered.starts_with(
+            "# HELP zet_documents The number of indexed documents.\n# TYPE zet_documents gauge\nzet_documents 3\n"
+        ));
+        assert!(rendered.contains("\nzet_index_runs_total 4\n"));
+        assert!(rendered.contains("\nzet_index_duration_seconds 0.5\n"));
+        assert!(rendered.ends_with("\nzet_last_index_timestamp_seconds 1.5\n"));
+
+        // a collection that was never indexed has no index timings
+        let rendered = Metrics::default().render();
+        assert!(!rendered.contains("index_duration"));
+    }
+}
@@ -1,30 +1,88 @@
+pub mod assets;
+pub mod backlinks;
+pub mod backup;
+pub mod board;
+pub mod capture;
+pub mod compile;
 pub mod date_parser;
 pub mod db;
+#[cfg(feature = "document-export")]
+pub mod document_export;
+pub mod editor;
+pub mod email;
+pub mod extract;
+pub mod fetch;
+pub mod filename;
+pub mod flavor;
+pub mod format;
+pub mod frontmatter;
+pub mod fuzzy;
+pub mod graph;
+pub mod hooks;
+pub mod html;
+pub mod ics;
+pub mod id;
+pub mod index_hook;
+pub mod index_journal;
+pub mod journal;
+pub mod lint;
+pub mod lock;
+pub mod mentions;
+pub mod merge;
+pub mod merge_notes;
+pub mod metrics;
+pub mod opml;
+pub mod outline;
+pub mod overlay;
 pub mod parser;
+#[cfg(feature = "wasm-plugins")]
+pub mod plugin;
 pub mod query;
+pub mod quotes;
+pub mod read;
+pub mod readlist;
+pub mod redact;
+pub mod rename;
+pub mod rollup;
+pub mod sequence;
 pub mod slug;
+pub mod snapshot;
+pub mod snippets;
+pub mod split;
+pub mod split_note;
+pub mod table;
+pub mod tags;
 pub mod template_engine;
+pub mod timeline;
+pub mod transclusion;
 pub mod types;
+pub mod uri;
+pub mod url;
+pub mod words;
 
+use crate::core::parser::arena::{Ast, NodeId};
 use crate::core::parser::ast_nodes::{self};
 
-use crate::core::db::{DB, DbList};
+use crate::config::IndexConfig;
+use crate::core::db::DbList;
 use crate::core::types::document::DocumentId;
-use crate::{CONFIG_NAME, preamble::*};
+use crate::{APP_ENV_PREFIX, CONFIG_NAME, preamble::*};
+use rusqlite::Connection;
 use std::path::Path;
 use std::path::PathBuf;
 
 use crate::core::types::document::CreatedTimestamp;
-use crate::core::types::document::Document;
 use crate::core::types::document::DocumentPath;
+use crate::core::types::document::DocumentSummary;
 use crate::core::types::document::ModifiedTimestamp;
 // use ignore::{DirEntry, WalkBuilder};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use twox_hash::XxHash32;
 
-use color_eyre::eyre::eyre;
 use ignore::{DirEntry, WalkBuilder};
+use normalize_path::NormalizePath;
+use sql_minifier::macros::minify_sql as sql;
 
 ////////////////////////////////////////////////////////////
 // Paths
@@ -54,43 +112,97 @@ pub fn collection_db_file(root: &Path) -> PathBuf {
     collection_config_dir(root).join(DB_NAME)
 }
 
+/// .zet/db.lock
+pub fn collection_db_lock_file(root: &Path) -> PathBuf {
+    collection_config_dir(root).join(DB_LOCK_NAME)
+}
+
+/// .zet/index.journal
+pub fn index_journal_file(root: &Path) -> PathBuf {
+    collection_config_dir(root).join(INDEX_JOURNAL_NAME)
+}
+
 /// .zet/config.toml
 pub fn collection_config_file(root: &Path) -> PathBuf {
     collection_config_dir(root).join(CONFIG_NAME)
 }
 
-/// from CWD, walk up the directory tree until a directory containing .zet
-/// is found or / is reached
-pub fn resolve_root(dir: Option<PathBuf>) -> Result<PathBuf> {
+/// .zet/locked
+pub fn locked_list_file(root: &Path) -> PathBuf {
+    collection_config_dir(root).join("locked")
+}
+
+/// The environment variable naming the collection root, `ZET_ROOT`
+pub fn root_env_var() -> String {
+    format!("{APP_ENV_PREFIX}ROOT")
+}
+
+/// Why no collection root could be resolved
+#[derive(Debug, thiserror::Error)]
+pub enum RootError {
+    #[error("{0:?} does not contain a .zet directory")]
+    NotACollection(PathBuf),
+    #[error("no .zet directory found in {start:?} or its parents up to {stop:?}")]
+    NotFound { start: PathBuf, stop: PathBuf },
+    #[error("could not resolve the collection root: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// The root of the collection: `dir` when given, else the directory in
+/// $ZET_ROOT, else the first directory containing .zet walking up from the
+/// CWD. The walk stops at $HOME and at filesystem boundaries.
+pub fn resolve_root(dir: Option<PathBuf>) -> std::result::Result<PathBuf, RootError> {
+    let dir = dir.or_else(|| std::env::var_os(root_env_var()).map(PathBuf::from));
     if let Some(dir) = dir {
-        if !collection_config_dir(&dir).is_dir() {
-            log::error!("provided root dir does not contain a .zet directory!");
-            return Err(eyre!("collection not found!"));
-        } else {
-            return Ok(dir);
+        let dir = std::path::absolute(dir)?;
+        if !is_collection(&dir)? {
+            return Err(RootError::NotACollection(dir));
         }
+        return Ok(dir);
     }
 
-    let mut dir = std::path::absolute(std::env::current_dir()?)?;
-    log::debug!("resolving zet root directory, starting from {:?}", dir);
-    // check if dir contains .zet or if / have been reached
-    while !collection_config_dir(&dir).is_dir() {
-        dir = match dir.parent() {
-            Some(p) => p.to_owned(),
-            None => {
-                log::error!("{:?} had no parent!", dir);
-                return Err(eyre!("no parent found"));
+    let start = std::path::absolute(std::env::current_dir()?)?;
+    log::debug!("resolving zet root directory, starting from {:?}", start);
+    let home = directories::BaseDirs::new().map(|dirs| dirs.home_dir().to_owned());
+    let start_device = device(&start)?;
+    let mut dir = start.as_path();
+    loop {
+        if is_collection(dir)? {
+            log::debug!("zet root directory resolved to {:?}", dir);
+            return Ok(dir.to_owned());
+        }
+        let at_home = home.as_deref() == Some(dir);
+        match dir.parent() {
+            Some(parent) if !at_home && device(parent)? == start_device => dir = parent,
+            _ => {
+                return Err(RootError::NotFound {
+                    stop: dir.to_owned(),
+                    start,
+                });
             }
         }
     }
+}
 
-    if !collection_config_dir(&dir).is_dir() {
-        log::error!("no .zet directory found");
-        return Err(eyre!("collection not found!"));
+/// Whether `dir` contains a .zet directory
+pub fn is_collection(dir: &Path) -> std::io::Result<bool> {
+    match std::fs::metadata(collection_config_dir(dir)) {
+        Ok(metadata) => Ok(metadata.is_dir()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
     }
-    log::debug!("zet root directory resolved to {:?}", dir);
+}
+
+/// The filesystem `path` is on
+#[cfg(unix)]
+fn device(path: &Path) -> std::io::Result<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Ok(std::fs::metadata(path)?.dev())
+}
 
-    Ok(dir)
+#[cfg(not(unix))]
+fn device(_path: &Path) -> std::io::Result<u64> {
+    Ok(0)
 }
 
 pub fn is_filetype(entry: &DirEntry, ext: &str) -> bool {
@@ -118,7 +230,11 @@ pub fn workspace_paths(root: &Path) -> Result<Vec<PathBuf>> {
 const HASH_SEED: u32 = 42;
 
 pub fn hash(content: &str) -> u32 {
-    XxHash32::oneshot(HASH_SEED, content.as_bytes())
+    hash_bytes(content.as_bytes())
+}
+
+pub fn hash_bytes(content: &[u8]) -> u32 {
+    XxHash32::oneshot(HASH_SEED, content)
 }
 
 pub type NewDocuments = Vec<DocumentPath>;
@@ -137,11 +253,53 @@ pub type CollectionStatus = (NewDocuments, ModifiedDocuments, DeletedDocuments);
 /// - are there any new documents?
 /// - are there any documents that we need to reparse?
 /// - are there any documents that have been removed?
-pub fn collection_status(root: &Path, db: &DB) -> CollectionStatus {
-    // collect paths of document from root
-    let disk_paths: Vec<PathBuf> = workspace_paths(root).unwrap();
+pub fn collection_status(root: &Path, db: &Connection, config: &IndexConfig) -> CollectionStatus {
+    status(root, db, config, None)
+}
+
+/// Like [`collection_status`], for the documents at `paths` only, e.g. a note
+/// that was just written. The other documents are left as they are.
+pub fn paths_status(
+    root: &Path,
+    db: &Connection,
+    config: &IndexConfig,
+    paths: &[PathBuf],
+) -> CollectionStatus {
+    let only: HashSet<&Path> = paths.iter().map(PathBuf::as_path).collect();
+    status(root, db, config, Some(&only))
+}
+
+fn status(
+    root: &Path,
+    db: &Connection,
+    config: &IndexConfig,
+    only: Option<&HashSet<&Path>>,
+) -> CollectionStatus {
+    let included = |path: &Path| only.is_none_or(|only| only.contains(path));
+    // collect paths of document from root, files too large to be indexed
+    // are left out
+    let disk_paths: Vec<PathBuf> = workspace_paths(root)
+        .unwrap()
+        .into_iter()
+        .filter(|path| included(path))
+        .filter(|path| {
+            let skipped = read::is_skipped(path, config);
+            if skipped {
+                log::warn!(
+                    "{:?} is larger than {} bytes and skipped",
+                    path,
+                    config.max_file_size
+                );
+            }
+            !skipped
+        })
+        .collect();
 
-    let db_documents: Vec<Document> = Document::list(db).unwrap();
+    let db_documents: Vec<DocumentSummary> = DocumentSummary::list(db)
+        .unwrap()
+        .into_iter()
+        .filter(|d| included(&d.path.0))
+        .collect();
 
     // we start by figuring out documents that have been removed, which are new
     // and which that we need to investigate further.
@@ -221,7 +379,9 @@ pub fn collection_status(root: &Path, db: &DB) -> CollectionStatus {
                 u32,
                 &u32,
             )> {
-                let content = std::fs::read_to_string(&path.0)?;
+                let content = read::read_document(&path.0, config)?
+                    .content
+                    .unwrap_or_default();
                 let current = crate::core::hash(&content);
                 let previous = &db_documents[index].hash;
                 Ok((index, path, modified, created, current, previous))
@@ -241,15 +401,201 @@ pub fn collection_status(root: &Path, db: &DB) -> CollectionStatus {
 // Path manipulation functions
 ////////////////////////////////////////////////////////////
 
+/// Whether the filesystem compares paths case-insensitively, as on windows
+pub const CASE_INSENSITIVE_PATHS: bool = cfg!(windows);
+
+/// `path` relative to `root`, comparing their components case-insensitively
+/// where the platform does, `None` if `path` is not within `root`
+pub fn relative_path(root: &Path, path: &Path) -> Option<PathBuf> {
+    strip_root(root, path, CASE_INSENSITIVE_PATHS)
+}
+
+fn strip_root(root: &Path, path: &Path, ignore_case: bool) -> Option<PathBuf> {
+    let mut components = path.components();
+    for root_component in root.components() {
+        let component = components.next()?;
+        let equal = match ignore_case {
+            true => component
+                .as_os_str()
+                .eq_ignore_ascii_case(root_component.as_os_str()),
+            false => component == root_component,
+        };
+        if !equal {
+            return None;
+        }
+    }
+    Some(components.as_path().to_owned())
+}
+
+/// The relative `path` with `/` separators, as ids and links are written on
+/// every platform
+pub fn path_to_slash(path: &Path) -> String {
+    let parts: Vec<_> = path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+    parts.join("/")
+}
+
 /// Given a path to a document within the collection, we compute its id.
 pub fn path_to_id(root: &Path, path: &Path) -> DocumentId {
-    let mut path = path.to_owned();
-    path.set_extension("");
-    let path = path.strip_prefix(root).unwrap();
-    let id = crate::core::slug::slugify(path.to_str().unwrap());
+    let path = relative_path(root, path).unwrap_or_else(|| path.to_owned());
+    let id = crate::core::slug::slugify(path_to_slash(&path.with_extension("")));
     DocumentId(id)
 }
 
+/// The document among `ids` a link to `to` points to, the one with the
+/// longest id `to` ends with. A link to a filename starting with a generated
+/// id points to the document of that id. `\` separators are read as `/`, and
+/// the case of `to` is ignored where the platform ignores the case of paths.
+pub fn resolve_target<'a>(ids: &'a [DocumentId], to: &str) -> Option<&'a DocumentId> {
+    let to = normalize_target(to, CASE_INSENSITIVE_PATHS);
+    let longest_suffix = |to: &str| {
+        ids.iter()
+            .filter(|id| to.ends_with(&id.0))
+            .max_by_key(|id| id.0.len())
+    };
+    longest_suffix(&to).or_else(|| longest_suffix(crate::core::id::strip_title(&to)?))
+}
+
+/// The link target `to` with `/` separators, lowercased when `ignore_case`
+fn normalize_target(to: &str, ignore_case: bool) -> std::borrow::Cow<'_, str> {
+    let to = match to.contains('\\') {
+        true => to.replace('\\', "/").into(),
+        false => std::borrow::Cow::Borrowed(to),
+    };
+    match ignore_case && to.bytes().any(|b| b.is_ascii_uppercase()) {
+        true => to.to_ascii_lowercase().into(),
+        false => to,
+    }
+}
+
+/// The path of the file a markdown link in the document at `from` to `to`
+/// points to, if `to` is a relative path: `.` and `..` are resolved against
+/// the directory of the document, percent encoded bytes are decoded and `.md`
+/// is added to a target without an extension. The `#anchor` of the target is
+/// ignored.
+pub fn link_path(from: &Path, to: &str) -> Option<PathBuf> {
+    let to = to.split(['#', '?']).next().unwrap_or_default();
+    if to.is_empty() || to.starts_with('/') || to.contains(':') {
+        return None;
+    }
+    let mut path = from.parent()?.join(percent_decode(to)).normalize();
+    if path.extension().is_none() {
+        path.set_extension("md");
+    }
+    Some(path)
+}
+
+/// The target of a markdown link in a document of `directory` to the file at
+/// `path`, e.g. `../b/c.md`
+pub fn relative_link(directory: &Path, path: &Path) -> String {
+    let directory: Vec<_> = directory.components().collect();
+    let path: Vec<_> = path.components().collect();
+    let common = directory
+        .iter()
+        .zip(&path)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let parents = std::iter::repeat_n("..".into(), directory.len() - common);
+    let rest = path[common..]
+        .iter()
+        .map(|c| c.as_os_str().to_string_lossy());
+    parents.chain(rest).collect::<Vec<_>>().join("/")
+}
+
+/// The start of the target of the wikilink being written at the end of
+/// `line`, the offset following its `[[`
+pub fn partial_wiki_link(line: &str) -> Option<usize> {
+    let start = line.rfind("[[")? + "[[".len();
+    let target = &line[start..];
+    (!target.contains([']', '[', '|', '#'])).then_some(start)
+}
+
+/// `text` with its percent encoded bytes, as in `%20`, decoded
+pub fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| text.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match hex {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// The documents links are resolved among, by id and by path
+#[derive(Debug, Default, Clone)]
+pub struct LinkTargets {
+    pub ids: Vec<DocumentId>,
+    paths: HashMap<DocumentId, PathBuf>,
+    documents: HashMap<PathBuf, DocumentId>,
+}
+
+impl LinkTargets {
+    /// The indexed documents
+    pub fn load(db: &Connection) -> Result<LinkTargets> {
+        let mut targets = LinkTargets::default();
+        let mut query = db.prepare_cached(sql!("select id, path from document"))?;
+        for row in query.query_map([], |r| Ok((r.get(0)?, r.get::<_, DocumentPath>(1)?)))? {
+            let (id, DocumentPath(path)) = row?;
+            targets.insert(id, path);
+        }
+        Ok(targets)
+    }
+
+    pub fn insert(&mut self, id: DocumentId, path: PathBuf) {
+        if let Some(previous) = self.paths.insert(id.clone(), path.clone()) {
+            self.documents.remove(&previous);
+        } else {
+            self.ids.push(id.clone());
+        }
+        self.documents.insert(path, id);
+    }
+
+    pub fn remove(&mut self, id: &DocumentId) {
+        if let Some(path) = self.paths.remove(id) {
+            self.documents.remove(&path);
+        }
+        self.ids.retain(|other| other != id);
+    }
+
+    /// The path of the document `id`
+    pub fn path(&self, id: &DocumentId) -> Option<&Path> {
+        self.paths.get(id).map(PathBuf::as_path)
+    }
+
+    /// The document a link in the document `from` to `to` points to. A
+    /// markdown link naming the path of a document relative to the linking one
+    /// points to it, other links are resolved by [`resolve_target`]. Links to
+    /// external pages point to no document.
+    pub fn resolve(&self, from: &DocumentId, to: &str, wiki: bool) -> Option<&DocumentId> {
+        if to.contains("://") {
+            return None;
+        }
+        let by_path = match wiki {
+            true => None,
+            false => self
+                .path(from)
+                .and_then(|from| link_path(from, to))
+                .and_then(|path| self.documents.get(&path)),
+        };
+        by_path.or_else(|| resolve_target(&self.ids, to))
+    }
+}
+
 /// given a string, we check if there exists any document in the database
 /// whose id ends in that string.
 // pub fn resolve_id(db: &DB, suffix: &str) -> Result<Vec<DocumentId>> {
@@ -272,6 +618,8 @@ pub fn path_to_id(root: &Path, path: &Path) -> DocumentId {
 
 pub const TITLE_KEY: &str = "title";
 pub const ID_KEY: &str = "id";
+pub const TAGS_KEY: &str = "tags";
+pub const ALIASES_KEY: &str = "aliases";
 
 pub fn extract_title_from_frontmatter(data: &serde_json::Value) -> Option<String> {
     let res = data.get("title")?;
@@ -295,7 +643,7 @@ pub fn extract_id_from_frontmatter(data: &serde_json::Value) -> Option<DocumentI
 
 pub fn extract_tags_from_frontmatter(frontmatter: &serde_json::Value) -> Vec<String> {
     frontmatter
-        .get("tags")
+        .get(TAGS_KEY)
         .and_then(|v| v.as_array())
         .map(|arr| {
             arr.iter()
@@ -305,14 +653,194 @@ pub fn extract_tags_from_frontmatter(frontmatter: &serde_json::Value) -> Vec<Str
         .unwrap_or_default()
 }
 
+/// The other names of a document, a single alias may be given as a string
+pub fn extract_aliases_from_frontmatter(frontmatter: &serde_json::Value) -> Vec<String> {
+    match frontmatter.get(ALIASES_KEY) {
+        Some(serde_json::Value::String(alias)) => vec![alias.to_owned()],
+        Some(serde_json::Value::Array(aliases)) => aliases
+            .iter()
+            .filter_map(|v| v.as_str().map(str::to_owned))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The plain text of a sequence of (inline) nodes `ids` of `ast`, with
+/// markup and link targets stripped
+pub fn extract_text_from_ast(ast: &Ast, ids: impl IntoIterator<Item = NodeId>) -> String {
+    use ast_nodes::Node;
+
+    let mut text = String::new();
+    for id in ids {
+        match &ast[id] {
+            Node::Text { text: t, .. } => text.push_str(t),
+            Node::TextDecoration { content, .. } => text.push_str(content),
+            Node::Highlight { text: t, .. } => text.push_str(t),
+            Node::Mention { name, .. } => {
+                text.push('@');
+                text.push_str(name);
+            }
+            Node::Code { code, .. } => text.push_str(code),
+            Node::InlineMath { text: t, .. } => text.push_str(t),
+            Node::WikiLink { title, target, .. } => {
+                text.push_str(if title.is_empty() { target } else { title })
+            }
+            Node::InlineLink { title, .. } | Node::ReferenceLink { title, .. } => {
+                text.push_str(title)
+            }
+            Node::ShortcutLink { id, .. } => text.push_str(id),
+            Node::AutoLink { target, .. } => text.push_str(target),
+            Node::HardBreak { .. } => text.push(' '),
+            Node::Paragraph { .. } | Node::Heading { .. } => {
+                if !text.is_empty() {
+                    text.push(' ');
+                }
+                text.push_str(&extract_text_from_ast(ast, ast.children(id)));
+            }
+            _ => {}
+        }
+    }
+    text.trim().to_owned()
+}
+
+/// The source span of a sequence of inline nodes `ids` of `ast`, and the
+/// ranges within it that are not text: code, links, html and the like. Text is
+/// best searched in the source, as the parser splits text nodes at characters
+/// such as `_`.
+pub(crate) fn inline_source_span(
+    ast: &Ast,
+    ids: impl IntoIterator<Item = NodeId>,
+) -> Option<(std::ops::Range<usize>, Vec<std::ops::Range<usize>>)> {
+    use ast_nodes::Node;
+
+    let mut span: Option<std::ops::Range<usize>> = None;
+    let mut excluded = Vec::new();
+    for id in ids {
+        let (range, is_text) = match &ast[id] {
+            Node::Text { range, .. }
+            | Node::TextDecoration { range, .. }
+            | Node::Highlight { range, .. }
+            | Node::Mention { range, .. }
+            | Node::HardBreak { range } => (range, true),
+            Node::Code { range, .. }
+            | Node::Html { range, .. }
+            | Node::InlineLink { range, .. }
+            | Node::ReferenceLink { range, .. }
+            | Node::ShortcutLink { range, .. }
+            | Node::AutoLink { range, .. }
+            | Node::WikiLink { range, .. }
+            | Node::InlineImage { range }
+            | Node::ReferenceImage { range }
+            | Node::InlineMath { range, .. }
+            | Node::FootnoteReference { range, .. } => (range, false),
+            _ => continue,
+        };
+        if !is_text {
+            excluded.push(range.clone());
+        }
+        span = Some(match span {
+            Some(span) => span.start.min(range.start)..span.end.max(range.end),
+            None => range.clone(),
+        });
+    }
+    span.map(|span| (span, excluded))
+}
+
 /// TODO write documentation for how we retrieve the title
-pub fn extract_title_from_ast(ast: &[ast_nodes::Node]) -> Option<String> {
+pub fn extract_title_from_ast(ast: &Ast) -> Option<String> {
     // the first heading found
-    for node in ast.iter() {
-        if let ast_nodes::Node::Heading { content, .. } = &node {
+    for id in ast.roots() {
+        if let ast_nodes::Node::Heading { content, .. } = &ast[id] {
             return Some(content.to_owned());
         }
     }
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_root() {
+        let root = Path::new("/Notes");
+        assert_eq!(
+            strip_root(root, Path::new("/Notes/z/a.md"), false),
+            Some(PathBuf::from("z/a.md"))
+        );
+        assert_eq!(strip_root(root, Path::new("/notes/z/a.md"), false), None);
+        assert_eq!(
+            strip_root(root, Path::new("/notes/z/a.md"), true),
+            Some(PathBuf::from("z/a.md"))
+        );
+        assert_eq!(strip_root(root, Path::new("/Notebook/a.md"), true), None);
+    }
+
+    #[test]
+    fn test_path_to_id() {
+        let root = Path::new("/notes");
+        let id = |path: &str| path_to_id(root, Path::new(path)).0;
+        assert_eq!(id("/notes/z/A Note.md"), "z/a-note");
+        assert_eq!(id("/notes/a.b.md"), "a.b");
+    }
+
+    #[test]
+    fn test_resolve_target() {
+        let ids = [DocumentId("z/a".into()), DocumentId("a".into())];
+        assert_eq!(resolve_target(&ids, "z/a"), Some(&ids[0]));
+        assert_eq!(resolve_target(&ids, "z\\a"), Some(&ids[0]));
+        assert_eq!(normalize_target("Z\\A", true), "z/a");
+        assert_eq!(normalize_target("Z\\A", false), "Z/A");
+    }
+
+    #[test]
+    fn test_link_path() {
+        let from = Path::new("/notes/a/b.md");
+        let path = |to: &str| link_path(from, to);
+        assert_eq!(path("../c/d.md"), Some(PathBuf::from("/notes/c/d.md")));
+        assert_eq!(path("./c%20d#top"), Some(PathBuf::from("/notes/a/c d.md")));
+        assert_eq!(path("e.png"), Some(PathBuf::from("/notes/a/e.png")));
+        assert_eq!(path("https://a.com/b.md"), None);
+        assert_eq!(path("/c.md"), None);
+        assert_eq!(path("#top"), None);
+        assert_eq!(
+            relative_link(Path::new("/notes/a"), Path::new("/notes/c/d.md")),
+            "../c/d.md"
+        );
+        assert_eq!(partial_wiki_link("see [[al"), Some(6));
+        assert_eq!(partial_wiki_link("![["), Some(3));
+        assert_eq!(partial_wiki_link("see [[a]] and"), None);
+        assert_eq!(partial_wiki_link("see [[a|the"), None);
+    }
+
+    #[test]
+    fn test_link_targets() {
+        let mut targets = LinkTargets::default();
+        targets.insert(DocumentId("a/b".into()), PathBuf::from("/n/a/B.md"));
+        targets.insert(DocumentId("c".into()), PathBuf::from("/n/c.md"));
+        let from = DocumentId("c".into());
+        let resolve = |to: &str, wiki: bool| targets.resolve(&from, to, wiki).map(|id| &id.0);
+        assert_eq!(resolve("a/B.md", false), Some(&"a/b".to_owned()));
+        assert_eq!(resolve("./a/B#top", false), Some(&"a/b".to_owned()));
+        assert_eq!(resolve("a/b", false), Some(&"a/b".to_owned()));
+        assert_eq!(resolve("a/B.md", true), None);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_windows_paths() {
+        let root = Path::new(r"C:\Users\me\Notes");
+        let id = |path: &str| path_to_id(root, Path::new(path)).0;
+        assert_eq!(id(r"C:\Users\me\Notes\z\a.md"), "z/a");
+        assert_eq!(id(r"c:\users\me\notes\z\a.md"), "z/a");
+        assert_eq!(id(r"C:/Users/me/Notes/z/a.md"), "z/a");
+        assert_eq!(
+            relative_path(root, Path::new(r"c:\USERS\me\notes\a.md")),
+            Some(PathBuf::from("a.md"))
+        );
+
+        let ids = [DocumentId("z/a".into())];
+        assert_eq!(resolve_target(&ids, r"Z\A"), Some(&ids[0]));
+    }
+}
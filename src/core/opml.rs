@@ -0,0 +1,393 @@
+//! Outlines in [OPML](http://opml.org/spec2.opml), the format outliners and
+//! feed readers exchange them in.
+//!
+//! An imported outline becomes a nested list, an `<outline>` with an
+//! `htmlUrl`, `url` or `xmlUrl` a link. The outline of a note is its
+//! structure: its headings, nested by level, and its list items, by the
+//! text of their first paragraph. Items that are a single link keep the url,
+//! so that the outline of an imported note is exported as it was imported.
+
+use color_eyre::eyre::eyre;
+use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd};
+use quick_xml::Reader;
+use quick_xml::escape::{escape, unescape};
+use quick_xml::events::{BytesStart, Event as XmlEvent};
+use quick_xml::name::QName;
+
+use crate::core::parser::DocumentParserOptions;
+use crate::result::Result;
+
+/// The attributes an outline's url is read from, in order
+const URL_ATTRIBUTES: [&str; 3] = ["htmlUrl", "url", "xmlUrl"];
+
+/// An entry of an outline and the entries below it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Outline {
+    pub text: String,
+    pub url: Option<String>,
+    pub children: Vec<Outline>,
+}
+
+/// A parsed OPML document
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Opml {
+    pub title: Option<String>,
+    pub outlines: Vec<Outline>,
+}
+
+impl Opml {
+    pub fn parse(xml: &str) -> Result<Opml> {
+        let mut reader = Reader::from_str(xml);
+        let mut opml = Opml::default();
+        // the outlines being read, the innermost last
+        let mut open: Vec<Outline> = Vec::new();
+        loop {
+            let event = reader
+                .read_event()
+                .map_err(|e| eyre!("invalid opml at {}: {}", reader.error_position(), e))?;
+            match event {
+                XmlEvent::Start(e) if e.name().as_ref() == "title" && open.is_empty() => {
+                    let title = reader.read_text(QName("title"))?;
+                    let title = unescape(title.as_ref())?.trim().to_owned();
+                    opml.title = (!title.is_empty()).then_some(title);
+                }
+                XmlEvent::Start(e) if e.name().as_ref() == "outline" => {
+                    open.push(outline(&e)?);
+                }
+                XmlEvent::Empty(e) if e.name().as_ref() == "outline" => {
+                    let outline = outline(&e)?;
+                    match open.last_mut() {
+                        Some(parent) => parent.children.push(outline),
+                        None => opml.outlines.push(outline),
+                    }
+                }
+                XmlEvent::End(e) if e.name().as_ref() == "outline" => {
+                    let Some(outline) = open.pop() else {
+                        continue;
+                    };
+                    match open.last_mut() {
+                        Some(parent) => parent.children.push(outline),
+                        None => opml.outlines.push(outline),
+                    }
+                }
+                XmlEvent::Eof => break,
+                _ => {}
+            }
+        }
+        Ok(opml)
+    }
+
+    /// The outline of the markdown `body`: its headings, nested by level, and
+    /// its list items. A first heading repeating the title is left out, its
+    /// entries taking its place.
+    pub fn from_markdown(title: Option<String>, body: &str) -> Opml {
+        let mut builder = OutlineBuilder::default();
+        for event in Parser::new_ext(body, DocumentParserOptions::default().0) {
+            builder.push(event);
+        }
+        let mut outlines = builder.finish();
+        if let Some(first) = outlines.first()
+            && title.as_ref() == Some(&first.text)
+            && first.url.is_none()
+        {
+            let first = outlines.remove(0);
+            outlines.splice(0..0, first.children);
+        }
+        Opml { title, outlines }
+    }
+
+    /// The outlines as a nested markdown list
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::new();
+        for outline in &self.outlines {
+            write_item(&mut markdown, outline, 0);
+        }
+        markdown
+    }
+
+    pub fn to_opml(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str("<opml version=\"2.0\">\n  <head>\n");
+        if let Some(title) = &self.title {
+            xml.push_str(&format!("    <title>{}</title>\n", escape(title.as_str())));
+        }
+        xml.push_str("  </head>\n  <body>\n");
+        for outline in &self.outlines {
+            write_outline(&mut xml, outline, 2);
+        }
+        xml.push_str("  </body>\n</opml>\n");
+        xml
+    }
+}
+
+fn outline(element: &BytesStart) -> Result<Outline> {
+    let mut text = None;
+    let mut title = None;
+    let mut urls: Vec<(usize, String)> = Vec::new();
+    for attribute in element.attributes() {
+        let attribute = attribute?;
+        let value = attribute
+            .normalized_value(quick_xml::XmlVersion::Implicit1_0)?
+            .trim()
+            .to_owned();
+        match attribute.key.as_ref() {
+            "text" => text = Some(value),
+            "title" => title = Some(value),
+            key => {
+                if let Some(rank) = URL_ATTRIBUTES.iter().position(|k| *k == key) {
+                    urls.push((rank, value));
+                }
+            }
+        }
+    }
+    urls.sort();
+    Ok(Outline {
+        text: text.or(title).unwrap_or_default(),
+        url: urls
+            .into_iter()
+            .map(|(_, url)| url)
+            .find(|url| !url.is_empty()),
+        children: Vec::new(),
+    })
+}
+
+fn write_item(markdown: &mut String, outline: &Outline, depth: usize) {
+    let text = outline.text.replace('\n', " ");
+    let item = match &outline.url {
+        Some(url) if text.is_empty() => format!("<{url}>"),
+        Some(url) => format!(
+            "[{}]({})",
+            text.replace(']', "\\]"),
+            url.replace(' ', "%20")
+        ),
+        None => text,
+    };
+    markdown.push_str(&format!("{}- {}\n", "  ".repeat(depth), item));
+    for child in &outline.children {
+        write_item(markdown, child, depth + 1);
+    }
+}
+
+fn write_outline(xml: &mut String, outline: &Outline, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let mut attributes = format!("text=\"{}\"", escape(outline.text.as_str()));
+    if let Some(url) = &outline.url {
+        attributes.push_str(&format!(" htmlUrl=\"{}\"", escape(url.as_str())));
+    }
+    if outline.children.is_empty() {
+        xml.push_str(&format!("{indent}<outline {attributes}/>\n"));
+        return;
+    }
+    xml.push_str(&format!("{indent}<outline {attributes}>\n"));
+    for child in &outline.children {
+        write_outline(xml, child, depth + 1);
+    }
+    xml.push_str(&format!("{indent}</outline>\n"));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Entry {
+    Heading(HeadingLevel),
+    Item,
+}
+
+/// Builds the outline of a markdown document from its parser events
+#[derive(Default)]
+struct OutlineBuilder {
+    outlines: Vec<Outline>,
+    /// the headings and items containing the current event, innermost last
+    open: Vec<(Entry, Outline)>,
+    /// whether the text of the innermost entry is being read
+    reading: bool,
+    /// the url and the end of the text of a link starting the entry
+    link: Option<(String, Option<usize>)>,
+}
+
+impl OutlineBuilder {
+    fn push(&mut self, event: Event) {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                while let Some((Entry::Heading(open), _)) = self.open.last()
+                    && *open >= level
+                {
+                    self.close();
+                }
+                self.open(Entry::Heading(level));
+            }
+            Event::End(TagEnd::Heading(_)) | Event::End(TagEnd::Paragraph) => self.stop(),
+            Event::Start(Tag::Item) => self.open(Entry::Item),
+            Event::Start(Tag::List(_)) => self.stop(),
+            Event::End(TagEnd::Item) => {
+                self.stop();
+                self.close();
+            }
+            Event::Start(Tag::Link { dest_url, .. }) if self.reading => {
+                let empty = self.open.last().is_some_and(|(_, o)| o.text.is_empty());
+                if empty && self.link.is_none() {
+                    self.link = Some((dest_url.into_string(), None));
+                }
+            }
+            Event::End(TagEnd::Link) if self.reading => {
+                let end = self.open.last().map(|(_, o)| o.text.len());
+                if let Some((_, link_end @ None)) = &mut self.link {
+                    *link_end = end;
+                }
+            }
+            Event::Text(text) | Event::Code(text) if self.reading => self.text(&text),
+            Event::SoftBreak | Event::HardBreak if self.reading => self.text(" "),
+            _ => {}
+        }
+    }
+
+    fn open(&mut self, entry: Entry) {
+        self.stop();
+        self.open.push((entry, Outline::default()));
+        self.reading = true;
+    }
+
+    fn text(&mut self, text: &str) {
+        if let Some((_, outline)) = self.open.last_mut() {
+            outline.text.push_str(text);
+        }
+    }
+
+    /// Stop reading the text of the innermost entry, keeping the url of a
+    /// link that is all of it
+    fn stop(&mut self) {
+        if !self.reading {
+            return;
+        }
+        self.reading = false;
+        let link = self.link.take();
+        let Some((_, outline)) = self.open.last_mut() else {
+            return;
+        };
+        outline.text = outline.text.trim().to_owned();
+        if let Some((url, Some(end))) = link
+            && end >= outline.text.len()
+        {
+            outline.url = Some(url);
+        }
+    }
+
+    /// Add the innermost entry to its parent
+    fn close(&mut self) {
+        let Some((_, outline)) = self.open.pop() else {
+            return;
+        };
+        match self.open.last_mut() {
+            Some((_, parent)) => parent.children.push(outline),
+            None => self.outlines.push(outline),
+        }
+    }
+
+    fn finish(mut self) -> Vec<Outline> {
+        self.stop();
+        while !self.open.is_empty() {
+            self.close();
+        }
+        self.outlines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<opml version="2.0">
+  <head><title>Reading &amp; writing</title></head>
+  <body>
+    <outline text="Books">
+      <outline text="Dune" _note="a note"/>
+      <outline title="Feeds">
+        <outline text="Blog" xmlUrl="https://example.com/feed" htmlUrl="https://example.com"/>
+      </outline>
+    </outline>
+    <outline text="Ideas &lt;3"/>
+  </body>
+</opml>"#;
+
+    fn leaf(text: &str, url: Option<&str>) -> Outline {
+        Outline {
+            text: text.to_owned(),
+            url: url.map(str::to_owned),
+            children: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let opml = Opml::parse(OPML).unwrap();
+        assert_eq!(opml.title.as_deref(), Some("Reading & writing"));
+        assert_eq!(
+            opml.outlines,
+            vec![
+                Outline {
+                    text: "Books".to_owned(),
+                    url: None,
+                    children: vec![
+                        leaf("Dune", None),
+                        Outline {
+                            text: "Feeds".to_owned(),
+                            url: None,
+                            children: vec![leaf("Blog", Some("https://example.com"))],
+                        },
+                    ],
+                },
+                leaf("Ideas <3", None),
+            ]
+        );
+        assert_eq!(
+            opml.to_markdown(),
+            "- Books\n  - Dune\n  - Feeds\n    - [Blog](https://example.com)\n- Ideas <3\n"
+        );
+        assert!(Opml::parse("<opml><body><outline text=\"a\"></body>").is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let opml = Opml::parse(OPML).unwrap();
+        let markdown = opml.to_markdown();
+        let outline = Opml::from_markdown(opml.title.clone(), &markdown);
+        assert_eq!(outline, opml);
+        let note = format!("# Reading & writing\n\n{markdown}");
+        assert_eq!(Opml::from_markdown(opml.title.clone(), &note), opml);
+        assert_eq!(Opml::parse(&outline.to_opml()).unwrap(), opml);
+    }
+
+    #[test]
+    fn test_from_markdown() {
+        let markdown = "# Plan\n\nsome text\n\n## Now\n\n- write **the** draft\n  - see [notes](notes.md) first\n- [review](https://example.com/review)\n\n## Later\n\n### Maybe\n\n# Done\n";
+        let outline = Opml::from_markdown(None, markdown);
+        assert_eq!(
+            outline.outlines,
+            vec![
+                Outline {
+                    text: "Plan".to_owned(),
+                    url: None,
+                    children: vec![
+                        Outline {
+                            text: "Now".to_owned(),
+                            url: None,
+                            children: vec![
+                                Outline {
+                                    text: "write the draft".to_owned(),
+                                    url: None,
+                                    children: vec![leaf("see notes first", None)],
+                                },
+                                leaf("review", Some("https://example.com/review")),
+                            ],
+                        },
+                        Outline {
+                            text: "Later".to_owned(),
+                            url: None,
+                            children: vec![leaf("Maybe", None)],
+                        },
+                    ],
+                },
+                leaf("Done", None),
+            ]
+        );
+    }
+}
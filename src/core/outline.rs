@@ -0,0 +1,254 @@
+//! Editing the outline of a document. A section is a heading and everything
+//! up to the next heading of the same or a higher level, its subsections
+//! included, and is moved, promoted and demoted as a whole.
+
+use std::ops::Range;
+
+use color_eyre::eyre::eyre;
+
+use crate::core::frontmatter::split_frontmatter;
+use crate::core::parser::arena::Ast;
+use crate::core::parser::ast_nodes::{Node, NodeKind};
+use crate::core::parser::{DocumentParser, Parse};
+use crate::result::Result;
+
+/// A section of the body of a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub level: u8,
+    /// the text of the heading
+    pub content: String,
+    /// the range of the heading
+    pub heading: Range<usize>,
+    /// the range of the heading and everything under it
+    pub range: Range<usize>,
+}
+
+/// Where a section is moved to, relative to another section
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Position {
+    Before(String),
+    After(String),
+}
+
+/// The sections of the body `body` parsed into `ast`, in document order
+pub fn sections(ast: &Ast, body: &str) -> Vec<Section> {
+    let headings: Vec<(u8, Range<usize>, String)> = ast
+        .nodes_of_kind(NodeKind::Heading)
+        .filter_map(|cursor| match cursor.node() {
+            Node::Heading {
+                level,
+                range,
+                content,
+                ..
+            } => Some((*level, range.clone(), content.clone())),
+            _ => None,
+        })
+        .collect();
+    headings
+        .iter()
+        .enumerate()
+        .map(|(i, (level, heading, content))| {
+            let end = headings[i + 1..]
+                .iter()
+                .find(|(other, _, _)| other <= level)
+                .map_or(body.len(), |(_, heading, _)| heading.start);
+            Section {
+                level: *level,
+                content: content.trim().to_owned(),
+                heading: heading.clone(),
+                range: heading.start..end,
+            }
+        })
+        .collect()
+}
+
+/// The first section with the heading `heading`, matched regardless of case
+pub fn find_section<'a>(sections: &'a [Section], heading: &str) -> Option<&'a Section> {
+    sections
+        .iter()
+        .find(|section| section.content.eq_ignore_ascii_case(heading.trim()))
+}
+
+/// The heading `source` written as an atx heading of `level`, setext headings
+/// being rewritten
+pub fn set_heading_level(source: &str, level: usize) -> String {
+    let hashes = "#".repeat(level);
+    match source.starts_with('#') {
+        true => format!("{hashes}{}", source.trim_start_matches('#')),
+        // a setext heading, its text being its first line
+        false => {
+            let text = source.lines().next().unwrap_or_default().trim();
+            let end = match source.ends_with('\n') {
+                true => "\n",
+                false => "",
+            };
+            format!("{hashes} {text}{end}")
+        }
+    }
+}
+
+/// `document` with the section `heading` moved before or after the section
+/// of `position`
+pub fn move_section(
+    document: &str,
+    heading: &str,
+    position: &Position,
+    parser: &DocumentParser,
+) -> Result<String> {
+    let (frontmatter, body) = split_body(document);
+    let ast = parser.parse(body)?;
+    let sections = sections(&ast, body);
+    let moved = find_section(&sections, heading).ok_or_else(|| no_heading(heading))?;
+    let (Position::Before(other) | Position::After(other)) = position;
+    let target = find_section(&sections, other).ok_or_else(|| no_heading(other))?;
+    if moved.range.contains(&target.range.start) || target.range.contains(&moved.range.start) {
+        return Err(eyre!(
+            "the section '{}' can not be moved relative to itself, its subsections or the sections it is in",
+            moved.content
+        ));
+    }
+
+    let text = format!("{}\n", body[moved.range.clone()].trim_end());
+    let at = match position {
+        Position::Before(_) => target.range.start,
+        Position::After(_) => target.range.end,
+    };
+    // the offset of the target once the moved section is cut
+    let at = match at > moved.range.start {
+        true => at - moved.range.len(),
+        false => at,
+    };
+    if at == moved.range.start {
+        let relation = match position {
+            Position::Before(_) => "before",
+            Position::After(_) => "after",
+        };
+        return Err(eyre!(
+            "the section '{}' is already {} '{}'",
+            moved.content,
+            relation,
+            target.content
+        ));
+    }
+    let mut edited = body.to_owned();
+    edited.replace_range(moved.range.clone(), "");
+    // the blank line before a last section is not left at the end
+    if moved.range.end == body.len() {
+        edited.truncate(edited.trim_end().len());
+        edited.push('\n');
+    }
+    let at = at.min(edited.len());
+
+    let before = match &edited[..at] {
+        "" => "",
+        text if text.ends_with("\n\n") => "",
+        text if text.ends_with('\n') => "\n",
+        _ => "\n\n",
+    };
+    let after = match at < edited.len() {
+        true => "\n",
+        false => "",
+    };
+    edited.insert_str(at, &format!("{before}{text}{after}"));
+    Ok(format!("{frontmatter}{edited}"))
+}
+
+/// `document` with the headings of the section `heading` raised or lowered by
+/// `by` levels, a negative `by` promoting them
+pub fn shift_section(
+    document: &str,
+    heading: &str,
+    by: i8,
+    parser: &DocumentParser,
+) -> Result<String> {
+    let (frontmatter, body) = split_body(document);
+    let ast = parser.parse(body)?;
+    let sections = sections(&ast, body);
+    let shifted = find_section(&sections, heading).ok_or_else(|| no_heading(heading))?;
+
+    let mut edited = body.to_owned();
+    let within = sections
+        .iter()
+        .filter(|section| shifted.range.contains(&section.heading.start));
+    for section in within.collect::<Vec<_>>().into_iter().rev() {
+        let level = section.level as i8 + by;
+        if !(1..=6).contains(&level) {
+            return Err(eyre!(
+                "the heading '{}' can not be of level {}",
+                section.content,
+                level
+            ));
+        }
+        let source = &body[section.heading.clone()];
+        edited.replace_range(
+            section.heading.clone(),
+            &set_heading_level(source, level as usize),
+        );
+    }
+    Ok(format!("{frontmatter}{edited}"))
+}
+
+/// The frontmatter of `document`, with its fences, and its body
+fn split_body(document: &str) -> (&str, &str) {
+    let offset = split_frontmatter(document).map_or(0, |(_, body)| document.len() - body.len());
+    document.split_at(offset)
+}
+
+fn no_heading(heading: &str) -> color_eyre::eyre::Report {
+    eyre!("the document has no heading '{}'", heading)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOCUMENT: &str =
+        "---\ntitle: T\n---\n# T\n\n## A\n\na\n\n### A1\n\na1\n\n## B\n\nb\n\n## C\n\nc\n";
+
+    #[test]
+    fn test_move_section() {
+        let parser = DocumentParser::new();
+        let after = |heading: &str, other: &str| {
+            move_section(
+                DOCUMENT,
+                heading,
+                &Position::After(other.to_owned()),
+                &parser,
+            )
+        };
+        assert_eq!(
+            after("a", "B").unwrap(),
+            "---\ntitle: T\n---\n# T\n\n## B\n\nb\n\n## A\n\na\n\n### A1\n\na1\n\n## C\n\nc\n"
+        );
+        assert_eq!(
+            after("A", "C").unwrap(),
+            "---\ntitle: T\n---\n# T\n\n## B\n\nb\n\n## C\n\nc\n\n## A\n\na\n\n### A1\n\na1\n"
+        );
+        assert_eq!(
+            move_section(DOCUMENT, "C", &Position::Before("A".to_owned()), &parser).unwrap(),
+            "---\ntitle: T\n---\n# T\n\n## C\n\nc\n\n## A\n\na\n\n### A1\n\na1\n\n## B\n\nb\n"
+        );
+        assert!(after("A", "A1").is_err());
+        assert!(after("A1", "A").is_err());
+        assert!(move_section(DOCUMENT, "A", &Position::Before("T".to_owned()), &parser).is_err());
+        // the section is already there
+        assert!(after("B", "A").is_err());
+        assert!(move_section(DOCUMENT, "A", &Position::Before("B".to_owned()), &parser).is_err());
+        assert!(after("A", "missing").is_err());
+    }
+
+    #[test]
+    fn test_shift_section() {
+        let parser = DocumentParser::new();
+        assert_eq!(
+            shift_section(DOCUMENT, "A", 1, &parser).unwrap(),
+            "---\ntitle: T\n---\n# T\n\n### A\n\na\n\n#### A1\n\na1\n\n## B\n\nb\n\n## C\n\nc\n"
+        );
+        assert_eq!(
+            shift_section("Title\n=====\n\n## Sub\n", "title", 1, &parser).unwrap(),
+            "## Title\n\n### Sub\n"
+        );
+        assert!(shift_section(DOCUMENT, "T", -1, &parser).is_err());
+    }
+}
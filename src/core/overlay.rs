@@ -0,0 +1,434 @@
+//! Unsaved editor buffers layered over the index.
+//!
+//! The language server parses every buffer the editor has open each time it
+//! changes. The headings, tasks and links derived from a buffer shadow the
+//! rows the index holds for the same document, so queries reflect edits
+//! before they are saved and indexed. Nothing is written to the db; once a
+//! buffer is closed the index is read again.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use jiff::Timestamp;
+use rusqlite::Connection;
+use sql_minifier::macros::minify_sql as sql;
+
+use crate::config::Config;
+use crate::core::db::DbGet;
+use crate::core::flavor::DocumentSettings;
+use crate::core::frontmatter::body_offset;
+use crate::core::graph::{LinkGraph, LocalGraph, LocalGraphNode};
+use crate::core::index_hook::{DerivedRow, IndexHooks};
+use crate::core::parser::comments::ParserConfig;
+#[cfg(feature = "tree-sitter")]
+use crate::core::parser::tree_sitter::TreeSitterParser;
+use crate::core::parser::{DocumentParser, FrontMatterParser, Parse};
+use crate::core::types::document::{
+    CreatedTimestamp, Document, DocumentId, DocumentPath, ModifiedTimestamp,
+};
+use crate::core::types::heading::NewDocumentHeading;
+use crate::core::types::link::UnresolvedLink;
+use crate::core::types::task::NewDocumentTask;
+use crate::core::{
+    LinkTargets, extract_id_from_frontmatter, extract_title_from_ast,
+    extract_title_from_frontmatter, hash,
+};
+use crate::result::Result;
+
+/// An open buffer, parsed the way the index parses a document. The due dates
+/// of its tasks are not resolved.
+#[derive(Debug, Clone)]
+pub struct Buffer {
+    pub text: String,
+    /// the byte offset of the content after the frontmatter, the ranges of
+    /// the rows below being relative to it
+    pub body_offset: usize,
+    pub document: Document,
+    pub headings: Vec<NewDocumentHeading>,
+    pub tasks: Vec<NewDocumentTask>,
+    pub links: Vec<UnresolvedLink>,
+}
+
+impl Buffer {
+    /// Parse the buffer of `path`, its body with the parser settings of the
+    /// document, as [`DocumentSettings`] resolves them
+    pub fn parse(config: &Config, root: &Path, path: &Path, text: String) -> Result<Self> {
+        Self::parse_with(config, root, path, text, DocumentParser::from_config)
+    }
+
+    /// [`Buffer::parse`], parsing the markdown with the parser `parser` makes
+    /// of the parser settings of the document
+    pub fn parse_with<P: Parse>(
+        config: &Config,
+        root: &Path,
+        path: &Path,
+        text: String,
+        parser: impl FnOnce(&ParserConfig) -> P,
+    ) -> Result<Self> {
+        let (frontmatter, body) =
+            FrontMatterParser::new(config.front_matter_format).parse(text.clone());
+        let settings = DocumentSettings::resolve(config, frontmatter.as_ref())?;
+        let ast = parser(&settings.parser).parse(&body)?;
+        let frontmatter = frontmatter.unwrap_or(serde_json::Value::Null);
+        let id = extract_id_from_frontmatter(&frontmatter)
+            .unwrap_or_else(|| config.id_scheme(root, path).path_to_id(root, path));
+        let title = extract_title_from_frontmatter(&frontmatter)
+            .or_else(|| extract_title_from_ast(&ast))
+            .unwrap_or_default();
+        let now = Timestamp::now();
+        let document = Document {
+            id,
+            title,
+            path: DocumentPath(path.to_path_buf()),
+            hash: hash(&text),
+            modified: ModifiedTimestamp(now),
+            created: CreatedTimestamp(now),
+            data: frontmatter,
+        };
+
+        let mut buffer = Self {
+            body_offset: body_offset(&text),
+            text,
+            document,
+            headings: Vec::new(),
+            tasks: Vec::new(),
+            links: Vec::new(),
+        };
+        for row in IndexHooks::builtin().run(&buffer.document, &ast) {
+            match row {
+                DerivedRow::Heading(heading) => buffer.headings.push(heading),
+                DerivedRow::Task(task) => buffer.tasks.push(task),
+                DerivedRow::Link(link) => buffer.links.push(link),
+                DerivedRow::Highlight(_)
+                | DerivedRow::Quote(_)
+                | DerivedRow::Mention(_)
+                | DerivedRow::Metadata(_)
+                | DerivedRow::Custom(_) => {}
+            }
+        }
+        Ok(buffer)
+    }
+}
+
+/// The open buffers, by path
+#[derive(Debug, Default)]
+pub struct Overlay {
+    buffers: HashMap<PathBuf, Buffer>,
+    /// the parsers of the buffers, reparsing them incrementally
+    #[cfg(feature = "tree-sitter")]
+    parsers: HashMap<PathBuf, TreeSitterParser>,
+}
+
+impl Overlay {
+    /// Parse the new content of the buffer of `path`, in the collection at
+    /// `root` configured by `config`
+    pub fn update(
+        &mut self,
+        config: &Config,
+        root: &Path,
+        path: &Path,
+        text: String,
+    ) -> Result<()> {
+        #[cfg(feature = "tree-sitter")]
+        let buffer = {
+            let parsers = &mut self.parsers;
+            Buffer::parse_with(config, root, path, text, move |settings| {
+                let parser = parsers.entry(path.to_path_buf()).or_default();
+                parser.comments = settings.comments.clone();
+                &*parser
+            })?
+        };
+        #[cfg(not(feature = "tree-sitter"))]
+        let buffer = Buffer::parse(config, root, path, text)?;
+        self.buffers.insert(path.to_path_buf(), buffer);
+        Ok(())
+    }
+
+    /// Forget the buffer of `path`, the index being read for it again
+    pub fn close(&mut self, path: &Path) {
+        self.buffers.remove(path);
+        #[cfg(feature = "tree-sitter")]
+        self.parsers.remove(path);
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&Buffer> {
+        self.buffers.get(path)
+    }
+
+    fn by_id(&self, id: &DocumentId) -> Option<&Buffer> {
+        self.buffers
+            .values()
+            .find(|buffer| buffer.document.id == *id)
+    }
+
+    /// The document at `path`, from its buffer or the index
+    pub fn document(&self, db: &mut Connection, path: &Path) -> Option<Document> {
+        match self.get(path) {
+            Some(buffer) => Some(buffer.document.clone()),
+            None => Document::get(db, &DocumentPath(path.to_path_buf())).ok(),
+        }
+    }
+
+    /// The headings of the document `id`, in document order
+    pub fn headings(&self, db: &Connection, id: &DocumentId) -> Result<Vec<NewDocumentHeading>> {
+        if let Some(buffer) = self.by_id(id) {
+            return Ok(buffer.headings.clone());
+        }
+        db.prepare(sql!(
+            r#"
+            select
+                document_id,
+                content,
+                level,
+                anchor,
+                json(metadata),
+                range_start,
+                range_end
+            from
+                document_heading
+            where
+                document_id = ?1
+            order by
+                range_start
+            "#
+        ))?
+        .query_map([id], |r| {
+            Ok(NewDocumentHeading {
+                document_id: r.get(0)?,
+                content: r.get(1)?,
+                level: r.get(2)?,
+                anchor: r.get(3)?,
+                metadata: r.get(4)?,
+                range_start: r.get(5)?,
+                range_end: r.get(6)?,
+            })
+        })?
+        .map(|f| f.map_err(From::from))
+        .collect()
+    }
+
+    /// The tasks of the document `id`, in document order
+    pub fn tasks(&self, db: &Connection, id: &DocumentId) -> Result<Vec<NewDocumentTask>> {
+        if let Some(buffer) = self.by_id(id) {
+            return Ok(buffer.tasks.clone());
+        }
+        db.prepare(sql!(
+            r#"
+            select
+                document_id,
+                parent_id,
+                checked,
+                status,
+                content,
+                range_start,
+                range_end,
+                due,
+                due_start,
+                due_end,
+                owner,
+                action_item
+            from
+                document_task
+            where
+                document_id = ?1
+            order by
+                range_start
+            "#
+        ))?
+        .query_map([id], |r| {
+            Ok(NewDocumentTask {
+                document_id: r.get(0)?,
+                parent_id: r.get(1)?,
+                checked: r.get(2)?,
+                status: r.get(3)?,
+                content: r.get(4)?,
+                range_start: r.get(5)?,
+                range_end: r.get(6)?,
+                due: r.get(7)?,
+                due_start: r.get(8)?,
+                due_end: r.get(9)?,
+                owner: r.get(10)?,
+                action_item: r.get(11)?,
+            })
+        })?
+        .map(|f| f.map_err(From::from))
+        .collect()
+    }
+
+    /// The link graph of the index, the links of the open buffers replacing
+    /// those indexed for their documents
+    pub fn link_graph(&self, db: &Connection) -> Result<LinkGraph> {
+        let mut graph = LinkGraph::load(db)?;
+        let mut targets = LinkTargets::load(db)?;
+        for buffer in self.buffers.values() {
+            targets.insert(buffer.document.id.clone(), buffer.document.path.0.clone());
+        }
+        for buffer in self.buffers.values() {
+            let id = &buffer.document.id;
+            let from = match graph.nodes.iter().position(|node| node == id) {
+                Some(from) => from,
+                None => {
+                    graph.nodes.push(id.clone());
+                    graph.nodes.len() - 1
+                }
+            };
+            graph.edges.retain(|(source, _)| *source != from);
+            for link in &buffer.links {
+                let Some(target) = targets.resolve(id, &link.to, link.wiki) else {
+                    continue;
+                };
+                let to = graph.nodes.iter().position(|node| node == target);
+                graph.edges.extend(to.map(|to| (from, to)));
+            }
+        }
+        Ok(graph)
+    }
+
+    /// The `hops` neighborhood of `center`, see [`Overlay::link_graph`]. The
+    /// documents of open buffers are described by their buffers.
+    pub fn local_graph(
+        &self,
+        db: &Connection,
+        center: &DocumentId,
+        hops: usize,
+    ) -> Result<LocalGraph> {
+        let graph = self.link_graph(db)?;
+        let mut local = LocalGraph::from_graph(db, &graph, center, hops)?;
+        for (i, depth) in graph.neighborhood(center, hops) {
+            let Some(buffer) = self.by_id(&graph.nodes[i]) else {
+                continue;
+            };
+            let node = LocalGraphNode {
+                id: buffer.document.id.clone(),
+                title: buffer.document.title.clone(),
+                path: buffer.document.path.clone(),
+                depth,
+            };
+            match local.nodes.iter_mut().find(|n| n.id == node.id) {
+                Some(existing) => *existing = node,
+                None => local.nodes.push(node),
+            }
+        }
+        Ok(local)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::db::{DB, DbInsert};
+    use crate::core::types::heading::DocumentHeading;
+    use crate::core::types::link::{DocumentLink, NewDocumentLink};
+
+    fn document(id: &str) -> Document {
+        Document {
+            id: DocumentId(id.to_owned()),
+            title: id.to_uppercase(),
+            path: DocumentPath(PathBuf::from(format!("/notes/{id}.md"))),
+            hash: 0,
+            modified: ModifiedTimestamp(Timestamp::UNIX_EPOCH),
+            created: CreatedTimestamp(Timestamp::UNIX_EPOCH),
+            data: serde_json::Value::Null,
+        }
+    }
+
+    fn setup() -> DB {
+        let mut db = DB::open(":memory:").unwrap();
+        Document::insert(&mut db, &[document("a"), document("b"), document("c")]).unwrap();
+        DocumentLink::insert(
+            &mut db,
+            &[NewDocumentLink {
+                from: DocumentId("a".to_owned()).into(),
+                to: Some(DocumentId("b".to_owned()).into()),
+                range_start: 0,
+                range_end: 5,
+                block_start: None,
+                block_end: None,
+                url: None,
+            }],
+        )
+        .unwrap();
+        DocumentHeading::insert(
+            &mut db,
+            &[NewDocumentHeading {
+                document_id: DocumentId("a".to_owned()),
+                content: "Saved".to_owned(),
+                level: 1,
+                anchor: "saved".to_owned(),
+                metadata: serde_json::json!({}),
+                range_start: 0,
+                range_end: 8,
+            }],
+        )
+        .unwrap();
+        db
+    }
+
+    fn edges(graph: &LinkGraph) -> Vec<(String, String)> {
+        let mut edges: Vec<_> = graph
+            .edges
+            .iter()
+            .map(|(from, to)| (graph.nodes[*from].0.clone(), graph.nodes[*to].0.clone()))
+            .collect();
+        edges.sort();
+        edges
+    }
+
+    #[test]
+    fn test_overlay() {
+        let db = setup();
+        let root = Path::new("/notes");
+        let path = Path::new("/notes/a.md");
+        let mut overlay = Overlay::default();
+        let id = DocumentId("a".to_owned());
+
+        assert_eq!(
+            edges(&overlay.link_graph(&db).unwrap()),
+            vec![("a".into(), "b".into())]
+        );
+        let headings = overlay.headings(&db, &id).unwrap();
+        assert_eq!(headings[0].content, "Saved");
+
+        let text = "# Unsaved\n\n- [ ] task [[c]]\n".to_owned();
+        overlay
+            .update(&Config::default(), root, path, text)
+            .unwrap();
+        assert_eq!(
+            edges(&overlay.link_graph(&db).unwrap()),
+            vec![("a".into(), "c".into())]
+        );
+        let headings = overlay.headings(&db, &id).unwrap();
+        assert_eq!(headings[0].content, "Unsaved");
+        assert_eq!(overlay.tasks(&db, &id).unwrap()[0].content, "task c");
+
+        let local = overlay.local_graph(&db, &id, 1).unwrap();
+        let mut titles: Vec<_> = local.nodes.iter().map(|n| n.title.as_str()).collect();
+        titles.sort();
+        assert_eq!(titles, vec!["C", "Unsaved"]);
+
+        overlay.close(path);
+        assert_eq!(
+            edges(&overlay.link_graph(&db).unwrap()),
+            vec![("a".into(), "b".into())]
+        );
+    }
+
+    #[test]
+    fn test_buffer_settings() {
+        let root = Path::new("/notes");
+        let path = Path::new("/notes/a.md");
+        let config = Config::default();
+        let links = |text: &str| {
+            Buffer::parse(&config, root, path, text.to_owned())
+                .unwrap()
+                .links
+                .len()
+        };
+        assert_eq!(links("# A\n\n[[c]]\n"), 1);
+        // parsed with the settings of the document
+        assert_eq!(
+            links("---\nzet:\n  parser:\n    wikilinks: false\n---\n# A\n\n[[c]]\n"),
+            0
+        );
+    }
+}
@@ -0,0 +1,631 @@
+//! The nodes of a parsed document, stored in a single vector.
+//!
+//! The parser pushes the nodes of a document in document order, a node being
+//! followed by the nodes within it. A node does not own its children, every
+//! node knows its parent and where its subtree ends, and nodes refer to each
+//! other by their [`NodeId`]. Walking the document is then a loop over the
+//! vector, and the ancestors of a node, e.g. the list item or section it is
+//! in, are found without keeping track of the path from the root.
+//!
+//! An [`Ast`] is serialized as a tree, each node listing the nodes within it
+//! as its `children`, save for the sub lists ending a list item, listed as
+//! its `sub_lists`, and the content of a table, listed as the `children` of
+//! its cells.
+
+use std::ops::{Index, IndexMut};
+
+use serde::de::Error as _;
+use serde::ser::{SerializeStruct, SerializeStructVariant};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::core::parser::ast_nodes::{Node, NodeKind, Range, TableCell};
+
+/// The index of a node in an [`Ast`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(u32);
+
+impl NodeId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+#[derive(Debug)]
+pub struct NodeData {
+    pub node: Node,
+    pub parent: Option<NodeId>,
+    /// the index following the last node of the subtree of the node
+    end: u32,
+}
+
+/// The nodes of a document in document order, a node being followed by the
+/// nodes within it
+#[derive(Debug, Default)]
+pub struct Ast {
+    nodes: Vec<NodeData>,
+}
+
+impl Ast {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `node` after the nodes added so far, within `parent`, which is the
+    /// last node added or one of the nodes containing it, or at the top
+    /// level
+    pub fn push(&mut self, node: Node, parent: Option<NodeId>) -> NodeId {
+        let id = NodeId(self.nodes.len() as u32);
+        debug_assert!(parent.is_none_or(|parent| self.data(parent).end == id.0));
+        self.nodes.push(NodeData {
+            node,
+            parent,
+            end: id.0 + 1,
+        });
+        let mut ancestor = parent;
+        while let Some(id) = ancestor {
+            let data = &mut self.nodes[id.index()];
+            data.end += 1;
+            ancestor = data.parent;
+        }
+        id
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn data(&self, id: NodeId) -> &NodeData {
+        &self.nodes[id.index()]
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.data(id).node
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.data(id).parent
+    }
+
+    /// All nodes, in document order
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Node> {
+        self.nodes.iter().map(|data| &data.node)
+    }
+
+    /// All nodes, in document order
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut Node> {
+        self.nodes.iter_mut().map(|data| &mut data.node)
+    }
+
+    /// The nodes and their parents, in document order, to build another
+    /// [`Ast`] from them
+    pub fn into_nodes(self) -> impl Iterator<Item = (Node, Option<NodeId>)> {
+        self.nodes.into_iter().map(|data| (data.node, data.parent))
+    }
+
+    /// The ids of all nodes, in document order
+    pub fn ids(&self) -> impl DoubleEndedIterator<Item = NodeId> + use<> {
+        (0..self.nodes.len() as u32).map(NodeId)
+    }
+
+    /// The top level nodes
+    pub fn roots(&self) -> Siblings<'_> {
+        Siblings {
+            ast: self,
+            next: 0,
+            end: self.nodes.len() as u32,
+        }
+    }
+
+    /// The nodes directly within the node `id`
+    pub fn children(&self, id: NodeId) -> Siblings<'_> {
+        Siblings {
+            ast: self,
+            next: id.0 + 1,
+            end: self.data(id).end,
+        }
+    }
+
+    /// The nodes within the node `id` at any depth, in document order
+    pub fn descendants(&self, id: NodeId) -> impl Iterator<Item = NodeId> + use<> {
+        (id.0 + 1..self.data(id).end).map(NodeId)
+    }
+
+    /// The nodes containing the node `id`, innermost first
+    pub fn ancestors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::successors(self.parent(id), |&id| self.parent(id))
+    }
+
+    /// The heading of the innermost section containing the node `id`
+    pub fn section(&self, id: NodeId) -> Option<NodeId> {
+        self.ancestors(id)
+            .find(|&id| matches!(self.node(id), Node::Heading { .. }))
+    }
+
+    /// The node `id`, to query the nodes around it
+    pub fn cursor(&self, id: NodeId) -> Cursor<'_> {
+        Cursor { ast: self, id }
+    }
+
+    /// The innermost node whose range contains the byte `offset`
+    pub fn node_at_offset(&self, offset: usize) -> Option<Cursor<'_>> {
+        // a node containing the offset precedes the nodes within it
+        self.ids()
+            .rev()
+            .find(|&id| self.node(id).range().contains(&offset))
+            .map(|id| self.cursor(id))
+    }
+
+    /// The nodes of `kind`, in document order
+    pub fn nodes_of_kind(&self, kind: NodeKind) -> impl Iterator<Item = Cursor<'_>> {
+        self.cursors(self.ids())
+            .filter(move |cursor| cursor.node().kind() == kind)
+    }
+
+    /// The links overlapping `range`, or containing it if it is empty, in
+    /// document order
+    pub fn find_links_in(&self, range: Range) -> impl Iterator<Item = Cursor<'_>> {
+        links_in(self.cursors(self.ids()), range)
+    }
+
+    fn cursors(&self, ids: impl Iterator<Item = NodeId>) -> impl Iterator<Item = Cursor<'_>> {
+        ids.map(|id| self.cursor(id))
+    }
+
+    /// Push the serialized node `tree` within `parent`, followed by the nodes
+    /// within it
+    fn push_tree(&mut self, mut tree: Value, parent: Option<NodeId>) -> serde_json::Result<()> {
+        let mut children = Vec::new();
+        let fields = tree
+            .as_object_mut()
+            .and_then(|variant| variant.values_mut().next())
+            .and_then(Value::as_object_mut);
+        if let Some(fields) = fields {
+            for name in ["children", "sub_lists"] {
+                if let Some(Value::Array(nodes)) = fields.remove(name) {
+                    children.extend(nodes);
+                }
+            }
+            // the header, then the rows of a table
+            for name in ["header", "rows"] {
+                let rows = match fields.get_mut(name) {
+                    Some(Value::Array(rows)) => rows.iter_mut().collect(),
+                    Some(header) => vec![header],
+                    None => continue,
+                };
+                for row in rows {
+                    let Some(Value::Array(cells)) = row.get_mut("cells") else {
+                        continue;
+                    };
+                    for cell in cells.iter_mut().filter_map(Value::as_object_mut) {
+                        if let Some(Value::Array(nodes)) = cell.remove("children") {
+                            children.extend(nodes);
+                        }
+                    }
+                }
+            }
+        }
+        let id = self.push(serde_json::from_value(tree)?, parent);
+        for child in children {
+            self.push_tree(child, Some(id))?;
+        }
+        Ok(())
+    }
+}
+
+/// The nodes among `cursors` that are links overlapping `range`, or
+/// containing it if it is empty
+fn links_in<'a>(
+    cursors: impl Iterator<Item = Cursor<'a>>,
+    range: Range,
+) -> impl Iterator<Item = Cursor<'a>> {
+    cursors.filter(move |cursor| {
+        let node = cursor.node();
+        let link = node.range();
+        node.link_target().is_some()
+            && if range.is_empty() {
+                link.contains(&range.start)
+            } else {
+                link.start < range.end && range.start < link.end
+            }
+    })
+}
+
+/// A node of an [`Ast`], from which the nodes around it are found
+#[derive(Debug, Clone, Copy)]
+pub struct Cursor<'a> {
+    ast: &'a Ast,
+    id: NodeId,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    pub fn node(&self) -> &'a Node {
+        self.ast.node(self.id)
+    }
+
+    pub fn parent(&self) -> Option<Self> {
+        self.ast.parent(self.id).map(|id| self.ast.cursor(id))
+    }
+
+    /// The nodes containing the node, innermost first
+    pub fn ancestors(&self) -> impl Iterator<Item = Cursor<'a>> + use<'a> {
+        let ast = self.ast;
+        ast.ancestors(self.id).map(|id| ast.cursor(id))
+    }
+
+    /// The nodes directly within the node
+    pub fn children(&self) -> impl Iterator<Item = Cursor<'a>> + use<'a> {
+        let ast = self.ast;
+        ast.children(self.id).map(|id| ast.cursor(id))
+    }
+
+    /// The nodes of `kind` within the node at any depth, in document order
+    pub fn descendants_of_kind(
+        &self,
+        kind: NodeKind,
+    ) -> impl Iterator<Item = Cursor<'a>> + use<'a> {
+        let ast = self.ast;
+        ast.cursors(ast.descendants(self.id))
+            .filter(move |cursor| cursor.node().kind() == kind)
+    }
+
+    /// The links within the node overlapping `range`, or containing it if
+    /// it is empty, in document order
+    pub fn find_links_in(&self, range: Range) -> impl Iterator<Item = Cursor<'a>> + use<'a> {
+        let ast = self.ast;
+        links_in(ast.cursors(ast.descendants(self.id)), range)
+    }
+}
+
+impl Index<NodeId> for Ast {
+    type Output = Node;
+
+    fn index(&self, id: NodeId) -> &Node {
+        self.node(id)
+    }
+}
+
+impl IndexMut<NodeId> for Ast {
+    fn index_mut(&mut self, id: NodeId) -> &mut Node {
+        &mut self.nodes[id.index()].node
+    }
+}
+
+/// The nodes directly within a node, or the top level nodes, of an [`Ast`]
+#[derive(Clone)]
+pub struct Siblings<'a> {
+    ast: &'a Ast,
+    next: u32,
+    end: u32,
+}
+
+impl Iterator for Siblings<'_> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        if self.next >= self.end {
+            return None;
+        }
+        let id = NodeId(self.next);
+        // the next sibling follows the subtree of the node
+        self.next = self.ast.data(id).end;
+        Some(id)
+    }
+}
+
+impl Serialize for Ast {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Trees::new(self, self.roots()).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Ast {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut ast = Self::new();
+        for tree in Vec::<Value>::deserialize(deserializer)? {
+            ast.push_tree(tree, None).map_err(D::Error::custom)?;
+        }
+        Ok(ast)
+    }
+}
+
+/// The nodes `ids` of an [`Ast`] and the nodes within them, serialized as a
+/// list of trees
+struct Trees<'a, I> {
+    ast: &'a Ast,
+    ids: I,
+}
+
+impl<'a, I: Iterator<Item = NodeId> + Clone> Trees<'a, I> {
+    fn new(ast: &'a Ast, ids: I) -> Self {
+        Self { ast, ids }
+    }
+}
+
+impl<I: Iterator<Item = NodeId> + Clone> Serialize for Trees<'_, I> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let ast = self.ast;
+        serializer.collect_seq(self.ids.clone().map(|id| Tree { ast, id }))
+    }
+}
+
+/// A node of an [`Ast`] serialized with the nodes within it, the way it was
+/// when nodes owned their children
+struct Tree<'a> {
+    ast: &'a Ast,
+    id: NodeId,
+}
+
+impl Serialize for Tree<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (ast, id) = (self.ast, self.id);
+        let children = Trees::new(ast, ast.children(id));
+        let mut fields = match &ast[id] {
+            Node::Heading {
+                range,
+                id,
+                classes,
+                attributes,
+                level,
+                content,
+            } => {
+                let mut fields = serializer.serialize_struct_variant("Node", 0, "Heading", 7)?;
+                fields.serialize_field("range", range)?;
+                fields.serialize_field("id", id)?;
+                fields.serialize_field("classes", classes)?;
+                fields.serialize_field("attributes", attributes)?;
+                fields.serialize_field("level", level)?;
+                fields.serialize_field("content", content)?;
+                fields
+            }
+            Node::Paragraph { range } => {
+                let mut fields = serializer.serialize_struct_variant("Node", 1, "Paragraph", 2)?;
+                fields.serialize_field("range", range)?;
+                fields
+            }
+            Node::BlockQuote { range, kind, depth } => {
+                let mut fields = serializer.serialize_struct_variant("Node", 2, "BlockQuote", 4)?;
+                fields.serialize_field("range", range)?;
+                fields.serialize_field("kind", kind)?;
+                fields.serialize_field("depth", depth)?;
+                fields
+            }
+            Node::List {
+                range,
+                start_index,
+                tight,
+            } => {
+                let mut fields = serializer.serialize_struct_variant("Node", 3, "List", 4)?;
+                fields.serialize_field("range", range)?;
+                fields.serialize_field("start_index", start_index)?;
+                fields.serialize_field("tight", tight)?;
+                fields
+            }
+            Node::Item {
+                range,
+                task_list_marker,
+            } => {
+                // the lists ending the item are its sub lists
+                let ids: Vec<NodeId> = ast.children(id).collect();
+                let content = ids
+                    .iter()
+                    .rposition(|&id| !matches!(ast[id], Node::List { .. }))
+                    .map_or(0, |i| i + 1);
+                let mut fields = serializer.serialize_struct_variant("Node", 4, "Item", 4)?;
+                fields.serialize_field("range", range)?;
+                fields.serialize_field("task_list_marker", task_list_marker)?;
+                let (children, sub_lists) = ids.split_at(content);
+                fields.serialize_field("children", &Trees::new(ast, children.iter().copied()))?;
+                fields.serialize_field("sub_lists", &Trees::new(ast, sub_lists.iter().copied()))?;
+                return fields.end();
+            }
+            Node::CodeBlock {
+                range,
+                tag,
+                is_fenced,
+            } => {
+                let mut fields = serializer.serialize_struct_variant("Node", 5, "CodeBlock", 4)?;
+                fields.serialize_field("range", range)?;
+                fields.serialize_field("tag", tag)?;
+                fields.serialize_field("is_fenced", is_fenced)?;
+                fields
+            }
+            Node::Table {
+                range,
+                header,
+                column_alignment,
+                rows,
+            } => {
+                let row = |name, range, cells| Row {
+                    name,
+                    ast,
+                    table: id,
+                    range,
+                    cells,
+                };
+                let rows: Vec<Row> = rows
+                    .iter()
+                    .map(|r| row("TableRow", &r.range, &r.cells))
+                    .collect();
+                let mut fields = serializer.serialize_struct_variant("Node", 7, "Table", 4)?;
+                fields.serialize_field("range", range)?;
+                fields
+                    .serialize_field("header", &row("TableHead", &header.range, &header.cells))?;
+                fields.serialize_field("column_alignment", column_alignment)?;
+                fields.serialize_field("rows", &rows)?;
+                return fields.end();
+            }
+            node => return node.serialize(serializer),
+        };
+        fields.serialize_field("children", &children)?;
+        fields.end()
+    }
+}
+
+/// The header or a row of the table `table`, its cells serialized with the
+/// nodes of the table within them
+struct Row<'a> {
+    name: &'static str,
+    ast: &'a Ast,
+    table: NodeId,
+    range: &'a Range,
+    cells: &'a [TableCell],
+}
+
+impl Serialize for Row<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let ast = self.ast;
+        let cells: Vec<Cell<_>> = self
+            .cells
+            .iter()
+            .map(|cell| Cell {
+                range: &cell.range,
+                children: Trees::new(
+                    ast,
+                    ast.children(self.table).filter(move |&id| {
+                        let range = ast[id].range();
+                        cell.range.start <= range.start && range.end <= cell.range.end
+                    }),
+                ),
+            })
+            .collect();
+        let mut fields = serializer.serialize_struct(self.name, 2)?;
+        fields.serialize_field("range", self.range)?;
+        fields.serialize_field("cells", &cells)?;
+        fields.end()
+    }
+}
+
+struct Cell<'a, I> {
+    range: &'a Range,
+    children: Trees<'a, I>,
+}
+
+impl<I: Iterator<Item = NodeId> + Clone> Serialize for Cell<'_, I> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut fields = serializer.serialize_struct("TableCell", 2)?;
+        fields.serialize_field("range", self.range)?;
+        fields.serialize_field("children", &self.children)?;
+        fields.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::{DocumentParser, Parse};
+
+    #[test]
+    fn test_ast() {
+        let body = "# A\n\nsee [[b]]\n\n- item\n  - [c](c)\n\n| x | [[d]] |\n| - | - |\n";
+        let ast = DocumentParser::new().parse(body).unwrap();
+        let kinds = |ids: &mut dyn Iterator<Item = NodeId>| -> Vec<NodeKind> {
+            ids.map(|id| ast[id].kind()).collect()
+        };
+
+        let roots: Vec<NodeId> = ast.roots().collect();
+        assert_eq!(kinds(&mut roots.iter().copied()), vec![NodeKind::Heading]);
+        assert_eq!(
+            kinds(&mut ast.children(roots[0])),
+            vec![NodeKind::Paragraph, NodeKind::List, NodeKind::Table]
+        );
+        assert_eq!(ast.descendants(roots[0]).count(), ast.len() - 1);
+
+        let link = |target: &str| {
+            ast.ids()
+                .find(|&id| ast[id].link_target() == Some(target))
+                .unwrap()
+        };
+        assert_eq!(
+            kinds(&mut ast.ancestors(link("c"))),
+            vec![
+                NodeKind::Item,
+                NodeKind::List,
+                NodeKind::Item,
+                NodeKind::List,
+                NodeKind::Heading
+            ]
+        );
+        assert_eq!(ast.section(link("d")), Some(roots[0]));
+        assert_eq!(ast.parent(roots[0]), None);
+
+        let offset = body.find("[[b]]").unwrap() + 2;
+        let cursor = ast.node_at_offset(offset).unwrap();
+        assert_eq!(cursor.id(), link("b"));
+        assert_eq!(
+            cursor
+                .ancestors()
+                .map(|c| c.node().kind())
+                .collect::<Vec<_>>(),
+            vec![NodeKind::Paragraph, NodeKind::Heading]
+        );
+        assert!(ast.node_at_offset(body.len() + 1).is_none());
+    }
+
+    #[test]
+    fn test_cursor() {
+        let body = "# A\n\n[[a]] [b](b)\n\n## B\n\n- [[c]]\n  - <https://d.org>\n";
+        let ast = DocumentParser::new().parse(body).unwrap();
+        let targets = |cursors: &mut dyn Iterator<Item = Cursor>| -> Vec<String> {
+            cursors
+                .map(|c| c.node().link_target().unwrap().to_owned())
+                .collect()
+        };
+
+        assert_eq!(
+            targets(&mut ast.find_links_in(0..body.len())),
+            vec!["a", "b", "c", "https://d.org"]
+        );
+        let b = body.find("[b]").unwrap();
+        assert_eq!(targets(&mut ast.find_links_in(b + 1..b + 1)), vec!["b"]);
+        assert_eq!(targets(&mut ast.find_links_in(0..b)), vec!["a"]);
+
+        let headings: Vec<Cursor> = ast.nodes_of_kind(NodeKind::Heading).collect();
+        assert_eq!(headings.len(), 2);
+        // sections of the same level are siblings
+        assert!(headings[1].parent().is_none());
+        assert_eq!(
+            targets(&mut headings[1].find_links_in(0..body.len())),
+            vec!["c", "https://d.org"]
+        );
+        assert_eq!(
+            headings[1]
+                .descendants_of_kind(NodeKind::Item)
+                .map(|item| item.children().count())
+                .collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+    }
+
+    #[test]
+    fn test_serialize() {
+        let body = "- a\n  - b\n\n| x | y |\n| - | - |\n| [[z]] | |\n";
+        let ast = DocumentParser::new().parse(body).unwrap();
+        let json = serde_json::to_value(&ast).unwrap();
+        let item = &json[0]["List"]["children"][0]["Item"];
+        assert_eq!(item["children"][0]["Text"]["text"], "a");
+        assert_eq!(item["sub_lists"].as_array().unwrap().len(), 1);
+        let table = &json[1]["Table"];
+        assert_eq!(
+            table["header"]["cells"][1]["children"][0]["Text"]["text"],
+            "y"
+        );
+        assert_eq!(
+            table["rows"][0]["cells"][0]["children"][0]["WikiLink"]["target"],
+            "z"
+        );
+        assert!(table.get("children").is_none());
+
+        let read: Ast = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(read.len(), ast.len());
+        assert_eq!(serde_json::to_value(&read).unwrap(), json);
+    }
+}
@@ -1,14 +1,55 @@
 use std::fmt::Display;
+use std::str::FromStr;
 
+use color_eyre::eyre::eyre;
+use rusqlite::ToSql;
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 
 pub type Range = std::ops::Range<usize>;
 
-#[derive(Debug, Serialize, Deserialize)]
+/// The version of the json shape of the nodes, as read by plugins and the
+/// output of `zet parse`. It is increased whenever a node is changed in a way
+/// existing readers would misread, e.g. a field is renamed or removed, not
+/// when a node or an optional field is added.
+pub const AST_VERSION: u32 = 1;
+
+/// The kinds of GFM alerts, block quotes starting with e.g. `> [!NOTE]`
+#[derive(PartialEq, Eq, Copy, Clone, Serialize, Deserialize, Debug)]
 pub enum BlockQuoteKind {
-    Inline,
-    Fenced(String),
+    Note,
+    Tip,
+    Important,
+    Warning,
+    Caution,
+}
+
+impl BlockQuoteKind {
+    /// The kind of alert marked by `marker`, e.g. `[!NOTE]`, in any case
+    pub fn from_marker(marker: &str) -> Option<Self> {
+        let name = marker.trim().strip_prefix("[!")?.strip_suffix(']')?;
+        match name.to_ascii_lowercase().as_str() {
+            "note" => Some(Self::Note),
+            "tip" => Some(Self::Tip),
+            "important" => Some(Self::Important),
+            "warning" => Some(Self::Warning),
+            "caution" => Some(Self::Caution),
+            _ => None,
+        }
+    }
+}
+
+impl From<pulldown_cmark::BlockQuoteKind> for BlockQuoteKind {
+    fn from(value: pulldown_cmark::BlockQuoteKind) -> Self {
+        match value {
+            pulldown_cmark::BlockQuoteKind::Note => Self::Note,
+            pulldown_cmark::BlockQuoteKind::Tip => Self::Tip,
+            pulldown_cmark::BlockQuoteKind::Important => Self::Important,
+            pulldown_cmark::BlockQuoteKind::Warning => Self::Warning,
+            pulldown_cmark::BlockQuoteKind::Caution => Self::Caution,
+        }
+    }
 }
 
 #[derive(PartialEq, Copy, Clone, Serialize, Deserialize, Debug)]
@@ -36,6 +77,52 @@ pub enum TaskListMarker {
     UnChecked,
     // - [x] some list
     Checked,
+    // - [/] some list
+    InProgress,
+    // - [-] some list
+    Cancelled,
+}
+
+/// The languages of fenced code blocks that hold a diagram
+#[derive(PartialEq, Eq, Copy, Clone, Serialize, Deserialize, Debug)]
+pub enum DiagramKind {
+    Mermaid,
+    /// graphviz
+    Dot,
+}
+
+impl DiagramKind {
+    /// The kind of diagram a code block with the info string `tag` holds
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        match tag.split([' ', ',', '{']).next()? {
+            "mermaid" => Some(Self::Mermaid),
+            "dot" | "graphviz" => Some(Self::Dot),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Mermaid => "mermaid",
+            Self::Dot => "dot",
+        }
+    }
+}
+
+impl Display for DiagramKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The syntaxes of comments, text that is not rendered
+#[derive(PartialEq, Eq, Copy, Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum CommentSyntax {
+    /// `%%...%%`, as obsidian writes them
+    Obsidian,
+    /// `<!-- ... -->`
+    Html,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -48,32 +135,38 @@ pub enum Node {
         attributes: Vec<(String, Option<String>)>,
         level: u8,
         content: String,
-        children: Vec<Node>,
     },
     Paragraph {
         range: Range,
-        children: Vec<Node>,
     },
     BlockQuote {
         range: Range,
-        children: Vec<Node>,
+        /// the kind of alert the quote is, if any
+        kind: Option<BlockQuoteKind>,
+        /// the number of block quotes the quote is within, plus one
+        depth: usize,
     },
     List {
         range: Range,
         start_index: Option<u64>,
-        children: Vec<Node>,
+        /// whether no blank line separates the items of the list or the
+        /// blocks within them, the items then not being paragraphs
+        tight: bool,
     },
     Item {
         range: Range,
         task_list_marker: TaskListMarker,
-        children: Vec<Node>,
-        sub_lists: Vec<Node>,
     },
     CodeBlock {
         range: Range,
         tag: Option<String>,
         is_fenced: bool,
-        children: Vec<Node>,
+    },
+    /// a fenced code block holding a diagram, e.g. ```mermaid
+    Diagram {
+        range: Range,
+        kind: DiagramKind,
+        source: String,
     },
     Table {
         range: Range,
@@ -99,6 +192,16 @@ pub enum Node {
         kind: TextDecorationKind,
         content: String,
     },
+    /// `==highlighted text==`
+    Highlight {
+        range: Range,
+        text: String,
+    },
+    /// `@name`, a mention of a person
+    Mention {
+        range: Range,
+        name: String,
+    },
     Html {
         range: Range,
         text: String,
@@ -167,6 +270,14 @@ pub enum Node {
         range: Range,
         text: String,
     },
+    /// a comment, inline or spanning blocks. Comments are top level nodes,
+    /// wherever they are written.
+    Comment {
+        range: Range,
+        syntax: CommentSyntax,
+        /// the text between the delimiters
+        text: String,
+    },
 }
 
 impl Node {
@@ -179,12 +290,12 @@ impl Node {
     pub fn inner_json_data(&self) -> Map<String, Value> {
         let value = serde_json::to_value(self).unwrap();
 
-        // we may assume that the value is an object and that the
-        // inner value is one as well.
-        let Value::Object(mut map) = value else {
+        // we may assume that the value is an object with a single entry,
+        // the variant, and that the inner value is one as well.
+        let Value::Object(map) = value else {
             unreachable!()
         };
-        let Some(Value::Object(mut map)) = map.remove(&self.kind().to_string()) else {
+        let Some((_, Value::Object(mut map))) = map.into_iter().next() else {
             unreachable!()
         };
         map.remove("range");
@@ -204,7 +315,6 @@ impl Node {
         attributes: Vec<(String, Option<String>)>,
         level: u8,
         content: String,
-        children: Vec<Node>,
     ) -> Self {
         Self::Heading {
             range,
@@ -213,14 +323,13 @@ impl Node {
             attributes,
             level,
             content,
-            children,
         }
     }
-    pub fn paragraph(range: Range, children: Vec<Node>) -> Self {
-        Self::Paragraph { children, range }
+    pub fn paragraph(range: Range) -> Self {
+        Self::Paragraph { range }
     }
-    pub fn blockquote(range: Range, children: Vec<Node>) -> Self {
-        Self::BlockQuote { range, children }
+    pub fn blockquote(range: Range, kind: Option<BlockQuoteKind>, depth: usize) -> Self {
+        Self::BlockQuote { range, kind, depth }
     }
     pub fn text(range: Range, text: String) -> Self {
         Self::Text { text, range }
@@ -232,6 +341,12 @@ impl Node {
             range,
         }
     }
+    pub fn highlight(range: Range, text: String) -> Self {
+        Self::Highlight { range, text }
+    }
+    pub fn mention(range: Range, name: String) -> Self {
+        Self::Mention { range, name }
+    }
     pub fn html(range: Range, text: String) -> Self {
         Self::Html { text, range }
     }
@@ -283,42 +398,36 @@ impl Node {
     pub fn referenceimage(range: Range) -> Self {
         Self::ReferenceImage { range }
     }
-    pub fn list(range: Range, start_index: Option<u64>, children: Vec<Node>) -> Self {
+    pub fn list(range: Range, start_index: Option<u64>, tight: bool) -> Self {
         Self::List {
             start_index,
-            children,
+            tight,
             range,
         }
     }
-    pub fn item(
-        range: Range,
-        task_list_marker: TaskListMarker,
-        children: Vec<Node>,
-        sub_lists: Vec<Node>,
-    ) -> Self {
+    pub fn item(range: Range, task_list_marker: TaskListMarker) -> Self {
         Self::Item {
-            children,
             task_list_marker,
-            sub_lists,
             range,
         }
     }
     pub fn code(range: Range, code: String) -> Self {
         Self::Code { code, range }
     }
-    pub fn codeblock(
-        range: Range,
-        tag: Option<String>,
-        is_fenced: bool,
-        children: Vec<Node>,
-    ) -> Self {
+    pub fn codeblock(range: Range, tag: Option<String>, is_fenced: bool) -> Self {
         Self::CodeBlock {
             tag,
             is_fenced,
-            children,
             range,
         }
     }
+    pub fn diagram(range: Range, kind: DiagramKind, source: String) -> Self {
+        Self::Diagram {
+            range,
+            kind,
+            source,
+        }
+    }
     pub fn horizontalrule(range: Range) -> Self {
         Self::HorizontalRule { range }
     }
@@ -341,6 +450,13 @@ impl Node {
     pub fn inlinemath(range: Range, text: String) -> Self {
         Self::InlineMath { text, range }
     }
+    pub fn comment(range: Range, syntax: CommentSyntax, text: String) -> Self {
+        Self::Comment {
+            range,
+            syntax,
+            text,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -353,10 +469,11 @@ pub struct TableRow {
     pub range: Range,
     pub cells: Vec<TableCell>,
 }
+/// A cell of a table, the nodes within it being the nodes of the table
+/// within its range
 #[derive(Serialize, Deserialize, Debug)]
 pub struct TableCell {
     pub range: Range,
-    pub children: Vec<Node>,
 }
 
 impl TableHead {
@@ -370,8 +487,8 @@ impl TableRow {
     }
 }
 impl TableCell {
-    pub fn new(range: Range, children: Vec<Node>) -> Self {
-        Self { range, children }
+    pub fn new(range: Range) -> Self {
+        Self { range }
     }
 }
 
@@ -384,6 +501,8 @@ pub enum NodeKind {
     BlockQuote,
     Text,
     TextDecoration,
+    Highlight,
+    Mention,
     Html,
     FootnoteReference,
     FootnoteDefinition,
@@ -400,6 +519,7 @@ pub enum NodeKind {
     HardBreak,
     Code,
     CodeBlock,
+    Diagram,
     HorizontalRule,
     Table,
     TableHead,
@@ -407,11 +527,120 @@ pub enum NodeKind {
     TableCell,
     DisplayMath,
     InlineMath,
+    Comment,
+}
+
+impl NodeKind {
+    pub fn all() -> [NodeKind; 34] {
+        [
+            NodeKind::Document,
+            NodeKind::NotImplemented,
+            NodeKind::Heading,
+            NodeKind::Paragraph,
+            NodeKind::BlockQuote,
+            NodeKind::Text,
+            NodeKind::TextDecoration,
+            NodeKind::Highlight,
+            NodeKind::Mention,
+            NodeKind::Html,
+            NodeKind::FootnoteReference,
+            NodeKind::FootnoteDefinition,
+            NodeKind::InlineLink,
+            NodeKind::ReferenceLink,
+            NodeKind::ShortcutLink,
+            NodeKind::AutoLink,
+            NodeKind::WikiLink,
+            NodeKind::LinkReference,
+            NodeKind::InlineImage,
+            NodeKind::ReferenceImage,
+            NodeKind::List,
+            NodeKind::Item,
+            NodeKind::HardBreak,
+            NodeKind::Code,
+            NodeKind::CodeBlock,
+            NodeKind::Diagram,
+            NodeKind::HorizontalRule,
+            NodeKind::Table,
+            NodeKind::TableHead,
+            NodeKind::TableRow,
+            NodeKind::TableCell,
+            NodeKind::DisplayMath,
+            NodeKind::InlineMath,
+            NodeKind::Comment,
+        ]
+    }
+
+    /// The name the kind is stored as, which is kept when a variant is
+    /// renamed
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NodeKind::Document => "document",
+            NodeKind::NotImplemented => "not_implemented",
+            NodeKind::Heading => "heading",
+            NodeKind::Paragraph => "paragraph",
+            NodeKind::BlockQuote => "block_quote",
+            NodeKind::Text => "text",
+            NodeKind::TextDecoration => "text_decoration",
+            NodeKind::Highlight => "highlight",
+            NodeKind::Mention => "mention",
+            NodeKind::Html => "html",
+            NodeKind::FootnoteReference => "footnote_reference",
+            NodeKind::FootnoteDefinition => "footnote_definition",
+            NodeKind::InlineLink => "inline_link",
+            NodeKind::ReferenceLink => "reference_link",
+            NodeKind::ShortcutLink => "shortcut_link",
+            NodeKind::AutoLink => "auto_link",
+            NodeKind::WikiLink => "wiki_link",
+            NodeKind::LinkReference => "link_reference",
+            NodeKind::InlineImage => "inline_image",
+            NodeKind::ReferenceImage => "reference_image",
+            NodeKind::List => "list",
+            NodeKind::Item => "item",
+            NodeKind::HardBreak => "hard_break",
+            NodeKind::Code => "code",
+            NodeKind::CodeBlock => "code_block",
+            NodeKind::Diagram => "diagram",
+            NodeKind::HorizontalRule => "horizontal_rule",
+            NodeKind::Table => "table",
+            NodeKind::TableHead => "table_head",
+            NodeKind::TableRow => "table_row",
+            NodeKind::TableCell => "table_cell",
+            NodeKind::DisplayMath => "display_math",
+            NodeKind::InlineMath => "inline_math",
+            NodeKind::Comment => "comment",
+        }
+    }
 }
 
 impl Display for NodeKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for NodeKind {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::all()
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| eyre!("unknown node kind {:?}", s))
+    }
+}
+
+impl ToSql for NodeKind {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        self.as_str().to_sql()
+    }
+}
+
+impl FromSql for NodeKind {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value
+            .as_str()?
+            .parse()
+            .map_err(|e: color_eyre::Report| FromSqlError::Other(e.into()))
     }
 }
 
@@ -431,6 +660,8 @@ impl Node {
             Node::BlockQuote { .. } => BlockQuote,
             Node::Text { .. } => Text,
             Node::TextDecoration { .. } => TextDecoration,
+            Node::Highlight { .. } => Highlight,
+            Node::Mention { .. } => Mention,
             Node::Html { .. } => Html,
             Node::FootnoteReference { .. } => FootnoteReference,
             Node::FootnoteDefinition { .. } => FootnoteDefinition,
@@ -446,11 +677,130 @@ impl Node {
             Node::Item { .. } => Item,
             Node::Code { .. } => Code,
             Node::CodeBlock { .. } => CodeBlock,
+            Node::Diagram { .. } => Diagram,
             Node::HorizontalRule { .. } => HorizontalRule,
             Node::Table { .. } => Table,
             Node::DisplayMath { .. } => DisplayMath,
             Node::InlineMath { .. } => InlineMath,
             Node::HardBreak { .. } => HardBreak,
+            Node::Comment { .. } => Comment,
+        }
+    }
+
+    pub fn range(&self) -> &Range {
+        match self {
+            Node::Heading { range, .. }
+            | Node::Paragraph { range, .. }
+            | Node::BlockQuote { range, .. }
+            | Node::List { range, .. }
+            | Node::Item { range, .. }
+            | Node::CodeBlock { range, .. }
+            | Node::Diagram { range, .. }
+            | Node::Table { range, .. }
+            | Node::HardBreak { range }
+            | Node::FootnoteDefinition { range, .. }
+            | Node::Text { range, .. }
+            | Node::TextDecoration { range, .. }
+            | Node::Highlight { range, .. }
+            | Node::Mention { range, .. }
+            | Node::Html { range, .. }
+            | Node::FootnoteReference { range, .. }
+            | Node::InlineLink { range, .. }
+            | Node::ReferenceLink { range, .. }
+            | Node::ShortcutLink { range, .. }
+            | Node::AutoLink { range, .. }
+            | Node::WikiLink { range, .. }
+            | Node::LinkReference { range, .. }
+            | Node::InlineImage { range }
+            | Node::ReferenceImage { range }
+            | Node::Code { range, .. }
+            | Node::HorizontalRule { range }
+            | Node::DisplayMath { range, .. }
+            | Node::InlineMath { range, .. }
+            | Node::Comment { range, .. } => range,
+        }
+    }
+
+    /// The target of a link node
+    pub fn link_target(&self) -> Option<&str> {
+        match self {
+            Node::InlineLink { target, .. }
+            | Node::ReferenceLink { target, .. }
+            | Node::ShortcutLink { target, .. }
+            | Node::AutoLink { target, .. }
+            | Node::WikiLink { target, .. } => Some(target),
+            _ => None,
+        }
+    }
+
+    /// Apply `f` to the range of the node and, for a table, the ranges of
+    /// its rows and cells
+    pub fn map_ranges(&mut self, f: &impl Fn(&mut Range)) {
+        if let Node::Table { header, rows, .. } = self {
+            let rows = std::iter::once((&mut header.range, &mut header.cells))
+                .chain(rows.iter_mut().map(|row| (&mut row.range, &mut row.cells)));
+            for (range, cells) in rows {
+                f(range);
+                cells.iter_mut().for_each(|cell| f(&mut cell.range));
+            }
+        }
+        f(self.range_mut());
+    }
+
+    fn range_mut(&mut self) -> &mut Range {
+        match self {
+            Node::Heading { range, .. }
+            | Node::Paragraph { range, .. }
+            | Node::BlockQuote { range, .. }
+            | Node::List { range, .. }
+            | Node::Item { range, .. }
+            | Node::CodeBlock { range, .. }
+            | Node::Diagram { range, .. }
+            | Node::Table { range, .. }
+            | Node::HardBreak { range }
+            | Node::FootnoteDefinition { range, .. }
+            | Node::Text { range, .. }
+            | Node::TextDecoration { range, .. }
+            | Node::Highlight { range, .. }
+            | Node::Mention { range, .. }
+            | Node::Html { range, .. }
+            | Node::FootnoteReference { range, .. }
+            | Node::InlineLink { range, .. }
+            | Node::ReferenceLink { range, .. }
+            | Node::ShortcutLink { range, .. }
+            | Node::AutoLink { range, .. }
+            | Node::WikiLink { range, .. }
+            | Node::LinkReference { range, .. }
+            | Node::InlineImage { range }
+            | Node::ReferenceImage { range }
+            | Node::Code { range, .. }
+            | Node::HorizontalRule { range }
+            | Node::DisplayMath { range, .. }
+            | Node::InlineMath { range, .. }
+            | Node::Comment { range, .. } => range,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_kind_names() {
+        for kind in NodeKind::all() {
+            assert_eq!(kind.as_str().parse::<NodeKind>().unwrap(), kind);
+        }
+        assert_eq!(NodeKind::FootnoteReference.as_str(), "footnote_reference");
+        assert!("Heading".parse::<NodeKind>().is_err());
+    }
+
+    #[test]
+    fn test_inner_json_data() {
+        let node = Node::code(0..3, "a".to_owned());
+        assert_eq!(
+            Value::Object(node.inner_json_data()),
+            serde_json::json!({"code": "a"})
+        );
+    }
+}
@@ -0,0 +1,226 @@
+//! Comments, text that is written but not rendered.
+//!
+//! An obsidian comment runs from a `%%` to the next one, or to the end of the
+//! document when it is not closed, and may span blocks. Html comments are the
+//! `<!-- -->` comments markdown passes through as html. Comments are not
+//! recognized in code, math or other html.
+
+use std::ops::Range;
+
+use pulldown_cmark::{Event, Parser, Tag};
+use serde::{Deserialize, Serialize};
+
+use crate::core::parser::DocumentParserOptions;
+use crate::core::parser::ast_nodes::CommentSyntax;
+
+/// The `[parser]` section of the configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParserConfig {
+    /// the comment syntaxes recognized. Comments are left out of the search
+    /// index and of exports, and left as written by `zet format`.
+    pub comments: Vec<CommentSyntax>,
+    /// `[[wiki links]]`
+    pub wikilinks: bool,
+    /// GFM alerts, block quotes starting with e.g. `> [!NOTE]`
+    pub alerts: bool,
+    pub footnotes: bool,
+    /// `$inline$` and `$$display$$` math
+    pub math: bool,
+    pub tables: bool,
+    /// `- [ ]` and `- [x]` task list items
+    pub task_lists: bool,
+    /// `~~struck through~~` text
+    pub strikethrough: bool,
+    /// `{#id .class key=value}` attributes after the text of headings
+    pub heading_attributes: bool,
+    /// `#tags` written in the text of documents, besides the `tags` of the
+    /// frontmatter
+    pub inline_tags: bool,
+}
+
+impl Default for ParserConfig {
+    fn default() -> Self {
+        Self {
+            comments: vec![CommentSyntax::Obsidian, CommentSyntax::Html],
+            wikilinks: true,
+            alerts: true,
+            footnotes: true,
+            math: true,
+            tables: true,
+            task_lists: true,
+            strikethrough: true,
+            heading_attributes: true,
+            inline_tags: true,
+        }
+    }
+}
+
+/// A comment, found in a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    /// the range of the comment, delimiters included
+    pub range: Range<usize>,
+    pub syntax: CommentSyntax,
+}
+
+impl Comment {
+    /// The text between the delimiters of the comment, in `document`
+    pub fn text<'a>(&self, document: &'a str) -> &'a str {
+        let source = &document[self.range.clone()];
+        let (open, close) = match self.syntax {
+            CommentSyntax::Obsidian => ("%%", "%%"),
+            CommentSyntax::Html => ("<!--", "-->"),
+        };
+        let source = source.strip_prefix(open).unwrap_or(source);
+        source.strip_suffix(close).unwrap_or(source)
+    }
+}
+
+/// The comments of `document` written in one of `syntaxes`, in order
+pub fn find_comments(document: &str, syntaxes: &[CommentSyntax]) -> Vec<Comment> {
+    let obsidian = syntaxes.contains(&CommentSyntax::Obsidian) && document.contains("%%");
+    let html = syntaxes.contains(&CommentSyntax::Html) && document.contains("<!--");
+    if !obsidian && !html {
+        return Vec::new();
+    }
+
+    // code, math and html, where obsidian comments are not recognized
+    let mut literal = Vec::new();
+    let mut comments: Vec<Comment> = Vec::new();
+    let parser = Parser::new_ext(document, DocumentParserOptions::default().0);
+    for (event, range) in parser.into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(_))
+            | Event::Code(_)
+            | Event::InlineMath(_)
+            | Event::DisplayMath(_) => literal.push(range),
+            Event::Start(Tag::HtmlBlock) | Event::InlineHtml(_) => {
+                let source = &document[range.clone()];
+                let start = range.start + source.len() - source.trim_start().len();
+                let within_comment = comments.last().is_some_and(|c| c.range.end > start);
+                if html && !within_comment && document[start..].starts_with("<!--") {
+                    // a comment may continue past the html event it starts in
+                    let end = document[start + "<!--".len()..]
+                        .find("-->")
+                        .map_or(range.end, |i| start + "<!--".len() + i + "-->".len());
+                    comments.push(Comment {
+                        range: start..end,
+                        syntax: CommentSyntax::Html,
+                    });
+                }
+                literal.push(range);
+            }
+            _ => {}
+        }
+    }
+
+    if obsidian {
+        let is_literal = |i: usize| {
+            literal
+                .iter()
+                .chain(comments.iter().map(|c| &c.range))
+                .any(|r| r.contains(&i))
+        };
+        let mut found = Vec::new();
+        let mut open = None;
+        for (i, _) in document.match_indices("%%") {
+            if is_literal(i) {
+                continue;
+            }
+            match open.take() {
+                None => open = Some(i),
+                Some(start) => found.push(start..i + "%%".len()),
+            }
+        }
+        found.extend(open.map(|start| start..document.len()));
+        comments.extend(found.into_iter().map(|range| Comment {
+            range,
+            syntax: CommentSyntax::Obsidian,
+        }));
+    }
+
+    // html comments within obsidian comments are part of them
+    comments.sort_by_key(|c| c.range.start);
+    let mut end = 0;
+    comments.retain(|c| {
+        let keep = c.range.start >= end;
+        end = end.max(c.range.end);
+        keep
+    });
+    comments
+}
+
+/// `document` without `comments`
+pub fn remove_comments(document: &str, comments: &[Comment]) -> String {
+    let mut stripped = String::with_capacity(document.len());
+    let mut offset = 0;
+    for comment in comments {
+        stripped.push_str(&document[offset..comment.range.start]);
+        offset = comment.range.end;
+    }
+    stripped.push_str(&document[offset..]);
+    stripped
+}
+
+/// `document` without its comments written in one of `syntaxes`
+pub fn strip_comments(document: &str, syntaxes: &[CommentSyntax]) -> String {
+    remove_comments(document, &find_comments(document, syntaxes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: &[CommentSyntax] = &[CommentSyntax::Obsidian, CommentSyntax::Html];
+
+    #[test]
+    fn test_find_comments() {
+        let document = "a %%inline%% b\n\n%%\nblock\n\nspanning\n%%\n\n<!-- html -->\n\nc <!-- inline\nhtml --> d\n";
+        let comments = find_comments(document, ALL);
+        let texts: Vec<_> = comments.iter().map(|c| c.text(document)).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "inline",
+                "\nblock\n\nspanning\n",
+                " html ",
+                " inline\nhtml "
+            ]
+        );
+        assert_eq!(comments[2].syntax, CommentSyntax::Html);
+        assert_eq!(
+            &document[comments[0].range.clone()],
+            "%%inline%%",
+            "{comments:?}"
+        );
+
+        // only the configured syntaxes
+        let comments = find_comments(document, &[CommentSyntax::Html]);
+        assert!(comments.iter().all(|c| c.syntax == CommentSyntax::Html));
+        assert!(find_comments(document, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_literal() {
+        let document = "`%%` and\n\n```\n%% code\n```\n\n$a %% b$ %%comment\n";
+        let comments = find_comments(document, ALL);
+        assert_eq!(comments.len(), 1);
+        // an unclosed comment runs to the end
+        assert_eq!(comments[0].text(document), "comment\n");
+
+        let document = "%% <!-- within --> %% <!-- after -->";
+        let comments = find_comments(document, ALL);
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[1].text(document), " after ");
+    }
+
+    #[test]
+    fn test_strip_comments() {
+        assert_eq!(strip_comments("a %%b%% c <!-- d --> e\n", ALL), "a  c  e\n");
+        assert_eq!(
+            strip_comments("a %%b%% c <!-- d --> e\n", &[CommentSyntax::Obsidian]),
+            "a  c <!-- d --> e\n"
+        );
+    }
+}
@@ -1,14 +1,18 @@
+pub mod arena;
 pub mod ast_nodes;
+pub mod comments;
+#[cfg(feature = "tree-sitter")]
+pub mod tree_sitter;
 
 use crate::preamble::*;
 
+use crate::core::frontmatter::split_frontmatter;
+use crate::core::parser::arena::{Ast, NodeId};
 use crate::core::parser::ast_nodes::*;
+use crate::core::parser::comments::{Comment, ParserConfig, find_comments, remove_comments};
 use clap::ValueEnum;
 use color_eyre::eyre::eyre;
-use gray_matter::{
-    Matter,
-    engine::{JSON, TOML, YAML},
-};
+use gray_matter::engine::{Engine, JSON, TOML, YAML};
 use pulldown_cmark::{
     CodeBlockKind, CowStr, Event, HeadingLevel, LinkType, OffsetIter, Options, Parser, Tag, TagEnd,
 };
@@ -20,6 +24,8 @@ use std::{fmt::Display, iter::Peekable, ops::Range};
 pub struct ParserIterator<'a> {
     inner: Peekable<OffsetIter<'a>>,
     text: &'a str,
+    /// the number of block quotes being parsed
+    quote_depth: usize,
 }
 
 impl<'a> Iterator for ParserIterator<'a> {
@@ -36,12 +42,12 @@ impl<'a> Iterator for ParserIterator<'a> {
 
 pub fn parse(
     frontmatter_parser: FrontMatterParser,
-    document_parser: DocumentParser,
+    document_parser: impl Parse,
     document: String,
-) -> Result<(Option<serde_json::Value>, Vec<Node>)> {
+) -> Result<(Option<serde_json::Value>, Ast)> {
     let (frontmatter, content) = frontmatter_parser.parse(document);
 
-    let events = document_parser.parse(content)?;
+    let events = document_parser.parse(&content)?;
 
     Ok((frontmatter, events))
 }
@@ -57,11 +63,11 @@ pub enum FrontMatterFormat {
     Json,
 }
 
-pub enum FrontMatterParser {
-    TomlParser(Matter<TOML>),
-    JsonParser(Matter<JSON>),
-    YamlParser(Matter<YAML>),
-}
+/// The parser of the frontmatter of documents. The frontmatter is found by
+/// [`split_frontmatter`], the boundaries every other reader of the body
+/// uses, so that the ranges of the nodes of the body are the same for all of
+/// them.
+pub struct FrontMatterParser(FrontMatterFormat);
 
 impl Display for FrontMatterFormat {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -71,43 +77,75 @@ impl Display for FrontMatterFormat {
 
 impl FrontMatterParser {
     pub fn new(format: FrontMatterFormat) -> Self {
-        match format {
-            FrontMatterFormat::Toml => FrontMatterParser::TomlParser(Matter::<TOML>::new()),
-            FrontMatterFormat::Json => FrontMatterParser::JsonParser(Matter::<JSON>::new()),
-            FrontMatterFormat::Yaml => FrontMatterParser::YamlParser(Matter::<YAML>::new()),
-        }
+        Self(format)
     }
 
+    /// The data of the frontmatter of `content`, if it has a non-empty one,
+    /// and the body after it, as written
     pub fn parse(&self, content: String) -> (Option<serde_json::Value>, String) {
-        let result = match self {
-            FrontMatterParser::TomlParser(matter) => matter.parse(&content),
-            FrontMatterParser::JsonParser(matter) => matter.parse(&content),
-            FrontMatterParser::YamlParser(matter) => matter.parse(&content),
+        let Some((frontmatter, body)) = split_frontmatter(&content) else {
+            return (None, content);
         };
-        ((result.data.map(|p| p.into())), result.content)
+        if frontmatter.trim().is_empty() {
+            return (None, body.to_owned());
+        }
+        let data = match self.0 {
+            FrontMatterFormat::Toml => TOML::parse(frontmatter),
+            FrontMatterFormat::Json => JSON::parse(frontmatter),
+            FrontMatterFormat::Yaml => YAML::parse(frontmatter),
+        };
+        (Some(data.into()), body.to_owned())
     }
 }
 
-/// The document parser, parameterized over what happens when it encounters each event
-#[repr(transparent)]
-#[derive(Default)]
+/// A markdown backend, parsing the body of a document into its nodes. The
+/// ranges of the nodes are byte offsets in the body.
+pub trait Parse {
+    fn parse(&self, text: &str) -> Result<Ast>;
+}
+
+/// The document parser, backed by pulldown-cmark
 pub struct DocumentParser {
     pub options: DocumentParserOptions,
+    /// the comment syntaxes parsed into comment nodes
+    pub comments: Vec<CommentSyntax>,
+}
+
+impl Default for DocumentParser {
+    fn default() -> Self {
+        Self {
+            options: DocumentParserOptions::default(),
+            comments: ParserConfig::default().comments,
+        }
+    }
 }
 
 pub struct DocumentParserOptions(pub Options);
 
 impl Default for DocumentParserOptions {
     fn default() -> Self {
+        Self::from_config(&ParserConfig::default())
+    }
+}
+
+impl DocumentParserOptions {
+    /// The options of the syntax enabled by `config`
+    pub fn from_config(config: &ParserConfig) -> Self {
         let mut options = Options::empty();
-        options.insert(Options::ENABLE_FOOTNOTES);
-        options.insert(Options::ENABLE_TASKLISTS);
-        options.insert(Options::ENABLE_STRIKETHROUGH);
-        options.insert(Options::ENABLE_HEADING_ATTRIBUTES);
-        options.insert(Options::ENABLE_MATH);
-        options.insert(Options::ENABLE_WIKILINKS);
-        options.insert(Options::ENABLE_TABLES);
-        options.insert(Options::ENABLE_GFM);
+        options.set(Options::ENABLE_FOOTNOTES, config.footnotes);
+        options.set(Options::ENABLE_TASKLISTS, config.task_lists);
+        options.set(Options::ENABLE_STRIKETHROUGH, config.strikethrough);
+        options.set(
+            Options::ENABLE_HEADING_ATTRIBUTES,
+            config.heading_attributes,
+        );
+        options.set(Options::ENABLE_MATH, config.math);
+        options.set(Options::ENABLE_WIKILINKS, config.wikilinks);
+        options.set(Options::ENABLE_TABLES, config.tables);
+        options.set(Options::ENABLE_GFM, config.alerts);
+        // metadata blocks are left disabled, the frontmatter is split off by
+        // FrontMatterParser before the body is parsed, and any later `---`
+        // is a horizontal rule or setext underline
         Self(options)
     }
 }
@@ -117,44 +155,331 @@ impl DocumentParser {
         Self::default()
     }
 
-    pub fn parse(&self, document: String) -> Result<Vec<Node>> {
-        let parser = Parser::new_ext(&document, self.options.0);
+    /// A parser recognizing the comment syntaxes `comments`
+    pub fn with_comments(comments: &[CommentSyntax]) -> Self {
+        Self {
+            comments: comments.to_vec(),
+            ..Self::default()
+        }
+    }
+
+    /// A parser recognizing the syntax enabled by `config`
+    pub fn from_config(config: &ParserConfig) -> Self {
+        Self {
+            options: DocumentParserOptions::from_config(config),
+            comments: config.comments.clone(),
+        }
+    }
+
+    fn parse_markdown(&self, document: &str) -> Result<Ast> {
+        let parser = Parser::new_ext(document, self.options.0);
 
         let mut parser_with_offset = ParserIterator {
             inner: parser.into_offset_iter().peekable(),
-            text: document.as_str(),
+            text: document,
+            quote_depth: 0,
         };
 
-        let mut nodes: Vec<ast_nodes::Node> = Vec::new();
+        let mut ast = Ast::new();
 
         while let Some((event, range)) = parser_with_offset.next() {
-            nodes.push(parse_event(event, range, &mut parser_with_offset)?);
+            parse_event(event, range, &mut parser_with_offset, &mut ast, None)?;
         }
+        let ast = split_text(ast, document, &HIGHLIGHTS);
+
+        Ok(split_text(ast, document, &MENTIONS))
+    }
+}
 
-        Ok(nodes)
+impl Parse for DocumentParser {
+    fn parse(&self, document: &str) -> Result<Ast> {
+        parse_with_comments(document, &self.comments, |text| self.parse_markdown(text))
     }
 }
 
-fn parse_event(event: Event, range: Range<usize>, iter: &mut ParserIterator) -> Result<Node> {
-    match event {
-        Event::Start(tag) => parse_start(tag, range, iter),
-        Event::Text(str) => Ok(Node::text(range, str.to_string())),
-        Event::Code(str) => parse_code(str, range, iter),
-        Event::InlineMath(str) => Ok(Node::inlinemath(range, str.into_string())),
-        Event::DisplayMath(str) => Ok(Node::displaymath(range, str.into_string())),
-        Event::Html(str) => Ok(Node::html(range, str.into_string())),
-        Event::InlineHtml(str) => Ok(Node::html(range, str.into_string())),
-        Event::FootnoteReference(str) => {
-            Ok(Node::footnotereference(range, String::from(str.as_ref())))
+impl<P: Parse + ?Sized> Parse for &P {
+    fn parse(&self, text: &str) -> Result<Ast> {
+        (**self).parse(text)
+    }
+}
+
+/// Parse `document` with `markdown`, its comments written in one of
+/// `syntaxes` being parsed as top level nodes and the rest of the document as
+/// if they were not there
+fn parse_with_comments(
+    document: &str,
+    syntaxes: &[CommentSyntax],
+    markdown: impl FnOnce(&str) -> Result<Ast>,
+) -> Result<Ast> {
+    let comments = find_comments(document, syntaxes);
+    if comments.is_empty() {
+        return markdown(document);
+    }
+    let mut nodes = markdown(&remove_comments(document, &comments))?;
+
+    // the offsets in the document without comments, and the lengths of
+    // the comments removed there
+    let mut removed = Vec::with_capacity(comments.len());
+    let mut total = 0;
+    for comment in &comments {
+        removed.push((comment.range.start - total, comment.range.len()));
+        total += comment.range.len();
+    }
+    // a range starting where a comment was removed starts after it, one
+    // ending there ends before it
+    let shift = |offset: usize, inclusive: bool| {
+        offset
+            + removed
+                .iter()
+                .take_while(|(at, _)| *at < offset || (inclusive && *at == offset))
+                .map(|(_, len)| len)
+                .sum::<usize>()
+    };
+    for node in nodes.iter_mut() {
+        node.map_ranges(&|range: &mut Range<usize>| {
+            let start = shift(range.start, true);
+            *range = start..shift(range.end, false).max(start)
+        });
+    }
+
+    // a comment precedes the first top level node not starting before it
+    let mut ast = Ast::new();
+    let mut ids = Vec::with_capacity(nodes.len());
+    let mut comments = comments.into_iter().peekable();
+    let comment = |comment: Comment| {
+        let text = comment.text(document).to_owned();
+        Node::comment(comment.range, comment.syntax, text)
+    };
+    for (node, parent) in nodes.into_nodes() {
+        if parent.is_none() {
+            while let Some(c) = comments.next_if(|c| c.range.start <= node.range().start) {
+                ast.push(comment(c), None);
+            }
         }
-        Event::HardBreak => Ok(Node::hardbreak(range)),
-        Event::Rule => Ok(Node::horizontalrule(range)),
-        _ => Err(eyre!(
-            "unexpected event! event should have been consumed by previous parse rule: {:?} - {:?}",
-            event,
-            range
-        )),
+        ids.push(ast.push(node, parent.map(|parent: NodeId| ids[parent.index()])));
     }
+    for c in comments {
+        ast.push(comment(c), None);
+    }
+    Ok(ast)
+}
+
+/// A splitter of runs of consecutive text nodes into other inline nodes
+struct TextSplitter {
+    /// text nodes containing this are split
+    marker: &'static str,
+    /// whether a run of text nodes continues past the `gap` between two
+    joins: fn(gap: &str) -> bool,
+    /// push a run of text nodes to the result, split
+    split: fn(text: Vec<Node>, document: &str, result: &mut Vec<Node>),
+}
+
+/// `==highlighted==` text, which can not contain markup but may span lines
+const HIGHLIGHTS: TextSplitter = TextSplitter {
+    marker: "==",
+    joins: |gap| gap.chars().all(|c| c.is_whitespace() || c == '>'),
+    split: split_highlights,
+};
+
+/// `@name` mentions of people
+const MENTIONS: TextSplitter = TextSplitter {
+    marker: "@",
+    joins: str::is_empty,
+    split: split_mentions,
+};
+
+/// Split the runs of consecutive text nodes of `ast` by `splitter`. Text is
+/// only found between other inline nodes.
+fn split_text(ast: Ast, document: &str, splitter: &TextSplitter) -> Ast {
+    if !ast
+        .iter()
+        .any(|n| matches!(n, Node::Text { text, .. } if text.contains(splitter.marker)))
+    {
+        return ast;
+    }
+
+    let mut result = Ast::new();
+    // the ids in the result of the nodes of `ast`, text nodes having no
+    // nodes within them
+    let mut ids = Vec::with_capacity(ast.len());
+    let mut text: Vec<Node> = Vec::new();
+    let mut text_parent = None;
+    let mut split = Vec::new();
+    let mut flush = |result: &mut Ast, text: &mut Vec<Node>, parent| {
+        (splitter.split)(std::mem::take(text), document, &mut split);
+        for node in split.drain(..) {
+            result.push(node, parent);
+        }
+    };
+    for (node, parent) in ast.into_nodes() {
+        let parent = parent.and_then(|parent: NodeId| ids[parent.index()]);
+        let continues = match (&node, text.last()) {
+            (Node::Text { range, .. }, Some(last)) => {
+                parent == text_parent
+                    && document
+                        .get(last.range().end..range.start)
+                        .is_some_and(splitter.joins)
+            }
+            (Node::Text { .. }, None) => true,
+            _ => false,
+        };
+        if !continues {
+            flush(&mut result, &mut text, text_parent);
+        }
+        match node {
+            Node::Text { .. } => {
+                text_parent = parent;
+                text.push(node);
+                ids.push(None);
+            }
+            _ => ids.push(Some(result.push(node, parent))),
+        }
+    }
+    flush(&mut result, &mut text, text_parent);
+    result
+}
+
+/// The lines of the inline `source` joined by spaces, without the
+/// indentation and block quote markers continuing them
+fn joined_lines(source: &str) -> String {
+    let mut lines = source.split('\n');
+    let first = lines.next().unwrap_or_default().to_owned();
+    lines.fold(first, |text, line| {
+        let line = line.trim_start_matches(|c: char| c.is_whitespace() || c == '>');
+        format!("{} {}", text.trim_end(), line)
+    })
+}
+
+/// Push the consecutive text nodes `text` to `result`, split at their
+/// highlights
+fn split_highlights(text: Vec<Node>, document: &str, result: &mut Vec<Node>) {
+    let (Some(first), Some(last)) = (text.first(), text.last()) else {
+        return;
+    };
+    let span = first.range().start..last.range().end;
+    let source = &document[span.clone()];
+
+    let mut highlights = Vec::new();
+    let mut open = None;
+    for (i, _) in source.match_indices("==") {
+        let inner_start = open.map(|start| start + "==".len());
+        match inner_start {
+            Some(inner) if i > inner && !source[..i].ends_with(char::is_whitespace) => {
+                highlights.push(open.take().unwrap_or_default()..i + "==".len());
+            }
+            _ if source[i + "==".len()..].starts_with(|c: char| !c.is_whitespace()) => {
+                open = Some(i)
+            }
+            _ => {}
+        }
+    }
+    if highlights.is_empty() {
+        result.extend(text);
+        return;
+    }
+
+    let mut offset = 0;
+    for highlight in highlights {
+        if highlight.start > offset {
+            let range = span.start + offset..span.start + highlight.start;
+            result.push(Node::text(
+                range,
+                joined_lines(&source[offset..highlight.start]),
+            ));
+        }
+        let inner = &source[highlight.start + "==".len()..highlight.end - "==".len()];
+        let range = span.start + highlight.start..span.start + highlight.end;
+        result.push(Node::highlight(range, joined_lines(inner)));
+        offset = highlight.end;
+    }
+    if offset < source.len() {
+        result.push(Node::text(
+            span.start + offset..span.end,
+            joined_lines(&source[offset..]),
+        ));
+    }
+}
+
+/// Push the consecutive text nodes `text` to `result`, split at their
+/// `@name` mentions. A mention does not follow a letter, digit or one of
+/// `@/._-`, leaving out email addresses and urls, and its name does not end
+/// in `.` or `-`.
+fn split_mentions(text: Vec<Node>, document: &str, result: &mut Vec<Node>) {
+    let (Some(first), Some(last)) = (text.first(), text.last()) else {
+        return;
+    };
+    let span = first.range().start..last.range().end;
+    let source = &document[span.clone()];
+
+    let mut mentions = Vec::new();
+    for (i, _) in source.match_indices('@') {
+        let before = document[..span.start + i].chars().next_back();
+        if before.is_some_and(|c| c.is_alphanumeric() || "@/._-".contains(c)) {
+            continue;
+        }
+        let name = &source[i + 1..];
+        let end = name
+            .find(|c: char| !(c.is_alphanumeric() || "._-".contains(c)))
+            .unwrap_or(name.len());
+        let name = name[..end].trim_end_matches(['.', '-']);
+        if name.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+            mentions.push(i..i + 1 + name.len());
+        }
+    }
+    if mentions.is_empty() {
+        result.extend(text);
+        return;
+    }
+
+    let mut offset = 0;
+    for mention in mentions {
+        if mention.start > offset {
+            let range = span.start + offset..span.start + mention.start;
+            result.push(Node::text(range, source[offset..mention.start].to_owned()));
+        }
+        let range = span.start + mention.start..span.start + mention.end;
+        let name = source[mention.start + 1..mention.end].to_owned();
+        result.push(Node::mention(range, name));
+        offset = mention.end;
+    }
+    if offset < source.len() {
+        result.push(Node::text(
+            span.start + offset..span.end,
+            source[offset..].to_owned(),
+        ));
+    }
+}
+
+/// Parse `event` and the events of the nodes within it into `ast`, within
+/// `parent`
+fn parse_event(
+    event: Event,
+    range: Range<usize>,
+    iter: &mut ParserIterator,
+    ast: &mut Ast,
+    parent: Option<NodeId>,
+) -> Result<()> {
+    let node = match event {
+        Event::Start(tag) => return parse_start(tag, range, iter, ast, parent),
+        Event::Text(str) => Node::text(range, str.to_string()),
+        Event::Code(str) => parse_code(str, range, iter)?,
+        Event::InlineMath(str) => Node::inlinemath(range, str.into_string()),
+        Event::DisplayMath(str) => Node::displaymath(range, str.into_string()),
+        Event::Html(str) => Node::html(range, str.into_string()),
+        Event::InlineHtml(str) => Node::html(range, str.into_string()),
+        Event::FootnoteReference(str) => Node::footnotereference(range, String::from(str.as_ref())),
+        Event::HardBreak => Node::hardbreak(range),
+        Event::Rule => Node::horizontalrule(range),
+        _ => {
+            return Err(eyre!(
+                "unexpected event! event should have been consumed by previous parse rule: {:?} - {:?}",
+                event,
+                range
+            ));
+        }
+    };
+    ast.push(node, parent);
+    Ok(())
 }
 
 fn parse_code(
@@ -169,30 +494,36 @@ fn parse_code(
     Ok(Node::code(range, raw_text.to_string()))
 }
 
-fn parse_start(start_tag: Tag, range: Range<usize>, iter: &mut ParserIterator) -> Result<Node> {
-    match start_tag {
+fn parse_start(
+    start_tag: Tag,
+    range: Range<usize>,
+    iter: &mut ParserIterator,
+    ast: &mut Ast,
+    parent: Option<NodeId>,
+) -> Result<()> {
+    let node = match start_tag {
         Tag::Heading {
             level,
             id,
             classes,
             attrs,
-        } => parse_heading(level, id, classes, attrs, range, iter),
-        Tag::Paragraph => parse_paragraph(range, iter),
-        Tag::BlockQuote(_) => parse_blockquote(range, iter),
-        Tag::CodeBlock(kind) => parse_code_block(kind, range, iter),
-        Tag::HtmlBlock => parse_htmlblock(range, iter),
-        Tag::List(n) => parse_list(n, range, iter),
-        Tag::Item => parse_item(range, iter),
-        Tag::FootnoteDefinition(id) => parse_footnote_def(id, range, iter),
-        Tag::Table(alignments) => parse_table(alignments, range, iter),
+        } => return parse_heading(level, id, classes, attrs, range, iter, ast, parent),
+        Tag::Paragraph => return parse_paragraph(range, iter, ast, parent),
+        Tag::BlockQuote(kind) => return parse_blockquote(kind, range, iter, ast, parent),
+        Tag::CodeBlock(kind) => return parse_code_block(kind, range, iter, ast, parent),
+        Tag::HtmlBlock => parse_htmlblock(range, iter)?,
+        Tag::List(n) => return parse_list(n, range, iter, ast, parent),
+        Tag::Item => return parse_item(range, iter, ast, parent),
+        Tag::FootnoteDefinition(id) => parse_footnote_def(id, range, iter)?,
+        Tag::Table(alignments) => return parse_table(alignments, range, iter, ast, parent),
         // Tag::TableHead => parse_table_head(range, iter).map(|h| h.into()),
         // Tag::TableRow => parse_table_row(range, iter).map(|r| r.into()),
         // Tag::TableCell => parse_table_cell(range, iter).map(|c| c.into()),
-        Tag::Emphasis => parse_text_decor(TextDecorationKind::Emphasis, range, iter),
-        Tag::Strong => parse_text_decor(TextDecorationKind::Strong, range, iter),
-        Tag::Strikethrough => parse_text_decor(TextDecorationKind::Strikethrough, range, iter),
-        Tag::Superscript => parse_text_decor(TextDecorationKind::Superscript, range, iter),
-        Tag::Subscript => parse_text_decor(TextDecorationKind::Subscript, range, iter),
+        Tag::Emphasis => parse_text_decor(TextDecorationKind::Emphasis, range, iter)?,
+        Tag::Strong => parse_text_decor(TextDecorationKind::Strong, range, iter)?,
+        Tag::Strikethrough => parse_text_decor(TextDecorationKind::Strikethrough, range, iter)?,
+        Tag::Superscript => parse_text_decor(TextDecorationKind::Superscript, range, iter)?,
+        Tag::Subscript => parse_text_decor(TextDecorationKind::Subscript, range, iter)?,
         Tag::Link {
             link_type,
             dest_url,
@@ -212,7 +543,7 @@ fn parse_start(start_tag: Tag, range: Range<usize>, iter: &mut ParserIterator) -
                 todo!()
             }
             LinkType::Collapsed => Err(eyre!("unsupported link type: {link_type:?}")),
-        },
+        }?,
 
         // parse_link(link_type, dest_url, title, id, range, iter),
         Tag::Image {
@@ -220,9 +551,11 @@ fn parse_start(start_tag: Tag, range: Range<usize>, iter: &mut ParserIterator) -
             dest_url,
             title,
             id,
-        } => parse_image(link_type, dest_url, title, id, range, iter),
-        _ => Err(eyre!("unsupported tag")),
-    }
+        } => parse_image(link_type, dest_url, title, id, range, iter)?,
+        _ => return Err(eyre!("unsupported tag")),
+    };
+    ast.push(node, parent);
+    Ok(())
 }
 
 fn parse_email_link(
@@ -333,7 +666,8 @@ fn parse_image(
         }
     }
     match link_type {
-        LinkType::Inline => Ok(Node::inlineimage(range)),
+        // an embed `![[target]]`, of an asset or of a note
+        LinkType::Inline | LinkType::WikiLink { .. } => Ok(Node::inlineimage(range)),
         LinkType::Reference
         | LinkType::ReferenceUnknown
         | LinkType::Collapsed
@@ -468,27 +802,37 @@ fn parse_text_decor(
     Ok(Node::textdecoration(range, kind, content))
 }
 
-fn parse_table_cell(range: Range<usize>, iter: &mut ParserIterator<'_>) -> Result<TableCell> {
-    let mut children = Vec::new();
-
+/// Parse a cell of the table `table`, the nodes within it being pushed
+/// within the table
+fn parse_table_cell(
+    range: Range<usize>,
+    iter: &mut ParserIterator<'_>,
+    ast: &mut Ast,
+    table: NodeId,
+) -> Result<TableCell> {
     while let Some((event, range)) = iter.next() {
         if let Event::End(TagEnd::TableCell) = event {
             break;
         } else {
-            children.push(parse_event(event, range, iter)?)
+            parse_event(event, range, iter, ast, Some(table))?
         }
     }
 
-    Ok(TableCell::new(range, children))
+    Ok(TableCell::new(range))
 }
 
-fn parse_table_row(range: Range<usize>, iter: &mut ParserIterator<'_>) -> Result<TableRow> {
+fn parse_table_row(
+    range: Range<usize>,
+    iter: &mut ParserIterator<'_>,
+    ast: &mut Ast,
+    table: NodeId,
+) -> Result<TableRow> {
     let mut cells = Vec::new();
 
     while let Some((event, range)) = iter.next() {
         match event {
             Event::End(TagEnd::TableRow) => break,
-            Event::Start(Tag::TableCell) => cells.push(parse_table_cell(range, iter)?),
+            Event::Start(Tag::TableCell) => cells.push(parse_table_cell(range, iter, ast, table)?),
             _ => return Err(eyre!("expected table row")),
         }
     }
@@ -500,45 +844,55 @@ fn parse_table(
     alignments: Vec<pulldown_cmark::Alignment>,
     range: Range<usize>,
     iter: &mut ParserIterator<'_>,
-) -> Result<Node> {
-    let header = if let Some((Event::Start(Tag::TableHead), range)) = iter.next() {
-        parse_table_head(range, iter)?
+    ast: &mut Ast,
+    parent: Option<NodeId>,
+) -> Result<()> {
+    let alignments = alignments
+        .into_iter()
+        .map(|a| match a {
+            pulldown_cmark::Alignment::None => ColumnAlignment::None,
+            pulldown_cmark::Alignment::Left => ColumnAlignment::Left,
+            pulldown_cmark::Alignment::Center => ColumnAlignment::Center,
+            pulldown_cmark::Alignment::Right => ColumnAlignment::Right,
+        })
+        .collect();
+    let empty = TableHead::new(range.start..range.start, Vec::new());
+    let table = ast.push(Node::table(range, empty, alignments, Vec::new()), parent);
+
+    let head = if let Some((Event::Start(Tag::TableHead), range)) = iter.next() {
+        parse_table_head(range, iter, ast, table)?
     } else {
         return Err(eyre!("header expected but received none"));
     };
 
-    let mut rows = Vec::new();
+    let mut body = Vec::new();
 
     while let Some((event, range)) = iter.next() {
         match event {
             Event::End(TagEnd::Table) => break,
-            Event::Start(Tag::TableRow) => rows.push(parse_table_row(range, iter)?),
+            Event::Start(Tag::TableRow) => body.push(parse_table_row(range, iter, ast, table)?),
             _ => return Err(eyre!("expected table row")),
         }
     }
 
-    Ok(Node::table(
-        range,
-        header,
-        alignments
-            .into_iter()
-            .map(|a| match a {
-                pulldown_cmark::Alignment::None => ColumnAlignment::None,
-                pulldown_cmark::Alignment::Left => ColumnAlignment::Left,
-                pulldown_cmark::Alignment::Center => ColumnAlignment::Center,
-                pulldown_cmark::Alignment::Right => ColumnAlignment::Right,
-            })
-            .collect(),
-        rows,
-    ))
+    if let Node::Table { header, rows, .. } = &mut ast[table] {
+        *header = head;
+        *rows = body;
+    }
+    Ok(())
 }
 
-fn parse_table_head(range: Range<usize>, iter: &mut ParserIterator<'_>) -> Result<TableHead> {
+fn parse_table_head(
+    range: Range<usize>,
+    iter: &mut ParserIterator<'_>,
+    ast: &mut Ast,
+    table: NodeId,
+) -> Result<TableHead> {
     let mut cells = Vec::new();
 
     while let Some((event, range)) = iter.next() {
         match event {
-            Event::Start(Tag::TableCell) => cells.push(parse_table_cell(range, iter)?),
+            Event::Start(Tag::TableCell) => cells.push(parse_table_cell(range, iter, ast, table)?),
             Event::End(TagEnd::TableHead) => break,
             e => {
                 return Err(eyre!("Received unexpected event: {:?}", e));
@@ -567,10 +921,19 @@ fn parse_footnote_def(
     Ok(Node::footnotedefinition(range, id.to_string(), target))
 }
 
-fn parse_item(range: Range<usize>, iter: &mut ParserIterator<'_>) -> Result<Node> {
-    let mut children = Vec::new();
-    let mut sub_lists = Vec::new();
-    let mut checkmark = TaskListMarker::NoCheckmark;
+fn parse_item(
+    range: Range<usize>,
+    iter: &mut ParserIterator<'_>,
+    ast: &mut Ast,
+    parent: Option<NodeId>,
+) -> Result<()> {
+    // pulldown-cmark only knows about `[ ]` and `[x]`, the remaining task
+    // states are left as text at the start of the item, the text nodes
+    // leading the item being held back until the marker is stripped
+    let extended = extended_task_marker(&iter.text[range.clone()]);
+    let mut checkmark = extended.unwrap_or(TaskListMarker::NoCheckmark);
+    let mut leading = extended.map(|_| Vec::new());
+    let item = ast.push(Node::item(range, checkmark), parent);
 
     while let Some((event, range)) = iter.next() {
         match event {
@@ -582,34 +945,118 @@ fn parse_item(range: Range<usize>, iter: &mut ParserIterator<'_>) -> Result<Node
                     checkmark = TaskListMarker::UnChecked;
                 }
             }
-            Event::Start(Tag::List(_)) => sub_lists.push(parse_event(event, range, iter)?),
             Event::Start(Tag::Paragraph) => {
                 // Handle paragraph wrapper around task list items
                 // The TaskListMarker might appear inside the paragraph
-                let para_children = parse_paragraph_in_item(&mut checkmark, range, iter)?;
-                children.append(&mut sub_lists);
-                children.extend(para_children);
-            }
-            // TODO: this structure looks a bit weird. Maybe we should reset the
-            // sublist list? Check out the snapshot test for it and see if it
-            // makes sense
-            _ => {
-                children.append(&mut sub_lists);
-                children.push(parse_event(event, range, iter)?)
+                parse_paragraph_in_item(&mut checkmark, &mut leading, iter, ast, item)?;
             }
+            _ => parse_item_content(event, range, iter, ast, item, &mut leading)?,
         }
     }
+    push_leading_text(ast, item, &mut leading);
 
-    Ok(Node::item(range, checkmark, children, sub_lists))
+    if let Node::Item {
+        task_list_marker, ..
+    } = &mut ast[item]
+    {
+        *task_list_marker = checkmark;
+    }
+    Ok(())
+}
+
+/// Parse `event`, part of the content of the list item `item`, holding back
+/// the text nodes in `leading`, if it is there
+fn parse_item_content(
+    event: Event,
+    range: Range<usize>,
+    iter: &mut ParserIterator<'_>,
+    ast: &mut Ast,
+    item: NodeId,
+    leading: &mut Option<Vec<Node>>,
+) -> Result<()> {
+    match (event, leading.as_mut()) {
+        (Event::Text(text), Some(leading)) => {
+            leading.push(Node::text(range, text.to_string()));
+            Ok(())
+        }
+        (event, _) => {
+            push_leading_text(ast, item, leading);
+            parse_event(event, range, iter, ast, Some(item))
+        }
+    }
 }
 
+/// Push the text nodes `leading` of the list item `item`, without its
+/// extended task marker
+fn push_leading_text(ast: &mut Ast, item: NodeId, leading: &mut Option<Vec<Node>>) {
+    let Some(mut nodes) = leading.take() else {
+        return;
+    };
+    strip_extended_task_marker(&mut nodes);
+    for node in nodes {
+        ast.push(node, Some(item));
+    }
+}
+
+/// Recognize the `[/]` (in progress) and `[-]` (cancelled) task markers at
+/// the start of a list item
+fn extended_task_marker(item: &str) -> Option<TaskListMarker> {
+    let item = item.trim_start();
+    let item = item
+        .strip_prefix(['-', '*', '+'])
+        .or_else(|| {
+            let digits = item.find(|c: char| !c.is_ascii_digit())?;
+            (digits > 0)
+                .then(|| item[digits..].strip_prefix(['.', ')']))
+                .flatten()
+        })?
+        .trim_start_matches([' ', '\t']);
+
+    let marker = match item.get(..3)? {
+        "[/]" => TaskListMarker::InProgress,
+        "[-]" => TaskListMarker::Cancelled,
+        _ => return None,
+    };
+    match item[3..].chars().next() {
+        None | Some(' ') | Some('\t') | Some('\n') => Some(marker),
+        _ => None,
+    }
+}
+
+/// Remove the textual `[/]`/`[-]` marker (and following whitespace) from the
+/// leading text nodes of an item. pulldown-cmark might split the marker over
+/// several text events.
+fn strip_extended_task_marker(children: &mut Vec<Node>) {
+    let mut remaining = "[/]".len();
+    while let Some(Node::Text { range, text }) = children.first_mut() {
+        // first the marker itself, then the whitespace following it
+        if remaining > 0 {
+            let n = remaining.min(text.len());
+            remaining -= n;
+            text.drain(..n);
+            range.start += n;
+        }
+        if remaining == 0 {
+            let ws = text.len() - text.trim_start().len();
+            text.drain(..ws);
+            range.start += ws;
+        }
+        if !text.is_empty() {
+            break;
+        }
+        children.remove(0);
+    }
+}
+
+/// Parse a paragraph in the list item `item`, its content being pushed
+/// within the item
 fn parse_paragraph_in_item(
     checkmark: &mut TaskListMarker,
-    _range: Range<usize>,
+    leading: &mut Option<Vec<Node>>,
     iter: &mut ParserIterator<'_>,
-) -> Result<Vec<Node>> {
-    let mut children = Vec::new();
-
+    ast: &mut Ast,
+    item: NodeId,
+) -> Result<()> {
     while let Some((event, range)) = iter.next() {
         match event {
             Event::End(TagEnd::Paragraph) => break,
@@ -620,34 +1067,47 @@ fn parse_paragraph_in_item(
                     *checkmark = TaskListMarker::UnChecked;
                 }
             }
-            _ => children.push(parse_event(event, range, iter)?),
+            _ => parse_item_content(event, range, iter, ast, item, leading)?,
         }
     }
 
-    Ok(children)
+    Ok(())
 }
 
-fn parse_list(n: Option<u64>, range: Range<usize>, iter: &mut ParserIterator<'_>) -> Result<Node> {
-    let mut children = Vec::new();
+fn parse_list(
+    n: Option<u64>,
+    range: Range<usize>,
+    iter: &mut ParserIterator<'_>,
+    ast: &mut Ast,
+    parent: Option<NodeId>,
+) -> Result<()> {
+    let list = ast.push(Node::list(range, n, true), parent);
+    // the content of the items of a loose list is wrapped in paragraphs
+    let mut tight = true;
 
     while let Some((event, range)) = iter.next() {
         if let Event::End(TagEnd::List(_)) = event {
             break;
         } else {
-            children.push(parse_event(event, range, iter)?)
+            if let Event::Start(Tag::Item) = event
+                && let Some((Event::Start(Tag::Paragraph), _)) = iter.inner.peek()
+            {
+                tight = false;
+            }
+            parse_event(event, range, iter, ast, Some(list))?
         }
     }
 
-    Ok(Node::list(range, n, children))
+    if let Node::List { tight: t, .. } = &mut ast[list] {
+        *t = tight;
+    }
+    Ok(())
 }
 
 fn parse_htmlblock(range: Range<usize>, iter: &mut ParserIterator<'_>) -> Result<Node> {
-    let mut children = Vec::new();
-
-    while let Some((event, range)) = iter.next() {
-        match event {
-            Event::End(TagEnd::HtmlBlock) => break,
-            _ => children.push(parse_event(event, range, iter)?),
+    for (event, _) in iter.by_ref() {
+        if let Event::End(TagEnd::HtmlBlock) = event {
+            break;
         }
     }
 
@@ -658,16 +1118,9 @@ fn parse_code_block(
     kind: pulldown_cmark::CodeBlockKind<'_>,
     range: Range<usize>,
     iter: &mut ParserIterator<'_>,
-) -> Result<Node> {
-    let mut children = Vec::new();
-
-    while let Some((event, range)) = iter.next() {
-        match event {
-            Event::End(TagEnd::CodeBlock) => break,
-            _ => children.push(parse_event(event, range, iter)?),
-        }
-    }
-
+    ast: &mut Ast,
+    parent: Option<NodeId>,
+) -> Result<()> {
     let is_fenced = matches!(kind, CodeBlockKind::Fenced(_));
     let tag = match kind {
         CodeBlockKind::Indented => None,
@@ -677,35 +1130,71 @@ fn parse_code_block(
         }
     };
 
-    Ok(Node::codeblock(range, tag, is_fenced, children))
+    if let Some(kind) = tag.as_deref().and_then(DiagramKind::from_tag) {
+        let mut source = String::new();
+        for (event, _) in iter.by_ref() {
+            match event {
+                Event::End(TagEnd::CodeBlock) => break,
+                Event::Text(text) => source.push_str(&text),
+                _ => {}
+            }
+        }
+        ast.push(Node::diagram(range, kind, source), parent);
+        return Ok(());
+    }
+
+    let code_block = ast.push(Node::codeblock(range, tag, is_fenced), parent);
+    while let Some((event, range)) = iter.next() {
+        match event {
+            Event::End(TagEnd::CodeBlock) => break,
+            _ => parse_event(event, range, iter, ast, Some(code_block))?,
+        }
+    }
+
+    Ok(())
 }
 
-fn parse_blockquote(range: Range<usize>, iter: &mut ParserIterator<'_>) -> Result<Node> {
-    let mut children = Vec::new();
+fn parse_blockquote(
+    kind: Option<pulldown_cmark::BlockQuoteKind>,
+    range: Range<usize>,
+    iter: &mut ParserIterator<'_>,
+    ast: &mut Ast,
+    parent: Option<NodeId>,
+) -> Result<()> {
+    iter.quote_depth += 1;
+    let depth = iter.quote_depth;
+    let quote = ast.push(Node::blockquote(range, kind.map(Into::into), depth), parent);
 
     while let Some((event, range)) = iter.next() {
         match event {
             Event::End(TagEnd::BlockQuote(_)) => break,
-            _ => children.push(parse_event(event, range, iter)?),
+            _ => parse_event(event, range, iter, ast, Some(quote))?,
         }
     }
 
-    Ok(Node::blockquote(range, children))
+    iter.quote_depth -= 1;
+    Ok(())
 }
 
-fn parse_paragraph(range: Range<usize>, iter: &mut ParserIterator<'_>) -> Result<Node> {
-    let mut children = Vec::new();
+fn parse_paragraph(
+    range: Range<usize>,
+    iter: &mut ParserIterator<'_>,
+    ast: &mut Ast,
+    parent: Option<NodeId>,
+) -> Result<()> {
+    let paragraph = ast.push(Node::paragraph(range), parent);
 
     while let Some((event, range)) = iter.next() {
         match event {
             Event::End(TagEnd::Paragraph) => break,
-            _ => children.push(parse_event(event, range, iter)?),
+            _ => parse_event(event, range, iter, ast, Some(paragraph))?,
         }
     }
 
-    Ok(Node::paragraph(range, children))
+    Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn parse_heading(
     orig_level: HeadingLevel,
     id: Option<CowStr<'_>>,
@@ -713,14 +1202,15 @@ fn parse_heading(
     attrs: Vec<(CowStr<'_>, Option<CowStr<'_>>)>,
     range: Range<usize>,
     iter: &mut ParserIterator<'_>,
-) -> Result<Node> {
+    ast: &mut Ast,
+    parent: Option<NodeId>,
+) -> Result<()> {
     // 1. we first parse the title itself.
     // 2. we then continue parsing the "section" under the title. We stop when
     // we find the start of a another section on the same "level" or higher, or
     // the end.
 
     let mut heading_content = String::new();
-    let mut children = Vec::new();
 
     // 1. parse the heading
     while let Some((event, _)) = iter.next() {
@@ -736,6 +1226,19 @@ fn parse_heading(
         }
     }
 
+    let heading = Node::heading(
+        range,
+        id.map(|s| s.to_string()),
+        classes.iter().map(|s| s.to_string()).collect(),
+        attrs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.as_ref().map(|s| s.to_string())))
+            .collect(),
+        orig_level as u8,
+        heading_content,
+    );
+    let heading = ast.push(heading, parent);
+
     // 2. And then the section
     //
     // We do not want to consume the `Event::Start(TagStart::Heading)` event,
@@ -751,21 +1254,105 @@ fn parse_heading(
             unreachable!();
         };
 
-        children.push(parse_event(event, range, iter)?);
+        parse_event(event, range, iter, ast, Some(heading))?;
     }
 
-    Ok(Node::heading(
-        range,
-        id.map(|s| s.to_string()),
-        classes.iter().map(|s| s.to_string()).collect(),
-        attrs
-            .iter()
-            .map(|(k, v)| (k.to_string(), v.as_ref().map(|s| s.to_string())))
-            .collect(),
-        orig_level as u8,
-        heading_content,
-        children,
-    ))
+    Ok(())
 }
 
 pub type FrontMatter = serde_json::Value;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A backend parsing any text as a single text node
+    struct PlainText;
+
+    impl Parse for PlainText {
+        fn parse(&self, text: &str) -> Result<Ast> {
+            let mut ast = Ast::new();
+            ast.push(Node::text(0..text.len(), text.to_owned()), None);
+            Ok(ast)
+        }
+    }
+
+    /// The top level nodes of `ast`
+    fn roots(ast: &Ast) -> Vec<&Node> {
+        ast.roots().map(|id| &ast[id]).collect()
+    }
+
+    #[test]
+    fn test_parse_with_backend() {
+        let document = "---\ntitle: a\n---\n# A\n".to_owned();
+        let (frontmatter, ast) = parse(
+            FrontMatterParser::new(FrontMatterFormat::Yaml),
+            PlainText,
+            document.clone(),
+        )
+        .unwrap();
+        assert_eq!(frontmatter, Some(serde_json::json!({ "title": "a" })));
+        assert!(matches!(&roots(&ast)[..], [Node::Text { text, .. }] if text == "# A\n"));
+
+        let (_, ast) = parse(
+            FrontMatterParser::new(FrontMatterFormat::Yaml),
+            DocumentParser::new(),
+            document,
+        )
+        .unwrap();
+        assert!(matches!(&roots(&ast)[..], [Node::Heading { content, .. }] if content == "A"));
+    }
+
+    #[test]
+    fn test_frontmatter_boundaries() {
+        let parser = FrontMatterParser::new(FrontMatterFormat::Yaml);
+        // the body is the rest of the document as written, the offsets of
+        // its nodes being relative to the body offset
+        let document = "---\ntitle: a\n---\n\n\n# A\n\n---\n\ntext\n";
+        let (frontmatter, body) = parser.parse(document.to_owned());
+        assert_eq!(frontmatter, Some(serde_json::json!({ "title": "a" })));
+        let offset = crate::core::frontmatter::body_offset(document);
+        assert_eq!(body, document[offset..]);
+        let ast = DocumentParser::new().parse(&body).unwrap();
+        let [heading] = ast.roots().collect::<Vec<_>>()[..] else {
+            panic!("{ast:?}");
+        };
+        assert_eq!(&document[offset + ast[heading].range().start..][..3], "# A");
+        let children: Vec<&Node> = ast.children(heading).map(|id| &ast[id]).collect();
+        assert!(matches!(
+            &children[..],
+            [Node::HorizontalRule { .. }, Node::Paragraph { .. }]
+        ));
+
+        // a document starting with a horizontal rule has no frontmatter
+        let document = "---\n\ntext\n\n---\n";
+        assert_eq!(
+            parser.parse(document.to_owned()),
+            (None, document.to_owned())
+        );
+        // nor an empty one
+        assert_eq!(
+            parser.parse("---\n---\ntext\n".to_owned()),
+            (None, "text\n".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_tight_lists() {
+        let tight = |text: &str| {
+            let ast = DocumentParser::new().parse(text).unwrap();
+            let [Node::List { tight, .. }] = roots(&ast)[..] else {
+                panic!("{ast:?}");
+            };
+            let sub_list = ast.ids().skip(1).find_map(|id| match ast[id] {
+                Node::List { tight, .. } => Some(tight),
+                _ => None,
+            });
+            (*tight, sub_list)
+        };
+        assert_eq!(tight("- a\n- b\n"), (true, None));
+        assert_eq!(tight("- a\n\n- b\n"), (false, None));
+        assert_eq!(tight("1. a\n\n   b\n2. c\n"), (false, None));
+        assert_eq!(tight("- a\n\n- b\n  - c\n  - d\n"), (false, Some(true)));
+    }
+}
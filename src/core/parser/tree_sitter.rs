@@ -0,0 +1,777 @@
+//! A tree-sitter backend of the parser, for the language server.
+//!
+//! The parser keeps the tree of the last text it parsed, and parses the next
+//! one incrementally, reusing the parts of the tree that the edit between them
+//! leaves as they are. Tree-sitter recovers from any syntax error, so a buffer
+//! being typed into always has a tree. The trees are converted to the nodes
+//! of [`DocumentParser`](super::DocumentParser), save for footnote
+//! definitions and math blocks, which the markdown grammar does not know
+//! about. Comments are handled the way the pulldown-cmark backend handles
+//! them.
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Mutex;
+
+use ::tree_sitter::{InputEdit, Node as TsNode, Point};
+use ::tree_sitter_md::{MarkdownParser, MarkdownTree};
+use color_eyre::eyre::eyre;
+
+use crate::core::parser::arena::{Ast, NodeId};
+use crate::core::parser::ast_nodes::*;
+use crate::core::parser::comments::ParserConfig;
+use crate::core::parser::{
+    HIGHLIGHTS, MENTIONS, Parse, extended_task_marker, parse_with_comments, split_text,
+    strip_extended_task_marker,
+};
+use crate::result::Result;
+
+/// The tree-sitter parser, reparsing the texts it is given incrementally
+pub struct TreeSitterParser {
+    /// the comment syntaxes parsed into comment nodes
+    pub comments: Vec<CommentSyntax>,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    parser: MarkdownParser,
+    /// the last text parsed and its tree
+    previous: Option<(String, MarkdownTree)>,
+}
+
+impl Default for TreeSitterParser {
+    fn default() -> Self {
+        Self::with_comments(&ParserConfig::default().comments)
+    }
+}
+
+impl std::fmt::Debug for TreeSitterParser {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TreeSitterParser")
+            .field("comments", &self.comments)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TreeSitterParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A parser recognizing the comment syntaxes `comments`
+    pub fn with_comments(comments: &[CommentSyntax]) -> Self {
+        Self {
+            comments: comments.to_vec(),
+            state: Mutex::default(),
+        }
+    }
+
+    fn parse_markdown(&self, text: &str) -> Result<Ast> {
+        let mut state = self.state.lock().unwrap();
+        let State { parser, previous } = &mut *state;
+        let old_tree = previous.take().map(|(old_text, mut tree)| {
+            tree.edit(&input_edit(&old_text, text));
+            tree
+        });
+        let tree = parser
+            .parse(text.as_bytes(), old_tree.as_ref())
+            .ok_or_else(|| eyre!("tree-sitter did not parse the document"))?;
+
+        let mut converter = Converter {
+            text,
+            tree: &tree,
+            references: HashMap::new(),
+        };
+        let root = tree.block_tree().root_node();
+        converter.collect_references(root);
+        let mut ast = Ast::new();
+        converter.blocks(root, &mut ast, None);
+        let ast = split_text(ast, text, &HIGHLIGHTS);
+        let ast = split_text(ast, text, &MENTIONS);
+
+        *previous = Some((text.to_owned(), tree));
+        Ok(ast)
+    }
+}
+
+impl Parse for TreeSitterParser {
+    fn parse(&self, text: &str) -> Result<Ast> {
+        parse_with_comments(text, &self.comments, |text| self.parse_markdown(text))
+    }
+}
+
+/// The edit turning `old` into `new`, the span between their common prefix
+/// and suffix
+fn input_edit(old: &str, new: &str) -> InputEdit {
+    let (old_bytes, new_bytes) = (old.as_bytes(), new.as_bytes());
+    let prefix = old_bytes
+        .iter()
+        .zip(new_bytes)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_bytes[prefix..]
+        .iter()
+        .rev()
+        .zip(new_bytes[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old_end, new_end) = (old.len() - suffix, new.len() - suffix);
+    InputEdit {
+        start_byte: prefix,
+        old_end_byte: old_end,
+        new_end_byte: new_end,
+        start_position: point(old_bytes, prefix),
+        old_end_position: point(old_bytes, old_end),
+        new_end_position: point(new_bytes, new_end),
+    }
+}
+
+/// The row and column, in bytes, of `offset` in `text`
+fn point(text: &[u8], offset: usize) -> Point {
+    let before = &text[..offset];
+    let line_start = before
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1);
+    Point {
+        row: before.iter().filter(|&&b| b == b'\n').count(),
+        column: offset - line_start,
+    }
+}
+
+/// Whether a blank line follows the content of `before`, up to `after`
+fn blank_line_between(text: &str, before: TsNode, after: TsNode) -> bool {
+    let source = &text[before.start_byte()..after.start_byte()];
+    source[source.trim_end().len()..].matches('\n').count() > 1
+}
+
+fn children<'t>(node: TsNode<'t>) -> Vec<TsNode<'t>> {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor).collect()
+}
+
+fn child<'t>(node: TsNode<'t>, kind: &str) -> Option<TsNode<'t>> {
+    children(node).into_iter().find(|c| c.kind() == kind)
+}
+
+/// Converts a markdown tree into nodes
+struct Converter<'a> {
+    text: &'a str,
+    tree: &'a MarkdownTree,
+    /// the destinations of the link reference definitions, by lowercase label
+    references: HashMap<String, String>,
+}
+
+impl<'a> Converter<'a> {
+    fn source(&self, node: TsNode) -> &'a str {
+        &self.text[node.byte_range()]
+    }
+
+    fn collect_references(&mut self, node: TsNode) {
+        for child in children(node) {
+            if child.kind() == "link_reference_definition" {
+                let (name, link, _) = self.link_reference(child);
+                self.references.entry(name.to_lowercase()).or_insert(link);
+            } else {
+                self.collect_references(child);
+            }
+        }
+    }
+
+    /// Push the blocks within `node` under `parent`
+    fn blocks(&self, node: TsNode, ast: &mut Ast, parent: Option<NodeId>) {
+        for child in children(node) {
+            self.block(child, ast, parent);
+        }
+    }
+
+    fn block(&self, node: TsNode, ast: &mut Ast, parent: Option<NodeId>) {
+        let range = node.byte_range();
+        match node.kind() {
+            // a heading and the content up to the next heading of its level
+            "section" => {
+                let mut blocks = children(node).into_iter();
+                match blocks.clone().next() {
+                    Some(heading) if heading.kind().ends_with("_heading") => {
+                        blocks.next();
+                        let section = self.heading(heading, ast, parent);
+                        blocks.for_each(|block| self.block(block, ast, Some(section)));
+                    }
+                    _ => blocks.for_each(|block| self.block(block, ast, parent)),
+                }
+            }
+            "atx_heading" | "setext_heading" => {
+                self.heading(node, ast, parent);
+            }
+            "paragraph" => {
+                let paragraph = ast.push(Node::paragraph(range), parent);
+                push_inline(ast, self.inline_children(node), paragraph);
+            }
+            "block_quote" => self.block_quote(node, ast, parent),
+            "list" => self.list(node, ast, parent),
+            "fenced_code_block" | "indented_code_block" => self.code_block(node, ast, parent),
+            "html_block" => {
+                ast.push(Node::html(range, self.source(node).to_owned()), parent);
+            }
+            "thematic_break" => {
+                ast.push(Node::horizontalrule(range), parent);
+            }
+            "pipe_table" => self.table(node, ast, parent),
+            "link_reference_definition" => {
+                let (name, link, title) = self.link_reference(node);
+                ast.push(Node::linkreference(range, name, link, title), parent);
+            }
+            _ => {}
+        }
+    }
+
+    /// Push the heading `node`, returning its id for the content of its
+    /// section to be pushed under it
+    fn heading(&self, node: TsNode, ast: &mut Ast, parent: Option<NodeId>) -> NodeId {
+        let markers = children(node);
+        let level = markers
+            .iter()
+            .find_map(|marker| match marker.kind() {
+                "setext_h1_underline" => Some(1),
+                "setext_h2_underline" => Some(2),
+                kind => kind
+                    .strip_prefix("atx_h")?
+                    .strip_suffix("_marker")?
+                    .parse()
+                    .ok(),
+            })
+            .unwrap_or(1);
+        // the inline content of an atx heading, the paragraph of a setext one
+        let content = match node.child_by_field_name("heading_content") {
+            Some(content) if content.kind() == "paragraph" => self.inline_children(content),
+            Some(content) => self.inline(content),
+            None => Vec::new(),
+        };
+        let heading = Node::heading(
+            node.byte_range(),
+            None,
+            Vec::new(),
+            Vec::new(),
+            level,
+            plain_text(&content).trim().to_owned(),
+        );
+        ast.push(heading, parent)
+    }
+
+    /// A block quote, or an alert when its first line is a marker such as
+    /// `[!NOTE]`, the marker then not being part of its content
+    fn block_quote(&self, node: TsNode, ast: &mut Ast, parent: Option<NodeId>) {
+        let range = node.byte_range();
+        let mut depth = 1;
+        let mut ancestor = node.parent();
+        while let Some(p) = ancestor {
+            depth += usize::from(p.kind() == "block_quote");
+            ancestor = p.parent();
+        }
+        let source = self.source(node);
+        let first_line = source.lines().next().unwrap_or_default();
+        let kind = first_line
+            .trim_start()
+            .strip_prefix('>')
+            .and_then(BlockQuoteKind::from_marker);
+        let marker_end = range.start + first_line.len();
+        let quote = ast.push(Node::blockquote(range, kind, depth), parent);
+        for block in children(node) {
+            let first = ast.children(quote).next().is_none();
+            if !first || kind.is_none() || block.kind() != "paragraph" {
+                self.block(block, ast, Some(quote));
+                continue;
+            }
+            let mut inline = self.inline_children(block);
+            inline.retain(|node| node.range().start > marker_end);
+            if let Some(first) = inline.first() {
+                let range = first.range().start..block.end_byte();
+                let paragraph = ast.push(Node::paragraph(range), Some(quote));
+                push_inline(ast, inline, paragraph);
+            }
+        }
+    }
+
+    fn list(&self, node: TsNode, ast: &mut Ast, parent: Option<NodeId>) {
+        let items = children(node);
+        let start_index = items
+            .first()
+            .and_then(|item| {
+                child(*item, "list_marker_dot").or(child(*item, "list_marker_parenthesis"))
+            })
+            .and_then(|marker| {
+                self.source(marker)
+                    .trim()
+                    .trim_end_matches(['.', ')'])
+                    .parse()
+                    .ok()
+            });
+        let items: Vec<TsNode> = items
+            .into_iter()
+            .filter(|item| item.kind() == "list_item")
+            .collect();
+        // the grammar wraps the content of every item in a paragraph, a loose
+        // list is told by the blank lines between its items or their blocks
+        let separated = |nodes: &[TsNode]| {
+            nodes
+                .windows(2)
+                .any(|pair| blank_line_between(self.text, pair[0], pair[1]))
+        };
+        let tight = !separated(&items)
+            && !items.iter().any(|item| {
+                let blocks: Vec<TsNode> = children(*item)
+                    .into_iter()
+                    .filter(|block| {
+                        !block.kind().starts_with("list_marker")
+                            && !block.kind().starts_with("task_list_marker")
+                            && block.kind() != "block_continuation"
+                    })
+                    .collect();
+                separated(&blocks)
+            });
+        let list = ast.push(Node::list(node.byte_range(), start_index, tight), parent);
+        for item in items {
+            self.item(item, ast, list);
+        }
+    }
+
+    fn item(&self, node: TsNode, ast: &mut Ast, list: NodeId) {
+        let range = node.byte_range();
+        let blocks = children(node);
+        let is_marker = |kind: &str| match kind {
+            "task_list_marker_checked" => Some(TaskListMarker::Checked),
+            "task_list_marker_unchecked" => Some(TaskListMarker::UnChecked),
+            _ => None,
+        };
+        let marker = blocks
+            .iter()
+            .flat_map(|block| match block.kind() {
+                "paragraph" => children(*block),
+                _ => vec![*block],
+            })
+            .find_map(|block| is_marker(block.kind()));
+        let extended = match marker {
+            Some(_) => None,
+            None => extended_task_marker(&self.text[range.clone()]),
+        };
+        let marker = marker.or(extended).unwrap_or(TaskListMarker::NoCheckmark);
+        let item = ast.push(Node::item(range, marker), Some(list));
+
+        // the sub lists come after the rest of the content of the item
+        let mut sub_lists = Vec::new();
+        for block in blocks {
+            match block.kind() {
+                kind if is_marker(kind).is_some() => {}
+                "paragraph" => {
+                    let mut inline: Vec<Node> = children(block)
+                        .into_iter()
+                        .filter(|inner| inner.kind() == "inline")
+                        .flat_map(|inner| self.inline(inner))
+                        .collect();
+                    if extended.is_some() && ast.children(item).next().is_none() {
+                        strip_extended_task_marker(&mut inline);
+                    }
+                    push_inline(ast, inline, item);
+                }
+                "list" => sub_lists.push(block),
+                _ => self.block(block, ast, Some(item)),
+            }
+        }
+        for sub_list in sub_lists {
+            self.list(sub_list, ast, Some(item));
+        }
+    }
+
+    fn code_block(&self, node: TsNode, ast: &mut Ast, parent: Option<NodeId>) {
+        let range = node.byte_range();
+        let is_fenced = node.kind() == "fenced_code_block";
+        let tag = child(node, "info_string")
+            .map(|info| self.source(info).trim().to_owned())
+            .filter(|tag| !tag.is_empty());
+        let (content_range, content) = match is_fenced {
+            true => match child(node, "code_fence_content") {
+                Some(content) => (content.byte_range(), self.source(content).to_owned()),
+                None => (range.end..range.end, String::new()),
+            },
+            false => {
+                let source = self.source(node);
+                let content = source
+                    .split_inclusive('\n')
+                    .map(|line| {
+                        line.strip_prefix("    ")
+                            .or(line.strip_prefix('\t'))
+                            .unwrap_or(line)
+                    })
+                    .collect();
+                (range.clone(), content)
+            }
+        };
+
+        if let Some(kind) = tag.as_deref().and_then(DiagramKind::from_tag) {
+            ast.push(Node::diagram(range, kind, content), parent);
+            return;
+        }
+        let block = ast.push(Node::codeblock(range, tag, is_fenced), parent);
+        if !content.is_empty() {
+            ast.push(Node::text(content_range, content), Some(block));
+        }
+    }
+
+    /// Push the table `node`, the content of its cells being pushed under it
+    fn table(&self, node: TsNode, ast: &mut Ast, parent: Option<NodeId>) {
+        let empty = || TableHead::new(node.start_byte()..node.start_byte(), Vec::new());
+        let table = ast.push(
+            Node::table(node.byte_range(), empty(), Vec::new(), Vec::new()),
+            parent,
+        );
+        let cells = |row: TsNode, ast: &mut Ast| -> Vec<TableCell> {
+            children(row)
+                .into_iter()
+                .filter(|cell| cell.kind() == "pipe_table_cell")
+                .map(|cell| {
+                    push_inline(ast, self.inline_children(cell), table);
+                    TableCell::new(cell.byte_range())
+                })
+                .collect()
+        };
+        let mut head = empty();
+        let mut alignments = Vec::new();
+        let mut body = Vec::new();
+        for row in children(node) {
+            match row.kind() {
+                "pipe_table_header" => head = TableHead::new(row.byte_range(), cells(row, ast)),
+                "pipe_table_delimiter_row" => {
+                    alignments = children(row)
+                        .into_iter()
+                        .filter(|cell| cell.kind() == "pipe_table_delimiter_cell")
+                        .map(|cell| {
+                            let left = child(cell, "pipe_table_align_left").is_some();
+                            let right = child(cell, "pipe_table_align_right").is_some();
+                            match (left, right) {
+                                (true, true) => ColumnAlignment::Center,
+                                (true, false) => ColumnAlignment::Left,
+                                (false, true) => ColumnAlignment::Right,
+                                (false, false) => ColumnAlignment::None,
+                            }
+                        })
+                        .collect();
+                }
+                "pipe_table_row" => body.push(TableRow::new(row.byte_range(), cells(row, ast))),
+                _ => {}
+            }
+        }
+        if let Node::Table {
+            header,
+            column_alignment,
+            rows,
+            ..
+        } = &mut ast[table]
+        {
+            *header = head;
+            *column_alignment = alignments;
+            *rows = body;
+        }
+    }
+
+    /// The name, destination and title of a link reference definition
+    fn link_reference(&self, node: TsNode) -> (String, String, Option<String>) {
+        let part = |kind: &str| child(node, kind).map(|c| self.source(c));
+        let name = part("link_label").unwrap_or_default();
+        let name = name
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_owned();
+        let link = part("link_destination").unwrap_or_default();
+        let link = link
+            .trim_start_matches('<')
+            .trim_end_matches('>')
+            .to_owned();
+        let title = part("link_title").map(|title| title[1..title.len() - 1].to_owned());
+        (name, link, title)
+    }
+
+    /// The inline nodes of the `inline` children of `node`
+    fn inline_children(&self, node: TsNode) -> Vec<Node> {
+        children(node)
+            .into_iter()
+            .filter(|c| c.kind() == "inline")
+            .flat_map(|inline| self.inline(inline))
+            .collect()
+    }
+
+    /// The inline nodes of the block tree node `inline`, the text between
+    /// the nodes of its inline tree becoming text nodes
+    fn inline(&self, inline: TsNode) -> Vec<Node> {
+        let range = inline.byte_range();
+        let mut nodes = Vec::new();
+        let mut offset = range.start;
+        if let Some(tree) = self.tree.inline_tree(&inline) {
+            for node in children(tree.root_node()) {
+                let Some((node_range, converted)) = self.inline_node(node) else {
+                    continue;
+                };
+                if node_range.start < offset {
+                    continue;
+                }
+                self.push_text(offset..node_range.start, &mut nodes);
+                offset = node_range.end;
+                nodes.push(converted);
+            }
+        }
+        self.push_text(offset..range.end, &mut nodes);
+        nodes
+    }
+
+    /// Push the lines of the text at `range`, without the indentation and
+    /// block quote markers of the lines continuing a block
+    fn push_text(&self, range: Range<usize>, nodes: &mut Vec<Node>) {
+        let mut start = range.start;
+        for (i, line) in self.text[range].split_inclusive('\n').enumerate() {
+            let line_start = start;
+            start += line.len();
+            let content = match i {
+                0 => line,
+                _ => line.trim_start_matches([' ', '\t', '>']),
+            };
+            let content_start = line_start + line.len() - content.len();
+            let content = content.trim_end_matches(['\n', '\r']);
+            if !content.is_empty() {
+                let end = content_start + content.len();
+                nodes.push(Node::text(content_start..end, content.to_owned()));
+            }
+        }
+    }
+
+    /// The node of an inline tree node and its range, which for a wiki link
+    /// read as a shortcut link includes its outer brackets. Nodes of other
+    /// kinds are left in the text.
+    fn inline_node(&self, node: TsNode) -> Option<(Range<usize>, Node)> {
+        let range = node.byte_range();
+        let source = self.source(node);
+        let part = |kind: &str| child(node, kind).map(|c| self.source(c));
+        let node = match node.kind() {
+            "code_span" => Node::code(range.clone(), source.trim_matches('`').to_owned()),
+            "emphasis" => decoration(range.clone(), TextDecorationKind::Emphasis, source),
+            "strong_emphasis" => decoration(range.clone(), TextDecorationKind::Strong, source),
+            "strikethrough" => decoration(range.clone(), TextDecorationKind::Strikethrough, source),
+            "inline_link" => Node::inlinelink(
+                range.clone(),
+                bracketed(part("link_text").unwrap_or_default()),
+                unbracketed(part("link_destination").unwrap_or_default()),
+            ),
+            "wiki_link" => wiki_link(range.clone(), &source[2..source.len() - 2]),
+            "full_reference_link" | "collapsed_reference_link" => {
+                let title = bracketed(part("link_text").unwrap_or_default());
+                let id = part("link_label").map_or_else(|| title.clone(), bracketed);
+                let target = self.references.get(&id.to_lowercase())?.clone();
+                Node::referencelink(range.clone(), title, id, target)
+            }
+            "shortcut_link" => {
+                let id = bracketed(source);
+                let before = self.text[..range.start].ends_with('[');
+                let after = self.text[range.end..].starts_with(']');
+                if before && after {
+                    let range = range.start - 1..range.end + 1;
+                    return Some((range.clone(), wiki_link(range, &id)));
+                }
+                match id.strip_prefix('^') {
+                    Some(name) => Node::footnotereference(range.clone(), name.to_owned()),
+                    None => {
+                        let target = self.references.get(&id.to_lowercase())?.clone();
+                        Node::shortcutlink(range.clone(), id, target)
+                    }
+                }
+            }
+            "uri_autolink" | "email_autolink" => Node::autolink(range.clone(), unbracketed(source)),
+            "image" => Node::inlineimage(range.clone()),
+            "hard_line_break" => Node::hardbreak(range.clone()),
+            "html_tag" => Node::html(range.clone(), source.to_owned()),
+            "latex_block" => Node::inlinemath(range.clone(), source.trim_matches('$').to_owned()),
+            _ => return None,
+        };
+        Some((range, node))
+    }
+}
+
+fn decoration(range: Range<usize>, kind: TextDecorationKind, source: &str) -> Node {
+    let content = source.trim_matches(['*', '_', '~']).to_owned();
+    Node::textdecoration(range, kind, content)
+}
+
+/// A wiki link of `inner`, the text between its brackets
+fn wiki_link(range: Range<usize>, inner: &str) -> Node {
+    let (target, title) = inner.split_once('|').unwrap_or((inner, inner));
+    Node::wikilink(range, title.trim().to_owned(), target.trim().to_owned())
+}
+
+/// `source` without its outer square brackets
+fn bracketed(source: &str) -> String {
+    let source = source.strip_prefix('[').unwrap_or(source);
+    source.strip_suffix(']').unwrap_or(source).to_owned()
+}
+
+/// `source` without its outer angle brackets
+fn unbracketed(source: &str) -> String {
+    let source = source.strip_prefix('<').unwrap_or(source);
+    source.strip_suffix('>').unwrap_or(source).to_owned()
+}
+
+/// Push the inline `nodes` under `parent`
+fn push_inline(ast: &mut Ast, nodes: Vec<Node>, parent: NodeId) {
+    for node in nodes {
+        ast.push(node, Some(parent));
+    }
+}
+
+/// The text of inline `nodes`, without their formatting
+fn plain_text<'n>(nodes: impl IntoIterator<Item = &'n Node>) -> String {
+    nodes
+        .into_iter()
+        .filter_map(|node| match node {
+            Node::Text { text, .. } => Some(text.as_str()),
+            Node::TextDecoration { content, .. } => Some(content.as_str()),
+            Node::Code { code, .. } => Some(code.as_str()),
+            Node::InlineLink { title, .. } | Node::WikiLink { title, .. } => Some(title.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::DocumentParser;
+
+    #[test]
+    fn test_input_edit() {
+        let edit = input_edit("# A\n\nsome text\n", "# A\n\nsome more text\n");
+        assert_eq!(
+            (edit.start_byte, edit.old_end_byte, edit.new_end_byte),
+            (10, 10, 15)
+        );
+        assert_eq!(edit.start_position, Point { row: 2, column: 5 });
+        assert_eq!(edit.new_end_position, Point { row: 2, column: 10 });
+
+        let edit = input_edit("a\nb", "a\nb");
+        assert_eq!(
+            (edit.start_byte, edit.old_end_byte, edit.new_end_byte),
+            (3, 3, 3)
+        );
+    }
+
+    /// The nodes of `ids` in `ast`
+    fn nodes(ast: &Ast, ids: impl Iterator<Item = NodeId>) -> Vec<&Node> {
+        ids.map(|id| &ast[id]).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let parser = TreeSitterParser::new();
+        let text = "# A\n\nSee [[b|B]] and [c](c.md).\n\n- [ ] a task\n- [x] done\n";
+        let ast = parser.parse(text).unwrap();
+        let [heading] = ast.roots().collect::<Vec<_>>()[..] else {
+            panic!("{ast:?}");
+        };
+        let Node::Heading {
+            content, level: 1, ..
+        } = &ast[heading]
+        else {
+            panic!("{ast:?}");
+        };
+        assert_eq!(content, "A");
+        let [paragraph, list] = ast.children(heading).collect::<Vec<_>>()[..] else {
+            panic!("{ast:?}");
+        };
+        assert!(matches!(ast[paragraph], Node::Paragraph { .. }));
+        assert!(matches!(ast[list], Node::List { tight: true, .. }));
+        let inline = nodes(&ast, ast.children(paragraph));
+        assert!(inline.iter().any(
+            |n| matches!(n, Node::WikiLink { target, title, .. } if target == "b" && title == "B")
+        ));
+        assert!(
+            inline
+                .iter()
+                .any(|n| matches!(n, Node::InlineLink { target, .. } if target == "c.md"))
+        );
+        assert!(matches!(
+            nodes(&ast, ast.children(list))[..],
+            [
+                Node::Item {
+                    task_list_marker: TaskListMarker::UnChecked,
+                    ..
+                },
+                Node::Item {
+                    task_list_marker: TaskListMarker::Checked,
+                    ..
+                },
+            ]
+        ));
+
+        // reparsed incrementally, and tolerating the unclosed code fence
+        let text = "# A\n\nSee [[b|B]] and [c](c.md).\n\n```rust\nfn a() {\n";
+        let ast = parser.parse(text).unwrap();
+        let [heading] = ast.roots().collect::<Vec<_>>()[..] else {
+            panic!("{ast:?}");
+        };
+        assert!(matches!(
+            nodes(&ast, ast.children(heading)).last(),
+            Some(Node::CodeBlock { tag: Some(tag), .. }) if tag == "rust"
+        ));
+    }
+
+    #[test]
+    fn test_loose_lists() {
+        let tight = |text: &str| {
+            let ast = TreeSitterParser::new().parse(text).unwrap();
+            let [Node::List { tight, .. }] = nodes(&ast, ast.roots())[..] else {
+                panic!("{ast:?}");
+            };
+            *tight
+        };
+        assert!(tight("- a\n- b\n"));
+        assert!(!tight("- a\n\n- b\n"));
+        assert!(!tight("1. a\n\n   b\n2. c\n"));
+    }
+
+    #[test]
+    fn test_alerts() {
+        let text = "> [!NOTE]\n> a note\n>\n> > quoted\n";
+        for ast in [
+            TreeSitterParser::new().parse(text).unwrap(),
+            DocumentParser::new().parse(text).unwrap(),
+        ] {
+            let [quote] = ast.roots().collect::<Vec<_>>()[..] else {
+                panic!("{ast:?}");
+            };
+            assert!(
+                matches!(
+                    ast[quote],
+                    Node::BlockQuote {
+                        kind: Some(BlockQuoteKind::Note),
+                        depth: 1,
+                        ..
+                    }
+                ),
+                "{ast:?}"
+            );
+            let children: Vec<NodeId> = ast.children(quote).collect();
+            let [paragraph, inner] = children[..] else {
+                panic!("{ast:?}");
+            };
+            assert!(
+                matches!(ast[paragraph], Node::Paragraph { .. })
+                    && matches!(
+                        ast[inner],
+                        Node::BlockQuote {
+                            kind: None,
+                            depth: 2,
+                            ..
+                        }
+                    )
+                    && plain_text(nodes(&ast, ast.children(paragraph))) == "a note",
+                "{ast:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,331 @@
+//! Processing documents with wasm plugins.
+//!
+//! Every `.wasm` module in `.zet/plugins` is a plugin, named after its file.
+//! A plugin is given each document as json and returns the metadata it
+//! derived from it and its diagnostics. Plugins have no imports, so they can
+//! not reach the filesystem or the network, and run with a limited amount of
+//! fuel.
+//!
+//! A plugin exports
+//! - `memory`
+//! - `zet_alloc(len: i32) -> i32`, returning a buffer of `len` bytes the input
+//!   is written to
+//! - `zet_process(ptr: i32, len: i32) -> i64`, processing the input in the
+//!   buffer and returning the location of its output as `ptr << 32 | len`
+//!
+//! The input is `{"id": .., "path": .., "frontmatter": .., "ast_version": ..,
+//! "ast": [..]}`, the ast being the nodes of the document content after the
+//! frontmatter, in the shape of version [`AST_VERSION`]. The output is
+//! `{"metadata": .., "diagnostics": [{"start": .., "end": .., "message":
+//! ..}]}`, the ranges of the diagnostics being byte offsets in the same
+//! content. Both fields are optional.
+
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::eyre;
+use serde::{Deserialize, Serialize};
+use wasmtime::{Engine, Instance, Module, Store};
+
+use crate::core::frontmatter::body_offset;
+use crate::core::index_hook::{DerivedRow, IndexHook};
+use crate::core::lint::{LintIssue, LintRule};
+use crate::core::parser::arena::Ast;
+use crate::core::parser::ast_nodes::AST_VERSION;
+use crate::core::parser::{DocumentParser, FrontMatterFormat, FrontMatterParser, Parse};
+use crate::core::types::document::{Document, DocumentId};
+use crate::core::types::metadata::{NewDocumentMetadata, PluginDiagnostic};
+use crate::core::{collection_config_dir, extract_id_from_frontmatter, path_to_id};
+use crate::result::Result;
+
+/// The fuel a plugin may consume per document, bounding plugins that never
+/// return
+const PLUGIN_FUEL: u64 = 1_000_000_000;
+
+/// .zet/plugins
+pub fn plugin_dir(root: &Path) -> PathBuf {
+    collection_config_dir(root).join("plugins")
+}
+
+#[derive(Debug, Serialize)]
+struct PluginInput<'a> {
+    id: &'a DocumentId,
+    path: &'a Path,
+    frontmatter: &'a serde_json::Value,
+    ast_version: u32,
+    ast: &'a Ast,
+}
+
+/// What a plugin returns for a document
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PluginOutput {
+    pub metadata: serde_json::Value,
+    pub diagnostics: Vec<PluginDiagnostic>,
+}
+
+struct Plugin {
+    name: String,
+    module: Module,
+}
+
+/// The plugins of a collection
+pub struct PluginHost {
+    engine: Engine,
+    plugins: Vec<Plugin>,
+}
+
+fn wasm_error(e: wasmtime::Error) -> color_eyre::Report {
+    eyre!("{:#}", e)
+}
+
+impl PluginHost {
+    /// Load the plugins in `.zet/plugins`, in the order of their names
+    pub fn load(root: &Path) -> Result<Self> {
+        let mut config = wasmtime::Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(wasm_error)?;
+
+        let mut paths = Vec::new();
+        let dir = plugin_dir(root);
+        if dir.is_dir() {
+            for entry in std::fs::read_dir(dir)? {
+                let path = entry?.path();
+                if path.extension().is_some_and(|ext| ext == "wasm") {
+                    paths.push(path);
+                }
+            }
+        }
+        paths.sort();
+
+        let mut plugins = Vec::with_capacity(paths.len());
+        for path in paths {
+            let module = Module::from_file(&engine, &path)
+                .map_err(|e| eyre!("could not load plugin {:?}: {:#}", path, e))?;
+            let name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            plugins.push(Plugin { name, module });
+        }
+        Ok(Self { engine, plugins })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// Run every plugin on a document, returning the name of each plugin and
+    /// its output
+    pub fn process(
+        &self,
+        id: &DocumentId,
+        path: &Path,
+        frontmatter: &serde_json::Value,
+        ast: &Ast,
+    ) -> Result<Vec<(String, PluginOutput)>> {
+        let input = serde_json::to_vec(&PluginInput {
+            id,
+            path,
+            frontmatter,
+            ast_version: AST_VERSION,
+            ast,
+        })?;
+        self.plugins
+            .iter()
+            .map(|plugin| {
+                let output = self
+                    .run(plugin, &input)
+                    .map_err(|e| eyre!("plugin {} failed: {}", plugin.name, e))?;
+                Ok((plugin.name.clone(), output))
+            })
+            .collect()
+    }
+
+    fn run(&self, plugin: &Plugin, input: &[u8]) -> Result<PluginOutput> {
+        let mut store = Store::new(&self.engine, ());
+        store.set_fuel(PLUGIN_FUEL).map_err(wasm_error)?;
+        let instance = Instance::new(&mut store, &plugin.module, &[]).map_err(wasm_error)?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| eyre!("no memory exported"))?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&mut store, "zet_alloc")
+            .map_err(wasm_error)?;
+        let process = instance
+            .get_typed_func::<(i32, i32), i64>(&mut store, "zet_process")
+            .map_err(wasm_error)?;
+
+        let len = i32::try_from(input.len())?;
+        let ptr = alloc.call(&mut store, len).map_err(wasm_error)?;
+        memory
+            .write(&mut store, ptr as u32 as usize, input)
+            .map_err(|e| eyre!("{}", e))?;
+        let location = process.call(&mut store, (ptr, len)).map_err(wasm_error)? as u64;
+
+        let (ptr, len) = ((location >> 32) as usize, (location & 0xffff_ffff) as usize);
+        let mut output = vec![0; len];
+        memory
+            .read(&store, ptr, &mut output)
+            .map_err(|e| eyre!("{}", e))?;
+        Ok(serde_json::from_slice(&output)?)
+    }
+}
+
+/// Stores the output of the plugins in `document_metadata`
+pub struct PluginHook {
+    host: PluginHost,
+}
+
+impl PluginHook {
+    pub fn new(host: PluginHost) -> Self {
+        Self { host }
+    }
+}
+
+impl IndexHook for PluginHook {
+    fn on_document(&mut self, doc: &Document, ast: &Ast) -> Vec<DerivedRow> {
+        match self.host.process(&doc.id, &doc.path.0, &doc.data, ast) {
+            Ok(outputs) => outputs
+                .into_iter()
+                .map(|(plugin, output)| {
+                    DerivedRow::Metadata(NewDocumentMetadata {
+                        document_id: doc.id.clone(),
+                        plugin,
+                        metadata: output.metadata,
+                        diagnostics: output.diagnostics,
+                    })
+                })
+                .collect(),
+            Err(e) => {
+                log::warn!("could not process {:?}: {}", doc.path.0, e);
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// The diagnostics of the plugins for `document` as lint issues
+pub fn lint_plugins(
+    host: &PluginHost,
+    root: &Path,
+    path: &Path,
+    document: &str,
+    format: FrontMatterFormat,
+) -> Result<Vec<LintIssue>> {
+    if host.is_empty() {
+        return Ok(Vec::new());
+    }
+    let body_offset = body_offset(document);
+    let (frontmatter, _) = FrontMatterParser::new(format).parse(document.to_owned());
+    let frontmatter = frontmatter.unwrap_or_default();
+    let ast = DocumentParser::new().parse(&document[body_offset..])?;
+    let id = extract_id_from_frontmatter(&frontmatter).unwrap_or_else(|| path_to_id(root, path));
+
+    let mut issues = Vec::new();
+    for (plugin, output) in host.process(&id, path, &frontmatter, &ast)? {
+        for diagnostic in output.diagnostics {
+            let start = (body_offset + diagnostic.start).min(document.len());
+            let end = (body_offset + diagnostic.end).clamp(start, document.len());
+            issues.push(LintIssue {
+                rule: LintRule::Plugin,
+                range: start..end,
+                message: format!("{}: {}", plugin, diagnostic.message),
+                fix: None,
+            });
+        }
+    }
+    Ok(issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A plugin returning the same output for every document
+    const PLUGIN: &str = r#"
+        (module
+            (memory (export "memory") 1)
+            (data (i32.const 0) "{\"metadata\":{\"words\":2},\"diagnostics\":[{\"start\":2,\"end\":7,\"message\":\"too short\"}]}")
+            (func (export "zet_alloc") (param i32) (result i32)
+                i32.const 1024)
+            (func (export "zet_process") (param i32 i32) (result i64)
+                i64.const 82))
+    "#;
+
+    const LOOPING_PLUGIN: &str = r#"
+        (module
+            (memory (export "memory") 1)
+            (func (export "zet_alloc") (param i32) (result i32)
+                i32.const 1024)
+            (func (export "zet_process") (param i32 i32) (result i64)
+                (loop $forever (br $forever))
+                i64.const 0))
+    "#;
+
+    fn setup(plugins: &[(&str, &str)]) -> assert_fs::TempDir {
+        let root = assert_fs::TempDir::new().unwrap();
+        std::fs::create_dir_all(plugin_dir(root.path())).unwrap();
+        for (name, wat) in plugins {
+            let wasm = wat::parse_str(wat).unwrap();
+            std::fs::write(plugin_dir(root.path()).join(format!("{name}.wasm")), wasm).unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn test_process() {
+        let root = setup(&[("words", PLUGIN)]);
+        let host = PluginHost::load(root.path()).unwrap();
+        let ast = DocumentParser::new().parse("# A\n\nb c\n").unwrap();
+        let outputs = host
+            .process(
+                &DocumentId("a".to_owned()),
+                Path::new("a.md"),
+                &serde_json::Value::Null,
+                &ast,
+            )
+            .unwrap();
+        assert_eq!(
+            outputs,
+            vec![(
+                "words".to_owned(),
+                PluginOutput {
+                    metadata: serde_json::json!({"words": 2}),
+                    diagnostics: vec![PluginDiagnostic {
+                        start: 2,
+                        end: 7,
+                        message: "too short".to_owned()
+                    }],
+                }
+            )]
+        );
+
+        let document = "---\ntitle: a\n---\n# A\n\nb c\n";
+        let issues = lint_plugins(
+            &host,
+            root.path(),
+            &root.path().join("a.md"),
+            document,
+            FrontMatterFormat::Yaml,
+        )
+        .unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(&document[issues[0].range.clone()], "A\n\nb ");
+        assert_eq!(issues[0].message, "words: too short");
+    }
+
+    #[test]
+    fn test_out_of_fuel() {
+        let root = setup(&[("loop", LOOPING_PLUGIN)]);
+        let host = PluginHost::load(root.path()).unwrap();
+        let error = host
+            .process(
+                &DocumentId("a".to_owned()),
+                Path::new("a.md"),
+                &serde_json::Value::Null,
+                &Ast::new(),
+            )
+            .unwrap_err();
+        assert!(error.to_string().starts_with("plugin loop failed"));
+    }
+}
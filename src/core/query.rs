@@ -1,11 +1,16 @@
+use std::str::FromStr;
+
+use color_eyre::eyre::eyre;
 use jiff::Timestamp;
 use rusqlite::Connection;
 use rusqlite::types::Value;
 
+use crate::core::date_parser::TimeRange;
+use crate::core::db::Page;
 use crate::result::Result;
 
 use super::types::document::{
-    CreatedTimestamp, Document, DocumentId, DocumentPath, ModifiedTimestamp,
+    CreatedTimestamp, Document, DocumentId, DocumentPath, DocumentSummary, ModifiedTimestamp,
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -32,8 +37,8 @@ pub struct DocumentQuery {
     pub tagless: bool,
     pub exclude_ids: Vec<String>,
     pub exclude_paths: Vec<String>,
-    pub created: Option<Timestamp>,
-    pub modified: Option<Timestamp>,
+    pub created: Option<TimeRange>,
+    pub modified: Option<TimeRange>,
     pub created_before: Option<Timestamp>,
     pub created_after: Option<Timestamp>,
     pub modified_before: Option<Timestamp>,
@@ -41,8 +46,47 @@ pub struct DocumentQuery {
     pub links_to: Vec<String>,
     pub links_from: Vec<String>,
     pub match_pattern: Option<String>,
+    /// frontmatter keys the documents have, set to one of the values unless
+    /// there are none
+    pub frontmatter: Vec<(String, Vec<String>)>,
     pub order_by: Vec<(SortByOption, SortOrder)>,
-    pub limit: Option<usize>,
+    pub page: Page,
+}
+
+/// A single `key:value` condition on the documents of a query, such as
+/// `tag:#book` or `path:journal/`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DocumentFilter {
+    Id(String),
+    Title(String),
+    /// suffix of the path
+    Path(String),
+    Tag(String),
+    LinksTo(String),
+    LinksFrom(String),
+    /// full-text search pattern
+    Match(String),
+}
+
+impl FromStr for DocumentFilter {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let Some((key, value)) = s.split_once(':') else {
+            return Err(eyre!("invalid filter {:?}, expected key:value", s));
+        };
+        let value = value.to_owned();
+        Ok(match key {
+            "id" => DocumentFilter::Id(value),
+            "title" => DocumentFilter::Title(value),
+            "path" => DocumentFilter::Path(value),
+            "tag" => DocumentFilter::Tag(value.trim_start_matches('#').to_owned()),
+            "links-to" => DocumentFilter::LinksTo(value),
+            "links-from" => DocumentFilter::LinksFrom(value),
+            "match" => DocumentFilter::Match(value),
+            _ => return Err(eyre!("unknown filter key {:?}", key)),
+        })
+    }
 }
 
 impl DocumentQuery {
@@ -85,13 +129,13 @@ impl DocumentQuery {
         self
     }
 
-    pub fn created(mut self, ts: Timestamp) -> Self {
-        self.created = Some(ts);
+    pub fn created(mut self, range: TimeRange) -> Self {
+        self.created = Some(range);
         self
     }
 
-    pub fn modified(mut self, ts: Timestamp) -> Self {
-        self.modified = Some(ts);
+    pub fn modified(mut self, range: TimeRange) -> Self {
+        self.modified = Some(range);
         self
     }
 
@@ -130,22 +174,85 @@ impl DocumentQuery {
         self
     }
 
+    /// Add the condition of `filter` to the query. Filters on tags, paths and
+    /// patterns must all match, while any of several ids, titles or links
+    /// matches.
+    /// Only documents with the frontmatter `key`, set to one of `values`
+    /// unless it is empty
+    pub fn with_frontmatter(mut self, key: &str, values: Vec<String>) -> Self {
+        self.frontmatter.push((key.to_owned(), values));
+        self
+    }
+
+    pub fn filter(mut self, filter: DocumentFilter) -> Self {
+        match filter {
+            DocumentFilter::Id(id) => self.ids.push(id),
+            DocumentFilter::Title(title) => self.titles.push(title),
+            DocumentFilter::Path(path) => self.paths.push(path),
+            DocumentFilter::Tag(tag) => self.tags.push(tag),
+            DocumentFilter::LinksTo(id) => self.links_to.push(id),
+            DocumentFilter::LinksFrom(id) => self.links_from.push(id),
+            DocumentFilter::Match(pattern) => {
+                self.match_pattern = Some(match self.match_pattern {
+                    Some(current) => format!("({current}) AND ({pattern})"),
+                    None => pattern,
+                })
+            }
+        }
+        self
+    }
+
     pub fn order_by(mut self, by: SortByOption, order: SortOrder) -> Self {
         self.order_by.push((by, order));
         self
     }
 
     pub fn limit(mut self, n: usize) -> Self {
-        self.limit = Some(n);
+        self.page.limit = Some(n);
+        self
+    }
+
+    pub fn page(mut self, page: Page) -> Self {
+        self.page = page;
         self
     }
 
     pub fn execute(self, db: &Connection) -> Result<Vec<Document>> {
-        let mut sql = String::from(
-            r#"SELECT DISTINCT d.id, d.title, d.path, d.hash, d.modified, d.created, json(d.frontmatter)
-FROM document d
-WHERE 1=1"#,
-        );
+        let (sql, params) =
+            self.sql("d.id, d.title, d.path, d.hash, d.modified, d.created, json(d.frontmatter)");
+        query(db, &sql, &params, |r| {
+            Ok(Document::new(
+                r.get::<_, DocumentId>(0)?,
+                r.get::<_, String>(1)?,
+                r.get::<_, DocumentPath>(2)?,
+                r.get::<_, u32>(3)?,
+                r.get::<_, ModifiedTimestamp>(4)?,
+                r.get::<_, CreatedTimestamp>(5)?,
+                r.get::<_, serde_json::Value>(6)?,
+            ))
+        })
+    }
+
+    /// Like [`DocumentQuery::execute`], without reading the frontmatter of
+    /// the documents
+    pub fn execute_summaries(self, db: &Connection) -> Result<Vec<DocumentSummary>> {
+        let (sql, params) = self.sql("d.id, d.title, d.path, d.hash, d.modified, d.created");
+        query(db, &sql, &params, |r| {
+            Ok(DocumentSummary {
+                id: r.get(0)?,
+                title: r.get(1)?,
+                path: r.get(2)?,
+                hash: r.get(3)?,
+                modified: r.get(4)?,
+                created: r.get(5)?,
+            })
+        })
+    }
+
+    /// The query selecting `columns` of the matching documents, and its
+    /// parameters
+    fn sql(self, columns: &str) -> (String, Vec<Value>) {
+        let mut sql = format!("SELECT DISTINCT {columns}\nFROM document d\nWHERE 1=1");
         let mut params: Vec<Value> = Vec::new();
 
         // --id filter
@@ -168,12 +275,15 @@ WHERE 1=1"#,
             params.push(Value::from(format!("%{}", path)));
         }
 
-        // --tag filter (AND semantics: document must have ALL specified tags)
+        // --tag filter (AND semantics: document must have ALL specified tags,
+        // or a tag nested under them)
         for tag in &self.tags {
             sql.push_str(
-                " AND EXISTS (SELECT 1 FROM document_tag_map m JOIN tag t ON m.tag_id = t.id WHERE m.document_id = d.id AND LOWER(t.tag) = LOWER(?))",
+                " AND EXISTS (SELECT 1 FROM document_tag_map m JOIN tag t ON m.tag_id = t.id WHERE m.document_id = d.id AND (LOWER(t.tag) = LOWER(?) OR INSTR(LOWER(t.tag), LOWER(?) || '/') = 1))",
             );
-            params.push(Value::from(tag.clone()));
+            let tag = tag.trim_end_matches('/');
+            params.push(Value::from(tag.to_owned()));
+            params.push(Value::from(tag.to_owned()));
         }
 
         // --tagless filter
@@ -196,16 +306,18 @@ WHERE 1=1"#,
             params.push(Value::from(format!("%{}", path)));
         }
 
-        // --created filter (exact date match)
-        if let Some(ts) = self.created {
-            sql.push_str(" AND date(d.created) = date(?)");
-            params.push(Value::from(ts.to_string()));
+        // --created filter (within range)
+        if let Some(range) = self.created {
+            sql.push_str(" AND d.created >= ? AND d.created < ?");
+            params.push(Value::from(range.start.to_string()));
+            params.push(Value::from(range.end.to_string()));
         }
 
-        // --modified filter (exact date match)
-        if let Some(ts) = self.modified {
-            sql.push_str(" AND date(d.modified) = date(?)");
-            params.push(Value::from(ts.to_string()));
+        // --modified filter (within range)
+        if let Some(range) = self.modified {
+            sql.push_str(" AND d.modified >= ? AND d.modified < ?");
+            params.push(Value::from(range.start.to_string()));
+            params.push(Value::from(range.end.to_string()));
         }
 
         // --created-before filter
@@ -258,6 +370,22 @@ WHERE 1=1"#,
             params.push(Value::from(pattern.clone()));
         }
 
+        // frontmatter filter
+        for (key, values) in self.frontmatter {
+            let path = format!("$.\"{key}\"");
+            if values.is_empty() {
+                sql.push_str(" AND json_extract(d.frontmatter, ?) IS NOT NULL");
+                params.push(Value::from(path));
+            } else {
+                let placeholders = generate_placeholders(values.len());
+                sql.push_str(&format!(
+                    " AND json_extract(d.frontmatter, ?) IN ({placeholders})"
+                ));
+                params.push(Value::from(path));
+                params.extend(values.into_iter().map(Value::from));
+            }
+        }
+
         // ORDER BY
         if !self.order_by.is_empty() {
             sql.push_str(" ORDER BY ");
@@ -282,37 +410,31 @@ WHERE 1=1"#,
             sql.push_str(&order_clauses.join(", "));
         }
 
-        // LIMIT
-        if let Some(n) = self.limit {
-            sql.push_str(&format!(" LIMIT {n}"));
-        }
+        // LIMIT and OFFSET
+        sql.push_str(&self.page.sql());
 
-        log::debug!("executing query: {}", sql);
-        log::debug!("with params: {:?}", params);
-
-        let mut stmt = db.prepare(&sql)?;
-        let params_slice: Vec<&dyn rusqlite::ToSql> =
-            params.iter().map(|v| v as &dyn rusqlite::ToSql).collect();
-
-        let documents = stmt
-            .query_map(params_slice.as_slice(), |r| {
-                Ok(Document::new(
-                    r.get::<_, DocumentId>(0)?,
-                    r.get::<_, String>(1)?,
-                    r.get::<_, DocumentPath>(2)?,
-                    r.get::<_, u32>(3)?,
-                    r.get::<_, ModifiedTimestamp>(4)?,
-                    r.get::<_, CreatedTimestamp>(5)?,
-                    r.get::<_, serde_json::Value>(6)?,
-                ))
-            })?
-            .map(|r| r.map_err(From::from))
-            .collect::<Result<Vec<Document>>>()?;
-
-        Ok(documents)
+        (sql, params)
     }
 }
 
+fn query<T>(
+    db: &Connection,
+    sql: &str,
+    params: &[Value],
+    row: impl FnMut(&rusqlite::Row) -> rusqlite::Result<T>,
+) -> Result<Vec<T>> {
+    log::debug!("executing query: {}", sql);
+    log::debug!("with params: {:?}", params);
+
+    let mut stmt = db.prepare(sql)?;
+    let params_slice: Vec<&dyn rusqlite::ToSql> =
+        params.iter().map(|v| v as &dyn rusqlite::ToSql).collect();
+
+    stmt.query_map(params_slice.as_slice(), row)?
+        .map(|r| r.map_err(From::from))
+        .collect()
+}
+
 fn generate_placeholders(count: usize) -> String {
     vec!["?"; count].join(", ")
 }
@@ -327,4 +449,18 @@ mod tests {
         assert_eq!(generate_placeholders(3), "?, ?, ?");
         assert_eq!(generate_placeholders(0), "");
     }
+
+    #[test]
+    fn test_parse_filter() {
+        assert_eq!(
+            "tag:#book".parse::<DocumentFilter>().unwrap(),
+            DocumentFilter::Tag("book".to_owned())
+        );
+        assert_eq!(
+            "links-to:notes/foo".parse::<DocumentFilter>().unwrap(),
+            DocumentFilter::LinksTo("notes/foo".to_owned())
+        );
+        assert!("book".parse::<DocumentFilter>().is_err());
+        assert!("color:red".parse::<DocumentFilter>().is_err());
+    }
 }
@@ -0,0 +1,151 @@
+//! Block quotes, and who they are attributed to.
+//!
+//! A quote is attributed by a last line starting with a dash, `— author`,
+//! `― author` or `-- author`, in the last paragraph of the quote or as a
+//! paragraph of its own.
+
+use crate::core::extract_text_from_ast;
+use crate::core::parser::arena::{Ast, NodeId};
+use crate::core::parser::ast_nodes::Node;
+
+pub const DEFAULT_QUOTES_TEMPLATE: &str = r#"---
+id: {{ id }}
+title: {{ title }}
+---
+
+# {{ title }}
+{% for quote in quotes %}
+> {{ quote.content }}
+{%- if quote.attribution %}
+>
+> — {{ quote.attribution }}
+{%- endif %}
+
+From [[{{ quote.document_id }}]]
+{% endfor %}"#;
+
+const DASHES: &[&str] = &["—", "―", "--"];
+
+/// The text of the block quote `quote` of `ast`, and its attribution
+pub fn quote_text(ast: &Ast, quote: NodeId) -> (String, Option<String>) {
+    let children: Vec<NodeId> = ast.children(quote).collect();
+    let Some((&paragraph, rest)) = children
+        .split_last()
+        .filter(|(last, _)| matches!(ast[**last], Node::Paragraph { .. }))
+    else {
+        return (blocks_text(ast, &children), None);
+    };
+    let last: Vec<NodeId> = ast.children(paragraph).collect();
+    // the last line starting with a dash, found by the text node it starts
+    // with, as the line breaks are not part of the ast
+    let starts_line =
+        |i: usize| i == 0 || ast[last[i]].range().start > ast[last[i - 1]].range().end;
+    let start = (0..last.len()).rev().find(|&i| {
+        starts_line(i)
+            && matches!(&ast[last[i]], Node::Text { text, .. }
+                if DASHES.iter().any(|dash| text.trim_start().starts_with(dash)))
+    });
+    let attribution = start
+        .filter(|&start| start > 0 || !rest.is_empty())
+        .and_then(|start| {
+            let text = extract_text_from_ast(ast, last[start..].iter().copied());
+            DASHES
+                .iter()
+                .find_map(|dash| text.strip_prefix(dash))
+                .map(|name| name.trim().to_owned())
+                .filter(|name| !name.is_empty())
+        });
+    let (Some(start), Some(_)) = (start, &attribution) else {
+        return (blocks_text(ast, &children), None);
+    };
+
+    let mut text = blocks_text(ast, rest);
+    let last = join_lines(ast, &lines(ast, &last[..start]));
+    if !text.is_empty() && !last.is_empty() {
+        text.push(' ');
+    }
+    text.push_str(&last);
+    (text, attribution)
+}
+
+/// The text of the paragraphs and headings `ids`, their lines joined by
+/// spaces
+fn blocks_text(ast: &Ast, ids: &[NodeId]) -> String {
+    let texts: Vec<String> = ids
+        .iter()
+        .filter_map(|&id| match ast[id] {
+            Node::Paragraph { .. } | Node::Heading { .. } => {
+                let children: Vec<NodeId> = ast.children(id).collect();
+                Some(join_lines(ast, &lines(ast, &children)))
+            }
+            _ => None,
+        })
+        .filter(|text| !text.is_empty())
+        .collect();
+    texts.join(" ")
+}
+
+fn join_lines(ast: &Ast, lines: &[&[NodeId]]) -> String {
+    let texts: Vec<String> = lines
+        .iter()
+        .map(|line| extract_text_from_ast(ast, line.iter().copied()))
+        .filter(|text| !text.is_empty())
+        .collect();
+    texts.join(" ")
+}
+
+/// The inline nodes `ids` split into lines. The soft breaks between lines
+/// are not part of the ast, a line starts where a node does not continue the
+/// previous one.
+fn lines<'a>(ast: &Ast, ids: &'a [NodeId]) -> Vec<&'a [NodeId]> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for i in 1..=ids.len() {
+        let previous = &ast[ids[i - 1]];
+        let ends_line = match ids.get(i).map(|&id| &ast[id]) {
+            Some(Node::HardBreak { .. }) | None => true,
+            Some(node) => {
+                !matches!(previous, Node::HardBreak { .. })
+                    && node.range().start > previous.range().end
+            }
+        };
+        if ends_line {
+            lines.push(&ids[start..i]);
+            start = i;
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::{DocumentParser, Parse};
+
+    fn quote(document: &str) -> (String, Option<String>) {
+        let ast = DocumentParser::new().parse(document).unwrap();
+        let Some(quote) = ast.roots().next() else {
+            panic!("no block quote in {ast:?}");
+        };
+        assert!(matches!(ast[quote], Node::BlockQuote { .. }));
+        quote_text(&ast, quote)
+    }
+
+    #[test]
+    fn test_quote_text() {
+        assert_eq!(
+            quote("> a *quote*\n> over lines\n"),
+            ("a quote over lines".to_owned(), None)
+        );
+        assert_eq!(
+            quote("> to be\n> — Hamlet\n"),
+            ("to be".to_owned(), Some("Hamlet".to_owned()))
+        );
+        assert_eq!(
+            quote("> one\n>\n> two\n>\n> -- [[ada]] Lovelace\n"),
+            ("one two".to_owned(), Some("ada Lovelace".to_owned()))
+        );
+        // a quote that is only a dash is not attributed
+        assert_eq!(quote("> — dash\n"), ("— dash".to_owned(), None));
+    }
+}
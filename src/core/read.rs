@@ -0,0 +1,230 @@
+//! Reading the files of documents for the index.
+//!
+//! A file that is not valid UTF-8 is decoded lossily, its invalid sequences
+//! replaced by `U+FFFD`. A file larger than `index.max_file_size` is skipped,
+//! and thereby left out of the collection, or truncated to that size when
+//! `index.large_files` is `truncate`. The index warns about both, and `zet
+//! doctor` lists the files concerned.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::IndexConfig;
+use crate::result::Result;
+
+/// Maximum size of an indexed file unless configured otherwise, 10 MiB
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// What the index does with the files larger than `index.max_file_size`
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LargeFilePolicy {
+    /// leave the file out of the collection
+    #[default]
+    Skip,
+    /// index the start of the file, up to the maximum size
+    Truncate,
+}
+
+/// Why a file is not indexed as it is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileIssue {
+    /// the file is not valid UTF-8, its invalid sequences are replaced
+    InvalidUtf8,
+    /// the file is too large and left out of the collection
+    Skipped,
+    /// the file is too large and only its start is indexed
+    Truncated,
+}
+
+impl FileIssue {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FileIssue::InvalidUtf8 => "invalid_utf8",
+            FileIssue::Skipped => "skipped",
+            FileIssue::Truncated => "truncated",
+        }
+    }
+}
+
+impl std::fmt::Display for FileIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A document file as it is indexed
+#[derive(Debug, Clone)]
+pub struct DocumentFile {
+    /// the content of the file, `None` if it is skipped
+    pub content: Option<String>,
+    pub size: u64,
+    pub issues: Vec<FileIssue>,
+}
+
+/// Whether the file at `path` is left out of the collection for its size
+pub fn is_skipped(path: &Path, config: &IndexConfig) -> bool {
+    config.large_files == LargeFilePolicy::Skip
+        && std::fs::metadata(path).is_ok_and(|m| m.len() > config.max_file_size)
+}
+
+/// Read the document file at `path` as it is indexed, see the module
+/// documentation. Skipped files are not read.
+pub fn read_document(path: &Path, config: &IndexConfig) -> Result<DocumentFile> {
+    let size = std::fs::metadata(path)?.len();
+    let mut issues = Vec::new();
+    let too_large = size > config.max_file_size;
+    if too_large && config.large_files == LargeFilePolicy::Skip {
+        return Ok(DocumentFile {
+            content: None,
+            size,
+            issues: vec![FileIssue::Skipped],
+        });
+    }
+
+    let mut bytes = Vec::new();
+    std::fs::File::open(path)?
+        .take(config.max_file_size)
+        .read_to_end(&mut bytes)?;
+    if too_large {
+        issues.push(FileIssue::Truncated);
+        bytes.truncate(complete_len(&bytes));
+    }
+    let content = match String::from_utf8(bytes) {
+        Ok(content) => content,
+        Err(e) => {
+            issues.push(FileIssue::InvalidUtf8);
+            String::from_utf8_lossy(e.as_bytes()).into_owned()
+        }
+    };
+    Ok(DocumentFile {
+        content: Some(content),
+        size,
+        issues,
+    })
+}
+
+/// [`read_document`], warning about the issues of the file
+pub fn read_document_logged(path: &Path, config: &IndexConfig) -> Result<DocumentFile> {
+    let file = read_document(path, config)?;
+    for issue in &file.issues {
+        match issue {
+            FileIssue::InvalidUtf8 => log::warn!(
+                "{:?} is not valid UTF-8, its invalid sequences are replaced",
+                path
+            ),
+            FileIssue::Skipped => log::warn!(
+                "{:?} is larger than {} bytes and skipped",
+                path,
+                config.max_file_size
+            ),
+            FileIssue::Truncated => log::warn!(
+                "{:?} is larger than {} bytes and truncated",
+                path,
+                config.max_file_size
+            ),
+        }
+    }
+    Ok(file)
+}
+
+/// The content of the file at `path`, `None` with a warning if it is not
+/// valid UTF-8. Commands that write documents back read them this way, as
+/// writing a lossily decoded document would replace its invalid sequences.
+pub fn read_utf8(path: &Path) -> Result<Option<String>> {
+    match String::from_utf8(std::fs::read(path)?) {
+        Ok(content) => Ok(Some(content)),
+        Err(_) => {
+            log::warn!("skipping {:?}, it is not valid UTF-8", path);
+            Ok(None)
+        }
+    }
+}
+
+/// The document files of `paths` that are not indexed as they are
+pub fn file_issues(
+    paths: &[PathBuf],
+    config: &IndexConfig,
+) -> Result<Vec<(PathBuf, DocumentFile)>> {
+    let mut files = Vec::new();
+    for path in paths {
+        let file = read_document(path, config)?;
+        if !file.issues.is_empty() {
+            files.push((path.clone(), file));
+        }
+    }
+    Ok(files)
+}
+
+/// The length of `bytes` without a UTF-8 sequence cut off at its end
+fn complete_len(bytes: &[u8]) -> usize {
+    let start = bytes.len().saturating_sub(3);
+    for i in (start..bytes.len()).rev() {
+        let width = match bytes[i] {
+            // a continuation byte
+            0x80..=0xBF => continue,
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => 1,
+        };
+        return match i + width > bytes.len() {
+            true => i,
+            false => bytes.len(),
+        };
+    }
+    bytes.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(max_file_size: u64, large_files: LargeFilePolicy) -> IndexConfig {
+        IndexConfig {
+            max_file_size,
+            large_files,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_complete_len() {
+        let bytes = "aé€".as_bytes();
+        assert_eq!(complete_len(bytes), bytes.len());
+        assert_eq!(complete_len(&bytes[..5]), 3);
+        assert_eq!(complete_len(&bytes[..4]), 3);
+        assert_eq!(complete_len(&bytes[..2]), 1);
+        assert_eq!(complete_len(b""), 0);
+    }
+
+    #[test]
+    fn test_read_document() {
+        let root = assert_fs::TempDir::new().unwrap();
+        let latin1 = root.path().join("latin1.md");
+        std::fs::write(&latin1, b"caf\xe9 au lait\n").unwrap();
+        let large = root.path().join("large.md");
+        std::fs::write(&large, "# Large\n\néé").unwrap();
+
+        let file = read_document(&latin1, &IndexConfig::default()).unwrap();
+        assert_eq!(file.content.as_deref(), Some("caf\u{FFFD} au lait\n"));
+        assert_eq!(file.issues, vec![FileIssue::InvalidUtf8]);
+
+        let file = read_document(&large, &config(12, LargeFilePolicy::Skip)).unwrap();
+        assert_eq!(file.content, None);
+        assert_eq!(file.issues, vec![FileIssue::Skipped]);
+        assert!(is_skipped(&large, &config(12, LargeFilePolicy::Skip)));
+        assert!(!is_skipped(&large, &config(12, LargeFilePolicy::Truncate)));
+
+        // not cut within a character
+        let file = read_document(&large, &config(12, LargeFilePolicy::Truncate)).unwrap();
+        assert_eq!(file.content.as_deref(), Some("# Large\n\né"));
+        assert_eq!(file.issues, vec![FileIssue::Truncated]);
+
+        assert_eq!(read_utf8(&latin1).unwrap(), None);
+        assert_eq!(read_utf8(&large).unwrap().as_deref(), Some("# Large\n\néé"));
+    }
+}
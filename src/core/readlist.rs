@@ -0,0 +1,102 @@
+//! The reading list: literature notes of web pages, with a reading status.
+//!
+//! A note is on the reading list when its frontmatter has a `url` and a
+//! `status` that is one of the statuses of the `[readlist]` configuration.
+//! Notes are added with the title and metadata of the page they point to.
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::core::query::{DocumentQuery, SortByOption, SortOrder};
+use crate::core::types::document::{Document, DocumentId};
+use crate::result::Result;
+
+pub const URL_KEY: &str = "url";
+pub const STATUS_KEY: &str = "status";
+
+pub const DEFAULT_READLIST_TEMPLATE: &str = r#"---
+id: {{ id }}
+title: {{ title }}
+---
+
+# {{ title }}
+
+<{{ url }}>
+{% if description %}
+> {{ description }}
+{% endif %}"#;
+
+/// The `[readlist]` section of the configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReadlistConfig {
+    /// directory relative to the collection root notes are added to
+    pub directory: String,
+    /// template name or path of added notes, resolved like group templates.
+    /// Besides the builtin variables, it is rendered with `url`, `author`,
+    /// `site`, `description` and `published`.
+    pub template: Option<String>,
+    /// the reading statuses, the first being the one of added notes
+    pub statuses: Vec<String>,
+}
+
+impl Default for ReadlistConfig {
+    fn default() -> Self {
+        Self {
+            directory: "reading".to_owned(),
+            template: None,
+            statuses: ["to-read", "reading", "read"].map(String::from).to_vec(),
+        }
+    }
+}
+
+impl ReadlistConfig {
+    /// The status of added notes
+    pub fn initial_status(&self) -> &str {
+        self.statuses.first().map_or("to-read", String::as_str)
+    }
+}
+
+/// A note of the reading list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadlistEntry {
+    pub id: DocumentId,
+    pub title: String,
+    pub url: String,
+    pub status: String,
+}
+
+impl ReadlistEntry {
+    fn from_document(document: Document) -> Option<Self> {
+        let text = |key: &str| document.data.get(key)?.as_str().map(str::to_owned);
+        Some(Self {
+            url: text(URL_KEY)?,
+            status: text(STATUS_KEY)?,
+            id: document.id,
+            title: document.title,
+        })
+    }
+}
+
+/// The notes of the reading list with one of `statuses`, oldest first
+pub fn entries(db: &Connection, statuses: &[String]) -> Result<Vec<ReadlistEntry>> {
+    let documents = DocumentQuery::new()
+        .with_frontmatter(URL_KEY, Vec::new())
+        .with_frontmatter(STATUS_KEY, statuses.to_vec())
+        .order_by(SortByOption::Created, SortOrder::Ascending)
+        .order_by(SortByOption::Id, SortOrder::Ascending)
+        .execute(db)?;
+    Ok(documents
+        .into_iter()
+        .filter_map(ReadlistEntry::from_document)
+        .collect())
+}
+
+/// The note of the reading list pointing to `url`, whatever its status
+pub fn find_url(db: &Connection, url: &str) -> Result<Option<DocumentId>> {
+    let documents = DocumentQuery::new()
+        .with_frontmatter(URL_KEY, vec![url.to_owned()])
+        .with_frontmatter(STATUS_KEY, Vec::new())
+        .execute_summaries(db)?;
+    Ok(documents.into_iter().next().map(|d| d.id))
+}
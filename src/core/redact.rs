@@ -0,0 +1,100 @@
+//! Private notes and private parts of notes, left out of redacted exports.
+//!
+//! A note is private by `private: true` in its frontmatter. In any note, the
+//! text between a `%%private%%` and a `%%/private%%` fence is private, an
+//! unclosed fence running to the end of the note. Private content stays in
+//! the source and the index, exports with `--redact` strip it.
+
+use std::ops::Range;
+
+use serde_json::Value;
+
+pub const PRIVATE_KEY: &str = "private";
+
+const OPEN: &str = "%%private%%";
+const CLOSE: &str = "%%/private%%";
+
+/// Whether the note with the frontmatter `data` is private
+pub fn is_private(data: &Value) -> bool {
+    data.get(PRIVATE_KEY) == Some(&Value::Bool(true))
+}
+
+/// The private parts of `body`, fences included, in order. A closing fence
+/// without an opening one is a part of its own.
+pub fn private_ranges(body: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut offset = 0;
+    loop {
+        let open = body[offset..].find(OPEN).map(|i| offset + i);
+        let close = body[offset..].find(CLOSE).map(|i| offset + i);
+        match (open, close) {
+            (Some(open), close) if close.is_none_or(|close| open < close) => {
+                let end = body[open + OPEN.len()..]
+                    .find(CLOSE)
+                    .map_or(body.len(), |i| open + OPEN.len() + i + CLOSE.len());
+                ranges.push(open..end);
+                offset = end;
+            }
+            (_, Some(close)) => {
+                ranges.push(close..close + CLOSE.len());
+                offset = close + CLOSE.len();
+            }
+            (_, None) => return ranges,
+        }
+    }
+}
+
+/// `body` without its private parts
+pub fn redact(body: &str) -> String {
+    let mut redacted = String::with_capacity(body.len());
+    let mut offset = 0;
+    for range in private_ranges(body) {
+        redacted.push_str(&body[offset..range.start]);
+        offset = range.end;
+    }
+    redacted.push_str(&body[offset..]);
+    redacted
+}
+
+/// Whether `range` of `body` overlaps one of its private parts
+pub fn overlaps_private(body: &str, range: &Range<usize>) -> bool {
+    private_ranges(body)
+        .iter()
+        .any(|private| private.start < range.end && range.start < private.end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact() {
+        assert_eq!(redact("public"), "public");
+        assert_eq!(
+            redact("a %%private%%secret%%/private%% b %%private%%more%%/private%%"),
+            "a  b "
+        );
+        assert_eq!(
+            redact("a\n\n%%private%%\nsecret\n%%/private%%\n\nb\n"),
+            "a\n\n\n\nb\n"
+        );
+        // an unclosed fence runs to the end
+        assert_eq!(redact("a %%private%% secret"), "a ");
+        assert_eq!(redact("a %%/private%% b"), "a  b");
+        assert_eq!(redact("%% a comment %%"), "%% a comment %%");
+    }
+
+    #[test]
+    fn test_overlaps_private() {
+        let body = "a [[b]]\n\n%%private%%\nsecret [[b]]\n%%/private%%\n";
+        assert!(!overlaps_private(body, &(0..7)));
+        assert!(overlaps_private(body, &(22..34)));
+    }
+
+    #[test]
+    fn test_is_private() {
+        assert!(is_private(&serde_json::json!({ "private": true })));
+        assert!(!is_private(&serde_json::json!({ "private": false })));
+        assert!(!is_private(&serde_json::json!({})));
+    }
+}
@@ -0,0 +1,425 @@
+//! Renaming documents while keeping the links to them intact.
+//!
+//! The id of a document without an `id` in its frontmatter is derived from its
+//! path, so renaming the file changes the id. The targets of the links
+//! resolving to such a document are rewritten to the new id. Markdown links
+//! naming a path relative to the linking document are rewritten to the new
+//! path.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::eyre;
+use rusqlite::Connection;
+use sql_minifier::macros::minify_sql as sql;
+
+use crate::core::frontmatter::split_frontmatter;
+use crate::core::id::IdScheme;
+use crate::core::lock::Locks;
+use crate::core::parser::arena::Ast;
+use crate::core::parser::ast_nodes::{Node, Range};
+use crate::core::parser::{DocumentParser, Parse};
+use crate::core::types::document::{Document, DocumentId, DocumentPath};
+use crate::core::{ID_KEY, link_path, relative_link, resolve_target};
+use crate::result::Result;
+
+#[derive(Debug, Clone)]
+pub struct Rename {
+    pub document: Document,
+    pub to: PathBuf,
+    /// the id scheme of the group the document is renamed into
+    pub id_scheme: IdScheme,
+}
+
+impl Rename {
+    /// The id of the document after the rename
+    pub fn new_id(&self, root: &Path) -> DocumentId {
+        match self.document.data.get(ID_KEY) {
+            Some(_) => self.document.id.clone(),
+            None => self.id_scheme.path_to_id(root, &self.to),
+        }
+    }
+}
+
+/// Rename the files of the documents and rewrite the links to those whose id
+/// changes. Locked documents are neither renamed nor rewritten, a rename
+/// requiring either fails. Returns the paths of the documents whose links
+/// were rewritten.
+pub fn rename_documents(
+    db: &Connection,
+    root: &Path,
+    renames: &[Rename],
+    locks: &Locks,
+) -> Result<Vec<PathBuf>> {
+    for rename in renames {
+        if rename.to.exists() {
+            return Err(eyre!(
+                "can not rename {:?}, {:?} already exists",
+                rename.document.path.0,
+                rename.to
+            ));
+        }
+        if locks.is_file_locked(&rename.document.path.0)? {
+            return Err(eyre!(
+                "can not rename {:?}, it is locked",
+                rename.document.path.0
+            ));
+        }
+    }
+
+    // the links are resolved among every id, a link to another document
+    // whose id ends with a renamed one is left as it is
+    let ids: Vec<DocumentId> = db
+        .prepare(sql!("select id from document"))?
+        .query_map([], |r| r.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    let new_ids: HashMap<DocumentId, DocumentId> = renames
+        .iter()
+        .map(|rename| (rename.document.id.clone(), rename.new_id(root)))
+        .collect();
+
+    // the rewritten content of the documents linking to a renamed document
+    let mut rewritten: HashMap<PathBuf, String> = HashMap::new();
+    let mut query = db.prepare(sql!(
+        r#"
+        select distinct
+            d.path
+        from
+            document_link l
+            join document d on d.id = l.from_id
+        where
+            l.to_id = ?1
+    "#
+    ))?;
+    for rename in renames {
+        let paths = query
+            .query_map([&rename.document.id], |r| r.get::<_, DocumentPath>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        for DocumentPath(path) in paths {
+            if rewritten.contains_key(&path) {
+                continue;
+            }
+            let content = std::fs::read_to_string(&path)?;
+            if locks.is_locked(&path, &content) {
+                return Err(eyre!(
+                    "can not rename {:?}, the locked document {:?} links to it",
+                    rename.document.path.0,
+                    path
+                ));
+            }
+            // every target rewritten at once, a target is rewritten at most once
+            let content = rewrite_resolved_links(&content, &ids, &new_ids)?.unwrap_or(content);
+            rewritten.insert(path, content);
+        }
+    }
+
+    // the links naming a path relative to the linking document follow the
+    // files they point to, and the renamed documents
+    let moves: HashMap<PathBuf, PathBuf> = renames
+        .iter()
+        .map(|rename| (rename.document.path.0.clone(), rename.to.clone()))
+        .collect();
+    for rename in renames {
+        let path = &rename.document.path.0;
+        if !rewritten.contains_key(path) {
+            rewritten.insert(path.clone(), std::fs::read_to_string(path)?);
+        }
+    }
+    for (path, content) in &mut rewritten {
+        let to = moves.get(path).unwrap_or(path);
+        if let Some(relinked) = rewrite_link_paths(content, path, to, &moves)? {
+            *content = relinked;
+        }
+    }
+    // documents left as they were are not written
+    for (path, content) in std::mem::take(&mut rewritten) {
+        if std::fs::read_to_string(&path)? != content {
+            rewritten.insert(path, content);
+        }
+    }
+
+    // links are rewritten before the files are moved, a linking document may
+    // be renamed itself
+    let mut paths: Vec<PathBuf> = rewritten.keys().cloned().collect();
+    paths.sort();
+    for (path, content) in rewritten {
+        std::fs::write(path, content)?;
+    }
+    for rename in renames {
+        std::fs::rename(&rename.document.path.0, &rename.to)?;
+    }
+
+    Ok(paths)
+}
+
+/// Rewrite the markdown links of the document moved from `from` to `to`
+/// naming a path relative to it: those pointing to a file moved by `moves`
+/// point to where it moved, and, if the document moved to another directory,
+/// those pointing to an existing file are made relative to its new
+/// directory. Returns `None` if no link was changed.
+pub fn rewrite_link_paths(
+    document: &str,
+    from: &Path,
+    to: &Path,
+    moves: &HashMap<PathBuf, PathBuf>,
+) -> Result<Option<String>> {
+    edit_links(document, |link| {
+        if link.wiki {
+            return None;
+        }
+        let target = link_path(from, link.target)?;
+        let moved = moves.get(&target);
+        if moved.is_none() && (from.parent() == to.parent() || !target.exists()) {
+            return None;
+        }
+        Some(LinkEdit::Target {
+            len: link.target.len(),
+            replacement: relink(link.target, to, moved.unwrap_or(&target)),
+        })
+    })
+}
+
+/// The target of a markdown link in the document at `from` to the file at
+/// `path`, written as `target` is: without the `.md` extension if it was
+/// omitted, with spaces percent encoded if they were and with its `#anchor`
+fn relink(target: &str, from: &Path, path: &Path) -> String {
+    let end = target.find(['#', '?']).unwrap_or(target.len());
+    let (written, anchor) = target.split_at(end);
+    let directory = from.parent().unwrap_or(Path::new(""));
+    let mut relative = relative_link(directory, path);
+    if Path::new(written).extension().is_none()
+        && let Some(stripped) = relative.strip_suffix(".md")
+    {
+        relative.truncate(stripped.len());
+    }
+    if !written.contains(' ') {
+        relative = relative.replace(' ', "%20");
+    }
+    relative + anchor
+}
+
+/// Rewrite the targets of the links in `document` resolving, among `ids`, to
+/// a document with a new id in `new_ids`. A target is resolved to the longest
+/// id it ends with, and rewritten at most once, even if its new id ends with
+/// the old id of another document. Returns `None` if no link was changed.
+pub fn rewrite_resolved_links(
+    document: &str,
+    ids: &[DocumentId],
+    new_ids: &HashMap<DocumentId, DocumentId>,
+) -> Result<Option<String>> {
+    edit_links(document, |link| {
+        let old_id = resolve_target(ids, link.target)?;
+        let new_id = new_ids.get(old_id).filter(|new_id| *new_id != old_id)?;
+        Some(retarget(link, old_id, new_id))
+    })
+}
+
+/// A link in a document
+#[derive(Debug, Clone, Copy)]
+pub struct Link<'a> {
+    pub target: &'a str,
+    /// the text of the link, the target itself for wikilinks without one
+    pub title: &'a str,
+    pub wiki: bool,
+}
+
+/// A change to a link
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkEdit {
+    /// replace the last `len` bytes of the target
+    Target { len: usize, replacement: String },
+    /// replace the link by its text
+    Unlink,
+}
+
+/// The edit pointing `link`, resolving to `old_id`, to `new_id`. A target
+/// naming a file by a generated id and a title is replaced from the id on.
+pub fn retarget(link: &Link, old_id: &DocumentId, new_id: &DocumentId) -> LinkEdit {
+    let start = link.target.rfind(old_id.0.as_str()).unwrap_or(0);
+    LinkEdit::Target {
+        len: link.target.len() - start,
+        replacement: new_id.0.clone(),
+    }
+}
+
+/// Apply the edits `edit` returns for the links in `document`. Returns `None`
+/// if no link was changed.
+pub fn edit_links(
+    document: &str,
+    edit: impl Fn(&Link) -> Option<LinkEdit>,
+) -> Result<Option<String>> {
+    let body_offset = match split_frontmatter(document) {
+        Some((_, body)) => document.len() - body.len(),
+        None => 0,
+    };
+    let body = &document[body_offset..];
+    let ast = DocumentParser::new().parse(body)?;
+
+    let mut links = Vec::new();
+    find_links(&mut links, body, &ast);
+    // the replaced byte ranges of the body, and their replacements
+    let mut replacements: Vec<(usize, usize, String)> = links
+        .into_iter()
+        .filter_map(|(range, offset, link)| match edit(&link)? {
+            LinkEdit::Target { len, replacement } => {
+                let end = offset + link.target.len();
+                Some((end - len, end, replacement))
+            }
+            LinkEdit::Unlink => Some((range.start, range.end, link.title.to_owned())),
+        })
+        .collect();
+    if replacements.is_empty() {
+        return Ok(None);
+    }
+    replacements.sort();
+
+    let mut result = String::with_capacity(document.len());
+    let mut last = 0;
+    for (start, end, replacement) in replacements {
+        result.push_str(&document[last..body_offset + start]);
+        result.push_str(&replacement);
+        last = body_offset + end;
+    }
+    result.push_str(&document[last..]);
+    Ok(Some(result))
+}
+
+/// The ranges of the links in `ast`, the offsets of their targets and the
+/// links
+fn find_links<'a>(links: &mut Vec<(Range, usize, Link<'a>)>, body: &str, ast: &'a Ast) {
+    for id in ast.ids() {
+        match ast.node(id) {
+            // the target comes first in `[[target|title]]` and last in `[title](target)`
+            Node::WikiLink {
+                target,
+                range,
+                title,
+            } => {
+                if let Some(position) = body[range.clone()].find(target.as_str()) {
+                    let title = if title.is_empty() { target } else { title };
+                    // the range of a wikilink may end before its closing brackets
+                    let end = body[range.start..]
+                        .find("]]")
+                        .map_or(range.end, |end| range.start + end + 2);
+                    links.push((
+                        range.start..end,
+                        range.start + position,
+                        Link {
+                            target,
+                            title,
+                            wiki: true,
+                        },
+                    ));
+                }
+            }
+            Node::InlineLink {
+                target,
+                range,
+                title,
+            } => {
+                if let Some(position) = body[range.clone()].rfind(target.as_str()) {
+                    links.push((
+                        range.clone(),
+                        range.start + position,
+                        Link {
+                            target,
+                            title,
+                            wiki: false,
+                        },
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_link_targets() {
+        let id = |id: &str| DocumentId(id.to_owned());
+        let ids = [id("notes/foo"), id("bar"), id("baz")];
+        let rewrite = |document: &str, old: &str, new: &str| {
+            rewrite_resolved_links(document, &ids, &HashMap::from([(id(old), id(new))]))
+        };
+        let document = "---\ntitle: a\n---\n\nsee [[notes/foo|foo]], [foo](notes/foo) and [[bar]]\n\n- [[notes/foo]]\n";
+        assert_eq!(
+            rewrite(document, "notes/foo", "notes/2025-foo")
+                .unwrap()
+                .as_deref(),
+            Some(
+                "---\ntitle: a\n---\n\nsee [[notes/2025-foo|foo]], [foo](notes/2025-foo) and [[bar]]\n\n- [[notes/2025-foo]]\n"
+            )
+        );
+        assert_eq!(rewrite(document, "baz", "qux").unwrap(), None);
+        assert_eq!(
+            rewrite("| a | [[notes/foo]] |\n| - | - |\n", "notes/foo", "b")
+                .unwrap()
+                .as_deref(),
+            Some("| a | [[b]] |\n| - | - |\n")
+        );
+    }
+
+    #[test]
+    fn test_unlink() {
+        let document = "see [[b]], [[c|the c]] and [text](c)\n";
+        let unlinked = edit_links(document, |link| {
+            link.target.ends_with('b').then_some(LinkEdit::Unlink)
+        })
+        .unwrap();
+        assert_eq!(
+            unlinked.as_deref(),
+            Some("see b, [[c|the c]] and [text](c)\n")
+        );
+        let unlinked = edit_links(document, |link| {
+            (link.target == "c").then_some(LinkEdit::Unlink)
+        })
+        .unwrap();
+        assert_eq!(unlinked.as_deref(), Some("see [[b]], the c and text\n"));
+    }
+
+    #[test]
+    fn test_rewrite_resolved_links() {
+        let id = |id: &str| DocumentId(id.to_owned());
+        let ids = [id("x/b"), id("b"), id("202603010930")];
+        let new_ids = HashMap::from([
+            (id("b"), id("work/b")),
+            (id("x/b"), id("work/x/b")),
+            (id("202603010930"), id("work-202603010930")),
+        ]);
+        let document = "see [[x/b]], [[b|B]], [c](c) and [[202603010930-a-note]]\n";
+        assert_eq!(
+            rewrite_resolved_links(document, &ids, &new_ids)
+                .unwrap()
+                .as_deref(),
+            Some("see [[work/x/b]], [[work/b|B]], [c](c) and [[work-202603010930]]\n")
+        );
+    }
+
+    #[test]
+    fn test_rewrite_link_paths() {
+        let moves = HashMap::from([
+            (
+                PathBuf::from("/n/a/index.md"),
+                PathBuf::from("/n/b/index.md"),
+            ),
+            (PathBuf::from("/n/a/x.md"), PathBuf::from("/n/c/x y.md")),
+        ]);
+        let document = "see [x](x.md#top), [x](./x), [[x]] and [y](y.md)\n";
+        let rewritten = |from: &str, to: &str| {
+            rewrite_link_paths(document, Path::new(from), Path::new(to), &moves).unwrap()
+        };
+        assert_eq!(
+            rewritten("/n/a/index.md", "/n/b/index.md").as_deref(),
+            Some("see [x](../c/x%20y.md#top), [x](../c/x%20y), [[x]] and [y](y.md)\n")
+        );
+        assert_eq!(
+            rewritten("/n/a/other.md", "/n/a/other.md").as_deref(),
+            Some("see [x](../c/x%20y.md#top), [x](../c/x%20y), [[x]] and [y](y.md)\n")
+        );
+        assert_eq!(rewritten("/n/c/other.md", "/n/c/other.md"), None);
+    }
+}
@@ -0,0 +1,252 @@
+//! Summaries of the activity in a period: notes created and modified, the
+//! tasks that were completed and the action items of the notes, by owner.
+
+use jiff::civil::Date;
+use jiff::tz::TimeZone;
+use jiff::{Timestamp, Zoned};
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+use sql_minifier::macros::minify_sql as sql;
+
+use crate::core::date_parser::TimeRange;
+use crate::core::journal::Period;
+use crate::core::mentions::{Person, resolve};
+use crate::core::query::{DocumentQuery, SortByOption, SortOrder};
+use crate::core::types::document::{Document, DocumentId};
+use crate::core::types::task::TaskStatus;
+use crate::result::Result;
+
+pub const DEFAULT_ROLLUP_TEMPLATE: &str = r#"---
+id: {{ id }}
+title: {{ title }}
+---
+
+# {{ title }}
+
+{% if period %}{{ period }} rollup{% else %}Rollup{% endif %} from {{ start }} to {{ end }}.
+
+## Completed tasks
+{% for task in completed_tasks %}
+- [x] {{ task.content }} ([[{{ task.document_id }}]])
+{%- endfor %}
+
+{% if action_items %}
+## Action items
+{% for group in action_items %}
+### {% if group.owner %}@{{ group.owner }}{% else %}Unassigned{% endif %}
+{% for task in group.tasks %}
+- [{% if task.status == "done" %}x{% else %} {% endif %}] {{ task.content }} ([[{{ task.document_id }}]])
+{%- endfor %}
+{% endfor %}{% endif %}
+## Created notes
+{% for document in created %}
+- [[{{ document.id }}]] {{ document.title }}
+{%- endfor %}
+
+## Modified notes
+{% for document in modified %}
+- [[{{ document.id }}]] {{ document.title }}
+{%- endfor %}
+"#;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollupDocument {
+    pub id: DocumentId,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollupTask {
+    pub document_id: DocumentId,
+    pub document_title: String,
+    pub content: String,
+    pub status: TaskStatus,
+    /// the name the task's owner is mentioned by
+    pub owner: Option<String>,
+}
+
+/// The action items of one owner
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollupOwner {
+    /// the handle of the owner if they have a note, the name they are first
+    /// mentioned by otherwise, `None` for the unowned action items
+    pub owner: Option<String>,
+    /// the note of the owner
+    pub person: Option<DocumentId>,
+    pub tasks: Vec<RollupTask>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rollup {
+    /// the period summarized, `None` for an arbitrary range
+    pub period: Option<Period>,
+    pub start: Date,
+    pub end: Date,
+    pub created: Vec<RollupDocument>,
+    /// documents modified but not created in the period
+    pub modified: Vec<RollupDocument>,
+    /// checked tasks of documents modified in the period. Tasks carry no
+    /// completion time, so this is the closest approximation.
+    pub completed_tasks: Vec<RollupTask>,
+    /// the action items, not cancelled, of documents modified in the period
+    /// by owner, the unowned ones last
+    pub action_items: Vec<RollupOwner>,
+}
+
+impl From<Document> for RollupDocument {
+    fn from(d: Document) -> Self {
+        Self {
+            id: d.id,
+            title: d.title,
+        }
+    }
+}
+
+fn start_of_day(date: Date, tz: &TimeZone) -> Result<Timestamp> {
+    let zoned: Zoned = date.to_zoned(tz.clone())?;
+    Ok(zoned.timestamp())
+}
+
+impl Rollup {
+    /// Collect the activity of the period starting at `start`, ignoring the
+    /// documents in `exclude` (typically the rollup note itself). The owners
+    /// of action items are resolved among `people`.
+    pub fn load(
+        db: &Connection,
+        period: Period,
+        start: Date,
+        tz: &TimeZone,
+        exclude: &[DocumentId],
+        people: &[Person],
+    ) -> Result<Rollup> {
+        let range = TimeRange {
+            start: start_of_day(start, tz)?,
+            end: start_of_day(period.next(start), tz)?,
+        };
+        let mut rollup = Rollup::load_range(db, range, tz, exclude, people)?;
+        rollup.period = Some(period);
+        Ok(rollup)
+    }
+
+    /// Collect the activity within `range`, see [`Rollup::load`]
+    pub fn load_range(
+        db: &Connection,
+        range: TimeRange,
+        tz: &TimeZone,
+        exclude: &[DocumentId],
+        people: &[Person],
+    ) -> Result<Rollup> {
+        let (start, end) = range.dates(tz);
+        let exclude: Vec<String> = exclude.iter().map(|id| id.0.clone()).collect();
+
+        let created: Vec<RollupDocument> = DocumentQuery::new()
+            .created(range)
+            .exclude_ids(exclude.clone())
+            .order_by(SortByOption::Created, SortOrder::Ascending)
+            .execute(db)?
+            .into_iter()
+            .map(From::from)
+            .collect();
+
+        let modified_documents = DocumentQuery::new()
+            .modified(range)
+            .exclude_ids(exclude)
+            .order_by(SortByOption::Modified, SortOrder::Ascending)
+            .execute(db)?;
+
+        let mut completed_tasks = Vec::new();
+        let mut action_items: Vec<RollupTask> = Vec::new();
+        {
+            let mut query = db.prepare(sql!(
+                r#"
+                select
+                    content,
+                    status,
+                    owner,
+                    checked,
+                    action_item
+                from
+                    document_task
+                where
+                    document_id = ?1 and (checked = 1 or action_item = 1)
+                order by
+                    range_start
+            "#
+            ))?;
+            for d in &modified_documents {
+                let rows = query.query_map(params![d.id], |r| {
+                    let task = RollupTask {
+                        document_id: d.id.clone(),
+                        document_title: d.title.clone(),
+                        content: r.get(0)?,
+                        status: r.get(1)?,
+                        owner: r.get(2)?,
+                    };
+                    Ok((task, r.get::<_, bool>(3)?, r.get::<_, bool>(4)?))
+                })?;
+                for row in rows {
+                    let (task, checked, action_item) = row?;
+                    if action_item && task.status != TaskStatus::Cancelled {
+                        action_items.push(task.clone());
+                    }
+                    if checked {
+                        completed_tasks.push(task);
+                    }
+                }
+            }
+        }
+        let action_items = by_owner(action_items, people);
+
+        let modified = modified_documents
+            .into_iter()
+            .filter(|d| !created.iter().any(|c| c.id == d.id))
+            .map(From::from)
+            .collect();
+
+        Ok(Rollup {
+            period: None,
+            start,
+            end,
+            created,
+            modified,
+            completed_tasks,
+            action_items,
+        })
+    }
+}
+
+/// `tasks` grouped by owner, the person they name or the name as written
+/// ignoring case, the unowned tasks last
+fn by_owner(tasks: Vec<RollupTask>, people: &[Person]) -> Vec<RollupOwner> {
+    let mut groups: Vec<RollupOwner> = Vec::new();
+    let mut unowned = Vec::new();
+    for task in tasks {
+        let Some(name) = &task.owner else {
+            unowned.push(task);
+            continue;
+        };
+        let person = resolve(people, name);
+        let owner = person.map_or(name, |p| &p.handle);
+        match groups.iter_mut().find(|g| {
+            g.owner
+                .as_ref()
+                .is_some_and(|o| o.eq_ignore_ascii_case(owner))
+        }) {
+            Some(group) => group.tasks.push(task),
+            None => groups.push(RollupOwner {
+                owner: Some(owner.to_owned()),
+                person: person.map(|p| p.id.clone()),
+                tasks: vec![task],
+            }),
+        }
+    }
+    groups.sort_by_key(|g| g.owner.as_ref().map(|o| o.to_lowercase()));
+    if !unowned.is_empty() {
+        groups.push(RollupOwner {
+            owner: None,
+            person: None,
+            tasks: unowned,
+        });
+    }
+    groups
+}
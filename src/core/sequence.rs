@@ -0,0 +1,192 @@
+//! Folgezettel, notes ordered by the structure of their ids.
+//!
+//! An id of alternating numbers and lowercase letters, optionally after a
+//! path, is read as a position in a sequence: `21/3a1` continues `21/3a`,
+//! which branches off `21/3`. The notes sharing the path of an id are read
+//! depth first, `3`, `3a`, `3a1`, `3b`, `4`, which orders timestamp ids by
+//! time and sequence ids by number.
+
+use std::cmp::Ordering;
+
+use serde::Serialize;
+
+use crate::core::types::document::{DocumentId, DocumentSummary};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Number(u64),
+    Letters(String),
+}
+
+impl Ord for Segment {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Segment::Number(a), Segment::Number(b)) => a.cmp(b),
+            // `z` is followed by `aa`
+            (Segment::Letters(a), Segment::Letters(b)) => {
+                a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+            }
+            (Segment::Number(_), Segment::Letters(_)) => Ordering::Less,
+            (Segment::Letters(_), Segment::Number(_)) => Ordering::Greater,
+        }
+    }
+}
+
+impl PartialOrd for Segment {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The position of a note in a sequence, read from its id
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SequenceId {
+    path: String,
+    segments: Vec<Segment>,
+}
+
+impl SequenceId {
+    /// The position `id` names, if it names one
+    pub fn parse(id: &str) -> Option<Self> {
+        let (path, name) = match id.rfind('/') {
+            Some(i) => id.split_at(i + 1),
+            None => ("", id),
+        };
+        if !name.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        let mut segments: Vec<Segment> = Vec::new();
+        for c in name.chars() {
+            match (c, segments.last_mut()) {
+                ('0'..='9', Some(Segment::Number(n))) => {
+                    *n = n.checked_mul(10)?.checked_add(c.to_digit(10)? as u64)?
+                }
+                ('0'..='9', _) => segments.push(Segment::Number(c.to_digit(10)? as u64)),
+                ('a'..='z', Some(Segment::Letters(letters))) => letters.push(c),
+                ('a'..='z', _) => segments.push(Segment::Letters(c.to_string())),
+                _ => return None,
+            }
+        }
+        Some(Self {
+            path: path.to_owned(),
+            segments,
+        })
+    }
+
+    /// Whether the note branches off `parent` directly
+    pub fn is_child_of(&self, parent: &SequenceId) -> bool {
+        self.path == parent.path
+            && self.segments.len() == parent.segments.len() + 1
+            && self.segments.starts_with(&parent.segments)
+    }
+}
+
+/// The neighbours of a note in its sequence
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Neighbours {
+    /// the note read before it
+    pub prev: Option<DocumentSummary>,
+    /// the note read after it
+    pub next: Option<DocumentSummary>,
+    /// the notes branching off it, in order
+    pub children: Vec<DocumentSummary>,
+}
+
+/// The neighbours of the note `id` among `documents`, if its id names a
+/// position in a sequence
+pub fn neighbours(documents: &[DocumentSummary], id: &DocumentId) -> Option<Neighbours> {
+    let position = SequenceId::parse(&id.0)?;
+    let mut sequence: Vec<(SequenceId, &DocumentSummary)> = documents
+        .iter()
+        .filter_map(|d| Some((SequenceId::parse(&d.id.0)?, d)))
+        .filter(|(p, _)| p.path == position.path)
+        .collect();
+    sequence.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let index = sequence.iter().position(|(_, d)| d.id == *id);
+    let (prev, next) = match index {
+        Some(i) => (
+            i.checked_sub(1).map(|i| sequence[i].1),
+            sequence.get(i + 1).map(|(_, d)| *d),
+        ),
+        // a note that is not indexed yet sits between its neighbours
+        None => (
+            sequence
+                .iter()
+                .rev()
+                .find(|(p, _)| *p < position)
+                .map(|(_, d)| *d),
+            sequence
+                .iter()
+                .find(|(p, _)| *p > position)
+                .map(|(_, d)| *d),
+        ),
+    };
+    Some(Neighbours {
+        prev: prev.cloned(),
+        next: next.cloned(),
+        children: sequence
+            .iter()
+            .filter(|(p, _)| p.is_child_of(&position))
+            .map(|(_, d)| (*d).clone())
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use jiff::Timestamp;
+
+    use super::*;
+    use crate::core::types::document::{CreatedTimestamp, DocumentPath, ModifiedTimestamp};
+
+    fn summary(id: &str) -> DocumentSummary {
+        DocumentSummary {
+            id: DocumentId(id.to_owned()),
+            title: id.to_owned(),
+            path: DocumentPath(PathBuf::from(format!("{id}.md"))),
+            hash: 0,
+            modified: ModifiedTimestamp(Timestamp::UNIX_EPOCH),
+            created: CreatedTimestamp(Timestamp::UNIX_EPOCH),
+        }
+    }
+
+    fn ids(documents: &[DocumentSummary]) -> Vec<&str> {
+        documents.iter().map(|d| d.id.0.as_str()).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert!(SequenceId::parse("21/3a1").is_some());
+        assert!(SequenceId::parse("202603010930").is_some());
+        assert!(SequenceId::parse("a-note").is_none());
+        assert!(SequenceId::parse("3-agenda").is_none());
+        assert!(SequenceId::parse("01J0000000000000000000000Z").is_none());
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let documents: Vec<_> = ["1", "10", "1a", "1b", "1a1", "2", "9", "z/1", "notes"]
+            .into_iter()
+            .map(summary)
+            .collect();
+        let get = |id: &str| neighbours(&documents, &DocumentId(id.to_owned())).unwrap();
+
+        let n = get("1a");
+        assert_eq!(n.prev.unwrap().id.0, "1");
+        assert_eq!(n.next.unwrap().id.0, "1a1");
+        assert_eq!(ids(&n.children), vec!["1a1"]);
+
+        assert_eq!(ids(&get("1").children), vec!["1a", "1b"]);
+        assert_eq!(get("1b").next.unwrap().id.0, "2");
+        // numbers are compared by value
+        assert_eq!(get("9").next.unwrap().id.0, "10");
+        assert!(get("10").next.is_none());
+        assert!(get("z/1").prev.is_none());
+        // an unindexed note
+        assert_eq!(get("1c").prev.unwrap().id.0, "1b");
+        assert!(neighbours(&documents, &DocumentId("notes".to_owned())).is_none());
+    }
+}
@@ -35,3 +35,65 @@ fn slugify_impl(s: &str) -> String {
     slug.shrink_to_fit();
     slug
 }
+
+/// The anchor of a heading with the text `content`, as github writes them:
+/// lowercased, spaces turned into `-` and punctuation other than `-` and `_`
+/// left out
+pub fn heading_anchor(content: &str) -> String {
+    content
+        .trim()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            '-' | '_' => Some(c),
+            c if c.is_alphanumeric() => Some(c),
+            _ => None,
+        })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// The anchors of the headings of a document, in document order. Headings
+/// with an explicit `{#id}` are anchored by it, others by the slug of their
+/// text, numbered from `-1` when an earlier heading has the same one.
+#[derive(Debug, Default)]
+pub struct HeadingAnchors {
+    seen: std::collections::HashMap<String, usize>,
+}
+
+impl HeadingAnchors {
+    pub fn anchor(&mut self, id: Option<&str>, content: &str) -> String {
+        if let Some(id) = id {
+            self.seen.entry(id.to_owned()).or_default();
+            return id.to_owned();
+        }
+        let anchor = heading_anchor(content);
+        match self.seen.get_mut(&anchor) {
+            Some(count) => {
+                *count += 1;
+                format!("{anchor}-{count}")
+            }
+            None => {
+                self.seen.insert(anchor.clone(), 0);
+                anchor
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heading_anchors() {
+        assert_eq!(heading_anchor("Hello, World! v2.0"), "hello-world-v20");
+        assert_eq!(heading_anchor("snake_case and-dash"), "snake_case-and-dash");
+
+        let mut anchors = HeadingAnchors::default();
+        assert_eq!(anchors.anchor(None, "Notes"), "notes");
+        assert_eq!(anchors.anchor(Some("custom"), "Notes"), "custom");
+        assert_eq!(anchors.anchor(None, "Notes"), "notes-1");
+        assert_eq!(anchors.anchor(None, "Custom"), "custom-1");
+    }
+}
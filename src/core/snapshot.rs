@@ -0,0 +1,139 @@
+//! Snapshots of expensive aggregate queries.
+//!
+//! Every index run that changes the collection increments the index
+//! generation. A snapshot records the generation its result was computed at
+//! and is reused until the generation moves on, at which point the result is
+//! computed again and the snapshot replaced.
+
+use jiff::Timestamp;
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use sql_minifier::macros::minify_sql as sql;
+
+use crate::result::Result;
+
+/// The current generation of the index
+pub fn generation(db: &Connection) -> Result<i64> {
+    Ok(db.query_row(
+        sql!("select generation from index_generation where id = 0"),
+        [],
+        |r| r.get(0),
+    )?)
+}
+
+/// The result of the query `key`, from its snapshot if it was computed at
+/// the current generation, otherwise from `compute`. Unless `use_cache` is
+/// false, in which case the result is always computed, the snapshot being
+/// replaced either way. A db that can not be written to is only read.
+pub fn cached<T, F>(db: &Connection, key: &str, use_cache: bool, compute: F) -> Result<T>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce(&Connection) -> Result<T>,
+{
+    let generation = generation(db)?;
+    if use_cache && let Some(value) = read(db, key, generation)? {
+        log::debug!(
+            "reusing the snapshot of {} at generation {}",
+            key,
+            generation
+        );
+        return Ok(value);
+    }
+
+    let value = compute(db)?;
+    if let Err(e) = write(db, key, generation, &value) {
+        log::debug!("could not store the snapshot of {}: {}", key, e);
+    }
+    Ok(value)
+}
+
+fn read<T: DeserializeOwned>(db: &Connection, key: &str, generation: i64) -> Result<Option<T>> {
+    let data: Option<serde_json::Value> = db
+        .query_row(
+            sql!("select json(data) from query_snapshot where key = ?1 and generation = ?2"),
+            params![key, generation],
+            |r| r.get(0),
+        )
+        .optional()?;
+    match data.map(serde_json::from_value) {
+        Some(Ok(value)) => Ok(Some(value)),
+        // written by a zet with a different notion of the result
+        Some(Err(e)) => {
+            log::debug!("discarding the snapshot of {}: {}", key, e);
+            Ok(None)
+        }
+        None => Ok(None),
+    }
+}
+
+fn write<T: Serialize>(db: &Connection, key: &str, generation: i64, value: &T) -> Result<()> {
+    db.execute(
+        sql!(
+            r#"
+            insert or replace into query_snapshot (
+                key,
+                generation,
+                created,
+                data
+            ) values (
+                ?1,
+                ?2,
+                ?3,
+                jsonb(?4)
+            )
+            "#
+        ),
+        params![
+            key,
+            generation,
+            Timestamp::now().to_string(),
+            serde_json::to_value(value)?
+        ],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::db::{DB, DbInsert};
+    use crate::core::types::index_run::{IndexRun, NewIndexRun};
+
+    fn index_run(db: &mut DB, changes: usize) {
+        let now = Timestamp::now();
+        IndexRun::insert(
+            db,
+            &[NewIndexRun {
+                started: now,
+                finished: now,
+                changes,
+            }],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_cached() {
+        let mut db = DB::open(":memory:").unwrap();
+        let mut runs = 0;
+        let mut query = |db: &Connection, use_cache: bool| -> usize {
+            cached(db, "runs", use_cache, |_| {
+                runs += 1;
+                Ok(runs)
+            })
+            .unwrap()
+        };
+
+        assert_eq!(query(&db, true), 1);
+        assert_eq!(query(&db, true), 1);
+        // an index run without changes keeps the generation
+        index_run(&mut db, 0);
+        assert_eq!(query(&db, true), 1);
+        index_run(&mut db, 3);
+        assert_eq!(generation(&db).unwrap(), 1);
+        assert_eq!(query(&db, true), 2);
+        assert_eq!(query(&db, false), 3);
+        assert_eq!(query(&db, true), 3);
+    }
+}
@@ -0,0 +1,128 @@
+//! User defined snippets, triggers expanding to a template body. They are read
+//! from `.zet/snippets.toml`, either as a body alone or with a description:
+//!
+//! ```toml
+//! sig = "-- {{ author }}"
+//!
+//! [meeting]
+//! description = "meeting notes"
+//! body = """
+//! ## Meeting {{ date }} {{ time }}
+//! """
+//! ```
+//!
+//! Bodies are rendered by the template engine, with the `id` and `title` of
+//! the document the snippet is expanded in, the current `date` and `time`, and
+//! any extra variables given.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::eyre;
+use jiff::Zoned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::core::collection_config_dir;
+use crate::core::template_engine::render_template;
+use crate::result::Result;
+
+pub const SNIPPETS_FILE: &str = "snippets.toml";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Snippet {
+    pub trigger: String,
+    pub description: Option<String>,
+    pub body: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum SnippetDefinition {
+    Body(String),
+    Full {
+        body: String,
+        description: Option<String>,
+    },
+}
+
+pub fn snippets_file(root: &Path) -> PathBuf {
+    collection_config_dir(root).join(SNIPPETS_FILE)
+}
+
+/// The snippets of the collection, sorted by trigger. A collection without a
+/// snippets file has none.
+pub fn load_snippets(root: &Path) -> Result<Vec<Snippet>> {
+    let path = snippets_file(root);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path)?;
+    parse_snippets(&content).map_err(|e| eyre!("invalid snippets file {:?}: {}", path, e))
+}
+
+fn parse_snippets(content: &str) -> Result<Vec<Snippet>> {
+    let definitions: BTreeMap<String, SnippetDefinition> = toml::from_str(content)?;
+    Ok(definitions
+        .into_iter()
+        .map(|(trigger, definition)| match definition {
+            SnippetDefinition::Body(body) => Snippet {
+                trigger,
+                description: None,
+                body,
+            },
+            SnippetDefinition::Full { body, description } => Snippet {
+                trigger,
+                description,
+                body,
+            },
+        })
+        .collect())
+}
+
+impl Snippet {
+    /// Render the body of the snippet in the document with `id` and `title`
+    /// at the time `now`
+    pub fn expand(
+        &self,
+        id: &str,
+        title: &str,
+        now: &Zoned,
+        variables: &HashMap<String, serde_json::Value>,
+    ) -> Result<String> {
+        let date = now.strftime("%Y-%m-%d").to_string();
+        let mut extra =
+            HashMap::from([("time".to_owned(), json!(now.strftime("%H:%M").to_string()))]);
+        extra.extend(variables.clone());
+        render_template(&self.body, id, title, &date, "", &extra)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_snippets() {
+        let snippets = parse_snippets(
+            "sig = \"-- {{ author }}\"\n\n[meeting]\ndescription = \"meeting notes\"\nbody = \"## Meeting {{ date }} {{ time }}\"\n",
+        )
+        .unwrap();
+        assert_eq!(snippets.len(), 2);
+        assert_eq!(snippets[0].trigger, "meeting");
+        assert_eq!(snippets[0].description.as_deref(), Some("meeting notes"));
+        assert_eq!(snippets[1].trigger, "sig");
+        assert_eq!(snippets[1].description, None);
+
+        let now: Zoned = "2025-03-04T09:30:00[UTC]".parse().unwrap();
+        let variables = HashMap::from([("author".to_owned(), json!("kit"))]);
+        assert_eq!(
+            snippets[0].expand("a", "A", &now, &variables).unwrap(),
+            "## Meeting 2025-03-04 09:30"
+        );
+        assert_eq!(
+            snippets[1].expand("a", "A", &now, &variables).unwrap(),
+            "-- kit"
+        );
+    }
+}
@@ -0,0 +1,411 @@
+//! Moving a directory of a collection into a new collection.
+//!
+//! The notes and assets in the directory keep their path relative to it. The
+//! new collection gets the configuration of the old one, with only the groups
+//! and periodic notes kept whose directories are in the split directory, and
+//! the templates they use. Links crossing the boundary between the two
+//! collections either point to a stub note, written in place of the linked
+//! note, or are replaced by their text.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use color_eyre::eyre::eyre;
+use rusqlite::Connection;
+use serde_json::Value;
+use sql_minifier::macros::minify_sql as sql;
+
+use crate::config::Config;
+use crate::core::assets::{asset_paths, collect_references};
+use crate::core::db::DbList;
+use crate::core::frontmatter::set_frontmatter_value;
+use crate::core::parser::FrontMatterFormat;
+use crate::core::rename::{LinkEdit, edit_links, retarget};
+use crate::core::template_engine::template_path;
+use crate::core::types::document::{Document, DocumentId};
+use crate::core::{ID_KEY, collection_config_file, path_to_slash, relative_path, resolve_target};
+use crate::result::Result;
+
+/// What becomes of the links between the notes that are moved and those that
+/// stay
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CrossLinks {
+    /// keep the links, pointing them to stub notes referring to the other
+    /// collection
+    #[default]
+    Stub,
+    /// replace the links by their text
+    Text,
+}
+
+/// A note moved to the new collection
+#[derive(Debug, Clone)]
+pub struct SplitNote {
+    pub document: Document,
+    pub to: PathBuf,
+    pub new_id: DocumentId,
+}
+
+/// A stub note standing in for a note of the other collection
+#[derive(Debug, Clone)]
+pub struct Stub {
+    pub path: PathBuf,
+    /// the note it stands in for
+    pub document: Document,
+    /// the root of the collection holding the note
+    pub collection: PathBuf,
+    /// the path of the note in that collection
+    pub note: PathBuf,
+}
+
+#[derive(Debug, Clone)]
+pub struct SplitPlan {
+    pub notes: Vec<SplitNote>,
+    /// the assets moved, or copied when notes that stay refer to them, and
+    /// whether they are copied
+    pub assets: Vec<(PathBuf, PathBuf, bool)>,
+    /// stubs of the moved notes in the old collection
+    pub old_stubs: Vec<Stub>,
+    /// stubs of the notes that stay in the new collection
+    pub new_stubs: Vec<Stub>,
+    /// the configuration file of the new collection
+    pub config: String,
+    /// the templates copied to the new collection
+    pub templates: Vec<(PathBuf, PathBuf)>,
+    /// the ids of the old collection, which its links resolve to
+    ids: Vec<DocumentId>,
+    /// the notes that stay whose links from the moved notes are replaced by
+    /// their text
+    unlinked_out: HashSet<DocumentId>,
+    /// the notes that stay whose links to the moved notes are replaced by
+    /// their text
+    unlinked_in: Vec<PathBuf>,
+    format: FrontMatterFormat,
+    new_root: PathBuf,
+}
+
+/// How the notes and assets in `directory` of the collection at `root` move
+/// into a new collection at `new_root`
+pub fn plan(
+    root: &Path,
+    config: &Config,
+    db: &Connection,
+    directory: &Path,
+    new_root: &Path,
+    cross_links: CrossLinks,
+) -> Result<SplitPlan> {
+    let prefix = relative_str(root, directory)?;
+    let (new_config, template_names) = split_config(root, &prefix)?;
+    let templates = template_names
+        .iter()
+        .map(|name| (template_path(root, name), template_path(new_root, name)))
+        .filter(|(from, _)| from.is_file())
+        .collect();
+
+    let mut documents = Document::list(db)?;
+    let ids: Vec<DocumentId> = documents.iter().map(|d| d.id.clone()).collect();
+    documents.sort_by(|a, b| a.path.0.cmp(&b.path.0));
+    let (moved, staying): (Vec<Document>, Vec<Document>) = documents
+        .into_iter()
+        .partition(|d| d.path.0.starts_with(directory));
+    if moved.is_empty() {
+        return Err(eyre!("{:?} contains no notes", directory));
+    }
+
+    // the id schemes of the new collection are those of its configuration
+    let parsed_config: Config = toml::from_str(&new_config)?;
+    let scheme_config = |path: &Path| parsed_config.id_scheme(new_root, path);
+    let notes: Vec<SplitNote> = moved
+        .into_iter()
+        .map(|document| {
+            let to = new_root.join(document.path.0.strip_prefix(directory)?);
+            let new_id = match document.data.get(ID_KEY) {
+                Some(_) => document.id.clone(),
+                None => scheme_config(&to).path_to_id(new_root, &to),
+            };
+            Ok(SplitNote {
+                document,
+                to,
+                new_id,
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    // links crossing the boundary, from the notes moved and to them
+    let moved_ids: HashMap<&DocumentId, &SplitNote> =
+        notes.iter().map(|n| (&n.document.id, n)).collect();
+    let staying_ids: HashMap<&DocumentId, &Document> = staying.iter().map(|d| (&d.id, d)).collect();
+    let mut outgoing = BTreeSet::new();
+    let mut incoming = BTreeSet::new();
+    let mut linking_in = BTreeSet::new();
+    let mut query = db.prepare(sql!(
+        "select distinct from_id, to_id from document_link where to_id is not null"
+    ))?;
+    let links = query
+        .query_map([], |r| {
+            Ok((r.get::<_, DocumentId>(0)?, r.get::<_, DocumentId>(1)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for (from, to) in links {
+        match (moved_ids.contains_key(&from), moved_ids.contains_key(&to)) {
+            (true, false) => {
+                outgoing.insert(to);
+            }
+            (false, true) => {
+                incoming.insert(to);
+                linking_in.extend(staying_ids.get(&from).map(|d| d.path.0.clone()));
+            }
+            _ => {}
+        }
+    }
+
+    let mut old_stubs = Vec::new();
+    let mut new_stubs = Vec::new();
+    let mut unlinked_out = HashSet::new();
+    let mut unlinked_in = Vec::new();
+    if cross_links == CrossLinks::Text {
+        unlinked_out.extend(outgoing);
+        unlinked_in.extend(linking_in);
+    } else {
+        // a moved note is replaced by its stub, keeping its id
+        for id in incoming {
+            let note = moved_ids[&id];
+            old_stubs.push(Stub {
+                path: note.document.path.0.clone(),
+                document: note.document.clone(),
+                collection: new_root.to_owned(),
+                note: note.to.strip_prefix(new_root)?.to_owned(),
+            });
+        }
+        // a note that stays is stubbed under its id in the new collection,
+        // unless a moved note takes the path or the id
+        let taken_paths: HashSet<&Path> = notes.iter().map(|n| n.to.as_path()).collect();
+        let taken_ids: HashSet<&DocumentId> = notes.iter().map(|n| &n.new_id).collect();
+        for id in outgoing {
+            let Some(document) = staying_ids.get(&id) else {
+                continue;
+            };
+            let relative = document.path.0.strip_prefix(root)?;
+            let path = new_root.join(relative);
+            let stub_id = match document.data.get(ID_KEY) {
+                Some(_) => document.id.clone(),
+                None => scheme_config(&path).path_to_id(new_root, &path),
+            };
+            if stub_id != id || taken_paths.contains(path.as_path()) || taken_ids.contains(&id) {
+                // the links to it from the moved notes are replaced by their
+                // text instead
+                unlinked_out.insert(id);
+                continue;
+            }
+            new_stubs.push(Stub {
+                path,
+                document: (*document).clone(),
+                collection: root.to_owned(),
+                note: relative.to_owned(),
+            });
+        }
+    }
+
+    let in_directory: Vec<PathBuf> = asset_paths(root, &config.assets)
+        .into_iter()
+        .filter(|path| path.starts_with(directory))
+        .collect();
+    let shared: HashSet<PathBuf> = collect_references(root, &in_directory)?
+        .into_iter()
+        .filter(|r| !r.document.starts_with(directory))
+        .map(|r| r.asset)
+        .collect();
+    let assets = in_directory
+        .into_iter()
+        .map(|from| {
+            let to = new_root.join(from.strip_prefix(directory)?);
+            let copied = shared.contains(&from);
+            Ok((from, to, copied))
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(SplitPlan {
+        notes,
+        assets,
+        old_stubs,
+        new_stubs,
+        config: new_config,
+        templates,
+        ids,
+        unlinked_out,
+        unlinked_in,
+        format: config.front_matter_format,
+        new_root: new_root.to_owned(),
+    })
+}
+
+impl SplitPlan {
+    /// Write the new collection, its notes, assets, configuration and stubs,
+    /// remove the moved notes and assets from the old collection and write
+    /// its stubs. The new collection is not indexed.
+    pub fn apply(&self) -> Result<()> {
+        let config_file = collection_config_file(&self.new_root);
+        std::fs::create_dir_all(config_file.parent().expect("config file is in .zet"))?;
+        std::fs::write(&config_file, &self.config)?;
+        for (from, to) in &self.templates {
+            write_file(to, &std::fs::read(from)?)?;
+        }
+
+        // the links of the moved notes to each other follow them, those to
+        // notes that stay keep their target unless they are unlinked
+        let new_ids: HashMap<&DocumentId, &DocumentId> = self
+            .notes
+            .iter()
+            .map(|n| (&n.document.id, &n.new_id))
+            .collect();
+        for note in &self.notes {
+            let content = std::fs::read_to_string(&note.document.path.0)?;
+            let edited = edit_links(&content, |link| {
+                let old_id = resolve_target(&self.ids, link.target)?;
+                match new_ids.get(old_id) {
+                    Some(new_id) if *new_id != old_id => Some(retarget(link, old_id, new_id)),
+                    Some(_) => None,
+                    None => self
+                        .unlinked_out
+                        .contains(old_id)
+                        .then_some(LinkEdit::Unlink),
+                }
+            })?;
+            write_file(&note.to, edited.as_deref().unwrap_or(&content).as_bytes())?;
+        }
+        for (from, to, copied) in &self.assets {
+            write_file(to, &std::fs::read(from)?)?;
+            if !copied {
+                std::fs::remove_file(from)?;
+            }
+        }
+        for note in &self.notes {
+            std::fs::remove_file(&note.document.path.0)?;
+        }
+
+        let moved: HashSet<&DocumentId> = new_ids.keys().copied().collect();
+        for path in &self.unlinked_in {
+            let content = std::fs::read_to_string(path)?;
+            let edited = edit_links(&content, |link| {
+                let old_id = resolve_target(&self.ids, link.target)?;
+                moved.contains(old_id).then_some(LinkEdit::Unlink)
+            })?;
+            if let Some(edited) = edited {
+                std::fs::write(path, edited)?;
+            }
+        }
+
+        for stub in self.old_stubs.iter().chain(&self.new_stubs) {
+            write_file(&stub.path, stub_content(stub, self.format)?.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+/// A note titled like the note `stub` stands in for, referring to it
+fn stub_content(stub: &Stub, format: FrontMatterFormat) -> Result<String> {
+    let content = format!(
+        "# {}\n\nSee {} in the collection at {}.\n",
+        stub.document.title,
+        stub.note.display(),
+        stub.collection.display()
+    );
+    match stub.document.data.get(ID_KEY) {
+        Some(_) => set_frontmatter_value(
+            &content,
+            format,
+            ID_KEY,
+            &Value::String(stub.document.id.0.clone()),
+        ),
+        None => Ok(content),
+    }
+}
+
+fn write_file(path: &Path, content: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    Ok(std::fs::write(path, content)?)
+}
+
+/// `path` relative to `root`, with `/` separators
+fn relative_str(root: &Path, path: &Path) -> Result<String> {
+    let relative =
+        relative_path(root, path).ok_or_else(|| eyre!("{:?} is not in the collection", path))?;
+    Ok(path_to_slash(&relative))
+}
+
+/// The configuration file of the collection at `root`, keeping only the
+/// groups and periodic notes in the directory `prefix`, their directories
+/// made relative to it, and the names of the templates they use
+fn split_config(root: &Path, prefix: &str) -> Result<(String, BTreeSet<String>)> {
+    let file = collection_config_file(root);
+    let mut table: toml::Table = match file.is_file() {
+        true => toml::from_str(&std::fs::read_to_string(&file)?)?,
+        false => toml::Table::new(),
+    };
+    let mut templates = BTreeSet::new();
+    let moved_dir = |dir: &str| -> Option<String> {
+        let dir = dir.trim_matches('/');
+        if dir == prefix {
+            return Some(String::new());
+        }
+        dir.strip_prefix(prefix)?
+            .strip_prefix('/')
+            .map(str::to_owned)
+    };
+
+    if let Some(toml::Value::Table(groups)) = table.get_mut("group") {
+        groups.retain(|_, group| {
+            let Some(group) = group.as_table_mut() else {
+                return false;
+            };
+            let directories: Vec<toml::Value> = group
+                .get("directories")
+                .and_then(|d| d.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|d| moved_dir(d.as_str()?))
+                .map(toml::Value::String)
+                .collect();
+            if directories.is_empty() {
+                return false;
+            }
+            group.insert("directories".to_owned(), toml::Value::Array(directories));
+            templates.extend(
+                group
+                    .get("template")
+                    .and_then(|t| t.as_str())
+                    .map(str::to_owned),
+            );
+            true
+        });
+    }
+    for section in ["journal", "rollup"] {
+        let Some(toml::Value::Table(periods)) = table.get_mut(section) else {
+            continue;
+        };
+        periods.retain(|_, period| {
+            let Some(period) = period.as_table_mut() else {
+                return false;
+            };
+            let directory = period
+                .get("directory")
+                .and_then(|d| d.as_str())
+                .unwrap_or("journal");
+            let Some(directory) = moved_dir(directory) else {
+                return false;
+            };
+            period.insert("directory".to_owned(), toml::Value::String(directory));
+            templates.extend(
+                period
+                    .get("template")
+                    .and_then(|t| t.as_str())
+                    .map(str::to_owned),
+            );
+            true
+        });
+    }
+    Ok((toml::to_string(&table)?, templates))
+}
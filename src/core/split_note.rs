@@ -0,0 +1,239 @@
+//! Splitting a note by its headings: each section of a level becomes a note
+//! of its own, titled by its heading, and is replaced by a link or an embed
+//! in the note.
+//!
+//! The links to the headings of the extracted sections, from other notes,
+//! from the note itself and from the sections, are pointed to the new notes.
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::core::frontmatter::split_frontmatter;
+use crate::core::outline::{Section, sections, set_heading_level};
+use crate::core::parser::{DocumentParser, Parse};
+use crate::core::rename::{LinkEdit, edit_links};
+use crate::core::slug::heading_anchor;
+use crate::core::types::document::DocumentId;
+use crate::core::{LinkTargets, relative_link};
+use crate::result::Result;
+
+/// A section taken out of the note
+#[derive(Debug, Clone)]
+pub struct ExtractedSection {
+    pub title: String,
+    /// the text under the heading, its subsections raised so that the
+    /// section would be of level 1
+    pub content: String,
+    /// the anchors of the headings of the section, its own first
+    pub anchors: Vec<String>,
+    /// the range of the section in the body of the note
+    range: Range<usize>,
+}
+
+/// A note and the sections taken out of it
+#[derive(Debug, Clone)]
+pub struct NoteSplit {
+    document: String,
+    body_offset: usize,
+    pub sections: Vec<ExtractedSection>,
+}
+
+/// The sections of level `level` of `document`
+pub fn split_note(document: &str, level: u8, parser: &DocumentParser) -> Result<NoteSplit> {
+    let body_offset =
+        split_frontmatter(document).map_or(0, |(_, body)| document.len() - body.len());
+    let body = &document[body_offset..];
+    let ast = parser.parse(body)?;
+    let all = sections(&ast, body);
+
+    let extracted = all
+        .iter()
+        .filter(|section| section.level == level)
+        .map(|section| {
+            let within: Vec<&Section> = all
+                .iter()
+                .filter(|other| section.range.contains(&other.heading.start))
+                .collect();
+            let mut content = body[section.heading.end..section.range.end].to_owned();
+            for subsection in within.iter().skip(1).rev() {
+                let heading = subsection.heading.start - section.heading.end
+                    ..subsection.heading.end - section.heading.end;
+                let raised = (subsection.level - level + 1) as usize;
+                let source = &body[subsection.heading.clone()];
+                content.replace_range(heading, &set_heading_level(source, raised));
+            }
+            ExtractedSection {
+                title: section.content.clone(),
+                content: content.trim().to_owned(),
+                anchors: within.iter().map(|s| heading_anchor(&s.content)).collect(),
+                range: section.range.clone(),
+            }
+        })
+        .collect();
+
+    Ok(NoteSplit {
+        document: document.to_owned(),
+        body_offset,
+        sections: extracted,
+    })
+}
+
+impl NoteSplit {
+    /// The note with its sections replaced by `replacements`, in order
+    pub fn replace(&self, replacements: &[String]) -> String {
+        let mut document = self.document.clone();
+        for (section, replacement) in self.sections.iter().zip(replacements).rev() {
+            let start = self.body_offset + section.range.start;
+            let end = self.body_offset + section.range.end;
+            let replacement = match end == document.len() {
+                true => format!("{}\n", replacement.trim_end()),
+                false => format!("{}\n\n", replacement.trim_end()),
+            };
+            document.replace_range(start..end, &replacement);
+        }
+        document
+    }
+}
+
+/// A heading moved to a new note
+#[derive(Debug, Clone)]
+pub struct MovedHeading {
+    pub anchor: String,
+    pub id: DocumentId,
+    pub path: PathBuf,
+    /// whether it is the heading of the section, the title of the note
+    pub top: bool,
+}
+
+/// Points the links to the headings of a split note to where they moved
+#[derive(Debug, Clone)]
+pub struct HeadingLinks<'a> {
+    /// the split note
+    pub split: &'a DocumentId,
+    pub split_path: &'a Path,
+    pub moved: &'a [MovedHeading],
+    /// the documents links resolved among before the split
+    pub targets: &'a LinkTargets,
+}
+
+impl HeadingLinks<'_> {
+    /// Rewrite the links to headings of `document`, at `path`. Its links
+    /// resolve as they did in the document `from`, the one the note is or
+    /// the text of the note was in before the split, and `here` is the note
+    /// it is now. Returns `None` if no link was changed.
+    pub fn retarget(
+        &self,
+        document: &str,
+        path: &Path,
+        from: &DocumentId,
+        here: &DocumentId,
+    ) -> Result<Option<String>> {
+        let directory = path.parent().unwrap_or(Path::new(""));
+        edit_links(document, |link| {
+            let (note, fragment) = link.target.split_once('#')?;
+            let to = match note.is_empty() {
+                true => from,
+                false => self.targets.resolve(from, note, link.wiki)?,
+            };
+            if to != self.split {
+                return None;
+            }
+            let anchor = heading_anchor(fragment);
+            let target = |id: &DocumentId, path: &Path| match link.wiki {
+                true => id.0.clone(),
+                false => relative_link(directory, path),
+            };
+            let replacement = match self.moved.iter().find(|m| m.anchor == anchor) {
+                Some(moved) if moved.id == *here => format!("#{fragment}"),
+                Some(moved) if moved.top => target(&moved.id, &moved.path),
+                Some(moved) => format!("{}#{fragment}", target(&moved.id, &moved.path)),
+                // a heading left in the split note, linked to from a section
+                None if here != self.split => {
+                    format!("{}#{fragment}", target(self.split, self.split_path))
+                }
+                None => return None,
+            };
+            (replacement != link.target).then_some(LinkEdit::Target {
+                len: link.target.len(),
+                replacement,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_note() {
+        let document = "---\ntitle: Paper\n---\n# Paper\n\nintro\n\n## Method\n\nm, see [[#Results]]\n\n### Setup\n\ns\n\n## Results\n\nr\n";
+        let split = split_note(document, 2, &DocumentParser::new()).unwrap();
+        let titles: Vec<&str> = split.sections.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, ["Method", "Results"]);
+        assert_eq!(
+            split.sections[0].content,
+            "m, see [[#Results]]\n\n## Setup\n\ns"
+        );
+        assert_eq!(split.sections[0].anchors, ["method", "setup"]);
+        assert_eq!(
+            split.replace(&["[[method]]".to_owned(), "![[results]]".to_owned()]),
+            "---\ntitle: Paper\n---\n# Paper\n\nintro\n\n[[method]]\n\n![[results]]\n"
+        );
+    }
+
+    #[test]
+    fn test_retarget() {
+        let mut targets = LinkTargets::default();
+        for id in ["paper", "other"] {
+            targets.insert(
+                DocumentId(id.to_owned()),
+                PathBuf::from(format!("/{id}.md")),
+            );
+        }
+        let [paper, other, method] =
+            ["paper", "other", "method"].map(|id| DocumentId(id.to_owned()));
+        let moved = [
+            MovedHeading {
+                anchor: "method".to_owned(),
+                id: method.clone(),
+                path: PathBuf::from("/method.md"),
+                top: true,
+            },
+            MovedHeading {
+                anchor: "setup".to_owned(),
+                id: method.clone(),
+                path: PathBuf::from("/method.md"),
+                top: false,
+            },
+        ];
+        let links = HeadingLinks {
+            split: &paper,
+            split_path: Path::new("/paper.md"),
+            moved: &moved,
+            targets: &targets,
+        };
+
+        let document = "[[paper#Method]] [[paper#setup|the setup]] [x](paper.md#setup) [[paper#Intro]] [[paper]]\n";
+        assert_eq!(
+            links
+                .retarget(document, Path::new("/other.md"), &other, &other)
+                .unwrap()
+                .unwrap(),
+            "[[method]] [[method#setup|the setup]] [x](method.md#setup) [[paper#Intro]] [[paper]]\n"
+        );
+        // from the text of a section
+        assert_eq!(
+            links
+                .retarget(
+                    "[[#Setup]] [[#Intro]]\n",
+                    Path::new("/method.md"),
+                    &paper,
+                    &method
+                )
+                .unwrap()
+                .unwrap(),
+            "[[#Setup]] [[paper#Intro]]\n"
+        );
+    }
+}
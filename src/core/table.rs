@@ -0,0 +1,386 @@
+//! Tabular data in notes: CSV and JSON imported as markdown tables under a
+//! heading, and the tables of a note exported back.
+//!
+//! CSV is read as of [RFC 4180](https://datatracker.ietf.org/doc/html/rfc4180),
+//! its first record being the header. JSON is an array of objects, the
+//! header being all their keys in sorted order, or an array of arrays, the
+//! first being the header.
+
+use std::collections::BTreeSet;
+
+use clap::ValueEnum;
+use color_eyre::eyre::eyre;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::core::format::format_table;
+use crate::core::frontmatter::split_frontmatter;
+use crate::core::outline::{find_section, sections};
+use crate::core::parser::ast_nodes::{ColumnAlignment, Node, NodeKind, TableCell};
+use crate::core::parser::{DocumentParser, Parse};
+use crate::result::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum TableFormat {
+    Csv,
+    Json,
+}
+
+impl TableFormat {
+    /// The format of the file at `path` by its extension
+    pub fn of_path(path: &std::path::Path) -> Option<TableFormat> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "csv" => Some(TableFormat::Csv),
+            "json" => Some(TableFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// A table of text cells, rows shorter than the header lacking their last
+/// cells
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Table {
+    pub header: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn parse(input: &str, format: TableFormat) -> Result<Table> {
+        match format {
+            TableFormat::Csv => Table::from_csv(input),
+            TableFormat::Json => Table::from_json(input),
+        }
+    }
+
+    pub fn from_csv(input: &str) -> Result<Table> {
+        let mut records = parse_csv(input)?.into_iter();
+        let header = records.next().ok_or_else(|| eyre!("the csv is empty"))?;
+        Ok(Table {
+            header,
+            rows: records.collect(),
+        })
+    }
+
+    pub fn from_json(input: &str) -> Result<Table> {
+        let Value::Array(values) = serde_json::from_str(input)? else {
+            return Err(eyre!("the json is not an array"));
+        };
+        if values.iter().all(Value::is_array) {
+            let mut rows = values.into_iter().map(|row| match row {
+                Value::Array(cells) => cells.iter().map(cell_text).collect(),
+                _ => Vec::new(),
+            });
+            let header = rows
+                .next()
+                .ok_or_else(|| eyre!("the json array is empty"))?;
+            return Ok(Table {
+                header,
+                rows: rows.collect(),
+            });
+        }
+
+        let objects = values
+            .iter()
+            .map(|value| value.as_object())
+            .collect::<Option<Vec<&Map<String, Value>>>>()
+            .ok_or_else(|| eyre!("the json is neither an array of objects nor of arrays"))?;
+        let header: Vec<String> = objects
+            .iter()
+            .flat_map(|object| object.keys().cloned())
+            .collect::<BTreeSet<String>>()
+            .into_iter()
+            .collect();
+        let rows = objects
+            .iter()
+            .map(|object| {
+                header
+                    .iter()
+                    .map(|key| object.get(key).map(cell_text).unwrap_or_default())
+                    .collect()
+            })
+            .collect();
+        Ok(Table { header, rows })
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        for record in std::iter::once(&self.header).chain(&self.rows) {
+            let fields: Vec<String> = record.iter().map(|field| csv_field(field)).collect();
+            csv.push_str(&fields.join(","));
+            csv.push_str("\r\n");
+        }
+        csv
+    }
+
+    /// The rows as objects keyed by the header
+    pub fn to_json(&self) -> Value {
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                let object: Map<String, Value> = self
+                    .header
+                    .iter()
+                    .enumerate()
+                    .map(|(i, key)| {
+                        let cell = row.get(i).cloned().unwrap_or_default();
+                        (key.clone(), Value::String(cell))
+                    })
+                    .collect();
+                Value::Object(object)
+            })
+            .collect();
+        Value::Array(rows)
+    }
+
+    /// The table in markdown, its columns padded to the same width. Pipes
+    /// are escaped and line breaks written as `<br>`.
+    pub fn to_markdown(&self) -> String {
+        let escape = |row: &Vec<String>| -> Vec<String> {
+            row.iter()
+                .map(|cell| {
+                    cell.trim()
+                        .replace('|', "\\|")
+                        .replace("\r\n", "<br>")
+                        .replace('\n', "<br>")
+                })
+                .collect()
+        };
+        let header = escape(&self.header);
+        let rows: Vec<Vec<String>> = self.rows.iter().map(escape).collect();
+        format_table(&header, &[ColumnAlignment::None], &rows, "")
+    }
+}
+
+/// The tables of `document`, in document order
+pub fn tables(document: &str, parser: &DocumentParser) -> Result<Vec<Table>> {
+    let body = split_frontmatter(document).map_or(document, |(_, body)| body);
+    let ast = parser.parse(body)?;
+    let cells = |cells: &[TableCell]| -> Vec<String> {
+        cells
+            .iter()
+            .map(|cell| body[cell.range.clone()].trim().replace("\\|", "|"))
+            .collect()
+    };
+    Ok(ast
+        .nodes_of_kind(NodeKind::Table)
+        .filter_map(|table| match table.node() {
+            Node::Table { header, rows, .. } => Some(Table {
+                header: cells(&header.cells),
+                rows: rows.iter().map(|row| cells(&row.cells)).collect(),
+            }),
+            _ => None,
+        })
+        .collect())
+}
+
+/// Put `table` under the heading `heading` of `document`, replacing the
+/// first table of its section or else ending the section with it. A missing
+/// heading is added, with the table, at the end of the document.
+pub fn insert_table(
+    document: &str,
+    heading: &str,
+    table: &Table,
+    parser: &DocumentParser,
+) -> Result<String> {
+    let body_offset =
+        split_frontmatter(document).map_or(0, |(_, body)| document.len() - body.len());
+    let body = &document[body_offset..];
+    let ast = parser.parse(body)?;
+    let markdown = table.to_markdown();
+
+    let sections = sections(&ast, body);
+    let Some(section) = find_section(&sections, heading).map(|s| s.range.clone()) else {
+        let mut edited = document.trim_end().to_owned();
+        if !edited.is_empty() {
+            edited.push_str("\n\n");
+        }
+        edited.push_str(&format!("## {heading}\n\n{markdown}\n"));
+        return Ok(edited);
+    };
+
+    let existing = ast
+        .nodes_of_kind(NodeKind::Table)
+        .map(|table| table.node().range().clone())
+        .find(|range| section.contains(&range.start));
+    let (range, replacement) = match existing {
+        Some(range) => {
+            // the range of a table may include the line break ending it
+            let end = range.start + body[range.clone()].trim_end().len();
+            (range.start..end, markdown)
+        }
+        None => {
+            let end = section.start + body[section.clone()].trim_end().len();
+            let after = match section.end == body.len() {
+                true => "\n",
+                false => "\n\n",
+            };
+            (end..section.end, format!("\n\n{markdown}{after}"))
+        }
+    };
+
+    let mut edited = document.to_owned();
+    edited.replace_range(
+        body_offset + range.start..body_offset + range.end,
+        &replacement,
+    );
+    Ok(edited)
+}
+
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_owned(),
+    }
+}
+
+/// The records of the csv `input`, fields being separated by commas, or by
+/// semicolons or tabs if the first line has no commas
+fn parse_csv(input: &str) -> Result<Vec<Vec<String>>> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let first_line = input.lines().next().unwrap_or_default();
+    let separator = [',', ';', '\t']
+        .into_iter()
+        .find(|separator| first_line.contains(*separator))
+        .unwrap_or(',');
+
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            c if quoted => field.push(c),
+            c if c == separator => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(eyre!("the csv ends in a quoted field"));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    // blank lines are not records
+    records.retain(|record| !(record.len() == 1 && record[0].is_empty()));
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(header: &[&str], rows: &[&[&str]]) -> Table {
+        let strings = |cells: &[&str]| cells.iter().map(|c| c.to_string()).collect();
+        Table {
+            header: strings(header),
+            rows: rows.iter().map(|row| strings(row)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let csv = "name,note\r\nAda,\"says \"\"hi\"\", twice\"\r\n\r\nBob,\"two\nlines\"\n";
+        let parsed = Table::from_csv(csv).unwrap();
+        assert_eq!(
+            parsed,
+            table(
+                &["name", "note"],
+                &[&["Ada", "says \"hi\", twice"], &["Bob", "two\nlines"]]
+            )
+        );
+        assert_eq!(Table::from_csv(&parsed.to_csv()).unwrap(), parsed);
+        assert_eq!(
+            Table::from_csv("a;b\n1;2").unwrap(),
+            table(&["a", "b"], &[&["1", "2"]])
+        );
+        assert!(Table::from_csv("a\n\"open").is_err());
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let objects =
+            r#"[{"name": "Ada", "age": 36}, {"name": "Bob", "city": null, "tags": ["x"]}]"#;
+        assert_eq!(
+            Table::from_json(objects).unwrap(),
+            table(
+                &["age", "city", "name", "tags"],
+                &[&["36", "", "Ada", ""], &["", "", "Bob", "[\"x\"]"]]
+            )
+        );
+        let arrays = r#"[["a", "b"], [1, true]]"#;
+        assert_eq!(
+            Table::from_json(arrays).unwrap(),
+            table(&["a", "b"], &[&["1", "true"]])
+        );
+        assert!(Table::from_json(r#"{"a": 1}"#).is_err());
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let parsed = table(&["a", "b|c"], &[&["line\nbreak", "x"], &["1"]]);
+        assert_eq!(
+            parsed.to_markdown(),
+            "| a             | b\\|c |\n| ------------- | ---- |\n| line<br>break | x    |\n| 1             |      |"
+        );
+    }
+
+    #[test]
+    fn test_insert_table() {
+        let parser = DocumentParser::new();
+        let data = table(&["a", "b"], &[&["1", "2"]]);
+        let markdown = data.to_markdown();
+
+        // added at the end of the section
+        let document = "---\ntitle: T\n---\n# T\n\n## Data\n\nsome text\n\n## Other\n\ntext\n";
+        let edited = insert_table(document, "data", &data, &parser).unwrap();
+        assert_eq!(
+            edited,
+            format!(
+                "---\ntitle: T\n---\n# T\n\n## Data\n\nsome text\n\n{markdown}\n\n## Other\n\ntext\n"
+            )
+        );
+        assert_eq!(tables(&edited, &parser).unwrap(), vec![data.clone()]);
+
+        // replacing the table of the section
+        let other = table(&["x"], &[&["y"]]);
+        let replaced = insert_table(&edited, "Data", &other, &parser).unwrap();
+        assert_eq!(
+            replaced,
+            format!(
+                "---\ntitle: T\n---\n# T\n\n## Data\n\nsome text\n\n{}\n\n## Other\n\ntext\n",
+                other.to_markdown()
+            )
+        );
+
+        // a section ending the document, and a missing heading
+        let edited = insert_table("# T\n\n## Data\n", "Data", &data, &parser).unwrap();
+        assert_eq!(edited, format!("# T\n\n## Data\n\n{markdown}\n"));
+        let edited = insert_table("# T\n\ntext\n", "Data", &data, &parser).unwrap();
+        assert_eq!(edited, format!("# T\n\ntext\n\n## Data\n\n{markdown}\n"));
+    }
+}
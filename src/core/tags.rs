@@ -0,0 +1,368 @@
+//! Inline `#tags`, nested tags and renaming tags across the collection.
+//!
+//! Tags are either listed in the `tags` array of the frontmatter or written in
+//! the text of a document. The index stores the byte range of every inline tag,
+//! which is what a rename rewrites. Tags nest through the `/` in their names,
+//! `project/zet/parser` is a child of `project/zet`.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::PathBuf;
+
+use color_eyre::eyre::eyre;
+use rusqlite::Connection;
+use serde::Serialize;
+use serde_json::Value;
+use sql_minifier::macros::minify_sql as sql;
+
+use crate::core::frontmatter::{set_frontmatter_value, split_frontmatter};
+use crate::core::lock::Locks;
+use crate::core::parser::arena::{Ast, Siblings};
+use crate::core::parser::ast_nodes::Node;
+use crate::core::parser::{DocumentParser, FrontMatterFormat, FrontMatterParser, Parse};
+use crate::core::types::document::DocumentPath;
+use crate::core::{TAGS_KEY, inline_source_span};
+use crate::result::Result;
+
+/// A tag written in the text of a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlineTag {
+    /// the lowercased name of the tag
+    pub tag: String,
+    /// byte range of the name, without the `#`, in the document
+    pub range: Range<usize>,
+}
+
+pub fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '/')
+}
+
+/// Whether `tag` can be written inline. It has to contain a letter, so that
+/// `#1` is not a tag.
+pub fn is_valid_tag(tag: &str) -> bool {
+    tag.chars().all(is_tag_char) && tag.chars().any(char::is_alphabetic)
+}
+
+/// The tag `tag` is nested under, `None` for a top level tag
+pub fn parent_tag(tag: &str) -> Option<&str> {
+    tag.rsplit_once('/').map(|(parent, _)| parent)
+}
+
+/// A tag and the number of documents tagged with it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub documents: usize,
+}
+
+/// A tag and the tags nested under it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TagTree {
+    /// the last segment of the tag
+    pub name: String,
+    pub tag: String,
+    /// the number of documents tagged with this very tag, zero for a tag only
+    /// used as a parent
+    pub documents: usize,
+    pub children: Vec<TagTree>,
+}
+
+/// The tags of the collection starting with `prefix`, `project/` lists the
+/// tags nested under `project`
+pub fn list_tags(db: &Connection, prefix: &str) -> Result<Vec<TagCount>> {
+    let mut query = db.prepare(sql!(
+        r#"
+        select
+            t.tag,
+            count(distinct m.document_id)
+        from
+            tag t
+            join document_tag_map m on m.tag_id = t.id
+        where
+            instr(t.tag, lower(?1)) = 1
+        group by
+            t.tag
+        order by
+            t.tag
+    "#
+    ))?;
+    let tags = query
+        .query_map([prefix], |r| {
+            Ok(TagCount {
+                tag: r.get(0)?,
+                documents: r.get(1)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(tags)
+}
+
+/// Arrange `tags` into trees, adding the parents missing from `tags`
+pub fn tag_tree(tags: &[TagCount]) -> Vec<TagTree> {
+    let mut roots: Vec<TagTree> = Vec::new();
+    for TagCount { tag, documents } in tags {
+        let mut level = &mut roots;
+        let mut path = String::new();
+        for name in tag.split('/') {
+            if !path.is_empty() {
+                path.push('/');
+            }
+            path.push_str(name);
+            let position = match level.iter().position(|node| node.name == name) {
+                Some(position) => position,
+                None => {
+                    level.push(TagTree {
+                        name: name.to_owned(),
+                        tag: path.clone(),
+                        documents: 0,
+                        children: Vec::new(),
+                    });
+                    level.len() - 1
+                }
+            };
+            if path == *tag {
+                level[position].documents = *documents;
+            }
+            level = &mut level[position].children;
+        }
+    }
+    roots
+}
+
+/// Find the `#tags` in the text of `document`, code and links excluded
+pub fn extract_inline_tags(document: &str) -> Result<Vec<InlineTag>> {
+    let body_offset = match split_frontmatter(document) {
+        Some((_, body)) => document.len() - body.len(),
+        None => 0,
+    };
+    let body = &document[body_offset..];
+    let ast = DocumentParser::new().parse(body)?;
+
+    let mut tags = Vec::new();
+    find_inline_tags(&mut tags, body, &ast, ast.roots());
+    for tag in &mut tags {
+        tag.range = tag.range.start + body_offset..tag.range.end + body_offset;
+    }
+    Ok(tags)
+}
+
+/// Find the tags in the nodes `ids` of `ast`, the siblings of a node or the
+/// top level nodes, and in the nodes within them
+fn find_inline_tags(tags: &mut Vec<InlineTag>, body: &str, ast: &Ast, ids: Siblings) {
+    if let Some((span, excluded)) = inline_source_span(ast, ids.clone()) {
+        let text = &body[span.clone()];
+        for (position, _) in text.match_indices('#') {
+            if excluded
+                .iter()
+                .any(|range| range.contains(&(span.start + position)))
+            {
+                continue;
+            }
+            // a tag starts a word, `C#` and `page#anchor` are not tags
+            if text[..position]
+                .chars()
+                .next_back()
+                .is_some_and(|c| !c.is_whitespace() && c != '(')
+            {
+                continue;
+            }
+            let rest = &text[position + 1..];
+            let name = &rest[..rest.find(|c| !is_tag_char(c)).unwrap_or(rest.len())];
+            let name = name.trim_end_matches(['-', '/']);
+            if !is_valid_tag(name) {
+                continue;
+            }
+            let start = span.start + position + 1;
+            tags.push(InlineTag {
+                tag: name.to_lowercase(),
+                range: start..start + name.len(),
+            });
+        }
+    }
+
+    for id in ids {
+        match ast[id] {
+            Node::Heading { .. }
+            | Node::Paragraph { .. }
+            | Node::BlockQuote { .. }
+            | Node::List { .. }
+            | Node::Item { .. } => find_inline_tags(tags, body, ast, ast.children(id)),
+            _ => {}
+        }
+    }
+}
+
+/// Rename the tags in `from` to `to` in the documents of the collection, both
+/// inline and in the frontmatter, using the tag ranges of the index. Renaming
+/// several tags to one merges them. Locked documents are left as they are.
+/// Returns the paths of the changed documents.
+pub fn rename_tags(
+    db: &Connection,
+    from: &[String],
+    to: &str,
+    format: FrontMatterFormat,
+    locks: &Locks,
+) -> Result<Vec<PathBuf>> {
+    if !is_valid_tag(to) {
+        return Err(eyre!("invalid tag {:?}", to));
+    }
+
+    // the ranges of the inline tags and whether the frontmatter lists the tag
+    let mut occurrences: BTreeMap<PathBuf, (Vec<Range<usize>>, bool)> = BTreeMap::new();
+    let mut query = db.prepare(sql!(
+        r#"
+        select
+            d.path,
+            m.range_start,
+            m.range_end
+        from
+            document_tag_map m
+            join tag t on t.id = m.tag_id
+            join document d on d.id = m.document_id
+        where
+            t.tag = ?1
+    "#
+    ))?;
+    for tag in from {
+        let rows = query.query_map([tag], |r| {
+            Ok((
+                r.get::<_, DocumentPath>(0)?,
+                r.get::<_, Option<usize>>(1)?,
+                r.get::<_, Option<usize>>(2)?,
+            ))
+        })?;
+        for row in rows {
+            let (DocumentPath(path), start, end) = row?;
+            let (ranges, in_frontmatter) = occurrences.entry(path).or_default();
+            match (start, end) {
+                (Some(start), Some(end)) => ranges.push(start..end),
+                _ => *in_frontmatter = true,
+            }
+        }
+    }
+
+    // every document is rewritten before any file is written, so that a
+    // stale index leaves the collection untouched
+    let mut rewritten = Vec::new();
+    for (path, (mut ranges, in_frontmatter)) in occurrences {
+        let original = std::fs::read_to_string(&path)?;
+        if locks.is_locked(&path, &original) {
+            log::warn!("not renaming the tags of the locked document {:?}", path);
+            continue;
+        }
+        let mut content = original.clone();
+        ranges.sort_by_key(|range| std::cmp::Reverse(range.start));
+        for range in ranges {
+            match content.get(range.clone()) {
+                Some(tag) if from.contains(&tag.to_lowercase()) => content.replace_range(range, to),
+                _ => return Err(eyre!("the index of {:?} is out of date", path)),
+            }
+        }
+        if in_frontmatter {
+            content = rename_frontmatter_tags(&content, format, from, to)?;
+        }
+        if content != original {
+            rewritten.push((path, content));
+        }
+    }
+
+    for (path, content) in &rewritten {
+        std::fs::write(path, content)?;
+    }
+    Ok(rewritten.into_iter().map(|(path, _)| path).collect())
+}
+
+/// Replace the tags in `from` with `to` in the `tags` array of the
+/// frontmatter, keeping a single `to`
+fn rename_frontmatter_tags(
+    document: &str,
+    format: FrontMatterFormat,
+    from: &[String],
+    to: &str,
+) -> Result<String> {
+    let (data, _) = FrontMatterParser::new(format).parse(document.to_owned());
+    let Some(Value::Array(tags)) = data.as_ref().and_then(|data| data.get(TAGS_KEY)) else {
+        return Ok(document.to_owned());
+    };
+
+    let mut renamed: Vec<Value> = Vec::new();
+    for tag in tags {
+        let tag = match tag.as_str() {
+            Some(name) if from.contains(&name.to_lowercase()) => Value::from(to),
+            _ => tag.clone(),
+        };
+        if !renamed.contains(&tag) {
+            renamed.push(tag);
+        }
+    }
+    set_frontmatter_value(document, format, TAGS_KEY, &Value::Array(renamed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_inline_tags() {
+        let document = "---\ntags: [a]\n---\n\n# Notes\n\n#draft read #books/sci-fi, not C# or `#code` or [#link](x)\n\n- item (#todo-list) #1\n";
+        let tags: Vec<(String, &str)> = extract_inline_tags(document)
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.tag, &document[t.range]))
+            .collect();
+        assert_eq!(
+            tags,
+            vec![
+                ("draft".to_owned(), "draft"),
+                ("books/sci-fi".to_owned(), "books/sci-fi"),
+                ("todo-list".to_owned(), "todo-list"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tag_tree() {
+        let count = |tag: &str, documents| TagCount {
+            tag: tag.to_owned(),
+            documents,
+        };
+        let tree = tag_tree(&[
+            count("book", 3),
+            count("project/zet", 1),
+            count("project/zet/parser", 2),
+        ]);
+        let node = |tag: &str, documents, children| TagTree {
+            name: tag.rsplit('/').next().unwrap().to_owned(),
+            tag: tag.to_owned(),
+            documents,
+            children,
+        };
+        assert_eq!(
+            tree,
+            vec![
+                node("book", 3, vec![]),
+                node(
+                    "project",
+                    0,
+                    vec![node(
+                        "project/zet",
+                        1,
+                        vec![node("project/zet/parser", 2, vec![])]
+                    )]
+                ),
+            ]
+        );
+        assert_eq!(parent_tag("project/zet/parser"), Some("project/zet"));
+        assert_eq!(parent_tag("project"), None);
+    }
+
+    #[test]
+    fn test_rename_frontmatter_tags() {
+        let document = "---\ntitle: A\ntags:\n  - Book\n  - novel\n  - read\n---\n# A\n";
+        let from = ["book".to_owned(), "novel".to_owned()];
+        assert_eq!(
+            rename_frontmatter_tags(document, FrontMatterFormat::Yaml, &from, "fiction").unwrap(),
+            "---\ntitle: A\ntags: [\"fiction\",\"read\"]\n---\n# A\n"
+        );
+    }
+}
@@ -1,13 +1,18 @@
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
 
 use color_eyre::eyre::eyre;
+use serde::Serialize;
+use tera::ast::{Expr, ExprVal, Node};
 use tera::{Context, Tera};
 
 use crate::config::{Config, GroupConfig};
 use crate::result::Result;
 
-const DEFAULT_TEMPLATE: &str = r#"---
+/// The variables every template is rendered with
+pub const BUILTIN_VARIABLES: [&str; 4] = ["id", "title", "date", "content"];
+
+pub const DEFAULT_TEMPLATE: &str = r#"---
 id: {{ id }}
 title: {{ title }}
 ---
@@ -60,23 +65,224 @@ pub fn resolve_template_string(
     Ok(DEFAULT_TEMPLATE.to_owned())
 }
 
-fn load_template_file(collection_root: &Path, name: &str) -> Result<String> {
-    let path = if name.contains('.') {
-        // treat as a path under .zet/templates/
-        collection_root
-            .join(format!(".{}", crate::APP_NAME))
-            .join("templates")
-            .join(name)
+/// .zet/templates
+pub fn template_dir(collection_root: &Path) -> PathBuf {
+    collection_root
+        .join(format!(".{}", crate::APP_NAME))
+        .join("templates")
+}
+
+/// The file of the template `name`. If it contains '.', it is a path in
+/// .zet/templates, otherwise it is .zet/templates/<name>.md
+pub fn template_path(collection_root: &Path, name: &str) -> PathBuf {
+    if name.contains('.') {
+        template_dir(collection_root).join(name)
     } else {
-        // try .zet/templates/<name>.md
-        collection_root
-            .join(format!(".{}", crate::APP_NAME))
-            .join("templates")
-            .join(format!("{}.md", name))
+        template_dir(collection_root).join(format!("{}.md", name))
+    }
+}
+
+pub fn load_template_file(collection_root: &Path, name: &str) -> Result<String> {
+    let path = template_path(collection_root, name);
+    std::fs::read_to_string(&path).map_err(|e| eyre!("could not read template {:?}: {}", path, e))
+}
+
+/// A template in .zet/templates
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateFile {
+    /// the name the template is referred to by, e.g. in `--template`
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// The templates in .zet/templates, in the order of their names
+pub fn list_templates(collection_root: &Path) -> Result<Vec<TemplateFile>> {
+    let dir = template_dir(collection_root);
+    let mut paths = Vec::new();
+    if dir.is_dir() {
+        collect_files(&dir, &mut paths)?;
+    }
+    let mut templates = Vec::with_capacity(paths.len());
+    for path in paths {
+        let relative = path.strip_prefix(&dir)?.to_string_lossy().into_owned();
+        // <name>.md can be referred to without its extension
+        let name = match relative.strip_suffix(".md") {
+            Some(name) if !name.contains('.') => name.to_owned(),
+            _ => relative,
+        };
+        templates.push(TemplateFile { name, path });
+    }
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(templates)
+}
+
+fn collect_files(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, paths)?;
+        } else {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The variables a template reads, in alphabetical order, including those
+/// read by the templates it extends and includes. Variables it defines
+/// itself, with `set` or as loop variables, are left out.
+pub fn template_variables(collection_root: &Path, template_str: &str) -> Result<Vec<String>> {
+    let mut tera = template_engine(collection_root)?;
+    tera.add_raw_template("note", template_str)
+        .map_err(|e| eyre!("failed to parse template: {}", e))?;
+
+    let mut collector = VariableCollector {
+        tera: &tera,
+        read: BTreeSet::new(),
+        defined: BTreeSet::from(["loop".to_owned()]),
+        visited: BTreeSet::new(),
     };
+    collector.template("note");
+    Ok(collector
+        .read
+        .difference(&collector.defined)
+        .cloned()
+        .collect())
+}
 
-    std::fs::read_to_string(&path)
-        .map_err(|e| eyre!("could not read template {:?}: {}", path, e))
+struct VariableCollector<'a> {
+    tera: &'a Tera,
+    read: BTreeSet<String>,
+    defined: BTreeSet<String>,
+    /// the templates already walked
+    visited: BTreeSet<String>,
+}
+
+impl VariableCollector<'_> {
+    fn template(&mut self, name: &str) {
+        if !self.visited.insert(name.to_owned()) {
+            return;
+        }
+        if let Ok(template) = self.tera.get_template(name) {
+            self.nodes(&template.ast);
+        }
+    }
+
+    fn nodes(&mut self, nodes: &[Node]) {
+        for node in nodes {
+            match node {
+                Node::VariableBlock(_, expr) => collect_expr_variables(expr, &mut self.read),
+                Node::Extends(_, name) => self.template(name),
+                Node::Include(_, names, _) => names.iter().for_each(|name| self.template(name)),
+                Node::Set(_, set) => {
+                    collect_expr_variables(&set.value, &mut self.read);
+                    self.defined.insert(set.key.clone());
+                }
+                Node::FilterSection(_, section, _) => {
+                    for arg in section.filter.args.values() {
+                        collect_expr_variables(arg, &mut self.read);
+                    }
+                    self.nodes(&section.body);
+                }
+                Node::Block(_, block, _) => self.nodes(&block.body),
+                Node::Forloop(_, forloop, _) => {
+                    collect_expr_variables(&forloop.container, &mut self.read);
+                    self.defined.extend(forloop.key.iter().cloned());
+                    self.defined.insert(forloop.value.clone());
+                    self.nodes(&forloop.body);
+                    if let Some(body) = &forloop.empty_body {
+                        self.nodes(body);
+                    }
+                }
+                Node::If(condition, _) => {
+                    for (_, expr, body) in &condition.conditions {
+                        collect_expr_variables(expr, &mut self.read);
+                        self.nodes(body);
+                    }
+                    if let Some((_, body)) = &condition.otherwise {
+                        self.nodes(body);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn collect_expr_variables(expr: &Expr, variables: &mut BTreeSet<String>) {
+    collect_value_variables(&expr.val, variables);
+    for filter in &expr.filters {
+        filter
+            .args
+            .values()
+            .for_each(|arg| collect_expr_variables(arg, variables));
+    }
+}
+
+fn collect_value_variables(value: &ExprVal, variables: &mut BTreeSet<String>) {
+    let mut ident = |name: &str| {
+        // `capture.cwd` and `items[0]` read the variables `capture` and `items`
+        let end = name.find(['.', '[']).unwrap_or(name.len());
+        variables.insert(name[..end].to_owned());
+    };
+    match value {
+        ExprVal::Ident(name) => ident(name),
+        ExprVal::Test(test) => {
+            ident(&test.ident);
+            test.args
+                .iter()
+                .for_each(|arg| collect_expr_variables(arg, variables));
+        }
+        ExprVal::Math(math) => {
+            collect_expr_variables(&math.lhs, variables);
+            collect_expr_variables(&math.rhs, variables);
+        }
+        ExprVal::Logic(logic) => {
+            collect_expr_variables(&logic.lhs, variables);
+            collect_expr_variables(&logic.rhs, variables);
+        }
+        ExprVal::In(expr) => {
+            collect_expr_variables(&expr.lhs, variables);
+            collect_expr_variables(&expr.rhs, variables);
+        }
+        ExprVal::FunctionCall(call) => call
+            .args
+            .values()
+            .for_each(|arg| collect_expr_variables(arg, variables)),
+        ExprVal::MacroCall(call) => call
+            .args
+            .values()
+            .for_each(|arg| collect_expr_variables(arg, variables)),
+        ExprVal::Array(values) => values
+            .iter()
+            .for_each(|value| collect_expr_variables(value, variables)),
+        ExprVal::StringConcat(concat) => concat
+            .values
+            .iter()
+            .for_each(|value| collect_value_variables(value, variables)),
+        ExprVal::String(_) | ExprVal::Int(_) | ExprVal::Float(_) | ExprVal::Bool(_) => {}
+    }
+}
+
+/// A template engine knowing the templates in .zet/templates by their path,
+/// so that templates can extend and include them, e.g. `{% extends
+/// "base.md" %}` or `{% include "partials/frontmatter.md" %}`
+pub fn template_engine(collection_root: &Path) -> Result<Tera> {
+    let dir = template_dir(collection_root);
+    let mut templates = Vec::new();
+    for template in list_templates(collection_root)? {
+        let name = template
+            .path
+            .strip_prefix(&dir)?
+            .to_string_lossy()
+            .into_owned();
+        templates.push((name, std::fs::read_to_string(&template.path)?));
+    }
+
+    let mut tera = Tera::default();
+    tera.add_raw_templates(templates)
+        .map_err(|e| eyre!("failed to parse the templates in {:?}: {}", dir, e))?;
+    Ok(tera)
 }
 
 /// Render a template string with the given context variables.
@@ -88,7 +294,43 @@ pub fn render_template(
     content: &str,
     extra: &HashMap<String, serde_json::Value>,
 ) -> Result<String> {
-    let mut tera = Tera::default();
+    render(
+        Tera::default(),
+        template_str,
+        id,
+        title,
+        date,
+        content,
+        extra,
+    )
+}
+
+/// Render a note template with the given context variables, the template
+/// being able to extend and include the templates of the collection.
+pub fn render_collection_template(
+    collection_root: &Path,
+    template_str: &str,
+    id: &str,
+    title: &str,
+    date: &str,
+    content: &str,
+    extra: &HashMap<String, serde_json::Value>,
+) -> Result<String> {
+    let tera = template_engine(collection_root)?;
+    render(tera, template_str, id, title, date, content, extra)
+}
+
+/// Render a template string with `tera`, e.g. a [`template_engine`] with
+/// additional functions registered
+pub fn render(
+    mut tera: Tera,
+    template_str: &str,
+    id: &str,
+    title: &str,
+    date: &str,
+    content: &str,
+    extra: &HashMap<String, serde_json::Value>,
+) -> Result<String> {
     tera.set_escape_fn(|s| s.to_string());
     tera.add_raw_template("note", template_str)
         .map_err(|e| eyre!("failed to parse template: {}", e))?;
@@ -0,0 +1,157 @@
+//! Timelines, the notes matching a query and their dated content, grouped by
+//! period.
+//!
+//! A note is placed at the date of its `date` frontmatter entry, or at its
+//! creation when it has none. The `event` entries of its frontmatter and its
+//! tasks with a due date are placed at their start and due date, see
+//! [`crate::core::ics`].
+
+use std::collections::HashSet;
+
+use jiff::Timestamp;
+use jiff::civil::{Date, DateTime};
+use jiff::tz::TimeZone;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::core::date_parser::Weekday;
+use crate::core::ics::{Calendar, EventTime};
+use crate::core::journal::Period;
+use crate::core::types::document::{Document, DocumentId};
+use crate::result::Result;
+
+/// Frontmatter key holding the date a note is placed at
+pub const DATE_KEY: &str = "date";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineEntryKind {
+    Note,
+    Event,
+    Task,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineEntry {
+    pub date: Date,
+    pub kind: TimelineEntryKind,
+    pub document_id: DocumentId,
+    /// the title of the note, the summary of the event or the content of the
+    /// task
+    pub title: String,
+}
+
+/// The entries of one period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineBucket {
+    pub start: Date,
+    pub title: String,
+    pub entries: Vec<TimelineEntry>,
+}
+
+/// The timeline of `documents` by `period`, oldest first, leaving out the
+/// entries outside of `range`. Dates without an offset are interpreted in
+/// `tz`, and weeks start on `week_start`.
+pub fn timeline(
+    db: &Connection,
+    documents: &[Document],
+    period: Period,
+    range: Option<(Date, Date)>,
+    tz: &TimeZone,
+    week_start: &Weekday,
+) -> Result<Vec<TimelineBucket>> {
+    let ids: HashSet<&DocumentId> = documents.iter().map(|d| &d.id).collect();
+    let mut entries: Vec<TimelineEntry> = documents
+        .iter()
+        .map(|document| TimelineEntry {
+            date: document
+                .data
+                .get(DATE_KEY)
+                .and_then(|date| parse_date(date, tz))
+                .unwrap_or_else(|| document.created.0.to_zoned(tz.clone()).date()),
+            kind: TimelineEntryKind::Note,
+            document_id: document.id.clone(),
+            title: document.title.clone(),
+        })
+        .collect();
+
+    let calendar = Calendar::load(db, tz)?;
+    let date = |time: EventTime| match time {
+        EventTime::Date(date) => date,
+        EventTime::DateTime(timestamp) => timestamp.to_zoned(tz.clone()).date(),
+    };
+    entries.extend(
+        calendar
+            .events
+            .into_iter()
+            .filter(|e| ids.contains(&e.document_id))
+            .map(|e| TimelineEntry {
+                date: date(e.start),
+                kind: TimelineEntryKind::Event,
+                document_id: e.document_id,
+                title: e.summary,
+            }),
+    );
+    entries.extend(
+        calendar
+            .todos
+            .into_iter()
+            .filter(|t| ids.contains(&t.document_id))
+            .map(|t| TimelineEntry {
+                date: date(t.due),
+                kind: TimelineEntryKind::Task,
+                document_id: t.document_id,
+                title: t.summary,
+            }),
+    );
+
+    if let Some((first, last)) = range {
+        entries.retain(|e| first <= e.date && e.date <= last);
+    }
+    // stable, so the entries of a date keep the order of the documents
+    entries.sort_by_key(|e| e.date);
+
+    let mut buckets: Vec<TimelineBucket> = Vec::new();
+    for entry in entries {
+        let start = period.start_of(entry.date, week_start)?;
+        match buckets.last_mut() {
+            Some(bucket) if bucket.start == start => bucket.entries.push(entry),
+            _ => buckets.push(TimelineBucket {
+                start,
+                title: period.title(start, None)?,
+                entries: vec![entry],
+            }),
+        }
+    }
+    Ok(buckets)
+}
+
+/// The date of a frontmatter value, a date, a date and time, or a timestamp
+fn parse_date(value: &Value, tz: &TimeZone) -> Option<Date> {
+    let value = value.as_str()?.trim();
+    if let Ok(timestamp) = value.parse::<Timestamp>() {
+        return Some(timestamp.to_zoned(tz.clone()).date());
+    }
+    if let Ok(datetime) = value.parse::<DateTime>() {
+        return Some(datetime.date());
+    }
+    value.parse::<Date>().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jiff::civil::date;
+
+    #[test]
+    fn test_parse_date() {
+        let tz = TimeZone::fixed(jiff::tz::offset(2));
+        let parse = |value: &str| parse_date(&Value::String(value.to_owned()), &tz);
+        assert_eq!(parse("2025-01-03"), Some(date(2025, 1, 3)));
+        assert_eq!(parse("2025-01-03T10:00"), Some(date(2025, 1, 3)));
+        assert_eq!(parse("2025-01-03T23:00:00Z"), Some(date(2025, 1, 4)));
+        assert_eq!(parse("soon"), None);
+        assert_eq!(parse_date(&Value::Bool(true), &tz), None);
+    }
+}
@@ -0,0 +1,222 @@
+//! Transclusion: the embeds `![[note]]` and `![[note#heading]]` of a body
+//! replaced by the body of the note, or by the section under the heading, so
+//! that a note composed of embeds reads as the whole assembled document.
+//!
+//! Embedded notes are expanded in turn. An embed of a note that is already
+//! being expanded would never end, it is left as is, as are embeds of
+//! assets, of blocks and of notes that are not indexed.
+//!
+//! When redacting, the private parts of an embedded note are stripped before
+//! its section is cut out, so that a section starting within private text
+//! does not carry the text after the opening fence.
+
+use std::ops::Range;
+
+use pulldown_cmark::{Event, HeadingLevel, LinkType, Parser, Tag, TagEnd};
+
+use crate::core::LinkTargets;
+use crate::core::parser::{DocumentParserOptions, FrontMatterFormat, FrontMatterParser};
+use crate::core::redact::redact;
+use crate::core::types::document::DocumentId;
+use crate::result::Result;
+
+/// Expands the embeds of bodies, reading the embedded notes from disk
+pub struct Transclusion<'a> {
+    targets: &'a LinkTargets,
+    format: FrontMatterFormat,
+    redacting: bool,
+}
+
+impl<'a> Transclusion<'a> {
+    pub fn new(targets: &'a LinkTargets, format: FrontMatterFormat) -> Self {
+        Self {
+            targets,
+            format,
+            redacting: false,
+        }
+    }
+
+    /// Strip the private parts of the embedded notes
+    pub fn redacting(mut self, redacting: bool) -> Self {
+        self.redacting = redacting;
+        self
+    }
+
+    /// `body`, the body of the document `id`, with its embeds expanded
+    pub fn expand(&self, id: &DocumentId, body: &str) -> Result<String> {
+        self.expand_in(&mut vec![id.clone()], body)
+    }
+
+    /// `stack` being the documents being expanded, the last one being the
+    /// one of `body`
+    fn expand_in(&self, stack: &mut Vec<DocumentId>, body: &str) -> Result<String> {
+        let Some(from) = stack.last().cloned() else {
+            return Ok(body.to_owned());
+        };
+        let mut expanded = String::with_capacity(body.len());
+        let mut last = 0;
+        for (range, target) in embeds(body) {
+            let (note, heading) = match target.split_once('#') {
+                Some((note, heading)) => (note, Some(heading)),
+                None => (target.as_str(), None),
+            };
+            if heading.is_some_and(|heading| heading.starts_with('^')) {
+                continue;
+            }
+            let Some(id) = self.targets.resolve(&from, note, true) else {
+                continue;
+            };
+            if stack.contains(id) {
+                log::warn!(
+                    "the embed of '{}' in '{}' is a cycle, it is left as is",
+                    id.0,
+                    from.0
+                );
+                continue;
+            }
+            let Some(path) = self.targets.path(id) else {
+                continue;
+            };
+            let content = std::fs::read_to_string(path)?;
+            let (_, mut embedded) = FrontMatterParser::new(self.format).parse(content);
+            if self.redacting {
+                embedded = redact(&embedded);
+            }
+            let embedded = match heading {
+                Some(heading) => match section(&embedded, heading) {
+                    Some(section) => embedded[section].to_owned(),
+                    None => continue,
+                },
+                None => embedded,
+            };
+
+            stack.push(id.clone());
+            let embedded = self.expand_in(stack, &embedded)?;
+            stack.pop();
+            expanded.push_str(&body[last..range.start]);
+            expanded.push_str(embedded.trim());
+            last = range.end;
+        }
+        expanded.push_str(&body[last..]);
+        Ok(expanded)
+    }
+}
+
+/// The ranges and targets of the wiki embeds of `body` that may be notes,
+/// their targets having no extension or `.md`
+fn embeds(body: &str) -> Vec<(Range<usize>, String)> {
+    Parser::new_ext(body, DocumentParserOptions::default().0)
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::Start(Tag::Image {
+                link_type: LinkType::WikiLink { .. },
+                dest_url,
+                ..
+            }) => {
+                // the range of a wiki embed may end before its closing brackets
+                let end = body[range.start..].find("]]")? + range.start + 2;
+                Some((range.start..end, dest_url.into_string()))
+            }
+            _ => None,
+        })
+        .filter(|(_, target)| {
+            let note = target.split('#').next().unwrap_or_default();
+            match note.rsplit_once('.') {
+                Some((_, extension)) => extension.eq_ignore_ascii_case("md"),
+                None => true,
+            }
+        })
+        .collect()
+}
+
+/// The range of the section under the heading with the text `heading`, up to
+/// the next heading of the same or a higher level
+fn section(body: &str, heading: &str) -> Option<Range<usize>> {
+    let mut headings: Vec<(HeadingLevel, Range<usize>, String)> = Vec::new();
+    let mut current: Option<(HeadingLevel, Range<usize>, String)> = None;
+    for (event, range) in
+        Parser::new_ext(body, DocumentParserOptions::default().0).into_offset_iter()
+    {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                current = Some((level, range, String::new()))
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, _, content)) = &mut current {
+                    content.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => headings.extend(current.take()),
+            _ => {}
+        }
+    }
+    let position = headings
+        .iter()
+        .position(|(_, _, content)| content.trim().eq_ignore_ascii_case(heading.trim()))?;
+    let (level, range, _) = &headings[position];
+    let end = headings[position + 1..]
+        .iter()
+        .find(|(other, _, _)| other <= level)
+        .map_or(body.len(), |(_, range, _)| range.start);
+    Some(range.end..end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let mut targets = LinkTargets::default();
+        for (id, content) in [
+            ("book", "# Book\n\n![[intro]]\n\n![[method#Setup]]\n"),
+            (
+                "intro",
+                "---\ntitle: Intro\n---\nIntro text, see ![[book]].\n",
+            ),
+            (
+                "method",
+                "# Method\n\n## Setup\n\nA rig. ![[a.png]]\n\n### Parts\n\nscrews\n\n## Results\n\nnone\n",
+            ),
+        ] {
+            let path = temp.path().join(format!("{id}.md"));
+            std::fs::write(&path, content).unwrap();
+            targets.insert(DocumentId(id.to_owned()), path);
+        }
+
+        let transclusion = Transclusion::new(&targets, FrontMatterFormat::Yaml);
+        let book = DocumentId("book".to_owned());
+        let expanded = transclusion
+            .expand(
+                &book,
+                "# Book\n\n![[intro]]\n\n![[method#setup]] and ![[missing]]\n",
+            )
+            .unwrap();
+        assert_eq!(
+            expanded,
+            "# Book\n\nIntro text, see ![[book]].\n\nA rig. ![[a.png]]\n\n### Parts\n\nscrews and ![[missing]]\n"
+        );
+    }
+
+    #[test]
+    fn test_expand_redacting() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let mut targets = LinkTargets::default();
+        let path = temp.path().join("notes.md");
+        std::fs::write(
+            &path,
+            "# Notes\n\n%%private%%\n\n## Secret\n\nTOPSECRET text\n\n%%/private%%\n\n## Open\n\nopen text\n",
+        )
+        .unwrap();
+        targets.insert(DocumentId("notes".to_owned()), path);
+
+        let transclusion = Transclusion::new(&targets, FrontMatterFormat::Yaml).redacting(true);
+        let book = DocumentId("book".to_owned());
+        let expanded = transclusion
+            .expand(&book, "![[notes#Secret]]\n\n![[notes#Open]]\n")
+            .unwrap();
+        assert!(!expanded.contains("TOPSECRET"), "{expanded}");
+        assert!(expanded.contains("open text"), "{expanded}");
+    }
+}
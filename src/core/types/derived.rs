@@ -0,0 +1,81 @@
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use sql_minifier::macros::minify_sql as sql;
+
+use crate::core::db::{DbInsert, DbList};
+use crate::core::types::document::DocumentId;
+use crate::result::Result;
+
+/// A row derived from a document by an index hook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentDerived {
+    pub id: i64,
+    pub document_id: DocumentId,
+    /// the name of the hook that derived the row
+    pub hook: String,
+    pub data: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewDocumentDerived {
+    pub document_id: DocumentId,
+    pub hook: String,
+    pub data: serde_json::Value,
+}
+
+impl DbInsert<NewDocumentDerived, i64> for DocumentDerived {
+    fn insert(db: &mut rusqlite::Connection, values: &[NewDocumentDerived]) -> Result<Vec<i64>> {
+        let tx = db.savepoint()?;
+        let mut ids = Vec::with_capacity(values.len());
+        {
+            let mut query = tx.prepare(sql!(
+                r#"
+                insert into document_derived (
+                    document_id,
+                    hook,
+                    data
+                ) values (
+                    ?1,
+                    ?2,
+                    jsonb(?3)
+                ) returning id;
+            "#
+            ))?;
+            for row in values {
+                ids.push(
+                    query.query_row(params![row.document_id, row.hook, row.data], |r| r.get(0))?,
+                );
+            }
+        }
+        tx.commit()?;
+        Ok(ids)
+    }
+}
+
+impl DbList<DocumentDerived> for DocumentDerived {
+    fn list(db: &rusqlite::Connection) -> Result<Vec<DocumentDerived>> {
+        db.prepare(sql!(
+            r#"
+            select
+                id,
+                document_id,
+                hook,
+                json(data)
+            from
+                document_derived
+            order by
+                id
+        "#
+        ))?
+        .query_map([], |r| {
+            Ok(DocumentDerived {
+                id: r.get(0)?,
+                document_id: r.get(1)?,
+                hook: r.get(2)?,
+                data: r.get(3)?,
+            })
+        })?
+        .map(|f| f.map_err(From::from))
+        .collect()
+    }
+}
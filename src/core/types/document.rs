@@ -18,7 +18,7 @@ use sql_minifier::macros::minify_sql as sql;
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub struct DocumentPath(pub PathBuf);
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct DocumentId(pub String);
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
@@ -64,10 +64,52 @@ impl Document {
     }
 }
 
+/// The columns of a document that are cheap to read, everything but its
+/// frontmatter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentSummary {
+    pub id: DocumentId,
+    pub title: String,
+    pub path: DocumentPath,
+    pub hash: u32,
+    pub modified: ModifiedTimestamp,
+    pub created: CreatedTimestamp,
+}
+
 ////////////////////////////////////////////////////////////
 // Crud trait implementations
 ////////////////////////////////////////////////////////////
 
+impl DbList<DocumentSummary> for DocumentSummary {
+    fn list(db: &rusqlite::Connection) -> Result<Vec<DocumentSummary>> {
+        db.prepare_cached(sql!(
+            r#"
+                select
+                    id,
+                    title,
+                    path,
+                    hash,
+                    modified,
+                    created
+                from
+                    document
+                "#
+        ))?
+        .query_map([], |r| {
+            Ok(DocumentSummary {
+                id: r.get(0)?,
+                title: r.get(1)?,
+                path: r.get(2)?,
+                hash: r.get(3)?,
+                modified: r.get(4)?,
+                created: r.get(5)?,
+            })
+        })?
+        .map(|f| f.map_err(From::from))
+        .collect::<Result<Vec<DocumentSummary>>>()
+    }
+}
+
 impl DbList<Document> for Document {
     fn list(db: &rusqlite::Connection) -> Result<Vec<Document>> {
         db.prepare(sql!(
@@ -132,11 +174,44 @@ impl DbGet<DocumentId, Document> for Document {
     }
 }
 
+impl DbGet<DocumentPath, Document> for Document {
+    fn get(db: &mut rusqlite::Connection, path: &DocumentPath) -> Result<Document> {
+        Ok(db
+            .prepare(sql!(
+                r#"
+            select
+                id,
+                title,
+                path,
+                hash,
+                modified,
+                created,
+                json(frontmatter) as frontmatter
+            from
+                document
+            where
+                path = ?
+        "#
+            ))?
+            .query_row([path], |r| {
+                Ok(Document::new(
+                    r.get(0)?,
+                    r.get(1)?,
+                    r.get(2)?,
+                    r.get(3)?,
+                    r.get(4)?,
+                    r.get(5)?,
+                    r.get(6)?,
+                ))
+            })?)
+    }
+}
+
 impl DbInsert<Document, DocumentId> for Document {
     fn insert(db: &mut rusqlite::Connection, values: &[Document]) -> Result<Vec<DocumentId>> {
         log::debug!("inserting {} documents", values.len());
         let mut ids = Vec::with_capacity(values.len());
-        let tx = db.transaction()?;
+        let tx = db.savepoint()?;
         {
             let query_str = sql!(
                 r#"
@@ -153,7 +228,7 @@ impl DbInsert<Document, DocumentId> for Document {
                 );
                 "#
             );
-            let mut query = tx.prepare(query_str)?;
+            let mut query = tx.prepare_cached(query_str)?;
 
             for d in values {
                 query.execute(params![
@@ -178,7 +253,7 @@ impl DbUpdate<Document, DocumentId> for Document {
     fn update(db: &mut rusqlite::Connection, values: &[Document]) -> Result<Vec<DocumentId>> {
         log::debug!("upserting {} documents", values.len());
         let mut ids = Vec::with_capacity(values.len());
-        let tx = db.transaction()?;
+        let tx = db.savepoint()?;
         {
             let query_str = sql!(
                 r#"
@@ -224,7 +299,7 @@ impl DbUpdate<Document, DocumentId> for Document {
 
 impl DbDelete<DocumentId> for Document {
     fn delete(db: &mut rusqlite::Connection, ids: &[DocumentId]) -> Result<()> {
-        let tx = db.transaction()?;
+        let tx = db.savepoint()?;
         {
             let query_str = sql!(r#"delete from document where id = ?1"#);
             let mut query = tx.prepare(query_str)?;
@@ -0,0 +1,145 @@
+use jiff::Timestamp;
+use rusqlite::{
+    ToSql, params,
+    types::{FromSql, FromSqlError, ToSqlOutput},
+};
+use serde::{Deserialize, Serialize};
+use sql_minifier::macros::minify_sql as sql;
+
+use crate::core::db::{DbInsert, DbQuery};
+use crate::core::types::document::DocumentId;
+use crate::result::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    DocumentAdded,
+    DocumentUpdated,
+    DocumentRemoved,
+    LinkAdded,
+    LinkRemoved,
+}
+
+impl EventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventKind::DocumentAdded => "document_added",
+            EventKind::DocumentUpdated => "document_updated",
+            EventKind::DocumentRemoved => "document_removed",
+            EventKind::LinkAdded => "link_added",
+            EventKind::LinkRemoved => "link_removed",
+        }
+    }
+}
+
+impl std::fmt::Display for EventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A change made to the index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    pub id: i64,
+    pub timestamp: Timestamp,
+    pub kind: EventKind,
+    pub document_id: DocumentId,
+    /// details of the event, the path of a document or the target of a link
+    pub data: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewEvent {
+    pub timestamp: Timestamp,
+    pub kind: EventKind,
+    pub document_id: DocumentId,
+    pub data: serde_json::Value,
+}
+
+impl DbInsert<NewEvent, i64> for Event {
+    fn insert(db: &mut rusqlite::Connection, values: &[NewEvent]) -> Result<Vec<i64>> {
+        let tx = db.savepoint()?;
+        let mut ids = Vec::with_capacity(values.len());
+        {
+            let mut query = tx.prepare(sql!(
+                r#"
+                insert into event (
+                    timestamp,
+                    kind,
+                    document_id,
+                    data
+                ) values (
+                    ?1,
+                    ?2,
+                    ?3,
+                    jsonb(?4)
+                ) returning id;
+            "#
+            ))?;
+            for e in values {
+                ids.push(
+                    query.query_row(params![e.timestamp, e.kind, e.document_id, e.data], |r| {
+                        r.get(0)
+                    })?,
+                );
+            }
+        }
+        tx.commit()?;
+        Ok(ids)
+    }
+}
+
+/// The events at or after a point in time, oldest first
+impl DbQuery<Event, Timestamp> for Event {
+    fn list(db: &rusqlite::Connection, since: Timestamp) -> Result<Vec<Event>> {
+        db.prepare(sql!(
+            r#"
+            select
+                id,
+                timestamp,
+                kind,
+                document_id,
+                json(data)
+            from
+                event
+            where
+                timestamp >= ?1
+            order by
+                id
+        "#
+        ))?
+        .query_map([since], |r| {
+            Ok(Event {
+                id: r.get(0)?,
+                timestamp: r.get(1)?,
+                kind: r.get(2)?,
+                document_id: r.get(3)?,
+                data: r.get(4)?,
+            })
+        })?
+        .map(|f| f.map_err(From::from))
+        .collect()
+    }
+}
+
+impl ToSql for EventKind {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        self.as_str().to_sql()
+    }
+}
+
+impl FromSql for EventKind {
+    fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        match value.as_str()? {
+            "document_added" => Ok(EventKind::DocumentAdded),
+            "document_updated" => Ok(EventKind::DocumentUpdated),
+            "document_removed" => Ok(EventKind::DocumentRemoved),
+            "link_added" => Ok(EventKind::LinkAdded),
+            "link_removed" => Ok(EventKind::LinkRemoved),
+            other => Err(FromSqlError::Other(
+                format!("unknown event kind: {other}").into(),
+            )),
+        }
+    }
+}
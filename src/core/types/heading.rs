@@ -15,6 +15,8 @@ pub struct DocumentHeading {
     pub id: i64,
     pub document_id: DocumentId,
     pub content: String,
+    /// the id of its `{#id}` attribute, or the slug of its content
+    pub anchor: String,
     pub metadata: serde_json::Value,
     pub range_start: usize,
     pub range_end: usize,
@@ -25,6 +27,8 @@ pub struct NewDocumentHeading {
     pub document_id: DocumentId,
     pub content: String,
     pub level: u8,
+    /// the id of its `{#id}` attribute, or the slug of its content
+    pub anchor: String,
     pub metadata: serde_json::Value,
     pub range_start: usize,
     pub range_end: usize,
@@ -32,15 +36,16 @@ pub struct NewDocumentHeading {
 
 impl DbInsert<NewDocumentHeading, i64> for DocumentHeading {
     fn insert(db: &mut rusqlite::Connection, headings: &[NewDocumentHeading]) -> Result<Vec<i64>> {
-        let tx = db.transaction()?;
+        let tx = db.savepoint()?;
         let mut ids = Vec::with_capacity(headings.len());
         {
-            let mut query = tx.prepare(sql!(
+            let mut query = tx.prepare_cached(sql!(
                 r#"
                 insert into document_heading (
                     document_id,
                     content,
                     level,
+                    anchor,
                     metadata,
                     range_start,
                     range_end
@@ -48,9 +53,10 @@ impl DbInsert<NewDocumentHeading, i64> for DocumentHeading {
                     ?1,
                     ?2,
                     ?3,
-                    jsonb(?4),
-                    ?5,
-                    ?6
+                    ?4,
+                    jsonb(?5),
+                    ?6,
+                    ?7
                 ) returning id;
             "#
             ))?;
@@ -61,6 +67,7 @@ impl DbInsert<NewDocumentHeading, i64> for DocumentHeading {
                         h.document_id,
                         h.content,
                         h.level,
+                        h.anchor,
                         h.metadata,
                         h.range_start,
                         h.range_end,
@@ -83,6 +90,7 @@ impl DbList<DocumentHeading> for DocumentHeading {
                 id,
                 document_id,
                 content,
+                anchor,
                 json(metadata) as metadata,
                 range_start,
                 range_end
@@ -95,9 +103,10 @@ impl DbList<DocumentHeading> for DocumentHeading {
                 id: r.get(0)?,
                 document_id: r.get(1)?,
                 content: r.get(2)?,
-                metadata: r.get(3)?,
-                range_start: r.get(4)?,
-                range_end: r.get(5)?,
+                anchor: r.get(3)?,
+                metadata: r.get(4)?,
+                range_start: r.get(5)?,
+                range_end: r.get(6)?,
             })
         })?
         .map(|f| f.map_err(From::from))
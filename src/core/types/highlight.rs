@@ -0,0 +1,95 @@
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use sql_minifier::macros::minify_sql as sql;
+
+use crate::core::db::{DbInsert, DbQuery};
+use crate::core::types::document::DocumentId;
+use crate::result::Result;
+
+/// The `==highlighted==` text of a document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentHighlight {
+    pub id: i64,
+    pub document_id: DocumentId,
+    /// the highlighted text, without the delimiters
+    pub content: String,
+    pub range_start: usize,
+    pub range_end: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewDocumentHighlight {
+    pub document_id: DocumentId,
+    pub content: String,
+    pub range_start: usize,
+    pub range_end: usize,
+}
+
+impl DbInsert<NewDocumentHighlight, i64> for DocumentHighlight {
+    fn insert(db: &mut rusqlite::Connection, values: &[NewDocumentHighlight]) -> Result<Vec<i64>> {
+        let tx = db.savepoint()?;
+        let mut ids = Vec::with_capacity(values.len());
+        {
+            let mut query = tx.prepare_cached(sql!(
+                r#"
+                insert into document_highlight (
+                    document_id,
+                    content,
+                    range_start,
+                    range_end
+                ) values (
+                    ?1,
+                    ?2,
+                    ?3,
+                    ?4
+                ) returning id;
+            "#
+            ))?;
+            for h in values {
+                ids.push(query.query_row(
+                    params![h.document_id, h.content, h.range_start, h.range_end],
+                    |r| r.get(0),
+                )?);
+            }
+        }
+        tx.commit()?;
+        Ok(ids)
+    }
+}
+
+/// The highlights of a document, or of every document if none is given, in
+/// the order they are written
+impl DbQuery<DocumentHighlight, Option<&DocumentId>> for DocumentHighlight {
+    fn list(
+        db: &rusqlite::Connection,
+        document: Option<&DocumentId>,
+    ) -> Result<Vec<DocumentHighlight>> {
+        db.prepare(sql!(
+            r#"
+            select
+                id,
+                document_id,
+                content,
+                range_start,
+                range_end
+            from
+                document_highlight
+            where
+                ?1 is null or document_id = ?1
+            order by
+                document_id, range_start
+        "#
+        ))?
+        .query_map([document], |r| {
+            Ok(DocumentHighlight {
+                id: r.get(0)?,
+                document_id: r.get(1)?,
+                content: r.get(2)?,
+                range_start: r.get(3)?,
+                range_end: r.get(4)?,
+            })
+        })?
+        .map(|f| f.map_err(From::from))
+        .collect()
+    }
+}
@@ -0,0 +1,94 @@
+use jiff::{SignedDuration, Timestamp};
+use rusqlite::{OptionalExtension, params};
+use serde::{Deserialize, Serialize};
+use sql_minifier::macros::minify_sql as sql;
+
+use crate::core::db::DbInsert;
+use crate::result::Result;
+
+/// A completed index run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexRun {
+    pub id: i64,
+    pub started: Timestamp,
+    pub finished: Timestamp,
+    /// the number of documents added, updated and removed
+    pub changes: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewIndexRun {
+    pub started: Timestamp,
+    pub finished: Timestamp,
+    pub changes: usize,
+}
+
+impl IndexRun {
+    pub fn duration(&self) -> SignedDuration {
+        self.finished.duration_since(self.started)
+    }
+
+    /// The most recent index run, if the collection has been indexed
+    pub fn last(db: &rusqlite::Connection) -> Result<Option<IndexRun>> {
+        Ok(db
+            .prepare(sql!(
+                r#"
+                select
+                    id,
+                    started,
+                    finished,
+                    changes
+                from
+                    index_run
+                order by
+                    id desc
+                limit 1
+            "#
+            ))?
+            .query_row([], |r| {
+                Ok(IndexRun {
+                    id: r.get(0)?,
+                    started: r.get(1)?,
+                    finished: r.get(2)?,
+                    changes: r.get(3)?,
+                })
+            })
+            .optional()?)
+    }
+
+    /// The number of completed index runs
+    pub fn count(db: &rusqlite::Connection) -> Result<usize> {
+        Ok(db.query_row(sql!("select count(*) from index_run"), [], |r| r.get(0))?)
+    }
+}
+
+impl DbInsert<NewIndexRun, i64> for IndexRun {
+    fn insert(db: &mut rusqlite::Connection, values: &[NewIndexRun]) -> Result<Vec<i64>> {
+        let tx = db.savepoint()?;
+        let mut ids = Vec::with_capacity(values.len());
+        {
+            let mut query = tx.prepare(sql!(
+                r#"
+                insert into index_run (
+                    started,
+                    finished,
+                    changes
+                ) values (
+                    ?1,
+                    ?2,
+                    ?3
+                ) returning id;
+            "#
+            ))?;
+            for run in values {
+                ids.push(
+                    query.query_row(params![run.started, run.finished, run.changes], |r| {
+                        r.get(0)
+                    })?,
+                );
+            }
+        }
+        tx.commit()?;
+        Ok(ids)
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     core::{
-        db::{DbInsert, DbList},
+        db::{DbInsert, DbList, DbQuery},
         types::{RangeEnd, RangeStart, document::DocumentId},
     },
     result::Result,
@@ -12,9 +12,9 @@ use sql_minifier::macros::minify_sql as sql;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DocumentLinkId(i64);
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DocumentLinkSource(DocumentId);
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DocumentLinkTarget(DocumentId);
 
 /// A link from one document to another
@@ -25,6 +25,12 @@ pub struct DocumentLink {
     pub to: Option<DocumentLinkTarget>,
     pub range_start: RangeStart,
     pub range_end: RangeEnd,
+    /// range of the paragraph or list item containing the link
+    pub block_start: Option<RangeStart>,
+    pub block_end: Option<RangeEnd>,
+    /// the normalized url of a link to an external page, see
+    /// [`normalize_url`](crate::core::url::normalize_url)
+    pub url: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,26 +39,52 @@ pub struct NewDocumentLink {
     pub to: Option<DocumentLinkTarget>,
     pub range_start: RangeStart,
     pub range_end: RangeEnd,
+    /// range of the paragraph or list item containing the link
+    pub block_start: Option<RangeStart>,
+    pub block_end: Option<RangeEnd>,
+    /// the normalized url of a link to an external page, see
+    /// [`normalize_url`](crate::core::url::normalize_url)
+    pub url: Option<String>,
+}
+
+/// A link as found in a document, before its target is resolved to a document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnresolvedLink {
+    pub from: DocumentLinkSource,
+    /// unresolved link target, might or might not map to a document_id
+    pub to: String,
+    pub range_start: RangeStart,
+    pub range_end: RangeEnd,
+    /// range of the block containing the link
+    pub block: Option<std::ops::Range<usize>>,
+    /// whether the link is a wikilink, only those may produce stubs
+    pub wiki: bool,
 }
 
 impl DbInsert<NewDocumentLink, i64> for DocumentLink {
     fn insert(db: &mut rusqlite::Connection, values: &[NewDocumentLink]) -> Result<Vec<i64>> {
         let mut ids = Vec::with_capacity(values.len());
 
-        let tx = db.transaction()?;
+        let tx = db.savepoint()?;
         {
-            let mut query = tx.prepare(sql!(
+            let mut query = tx.prepare_cached(sql!(
                 r#"
                 insert into document_link (
                     from_id,
                     to_id,
                     range_start,
-                    range_end
+                    range_end,
+                    block_start,
+                    block_end,
+                    url
                 ) values (
                     ?1,
                     ?2,
                     ?3,
-                    ?4
+                    ?4,
+                    ?5,
+                    ?6,
+                    ?7
                 ) returning id;
             "#
             ))?;
@@ -61,9 +93,23 @@ impl DbInsert<NewDocumentLink, i64> for DocumentLink {
                 to,
                 range_start,
                 range_end,
+                block_start,
+                block_end,
+                url,
             } in values
             {
-                ids.push(query.query_row(params![from, to, range_start, range_end], |r| r.get(0))?);
+                ids.push(query.query_row(
+                    params![
+                        from,
+                        to,
+                        range_start,
+                        range_end,
+                        block_start,
+                        block_end,
+                        url
+                    ],
+                    |r| r.get(0),
+                )?);
             }
         }
         tx.commit()?;
@@ -81,6 +127,46 @@ impl DbList<DocumentLinkId> for DocumentLink {
     }
 }
 
+/// The links to the page with the normalized url, in the order they are
+/// written
+impl DbQuery<DocumentLink, &str> for DocumentLink {
+    fn list(db: &rusqlite::Connection, url: &str) -> Result<Vec<DocumentLink>> {
+        db.prepare(sql!(
+            r#"
+            select
+                id,
+                from_id,
+                to_id,
+                range_start,
+                range_end,
+                block_start,
+                block_end,
+                url
+            from
+                document_link
+            where
+                url = ?1
+            order by
+                from_id, range_start
+        "#
+        ))?
+        .query_map([url], |r| {
+            Ok(DocumentLink {
+                id: r.get(0)?,
+                from: r.get(1)?,
+                to: r.get(2)?,
+                range_start: r.get(3)?,
+                range_end: r.get(4)?,
+                block_start: r.get(5)?,
+                block_end: r.get(6)?,
+                url: r.get(7)?,
+            })
+        })?
+        .map(|f| f.map_err(From::from))
+        .collect()
+    }
+}
+
 impl ToSql for DocumentLinkId {
     fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
         self.0.to_sql()
@@ -98,6 +184,11 @@ impl From<DocumentId> for DocumentLinkSource {
         Self(value)
     }
 }
+impl From<DocumentLinkSource> for DocumentId {
+    fn from(value: DocumentLinkSource) -> Self {
+        value.0
+    }
+}
 impl From<DocumentId> for DocumentLinkTarget {
     fn from(value: DocumentId) -> Self {
         Self(value)
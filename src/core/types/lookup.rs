@@ -0,0 +1,89 @@
+use rusqlite::{
+    ToSql, params,
+    types::{FromSql, FromSqlError, ToSqlOutput},
+};
+use serde::{Deserialize, Serialize};
+use sql_minifier::macros::minify_sql as sql;
+
+use crate::core::db::{DbInsert, DbList};
+use crate::core::types::document::DocumentId;
+use crate::result::Result;
+
+/// What a name of a document is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LookupKind {
+    Title,
+    Alias,
+    Heading,
+}
+
+impl LookupKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LookupKind::Title => "title",
+            LookupKind::Alias => "alias",
+            LookupKind::Heading => "heading",
+        }
+    }
+}
+
+/// A name a document can be found by
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocumentLookup {
+    pub document_id: DocumentId,
+    pub kind: LookupKind,
+    pub text: String,
+}
+
+impl DbInsert<DocumentLookup, ()> for DocumentLookup {
+    fn insert(db: &mut rusqlite::Connection, values: &[DocumentLookup]) -> Result<Vec<()>> {
+        let tx = db.savepoint()?;
+        {
+            let mut query = tx.prepare_cached(sql!(
+                "insert into document_lookup (document_id, kind, text) values (?1, ?2, ?3)"
+            ))?;
+            for lookup in values {
+                query.execute(params![lookup.document_id, lookup.kind, lookup.text])?;
+            }
+        }
+        tx.commit()?;
+        Ok(vec![(); values.len()])
+    }
+}
+
+impl DbList<DocumentLookup> for DocumentLookup {
+    fn list(db: &rusqlite::Connection) -> Result<Vec<DocumentLookup>> {
+        db.prepare(sql!(
+            "select document_id, kind, text from document_lookup order by id"
+        ))?
+        .query_map([], |r| {
+            Ok(DocumentLookup {
+                document_id: r.get(0)?,
+                kind: r.get(1)?,
+                text: r.get(2)?,
+            })
+        })?
+        .map(|f| f.map_err(From::from))
+        .collect()
+    }
+}
+
+impl ToSql for LookupKind {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        self.as_str().to_sql()
+    }
+}
+
+impl FromSql for LookupKind {
+    fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        match value.as_str()? {
+            "title" => Ok(LookupKind::Title),
+            "alias" => Ok(LookupKind::Alias),
+            "heading" => Ok(LookupKind::Heading),
+            other => Err(FromSqlError::Other(
+                format!("unknown lookup kind: {other}").into(),
+            )),
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use sql_minifier::macros::minify_sql as sql;
+
+use crate::core::db::{DbInsert, DbQuery};
+use crate::core::types::document::DocumentId;
+use crate::result::Result;
+
+/// An `@name` mention in a document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentMention {
+    pub id: i64,
+    pub document_id: DocumentId,
+    /// the name mentioned, without the `@`
+    pub name: String,
+    pub range_start: usize,
+    pub range_end: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewDocumentMention {
+    pub document_id: DocumentId,
+    pub name: String,
+    pub range_start: usize,
+    pub range_end: usize,
+}
+
+impl DbInsert<NewDocumentMention, i64> for DocumentMention {
+    fn insert(db: &mut rusqlite::Connection, values: &[NewDocumentMention]) -> Result<Vec<i64>> {
+        let tx = db.savepoint()?;
+        let mut ids = Vec::with_capacity(values.len());
+        {
+            let mut query = tx.prepare_cached(sql!(
+                r#"
+                insert into document_mention (
+                    document_id,
+                    name,
+                    range_start,
+                    range_end
+                ) values (
+                    ?1,
+                    ?2,
+                    ?3,
+                    ?4
+                ) returning id;
+            "#
+            ))?;
+            for m in values {
+                ids.push(query.query_row(
+                    params![m.document_id, m.name, m.range_start, m.range_end],
+                    |r| r.get(0),
+                )?);
+            }
+        }
+        tx.commit()?;
+        Ok(ids)
+    }
+}
+
+/// The mentions of any of the names, ignoring case, in the order they are
+/// written
+impl DbQuery<DocumentMention, &[String]> for DocumentMention {
+    fn list(db: &rusqlite::Connection, names: &[String]) -> Result<Vec<DocumentMention>> {
+        db.prepare(sql!(
+            r#"
+            select
+                id,
+                document_id,
+                name,
+                range_start,
+                range_end
+            from
+                document_mention
+            where
+                name collate nocase in (select value from json_each(?1))
+            order by
+                document_id, range_start
+        "#
+        ))?
+        .query_map([serde_json::to_string(names)?], |r| {
+            Ok(DocumentMention {
+                id: r.get(0)?,
+                document_id: r.get(1)?,
+                name: r.get(2)?,
+                range_start: r.get(3)?,
+                range_end: r.get(4)?,
+            })
+        })?
+        .map(|f| f.map_err(From::from))
+        .collect()
+    }
+}
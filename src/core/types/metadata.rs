@@ -0,0 +1,107 @@
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use sql_minifier::macros::minify_sql as sql;
+
+use crate::core::db::{DbInsert, DbList};
+use crate::core::types::document::DocumentId;
+use crate::core::types::{RangeEnd, RangeStart};
+use crate::result::Result;
+
+/// A diagnostic reported by a plugin
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PluginDiagnostic {
+    /// byte range in the document content after the frontmatter
+    pub start: RangeStart,
+    pub end: RangeEnd,
+    pub message: String,
+}
+
+/// The output of a plugin for a document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentMetadata {
+    pub id: i64,
+    pub document_id: DocumentId,
+    pub plugin: String,
+    pub metadata: serde_json::Value,
+    pub diagnostics: Vec<PluginDiagnostic>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewDocumentMetadata {
+    pub document_id: DocumentId,
+    pub plugin: String,
+    pub metadata: serde_json::Value,
+    pub diagnostics: Vec<PluginDiagnostic>,
+}
+
+impl DbInsert<NewDocumentMetadata, i64> for DocumentMetadata {
+    fn insert(db: &mut rusqlite::Connection, values: &[NewDocumentMetadata]) -> Result<Vec<i64>> {
+        let tx = db.savepoint()?;
+        let mut ids = Vec::with_capacity(values.len());
+        {
+            let mut query = tx.prepare(sql!(
+                r#"
+                insert into document_metadata (
+                    document_id,
+                    plugin,
+                    metadata,
+                    diagnostics
+                ) values (
+                    ?1,
+                    ?2,
+                    jsonb(?3),
+                    jsonb(?4)
+                ) returning id;
+            "#
+            ))?;
+            for row in values {
+                let diagnostics = serde_json::to_value(&row.diagnostics)?;
+                ids.push(query.query_row(
+                    params![row.document_id, row.plugin, row.metadata, diagnostics],
+                    |r| r.get(0),
+                )?);
+            }
+        }
+        tx.commit()?;
+        Ok(ids)
+    }
+}
+
+impl DbList<DocumentMetadata> for DocumentMetadata {
+    fn list(db: &rusqlite::Connection) -> Result<Vec<DocumentMetadata>> {
+        db.prepare(sql!(
+            r#"
+            select
+                id,
+                document_id,
+                plugin,
+                json(metadata),
+                json(diagnostics)
+            from
+                document_metadata
+            order by
+                id
+        "#
+        ))?
+        .query_map([], |r| {
+            Ok((
+                r.get(0)?,
+                r.get(1)?,
+                r.get(2)?,
+                r.get(3)?,
+                r.get::<_, serde_json::Value>(4)?,
+            ))
+        })?
+        .map(|row| {
+            let (id, document_id, plugin, metadata, diagnostics) = row?;
+            Ok(DocumentMetadata {
+                id,
+                document_id,
+                plugin,
+                metadata,
+                diagnostics: serde_json::from_value(diagnostics)?,
+            })
+        })
+        .collect()
+    }
+}
@@ -1,6 +1,16 @@
+pub mod derived;
 pub mod document;
+pub mod event;
 pub mod heading;
+pub mod highlight;
+pub mod index_run;
 pub mod link;
+pub mod lookup;
+pub mod mention;
+pub mod metadata;
+pub mod quote;
+pub mod source;
+pub mod stub;
 pub mod tag;
 pub mod task;
 
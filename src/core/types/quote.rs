@@ -0,0 +1,108 @@
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use sql_minifier::macros::minify_sql as sql;
+
+use crate::core::db::{DbInsert, DbQuery};
+use crate::core::types::document::DocumentId;
+use crate::result::Result;
+
+/// A block quote of a document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentQuote {
+    pub id: i64,
+    pub document_id: DocumentId,
+    /// the text of the quote, without its attribution
+    pub content: String,
+    /// who the quote is attributed to, the text of a `— author` last line
+    pub attribution: Option<String>,
+    pub range_start: usize,
+    pub range_end: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewDocumentQuote {
+    pub document_id: DocumentId,
+    pub content: String,
+    pub attribution: Option<String>,
+    pub range_start: usize,
+    pub range_end: usize,
+}
+
+impl DbInsert<NewDocumentQuote, i64> for DocumentQuote {
+    fn insert(db: &mut rusqlite::Connection, values: &[NewDocumentQuote]) -> Result<Vec<i64>> {
+        let tx = db.savepoint()?;
+        let mut ids = Vec::with_capacity(values.len());
+        {
+            let mut query = tx.prepare_cached(sql!(
+                r#"
+                insert into document_quote (
+                    document_id,
+                    content,
+                    attribution,
+                    range_start,
+                    range_end
+                ) values (
+                    ?1,
+                    ?2,
+                    ?3,
+                    ?4,
+                    ?5
+                ) returning id;
+            "#
+            ))?;
+            for q in values {
+                ids.push(query.query_row(
+                    params![
+                        q.document_id,
+                        q.content,
+                        q.attribution,
+                        q.range_start,
+                        q.range_end
+                    ],
+                    |r| r.get(0),
+                )?);
+            }
+        }
+        tx.commit()?;
+        Ok(ids)
+    }
+}
+
+/// The quotes of a document, or of every document if none is given, in the
+/// order they are written
+impl DbQuery<DocumentQuote, Option<&DocumentId>> for DocumentQuote {
+    fn list(
+        db: &rusqlite::Connection,
+        document: Option<&DocumentId>,
+    ) -> Result<Vec<DocumentQuote>> {
+        db.prepare(sql!(
+            r#"
+            select
+                id,
+                document_id,
+                content,
+                attribution,
+                range_start,
+                range_end
+            from
+                document_quote
+            where
+                ?1 is null or document_id = ?1
+            order by
+                document_id, range_start
+        "#
+        ))?
+        .query_map([document], |r| {
+            Ok(DocumentQuote {
+                id: r.get(0)?,
+                document_id: r.get(1)?,
+                content: r.get(2)?,
+                attribution: r.get(3)?,
+                range_start: r.get(4)?,
+                range_end: r.get(5)?,
+            })
+        })?
+        .map(|f| f.map_err(From::from))
+        .collect()
+    }
+}
@@ -0,0 +1,84 @@
+use jiff::civil::Date;
+use rusqlite::{OptionalExtension, params};
+use serde::{Deserialize, Serialize};
+use sql_minifier::macros::minify_sql as sql;
+
+use crate::core::db::DbInsert;
+use crate::result::Result;
+
+/// An external page or DOI referenced from the collection, by a link or the
+/// frontmatter url of a note
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Source {
+    /// the normalized url, see [`normalize_url`](crate::core::url::normalize_url)
+    pub url: String,
+    /// the url as it was first written
+    pub original: String,
+    pub first_seen: Date,
+    pub last_seen: Date,
+}
+
+impl Source {
+    /// The source with the normalized url, if it was ever referenced
+    pub fn find(db: &rusqlite::Connection, url: &str) -> Result<Option<Source>> {
+        Ok(db
+            .prepare(sql!(
+                r#"
+                select
+                    url,
+                    original,
+                    first_seen,
+                    last_seen
+                from
+                    source
+                where
+                    url = ?1
+            "#
+            ))?
+            .query_row([url], |r| {
+                Ok(Source {
+                    url: r.get(0)?,
+                    original: r.get(1)?,
+                    first_seen: r.get(2)?,
+                    last_seen: r.get(3)?,
+                })
+            })
+            .optional()?)
+    }
+}
+
+/// Records the sources, widening the first and last seen dates of those
+/// already known
+impl DbInsert<Source, ()> for Source {
+    fn insert(db: &mut rusqlite::Connection, values: &[Source]) -> Result<Vec<()>> {
+        let tx = db.savepoint()?;
+        {
+            let mut query = tx.prepare_cached(sql!(
+                r#"
+                insert into source (
+                    url,
+                    original,
+                    first_seen,
+                    last_seen
+                ) values (
+                    ?1,
+                    ?2,
+                    ?3,
+                    ?4
+                ) on conflict (url) do update set
+                    original = case
+                        when excluded.first_seen < first_seen then excluded.original
+                        else original
+                    end,
+                    first_seen = min(first_seen, excluded.first_seen),
+                    last_seen = max(last_seen, excluded.last_seen);
+            "#
+            ))?;
+            for s in values {
+                query.execute(params![s.url, s.original, s.first_seen, s.last_seen])?;
+            }
+        }
+        tx.commit()?;
+        Ok(vec![(); values.len()])
+    }
+}
@@ -0,0 +1,118 @@
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use sql_minifier::macros::minify_sql as sql;
+
+use crate::core::db::{DbDelete, DbGet, DbInsert, DbList};
+use crate::core::types::document::DocumentId;
+use crate::result::Result;
+
+/// A placeholder for a wikilink target that does not resolve to any document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentStub {
+    pub id: DocumentId,
+    pub title: String,
+    /// documents containing a link to this stub
+    pub referenced_by: Vec<DocumentId>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewDocumentStub {
+    pub id: DocumentId,
+    pub title: String,
+    pub from: DocumentId,
+}
+
+////////////////////////////////////////////////////////////
+// Crud trait implementations
+////////////////////////////////////////////////////////////
+
+fn stub_from_row(r: &rusqlite::Row) -> rusqlite::Result<DocumentStub> {
+    let referenced_by: serde_json::Value = r.get(2)?;
+    Ok(DocumentStub {
+        id: r.get(0)?,
+        title: r.get(1)?,
+        referenced_by: serde_json::from_value(referenced_by).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+    })
+}
+
+impl DbList<DocumentStub> for DocumentStub {
+    fn list(db: &rusqlite::Connection) -> Result<Vec<DocumentStub>> {
+        db.prepare(sql!(
+            r#"
+                select
+                    id,
+                    min(title),
+                    json_group_array(from_id)
+                from
+                    document_stub
+                group by
+                    id
+                order by
+                    id
+            "#
+        ))?
+        .query_map([], stub_from_row)?
+        .map(|f| f.map_err(From::from))
+        .collect::<Result<Vec<DocumentStub>>>()
+    }
+}
+
+impl DbGet<DocumentId, DocumentStub> for DocumentStub {
+    fn get(db: &mut rusqlite::Connection, id: &DocumentId) -> Result<DocumentStub> {
+        Ok(db
+            .prepare(sql!(
+                r#"
+                select
+                    id,
+                    min(title),
+                    json_group_array(from_id)
+                from
+                    document_stub
+                where
+                    id = ?
+                group by
+                    id
+            "#
+            ))?
+            .query_row([id], stub_from_row)?)
+    }
+}
+
+impl DbInsert<NewDocumentStub, ()> for DocumentStub {
+    fn insert(db: &mut rusqlite::Connection, values: &[NewDocumentStub]) -> Result<Vec<()>> {
+        let tx = db.savepoint()?;
+        {
+            // a stub is never created for an id that is already taken by a
+            // real document
+            let mut query = tx.prepare(sql!(
+                r#"
+                insert or ignore into document_stub (id, title, from_id)
+                select ?1, ?2, ?3
+                where not exists (select 1 from document where id = ?1)
+            "#
+            ))?;
+            for NewDocumentStub { id, title, from } in values {
+                query.execute(params![id, title, from])?;
+            }
+        }
+        tx.commit()?;
+
+        Ok(vec![(); values.len()])
+    }
+}
+
+impl DbDelete<DocumentId> for DocumentStub {
+    fn delete(db: &mut rusqlite::Connection, ids: &[DocumentId]) -> Result<()> {
+        let tx = db.savepoint()?;
+        {
+            let mut query = tx.prepare(sql!(r#"delete from document_stub where id = ?1"#))?;
+            for id in ids {
+                query.execute(params![id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
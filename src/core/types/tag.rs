@@ -3,7 +3,9 @@ use serde::{Deserialize, Serialize};
 use sql_minifier::macros::minify_sql as sql;
 
 use crate::core::db::DbInsert;
+use crate::core::tags::parent_tag;
 use crate::core::types::document::DocumentId;
+use crate::core::types::{RangeEnd, RangeStart};
 use crate::result::Result;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,23 +23,33 @@ pub struct NewTag {
 pub struct NewDocumentTag {
     pub document_id: DocumentId,
     pub tag: String,
+    /// byte range of an inline tag in the document, `None` for frontmatter tags
+    pub range_start: Option<RangeStart>,
+    pub range_end: Option<RangeEnd>,
 }
 
 impl DbInsert<NewDocumentTag, ()> for NewDocumentTag {
     fn insert(db: &mut rusqlite::Connection, values: &[NewDocumentTag]) -> Result<Vec<()>> {
-        let tx = db.transaction()?;
+        let tx = db.savepoint()?;
         {
-            let mut insert_tag =
-                tx.prepare(sql!(r#"INSERT OR IGNORE INTO tag (tag) VALUES (?1)"#))?;
+            let mut insert_tag = tx.prepare(sql!(
+                r#"INSERT OR IGNORE INTO tag (tag, parent) VALUES (?1, ?2)"#
+            ))?;
             let mut get_tag_id = tx.prepare(sql!(r#"SELECT id FROM tag WHERE tag = ?1"#))?;
             let mut insert_map = tx.prepare(sql!(
-                r#"INSERT INTO document_tag_map (document_id, tag_id) VALUES (?1, ?2)"#
+                r#"INSERT INTO document_tag_map (document_id, tag_id, range_start, range_end) VALUES (?1, ?2, ?3, ?4)"#
             ))?;
 
-            for NewDocumentTag { document_id, tag } in values {
-                insert_tag.execute(params![tag])?;
+            for NewDocumentTag {
+                document_id,
+                tag,
+                range_start,
+                range_end,
+            } in values
+            {
+                insert_tag.execute(params![tag, parent_tag(tag)])?;
                 let tag_id: i64 = get_tag_id.query_row(params![tag], |r| r.get(0))?;
-                insert_map.execute(params![document_id, tag_id])?;
+                insert_map.execute(params![document_id, tag_id, range_start, range_end])?;
             }
         }
         tx.commit()?;
@@ -1,21 +1,86 @@
-use rusqlite::params;
+use jiff::Timestamp;
+use rusqlite::{
+    ToSql, params,
+    types::{FromSql, FromSqlError, ToSqlOutput},
+};
 use serde::{Deserialize, Serialize};
 use sql_minifier::macros::minify_sql as sql;
 
 use crate::core::{
-    db::DbInsert,
+    db::{DbInsert, DbList},
     types::{RangeEnd, RangeStart, document::DocumentId},
 };
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    /// `- [ ]`
+    Todo,
+    /// `- [/]`
+    InProgress,
+    /// `- [x]`
+    Done,
+    /// `- [-]`
+    Cancelled,
+}
+
+impl TaskStatus {
+    pub fn all() -> [TaskStatus; 4] {
+        [
+            TaskStatus::Todo,
+            TaskStatus::InProgress,
+            TaskStatus::Done,
+            TaskStatus::Cancelled,
+        ]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskStatus::Todo => "todo",
+            TaskStatus::InProgress => "in_progress",
+            TaskStatus::Done => "done",
+            TaskStatus::Cancelled => "cancelled",
+        }
+    }
+
+    /// The checkbox of a task with this status
+    pub fn marker(&self) -> &'static str {
+        match self {
+            TaskStatus::Todo => "[ ]",
+            TaskStatus::InProgress => "[/]",
+            TaskStatus::Done => "[x]",
+            TaskStatus::Cancelled => "[-]",
+        }
+    }
+}
+
+impl std::fmt::Display for TaskStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DocumentTask {
     pub id: i64,
     pub document_id: DocumentId,
     pub parent_id: Option<i64>,
     pub checked: bool,
+    pub status: TaskStatus,
     pub content: String,
     pub range_start: RangeStart,
     pub range_end: RangeEnd,
+    /// due date, resolved from the first date expression in `content`
+    pub due: Option<Timestamp>,
+    /// byte offset of the start of the due date expression in `content`
+    pub due_start: Option<usize>,
+    /// byte offset of the end of the due date expression in `content`
+    pub due_end: Option<usize>,
+    /// the name of the person owning the task, its first `@name` mention
+    pub owner: Option<String>,
+    /// whether the task is an action item, owned or under an `Action items`
+    /// heading
+    pub action_item: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,9 +88,21 @@ pub struct NewDocumentTask {
     pub document_id: DocumentId,
     pub parent_id: Option<i64>,
     pub checked: bool,
+    pub status: TaskStatus,
     pub content: String,
     pub range_start: RangeStart,
     pub range_end: RangeEnd,
+    /// due date, resolved from the first date expression in `content`
+    pub due: Option<Timestamp>,
+    /// byte offset of the start of the due date expression in `content`
+    pub due_start: Option<usize>,
+    /// byte offset of the end of the due date expression in `content`
+    pub due_end: Option<usize>,
+    /// the name of the person owning the task, its first `@name` mention
+    pub owner: Option<String>,
+    /// whether the task is an action item, owned or under an `Action items`
+    /// heading
+    pub action_item: bool,
 }
 
 impl DbInsert<NewDocumentTask, i64> for DocumentTask {
@@ -33,23 +110,35 @@ impl DbInsert<NewDocumentTask, i64> for DocumentTask {
         db: &mut rusqlite::Connection,
         values: &[NewDocumentTask],
     ) -> crate::result::Result<Vec<i64>> {
-        let tx = db.transaction()?;
+        let tx = db.savepoint()?;
         let mut ids = Vec::with_capacity(values.len());
         {
-            let mut query = tx.prepare(sql!(
+            let mut query = tx.prepare_cached(sql!(
                 r#"
                 insert into document_task (
                     document_id,
                     checked,
+                    status,
                     content,
                     range_start,
-                    range_end
+                    range_end,
+                    due,
+                    due_start,
+                    due_end,
+                    owner,
+                    action_item
                 ) values (
                     ?1,
                     ?2,
                     ?3,
                     ?4,
-                    ?5
+                    ?5,
+                    ?6,
+                    ?7,
+                    ?8,
+                    ?9,
+                    ?10,
+                    ?11
                 ) returning id;
             "#
             ))?;
@@ -58,9 +147,15 @@ impl DbInsert<NewDocumentTask, i64> for DocumentTask {
                     params![
                         task.document_id,
                         task.checked,
+                        task.status,
                         task.content,
                         task.range_start,
                         task.range_end,
+                        task.due,
+                        task.due_start,
+                        task.due_end,
+                        task.owner,
+                        task.action_item,
                     ],
                     |r| r.get(0),
                 )?;
@@ -71,3 +166,69 @@ impl DbInsert<NewDocumentTask, i64> for DocumentTask {
         Ok(ids)
     }
 }
+
+impl DbList<DocumentTask> for DocumentTask {
+    fn list(db: &rusqlite::Connection) -> crate::result::Result<Vec<DocumentTask>> {
+        db.prepare(sql!(
+            r#"
+                select
+                    id,
+                    document_id,
+                    parent_id,
+                    checked,
+                    status,
+                    content,
+                    range_start,
+                    range_end,
+                    due,
+                    due_start,
+                    due_end,
+                    owner,
+                    action_item
+                from
+                    document_task
+                order by
+                    document_id, range_start
+            "#
+        ))?
+        .query_map([], |r| {
+            Ok(DocumentTask {
+                id: r.get(0)?,
+                document_id: r.get(1)?,
+                parent_id: r.get(2)?,
+                checked: r.get(3)?,
+                status: r.get(4)?,
+                content: r.get(5)?,
+                range_start: r.get(6)?,
+                range_end: r.get(7)?,
+                due: r.get(8)?,
+                due_start: r.get(9)?,
+                due_end: r.get(10)?,
+                owner: r.get(11)?,
+                action_item: r.get(12)?,
+            })
+        })?
+        .map(|f| f.map_err(From::from))
+        .collect()
+    }
+}
+
+impl ToSql for TaskStatus {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        self.as_str().to_sql()
+    }
+}
+
+impl FromSql for TaskStatus {
+    fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        match value.as_str()? {
+            "todo" => Ok(TaskStatus::Todo),
+            "in_progress" => Ok(TaskStatus::InProgress),
+            "done" => Ok(TaskStatus::Done),
+            "cancelled" => Ok(TaskStatus::Cancelled),
+            other => Err(FromSqlError::Other(
+                format!("unknown task status: {other}").into(),
+            )),
+        }
+    }
+}
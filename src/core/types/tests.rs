@@ -2,13 +2,14 @@
 mod crud_tests {
     use crate::core::db::{DB, DbDelete, DbGet, DbInsert, DbList, DbUpdate};
     use crate::core::types::document::{
-        CreatedTimestamp, Document, DocumentId, DocumentPath, ModifiedTimestamp,
+        CreatedTimestamp, Document, DocumentId, DocumentPath, DocumentSummary, ModifiedTimestamp,
     };
     use crate::core::types::heading::{DocumentHeading, NewDocumentHeading};
     use crate::core::types::link::{
         DocumentLink, DocumentLinkSource, DocumentLinkTarget, NewDocumentLink,
     };
-    use crate::core::types::task::{DocumentTask, NewDocumentTask};
+    use crate::core::types::stub::{DocumentStub, NewDocumentStub};
+    use crate::core::types::task::{DocumentTask, NewDocumentTask, TaskStatus};
     use jiff::Timestamp;
     use std::path::PathBuf;
 
@@ -64,6 +65,30 @@ mod crud_tests {
         assert_eq!(retrieved.hash, 99999u32);
     }
 
+    #[test]
+    fn test_document_summary_list() {
+        let mut db = setup_db();
+
+        let doc = Document::new(
+            DocumentId("summary-test".to_string()),
+            "Summary Test".to_string(),
+            DocumentPath(PathBuf::from("/summary/test.md")),
+            4242u32,
+            ModifiedTimestamp(Timestamp::now()),
+            CreatedTimestamp(Timestamp::now()),
+            serde_json::json!({"large": "frontmatter"}),
+        );
+
+        Document::insert(&mut db, &[doc]).expect("Failed to insert document");
+
+        let summaries = DocumentSummary::list(&db).expect("Failed to list summaries");
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].id.0, "summary-test");
+        assert_eq!(summaries[0].title, "Summary Test");
+        assert_eq!(summaries[0].path.0, PathBuf::from("/summary/test.md"));
+        assert_eq!(summaries[0].hash, 4242u32);
+    }
+
     #[test]
     fn test_document_update() {
         let mut db = setup_db();
@@ -156,6 +181,7 @@ mod crud_tests {
             document_id: DocumentId("doc-with-heading".to_string()),
             content: "Test Heading".to_string(),
             level: 2,
+            anchor: "test-heading".to_string(),
             metadata: serde_json::json!({"style": "bold"}),
             range_start: 0,
             range_end: 13,
@@ -168,6 +194,7 @@ mod crud_tests {
         let headings = DocumentHeading::list(&db).expect("Failed to list headings");
         assert_eq!(headings.len(), 1);
         assert_eq!(headings[0].content, "Test Heading");
+        assert_eq!(headings[0].anchor, "test-heading");
         assert_eq!(headings[0].document_id.0, "doc-with-heading");
         assert_eq!(headings[0].metadata["style"], "bold");
         assert_eq!(headings[0].range_start, 0);
@@ -206,6 +233,9 @@ mod crud_tests {
             ))),
             range_start: 10,
             range_end: 25,
+            block_start: Some(0),
+            block_end: Some(40),
+            url: None,
         };
 
         let ids = DocumentLink::insert(&mut db, &[link]).expect("Failed to insert link");
@@ -237,6 +267,9 @@ mod crud_tests {
             to: None,
             range_start: 5,
             range_end: 15,
+            block_start: None,
+            block_end: None,
+            url: None,
         };
 
         let ids =
@@ -244,6 +277,59 @@ mod crud_tests {
         assert_eq!(ids.len(), 1);
     }
 
+    #[test]
+    fn test_stub_insert_and_materialize() {
+        let mut db = setup_db();
+
+        let doc = |id: &str| {
+            Document::new(
+                DocumentId(id.to_string()),
+                id.to_string(),
+                DocumentPath(PathBuf::from(format!("/{}.md", id))),
+                1u32,
+                ModifiedTimestamp(Timestamp::now()),
+                CreatedTimestamp(Timestamp::now()),
+                serde_json::json!({}),
+            )
+        };
+        let stub = |id: &str, from: &str| NewDocumentStub {
+            id: DocumentId(id.to_string()),
+            title: id.to_string(),
+            from: DocumentId(from.to_string()),
+        };
+        Document::insert(&mut db, &[doc("a"), doc("b"), doc("c,d")])
+            .expect("Failed to insert documents");
+
+        // duplicates are ignored and existing documents never become stubs
+        DocumentStub::insert(
+            &mut db,
+            &[
+                stub("missing", "a"),
+                stub("missing", "a"),
+                stub("missing", "b"),
+                stub("missing", "c,d"),
+                stub("b", "a"),
+            ],
+        )
+        .expect("Failed to insert stubs");
+
+        let stubs = DocumentStub::list(&db).expect("Failed to list stubs");
+        assert_eq!(stubs.len(), 1);
+        assert_eq!(stubs[0].id.0, "missing");
+        let mut referenced_by: Vec<&str> = stubs[0]
+            .referenced_by
+            .iter()
+            .map(|id| id.0.as_str())
+            .collect();
+        referenced_by.sort();
+        assert_eq!(referenced_by, ["a", "b", "c,d"]);
+
+        // creating the document removes the stub
+        Document::insert(&mut db, &[doc("missing")]).expect("Failed to insert document");
+        let stubs = DocumentStub::list(&db).expect("Failed to list stubs");
+        assert!(stubs.is_empty());
+    }
+
     #[test]
     fn test_task_insert() {
         let mut db = setup_db();
@@ -264,18 +350,30 @@ mod crud_tests {
             document_id: DocumentId("doc-with-tasks".to_string()),
             parent_id: None,
             checked: false,
+            status: TaskStatus::Todo,
             content: "Unchecked task".to_string(),
             range_start: 0,
             range_end: 14,
+            due: None,
+            due_start: None,
+            due_end: None,
+            owner: None,
+            action_item: false,
         };
 
         let task2 = NewDocumentTask {
             document_id: DocumentId("doc-with-tasks".to_string()),
             parent_id: None,
             checked: true,
+            status: TaskStatus::Done,
             content: "Checked task".to_string(),
             range_start: 15,
             range_end: 27,
+            due: None,
+            due_start: None,
+            due_end: None,
+            owner: None,
+            action_item: false,
         };
 
         let ids = DocumentTask::insert(&mut db, &[task1, task2]).expect("Failed to insert tasks");
@@ -0,0 +1,241 @@
+//! Conversion between the `file://` uris of the language server protocol and
+//! filesystem paths.
+//!
+//! Paths are percent-encoded in uris, with `/` separators. On windows, the
+//! path `C:\notes` is the uri `file:///C:/notes`, editors often writing its
+//! drive letter in lowercase and its colon encoded, `file:///c%3A/notes`, and
+//! the unc path `\\server\share` is the uri `file://server/share`.
+
+use std::path::{Path, PathBuf};
+
+use tower_lsp_server::ls_types::Uri;
+
+use crate::core::relative_path;
+
+/// The filesystem path of a `file://` uri, `None` for other uris
+pub fn uri_to_path(uri: &Uri) -> Option<PathBuf> {
+    parse_file_uri(uri.as_str(), cfg!(windows)).map(PathBuf::from)
+}
+
+/// The `file://` uri of the absolute `path`, `None` if it is not valid
+/// UTF-8
+pub fn path_to_uri(path: &Path) -> Option<Uri> {
+    file_uri(path.to_str()?, cfg!(windows)).parse().ok()
+}
+
+/// The path relative to the collection at `root` of the document at `uri`,
+/// `None` if it is not within the collection
+pub fn uri_to_relative(root: &Path, uri: &Uri) -> Option<PathBuf> {
+    relative_path(root, &uri_to_path(uri)?)
+}
+
+/// The uri of the document at the path `relative` to the collection at
+/// `root`
+pub fn relative_to_uri(root: &Path, relative: &Path) -> Option<Uri> {
+    path_to_uri(&root.join(relative))
+}
+
+/// The filesystem path of the `file://` uri `uri`, written the windows way
+/// when `windows`
+fn parse_file_uri(uri: &str, windows: bool) -> Option<String> {
+    if !uri.get(..5)?.eq_ignore_ascii_case("file:") {
+        return None;
+    }
+    let rest = &uri[5..];
+    let rest = &rest[..rest.find(['?', '#']).unwrap_or(rest.len())];
+    let (host, path) = match rest.strip_prefix("//") {
+        Some(rest) => rest.split_at(rest.find('/').unwrap_or(rest.len())),
+        None => ("", rest),
+    };
+    Some(file_path(&decode(host)?, &decode(path)?, windows))
+}
+
+/// The filesystem path of a `file://` uri of `host` and decoded `path`:
+/// `/c:/notes` is `C:\notes` when `windows`, and a `host` the server of an unc
+/// path
+fn file_path(host: &str, path: &str, windows: bool) -> String {
+    if !windows {
+        return path.to_owned();
+    }
+    let path = path.replace('/', "\\");
+    let bytes = path.as_bytes();
+    let drive =
+        bytes.len() >= 3 && bytes[0] == b'\\' && bytes[1].is_ascii_alphabetic() && bytes[2] == b':';
+    match (host, drive) {
+        ("" | "localhost", true) => path[1..2].to_ascii_uppercase() + &path[2..],
+        ("" | "localhost", false) => path,
+        (host, _) => format!("\\\\{host}{path}"),
+    }
+}
+
+/// The `file://` uri of the absolute `path`, read the windows way when
+/// `windows`
+fn file_uri(path: &str, windows: bool) -> String {
+    if !windows {
+        return format!("file://{}", encode(path));
+    }
+    // verbatim paths, as returned by `std::fs::canonicalize`
+    let path = match path.strip_prefix(r"\\?\") {
+        Some(rest) => match rest.strip_prefix(r"UNC\") {
+            Some(unc) => format!(r"\\{unc}"),
+            None => rest.to_owned(),
+        },
+        None => path.to_owned(),
+    };
+    let path = path.replace('\\', "/");
+    match path.strip_prefix("//") {
+        Some(unc) => {
+            let (host, path) = unc.split_at(unc.find('/').unwrap_or(unc.len()));
+            format!("file://{}{}", encode(host), encode(path))
+        }
+        None => format!("file:///{}", encode(path.trim_start_matches('/'))),
+    }
+}
+
+/// `s` with the bytes other than unreserved characters, `/` and `:`
+/// percent-encoded
+fn encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                encoded.push(b.into())
+            }
+            _ => encoded.push_str(&format!("%{b:02X}")),
+        }
+    }
+    encoded
+}
+
+/// `s` with its percent-encoded bytes decoded, `None` if an escape is
+/// malformed or the result is not valid UTF-8
+fn decode(s: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut rest = s.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        if b != b'%' {
+            bytes.push(b);
+            rest = tail;
+            continue;
+        }
+        let hex = tail
+            .get(..2)
+            .filter(|h| h.iter().all(u8::is_ascii_hexdigit))?;
+        bytes.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+        rest = &tail[2..];
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_file_uri() {
+        let parse = |uri: &str| parse_file_uri(uri, false);
+        assert_eq!(
+            parse("file:///home/a/a%20b.md").as_deref(),
+            Some("/home/a/a b.md")
+        );
+        assert_eq!(
+            parse("file:///home/a/%C3%A9t%C3%A9.md").as_deref(),
+            Some("/home/a/été.md")
+        );
+        assert_eq!(
+            parse("file:///home/a/a.md#L3").as_deref(),
+            Some("/home/a/a.md")
+        );
+        assert_eq!(parse("https://example.com/a.md"), None);
+        assert_eq!(parse("file:///a%2"), None);
+        assert_eq!(parse("file:///a%FF"), None);
+
+        let parse = |uri: &str| parse_file_uri(uri, true);
+        assert_eq!(
+            parse("file:///c%3A/Notes/a%20b.md").as_deref(),
+            Some(r"C:\Notes\a b.md")
+        );
+        assert_eq!(parse("file:///D:/a.md").as_deref(), Some(r"D:\a.md"));
+        assert_eq!(
+            parse("file://localhost/c:/a.md").as_deref(),
+            Some(r"C:\a.md")
+        );
+        assert_eq!(
+            parse("file://server/share/a.md").as_deref(),
+            Some(r"\\server\share\a.md")
+        );
+    }
+
+    #[test]
+    fn test_file_uri() {
+        assert_eq!(
+            file_uri("/home/a/a b#1.md", false),
+            "file:///home/a/a%20b%231.md"
+        );
+        assert_eq!(
+            file_uri(r"C:\Notes\a b.md", true),
+            "file:///C:/Notes/a%20b.md"
+        );
+        assert_eq!(
+            file_uri(r"\\?\C:\Notes\a.md", true),
+            "file:///C:/Notes/a.md"
+        );
+        assert_eq!(
+            file_uri(r"\\server\share\a.md", true),
+            "file://server/share/a.md"
+        );
+        assert_eq!(
+            file_uri(r"\\?\UNC\server\share\a.md", true),
+            "file://server/share/a.md"
+        );
+
+        for (path, windows) in [
+            ("/home/a/été [1].md", false),
+            (r"C:\Notes\été [1].md", true),
+            (r"\\server\share\a.md", true),
+        ] {
+            assert_eq!(
+                parse_file_uri(&file_uri(path, windows), windows).as_deref(),
+                Some(path)
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_uri() {
+        let root = Path::new("/home/a/notes");
+        let uri: Uri = "file:///home/a/notes/z/a%20b.md".parse().unwrap();
+        assert_eq!(uri_to_path(&uri), Some(root.join("z/a b.md")));
+        assert_eq!(uri_to_relative(root, &uri), Some(PathBuf::from("z/a b.md")));
+        assert_eq!(relative_to_uri(root, Path::new("z/a b.md")), Some(uri));
+        assert_eq!(
+            uri_to_relative(
+                Path::new("/home/b"),
+                &"file:///home/a/a.md".parse().unwrap()
+            ),
+            None
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_uri() {
+        let root = Path::new(r"C:\Notes");
+        let uri: Uri = "file:///c%3A/notes/z/a%20b.md".parse().unwrap();
+        assert_eq!(uri_to_path(&uri), Some(PathBuf::from(r"C:\notes\z\a b.md")));
+        assert_eq!(
+            uri_to_relative(root, &uri),
+            Some(PathBuf::from(r"z\a b.md"))
+        );
+        assert_eq!(
+            relative_to_uri(root, Path::new(r"z\a b.md")).map(|uri| uri.as_str().to_owned()),
+            Some("file:///C:/Notes/z/a%20b.md".to_owned())
+        );
+        let unc: Uri = "file://server/share/a.md".parse().unwrap();
+        assert_eq!(
+            uri_to_path(&unc),
+            Some(PathBuf::from(r"\\server\share\a.md"))
+        );
+    }
+}
@@ -0,0 +1,125 @@
+//! Normalizing the urls of links to external pages, so that the links to the
+//! same page are found however they are written.
+
+/// Query parameters tracking where a visitor came from, which do not change
+/// the page an url points to
+const TRACKING_PARAMETERS: [&str; 10] = [
+    "fbclid", "gclid", "dclid", "msclkid", "mc_cid", "mc_eid", "igshid", "ref_src", "_hsenc",
+    "_hsmi",
+];
+
+/// The resolvers DOIs are written as urls of
+const DOI_RESOLVERS: [&str; 2] = ["doi.org/", "dx.doi.org/"];
+
+/// The normalized form of `target`, an http(s) url or a DOI, `None` for any
+/// other link target. The scheme and host are lowercased, default ports,
+/// fragments, tracking parameters and trailing slashes left out. DOIs,
+/// written as `doi:10.1000/x`, `10.1000/x` or as the url of a resolver, are
+/// lowercased and written as `https://doi.org/10.1000/x`.
+pub fn normalize_url(target: &str) -> Option<String> {
+    let target = target.trim();
+    if let Some(doi) = doi(target) {
+        return Some(format!("https://doi.org/{}", doi.to_lowercase()));
+    }
+
+    let (scheme, rest) = target.split_once("://")?;
+    let scheme = scheme.to_ascii_lowercase();
+    if scheme != "http" && scheme != "https" {
+        return None;
+    }
+    let rest = rest.split('#').next().unwrap_or_default();
+    let (rest, query) = match rest.split_once('?') {
+        Some((rest, query)) => (rest, Some(query)),
+        None => (rest, None),
+    };
+    let (host, path) = match rest.find('/') {
+        Some(slash) => rest.split_at(slash),
+        None => (rest, ""),
+    };
+    if host.is_empty() {
+        return None;
+    }
+    let host = host.to_ascii_lowercase();
+    let default_port = match scheme.as_str() {
+        "http" => ":80",
+        _ => ":443",
+    };
+    let host = host.strip_suffix(default_port).unwrap_or(&host);
+
+    let mut url = format!("{scheme}://{host}{}", path.trim_end_matches('/'));
+    let parameters: Vec<&str> = query
+        .unwrap_or_default()
+        .split('&')
+        .filter(|parameter| !parameter.is_empty() && !is_tracking(parameter))
+        .collect();
+    if !parameters.is_empty() {
+        url.push('?');
+        url.push_str(&parameters.join("&"));
+    }
+    Some(url)
+}
+
+/// The DOI `target` names, as `10.1000/x`
+fn doi(target: &str) -> Option<&str> {
+    let lower = target.to_ascii_lowercase();
+    let doi = if lower.starts_with("doi:") {
+        &target["doi:".len()..]
+    } else if let Some((scheme, rest)) = lower.split_once("://")
+        && (scheme == "http" || scheme == "https")
+        && let Some(resolver) = DOI_RESOLVERS.iter().find(|r| rest.starts_with(**r))
+    {
+        &target[scheme.len() + "://".len() + resolver.len()..]
+    } else {
+        target
+    };
+    let doi = doi.trim();
+    (doi.starts_with("10.") && doi.contains('/') && !doi.contains(char::is_whitespace))
+        .then_some(doi)
+}
+
+fn is_tracking(parameter: &str) -> bool {
+    let key = parameter.split('=').next().unwrap_or_default();
+    key.starts_with("utm_") || TRACKING_PARAMETERS.contains(&key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_url() {
+        let normalized = |url: &str| normalize_url(url);
+        assert_eq!(
+            normalized("HTTPS://Example.com:443/a/b/?utm_source=x&id=2&fbclid=y#top").as_deref(),
+            Some("https://example.com/a/b?id=2")
+        );
+        assert_eq!(
+            normalized("http://example.com/").as_deref(),
+            Some("http://example.com")
+        );
+        assert_eq!(
+            normalized("https://example.com/A?utm_medium=mail").as_deref(),
+            Some("https://example.com/A")
+        );
+        assert_eq!(normalized("notes/a.md"), None);
+        assert_eq!(normalized("mailto:a@example.com"), None);
+        assert_eq!(normalized("https://"), None);
+    }
+
+    #[test]
+    fn test_normalize_doi() {
+        for doi in [
+            "10.1000/ABC.1",
+            "doi:10.1000/abc.1",
+            "https://doi.org/10.1000/abc.1",
+            "http://dx.doi.org/10.1000/ABC.1",
+        ] {
+            assert_eq!(
+                normalize_url(doi).as_deref(),
+                Some("https://doi.org/10.1000/abc.1"),
+                "{doi}"
+            );
+        }
+        assert_eq!(normalize_url("10.5 is a number"), None);
+    }
+}
@@ -0,0 +1,42 @@
+//! Counting the words of notes. Words are the whitespace separated runs of
+//! the text and code spans of a body, its markup, code blocks, images, html
+//! and comments left out.
+
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+
+use crate::core::parser::DocumentParserOptions;
+
+/// The number of words of `body`, runs of punctuation not being words
+pub fn count_words(body: &str) -> usize {
+    // the text of code blocks and the alt text of images are not counted
+    let mut skipped = 0;
+    let mut text = String::with_capacity(body.len());
+    for event in Parser::new_ext(body, DocumentParserOptions::default().0) {
+        match event {
+            Event::Start(Tag::CodeBlock(_) | Tag::Image { .. }) => skipped += 1,
+            Event::End(TagEnd::CodeBlock | TagEnd::Image) => skipped -= 1,
+            Event::Text(words) | Event::Code(words) if skipped == 0 => text.push_str(&words),
+            // text is split by blocks and line breaks, not by inline markup
+            Event::SoftBreak
+            | Event::HardBreak
+            | Event::End(
+                TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::Item | TagEnd::TableCell,
+            ) => text.push(' '),
+            _ => {}
+        }
+    }
+    text.split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_words() {
+        let body = "# A title\n\nSome *emphasized* text with `code` and a [link](https://example.com) -- un**bold**.\n\n```\nnot counted\n```\n\n<!-- nor this -->\n![nor this](a.png) ![[embed]]\n\n- [ ] a task\n";
+        assert_eq!(count_words(body), 2 + 9 + 2);
+    }
+}
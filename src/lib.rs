@@ -3,12 +3,14 @@ pub mod core;
 
 pub const APP_NAME: &str = "zet";
 pub const DB_NAME: &str = "db.sqlite";
+pub const DB_LOCK_NAME: &str = "db.lock";
+pub const INDEX_JOURNAL_NAME: &str = "index.journal";
 pub const CONFIG_NAME: &str = "config.toml";
 pub const APP_ENV_PREFIX: &str = "ZET_";
 
 pub mod preamble {
     pub use crate::result::*;
-    pub use crate::{APP_NAME, DB_NAME};
+    pub use crate::{APP_NAME, DB_LOCK_NAME, DB_NAME, INDEX_JOURNAL_NAME};
 }
 
 pub mod result {
@@ -20,11 +22,31 @@ pub mod config {
     use std::path::Path;
 
     use figment::Figment;
-    use figment::providers::{Env, Format, Toml};
+    use figment::providers::{Env, Format, Serialized, Toml};
+    use jiff::tz::TimeZone;
     use serde::{Deserialize, Serialize};
 
     use crate::APP_ENV_PREFIX;
+    use crate::core::assets::AssetsConfig;
+    use crate::core::capture::CaptureField;
+    use crate::core::date_parser::DateParserConfig;
+    #[cfg(feature = "document-export")]
+    use crate::core::document_export::ExportConfig;
+    use crate::core::editor::EditorConfig;
+    use crate::core::extract::ExtractConfig;
+    use crate::core::fetch::FetchConfig;
+    use crate::core::flavor::Flavor;
+    use crate::core::format::FormatConfig;
+    use crate::core::hooks::HooksConfig;
+    use crate::core::html::HtmlConfig;
+    use crate::core::id::IdScheme;
+    use crate::core::journal::Period;
+    use crate::core::lint::LintConfig;
+    use crate::core::mentions::MentionsConfig;
     use crate::core::parser::FrontMatterFormat;
+    use crate::core::parser::comments::ParserConfig;
+    use crate::core::read::{DEFAULT_MAX_FILE_SIZE, LargeFilePolicy};
+    use crate::core::readlist::ReadlistConfig;
     use crate::core::{collection_config_file, global_config_file};
     use crate::result::Result;
 
@@ -35,6 +57,116 @@ pub mod config {
         /// Template name or path. If it contains '.', treated as path in .zet/templates/<path>.
         /// Otherwise tries .zet/templates/<name>.md
         pub template: Option<String>,
+        /// What `zet create` does when the note already exists, unless given
+        /// on the command line
+        #[serde(default)]
+        pub on_duplicate: Option<DuplicatePolicy>,
+        /// How the ids of notes created in the group are generated, and read
+        /// from the filenames of its documents
+        #[serde(default)]
+        pub id_scheme: IdScheme,
+    }
+
+    /// What `zet create` does when a note with the same filename exists
+    #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum DuplicatePolicy {
+        /// fail with an error
+        #[default]
+        Fail,
+        /// create the note under the first free filename, appending `-2`,
+        /// `-3`, ... to the slug
+        Suffix,
+        /// leave the existing note as is and report its path instead
+        OpenExisting,
+        /// overwrite the existing note
+        Force,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct IndexConfig {
+        /// Create stub documents for wikilinks that do not resolve to any document
+        #[serde(default)]
+        pub create_stubs: bool,
+        /// Files larger than this, in bytes, are skipped or truncated
+        #[serde(default = "default_max_file_size")]
+        pub max_file_size: u64,
+        /// What is done with the files larger than `max_file_size`
+        #[serde(default)]
+        pub large_files: LargeFilePolicy,
+    }
+
+    fn default_max_file_size() -> u64 {
+        DEFAULT_MAX_FILE_SIZE
+    }
+
+    impl Default for IndexConfig {
+        fn default() -> Self {
+            Self {
+                create_stubs: false,
+                max_file_size: default_max_file_size(),
+                large_files: LargeFilePolicy::default(),
+            }
+        }
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct CaptureConfig {
+        /// Record capture metadata in the frontmatter of created notes
+        #[serde(default)]
+        pub enabled: bool,
+        /// Frontmatter key the metadata is stored under
+        #[serde(default = "default_capture_key")]
+        pub key: String,
+        /// Which metadata to record
+        #[serde(default = "CaptureField::all")]
+        pub fields: Vec<CaptureField>,
+    }
+
+    fn default_capture_key() -> String {
+        "capture".to_owned()
+    }
+
+    impl Default for CaptureConfig {
+        fn default() -> Self {
+            Self {
+                enabled: false,
+                key: default_capture_key(),
+                fields: CaptureField::all(),
+            }
+        }
+    }
+
+    #[derive(Default, Debug, Serialize, Deserialize)]
+    pub struct PeriodicNoteConfig {
+        /// Directory relative to collection root the notes are created in.
+        /// Defaults to `journal`.
+        pub directory: Option<String>,
+        /// Template name or path, resolved like group templates
+        pub template: Option<String>,
+        /// strftime format of the note title, which the filename is derived from
+        pub title_format: Option<String>,
+    }
+
+    /// Per period configuration, used for journal and rollup notes
+    #[derive(Default, Debug, Serialize, Deserialize)]
+    pub struct PeriodicNotesConfig {
+        #[serde(default)]
+        pub daily: PeriodicNoteConfig,
+        #[serde(default)]
+        pub weekly: PeriodicNoteConfig,
+        #[serde(default)]
+        pub monthly: PeriodicNoteConfig,
+    }
+
+    impl PeriodicNotesConfig {
+        pub fn period(&self, period: Period) -> &PeriodicNoteConfig {
+            match period {
+                Period::Daily => &self.daily,
+                Period::Weekly => &self.weekly,
+                Period::Monthly => &self.monthly,
+            }
+        }
     }
 
     #[derive(Default, Debug, Serialize, Deserialize)]
@@ -42,18 +174,81 @@ pub mod config {
         // pub root: PathBuf,
         #[serde(default)]
         pub front_matter_format: FrontMatterFormat,
+        /// The markdown flavor of the collection, presetting the `[parser]`
+        /// and `[format]` settings not written in the configuration
+        #[serde(default)]
+        pub flavor: Option<Flavor>,
         #[serde(default)]
         pub group: HashMap<String, GroupConfig>,
+        #[serde(default)]
+        pub index: IndexConfig,
+        #[serde(default)]
+        pub capture: CaptureConfig,
+        #[serde(default)]
+        pub journal: PeriodicNotesConfig,
+        #[serde(default)]
+        pub rollup: PeriodicNotesConfig,
+        #[serde(default)]
+        pub dates: DateParserConfig,
+        #[serde(default)]
+        pub lint: LintConfig,
+        #[serde(default)]
+        pub format: FormatConfig,
+        #[serde(default)]
+        pub assets: AssetsConfig,
+        #[serde(default)]
+        pub html: HtmlConfig,
+        #[serde(default)]
+        pub parser: ParserConfig,
+        #[serde(default)]
+        pub hooks: HooksConfig,
+        #[serde(default)]
+        pub editor: EditorConfig,
+        #[serde(default)]
+        pub extract: ExtractConfig,
+        #[serde(default)]
+        pub fetch: FetchConfig,
+        #[serde(default)]
+        pub readlist: ReadlistConfig,
+        #[serde(default)]
+        pub mentions: MentionsConfig,
+        #[cfg(feature = "document-export")]
+        #[serde(default)]
+        pub export: ExportConfig,
+        /// IANA name of the timezone dates are interpreted in, e.g.
+        /// "Europe/Stockholm". Defaults to the timezone of the system.
+        #[serde(default)]
+        pub timezone: Option<String>,
     }
 
     impl Config {
+        pub fn timezone(&self) -> Result<TimeZone> {
+            match &self.timezone {
+                Some(name) => Ok(TimeZone::get(name)?),
+                None => Ok(TimeZone::system()),
+            }
+        }
+
+        /// The id scheme of the group the document at `path` belongs to
+        pub fn id_scheme(&self, root: &Path, path: &Path) -> IdScheme {
+            crate::core::template_engine::resolve_group_from_cwd(self, root, path)
+                .map(|(_, group)| group.id_scheme)
+                .unwrap_or_default()
+        }
+
         pub fn resolve(root: &Path) -> Result<Config> {
-            Ok(Figment::new()
+            let figment = Figment::new()
                 // global config
                 .merge(Toml::file(global_config_file()))
                 .merge(Toml::file(collection_config_file(root)))
-                .merge(Env::prefixed(APP_ENV_PREFIX))
-                .extract()?)
+                .merge(Env::prefixed(APP_ENV_PREFIX));
+            // the settings of the flavor are overridden by the ones written
+            // out, an invalid flavor is reported by the extraction below
+            let figment = match figment.extract_inner::<Flavor>("flavor") {
+                Ok(flavor) => Figment::from(Serialized::defaults(flavor.preset())).merge(figment),
+                Err(_) => figment,
+            };
+            Ok(figment.extract()?)
         }
     }
 }
@@ -16,7 +16,7 @@ fn main() -> Result<()> {
         env_logger::init_from_env(env);
     }
 
-    app::command_handler::handle_command(cli.command, cli.root)?;
+    app::command_handler::handle_command(cli.command, cli.root, cli.read_only)?;
 
     Ok(())
 }
@@ -0,0 +1,62 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+fn setup() -> (assert_fs::TempDir, std::path::PathBuf) {
+    let (temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::create_dir_all(workspace.join("img")).unwrap();
+    fs::write(workspace.join("img/big.png"), vec![0u8; 500]).unwrap();
+    fs::write(workspace.join("img/small.png"), vec![0u8; 50]).unwrap();
+    fs::write(workspace.join("img/unused.png"), vec![0u8; 50]).unwrap();
+    fs::write(workspace.join("report.pdf"), "pdf").unwrap();
+    fs::write(
+        workspace.join("note.md"),
+        "# Note\n\n![big](img/big.png) ![[small.png]]\n\n[the report](report.pdf)\n",
+    )
+    .unwrap();
+    (temp, workspace)
+}
+
+#[test]
+fn test_assets_gc() {
+    let (_temp, workspace) = setup();
+
+    let unused = query_document_ids(&workspace, &["assets", "gc", "--dry-run"]);
+    assert_eq!(unused, vec!["img/unused.png"]);
+    assert!(workspace.join("img/unused.png").exists());
+
+    run_cli_cmd(&["assets", "gc"], &workspace)
+        .assert()
+        .success();
+    assert!(!workspace.join("img/unused.png").exists());
+    assert!(workspace.join("img/small.png").exists());
+    assert!(workspace.join("report.pdf").exists());
+}
+
+#[test]
+fn test_assets_optimize() {
+    let (_temp, workspace) = setup();
+    // an "optimizer" keeping the first bytes of the image
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        "[assets]\nmax_size = 100\noptimize_extension = \"webp\"\noptimize_command = [\"sh\", \"-c\", \"head -c 10 \\\"$0\\\" > \\\"$1\\\"\", \"{input}\", \"{output}\"]\n",
+    )
+    .unwrap();
+
+    let images = query_document_ids(&workspace, &["assets", "optimize", "--dry-run"]);
+    assert_eq!(images, vec!["img/big.png (500 bytes)"]);
+
+    let images = query_document_ids(&workspace, &["assets", "optimize"]);
+    assert_eq!(
+        images,
+        vec!["img/big.png -> img/big.webp (500 -> 10 bytes)"]
+    );
+    assert!(!workspace.join("img/big.png").exists());
+    assert_eq!(fs::read(workspace.join("img/big.webp")).unwrap().len(), 10);
+    assert_eq!(
+        fs::read_to_string(workspace.join("note.md")).unwrap(),
+        "# Note\n\n![big](img/big.webp) ![[small.png]]\n\n[the report](report.pdf)\n"
+    );
+}
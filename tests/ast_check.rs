@@ -3,6 +3,7 @@ use insta::glob;
 use std::fs;
 use zet::core::parser::FrontMatterFormat;
 use zet::core::parser::FrontMatterParser;
+use zet::core::parser::arena::Ast;
 
 #[test]
 fn test_input_files() {
@@ -17,3 +18,19 @@ fn test_input_files() {
         assert_yaml_snapshot!(res);
     });
 }
+
+#[test]
+fn test_json_round_trip() {
+    glob!("input_files/*.md", |path| {
+        let input = fs::read_to_string(path).unwrap();
+
+        let frontmatter_parser = FrontMatterParser::new(FrontMatterFormat::Toml);
+        let content_parser = zet::core::parser::DocumentParser::new();
+
+        let (_, ast) = zet::core::parser::parse(frontmatter_parser, content_parser, input).unwrap();
+
+        let json = serde_json::to_value(&ast).unwrap();
+        let read: Ast = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&read).unwrap(), json);
+    });
+}
@@ -0,0 +1,120 @@
+mod helpers;
+
+use helpers::{cli::*, db::*, *};
+use std::fs;
+
+fn write_collection(workspace: &std::path::Path) {
+    fs::create_dir_all(workspace.join("projects")).unwrap();
+    fs::write(workspace.join("a.md"), "# A\n\nSee [b](projects/b.md).\n").unwrap();
+    fs::write(
+        workspace.join("projects/b.md"),
+        "# B\n\n![diagram](diagram.png)\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("projects/diagram.png"),
+        [0x89, b'P', b'N', b'G', 0, 1],
+    )
+    .unwrap();
+    run_cli_cmd(&["init"], workspace).assert().success();
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        "[editor]\nopen_on_create = false\n",
+    )
+    .unwrap();
+    run_cli_cmd(&["index"], workspace).assert().success();
+}
+
+#[test]
+fn test_backup_and_restore() {
+    let (_temp, workspace) = setup_temp_workspace();
+    write_collection(&workspace);
+    let out = assert_fs::TempDir::new().unwrap();
+
+    for (archive, include_db) in [("notes.tar.gz", false), ("notes.tar", true)] {
+        let archive = out.path().join(archive);
+        let mut args = vec!["backup", "--out", archive.to_str().unwrap()];
+        if include_db {
+            args.push("--include-db");
+        }
+        run_cli_cmd(&args, &workspace).assert().success();
+
+        let target = out.path().join(format!("restored-{include_db}"));
+        run_cli_cmd(
+            &[
+                "restore",
+                archive.to_str().unwrap(),
+                "--into",
+                target.to_str().unwrap(),
+            ],
+            &workspace,
+        )
+        .assert()
+        .success();
+
+        for file in [
+            "a.md",
+            "projects/b.md",
+            "projects/diagram.png",
+            ".zet/config.toml",
+        ] {
+            assert_eq!(
+                fs::read(workspace.join(file)).unwrap(),
+                fs::read(target.join(file)).unwrap(),
+                "{file}"
+            );
+        }
+        let db = open_test_db(&target);
+        assert_eq!(count_documents(&db), 2);
+        assert_eq!(get_links_from(&db, "a").len(), 1);
+    }
+
+    // a directory that is not empty is only restored into with --force
+    let archive = out.path().join("notes.tar");
+    run_cli_cmd(&["restore", archive.to_str().unwrap()], &workspace)
+        .assert()
+        .failure();
+    run_cli_cmd(
+        &["restore", archive.to_str().unwrap(), "--force"],
+        &workspace,
+    )
+    .assert()
+    .success();
+}
+
+#[test]
+fn test_restore_rejects_tampered_archive() {
+    let (_temp, workspace) = setup_temp_workspace();
+    write_collection(&workspace);
+    let out = assert_fs::TempDir::new().unwrap();
+    let archive = out.path().join("notes.tar");
+    run_cli_cmd(&["backup", "--out", archive.to_str().unwrap()], &workspace)
+        .assert()
+        .success();
+
+    // change a character of a document, keeping its size
+    let mut content = fs::read(&archive).unwrap();
+    let at = content.windows(4).position(|w| w == b"# B\n").unwrap();
+    content[at + 2] = b'C';
+    fs::write(&archive, content).unwrap();
+
+    let target = out.path().join("restored");
+    let output = run_cli_cmd(
+        &[
+            "restore",
+            archive.to_str().unwrap(),
+            "--into",
+            target.to_str().unwrap(),
+        ],
+        &workspace,
+    )
+    .output()
+    .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("projects/b.md does not match the manifest"),
+        "{stderr}"
+    );
+    assert!(!target.join("projects/b.md").exists());
+}
@@ -0,0 +1,64 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+fn setup_board_workspace() -> (assert_fs::TempDir, std::path::PathBuf) {
+    let (temp, workspace) = setup_temp_workspace();
+    copy_fixture_to_temp("query-test", &temp).unwrap();
+    fs::write(
+        workspace.join("project.md"),
+        "---\ntitle: Project\ntags:\n  - work\n---\n\n- [ ] plan\n- [/] build\n- [x] setup\n- [-] scrap\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    (temp, workspace)
+}
+
+fn board_json(workspace: &std::path::Path, args: &[&str]) -> serde_json::Value {
+    let mut cmd_args = vec!["board", "--output-format", "json"];
+    cmd_args.extend_from_slice(args);
+    let output = run_cli_cmd(&cmd_args, workspace).output().unwrap();
+    assert!(output.status.success());
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn test_board_group_by_status() {
+    let (_temp, workspace) = setup_board_workspace();
+
+    let value = board_json(&workspace, &[]);
+    let columns = value["columns"].as_array().unwrap();
+    let names: Vec<&str> = columns
+        .iter()
+        .map(|c| c["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["todo", "in_progress", "done", "cancelled"]);
+    for column in columns {
+        assert_eq!(column["tasks"].as_array().unwrap().len(), 1);
+    }
+    assert_eq!(columns[1]["tasks"][0]["content"], "build");
+}
+
+#[test]
+fn test_board_group_by_tag() {
+    let (_temp, workspace) = setup_board_workspace();
+
+    let value = board_json(&workspace, &["--group-by", "tag"]);
+    assert_eq!(value["columns"][0]["name"], "work");
+    assert_eq!(value["columns"][0]["tasks"].as_array().unwrap().len(), 4);
+}
+
+#[test]
+fn test_board_text() {
+    let (_temp, workspace) = setup_board_workspace();
+
+    let output = run_cli_cmd(&["board"], &workspace).output().unwrap();
+    assert!(output.status.success());
+    let text = String::from_utf8(output.stdout).unwrap();
+    assert!(text.starts_with("todo (1)"), "{text}");
+    assert!(text.contains("| build"), "{text}");
+}
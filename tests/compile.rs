@@ -0,0 +1,76 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+#[test]
+fn test_compile_markdown() {
+    let (_temp, workspace) = setup_temp_workspace();
+    fs::write(
+        workspace.join("book.md"),
+        "---\ntitle: The Book\n---\n\n# The Book\n\n1. [[intro]]\n2. [[method]]\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("intro.md"),
+        "---\ntitle: Introduction\n---\n\nSee the [[method#Setup|setup]]. %%a comment%%\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("method.md"),
+        "# Method\n\n## Setup\n\n![[rig]]\n",
+    )
+    .unwrap();
+    fs::write(workspace.join("rig.md"), "A rig, see [[intro]].\n").unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    let out = workspace.parent().unwrap().join("the-book.md");
+    run_cli_cmd(
+        &["compile", "book", "--out", out.to_str().unwrap()],
+        &workspace,
+    )
+    .assert()
+    .success();
+    assert_eq!(
+        fs::read_to_string(&out).unwrap(),
+        "# The Book\n\n\
+         ## Introduction\n\n\
+         See the [setup](#setup).\n\n\
+         ## Method\n\n\
+         ### Setup\n\n\
+         A rig, see [intro](#introduction).\n"
+    );
+
+    run_cli_cmd(
+        &["compile", "rig", "--out", out.to_str().unwrap()],
+        &workspace,
+    )
+    .assert()
+    .failure();
+}
+
+#[cfg(feature = "document-export")]
+#[test]
+fn test_compile_pdf() {
+    let (_temp, workspace) = setup_temp_workspace();
+    fs::write(workspace.join("book.md"), "# Book\n\n- [[chapter]]\n").unwrap();
+    fs::write(workspace.join("chapter.md"), "# Chapter\n\nCHAPTER-TEXT\n").unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    // a "converter" keeping the html
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        "[export]\npdf_command = [\"cp\", \"{input}\", \"{output}\"]\n",
+    )
+    .unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    run_cli_cmd(&["compile", "book", "--out", "book.pdf"], &workspace)
+        .assert()
+        .success();
+
+    let html = fs::read_to_string(workspace.join("book.pdf")).unwrap();
+    assert!(html.contains("<title>Book</title>"), "{html}");
+    assert!(html.contains("<h2 id=\"chapter\">Chapter</h2>"), "{html}");
+    assert!(html.contains("CHAPTER-TEXT"), "{html}");
+}
@@ -1,5 +1,6 @@
 mod helpers;
 
+use helpers::db::{count_documents, get_links_from, open_test_db};
 use helpers::{cli::run_cli_cmd, setup_temp_workspace};
 use std::fs;
 use std::path::Path;
@@ -111,6 +112,214 @@ fn test_create_date_is_injected() {
     );
 }
 
+#[test]
+fn test_create_indexes_note() {
+    let (_temp, workspace) = setup_temp_workspace();
+    init_workspace(&workspace);
+    // only the created note is indexed
+    fs::write(workspace.join("other.md"), "# Other\n").unwrap();
+
+    run_cli_cmd(&["create", "Indexed Note"], &workspace)
+        .assert()
+        .success();
+    assert_eq!(count_documents(&open_test_db(&workspace)), 1);
+    let assert = run_cli_cmd(&["query", "--title", "Indexed Note"], &workspace)
+        .assert()
+        .success();
+    assert!(get_stdout(&assert).starts_with("indexed-note\t"));
+
+    run_cli_cmd(&["create", "Later Note", "--no-index"], &workspace)
+        .assert()
+        .success();
+    assert_eq!(count_documents(&open_test_db(&workspace)), 1);
+}
+
+#[test]
+fn test_create_with_id_scheme() {
+    let (_temp, workspace) = setup_temp_workspace();
+    init_workspace(&workspace);
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        r#"
+timezone = "Etc/GMT-14"
+
+[group.zettel]
+directories = ["zettel"]
+id_scheme = "timestamp"
+"#,
+    )
+    .unwrap();
+
+    // the id is the time in the timezone of the collection
+    let day = || {
+        let tz = jiff::tz::TimeZone::get("Etc/GMT-14").unwrap();
+        jiff::Timestamp::now()
+            .to_zoned(tz)
+            .strftime("%Y%m%d")
+            .to_string()
+    };
+    let before = day();
+    let assert = run_cli_cmd(&["create", "First Idea", "--group", "zettel"], &workspace)
+        .assert()
+        .success();
+    let days = [before, day()];
+    let path = get_stdout(&assert).trim().to_owned();
+    let filename = Path::new(&path).file_name().unwrap().to_str().unwrap();
+    let (id, slug) = filename.split_at(12);
+    assert!(id.bytes().all(|b| b.is_ascii_digit()), "got: {filename}");
+    assert!(
+        days.iter().any(|day| id.starts_with(day)),
+        "got: {filename}"
+    );
+    assert_eq!(slug, "-first-idea.md");
+    let content = fs::read_to_string(&path).unwrap();
+    assert!(content.contains(&format!("id: {id}")), "got: {content}");
+
+    // documents without an id in their frontmatter are identified by the
+    // start of their filename, and links to the filename resolve to the id
+    fs::write(
+        workspace.join("zettel/202001010000-legacy.md"),
+        "# Legacy\n",
+    )
+    .unwrap();
+    fs::write(workspace.join("linker.md"), "[[202001010000-legacy]]\n").unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    let db = open_test_db(&workspace);
+    assert_eq!(
+        get_links_from(&db, "linker"),
+        vec![("linker".to_owned(), Some("202001010000".to_owned()))]
+    );
+}
+
+#[test]
+fn test_create_timestamp_ids_unique_across_groups() {
+    let (_temp, workspace) = setup_temp_workspace();
+    init_workspace(&workspace);
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        r#"
+[group.a]
+directories = ["a"]
+id_scheme = "timestamp"
+
+[group.b]
+directories = ["b"]
+id_scheme = "timestamp"
+"#,
+    )
+    .unwrap();
+
+    let id = |title: &str, group: &str| {
+        let assert = run_cli_cmd(&["create", title, "--group", group], &workspace)
+            .assert()
+            .success();
+        let path = get_stdout(&assert).trim().to_owned();
+        Path::new(&path).file_name().unwrap().to_str().unwrap()[..12].to_owned()
+    };
+    let one = id("One", "a");
+    let two = id("Two", "b");
+    assert_ne!(one, two);
+
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    assert_eq!(count_documents(&open_test_db(&workspace)), 2);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_create_open() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let (temp, workspace) = setup_temp_workspace();
+    init_workspace(&workspace);
+
+    // an editor recording the arguments it was started with
+    let editor = temp.path().join("vim");
+    fs::write(
+        &editor,
+        "#!/bin/sh\necho \"$@\" > \"$(dirname \"$0\")/args\"\n",
+    )
+    .unwrap();
+    fs::set_permissions(&editor, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let assert = run_cli_cmd(
+        &["create", "Opened Note", "--open", "--no-index"],
+        &workspace,
+    )
+    .env_remove("VISUAL")
+    .env("EDITOR", &editor)
+    .assert()
+    .success();
+    let path = get_stdout(&assert).trim().to_owned();
+    let args = fs::read_to_string(temp.path().join("args")).unwrap();
+    // the cursor is placed on the line after the frontmatter
+    assert_eq!(args.trim(), format!("+5 {path}"));
+}
+
+#[test]
+fn test_create_from() {
+    let (temp, workspace) = setup_temp_workspace();
+    init_workspace(&workspace);
+
+    let agenda = temp.path().join("agenda.md");
+    fs::write(&agenda, "- budget\n- hiring\n").unwrap();
+    let assert = run_cli_cmd(
+        &[
+            "create",
+            "Meeting",
+            "--from",
+            agenda.to_str().unwrap(),
+            "--no-index",
+        ],
+        &workspace,
+    )
+    .assert()
+    .success();
+    let content = fs::read_to_string(get_stdout(&assert).trim()).unwrap();
+    assert!(content.contains("- budget\n- hiring\n"), "got: {content}");
+
+    // pages are fetched and converted by the configured commands
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        r#"
+[fetch]
+command = ["printf", "%s", "<html><body>Abstract of {url}</body></html>"]
+html_command = ["sed", "s/<[^>]*>//g"]
+"#,
+    )
+    .unwrap();
+    let assert = run_cli_cmd(
+        &[
+            "create",
+            "Paper",
+            "--from",
+            "https://example.com/paper",
+            "--no-index",
+        ],
+        &workspace,
+    )
+    .assert()
+    .success();
+    let content = fs::read_to_string(get_stdout(&assert).trim()).unwrap();
+    assert!(
+        content.contains("Abstract of https://example.com/paper"),
+        "got: {content}"
+    );
+
+    let assert = run_cli_cmd(
+        &[
+            "create",
+            "Both",
+            "inline",
+            "--from",
+            agenda.to_str().unwrap(),
+        ],
+        &workspace,
+    )
+    .assert()
+    .failure();
+    assert!(get_stderr(&assert).contains("cannot be used with"));
+}
+
 #[test]
 fn test_create_with_explicit_template() {
     let (_temp, workspace) = setup_temp_workspace();
@@ -141,6 +350,44 @@ fn test_create_with_explicit_template() {
     );
 }
 
+#[test]
+fn test_create_with_extending_template() {
+    let (_temp, workspace) = setup_temp_workspace();
+    init_workspace(&workspace);
+
+    let templates_dir = workspace.join(".zet/templates");
+    fs::create_dir_all(templates_dir.join("partials")).unwrap();
+    fs::write(
+        templates_dir.join("partials/frontmatter.md"),
+        "---\nid: {{ id }}\ntitle: {{ title }}\n---\n",
+    )
+    .unwrap();
+    fs::write(
+        templates_dir.join("base.md"),
+        "{% include \"partials/frontmatter.md\" %}\n# {{ title }}\n\n{% block body %}{% endblock body %}\n",
+    )
+    .unwrap();
+    fs::write(
+        templates_dir.join("meeting.md"),
+        "{% extends \"base.md\" %}{% block body %}## Attendees\n{% endblock body %}",
+    )
+    .unwrap();
+
+    let assert = run_cli_cmd(&["create", "Standup", "--template", "meeting"], &workspace)
+        .assert()
+        .success();
+    let content = fs::read_to_string(get_stdout(&assert).trim()).unwrap();
+    assert_eq!(
+        content,
+        "---\nid: standup\ntitle: Standup\n---\n\n# Standup\n\n## Attendees\n\n"
+    );
+
+    let assert = run_cli_cmd(&["templates", "show", "meeting"], &workspace)
+        .assert()
+        .success();
+    assert!(get_stdout(&assert).contains("variables: id, title\n"));
+}
+
 #[test]
 fn test_create_with_group() {
     let (_temp, workspace) = setup_temp_workspace();
@@ -408,6 +655,63 @@ fn test_create_duplicate_fails() {
     );
 }
 
+#[test]
+fn test_create_duplicate_policies() {
+    let (_temp, workspace) = setup_temp_workspace();
+    init_workspace(&workspace);
+
+    let create = |args: &[&str]| {
+        let assert = run_cli_cmd(args, &workspace).assert().success();
+        get_stdout(&assert).trim().to_owned()
+    };
+    let original = create(&["create", "Standup", "first", "--no-index"]);
+
+    let suffixed = create(&["create", "Standup", "second", "--suffix", "--no-index"]);
+    assert!(suffixed.ends_with("standup-2.md"), "got: {suffixed}");
+    let content = fs::read_to_string(&suffixed).unwrap();
+    assert!(content.contains("id: standup-2"), "got: {content}");
+    let suffixed = create(&["create", "Standup", "--suffix", "--no-index"]);
+    assert!(suffixed.ends_with("standup-3.md"), "got: {suffixed}");
+
+    let existing = create(&["create", "Standup", "third", "--open-existing"]);
+    assert_eq!(existing, original);
+    assert!(fs::read_to_string(&original).unwrap().contains("first"));
+
+    create(&["create", "Standup", "fourth", "--force", "--no-index"]);
+    let content = fs::read_to_string(&original).unwrap();
+    assert!(content.contains("fourth") && !content.contains("first"));
+
+    run_cli_cmd(&["create", "Standup", "--suffix", "--force"], &workspace)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_create_duplicate_policy_of_group() {
+    let (_temp, workspace) = setup_temp_workspace();
+    init_workspace(&workspace);
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        r#"
+[group.journal]
+directories = ["journal"]
+on_duplicate = "open-existing"
+"#,
+    )
+    .unwrap();
+
+    let create = |args: &[&str]| run_cli_cmd(args, &workspace).assert().success();
+    let first = get_stdout(&create(&["create", "Today", "--group", "journal"]));
+    let second = get_stdout(&create(&["create", "Today", "--group", "journal"]));
+    assert_eq!(first, second);
+
+    // the command line takes precedence over the group
+    let third = get_stdout(&create(&[
+        "create", "Today", "--group", "journal", "--suffix",
+    ]));
+    assert!(third.trim().ends_with("today-2.md"), "got: {third}");
+}
+
 #[test]
 fn test_create_stdin_and_content_conflict() {
     let (_temp, workspace) = setup_temp_workspace();
@@ -455,3 +759,51 @@ fn test_create_missing_template_fails() {
         "expected 'could not read template' in stderr: {stderr}"
     );
 }
+
+// ---- Capture Metadata ----
+
+#[test]
+fn test_create_without_capture_metadata() {
+    let (_temp, workspace) = setup_temp_workspace();
+    init_workspace(&workspace);
+
+    let assert = run_cli_cmd(&["create", "Plain Note"], &workspace)
+        .assert()
+        .success();
+    let content = fs::read_to_string(get_stdout(&assert).trim()).unwrap();
+    assert!(!content.contains("capture:"), "capture metadata is opt-in");
+}
+
+#[test]
+fn test_create_with_capture_metadata() {
+    let (_temp, workspace) = setup_temp_workspace();
+    init_workspace(&workspace);
+
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        "[capture]\nenabled = true\nkey = \"logbook\"\nfields = [\"cwd\"]\n",
+    )
+    .unwrap();
+
+    let assert = run_cli_cmd(&["create", "Captured Note"], &workspace)
+        .assert()
+        .success();
+    let content = fs::read_to_string(get_stdout(&assert).trim()).unwrap();
+
+    let (frontmatter, _) = content
+        .strip_prefix("---\n")
+        .and_then(|c| c.split_once("---\n"))
+        .expect("missing frontmatter");
+    assert!(
+        frontmatter.contains("id: captured-note"),
+        "template fields kept"
+    );
+    assert!(
+        frontmatter.contains("logbook:\n  cwd: "),
+        "missing capture: {content}"
+    );
+    assert!(
+        !frontmatter.contains("hostname"),
+        "only configured fields are captured"
+    );
+}
@@ -0,0 +1,58 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+#[test]
+fn test_date_formats() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        "timezone = \"Etc/GMT-2\"\n",
+    )
+    .unwrap();
+
+    let lines = query_document_ids(&workspace, &["date", "2025-06-01 at 10:00"]);
+    assert_eq!(
+        lines,
+        vec![
+            "timestamp: 2025-06-01T08:00:00Z",
+            "local: 2025-06-01T10:00:00+02:00[Etc/GMT-2]",
+            "date: 2025-06-01",
+            "time: 10:00:00",
+            "unix: 1748764800",
+        ]
+    );
+}
+
+#[test]
+fn test_date_range_json() {
+    let (_temp, workspace) = setup_temp_workspace();
+
+    let output = run_cli_cmd(
+        &[
+            "date",
+            "--range",
+            "--output-format",
+            "json",
+            "from 2025-06-01 to 2025-06-03",
+        ],
+        &workspace,
+    )
+    .output()
+    .unwrap();
+    assert!(output.status.success());
+
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(value["start"]["date"], "2025-06-01");
+    assert_eq!(value["end"]["date"], "2025-06-04");
+}
+
+#[test]
+fn test_date_invalid_expression() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["date", "the day after never"], &workspace)
+        .assert()
+        .failure();
+}
@@ -0,0 +1,64 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+#[test]
+fn test_diff_index() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(workspace.join("a.md"), "# A\n\nsee [[b]]\n\n- [ ] one\n").unwrap();
+    fs::write(workspace.join("b.md"), "# B\n").unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    fs::write(workspace.join("a.md"), "# A2\n\nsee [[c]]\n\n- [x] one\n").unwrap();
+    fs::remove_file(workspace.join("b.md")).unwrap();
+    fs::write(workspace.join("c.md"), "# C\n\n- [ ] two\n").unwrap();
+
+    let diff = "\
+~ a  A2
+    title: A -> A2
+    - link b
+    + link c
+    - task [ ] one
+    + task [x] one
+- b  B
++ c  C
+    + task [ ] two
+";
+    run_cli_cmd(&["diff-index"], &workspace)
+        .assert()
+        .success()
+        .stdout(diff);
+    // nothing has been written
+    run_cli_cmd(&["diff-index"], &workspace)
+        .assert()
+        .success()
+        .stdout(diff);
+
+    assert_eq!(
+        query_document_ids(&workspace, &["index", "--dry-run"]),
+        vec!["new      c.md", "updated  a", "removed  b"]
+    );
+
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    run_cli_cmd(&["diff-index"], &workspace)
+        .assert()
+        .success()
+        .stdout("");
+}
+
+#[test]
+fn test_diff_index_unchanged_content() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(workspace.join("a.md"), "# A\n\nsome text\n").unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    // the text changed but not the title, links or tasks
+    fs::write(workspace.join("a.md"), "# A\n\nother text\n").unwrap();
+    run_cli_cmd(&["diff-index"], &workspace)
+        .assert()
+        .success()
+        .stdout("");
+}
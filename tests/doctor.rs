@@ -0,0 +1,98 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+fn list_ids(workspace: &std::path::Path) -> Vec<String> {
+    query_document_ids(workspace, &["list"])
+        .iter()
+        .map(|line| line.split('\t').next().unwrap().to_owned())
+        .collect()
+}
+
+#[test]
+fn test_index_large_and_invalid_files() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        "[index]\nmax_file_size = 64\n",
+    )
+    .unwrap();
+    fs::write(workspace.join("small.md"), "# Small\n").unwrap();
+    fs::write(workspace.join("latin1.md"), b"# Caf\xe9\n").unwrap();
+    fs::write(
+        workspace.join("large.md"),
+        format!("# Large\n\n{}\n", "a".repeat(100)),
+    )
+    .unwrap();
+
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    assert_eq!(list_ids(&workspace), vec!["latin1", "small"]);
+
+    let output = run_cli_cmd(&["doctor"], &workspace).output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "skipped      large.md\t110 bytes\ninvalid_utf8 latin1.md\t7 bytes\n"
+    );
+
+    // truncated instead, the index picks the file up
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        "[index]\nmax_file_size = 64\nlarge_files = \"truncate\"\n",
+    )
+    .unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    assert_eq!(list_ids(&workspace), vec!["large", "latin1", "small"]);
+
+    let output = run_cli_cmd(&["doctor", "--output-format", "json"], &workspace)
+        .output()
+        .unwrap();
+    let reports: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let issues: Vec<&str> = reports
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["issues"][0].as_str().unwrap())
+        .collect();
+    assert_eq!(issues, vec!["truncated", "invalid_utf8"]);
+}
+
+#[test]
+fn test_commands_skip_invalid_files() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    let latin1: &[u8] = b"# Caf\xe9\n\nsee https://example.com.\n";
+    fs::write(workspace.join("latin1.md"), latin1).unwrap();
+    fs::write(
+        workspace.join("notes.md"),
+        "---\ntitle: Notes\n---\n\n# Notes\n\nsee https://example.com.\n",
+    )
+    .unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    // the invalid file is read lossily, the other files are still reached
+    let output = run_cli_cmd(&["lint"], &workspace).output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("latin1.md:3:5: bare-url"), "{stdout}");
+    assert!(stdout.contains("notes.md:7:5: bare-url"), "{stdout}");
+
+    let output = run_cli_cmd(&["words"], &workspace).output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("latin1\t"), "{stdout}");
+
+    // but not written back
+    run_cli_cmd(&["lint", "--fix"], &workspace)
+        .assert()
+        .failure();
+    run_cli_cmd(&["format"], &workspace).assert().success();
+    let edited = query_document_ids(&workspace, &["meta", "set", "status", "draft"]);
+    assert_eq!(edited, vec!["notes.md"]);
+    assert_eq!(fs::read(workspace.join("latin1.md")).unwrap(), latin1);
+    assert_eq!(
+        fs::read_to_string(workspace.join("notes.md")).unwrap(),
+        "---\ntitle: Notes\nstatus: \"draft\"\n---\n\n# Notes\n\nsee <https://example.com>.\n"
+    );
+}
@@ -0,0 +1,28 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+#[test]
+fn test_expand_snippet() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(
+        workspace.join(".zet/snippets.toml"),
+        "sig = \"-- {{ author }}\"\n\n[ref]\ndescription = \"a reference\"\nbody = \"see [[{{ id }}]] ({{ title }})\"\n",
+    )
+    .unwrap();
+
+    let output = query_document_ids(&workspace, &["expand", "sig", "--var", "author=kit"]);
+    assert_eq!(output, vec!["-- kit"]);
+
+    let output = query_document_ids(
+        &workspace,
+        &["expand", "ref", "--id", "notes/a", "--title", "A"],
+    );
+    assert_eq!(output, vec!["see [[notes/a]] (A)"]);
+
+    run_cli_cmd(&["expand", "missing"], &workspace)
+        .assert()
+        .failure();
+}
@@ -0,0 +1,343 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+#[test]
+fn test_export_ics() {
+    let (temp, workspace) = setup_temp_workspace();
+    copy_fixture_to_temp("query-test", &temp).unwrap();
+    fs::write(
+        workspace.join("trip.md"),
+        "---\ntitle: Trip\nevent:\n  - 2025-06-01\n  - start: 2025-06-03 10:00\n    end: 2025-06-03 12:00\n    title: Museum\n---\n\n- [ ] book hotel 2025-05-20\n- [x] pack\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    let output = run_cli_cmd(&["export", "ics"], &workspace)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let ics = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2, "{ics}");
+    assert!(ics.contains("DTSTART;VALUE=DATE:20250601\r\n"), "{ics}");
+    assert!(ics.contains("SUMMARY:Museum\r\n"), "{ics}");
+    assert_eq!(ics.matches("BEGIN:VTODO").count(), 1, "{ics}");
+    assert!(ics.contains("DUE;VALUE=DATE:20250520\r\n"), "{ics}");
+
+    // written to a file
+    let path = workspace.join("calendar.ics");
+    run_cli_cmd(&["export", "ics", "--output", "calendar.ics"], &workspace)
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(path).unwrap(), ics);
+}
+
+#[test]
+fn test_export_html_linked_mentions() {
+    let (_temp, workspace) = setup_temp_workspace();
+    fs::create_dir_all(workspace.join("notes")).unwrap();
+    fs::write(
+        workspace.join("rust.md"),
+        "# Rust\n\nA language. %%a comment%%\n\n<!-- an html comment -->\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("notes/ownership.md"),
+        "---\ntitle: Ownership\n---\n\n# Ownership\n\nIntro.\n\nThe borrow checker of [[rust]] enforces it.\n\n- also see [rust](rust)\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    run_cli_cmd(&["export", "html", "--output", "site"], &workspace)
+        .assert()
+        .success();
+
+    let rust = fs::read_to_string(workspace.join("site/rust.html")).unwrap();
+    assert!(rust.contains("<h2>Linked mentions</h2>"), "{rust}");
+    assert!(
+        rust.contains("<a href=\"notes/ownership.html\">Ownership</a>"),
+        "{rust}"
+    );
+    assert!(
+        rust.contains("The borrow checker of <a href=\"rust.html\">rust</a> enforces it."),
+        "{rust}"
+    );
+    assert!(rust.contains("also see"), "{rust}");
+    assert!(!rust.contains("Intro."), "{rust}");
+    assert!(!rust.contains("comment"), "{rust}");
+
+    let ownership = fs::read_to_string(workspace.join("site/notes/ownership.html")).unwrap();
+    assert!(
+        ownership.contains("<a href=\"../rust.html\">rust</a>"),
+        "{ownership}"
+    );
+    assert!(!ownership.contains("Linked mentions"), "{ownership}");
+}
+
+#[cfg(feature = "document-export")]
+#[test]
+fn test_export_pdf() {
+    let (_temp, workspace) = setup_temp_workspace();
+    fs::create_dir_all(workspace.join("notes/img")).unwrap();
+    fs::write(workspace.join("notes/img/diagram.png"), "png").unwrap();
+    fs::write(workspace.join("rust.md"), "# Rust\n\nA language.\n").unwrap();
+    fs::write(
+        workspace.join("notes/ownership.md"),
+        "---\ntitle: Ownership\n---\n\n# Ownership\n\nSee [[rust]] and $a^2$.\n\n![diagram](img/diagram.png)\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    // a "converter" keeping the html
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        "[export]\npdf_command = [\"cp\", \"{input}\", \"{output}\"]\n",
+    )
+    .unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    run_cli_cmd(
+        &[
+            "export",
+            "pdf",
+            "notes/ownership",
+            "rust",
+            "--output",
+            "out.pdf",
+        ],
+        &workspace,
+    )
+    .assert()
+    .success();
+
+    let html = fs::read_to_string(workspace.join("out.pdf")).unwrap();
+    assert!(html.contains("<title>Ownership</title>"), "{html}");
+    let ownership = html.find("<section id=\"notes/ownership\">").unwrap();
+    let rust = html.find("<section id=\"rust\">").unwrap();
+    assert!(ownership < rust, "{html}");
+    assert!(html.contains("<a href=\"#rust\">rust</a>"), "{html}");
+    assert!(
+        html.contains("<span class=\"math math-inline\">a^2</span>"),
+        "{html}"
+    );
+    let image = workspace.join("notes/img/diagram.png");
+    assert!(
+        html.contains(&format!("src=\"{}\"", image.display())),
+        "{html}"
+    );
+
+    run_cli_cmd(
+        &["export", "pdf", "missing", "--output", "out.pdf"],
+        &workspace,
+    )
+    .assert()
+    .failure();
+}
+
+#[test]
+fn test_export_html_redact() {
+    let (_temp, workspace) = setup_temp_workspace();
+    fs::write(
+        workspace.join("rust.md"),
+        "# Rust\n\nA language.\n\n%%private%%\nSECRET-FENCE [[diary]]\n%%/private%%\n\nPublic again.\n\n%%private%% SECRET-UNCLOSED\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("diary.md"),
+        "---\nprivate: true\n---\n\n# SECRET-TITLE\n\nSECRET-DIARY about [[rust]].\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("ownership.md"),
+        "# Ownership\n\nUses [[rust]].\n\n%%private%%SECRET-MENTION of [[rust]]%%/private%%\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    run_cli_cmd(
+        &["export", "html", "--output", "site", "--redact"],
+        &workspace,
+    )
+    .assert()
+    .success();
+
+    assert!(!workspace.join("site/diary.html").exists());
+    let mut pages = String::new();
+    for entry in fs::read_dir(workspace.join("site")).unwrap() {
+        pages.push_str(&fs::read_to_string(entry.unwrap().path()).unwrap());
+    }
+    assert!(!pages.contains("SECRET"), "{pages}");
+    assert!(!pages.contains("%%"), "{pages}");
+    assert!(pages.contains("Public again."), "{pages}");
+    assert!(
+        pages.contains("Uses <a href=\"rust.html\">rust</a>."),
+        "{pages}"
+    );
+
+    // the source and the index keep the private content
+    assert!(
+        fs::read_to_string(workspace.join("rust.md"))
+            .unwrap()
+            .contains("SECRET-FENCE")
+    );
+    let ids = query_document_ids(&workspace, &["query", "--output-format", "ids"]);
+    assert!(ids.contains(&"diary".to_string()), "{ids:?}");
+
+    // without --redact everything is exported
+    run_cli_cmd(&["export", "html", "--output", "all"], &workspace)
+        .assert()
+        .success();
+    let diary = fs::read_to_string(workspace.join("all/diary.html")).unwrap();
+    assert!(diary.contains("SECRET-DIARY"), "{diary}");
+}
+
+#[test]
+fn test_export_html_redact_section_embed() {
+    let (_temp, workspace) = setup_temp_workspace();
+    fs::write(
+        workspace.join("sec.md"),
+        "# Sec\n\n%%private%%\n\n## Secret\n\nTOPSECRET text\n\n%%/private%%\n\n## Open\n\nOPEN text\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("pub.md"),
+        "# Pub\n\n![[sec#Secret]]\n\n![[sec#Open]]\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    run_cli_cmd(
+        &[
+            "export",
+            "html",
+            "--output",
+            "site",
+            "--redact",
+            "--transclude",
+        ],
+        &workspace,
+    )
+    .assert()
+    .success();
+
+    let page = fs::read_to_string(workspace.join("site/pub.html")).unwrap();
+    assert!(!page.contains("TOPSECRET"), "{page}");
+    assert!(!page.contains("%%"), "{page}");
+    assert!(page.contains("OPEN text"), "{page}");
+}
+
+#[cfg(feature = "document-export")]
+#[test]
+fn test_export_pdf_redact() {
+    let (_temp, workspace) = setup_temp_workspace();
+    fs::write(
+        workspace.join("rust.md"),
+        "---\ntags: [lang]\n---\n\n# Rust\n\nA language.\n\n%%private%%SECRET-FENCE%%/private%%\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("diary.md"),
+        "---\ntags: [lang]\nprivate: true\n---\n\n# Diary\n\nSECRET-DIARY\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        "[export]\npdf_command = [\"cp\", \"{input}\", \"{output}\"]\n",
+    )
+    .unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    run_cli_cmd(
+        &[
+            "export", "pdf", "--filter", "tag:lang", "--output", "out.pdf", "--redact",
+        ],
+        &workspace,
+    )
+    .assert()
+    .success();
+
+    let html = fs::read_to_string(workspace.join("out.pdf")).unwrap();
+    assert!(html.contains("A language."), "{html}");
+    assert!(!html.contains("SECRET"), "{html}");
+    assert!(!html.contains("%%"), "{html}");
+
+    let output = run_cli_cmd(
+        &["export", "pdf", "diary", "--output", "out.pdf", "--redact"],
+        &workspace,
+    )
+    .output()
+    .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("private"));
+}
+
+#[cfg(feature = "document-export")]
+#[test]
+fn test_export_pdf_transclude() {
+    let (_temp, workspace) = setup_temp_workspace();
+    fs::write(
+        workspace.join("book.md"),
+        "# Book\n\n![[chapter-1]]\n\n![[chapter-2]]\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("chapter-1.md"),
+        "---\ntitle: Chapter 1\n---\n\nFIRST-CHAPTER, then ![[book]].\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("chapter-2.md"),
+        "---\nprivate: true\n---\n\nSECRET-CHAPTER\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        "[export]\npdf_command = [\"cp\", \"{input}\", \"{output}\"]\n",
+    )
+    .unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    run_cli_cmd(
+        &[
+            "export",
+            "pdf",
+            "book",
+            "--output",
+            "out.pdf",
+            "--transclude",
+        ],
+        &workspace,
+    )
+    .assert()
+    .success();
+    let html = fs::read_to_string(workspace.join("out.pdf")).unwrap();
+    assert!(html.contains("FIRST-CHAPTER"), "{html}");
+    assert!(html.contains("SECRET-CHAPTER"), "{html}");
+
+    // private notes are not embedded when redacting
+    run_cli_cmd(
+        &[
+            "export",
+            "pdf",
+            "book",
+            "--output",
+            "out.pdf",
+            "--transclude",
+            "--redact",
+        ],
+        &workspace,
+    )
+    .assert()
+    .success();
+    let html = fs::read_to_string(workspace.join("out.pdf")).unwrap();
+    assert!(html.contains("FIRST-CHAPTER"), "{html}");
+    assert!(!html.contains("SECRET"), "{html}");
+}
@@ -0,0 +1,47 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+#[test]
+fn test_find() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(
+        workspace.join("ml.md"),
+        "---\ntitle: Machine learning\naliases: [ML]\n---\n\n## Linear models\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("html.md"),
+        "---\naliases: Markup\n---\n# Html\n",
+    )
+    .unwrap();
+    fs::write(workspace.join("rust.md"), "# Rust\n\n## Ownership\n").unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    assert_eq!(
+        query_document_ids(&workspace, &["find", "ml"]),
+        vec![
+            "alias    ml  ML",
+            "title    ml  Machine learning",
+            "title    html  Html",
+            "heading  html  Html",
+            "heading  ml  Linear models",
+        ]
+    );
+    assert_eq!(
+        query_document_ids(&workspace, &["find", "owner"]),
+        vec!["heading  rust  Ownership"]
+    );
+    assert_eq!(
+        query_document_ids(&workspace, &["find", "markup", "--limit", "1"]),
+        vec!["alias    html  Markup"]
+    );
+    assert!(query_document_ids(&workspace, &["find", "zzz"]).is_empty());
+
+    // the names of updated documents are replaced
+    fs::write(workspace.join("rust.md"), "# Rust\n\n## Borrowing\n").unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    assert!(query_document_ids(&workspace, &["find", "owner"]).is_empty());
+}
@@ -0,0 +1,92 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+const DOCUMENT: &str = "---\ntitle: Notes\n---\n\n# Notes\n\nsee [rust](https://rust-lang.org \"Rust\") and [zet][1]\n\n[1]: https://github.com/lakrestofer/zet\n";
+
+#[test]
+fn test_format_link_style() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(workspace.join("notes.md"), DOCUMENT).unwrap();
+    fs::write(workspace.join("clean.md"), "# Clean\n").unwrap();
+
+    // nothing to do without a configured link style
+    run_cli_cmd(&["format", "--check"], &workspace)
+        .assert()
+        .success();
+
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        "[format]\nlink_style = \"reference\"\n",
+    )
+    .unwrap();
+    run_cli_cmd(&["format", "--check"], &workspace)
+        .assert()
+        .failure();
+    assert_eq!(
+        fs::read_to_string(workspace.join("notes.md")).unwrap(),
+        DOCUMENT
+    );
+
+    let changed = query_document_ids(&workspace, &["format"]);
+    assert_eq!(changed.len(), 1);
+    assert!(changed[0].ends_with("notes.md"));
+    assert_eq!(
+        fs::read_to_string(workspace.join("notes.md")).unwrap(),
+        "---\ntitle: Notes\n---\n\n# Notes\n\nsee [rust][2] and [zet][1]\n\n[1]: https://github.com/lakrestofer/zet\n\n[2]: https://rust-lang.org \"Rust\"\n"
+    );
+
+    run_cli_cmd(&["format", "--link-style", "inline"], &workspace)
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(workspace.join("notes.md")).unwrap(),
+        "---\ntitle: Notes\n---\n\n# Notes\n\nsee [rust](https://rust-lang.org \"Rust\") and [zet](https://github.com/lakrestofer/zet)\n"
+    );
+}
+
+#[test]
+fn test_format_wrap() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(
+        workspace.join("notes.md"),
+        "# Notes\n\nA paragraph that is a bit too\nlong.\n\n| a table | that is too long |\n| --- | --- |\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(&["format", "--wrap", "20"], &workspace)
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(workspace.join("notes.md")).unwrap(),
+        "# Notes\n\nA paragraph that is\na bit too long.\n\n| a table | that is too long |\n| --- | --- |\n"
+    );
+}
+
+#[test]
+fn test_format_document_overrides() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(workspace.join(".zet/config.toml"), "[format]\nwrap = 20\n").unwrap();
+    let imported =
+        "---\nzet:\n  format:\n    wrap: false\n---\n\nA paragraph that is a bit too long.\n";
+    fs::write(workspace.join("imported.md"), imported).unwrap();
+    fs::write(
+        workspace.join("notes.md"),
+        "A paragraph that is a bit too long.\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(&["format"], &workspace).assert().success();
+    assert_eq!(
+        fs::read_to_string(workspace.join("imported.md")).unwrap(),
+        imported
+    );
+    assert_eq!(
+        fs::read_to_string(workspace.join("notes.md")).unwrap(),
+        "A paragraph that is\na bit too long.\n"
+    );
+}
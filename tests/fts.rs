@@ -137,3 +137,40 @@ fn test_fts_search_common_term() {
     assert!(ids.contains(&"rust-programming".to_string()));
     assert!(ids.contains(&"memory-management".to_string()));
 }
+
+#[test]
+fn test_fts_skips_comments() {
+    let (_temp, workspace) = setup_temp_workspace();
+    std::fs::write(
+        workspace.join("note.md"),
+        "# Note\n\nVisible text. %%hiddenword%%\n\n<!-- htmlhidden -->\n",
+    )
+    .unwrap();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    let search = |term: &str| {
+        query_document_ids(
+            &workspace,
+            &["query", "--match", term, "--output-format", "ids"],
+        )
+    };
+    assert_eq!(search("visible"), vec!["note".to_string()]);
+    assert!(search("hiddenword").is_empty());
+    assert!(search("htmlhidden").is_empty());
+
+    // with obsidian comments not recognized, their text is searched
+    std::fs::write(
+        workspace.join(".zet/config.toml"),
+        "[parser]\ncomments = [\"html\"]\n",
+    )
+    .unwrap();
+    std::fs::write(
+        workspace.join("note.md"),
+        "# Note\n\nVisible text. %%hiddenword%%\n\n<!-- htmlhidden -->\n\nMore.\n",
+    )
+    .unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    assert_eq!(search("hiddenword"), vec!["note".to_string()]);
+    assert!(search("htmlhidden").is_empty());
+}
@@ -0,0 +1,117 @@
+mod helpers;
+
+use helpers::{cli::*, db::*, *};
+
+/// Helper to setup graph test workspace
+///
+/// alpha -> beta, alpha -> gamma, beta -> gamma, delta -> alpha, epsilon is
+/// not linked at all.
+fn setup_graph_workspace() -> (assert_fs::TempDir, std::path::PathBuf) {
+    let (temp, workspace) = setup_temp_workspace();
+    copy_fixture_to_temp("query-test", &temp).unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    (temp, workspace)
+}
+
+fn graph_stats_json(workspace: &std::path::Path, args: &[&str]) -> serde_json::Value {
+    let mut cmd_args = vec!["graph", "stats", "--output-format", "json"];
+    cmd_args.extend_from_slice(args);
+    let output = run_cli_cmd(&cmd_args, workspace).output().unwrap();
+    assert!(output.status.success());
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn test_graph_stats_degrees() {
+    let (_temp, workspace) = setup_graph_workspace();
+
+    let value = graph_stats_json(&workspace, &[]);
+
+    assert_eq!(value["documents"], 5);
+    assert_eq!(value["links"], 4);
+    assert_eq!(value["components"], 2);
+
+    let stats = value["stats"].as_array().unwrap();
+    let get = |id: &str| {
+        stats
+            .iter()
+            .find(|s| s["document_id"] == id)
+            .unwrap_or_else(|| panic!("missing stats for {id}"))
+    };
+    assert_eq!(get("alpha")["in_degree"], 1);
+    assert_eq!(get("alpha")["out_degree"], 2);
+    assert_eq!(get("gamma")["in_degree"], 2);
+    assert_eq!(get("gamma")["out_degree"], 0);
+
+    // gamma is the most linked to document and should be the top hub
+    assert_eq!(value["hubs"][0]["document_id"], "gamma");
+}
+
+#[test]
+fn test_graph_stats_isolated_clusters() {
+    let (_temp, workspace) = setup_graph_workspace();
+
+    let value = graph_stats_json(&workspace, &[]);
+
+    let clusters = value["isolated_clusters"].as_array().unwrap();
+    assert_eq!(clusters.len(), 1);
+    assert_eq!(clusters[0], serde_json::json!(["epsilon"]));
+}
+
+#[test]
+fn test_graph_stats_cache() {
+    let (_temp, workspace) = setup_graph_workspace();
+
+    run_cli_cmd(&["graph", "stats"], &workspace)
+        .assert()
+        .success();
+    let db = open_test_db(&workspace);
+    let stored: i64 = db
+        .query_row("SELECT COUNT(*) FROM document_graph_stats", [], |r| {
+            r.get(0)
+        })
+        .unwrap();
+    assert_eq!(stored, 0, "statistics should only be stored with --cache");
+    drop(db);
+
+    run_cli_cmd(&["graph", "stats", "--cache"], &workspace)
+        .assert()
+        .success();
+    let db = open_test_db(&workspace);
+    let stored: i64 = db
+        .query_row("SELECT COUNT(*) FROM document_graph_stats", [], |r| {
+            r.get(0)
+        })
+        .unwrap();
+    assert_eq!(stored, 5);
+}
+
+#[test]
+fn test_graph_stats_snapshot() {
+    let (_temp, workspace) = setup_graph_workspace();
+
+    assert_eq!(graph_stats_json(&workspace, &[])["documents"], 5);
+
+    // the snapshot is reused as long as the index has not changed
+    let db = open_test_db(&workspace);
+    db.execute(
+        "UPDATE query_snapshot SET data = jsonb_set(data, '$.documents', 99) WHERE key = 'graph_stats'",
+        [],
+    )
+    .unwrap();
+    drop(db);
+    assert_eq!(graph_stats_json(&workspace, &[])["documents"], 99);
+    assert_eq!(
+        graph_stats_json(&workspace, &["--no-cache"])["documents"],
+        5
+    );
+
+    std::fs::write(workspace.join("zeta.md"), "# Zeta\n\n[[alpha]]\n").unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    let value = graph_stats_json(&workspace, &[]);
+    assert_eq!(value["documents"], 6);
+    assert_eq!(value["links"], 5);
+}
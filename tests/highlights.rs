@@ -0,0 +1,59 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+#[test]
+fn test_highlights() {
+    let (_temp, workspace) = setup_temp_workspace();
+    fs::write(
+        workspace.join("paper.md"),
+        "# A Paper\n\nThe ==main claim== holds, ==with caveats==.\n\nNot == a highlight ==, nor `==code==`.\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("book.md"),
+        "# A Book\n\n- a list item ==worth keeping==\n",
+    )
+    .unwrap();
+    fs::write(workspace.join("plain.md"), "# Plain\n\nNothing marked.\n").unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    let output = run_cli_cmd(&["highlights"], &workspace).output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "A Book (book)\n- worth keeping\n\nA Paper (paper)\n- main claim\n- with caveats\n"
+    );
+
+    let output = run_cli_cmd(
+        &[
+            "highlights",
+            "--document",
+            "paper",
+            "--output-format",
+            "json",
+        ],
+        &workspace,
+    )
+    .output()
+    .unwrap();
+    let highlights: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let highlights = highlights.as_array().unwrap();
+    assert_eq!(highlights.len(), 2);
+    assert_eq!(highlights[0]["content"], "main claim");
+
+    // highlights follow the document as it changes
+    fs::write(workspace.join("paper.md"), "# A Paper\n\nNo more.\n").unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    let output = run_cli_cmd(&["highlights", "--document", "paper"], &workspace)
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "");
+
+    run_cli_cmd(&["highlights", "--document", "missing"], &workspace)
+        .assert()
+        .failure();
+}
@@ -0,0 +1,64 @@
+mod helpers;
+
+use helpers::{cli::run_cli_cmd, setup_temp_workspace};
+use serde_json::{Value, json};
+use std::fs;
+
+#[test]
+fn test_hooks_receive_events() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        r#"
+[hooks]
+on_create = ["sh", "-c", "cat > created.json"]
+on_index = ["sh", "-c", "cat >> indexed.json; echo >> indexed.json"]
+"#,
+    )
+    .unwrap();
+
+    run_cli_cmd(&["create", "My Note"], &workspace)
+        .assert()
+        .success();
+    let created: Value =
+        serde_json::from_str(&fs::read_to_string(workspace.join("created.json")).unwrap()).unwrap();
+    assert_eq!(created["event"], "create");
+    assert_eq!(created["id"], "my-note");
+    assert_eq!(created["title"], "My Note");
+
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    // an index without changes runs no hook
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    fs::remove_file(workspace.join("my-note.md")).unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    let indexed: Vec<Value> = fs::read_to_string(workspace.join("indexed.json"))
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(indexed.len(), 2);
+    assert_eq!(indexed[0]["event"], "index");
+    assert_eq!(
+        indexed[0]["new"],
+        json!([workspace.join("my-note.md").to_string_lossy()])
+    );
+    assert_eq!(indexed[1]["removed"], json!(["my-note"]));
+}
+
+#[test]
+fn test_failing_hook_does_not_fail_command() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        "[hooks]\non_create = [\"false\"]\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(&["create", "My Note"], &workspace)
+        .assert()
+        .success();
+    assert!(workspace.join("my-note.md").exists());
+}
@@ -0,0 +1,160 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+const EMAIL: &str = "From: Alice <alice@example.com>\r
+Subject: Fwd: =?utf-8?Q?Caf=C3=A9?= reading\r
+Date: Tue, 1 Jul 2025 10:52:37 +0200\r
+Message-ID: <1@example.com>\r
+Content-Type: multipart/mixed; boundary=\"b\"\r
+\r
+--b\r
+Content-Type: text/plain; charset=utf-8\r
+Content-Transfer-Encoding: quoted-printable\r
+\r
+Worth a read, see <https://example.com/post>.\r
+--b\r
+Content-Type: application/pdf\r
+Content-Disposition: attachment; filename=\"paper.pdf\"\r
+Content-Transfer-Encoding: base64\r
+\r
+JVBERg==\r
+--b--\r
+";
+
+#[test]
+fn test_import_eml() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+
+    let maildir = workspace.parent().unwrap().join("mail");
+    for sub in ["cur", "new", "tmp"] {
+        fs::create_dir_all(maildir.join(sub)).unwrap();
+    }
+    fs::write(maildir.join("new").join("1.eml"), EMAIL).unwrap();
+    fs::write(
+        maildir.join("cur").join("2.eml"),
+        "Subject: Fwd:\nMessage-ID: <2@example.com>\n\nno subject\n",
+    )
+    .unwrap();
+    // a subject that slugs to nothing
+    fs::write(
+        maildir.join("cur").join("3.eml"),
+        "Subject: =?utf-8?Q?=E2=82?=\nMessage-ID: <3@example.com>\n\nmis-encoded\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(&["import", "eml", maildir.to_str().unwrap()], &workspace)
+        .assert()
+        .success();
+
+    let inbox: Vec<_> = fs::read_dir(workspace.join("inbox"))
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect();
+    assert_eq!(inbox.len(), 4, "{inbox:?}");
+    assert!(!inbox.iter().any(|name| name.starts_with('.')), "{inbox:?}");
+    let note = inbox
+        .iter()
+        .find(|name| {
+            name.ends_with(".md")
+                && fs::read_to_string(workspace.join("inbox").join(name))
+                    .unwrap()
+                    .contains("Café")
+        })
+        .unwrap();
+    let content = fs::read_to_string(workspace.join("inbox").join(note)).unwrap();
+    assert!(content.contains("title: \"Café reading\""), "{content}");
+    assert!(
+        content.contains("from: \"Alice <alice@example.com>\""),
+        "{content}"
+    );
+    assert!(content.contains("date: \"2025-07-01T"), "{content}");
+    assert!(
+        content.contains("message_id: \"1@example.com\""),
+        "{content}"
+    );
+    assert!(content.contains("Worth a read, see <https://example.com/post>."));
+    let stem = note.strip_suffix(".md").unwrap();
+    assert!(
+        content.contains(&format!("- [paper.pdf](<{stem}/paper.pdf>)")),
+        "{content}"
+    );
+    assert_eq!(
+        fs::read(workspace.join("inbox").join(stem).join("paper.pdf")).unwrap(),
+        b"%PDF"
+    );
+
+    let ids = query_document_ids(&workspace, &["query", "--output-format", "ids"]);
+    assert_eq!(
+        ids.iter()
+            .filter(|id| id.contains("untitled-email"))
+            .count(),
+        2,
+        "{ids:?}"
+    );
+
+    // imported emails are skipped
+    let output = run_cli_cmd(&["import", "eml", maildir.to_str().unwrap()], &workspace)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn test_import_opml_round_trip() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+
+    let opml = workspace.parent().unwrap().join("reading.opml");
+    fs::write(
+        &opml,
+        r#"<?xml version="1.0"?>
+<opml version="2.0">
+  <head><title>Reading list</title></head>
+  <body>
+    <outline text="Books">
+      <outline text="Dune"/>
+    </outline>
+    <outline text="Blog" type="rss" xmlUrl="https://example.com/feed.xml"/>
+  </body>
+</opml>"#,
+    )
+    .unwrap();
+
+    let output = run_cli_cmd(&["import", "opml", opml.to_str().unwrap()], &workspace)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let path = String::from_utf8(output.stdout).unwrap();
+    let content = fs::read_to_string(path.trim()).unwrap();
+    assert!(
+        content.ends_with(
+            "# Reading list\n\n- Books\n  - Dune\n- [Blog](https://example.com/feed.xml)\n\n"
+        ),
+        "{content}"
+    );
+
+    let output = run_cli_cmd(&["export", "opml", "reading-list"], &workspace)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<opml version="2.0">
+  <head>
+    <title>Reading list</title>
+  </head>
+  <body>
+    <outline text="Books">
+      <outline text="Dune"/>
+    </outline>
+    <outline text="Blog" htmlUrl="https://example.com/feed.xml"/>
+  </body>
+</opml>
+"#
+    );
+}
@@ -1,6 +1,8 @@
 mod helpers;
 
 use helpers::{cli::*, db::*, *};
+use zet::core::db::DbList;
+use zet::core::types::task::DocumentTask;
 
 #[test]
 fn test_index_new_documents() {
@@ -105,3 +107,227 @@ fn test_frontmatter_title_and_id_override() {
         "Title should match frontmatter title field"
     );
 }
+
+#[test]
+fn test_task_due_dates() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    std::fs::write(workspace.join(".zet/config.toml"), "timezone = \"UTC\"\n").unwrap();
+    std::fs::write(
+        workspace.join("tasks.md"),
+        "# Tasks\n\n- [ ] call Bob june 3 2025 at 3pm\n- [ ] pack\n",
+    )
+    .unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    let db = open_test_db(&workspace);
+    let tasks = DocumentTask::list(&db).unwrap();
+
+    assert_eq!(tasks.len(), 2);
+    assert_eq!(tasks[0].due, Some("2025-06-03T15:00:00Z".parse().unwrap()));
+    let span = tasks[0].due_start.unwrap()..tasks[0].due_end.unwrap();
+    assert_eq!(&tasks[0].content[span], "june 3 2025 at 3pm");
+    assert_eq!(tasks[1].due, None);
+}
+
+#[test]
+fn test_index_populates_document_data() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    std::fs::write(
+        workspace.join("a.md"),
+        "# A\n\n## Section\n\nsee [[b]] and [[missing]] #topic\n\n- [ ] write b\n",
+    )
+    .unwrap();
+    std::fs::write(workspace.join("b.md"), "# B\n\nback to [a](a)\n").unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    let db = open_test_db(&workspace);
+    assert_eq!(count_headings(&db), 3);
+    assert_eq!(count_tasks(&db), 1);
+    assert_eq!(get_tags_for_document(&db, "a"), vec!["topic"]);
+    let mut links = get_links_from(&db, "a");
+    links.sort();
+    assert_eq!(
+        links,
+        vec![
+            ("a".to_owned(), None),
+            ("a".to_owned(), Some("b".to_owned()))
+        ]
+    );
+    assert_eq!(
+        get_links_from(&db, "b"),
+        vec![("b".to_owned(), Some("a".to_owned()))]
+    );
+    drop(db);
+
+    // reindexing an updated document replaces its data instead of adding to it
+    std::fs::write(workspace.join("a.md"), "# A\n\n- [x] write b\n").unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    let db = open_test_db(&workspace);
+    assert_eq!(count_headings(&db), 2);
+    assert_eq!(count_tasks(&db), 1);
+    assert_eq!(count_checked_tasks(&db), 1);
+    assert!(get_tags_for_document(&db, "a").is_empty());
+    assert!(get_links_from(&db, "a").is_empty());
+}
+
+#[test]
+fn test_index_relative_links() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    std::fs::create_dir_all(workspace.join("notes/deep")).unwrap();
+    std::fs::write(
+        workspace.join("notes/deep/a.md"),
+        "---\nid: custom\n---\n\n# A\n\nsee [b](../My%20B.md#top) and [c](./../../c)\n",
+    )
+    .unwrap();
+    std::fs::write(workspace.join("notes/My B.md"), "# B\n").unwrap();
+    std::fs::write(
+        workspace.join("c.md"),
+        "# C\n\nback to [a](notes/deep/a.md)\n",
+    )
+    .unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    let db = open_test_db(&workspace);
+    assert_eq!(
+        get_links_from(&db, "custom"),
+        vec![
+            ("custom".to_owned(), Some("notes/my-b".to_owned())),
+            ("custom".to_owned(), Some("c".to_owned()))
+        ]
+    );
+    assert_eq!(
+        get_links_from(&db, "c"),
+        vec![("c".to_owned(), Some("custom".to_owned()))]
+    );
+}
+
+#[test]
+fn test_index_removed_document_cascades() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    std::fs::write(
+        workspace.join("a.md"),
+        "# A\n\nsee [[b]] #topic\n\n- [ ] write b\n",
+    )
+    .unwrap();
+    std::fs::write(workspace.join("b.md"), "# B\n\nback to [[a]]\n").unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    std::fs::remove_file(workspace.join("a.md")).unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    let db = open_test_db(&workspace);
+    assert_eq!(count_documents(&db), 1);
+    assert_eq!(count_headings(&db), 1);
+    assert_eq!(count_tasks(&db), 0);
+    assert_eq!(count_tags(&db), 0);
+    // the link from the removed document is gone, the one to it is broken
+    assert!(get_links_from(&db, "a").is_empty());
+    assert_eq!(get_links_from(&db, "b"), vec![("b".to_owned(), None)]);
+}
+
+#[test]
+fn test_index_failure_rolls_back() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    std::fs::write(workspace.join("a.md"), "# A\n\n- [ ] task\n").unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    // a document that can not be read, here a directory, fails the whole index
+    std::fs::remove_file(workspace.join("a.md")).unwrap();
+    std::fs::write(workspace.join("b.md"), "# B\n").unwrap();
+    std::fs::create_dir(workspace.join("c.md")).unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().failure();
+
+    let db = open_test_db(&workspace);
+    assert_eq!(
+        get_all_document_ids(&db),
+        vec![zet::core::types::document::DocumentId("a".to_owned())]
+    );
+    assert_eq!(count_tasks(&db), 1);
+    drop(db);
+
+    std::fs::remove_dir(workspace.join("c.md")).unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    let db = open_test_db(&workspace);
+    assert_eq!(count_documents(&db), 1);
+    assert_eq!(count_tasks(&db), 0);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_index_dangling_symlink_fails() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    std::fs::write(workspace.join("a.md"), "# A\n").unwrap();
+    std::os::unix::fs::symlink(workspace.join("missing.md"), workspace.join("c.md")).unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().failure();
+    assert_eq!(count_documents(&open_test_db(&workspace)), 0);
+
+    std::fs::remove_file(workspace.join("c.md")).unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    assert_eq!(count_documents(&open_test_db(&workspace)), 1);
+}
+
+#[test]
+fn test_index_interrupted_journal() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    std::fs::write(workspace.join("a.md"), "# A\n").unwrap();
+
+    // the journal of an earlier run that never committed
+    let journal = zet::core::index_journal_file(&workspace);
+    std::fs::write(
+        &journal,
+        r#"{"started":"2025-01-01T00:00:00Z","new":[],"updated":[],"removed":["b"]}"#,
+    )
+    .unwrap();
+
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    assert!(!journal.exists());
+    assert_eq!(count_documents(&open_test_db(&workspace)), 1);
+}
+
+#[test]
+fn test_index_waits_for_lock() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    std::fs::write(workspace.join("a.md"), "# A\n").unwrap();
+
+    // another process indexing the collection
+    let lock =
+        zet::core::db::DbLock::acquire(&zet::core::collection_db_lock_file(&workspace)).unwrap();
+    let release = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        drop(lock);
+    });
+
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    release.join().unwrap();
+    assert_eq!(count_documents(&open_test_db(&workspace)), 1);
+}
+
+#[test]
+fn test_writers_wait_for_lock() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    std::fs::write(workspace.join("a.md"), "# A\n").unwrap();
+
+    // another process writing to the collection for longer than zet waits
+    let _lock =
+        zet::core::db::DbLock::acquire(&zet::core::collection_db_lock_file(&workspace)).unwrap();
+
+    let output = run_cli_cmd(&["meta", "set", "status", "done"], &workspace)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("another zet process"));
+    assert_eq!(
+        std::fs::read_to_string(workspace.join("a.md")).unwrap(),
+        "# A\n"
+    );
+}
@@ -0,0 +1,207 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+fn journal_files(dir: &std::path::Path) -> Vec<String> {
+    let mut files: Vec<String> = fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn test_journal_ensure_daily() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+
+    let ensure = ["journal", "ensure", "--range", "from today to in 2 days"];
+    let created = query_document_ids(&workspace, &ensure);
+    assert_eq!(created.len(), 3);
+
+    let files = journal_files(&workspace.join("journal"));
+    assert_eq!(files.len(), 3);
+    let today = jiff::Zoned::now().strftime("%Y-%m-%d").to_string();
+    assert_eq!(files[0], format!("{today}.md"));
+
+    // already existing notes are not recreated
+    let created = query_document_ids(&workspace, &ensure);
+    assert!(created.is_empty());
+}
+
+#[test]
+fn test_journal_ensure_next_days() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+
+    // today and the six days after it
+    let ensure = ["journal", "ensure", "--range", "next 7 days"];
+    let created = query_document_ids(&workspace, &ensure);
+    assert_eq!(created.len(), 7);
+
+    let files = journal_files(&workspace.join("journal"));
+    let today = jiff::Zoned::now().strftime("%Y-%m-%d").to_string();
+    assert_eq!(files.len(), 7);
+    assert_eq!(files[0], format!("{today}.md"));
+}
+
+#[test]
+fn test_journal_ensure_weekly_with_config() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        "[journal.weekly]\ndirectory = \"weeks\"\ntemplate = \"week\"\n",
+    )
+    .unwrap();
+    let templates_dir = workspace.join(".zet/templates");
+    fs::create_dir_all(&templates_dir).unwrap();
+    fs::write(
+        templates_dir.join("week.md"),
+        "---\ntitle: {{ title }}\n---\n\n{{ period }} {{ date }} - {{ end }}\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(
+        &[
+            "journal",
+            "ensure",
+            "--period",
+            "weekly",
+            "--range",
+            "2025-01-06 to 2025-01-19",
+        ],
+        &workspace,
+    )
+    .assert()
+    .success();
+
+    let files = journal_files(&workspace.join("weeks"));
+    assert_eq!(files.len(), 2);
+    assert!(
+        files[0].contains("-w"),
+        "unexpected weekly filename {}",
+        files[0]
+    );
+
+    let content = fs::read_to_string(workspace.join("weeks").join(&files[0])).unwrap();
+    assert!(
+        content.contains("weekly "),
+        "template was not used: {content}"
+    );
+}
+
+#[test]
+fn test_journal_ensure_weekly_week_start() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        "[dates]\nweek_start = \"sunday\"\n",
+    )
+    .unwrap();
+
+    // a week from sunday to saturday is one note, titled by the iso week
+    // most of its days are in
+    let ensure = [
+        "journal",
+        "ensure",
+        "--period",
+        "weekly",
+        "--range",
+        "2026-10-11 to 2026-10-17",
+    ];
+    let created = query_document_ids(&workspace, &ensure);
+    assert_eq!(created.len(), 1);
+    assert_eq!(journal_files(&workspace.join("journal")), ["2026-w42.md"]);
+}
+
+#[test]
+fn test_journal_ensure_configured_timezone() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        "timezone = \"Etc/GMT-14\"\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(&["journal", "ensure"], &workspace)
+        .assert()
+        .success();
+
+    let files = journal_files(&workspace.join("journal"));
+    let today = jiff::Zoned::now()
+        .with_time_zone(jiff::tz::TimeZone::get("Etc/GMT-14").unwrap())
+        .strftime("%Y-%m-%d")
+        .to_string();
+    assert_eq!(files, vec![format!("{today}.md")]);
+}
+
+#[test]
+fn test_journal_ensure_invalid_timezone() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        "timezone = \"Nowhere/Atlantis\"\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(&["journal", "ensure"], &workspace)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_journal_navigation() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+
+    for from in ["2025-01-01", "2025-01-05"] {
+        run_cli_cmd(&["journal", "ensure", "--range", from], &workspace)
+            .assert()
+            .success();
+    }
+
+    // the default template links the nearest notes, or the adjacent days
+    let content = fs::read_to_string(workspace.join("journal/2025-01-05.md")).unwrap();
+    assert!(
+        content.contains("« [[2025-01-01]] | [[2025-01-06]] »"),
+        "{content}"
+    );
+    let content = fs::read_to_string(workspace.join("journal/2025-01-01.md")).unwrap();
+    assert!(
+        content.contains("« [[2024-12-31]] | [[2025-01-02]] »"),
+        "{content}"
+    );
+
+    let navigate = |args: &[&str]| {
+        let output = run_cli_cmd(args, &workspace).output().unwrap();
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8(output.stdout).unwrap())
+    };
+    let prev = navigate(&["journal", "--date", "2025-01-03", "--prev"]).unwrap();
+    assert!(prev.trim().ends_with("journal/2025-01-01.md"), "{prev}");
+    let next = navigate(&["journal", "--date", "2025-01-03", "--next"]).unwrap();
+    assert!(next.trim().ends_with("journal/2025-01-05.md"), "{next}");
+    let current = navigate(&["journal", "--date", "2025-01-05"]).unwrap();
+    assert!(
+        current.trim().ends_with("journal/2025-01-05.md"),
+        "{current}"
+    );
+
+    assert_eq!(
+        navigate(&["journal", "--date", "2025-01-05", "--next"]),
+        None
+    );
+    assert_eq!(navigate(&["journal", "--date", "2025-01-03"]), None);
+    assert_eq!(navigate(&["journal", "--prev", "--next"]), None);
+}
@@ -0,0 +1,50 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+const DOCUMENT: &str = "---\ntitle: Notes\nid: notes\n---\n\n# Notes\n\nsee https://example.com.\n\n```rust\nfn main() {}\n";
+
+#[test]
+fn test_lint_reports_issues() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(workspace.join("notes.md"), DOCUMENT).unwrap();
+
+    let output = run_cli_cmd(&["lint"], &workspace).output().unwrap();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 3, "{stdout}");
+    assert!(lines[0].ends_with(
+        "notes.md:2:1: frontmatter-key-order: frontmatter keys should start with id, title"
+    ));
+    assert!(
+        lines[1]
+            .ends_with("notes.md:8:5: bare-url: bare url https://example.com, should be a link")
+    );
+    assert!(lines[2].ends_with("notes.md:10:1: unclosed-code-fence: code block is never closed"));
+
+    // nothing is changed without --fix
+    assert_eq!(
+        fs::read_to_string(workspace.join("notes.md")).unwrap(),
+        DOCUMENT
+    );
+}
+
+#[test]
+fn test_lint_fix() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(workspace.join("notes.md"), DOCUMENT).unwrap();
+
+    run_cli_cmd(&["lint", "--fix"], &workspace)
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(workspace.join("notes.md")).unwrap(),
+        "---\nid: notes\ntitle: Notes\n---\n\n# Notes\n\nsee <https://example.com>.\n\n```rust\nfn main() {}\n```\n"
+    );
+    run_cli_cmd(&["lint"], &workspace).assert().success();
+}
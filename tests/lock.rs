@@ -0,0 +1,114 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+const LINKED: &str = "see [rust](https://rust-lang.org) and #draft\n";
+
+fn setup() -> (assert_fs::TempDir, std::path::PathBuf) {
+    let (temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        "[format]\nlink_style = \"reference\"\n",
+    )
+    .unwrap();
+    fs::write(workspace.join(".zet/locked"), "# published\npublished/\n").unwrap();
+    fs::create_dir_all(workspace.join("published")).unwrap();
+    fs::write(workspace.join("open.md"), format!("# Open\n\n{LINKED}")).unwrap();
+    fs::write(
+        workspace.join("canonical.md"),
+        format!("---\nlocked: true\n---\n# Canonical\n\n{LINKED}"),
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("published/post.md"),
+        format!("# Post\n\n{LINKED}"),
+    )
+    .unwrap();
+    (temp, workspace)
+}
+
+#[test]
+fn test_locked_documents_are_not_modified() {
+    let (_temp, workspace) = setup();
+    let locked = [
+        (
+            "canonical.md",
+            fs::read_to_string(workspace.join("canonical.md")).unwrap(),
+        ),
+        (
+            "published/post.md",
+            fs::read_to_string(workspace.join("published/post.md")).unwrap(),
+        ),
+    ];
+
+    let changed = query_document_ids(&workspace, &["format"]);
+    assert_eq!(changed.len(), 1);
+    assert!(changed[0].ends_with("open.md"));
+
+    run_cli_cmd(&["meta", "set", "status", "done"], &workspace)
+        .assert()
+        .success();
+    assert!(
+        fs::read_to_string(workspace.join("open.md"))
+            .unwrap()
+            .contains("status: \"done\"")
+    );
+
+    run_cli_cmd(&["tag", "rename", "draft", "final"], &workspace)
+        .assert()
+        .success();
+    assert!(
+        fs::read_to_string(workspace.join("open.md"))
+            .unwrap()
+            .contains("#final")
+    );
+
+    for (path, content) in &locked {
+        assert_eq!(&fs::read_to_string(workspace.join(path)).unwrap(), content);
+    }
+}
+
+#[test]
+fn test_locked_documents_are_not_fixed() {
+    let (_temp, workspace) = setup();
+    let document = "---\nlocked: true\n---\n# Notes\n\nsee https://example.com.\n";
+    fs::write(workspace.join("notes.md"), document).unwrap();
+
+    run_cli_cmd(&["lint", "--fix"], &workspace)
+        .assert()
+        .failure();
+    assert_eq!(
+        fs::read_to_string(workspace.join("notes.md")).unwrap(),
+        document
+    );
+}
+
+#[test]
+fn test_rename_refuses_to_rewrite_locked_documents() {
+    let (_temp, workspace) = setup();
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        "[lint]\nfilename_pattern = \"{{ slug }}.md\"\n",
+    )
+    .unwrap();
+    fs::write(workspace.join("Draft.md"), "# My Note\n").unwrap();
+    fs::write(
+        workspace.join("published/index.md"),
+        "# Index\n\nsee [[draft]]\n",
+    )
+    .unwrap();
+
+    let output = run_cli_cmd(&["normalize-filenames"], &workspace)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("links to it"), "{stderr}");
+    assert!(workspace.join("Draft.md").exists());
+    assert_eq!(
+        fs::read_to_string(workspace.join("published/index.md")).unwrap(),
+        "# Index\n\nsee [[draft]]\n"
+    );
+}
@@ -0,0 +1,73 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+fn events(workspace: &std::path::Path, args: &[&str]) -> Vec<(String, String, String)> {
+    let mut cmd = run_cli_cmd(
+        &[&["log", "--output-format", "json"], args].concat(),
+        workspace,
+    );
+    let output = assert_success(&mut cmd).get_output().stdout.clone();
+    let events: Vec<serde_json::Value> = serde_json::from_slice(&output).unwrap();
+    let mut events: Vec<_> = events
+        .into_iter()
+        .map(|event| {
+            let detail = event["data"]
+                .get("to")
+                .unwrap_or(&event["data"]["path"])
+                .as_str()
+                .unwrap()
+                .rsplit('/')
+                .next()
+                .unwrap()
+                .to_owned();
+            (
+                event["kind"].as_str().unwrap().to_owned(),
+                event["document_id"].as_str().unwrap().to_owned(),
+                detail,
+            )
+        })
+        .collect();
+    // the documents of an index run are recorded in the order they were read
+    events.sort();
+    events
+}
+
+fn event(kind: &str, id: &str, detail: &str) -> (String, String, String) {
+    (kind.to_owned(), id.to_owned(), detail.to_owned())
+}
+
+#[test]
+fn test_log() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(workspace.join("a.md"), "# A\n\nsee [[b]]\n").unwrap();
+    fs::write(workspace.join("b.md"), "# B\n").unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    let mut expected = vec![
+        event("document_added", "a", "a.md"),
+        event("document_added", "b", "b.md"),
+        event("link_added", "a", "b"),
+    ];
+    assert_eq!(events(&workspace, &[]), expected);
+
+    // an index without changes records nothing
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    assert_eq!(events(&workspace, &[]), expected);
+
+    fs::write(workspace.join("a.md"), "# A\n\nno links\n").unwrap();
+    fs::remove_file(workspace.join("b.md")).unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    expected.extend([
+        event("document_removed", "b", "b.md"),
+        event("document_updated", "a", "a.md"),
+        event("link_removed", "a", "b"),
+    ]);
+    expected.sort();
+    assert_eq!(events(&workspace, &[]), expected);
+    assert_eq!(events(&workspace, &["--since", "yesterday"]), expected);
+    assert_eq!(events(&workspace, &["--since", "tomorrow"]), vec![]);
+}
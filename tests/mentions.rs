@@ -0,0 +1,83 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+#[test]
+fn test_mentions_of() {
+    let (_temp, workspace) = setup_temp_workspace();
+    fs::create_dir_all(workspace.join("people")).unwrap();
+    fs::write(
+        workspace.join("people/alice.md"),
+        "---\naliases: [ali]\n---\n# Alice Liddell\n",
+    )
+    .unwrap();
+    fs::write(workspace.join("people/bob.md"), "# Bob\n").unwrap();
+    fs::write(
+        workspace.join("meeting.md"),
+        "# Meeting\n\nWith @alice and @bob, @Ali agreed.\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("letter.md"),
+        "# Letter\n\nDear @alice-liddell, write to alice@example.com.\n",
+    )
+    .unwrap();
+    fs::write(workspace.join("other.md"), "# Other\n\nOnly @bob.\n").unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    let output = run_cli_cmd(&["mentions-of", "@alice"], &workspace)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "letter\tLetter\t1\nmeeting\tMeeting\t2\n"
+    );
+
+    // by alias, as json
+    let output = run_cli_cmd(
+        &["mentions-of", "ali", "--output-format", "json"],
+        &workspace,
+    )
+    .output()
+    .unwrap();
+    let mentions: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(mentions["person"]["id"], "people/alice");
+    assert_eq!(mentions["mentions"].as_array().unwrap().len(), 3);
+
+    // names without a note are matched as written
+    let output = run_cli_cmd(&["mentions-of", "@nobody"], &workspace)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "");
+}
+
+#[test]
+fn test_mentions_people_group() {
+    let (_temp, workspace) = setup_temp_workspace();
+    fs::create_dir_all(workspace.join("contacts")).unwrap();
+    fs::write(workspace.join("contacts/carol.md"), "# Carol\n").unwrap();
+    fs::write(workspace.join("note.md"), "# Note\n\nSee @Carol.\n").unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        "[mentions]\ngroup = \"contacts\"\n\n[group.contacts]\ndirectories = [\"contacts\"]\n",
+    )
+    .unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    let output = run_cli_cmd(
+        &["mentions-of", "carol", "--output-format", "json"],
+        &workspace,
+    )
+    .output()
+    .unwrap();
+    let mentions: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(mentions["person"]["title"], "Carol");
+    assert_eq!(mentions["mentions"][0]["name"], "Carol");
+}
@@ -0,0 +1,118 @@
+mod helpers;
+
+use helpers::{cli::*, db::*, *};
+use std::fs;
+use std::path::Path;
+
+/// A collection at `dir` with the given files
+fn collection(dir: &Path, files: &[(&str, &[u8])]) {
+    for (path, content) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    run_cli_cmd(&["init"], dir).assert().success();
+}
+
+fn links_from(dir: &Path, id: &str) -> Vec<Option<String>> {
+    let db = open_test_db(dir);
+    get_links_from(&db, id)
+        .into_iter()
+        .map(|(_, to)| to)
+        .collect()
+}
+
+#[test]
+fn test_merge_with_prefix() {
+    let (_temp, workspace) = setup_temp_workspace();
+    let (work, personal) = (workspace.join("work"), workspace.join("personal"));
+    collection(
+        &work,
+        &[
+            ("a.md", b"# A\n\nsee [[b]]\n"),
+            ("b.md", b"# B\n\n![diagram](d.png)\n"),
+            ("d.png", &[1]),
+        ],
+    );
+    collection(
+        &personal,
+        &[
+            ("b.md", b"# Personal B\n\n![diagram](d.png)\n"),
+            ("c.md", b"# C\n\nsee [[b]] and [[a]]\n"),
+            ("e.md", b"---\nid: a\n---\n# E\n"),
+            ("d.png", &[2]),
+        ],
+    );
+    let other = personal.to_str().unwrap();
+
+    // b.md, d.png and the id of e.md collide
+    let output = run_cli_cmd(&["merge", other], &work).output().unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("3 files collide"), "{stderr}");
+    assert!(!work.join("c.md").exists());
+
+    run_cli_cmd(&["merge", other, "--on-collision", "prefix"], &work)
+        .assert()
+        .success();
+    assert_eq!(fs::read(work.join("d.png")).unwrap(), [1]);
+    assert_eq!(fs::read(work.join("personal-d.png")).unwrap(), [2]);
+    assert_eq!(
+        fs::read_to_string(work.join("personal-b.md")).unwrap(),
+        "# Personal B\n\n![diagram](personal-d.png)\n"
+    );
+    assert_eq!(
+        fs::read_to_string(work.join("c.md")).unwrap(),
+        "# C\n\nsee [[personal-b]] and [[personal-a]]\n"
+    );
+    assert!(
+        fs::read_to_string(work.join("personal-e.md"))
+            .unwrap()
+            .contains("id: \"personal-a\"")
+    );
+    // the notes of both collections are indexed, and linked as before
+    assert_eq!(count_documents(&open_test_db(&work)), 5);
+    assert_eq!(
+        links_from(&work, "c"),
+        vec![Some("personal-b".to_owned()), Some("personal-a".to_owned())]
+    );
+    assert_eq!(links_from(&work, "a"), vec![Some("b".to_owned())]);
+}
+
+#[test]
+fn test_merge_into_directory() {
+    let (_temp, workspace) = setup_temp_workspace();
+    let (work, personal) = (workspace.join("work"), workspace.join("personal"));
+    collection(&work, &[("a.md", b"# A\n"), ("archive/c.md", b"# Old C\n")]);
+    collection(
+        &personal,
+        &[("a.md", b"# Personal A\n\n[[c]]\n"), ("c.md", b"# C\n")],
+    );
+    let other = personal.to_str().unwrap();
+
+    let args = [
+        "merge",
+        other,
+        "--into",
+        "archive",
+        "--on-collision",
+        "skip",
+    ];
+    let lines = query_document_ids(&work, &[&args[..], &["--dry-run"]].concat());
+    assert_eq!(lines, vec!["a.md -> archive/a.md", "c.md skipped"]);
+    assert!(!work.join("archive/a.md").exists());
+
+    run_cli_cmd(&args, &work).assert().success();
+    assert_eq!(
+        fs::read_to_string(work.join("archive/a.md")).unwrap(),
+        "# Personal A\n\n[[archive/c]]\n"
+    );
+    assert_eq!(
+        fs::read_to_string(work.join("archive/c.md")).unwrap(),
+        "# Old C\n"
+    );
+    assert_eq!(
+        links_from(&work, "archive/a"),
+        vec![Some("archive/c".to_owned())]
+    );
+}
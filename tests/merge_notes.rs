@@ -0,0 +1,77 @@
+mod helpers;
+
+use helpers::{cli::*, db::*, *};
+use std::fs;
+
+#[test]
+fn test_merge_notes() {
+    let (_temp, workspace) = setup_temp_workspace();
+    fs::write(
+        workspace.join("apples.md"),
+        "---\ntitle: Apples\ntags: [fruit]\n---\n# Apples\n\nLike [[pears]].\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("pears.md"),
+        "---\ntitle: Pears\ntags: [green]\n---\n# Pears\n\n## Kinds\n\nfew\n",
+    )
+    .unwrap();
+    let other = workspace.join("other.md");
+    fs::write(
+        &other,
+        "# Other\n\n[[apples]] and [pear kinds](pears.md#kinds)\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    run_cli_cmd(
+        &[
+            "merge-notes",
+            "apples",
+            "pears",
+            "--into",
+            "fruit",
+            "--title",
+            "Fruit",
+        ],
+        &workspace,
+    )
+    .assert()
+    .success();
+
+    let fruit = fs::read_to_string(workspace.join("fruit.md")).unwrap();
+    assert!(fruit.contains("tags: [\"fruit\",\"green\"]"));
+    assert!(fruit.contains("aliases: [\"Apples\",\"Pears\"]"));
+    assert!(fruit.ends_with("# Apples\n\nLike [[#pears]].\n\n# Pears\n\n## Kinds\n\nfew\n"));
+    assert_eq!(
+        fs::read_to_string(&other).unwrap(),
+        "# Other\n\n[[fruit]] and [pear kinds](fruit.md#kinds)\n"
+    );
+    assert!(!workspace.join("apples.md").exists());
+    assert!(workspace.join(".zet/trash/apples.md").exists());
+    assert!(workspace.join(".zet/trash/pears.md").exists());
+
+    let db = open_test_db(&workspace);
+    let ids: Vec<String> = db
+        .prepare("select id from document order by id")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .map(|id| id.unwrap())
+        .collect();
+    assert_eq!(ids, ["fruit", "other"]);
+    drop(db);
+
+    run_cli_cmd(
+        &["merge-notes", "fruit", "other", "--into", "other"],
+        &workspace,
+    )
+    .assert()
+    .success();
+    assert!(
+        fs::read_to_string(&other)
+            .unwrap()
+            .contains("# Other\n\n[[#fruit]] and [pear kinds](#kinds)\n")
+    );
+}
@@ -0,0 +1,95 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+fn setup() -> (assert_fs::TempDir, std::path::PathBuf) {
+    let (temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(
+        workspace.join("dune.md"),
+        "---\ntitle: Dune\ntags:\n  - book\n# read in 2024\nstatus: reading\n---\n\n# Dune\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("alien.md"),
+        "---\ntitle: Alien\ntags: [film]\nstatus: watched\n---\n\n# Alien\n",
+    )
+    .unwrap();
+    (temp, workspace)
+}
+
+#[test]
+fn test_meta_set() {
+    let (_temp, workspace) = setup();
+
+    let diff = query_document_ids(
+        &workspace,
+        &[
+            "meta",
+            "set",
+            "status",
+            "read",
+            "--filter",
+            "tag:#book",
+            "--dry-run",
+        ],
+    );
+    assert_eq!(
+        diff,
+        vec![
+            "--- dune.md",
+            "+++ dune.md",
+            "@@ -6,1 +6,1 @@",
+            "-status: reading",
+            "+status: \"read\"",
+        ]
+    );
+    assert!(
+        fs::read_to_string(workspace.join("dune.md"))
+            .unwrap()
+            .contains("status: reading")
+    );
+
+    let edited = query_document_ids(
+        &workspace,
+        &["meta", "set", "rating", "5", "--filter", "tag:#book"],
+    );
+    assert_eq!(edited, vec!["dune.md"]);
+    assert_eq!(
+        fs::read_to_string(workspace.join("dune.md")).unwrap(),
+        "---\ntitle: Dune\ntags:\n  - book\n# read in 2024\nstatus: reading\nrating: 5\n---\n\n# Dune\n"
+    );
+    assert!(
+        !fs::read_to_string(workspace.join("alien.md"))
+            .unwrap()
+            .contains("rating")
+    );
+}
+
+#[test]
+fn test_meta_unset_and_rename_key() {
+    let (_temp, workspace) = setup();
+
+    let edited = query_document_ids(&workspace, &["meta", "rename-key", "status", "state"]);
+    assert_eq!(edited, vec!["alien.md", "dune.md"]);
+    assert_eq!(
+        fs::read_to_string(workspace.join("alien.md")).unwrap(),
+        "---\ntitle: Alien\ntags: [film]\nstate: watched\n---\n\n# Alien\n"
+    );
+
+    let edited = query_document_ids(
+        &workspace,
+        &["meta", "unset", "state", "--filter", "tag:book"],
+    );
+    assert_eq!(edited, vec!["dune.md"]);
+    assert_eq!(
+        fs::read_to_string(workspace.join("dune.md")).unwrap(),
+        "---\ntitle: Dune\ntags:\n  - book\n---\n\n# Dune\n"
+    );
+
+    // the new key must not exist already
+    run_cli_cmd(&["meta", "rename-key", "title", "tags"], &workspace)
+        .assert()
+        .failure();
+}
@@ -0,0 +1,107 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+fn setup() -> (assert_fs::TempDir, std::path::PathBuf) {
+    let (temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        "[lint]\nfilename_pattern = \"{{ slug }}.md\"\n",
+    )
+    .unwrap();
+    fs::create_dir_all(workspace.join("notes")).unwrap();
+    fs::write(workspace.join("notes/Draft.md"), "# My Note\n").unwrap();
+    fs::write(
+        workspace.join("index.md"),
+        "# Index\n\nsee [[notes/draft]], [the note](notes/draft) and [the file](notes/Draft.md#top)\n",
+    )
+    .unwrap();
+    (temp, workspace)
+}
+
+#[test]
+fn test_lint_filename_pattern() {
+    let (_temp, workspace) = setup();
+
+    let output = run_cli_cmd(&["lint"], &workspace).output().unwrap();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("Draft.md:1:1: filename-pattern: file name should be my-note.md"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn test_normalize_filenames() {
+    let (_temp, workspace) = setup();
+
+    // nothing is renamed in a dry run
+    let renames = query_document_ids(&workspace, &["normalize-filenames", "--dry-run"]);
+    assert_eq!(renames, vec!["notes/Draft.md -> notes/my-note.md"]);
+    assert!(workspace.join("notes/Draft.md").exists());
+
+    run_cli_cmd(&["normalize-filenames"], &workspace)
+        .assert()
+        .success();
+
+    assert!(!workspace.join("notes/Draft.md").exists());
+    assert!(workspace.join("notes/my-note.md").exists());
+    assert_eq!(
+        fs::read_to_string(workspace.join("index.md")).unwrap(),
+        "# Index\n\nsee [[notes/my-note]], [the note](notes/my-note) and [the file](notes/my-note.md#top)\n"
+    );
+    run_cli_cmd(&["lint"], &workspace).assert().success();
+}
+
+#[test]
+fn test_rename_hook() {
+    let (_temp, workspace) = setup();
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        "[lint]\nfilename_pattern = \"{{ slug }}.md\"\n\n[hooks]\non_rename = [\"sh\", \"-c\", \"cat > renamed.json\"]\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(&["normalize-filenames"], &workspace)
+        .assert()
+        .success();
+
+    let renamed: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(workspace.join("renamed.json")).unwrap()).unwrap();
+    assert_eq!(renamed["event"], "rename");
+    assert_eq!(renamed["old_id"], "notes/draft");
+    assert_eq!(renamed["new_id"], "notes/my-note");
+}
+
+#[test]
+fn test_normalize_filenames_suffix_sharing_ids() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        "[lint]\nfilename_pattern = \"{{ slug }}.md\"\n",
+    )
+    .unwrap();
+    fs::write(workspace.join("foo.md"), "# Renamed\n").unwrap();
+    fs::write(workspace.join("bar-foo.md"), "# Bar Foo\n").unwrap();
+    fs::write(
+        workspace.join("linker.md"),
+        "# Linker\n\n[[foo]] and [[bar-foo]]\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(&["normalize-filenames"], &workspace)
+        .assert()
+        .success();
+
+    assert!(workspace.join("renamed.md").exists());
+    assert!(workspace.join("bar-foo.md").exists());
+    // the link to bar-foo is left as it is, even though its id ends with foo
+    assert_eq!(
+        fs::read_to_string(workspace.join("linker.md")).unwrap(),
+        "# Linker\n\n[[renamed]] and [[bar-foo]]\n"
+    );
+}
@@ -272,6 +272,49 @@ fn test_query_sort_and_limit() {
     assert_eq!(ids[1], "beta");
 }
 
+#[test]
+fn test_query_page() {
+    let (_temp, workspace) = setup_query_workspace();
+
+    let page = |page: &str| {
+        query_document_ids(
+            &workspace,
+            &[
+                "query",
+                "--sort",
+                "id+",
+                "--limit",
+                "2",
+                "--page",
+                page,
+                "--output-format",
+                "ids",
+            ],
+        )
+    };
+    let all = query_document_ids(
+        &workspace,
+        &["query", "--sort", "id+", "--output-format", "ids"],
+    );
+
+    assert_eq!(page("1"), all[0..2]);
+    assert_eq!(page("2"), all[2..4]);
+    assert_eq!(page("3"), all[4..]);
+    assert!(page("4").is_empty());
+
+    // a page is only meaningful with a limit
+    run_cli_cmd(&["query", "--page", "2"], &workspace)
+        .assert()
+        .failure();
+
+    let listed = query_document_ids(&workspace, &["list", "--limit", "2", "--page", "2"]);
+    let ids: Vec<&str> = listed
+        .iter()
+        .map(|line| line.split('\t').next().unwrap())
+        .collect();
+    assert_eq!(ids, all[2..4]);
+}
+
 // =============================================================================
 // Combined filters
 // =============================================================================
@@ -321,3 +364,31 @@ fn test_query_links_to_and_tag() {
     assert!(ids.contains(&"alpha".to_string()));
     assert!(ids.contains(&"beta".to_string()));
 }
+
+// =============================================================================
+// Date ranges
+// =============================================================================
+
+#[test]
+fn test_query_created_range() {
+    let (_temp, workspace) = setup_query_workspace();
+
+    // the fixture was just copied, so every note was created today
+    let ids = query_document_ids(
+        &workspace,
+        &["query", "--created", "this week", "--output-format", "ids"],
+    );
+    assert_eq!(ids.len(), 5);
+
+    let ids = query_document_ids(
+        &workspace,
+        &[
+            "query",
+            "--modified",
+            "last month",
+            "--output-format",
+            "ids",
+        ],
+    );
+    assert!(ids.is_empty());
+}
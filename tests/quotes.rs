@@ -0,0 +1,74 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+#[test]
+fn test_quotes() {
+    let (_temp, workspace) = setup_temp_workspace();
+    fs::write(
+        workspace.join("reading.md"),
+        "# Reading\n\n> To be, or not\n> to be.\n> — Hamlet\n\nBetween.\n\n> Unattributed,\n> > with a nested quote\n\n> [!TIP]\n> Not a quote\n",
+    )
+    .unwrap();
+    fs::write(workspace.join("plain.md"), "# Plain\n\nNothing quoted.\n").unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    let output = run_cli_cmd(&["quotes"], &workspace).output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Reading (reading)\n- To be, or not to be. — Hamlet\n- Unattributed,\n"
+    );
+
+    let output = run_cli_cmd(
+        &["quotes", "--attributed", "--output-format", "json"],
+        &workspace,
+    )
+    .output()
+    .unwrap();
+    let quotes: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let quotes = quotes.as_array().unwrap();
+    assert_eq!(quotes.len(), 1);
+    assert_eq!(quotes[0]["attribution"], "Hamlet");
+
+    run_cli_cmd(&["quotes", "--document", "missing"], &workspace)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_quotes_note() {
+    let (_temp, workspace) = setup_temp_workspace();
+    fs::write(
+        workspace.join("reading.md"),
+        "# Reading\n\n> To be.\n> — Hamlet\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    run_cli_cmd(&["quotes", "--note", "Commonplace"], &workspace)
+        .assert()
+        .success();
+    let note = fs::read_to_string(workspace.join("commonplace.md")).unwrap();
+    assert!(note.contains("# Commonplace"), "{note}");
+    assert!(
+        note.contains("> To be.\n>\n> — Hamlet\n\nFrom [[reading]]"),
+        "{note}"
+    );
+
+    // the note exists, and its own quotes are left out when compiled again
+    run_cli_cmd(&["quotes", "--note", "Commonplace"], &workspace)
+        .assert()
+        .failure();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    run_cli_cmd(&["quotes", "--note", "Commonplace", "--force"], &workspace)
+        .assert()
+        .success();
+    let compiled = fs::read_to_string(workspace.join("commonplace.md")).unwrap();
+    assert_eq!(compiled, note);
+}
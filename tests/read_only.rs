@@ -0,0 +1,63 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+fn get_stderr(assert: &assert_cmd::assert::Assert) -> String {
+    String::from_utf8(assert.get_output().stderr.clone()).unwrap()
+}
+
+fn setup() -> (assert_fs::TempDir, std::path::PathBuf) {
+    let (temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(workspace.join("a.md"), "# A\n").unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    (temp, workspace)
+}
+
+#[test]
+fn test_read_only_flag() {
+    let (_temp, workspace) = setup();
+    fs::write(workspace.join("b.md"), "# B\n").unwrap();
+
+    let ids = query_document_ids(&workspace, &["--read-only", "list"]);
+    assert_eq!(ids.len(), 1);
+
+    let assert = run_cli_cmd(&["--read-only", "index"], &workspace)
+        .assert()
+        .failure();
+    assert!(get_stderr(&assert).contains("--read-only"));
+    run_cli_cmd(&["--read-only", "create", "c"], &workspace)
+        .assert()
+        .failure();
+    assert!(!workspace.join("c.md").exists());
+    let assert = run_cli_cmd(&["--read-only", "graph", "stats", "--cache"], &workspace)
+        .assert()
+        .failure();
+    assert!(get_stderr(&assert).contains("--read-only"));
+    run_cli_cmd(&["--read-only", "graph", "stats"], &workspace)
+        .assert()
+        .success();
+
+    // commands that only report what they would change still work
+    let plan = query_document_ids(&workspace, &["--read-only", "index", "--dry-run"]);
+    assert_eq!(plan, vec!["new      b.md"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_read_only_detected() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let (_temp, workspace) = setup();
+    let db = zet::core::collection_db_file(&workspace);
+    fs::set_permissions(&db, fs::Permissions::from_mode(0o444)).unwrap();
+
+    let ids = query_document_ids(&workspace, &["list"]);
+    assert_eq!(ids.len(), 1);
+    let assert = run_cli_cmd(&["index"], &workspace).assert().failure();
+    assert!(get_stderr(&assert).contains("is not writable"));
+
+    fs::set_permissions(&db, fs::Permissions::from_mode(0o644)).unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+}
@@ -0,0 +1,94 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+const PAGE: &str = r#"<html><head><title>Ignored</title><meta property="og:title" content="Attention: All You Need"><meta name="author" content="Vaswani"><meta name="description" content="Transformers &amp; more"></head></html>"#;
+
+#[test]
+fn test_readlist() {
+    let (temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+
+    let page = temp.path().join("page.html");
+    fs::write(&page, PAGE).unwrap();
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        format!(
+            "[fetch]\ncommand = [\"cat\", {:?}]\n",
+            page.to_str().unwrap()
+        ),
+    )
+    .unwrap();
+
+    let output = run_cli_cmd(
+        &["readlist", "add", "https://example.com/attention"],
+        &workspace,
+    )
+    .output()
+    .unwrap();
+    assert!(output.status.success());
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+    assert!(
+        path.ends_with("reading/attention--all-you-need.md"),
+        "{path}"
+    );
+    let content = fs::read_to_string(&path).unwrap();
+    assert!(
+        content.contains("title: \"Attention: All You Need\""),
+        "{content}"
+    );
+    assert!(
+        content.contains("url: \"https://example.com/attention\""),
+        "{content}"
+    );
+    assert!(content.contains("status: \"to-read\""), "{content}");
+    assert!(content.contains("author: \"Vaswani\""), "{content}");
+    assert!(content.contains("> Transformers & more"), "{content}");
+
+    // a url is added once
+    run_cli_cmd(
+        &["readlist", "add", "https://example.com/attention"],
+        &workspace,
+    )
+    .assert()
+    .failure();
+
+    run_cli_cmd(
+        &[
+            "readlist",
+            "add",
+            "https://example.com/other",
+            "--title",
+            "Other",
+        ],
+        &workspace,
+    )
+    .assert()
+    .success();
+    run_cli_cmd(
+        &["meta", "set", "status", "read", "--filter", "id:other"],
+        &workspace,
+    )
+    .assert()
+    .success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    let output = run_cli_cmd(&["readlist", "list", "--status", "to-read"], &workspace)
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "attention--all-you-need\tto-read\tAttention: All You Need\thttps://example.com/attention\n"
+    );
+
+    let output = run_cli_cmd(&["readlist", "list", "--output-format", "json"], &workspace)
+        .output()
+        .unwrap();
+    let entries: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(entries.as_array().unwrap().len(), 2);
+
+    run_cli_cmd(&["readlist", "list", "--status", "unknown"], &workspace)
+        .assert()
+        .failure();
+}
@@ -0,0 +1,55 @@
+mod helpers;
+
+use helpers::{cli::*, db::*, *};
+use std::fs;
+
+#[test]
+fn test_refs() {
+    let (_temp, workspace) = setup_temp_workspace();
+    fs::write(
+        workspace.join("reading.md"),
+        "# Reading\n\nsee [the post](https://Example.com/post/?utm_source=feed) and <https://example.com/post#intro>\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("paper.md"),
+        "# Paper\n\ncites [it](https://doi.org/10.1000/ABC) and [the post](http://example.com/other)\n",
+    )
+    .unwrap();
+    fs::write(workspace.join("post.md"), "# Post\n").unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    let output = run_cli_cmd(&["refs", "https://example.com/post"], &workspace)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "reading\tReading\t2\n"
+    );
+
+    // a DOI, however it is written
+    let output = run_cli_cmd(
+        &["refs", "doi:10.1000/abc", "--output-format", "json"],
+        &workspace,
+    )
+    .output()
+    .unwrap();
+    let references: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(references["url"], "https://doi.org/10.1000/abc");
+    assert_eq!(references["links"][0]["from"], "paper");
+
+    // links to pages are not links to the documents their paths end with
+    let db = open_test_db(&workspace);
+    assert_eq!(
+        get_links_from(&db, "reading"),
+        vec![("reading".to_owned(), None), ("reading".to_owned(), None)]
+    );
+    drop(db);
+
+    run_cli_cmd(&["refs", "notes/post.md"], &workspace)
+        .assert()
+        .failure();
+}
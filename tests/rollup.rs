@@ -0,0 +1,74 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+fn setup_rollup_workspace() -> (assert_fs::TempDir, std::path::PathBuf) {
+    let (temp, workspace) = setup_temp_workspace();
+    copy_fixture_to_temp("knowledge-base", &temp).unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    (temp, workspace)
+}
+
+#[test]
+fn test_rollup_week() {
+    let (_temp, workspace) = setup_rollup_workspace();
+
+    let output = run_cli_cmd(&["rollup", "--week", "today"], &workspace)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let path = String::from_utf8(output.stdout).unwrap();
+    let content = fs::read_to_string(path.trim()).unwrap();
+
+    // the fixture was just copied, so every note was created this week
+    assert!(content.contains("weekly rollup from"), "{content}");
+    assert!(content.contains("- [[tasks-and-checkboxes]]"), "{content}");
+    assert!(
+        content.contains(
+            "- [x] This completed task references another-page ([[tasks-and-checkboxes]])"
+        ),
+        "{content}"
+    );
+}
+
+#[test]
+fn test_rollup_requires_force_to_overwrite() {
+    let (_temp, workspace) = setup_rollup_workspace();
+
+    run_cli_cmd(&["rollup", "--month", "today"], &workspace)
+        .assert()
+        .success();
+    run_cli_cmd(&["rollup", "--month", "today"], &workspace)
+        .assert()
+        .failure();
+    run_cli_cmd(&["rollup", "--month", "today", "--force"], &workspace)
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_rollup_requires_period() {
+    let (_temp, workspace) = setup_rollup_workspace();
+
+    run_cli_cmd(&["rollup"], &workspace).assert().failure();
+}
+
+#[test]
+fn test_rollup_range() {
+    let (_temp, workspace) = setup_rollup_workspace();
+
+    let output = run_cli_cmd(&["rollup", "--range", "last 3 days"], &workspace)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let path = String::from_utf8(output.stdout).unwrap();
+    assert!(path.contains("rollup-"), "{path}");
+    let content = fs::read_to_string(path.trim()).unwrap();
+
+    assert!(content.contains("Rollup from"), "{content}");
+    assert!(content.contains("- [[tasks-and-checkboxes]]"), "{content}");
+}
@@ -0,0 +1,60 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+fn get_stderr(assert: &assert_cmd::assert::Assert) -> String {
+    String::from_utf8(assert.get_output().stderr.clone()).unwrap()
+}
+
+#[test]
+fn test_root_from_env() {
+    let (temp, workspace) = setup_temp_workspace();
+    fs::write(workspace.join("note.md"), "# Note\n").unwrap();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    let elsewhere = temp.path().join("elsewhere");
+    fs::create_dir_all(&elsewhere).unwrap();
+    let output = run_cli_cmd(&["list"], &elsewhere)
+        .env("ZET_ROOT", &workspace)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("note\t"));
+
+    let assert = run_cli_cmd(&["list"], &workspace)
+        .env("ZET_ROOT", &elsewhere)
+        .assert()
+        .failure();
+    assert!(
+        get_stderr(&assert).contains("does not contain a .zet directory"),
+        "{}",
+        get_stderr(&assert)
+    );
+}
+
+#[test]
+fn test_root_search_stops_at_home() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    let home = workspace.join("home");
+    let cwd = home.join("notes");
+    fs::create_dir_all(&cwd).unwrap();
+
+    // found walking up from a subdirectory
+    run_cli_cmd(&["list"], &cwd)
+        .env_remove("ZET_ROOT")
+        .assert()
+        .success();
+
+    // but not past $HOME
+    let assert = run_cli_cmd(&["list"], &cwd)
+        .env_remove("ZET_ROOT")
+        .env("HOME", &home)
+        .assert()
+        .failure();
+    let stderr = get_stderr(&assert);
+    assert!(stderr.contains("no .zet directory found"), "{stderr}");
+    assert!(stderr.contains(&format!("up to {:?}", home)), "{stderr}");
+}
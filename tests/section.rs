@@ -0,0 +1,59 @@
+mod helpers;
+
+use helpers::{cli::*, db::*, *};
+use std::fs;
+
+#[test]
+fn test_section_move_promote_demote() {
+    let (_temp, workspace) = setup_temp_workspace();
+    let note = workspace.join("paper.md");
+    fs::write(
+        &note,
+        "# Paper\n\n## Results\n\nr\n\n### Tables\n\nt\n\n## Method\n\nm\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    run_cli_cmd(
+        &["section", "move", "paper", "results", "--after", "Method"],
+        &workspace,
+    )
+    .assert()
+    .success();
+    assert_eq!(
+        fs::read_to_string(&note).unwrap(),
+        "# Paper\n\n## Method\n\nm\n\n## Results\n\nr\n\n### Tables\n\nt\n"
+    );
+
+    run_cli_cmd(&["section", "promote", "paper", "Tables"], &workspace)
+        .assert()
+        .success();
+    run_cli_cmd(&["section", "demote", "paper", "Method"], &workspace)
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(&note).unwrap(),
+        "# Paper\n\n### Method\n\nm\n\n## Results\n\nr\n\n## Tables\n\nt\n"
+    );
+
+    // the index follows the edits
+    let db = open_test_db(&workspace);
+    let level: i64 = db
+        .query_row(
+            "select level from document_heading where content = 'Method'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(level, 3);
+    drop(db);
+
+    run_cli_cmd(&["section", "promote", "paper", "Paper"], &workspace)
+        .assert()
+        .failure();
+    run_cli_cmd(&["section", "move", "paper", "Results"], &workspace)
+        .assert()
+        .failure();
+}
@@ -0,0 +1,57 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+#[test]
+fn test_sequence_navigation() {
+    let (_temp, workspace) = setup_temp_workspace();
+    for (id, title) in [
+        ("1", "Start"),
+        ("1a", "Branch"),
+        ("1a1", "Deeper"),
+        ("1b", "Other"),
+        ("2", "Next"),
+    ] {
+        fs::write(
+            workspace.join(format!("{id}.md")),
+            format!("---\nid: \"{id}\"\ntitle: {title}\n---\n\n# {title}\n"),
+        )
+        .unwrap();
+    }
+    fs::write(workspace.join("unrelated.md"), "# Unrelated\n").unwrap();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    let ids = |args: &[&str]| -> Vec<String> {
+        query_document_ids(&workspace, args)
+            .iter()
+            .map(|line| line.split('\t').next().unwrap().to_owned())
+            .collect()
+    };
+    assert_eq!(ids(&["next", "1a1"]), vec!["1b"]);
+    assert_eq!(ids(&["prev", "1a"]), vec!["1"]);
+    assert_eq!(ids(&["children", "1"]), vec!["1a", "1b"]);
+    assert!(ids(&["next", "2"]).is_empty());
+    assert!(ids(&["prev", "1"]).is_empty());
+
+    run_cli_cmd(&["next", "unrelated"], &workspace)
+        .assert()
+        .failure();
+
+    run_cli_cmd(&["export", "html", "--output", "site"], &workspace)
+        .assert()
+        .success();
+    let page = fs::read_to_string(workspace.join("site/1a.html")).unwrap();
+    assert!(page.contains("<nav class=\"sequence\">"), "{page}");
+    assert!(
+        page.contains("<span class=\"prev\"><a href=\"1.html\">Start</a></span>"),
+        "{page}"
+    );
+    assert!(
+        page.contains("<li><a href=\"1a1.html\">Deeper</a></li>"),
+        "{page}"
+    );
+    let page = fs::read_to_string(workspace.join("site/unrelated.html")).unwrap();
+    assert!(!page.contains("class=\"sequence\""));
+}
@@ -0,0 +1,87 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+/// Kills the server once the test is done
+struct Server(std::process::Child);
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn get(address: &str, path: &str) -> String {
+    // the server may still be starting
+    let mut stream = (0..50)
+        .find_map(|_| {
+            TcpStream::connect(address)
+                .inspect_err(|_| std::thread::sleep(Duration::from_millis(100)))
+                .ok()
+        })
+        .expect("the server did not start");
+    write!(stream, "GET {path} HTTP/1.1\r\nhost: {address}\r\n\r\n").unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+#[test]
+fn test_serve_metrics() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    std::fs::write(
+        workspace.join("a.md"),
+        "# A\n\n[[b]] [[missing]]\n\n- [ ] one\n- [x] two\n",
+    )
+    .unwrap();
+    std::fs::write(workspace.join("b.md"), "# B\n\n- [/] three\n").unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let address = format!("127.0.0.1:{port}");
+    let _server = Server(
+        std::process::Command::new(assert_cmd::cargo::cargo_bin!("zet"))
+            .args(["serve", "--metrics", "--address", &address])
+            .current_dir(&workspace)
+            .spawn()
+            .unwrap(),
+    );
+
+    let response = get(&address, "/metrics");
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{response}");
+    for metric in [
+        "zet_documents 2\n",
+        "zet_broken_links 1\n",
+        "zet_open_tasks 2\n",
+        "zet_index_runs_total 1\n",
+        "zet_index_changes 2\n",
+        "zet_last_index_timestamp_seconds ",
+    ] {
+        assert!(
+            response.contains(metric),
+            "{metric:?} missing from {response}"
+        );
+    }
+
+    let response = get(&address, "/");
+    assert!(
+        response.starts_with("HTTP/1.1 404 Not Found\r\n"),
+        "{response}"
+    );
+}
+
+#[test]
+fn test_serve_nothing() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["serve"], &workspace).assert().failure();
+}
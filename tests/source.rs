@@ -0,0 +1,69 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+#[test]
+fn test_source_check() {
+    let (_temp, workspace) = setup_temp_workspace();
+    fs::write(
+        workspace.join("post.md"),
+        "---\nurl: https://Example.com/post/?utm_source=feed\nstatus: unread\n---\n# Post\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("paper.md"),
+        "# Paper\n\ncites [it](doi:10.1000/ABC) and [the post](https://example.com/post)\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    let output = run_cli_cmd(
+        &[
+            "source",
+            "check",
+            "HTTPS://EXAMPLE.com/post#intro",
+            "--output-format",
+            "json",
+        ],
+        &workspace,
+    )
+    .output()
+    .unwrap();
+    assert!(output.status.success());
+    let source: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(source["url"], "https://example.com/post");
+    assert_eq!(source["documents"], serde_json::json!(["paper", "post"]));
+    let today = jiff::Zoned::now().date().to_string();
+    assert!(source["first_seen"].as_str().unwrap() <= today.as_str());
+    assert!(source["last_seen"].as_str().unwrap() >= source["first_seen"].as_str().unwrap());
+
+    // a source stays known once its references are gone
+    fs::write(workspace.join("paper.md"), "# Paper\n").unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    let output = run_cli_cmd(
+        &["source", "check", "https://doi.org/10.1000/abc"],
+        &workspace,
+    )
+    .output()
+    .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.starts_with("https://doi.org/10.1000/abc\t"),
+        "{stdout}"
+    );
+    assert_eq!(stdout.lines().count(), 1);
+
+    run_cli_cmd(
+        &["source", "check", "https://example.com/other"],
+        &workspace,
+    )
+    .assert()
+    .failure();
+    run_cli_cmd(&["source", "check", "notes/post.md"], &workspace)
+        .assert()
+        .failure();
+}
@@ -0,0 +1,124 @@
+mod helpers;
+
+use helpers::{cli::*, db::*, *};
+use std::fs;
+use std::path::Path;
+
+fn write_collection(work: &Path) {
+    let files: [(&str, &[u8]); 7] = [
+        ("index.md", b"# Index\n\nsee [[projects/alpha/plan]]\n"),
+        ("other.md", b"# Other\n"),
+        ("projects/alpha/plan.md", b"# Plan\n\nsee [[tasks]] and [[index]]\n"),
+        ("projects/alpha/tasks.md", b"# Tasks\n\n![chart](chart.png)\n"),
+        ("projects/alpha/chart.png", &[1, 2]),
+        (
+            ".zet/config.toml",
+            b"[group.projects]\ndirectories = [\"projects/alpha\"]\ntemplate = \"project\"\n\n[group.daily]\ndirectories = [\"journal\"]\n",
+        ),
+        (".zet/templates/project.md", b"# {{ title }}\n"),
+    ];
+    for (path, content) in files {
+        let path = work.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    let db = work.join(".zet/db.sqlite");
+    drop(zet::core::db::DB::open(db).unwrap());
+}
+
+#[test]
+fn test_split_with_stubs() {
+    let (_temp, workspace) = setup_temp_workspace();
+    let (work, alpha) = (workspace.join("work"), workspace.join("alpha"));
+    write_collection(&work);
+
+    run_cli_cmd(
+        &["split", "projects/alpha", "--out", alpha.to_str().unwrap()],
+        &work,
+    )
+    .assert()
+    .success();
+
+    // the moved notes link to each other, and to a stub of the note that stays
+    assert_eq!(
+        fs::read_to_string(alpha.join("plan.md")).unwrap(),
+        "# Plan\n\nsee [[tasks]] and [[index]]\n"
+    );
+    assert_eq!(fs::read(alpha.join("chart.png")).unwrap(), [1, 2]);
+    let stub = fs::read_to_string(alpha.join("index.md")).unwrap();
+    assert!(
+        stub.starts_with("# Index\n\nSee index.md in the collection at"),
+        "{stub}"
+    );
+    assert_eq!(
+        links_to(&alpha, "plan"),
+        vec![Some("index".to_owned()), Some("tasks".to_owned())]
+    );
+
+    // only the group in the directory, and its template, are carried over
+    let config = fs::read_to_string(alpha.join(".zet/config.toml")).unwrap();
+    assert!(config.contains("[group.projects]"), "{config}");
+    assert!(!config.contains("daily"), "{config}");
+    assert!(alpha.join(".zet/templates/project.md").is_file());
+
+    // the linked note is replaced by a stub in the old collection
+    assert!(!work.join("projects/alpha/tasks.md").exists());
+    assert!(!work.join("projects/alpha/chart.png").exists());
+    let stub = fs::read_to_string(work.join("projects/alpha/plan.md")).unwrap();
+    assert!(
+        stub.starts_with("# Plan\n\nSee plan.md in the collection at"),
+        "{stub}"
+    );
+    assert_eq!(
+        links_to(&work, "index"),
+        vec![Some("projects/alpha/plan".to_owned())]
+    );
+    assert_eq!(count_documents(&open_test_db(&work)), 3);
+}
+
+#[test]
+fn test_split_with_text() {
+    let (_temp, workspace) = setup_temp_workspace();
+    let (work, alpha) = (workspace.join("work"), workspace.join("alpha"));
+    write_collection(&work);
+
+    let args = [
+        "split",
+        "projects/alpha",
+        "--out",
+        alpha.to_str().unwrap(),
+        "--cross-links",
+        "text",
+    ];
+    run_cli_cmd(&[&args[..], &["--dry-run"]].concat(), &work)
+        .assert()
+        .success();
+    assert!(!alpha.exists());
+
+    run_cli_cmd(&args, &work).assert().success();
+    assert_eq!(
+        fs::read_to_string(alpha.join("plan.md")).unwrap(),
+        "# Plan\n\nsee [[tasks]] and index\n"
+    );
+    assert_eq!(
+        fs::read_to_string(work.join("index.md")).unwrap(),
+        "# Index\n\nsee projects/alpha/plan\n"
+    );
+    assert!(!work.join("projects/alpha").exists());
+    assert_eq!(count_documents(&open_test_db(&alpha)), 2);
+
+    // a collection is not split into a directory that is not empty
+    run_cli_cmd(&["split", ".", "--out", alpha.to_str().unwrap()], &work)
+        .assert()
+        .failure();
+}
+
+fn links_to(dir: &Path, id: &str) -> Vec<Option<String>> {
+    let db = open_test_db(dir);
+    let mut links: Vec<_> = get_links_from(&db, id)
+        .into_iter()
+        .map(|(_, to)| to)
+        .collect();
+    links.sort();
+    links
+}
@@ -0,0 +1,55 @@
+mod helpers;
+
+use helpers::{cli::*, db::*, *};
+use std::fs;
+
+#[test]
+fn test_split_note() {
+    let (_temp, workspace) = setup_temp_workspace();
+    let note = workspace.join("paper.md");
+    fs::write(
+        &note,
+        "# Paper\n\nintro\n\n## Method\n\nSee [[#Results]].\n\n### Setup\n\ns\n\n## Results\n\nr\n",
+    )
+    .unwrap();
+    let other = workspace.join("other.md");
+    fs::write(
+        &other,
+        "# Other\n\n[[paper#Setup]], [[paper#Results|results]] and [[paper#Paper]]\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    run_cli_cmd(&["split-note", "paper"], &workspace)
+        .assert()
+        .success();
+
+    let db = open_test_db(&workspace);
+    let note_of = |title: &str| -> (String, String) {
+        db.query_row(
+            "select id, path from document where title = ?1",
+            [title],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap()
+    };
+    let (method, method_path) = note_of("Method");
+    let (results, _) = note_of("Results");
+    drop(db);
+
+    assert_eq!(
+        fs::read_to_string(&note).unwrap(),
+        format!("# Paper\n\nintro\n\n[[{method}|Method]]\n\n[[{results}|Results]]\n")
+    );
+    let method_note = fs::read_to_string(&method_path).unwrap();
+    assert!(method_note.contains(&format!("See [[{results}]].\n\n## Setup\n\ns")));
+    assert_eq!(
+        fs::read_to_string(&other).unwrap(),
+        format!("# Other\n\n[[{method}#Setup]], [[{results}|results]] and [[paper#Paper]]\n")
+    );
+
+    run_cli_cmd(&["split-note", "paper", "--level", "3"], &workspace)
+        .assert()
+        .failure();
+}
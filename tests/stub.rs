@@ -0,0 +1,75 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+/// Helper to setup a knowledge-base workspace, optionally with stubs enabled
+fn setup_stub_workspace(create_stubs: bool) -> (assert_fs::TempDir, std::path::PathBuf) {
+    let (temp, workspace) = setup_temp_workspace();
+    copy_fixture_to_temp("knowledge-base", &temp).unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        format!("[index]\ncreate_stubs = {create_stubs}\n"),
+    )
+    .unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    (temp, workspace)
+}
+
+fn list_stub_ids(workspace: &std::path::Path) -> Vec<String> {
+    query_document_ids(workspace, &["list", "--stubs"])
+        .into_iter()
+        .map(|line| line.split('\t').next().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn test_stubs_disabled_by_default() {
+    let (_temp, workspace) = setup_stub_workspace(false);
+
+    assert!(list_stub_ids(&workspace).is_empty());
+}
+
+#[test]
+fn test_stubs_for_unresolved_wikilinks() {
+    let (_temp, workspace) = setup_stub_workspace(true);
+
+    let stubs = list_stub_ids(&workspace);
+    assert!(stubs.contains(&"non-existent-page".to_string()));
+    assert!(stubs.contains(&"wiki-link".to_string()));
+    // resolved wikilinks and markdown links never become stubs
+    assert!(!stubs.contains(&"index".to_string()));
+    assert!(!stubs.iter().any(|id| id.contains("test.com")));
+}
+
+#[test]
+fn test_create_from_stub() {
+    let (_temp, workspace) = setup_stub_workspace(true);
+
+    let output = run_cli_cmd(&["create", "--from-stub", "non-existent-page"], &workspace)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let path = String::from_utf8(output.stdout).unwrap();
+    let path = std::path::Path::new(path.trim());
+    assert_eq!(path.file_name().unwrap(), "non-existent-page.md");
+    assert!(path.exists());
+
+    assert!(!list_stub_ids(&workspace).contains(&"non-existent-page".to_string()));
+
+    // the stub does not come back once the note is indexed
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    assert!(!list_stub_ids(&workspace).contains(&"non-existent-page".to_string()));
+}
+
+#[test]
+fn test_create_from_unknown_stub_fails() {
+    let (_temp, workspace) = setup_stub_workspace(true);
+
+    run_cli_cmd(&["create", "--from-stub", "no-such-stub"], &workspace)
+        .assert()
+        .failure();
+}
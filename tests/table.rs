@@ -0,0 +1,83 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+#[test]
+fn test_table_import_export() {
+    let (_temp, workspace) = setup_temp_workspace();
+    fs::write(
+        workspace.join("results.md"),
+        "# Results\n\n## Data\n\nmeasured in march\n\n## Discussion\n\nfine\n",
+    )
+    .unwrap();
+    let csv = workspace.parent().unwrap().join("results.csv");
+    fs::write(&csv, "run,score\n1,\"0.5, rounded\"\n2,0.7\n").unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    run_cli_cmd(
+        &[
+            "table",
+            "import",
+            csv.to_str().unwrap(),
+            "--into",
+            "results",
+            "--under",
+            "Data",
+        ],
+        &workspace,
+    )
+    .assert()
+    .success();
+    let content = fs::read_to_string(workspace.join("results.md")).unwrap();
+    assert_eq!(
+        content,
+        "# Results\n\n## Data\n\nmeasured in march\n\n\
+         | run | score        |\n\
+         | --- | ------------ |\n\
+         | 1   | 0.5, rounded |\n\
+         | 2   | 0.7          |\n\n\
+         ## Discussion\n\nfine\n"
+    );
+
+    let output = run_cli_cmd(&["table", "export", "results", "--nth", "1"], &workspace)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "run,score\r\n1,\"0.5, rounded\"\r\n2,0.7\r\n"
+    );
+
+    // importing again replaces the table
+    let json = workspace.parent().unwrap().join("results.json");
+    fs::write(&json, r#"[{"run": 3, "score": 0.9}]"#).unwrap();
+    run_cli_cmd(
+        &[
+            "table",
+            "import",
+            json.to_str().unwrap(),
+            "--into",
+            "results",
+            "--under",
+            "data",
+        ],
+        &workspace,
+    )
+    .assert()
+    .success();
+    let output = run_cli_cmd(
+        &["table", "export", "results", "--format", "json"],
+        &workspace,
+    )
+    .output()
+    .unwrap();
+    let rows: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(rows, serde_json::json!([{"run": "3", "score": "0.9"}]));
+
+    run_cli_cmd(&["table", "export", "results", "--nth", "2"], &workspace)
+        .assert()
+        .failure();
+}
@@ -0,0 +1,83 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+fn setup() -> (assert_fs::TempDir, std::path::PathBuf) {
+    let (temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(
+        workspace.join("dune.md"),
+        "---\ntitle: Dune\ntags: [book, scifi]\n---\n\n# Dune\n\nA #Book about #spice, see `#book`\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("alien.md"),
+        "---\ntitle: Alien\n---\n\n# Alien\n\n- [ ] watch again #sci-fi\n",
+    )
+    .unwrap();
+    (temp, workspace)
+}
+
+#[test]
+fn test_tag_rename() {
+    let (_temp, workspace) = setup();
+
+    let changed = query_document_ids(&workspace, &["tag", "rename", "#book", "novel"]);
+    assert_eq!(changed, vec!["dune.md"]);
+    assert_eq!(
+        fs::read_to_string(workspace.join("dune.md")).unwrap(),
+        "---\ntitle: Dune\ntags: [\"novel\",\"scifi\"]\n---\n\n# Dune\n\nA #novel about #spice, see `#book`\n"
+    );
+
+    let ids = query_document_ids(
+        &workspace,
+        &["query", "--tag", "novel", "--output-format", "ids"],
+    );
+    assert_eq!(ids, vec!["dune"]);
+    let ids = query_document_ids(
+        &workspace,
+        &["query", "--tag", "book", "--output-format", "ids"],
+    );
+    assert!(ids.is_empty());
+}
+
+#[test]
+fn test_tag_merge() {
+    let (_temp, workspace) = setup();
+
+    let changed = query_document_ids(
+        &workspace,
+        &[
+            "tag",
+            "merge",
+            "scifi",
+            "sci-fi",
+            "--into",
+            "science-fiction",
+        ],
+    );
+    assert_eq!(changed, vec!["alien.md", "dune.md"]);
+    assert_eq!(
+        fs::read_to_string(workspace.join("alien.md")).unwrap(),
+        "---\ntitle: Alien\n---\n\n# Alien\n\n- [ ] watch again #science-fiction\n"
+    );
+
+    let ids = query_document_ids(
+        &workspace,
+        &[
+            "query",
+            "--tag",
+            "science-fiction",
+            "--sort",
+            "id",
+            "--output-format",
+            "ids",
+        ],
+    );
+    assert_eq!(ids, vec!["alien", "dune"]);
+
+    run_cli_cmd(&["tag", "rename", "spice", "not a tag"], &workspace)
+        .assert()
+        .failure();
+}
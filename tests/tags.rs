@@ -0,0 +1,104 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+fn setup() -> (assert_fs::TempDir, std::path::PathBuf) {
+    let (temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    fs::write(
+        workspace.join("parser.md"),
+        "---\ntags: [project/zet/parser]\n---\n\n# Parser\n\nsee #project/zet\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("lsp.md"),
+        "# Lsp\n\n#project/zet/lsp #book\n",
+    )
+    .unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+    (temp, workspace)
+}
+
+#[test]
+fn test_tags_prefix() {
+    let (_temp, workspace) = setup();
+
+    let tags = query_document_ids(&workspace, &["tags", "project/zet/"]);
+    assert_eq!(tags, vec!["1  project/zet/lsp", "1  project/zet/parser"]);
+
+    let tags = query_document_ids(&workspace, &["tags", "--tree"]);
+    assert_eq!(
+        tags,
+        vec![
+            "1  book",
+            "0  project",
+            "1    zet",
+            "1      lsp",
+            "1      parser",
+        ]
+    );
+}
+
+#[test]
+fn test_query_nested_tag() {
+    let (_temp, workspace) = setup();
+
+    let ids = query_document_ids(
+        &workspace,
+        &[
+            "query",
+            "--tag",
+            "project",
+            "--sort",
+            "id",
+            "--output-format",
+            "ids",
+        ],
+    );
+    assert_eq!(ids, vec!["lsp", "parser"]);
+
+    let ids = query_document_ids(
+        &workspace,
+        &[
+            "query",
+            "--tag",
+            "project/zet/lsp",
+            "--output-format",
+            "ids",
+        ],
+    );
+    assert_eq!(ids, vec!["lsp"]);
+}
+
+#[test]
+fn test_flavor() {
+    let (_temp, workspace) = setup();
+    let reindex = |config: &str| {
+        fs::write(workspace.join(".zet/config.toml"), config).unwrap();
+        fs::remove_file(workspace.join(".zet/db.sqlite")).unwrap();
+        run_cli_cmd(&["index"], &workspace).assert().success();
+        query_document_ids(&workspace, &["tags"])
+    };
+
+    // gfm has no inline tags, only the ones of the frontmatter are left
+    assert_eq!(reindex("flavor = \"gfm\"\n"), vec!["1  project/zet/parser"]);
+    // settings written out take precedence over the flavor
+    let tags = reindex("flavor = \"gfm\"\n\n[parser]\ninline_tags = true\n");
+    assert!(tags.contains(&"1  book".to_owned()), "{tags:?}");
+    // as is the flavor of a document
+    fs::write(
+        workspace.join("imported.md"),
+        "---\nzet:\n  flavor: obsidian\n---\n\n#imported\n",
+    )
+    .unwrap();
+    let tags = reindex("flavor = \"gfm\"\n");
+    assert_eq!(tags, vec!["1  imported", "1  project/zet/parser"]);
+
+    fs::write(
+        workspace.join(".zet/config.toml"),
+        "flavor = \"markdown\"\n",
+    )
+    .unwrap();
+    run_cli_cmd(&["index"], &workspace).assert().failure();
+}
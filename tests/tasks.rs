@@ -0,0 +1,111 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+fn setup_meeting_workspace() -> (assert_fs::TempDir, std::path::PathBuf) {
+    let (temp, workspace) = setup_temp_workspace();
+    fs::create_dir_all(workspace.join("people")).unwrap();
+    fs::write(
+        workspace.join("people/alice.md"),
+        "---\naliases: [ali]\n---\n# Alice Liddell\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("meeting.md"),
+        "# Meeting\n\n- [ ] an ordinary task\n- [ ] @Ali sends the notes\n\n## Action items\n\n- [ ] book the room\n- [x] @bob writes the agenda\n- [-] @alice calls the caterer\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("standup.md"),
+        "# Standup\n\n- [/] review the plan @alice-liddell\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    (temp, workspace)
+}
+
+#[test]
+fn test_tasks_owner() {
+    let (_temp, workspace) = setup_meeting_workspace();
+
+    let output = run_cli_cmd(&["tasks", "--owner", "alice"], &workspace)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Meeting (meeting)\n- [ ] @Ali sends the notes\n- [-] @alice calls the caterer\n\nStandup (standup)\n- [/] review the plan @alice-liddell\n"
+    );
+
+    // open tasks only, names without a note are matched as written
+    let output = run_cli_cmd(&["tasks", "--owner", "@alice", "--open"], &workspace)
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout).lines().count(), 5);
+    let output = run_cli_cmd(&["tasks", "--owner", "bob"], &workspace)
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Meeting (meeting)\n- [x] @bob writes the agenda\n"
+    );
+}
+
+#[test]
+fn test_tasks_action_items() {
+    let (_temp, workspace) = setup_meeting_workspace();
+
+    let output = run_cli_cmd(
+        &["tasks", "--action-items", "--output-format", "json"],
+        &workspace,
+    )
+    .output()
+    .unwrap();
+    assert!(output.status.success());
+    let tasks: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let contents: Vec<&str> = tasks
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["content"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        contents,
+        vec![
+            "@Ali sends the notes",
+            "book the room",
+            "@bob writes the agenda",
+            "@alice calls the caterer",
+            "review the plan @alice-liddell",
+        ]
+    );
+    assert_eq!(tasks[0]["owner"], "Ali");
+    assert_eq!(tasks[1]["owner"], serde_json::Value::Null);
+}
+
+#[test]
+fn test_rollup_action_items() {
+    let (_temp, workspace) = setup_meeting_workspace();
+
+    let output = run_cli_cmd(&["rollup", "--week", "today"], &workspace)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let path = String::from_utf8(output.stdout).unwrap();
+    let content = fs::read_to_string(path.trim()).unwrap();
+
+    // by person, cancelled ones left out
+    let section = content
+        .split("## Action items")
+        .nth(1)
+        .and_then(|s| s.split("## Created notes").next())
+        .unwrap_or_else(|| panic!("{content}"));
+    assert_eq!(
+        section.trim(),
+        "### @alice\n\n- [ ] @Ali sends the notes ([[meeting]])\n- [ ] review the plan @alice-liddell ([[standup]])\n\n### @bob\n\n- [x] @bob writes the agenda ([[meeting]])\n\n### Unassigned\n\n- [ ] book the room ([[meeting]])"
+    );
+}
@@ -0,0 +1,49 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+#[test]
+fn test_templates() {
+    let (_temp, workspace) = setup_temp_workspace();
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    let templates = workspace.join(".zet/templates");
+    fs::create_dir_all(templates.join("work")).unwrap();
+    fs::write(
+        templates.join("meeting.md"),
+        "# {{ title }}\n\n{% for person in attendees %}- {{ person.name }}\n{% endfor %}\
+         {% set when = date %}{% if project is defined %}{{ project | upper }}{% endif %}\n",
+    )
+    .unwrap();
+    fs::write(templates.join("work/review.md"), "{{ capture.cwd }}\n").unwrap();
+
+    assert_eq!(
+        query_document_ids(&workspace, &["templates", "list"]),
+        vec![
+            "meeting      attendees, date, project, title",
+            "work/review  capture",
+        ]
+    );
+
+    let show = query_document_ids(&workspace, &["templates", "show", "meeting"]);
+    assert!(show[0].ends_with("meeting.md"));
+    assert_eq!(show[1], "variables: attendees, date, project, title");
+    assert_eq!(show[2], "# {{ title }}");
+
+    let created = query_document_ids(&workspace, &["templates", "new", "daily"]);
+    assert!(created[0].ends_with("daily.md"));
+    let content = fs::read_to_string(templates.join("daily.md")).unwrap();
+    assert!(content.contains("title: {{ title }}"));
+
+    // existing templates are never overwritten
+    let assert = run_cli_cmd(&["templates", "new", "meeting"], &workspace)
+        .assert()
+        .failure();
+    let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+    assert!(stderr.contains("already exists"));
+
+    // the new template can be used right away
+    run_cli_cmd(&["create", "Today", "--template", "daily"], &workspace)
+        .assert()
+        .success();
+}
@@ -0,0 +1,75 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+fn setup_timeline_workspace() -> (assert_fs::TempDir, std::path::PathBuf) {
+    let (temp, workspace) = setup_temp_workspace();
+    fs::write(
+        workspace.join("kickoff.md"),
+        "---\ntitle: Kickoff\ndate: 2025-01-03\ntags: [project-x]\nevent:\n  - start: 2025-02-10\n    title: Launch\n---\n\n- [ ] ship the beta 2025-01-20\n- [ ] no date\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("retro.md"),
+        "---\ntitle: Retro\ndate: 2025-02-14\ntags: [project-x]\n---\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("other.md"),
+        "---\ntitle: Other\ndate: 2025-01-05\n---\n\n- [ ] elsewhere 2025-01-06\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    (temp, workspace)
+}
+
+#[test]
+fn test_timeline_tag() {
+    let (_temp, workspace) = setup_timeline_workspace();
+
+    let output = run_cli_cmd(&["timeline", "--tag", "#project-x"], &workspace)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "2025-01\n- 2025-01-03 Kickoff (kickoff)\n- 2025-01-20 task: ship the beta 2025-01-20 (kickoff)\n\n2025-02\n- 2025-02-10 event: Launch (kickoff)\n- 2025-02-14 Retro (retro)\n"
+    );
+}
+
+#[test]
+fn test_timeline_json() {
+    let (_temp, workspace) = setup_timeline_workspace();
+
+    let output = run_cli_cmd(
+        &[
+            "timeline",
+            "--by",
+            "weekly",
+            "--range",
+            "from 2025-01-01 to 2025-01-31",
+            "--output-format",
+            "json",
+        ],
+        &workspace,
+    )
+    .output()
+    .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let buckets: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let titles: Vec<&str> = buckets
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| b["title"].as_str().unwrap())
+        .collect();
+    assert_eq!(titles, vec!["2025-W01", "2025-W02", "2025-W04"]);
+    // weeks start on monday, 2025-01-05 is a sunday
+    assert_eq!(buckets[0]["entries"][1]["document_id"], "other");
+    assert_eq!(buckets[1]["entries"][0]["kind"], "task");
+    assert_eq!(buckets[1]["entries"][0]["date"], "2025-01-06");
+}
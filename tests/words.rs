@@ -0,0 +1,38 @@
+mod helpers;
+
+use helpers::{cli::*, *};
+use std::fs;
+
+#[test]
+fn test_words_transclude() {
+    let (_temp, workspace) = setup_temp_workspace();
+    fs::write(
+        workspace.join("book.md"),
+        "# Book\n\n![[chapter]] and ![[book]]\n",
+    )
+    .unwrap();
+    fs::write(
+        workspace.join("chapter.md"),
+        "---\ntitle: Chapter\n---\n\nThree more words %%not this%%\n",
+    )
+    .unwrap();
+
+    run_cli_cmd(&["init"], &workspace).assert().success();
+    run_cli_cmd(&["index"], &workspace).assert().success();
+
+    let words = |args: &[&str]| {
+        let output = run_cli_cmd(args, &workspace).output().unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+    assert_eq!(words(&["words", "book"]), "book\tBook\t2\n");
+    assert_eq!(words(&["words", "book", "--transclude"]), "book\tBook\t5\n");
+    assert_eq!(
+        words(&["words", "book", "chapter"]),
+        "book\tBook\t2\nchapter\tChapter\t3\ntotal\t\t5\n"
+    );
+
+    run_cli_cmd(&["words", "missing"], &workspace)
+        .assert()
+        .failure();
+}